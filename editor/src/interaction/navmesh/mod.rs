@@ -30,6 +30,7 @@ use crate::fyrox::{
         TypeUuidProvider,
     },
     engine::Engine,
+    gui::utils::make_simple_tooltip,
     gui::{
         button::{ButtonBuilder, ButtonMessage},
         grid::{Column, GridBuilder, Row},
@@ -41,6 +42,7 @@ use crate::fyrox::{
     },
     gui::{HorizontalAlignment, VerticalAlignment},
     scene::{camera::Camera, navmesh::NavigationalMesh},
+    utils::navmesh::{generation::NavmeshGeometry, Navmesh},
 };
 use crate::scene::SelectionContainer;
 use crate::{
@@ -58,7 +60,7 @@ use crate::{
         commands::{
             navmesh::{
                 AddNavmeshEdgeCommand, ConnectNavmeshEdgesCommand, DeleteNavmeshVertexCommand,
-                MoveNavmeshVertexCommand,
+                MoveNavmeshVertexCommand, SetNavmeshCommand,
             },
             ChangeSelectionCommand,
         },
@@ -76,6 +78,7 @@ pub mod selection;
 pub struct NavmeshPanel {
     pub window: Handle<UiNode>,
     connect_edges: Handle<UiNode>,
+    generate: Handle<UiNode>,
     sender: MessageSender,
     scene_frame: Handle<UiNode>,
 }
@@ -93,20 +96,39 @@ fn fetch_selection(editor_selection: &Selection) -> Option<NavmeshSelection> {
 impl NavmeshPanel {
     pub fn new(scene_frame: Handle<UiNode>, ctx: &mut BuildContext, sender: MessageSender) -> Self {
         let connect_edges;
+        let generate;
         let window = WindowBuilder::new(WidgetBuilder::new().with_name("NavmeshPanel"))
             .open(false)
             .with_title(WindowTitle::text("Navmesh"))
             .with_content(
                 GridBuilder::new(
                     WidgetBuilder::new().with_child(
-                        StackPanelBuilder::new(WidgetBuilder::new().with_child({
-                            connect_edges = ButtonBuilder::new(
-                                WidgetBuilder::new().with_margin(Thickness::uniform(1.0)),
-                            )
-                            .with_text("Connect Edges")
-                            .build(ctx);
-                            connect_edges
-                        }))
+                        StackPanelBuilder::new(
+                            WidgetBuilder::new()
+                                .with_child({
+                                    connect_edges = ButtonBuilder::new(
+                                        WidgetBuilder::new().with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .with_text("Connect Edges")
+                                    .build(ctx);
+                                    connect_edges
+                                })
+                                .with_child({
+                                    generate = ButtonBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_margin(Thickness::uniform(1.0))
+                                            .with_tooltip(make_simple_tooltip(
+                                                ctx,
+                                                "Generate the navmesh from static meshes and \
+                                                colliders of the scene using the generation \
+                                                settings of the navmesh node.",
+                                            )),
+                                    )
+                                    .with_text("Generate")
+                                    .build(ctx);
+                                    generate
+                                }),
+                        )
                         .with_orientation(Orientation::Horizontal)
                         .build(ctx),
                     ),
@@ -121,13 +143,43 @@ impl NavmeshPanel {
             window,
             sender,
             connect_edges,
+            generate,
             scene_frame,
         }
     }
 
-    pub fn handle_message(&mut self, message: &UiMessage, editor_selection: &Selection) {
+    pub fn handle_message(
+        &mut self,
+        message: &UiMessage,
+        editor_selection: &Selection,
+        game_scene: &GameScene,
+        engine: &Engine,
+    ) {
         if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
-            if message.destination() == self.connect_edges {
+            if message.destination() == self.generate {
+                if let Some(selection) = fetch_selection(editor_selection) {
+                    let graph = &engine.scenes[game_scene.scene].graph;
+                    if let Some(navmesh_node) =
+                        graph.try_get_of_type::<NavigationalMesh>(selection.navmesh_node())
+                    {
+                        let geometry = NavmeshGeometry::from_graph(graph);
+                        let navmesh =
+                            Navmesh::from_geometry(&geometry, navmesh_node.generation_settings());
+                        let mut commands = Vec::new();
+                        // Old selection of vertices and edges is no longer valid.
+                        if editor_selection.as_navmesh().is_some() {
+                            commands.push(Command::new(ChangeSelectionCommand::new(
+                                Selection::new(NavmeshSelection::empty(selection.navmesh_node())),
+                            )));
+                        }
+                        commands.push(Command::new(SetNavmeshCommand::new(
+                            selection.navmesh_node(),
+                            navmesh,
+                        )));
+                        self.sender.do_command(CommandGroup::from(commands));
+                    }
+                }
+            } else if message.destination() == self.connect_edges {
                 if let Some(selection) = fetch_selection(editor_selection) {
                     let vertices = selection
                         .entities()
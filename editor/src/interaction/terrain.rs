@@ -18,7 +18,7 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use fyrox::scene::terrain::brushstroke::{BrushSender, BrushThreadMessage, UndoData};
+use fyrox::scene::terrain::brushstroke::{BrushSender, BrushThreadMessage, ChunkData, UndoData};
 
 use crate::fyrox::core::uuid::{uuid, Uuid};
 use crate::fyrox::core::TypeUuidProvider;
@@ -32,20 +32,26 @@ use crate::fyrox::{
         log::{Log, MessageKind},
         math::vector_to_quat,
         pool::Handle,
+        reflect::prelude::*,
     },
     engine::Engine,
     gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
         inspector::{
             editors::{
-                enumeration::EnumPropertyEditorDefinition, PropertyEditorDefinitionContainer,
+                enumeration::EnumPropertyEditorDefinition,
+                inspectable::InspectablePropertyEditorDefinition,
+                PropertyEditorDefinitionContainer,
             },
             Inspector, InspectorBuilder, InspectorContext, InspectorMessage, PropertyAction,
         },
         key::HotKey,
         message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
         widget::{WidgetBuilder, WidgetMessage},
         window::{WindowBuilder, WindowMessage, WindowTitle},
-        BuildContext, UiNode, UserInterface,
+        BuildContext, Orientation, UiNode, UserInterface,
     },
     scene::{
         base::BaseBuilder,
@@ -57,7 +63,10 @@ use crate::fyrox::{
         },
         node::Node,
         terrain::brushstroke::{Brush, BrushMode, BrushShape, BrushStroke, BrushTarget},
-        terrain::{Terrain, TerrainRayCastResult},
+        terrain::{
+            HeightMapError, HydraulicErosionOptions, NoiseOptions, TerraceOptions, Terrain,
+            TerrainOperation, TerrainRayCastResult, ThermalErosionOptions,
+        },
     },
 };
 use crate::interaction::make_interaction_mode_button;
@@ -77,6 +86,7 @@ use crate::{
     MSG_SYNC_FLAG,
 };
 use fyrox::asset::untyped::ResourceKind;
+use std::path::PathBuf;
 use std::sync::mpsc::channel;
 use std::sync::Arc;
 
@@ -112,6 +122,8 @@ pub struct TerrainInteractionMode {
     brush_value: f32,
     brush: Brush,
     brush_panel: BrushPanel,
+    tools: TerrainTools,
+    tools_panel: TerrainToolsPanel,
     scene_viewer_frame: Handle<UiNode>,
 }
 
@@ -134,10 +146,16 @@ impl TerrainInteractionMode {
         let brush_panel =
             BrushPanel::new(&mut engine.user_interfaces.first_mut().build_ctx(), &brush);
 
+        let tools = TerrainTools::default();
+        let tools_panel =
+            TerrainToolsPanel::new(&mut engine.user_interfaces.first_mut().build_ctx(), &tools);
+
         Self {
             message_sender,
             brush_sender: None,
             brush_panel,
+            tools,
+            tools_panel,
             brush_gizmo: BrushGizmo::new(game_scene, engine),
             interacting: false,
             brush,
@@ -434,6 +452,20 @@ impl InteractionMode for TerrainInteractionMode {
                 false,
                 false,
             ));
+
+        engine
+            .user_interfaces
+            .first_mut()
+            .send_message(WindowMessage::open_and_align(
+                self.tools_panel.window,
+                MessageDirection::ToWidget,
+                self.scene_viewer_frame,
+                HorizontalAlignment::Right,
+                VerticalAlignment::Bottom,
+                Thickness::bottom_right(5.0),
+                false,
+                false,
+            ));
     }
 
     fn deactivate(&mut self, controller: &dyn SceneController, engine: &mut Engine) {
@@ -453,18 +485,41 @@ impl InteractionMode for TerrainInteractionMode {
                 self.brush_panel.window,
                 MessageDirection::ToWidget,
             ));
+
+        engine
+            .user_interfaces
+            .first_mut()
+            .send_message(WindowMessage::close(
+                self.tools_panel.window,
+                MessageDirection::ToWidget,
+            ));
     }
 
     fn handle_ui_message(
         &mut self,
         message: &UiMessage,
         editor_selection: &Selection,
-        _controller: &mut dyn SceneController,
-        _engine: &mut Engine,
+        controller: &mut dyn SceneController,
+        engine: &mut Engine,
     ) {
         if let Some(selection) = editor_selection.as_graph() {
             if selection.is_single_selection() {
                 self.brush_panel.handle_ui_message(message, &mut self.brush);
+
+                let Some(game_scene) = controller.downcast_mut::<GameScene>() else {
+                    return;
+                };
+                let handle = selection.nodes()[0];
+                let graph = &mut engine.scenes[game_scene.scene].graph;
+                if let Some(terrain) = graph[handle].cast_mut::<Terrain>() {
+                    self.tools_panel.handle_ui_message(
+                        message,
+                        &mut self.tools,
+                        terrain,
+                        handle,
+                        &self.message_sender,
+                    );
+                }
             }
         }
     }
@@ -477,6 +532,13 @@ impl InteractionMode for TerrainInteractionMode {
                 self.brush_panel.window,
                 MessageDirection::ToWidget,
             ));
+        engine
+            .user_interfaces
+            .first_mut()
+            .send_message(WidgetMessage::remove(
+                self.tools_panel.window,
+                MessageDirection::ToWidget,
+            ));
     }
 
     fn on_hot_key_pressed(
@@ -703,3 +765,196 @@ impl BrushPanel {
         Some(())
     }
 }
+
+/// Options of the one-shot terrain tools, that modify the whole height map at once.
+#[derive(Clone, Reflect, Debug)]
+struct TerrainTools {
+    #[reflect(description = "Procedural operation that will be applied to the whole height map.")]
+    operation: TerrainOperation,
+    #[reflect(description = "Path to a 16-bit PNG or RAW height map file to import or export.")]
+    height_map_path: PathBuf,
+    #[reflect(description = "Height that corresponds to black pixels of an imported height map.")]
+    min_height: f32,
+    #[reflect(description = "Height that corresponds to white pixels of an imported height map.")]
+    max_height: f32,
+}
+
+impl Default for TerrainTools {
+    fn default() -> Self {
+        Self {
+            operation: Default::default(),
+            height_map_path: PathBuf::from("heightmap.png"),
+            min_height: 0.0,
+            max_height: 100.0,
+        }
+    }
+}
+
+struct TerrainToolsPanel {
+    window: Handle<UiNode>,
+    inspector: Handle<UiNode>,
+    apply: Handle<UiNode>,
+    import: Handle<UiNode>,
+    export: Handle<UiNode>,
+}
+
+impl TerrainToolsPanel {
+    fn new(ctx: &mut BuildContext, tools: &TerrainTools) -> Self {
+        let property_editors = PropertyEditorDefinitionContainer::with_default_editors();
+        property_editors.insert(EnumPropertyEditorDefinition::<TerrainOperation>::new());
+        property_editors.insert(InspectablePropertyEditorDefinition::<NoiseOptions>::new());
+        property_editors
+            .insert(InspectablePropertyEditorDefinition::<ThermalErosionOptions>::new());
+        property_editors
+            .insert(InspectablePropertyEditorDefinition::<HydraulicErosionOptions>::new());
+        property_editors.insert(InspectablePropertyEditorDefinition::<TerraceOptions>::new());
+
+        let context = InspectorContext::from_object(
+            tools,
+            ctx,
+            Arc::new(property_editors),
+            None,
+            MSG_SYNC_FLAG,
+            0,
+            true,
+            Default::default(),
+            150.0,
+        );
+
+        let inspector;
+        let apply;
+        let import;
+        let export;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(350.0))
+            .can_minimize(false)
+            .can_maximize(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            inspector = InspectorBuilder::new(WidgetBuilder::new())
+                                .with_context(context)
+                                .build(ctx);
+                            inspector
+                        })
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_child({
+                                        apply = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Apply")
+                                        .build(ctx);
+                                        apply
+                                    })
+                                    .with_child({
+                                        import = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Import")
+                                        .build(ctx);
+                                        import
+                                    })
+                                    .with_child({
+                                        export = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Export")
+                                        .build(ctx);
+                                        export
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_column(Column::stretch())
+                .add_row(Row::stretch())
+                .add_row(Row::strict(24.0))
+                .build(ctx),
+            )
+            .open(false)
+            .with_title(WindowTitle::text("Terrain Tools"))
+            .build(ctx);
+
+        Self {
+            window,
+            inspector,
+            apply,
+            import,
+            export,
+        }
+    }
+
+    fn handle_ui_message(
+        &self,
+        message: &UiMessage,
+        tools: &mut TerrainTools,
+        terrain: &mut Terrain,
+        terrain_handle: Handle<Node>,
+        sender: &MessageSender,
+    ) {
+        if message.destination() == self.inspector
+            && message.direction() == MessageDirection::FromWidget
+        {
+            if let Some(InspectorMessage::PropertyChanged(msg)) = message.data::<InspectorMessage>()
+            {
+                PropertyAction::from_field_kind(&msg.value).apply(
+                    &msg.path(),
+                    tools,
+                    &mut |result| {
+                        Log::verify(result);
+                    },
+                );
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.apply {
+                modify_height_field(terrain, terrain_handle, sender, |terrain| {
+                    terrain.apply_operation(&tools.operation)
+                });
+            } else if message.destination() == self.import {
+                modify_height_field(terrain, terrain_handle, sender, |terrain| {
+                    terrain.import_height_map(
+                        &tools.height_map_path,
+                        tools.min_height,
+                        tools.max_height,
+                    )
+                });
+            } else if message.destination() == self.export {
+                match terrain.export_height_map(&tools.height_map_path) {
+                    Ok((min, max)) => Log::info(format!(
+                        "Height map was exported to {}. Min height: {min}, max height: {max}.",
+                        tools.height_map_path.display()
+                    )),
+                    Err(e) => Log::err(format!("Failed to export height map. Reason: {e}")),
+                }
+            }
+        }
+    }
+}
+
+/// Modifies the height map of the terrain using the given function and registers an undoable
+/// command with the previous content of the height map.
+fn modify_height_field<F>(
+    terrain: &mut Terrain,
+    terrain_handle: Handle<Node>,
+    sender: &MessageSender,
+    func: F,
+) where
+    F: FnOnce(&mut Terrain) -> Result<(), HeightMapError>,
+{
+    let chunks = terrain
+        .chunks_ref()
+        .iter()
+        .map(|c| ChunkData::from_texture(c.grid_position(), c.heightmap()))
+        .collect::<Vec<_>>();
+    match func(terrain) {
+        Ok(()) => sender.do_command(ModifyTerrainHeightCommand::new(terrain_handle, chunks)),
+        Err(e) => Log::err(format!("Failed to modify terrain height map. Reason: {e}")),
+    }
+}
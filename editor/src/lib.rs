@@ -47,6 +47,7 @@ pub mod particle;
 pub mod plugin;
 pub mod plugins;
 pub mod preview;
+pub mod profiler;
 pub mod scene;
 pub mod scene_viewer;
 pub mod settings;
@@ -119,7 +120,10 @@ use crate::{
             TextureResourceExtension,
         },
         scene::{graph::GraphUpdateSwitches, mesh::Mesh, Scene, SceneLoader},
-        utils::{translate_cursor_icon, translate_event},
+        utils::{
+            behavior::debug::{BehaviorDebugServer, DEBUGGER_ADDRESS_ENV_VAR},
+            translate_cursor_icon, translate_event,
+        },
         window::WindowAttributes,
     },
     highlight::HighlightRenderPass,
@@ -140,10 +144,13 @@ use crate::{
     plugin::{EditorPlugin, EditorPluginsContainer},
     plugins::{
         absm::AbsmEditor, absm::AbsmEditorPlugin, animation::AnimationEditorPlugin,
-        collider::ColliderPlugin, curve_editor::CurveEditorPlugin, material::MaterialPlugin,
-        path_fixer::PathFixerPlugin, ragdoll::RagdollPlugin, settings::SettingsPlugin,
+        behavior_debugger::BehaviorDebuggerPlugin, collider::ColliderPlugin,
+        curve_editor::CurveEditorPlugin, humanoid_mapping::HumanoidMappingPlugin,
+        material::MaterialPlugin, path_fixer::PathFixerPlugin,
+        physics_debugger::PhysicsDebuggerPlugin, ragdoll::RagdollPlugin, settings::SettingsPlugin,
         stats::UiStatisticsPlugin, tilemap::TileMapEditorPlugin,
     },
+    profiler::{ProfilerWindow, ProfilerWindowAction},
     scene::{
         commands::{
             make_delete_selection_command, ChangeSelectionCommand, GameSceneContext, PasteCommand,
@@ -320,6 +327,9 @@ pub enum Mode {
     Play {
         process: std::process::Child,
         active: Arc<AtomicBool>,
+        /// Receives behavior tree snapshots from the running game, `None` if the debugger could not
+        /// be started.
+        behavior_debugger: Option<BehaviorDebugServer>,
     },
 }
 
@@ -570,6 +580,7 @@ pub struct Editor {
     pub highlighter: Option<Rc<RefCell<HighlightRenderPass>>>,
     pub export_window: Option<ExportWindow>,
     pub statistics_window: Option<StatisticsWindow>,
+    pub profiler_window: Option<ProfilerWindow>,
     pub surface_data_viewer: Option<SurfaceDataViewer>,
     pub processed_ui_messages: usize,
     pub styles: FxHashMap<EditorStyle, StyleResource>,
@@ -961,6 +972,9 @@ impl Editor {
                 .with(TileMapEditorPlugin::default())
                 .with(MaterialPlugin::default())
                 .with(RagdollPlugin::default())
+                .with(PhysicsDebuggerPlugin::default())
+                .with(HumanoidMappingPlugin::default())
+                .with(BehaviorDebuggerPlugin::default())
                 .with(SettingsPlugin::default())
                 .with(AnimationEditorPlugin::default())
                 .with(AbsmEditorPlugin::default())
@@ -980,6 +994,7 @@ impl Editor {
             highlighter: None,
             export_window: None,
             statistics_window: None,
+            profiler_window: None,
             surface_data_viewer: None,
             processed_ui_messages: 0,
             styles,
@@ -1301,6 +1316,7 @@ impl Editor {
                     scene_settings: &self.scene_settings,
                     export_window: &mut self.export_window,
                     statistics_window: &mut self.statistics_window,
+                    profiler_window: &mut self.profiler_window,
                 },
                 settings: &mut self.settings,
             },
@@ -1324,6 +1340,11 @@ impl Editor {
                 self.statistics_window.take();
             }
         }
+        if let Some(profiler) = self.profiler_window.as_mut() {
+            if let ProfilerWindowAction::Remove = profiler.handle_ui_message(message, ui) {
+                self.profiler_window.take();
+            }
+        }
         self.log.handle_ui_message(message, ui);
         self.asset_browser
             .handle_ui_message(message, engine, self.message_sender.clone());
@@ -1382,8 +1403,12 @@ impl Editor {
                 self.scene_settings
                     .handle_ui_message(message, &self.message_sender);
 
-                self.navmesh_panel
-                    .handle_message(message, &current_scene_entry.selection);
+                self.navmesh_panel.handle_message(
+                    message,
+                    &current_scene_entry.selection,
+                    game_scene,
+                    engine,
+                );
 
                 if let Some(interaction_mode) = current_scene_entry
                     .current_interaction_mode
@@ -1526,6 +1551,21 @@ impl Editor {
             .arg("--override-scene")
             .arg(path);
 
+        let behavior_debugger = match BehaviorDebugServer::bind("127.0.0.1:0")
+            .and_then(|server| server.local_address().map(|address| (server, address)))
+        {
+            Ok((server, address)) => {
+                command.env(DEBUGGER_ADDRESS_ENV_VAR, address.to_string());
+                Some(server)
+            }
+            Err(err) => {
+                Log::err(format!(
+                    "Unable to start behavior tree debugger. Reason: {err}"
+                ));
+                None
+            }
+        };
+
         match command.spawn() {
             Ok(mut process) => {
                 let active = Arc::new(AtomicBool::new(true));
@@ -1541,7 +1581,11 @@ impl Editor {
                     }
                 });
 
-                self.mode = Mode::Play { active, process };
+                self.mode = Mode::Play {
+                    active,
+                    process,
+                    behavior_debugger,
+                };
 
                 self.on_mode_changed();
             }
@@ -2137,6 +2181,7 @@ impl Editor {
             Mode::Play {
                 ref mut process,
                 ref active,
+                ..
             } => {
                 match process.try_wait() {
                     Ok(status) => {
@@ -2223,6 +2268,10 @@ impl Editor {
 
         let ui = self.engine.user_interfaces.first_mut();
 
+        if let Some(profiler) = self.profiler_window.as_mut() {
+            profiler.update(dt, ui);
+        }
+
         if let Some(active_tooltip) = ui.active_tooltip() {
             if !active_tooltip.shown {
                 // Keep the editor running until the current tooltip is not shown.
@@ -2281,6 +2330,14 @@ impl Editor {
 
                 if let Some(entry) = self.scenes.current_scene_entry_mut() {
                     if let Some(game_scene) = entry.controller.downcast_mut::<GameScene>() {
+                        if let Message::SaveSelectionAsPrefab(path) = &message {
+                            game_scene.try_save_selection_as_prefab(
+                                path,
+                                &entry.selection,
+                                &self.engine,
+                                self.settings.general.scene_save_format,
+                            );
+                        }
                         self.particle_system_control_panel.handle_message(
                             &message,
                             &entry.selection,
@@ -29,10 +29,13 @@ use crate::fyrox::{
 };
 use crate::scene::controller::SceneController;
 use crate::{
-    menu::{create_menu_item_shortcut, create_root_menu_item},
+    menu::{create_menu_item, create_menu_item_shortcut, create_root_menu_item},
     message::MessageSender,
-    scene::{commands::PasteCommand, GameScene, Selection},
-    Engine, Message, Mode,
+    scene::{
+        commands::{graph::SetLayerInteractionsCommand, PasteCommand},
+        GameScene, Selection,
+    },
+    Engine, Message, Mode, Settings,
 };
 
 pub struct EditMenu {
@@ -41,6 +44,7 @@ pub struct EditMenu {
     pub redo: Handle<UiNode>,
     pub copy: Handle<UiNode>,
     pub paste: Handle<UiNode>,
+    pub layer_interactions: Handle<UiNode>,
 }
 
 impl EditMenu {
@@ -49,6 +53,7 @@ impl EditMenu {
         let undo;
         let copy;
         let paste;
+        let layer_interactions;
         let menu = create_root_menu_item(
             "Edit",
             vec![
@@ -68,6 +73,10 @@ impl EditMenu {
                     paste = create_menu_item_shortcut("Paste", "Ctrl+V", vec![], ctx);
                     paste
                 },
+                {
+                    layer_interactions = create_menu_item("Toggle Layer Interactions", vec![], ctx);
+                    layer_interactions
+                },
             ],
             ctx,
         );
@@ -78,6 +87,7 @@ impl EditMenu {
             redo,
             copy,
             paste,
+            layer_interactions,
         }
     }
 
@@ -88,6 +98,7 @@ impl EditMenu {
         editor_selection: &Selection,
         controller: &mut dyn SceneController,
         engine: &mut Engine,
+        settings: &Settings,
     ) {
         if let Some(MenuItemMessage::Click) = message.data::<MenuItemMessage>() {
             if message.destination() == self.copy {
@@ -106,6 +117,18 @@ impl EditMenu {
                         sender.do_command(PasteCommand::new(game_scene.scene_content_root));
                     }
                 }
+            } else if message.destination() == self.layer_interactions {
+                if let Some(game_scene) = controller.downcast_mut::<GameScene>() {
+                    // Layer interactions are opt-in per scene, because they overwrite collision
+                    // groups of the colliders.
+                    let graph = &engine.scenes[game_scene.scene].graph;
+                    let interactions = if graph.layer_interactions().is_some() {
+                        None
+                    } else {
+                        Some(settings.tags_and_layers.interactions())
+                    };
+                    sender.do_command(SetLayerInteractionsCommand::new(interactions));
+                }
             } else if message.destination() == self.undo {
                 sender.send(Message::UndoCurrentSceneCommand);
             } else if message.destination() == self.redo {
@@ -34,6 +34,7 @@ use crate::{
         utils::UtilsMenu, view::ViewMenu,
     },
     message::MessageSender,
+    profiler::ProfilerWindow,
     scene::{container::EditorSceneEntry, controller::SceneController},
     send_sync_message,
     settings::Settings,
@@ -75,6 +76,7 @@ pub struct Panels<'b> {
     pub scene_settings: &'b SceneSettingsWindow,
     pub export_window: &'b mut Option<ExportWindow>,
     pub statistics_window: &'b mut Option<StatisticsWindow>,
+    pub profiler_window: &'b mut Option<ProfilerWindow>,
 }
 
 pub struct MenuContext<'a, 'b> {
@@ -190,6 +192,7 @@ impl Menu {
                 &entry.selection,
                 &mut *entry.controller,
                 ctx.engine,
+                ctx.settings,
             );
 
             self.create_entity_menu.handle_ui_message(
@@ -205,6 +208,7 @@ impl Menu {
             message,
             &mut ctx.panels,
             ctx.engine.user_interfaces.first_mut(),
+            &ctx.engine.resource_manager,
         );
         self.file_menu.handle_ui_message(
             message,
@@ -20,33 +20,53 @@
 
 use crate::{
     fyrox::{
-        asset::core::pool::Handle,
+        asset::{
+            core::{log::Log, pool::Handle},
+            manager::ResourceManager,
+        },
         gui::{menu::MenuItemMessage, message::UiMessage, BuildContext, UiNode, UserInterface},
     },
     menu::{create_menu_item, create_root_menu_item, Panels},
+    profiler::ProfilerWindow,
     stats::StatisticsWindow,
 };
 
 pub struct UtilsMenu {
     pub menu: Handle<UiNode>,
     pub rendering_statistics: Handle<UiNode>,
+    pub cpu_profiler: Handle<UiNode>,
+    pub upgrade_assets: Handle<UiNode>,
 }
 
 impl UtilsMenu {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let rendering_statistics;
+        let cpu_profiler;
+        let upgrade_assets;
         let menu = create_root_menu_item(
             "Utils",
-            vec![{
-                rendering_statistics = create_menu_item("Rendering Statistics", vec![], ctx);
-                rendering_statistics
-            }],
+            vec![
+                {
+                    rendering_statistics = create_menu_item("Rendering Statistics", vec![], ctx);
+                    rendering_statistics
+                },
+                {
+                    cpu_profiler = create_menu_item("CPU Profiler", vec![], ctx);
+                    cpu_profiler
+                },
+                {
+                    upgrade_assets = create_menu_item("Upgrade Assets", vec![], ctx);
+                    upgrade_assets
+                },
+            ],
             ctx,
         );
 
         Self {
             menu,
             rendering_statistics,
+            cpu_profiler,
+            upgrade_assets,
         }
     }
 
@@ -55,6 +75,7 @@ impl UtilsMenu {
         message: &UiMessage,
         panels: &mut Panels,
         ui: &mut UserInterface,
+        resource_manager: &ResourceManager,
     ) {
         if let Some(MenuItemMessage::Click) = message.data::<MenuItemMessage>() {
             if message.destination() == self.rendering_statistics {
@@ -62,7 +83,48 @@ impl UtilsMenu {
                     &mut ui.build_ctx(),
                     panels.scene_frame,
                 ))
+            } else if message.destination() == self.cpu_profiler {
+                if panels.profiler_window.is_none() {
+                    *panels.profiler_window =
+                        Some(ProfilerWindow::new(&mut ui.build_ctx(), panels.scene_frame));
+                }
+            } else if message.destination() == self.upgrade_assets {
+                upgrade_assets(resource_manager);
             }
         }
     }
 }
+
+/// Loads and saves back all the assets of the project, so all registered data migrations are
+/// applied to them. The assets are upgraded in the background, the progress is written to the log.
+fn upgrade_assets(resource_manager: &ResourceManager) {
+    let rm = resource_manager.clone();
+    resource_manager.task_pool().spawn_task(async move {
+        let result = rm
+            .upgrade_resources_with_progress("./", |progress| {
+                Log::info(format!(
+                    "Upgrading assets ({}/{}): {}",
+                    progress.index + 1,
+                    progress.total,
+                    progress.path.display()
+                ))
+            })
+            .await;
+        match result {
+            Ok(report) => {
+                for (path, reason) in report.failed.iter() {
+                    Log::err(format!(
+                        "Unable to upgrade {} asset. Reason: {reason}",
+                        path.display()
+                    ));
+                }
+                Log::info(format!(
+                    "{} assets were upgraded, {} failed.",
+                    report.upgraded.len(),
+                    report.failed.len()
+                ));
+            }
+            Err(err) => Log::err(format!("Unable to upgrade assets. Reason: {err:?}")),
+        }
+    });
+}
@@ -83,6 +83,20 @@ define_set_collection_element_command!(
     }
 );
 
+define_set_collection_element_command!(
+    SetPoseDifferenceSourceCommand<Handle<PoseNode<Handle<N>>>, Handle<PoseNode<Handle<N>>>>(self, context) {
+        let machine = fetch_machine(context, self.node_handle);
+        if let PoseNode::PoseDifference(ref mut definition) = machine.layers_mut()[self.layer_index].nodes_mut()[self.handle] {
+            let source = if self.index == 0 {
+                &mut definition.pose_source
+            } else {
+                &mut definition.reference_source
+            };
+            std::mem::swap(source, &mut self.value);
+        }
+    }
+);
+
 define_set_collection_element_command!(
     SetBlendSpacePointPositionCommand<Handle<PoseNode<Handle<N>>>, Vector2<f32>>(self, context) {
         let machine = fetch_machine(context, self.node_handle);
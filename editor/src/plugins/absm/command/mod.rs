@@ -27,7 +27,9 @@ use crate::fyrox::{
         pool::{Handle, Ticket},
     },
     generic_animation::machine::{
-        layer::MachineLayer, mask::LayerMask, Machine, PoseNode, State, Transition,
+        layer::{LayerBlendMode, MachineLayer},
+        mask::LayerMask,
+        Machine, PoseNode, State, Transition,
     },
 };
 use crate::{
@@ -845,6 +847,35 @@ impl<N: Debug + 'static> CommandTrait for SetLayerNameCommand<N> {
     }
 }
 
+#[derive(Debug)]
+pub struct SetLayerBlendModeCommand<N: Debug + 'static> {
+    pub absm_node_handle: Handle<N>,
+    pub layer_index: usize,
+    pub blend_mode: LayerBlendMode,
+}
+
+impl<N: Debug + 'static> SetLayerBlendModeCommand<N> {
+    fn swap(&mut self, context: &mut dyn CommandContext) {
+        let layer =
+            &mut fetch_machine(context, self.absm_node_handle).layers_mut()[self.layer_index];
+        self.blend_mode = layer.set_blend_mode(self.blend_mode);
+    }
+}
+
+impl<N: Debug + 'static> CommandTrait for SetLayerBlendModeCommand<N> {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Set Layer Blend Mode".to_string()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context)
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context)
+    }
+}
+
 #[derive(Debug)]
 pub struct AddLayerCommand<N: Debug + 'static> {
     pub absm_node_handle: Handle<N>,
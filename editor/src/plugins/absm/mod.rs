@@ -695,6 +695,9 @@ impl AbsmEditor {
                                             BlendSpacePoint::default(),
                                         ));
                                     }
+                                    PoseNode::PoseDifference(_) => {
+                                        // Fixed set of input sockets
+                                    }
                                 }
                             }
                         }
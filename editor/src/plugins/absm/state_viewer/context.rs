@@ -22,7 +22,10 @@ use crate::fyrox::graph::SceneGraphNode;
 use crate::fyrox::{
     core::{algebra::Vector2, pool::Handle},
     generic_animation::machine::{
-        node::{blendspace::BlendSpace, blendspace::BlendSpacePoint, BasePoseNode},
+        node::{
+            blendspace::BlendSpace, blendspace::BlendSpacePoint, difference::PoseDifference,
+            BasePoseNode,
+        },
         BlendAnimations, BlendAnimationsByIndex, MachineLayer, PlayAnimation, PoseNode, State,
     },
     graph::BaseSceneGraph,
@@ -39,7 +42,7 @@ use crate::plugins::absm::{
     command::{
         blend::{
             SetBlendAnimationByIndexInputPoseSourceCommand, SetBlendAnimationsPoseSourceCommand,
-            SetBlendSpacePoseSourceCommand,
+            SetBlendSpacePoseSourceCommand, SetPoseDifferenceSourceCommand,
         },
         AddPoseNodeCommand, DeletePoseNodeCommand, SetStateRootPoseCommand,
     },
@@ -60,6 +63,7 @@ pub struct CanvasContextMenu {
     create_blend_animations: Handle<UiNode>,
     create_blend_by_index: Handle<UiNode>,
     create_blend_space: Handle<UiNode>,
+    create_pose_difference: Handle<UiNode>,
     pub menu: RcUiNodeHandle,
     pub canvas: Handle<UiNode>,
     pub node_context_menu: Option<RcUiNodeHandle>,
@@ -71,6 +75,7 @@ impl CanvasContextMenu {
         let create_blend_animations;
         let create_blend_by_index;
         let create_blend_space;
+        let create_pose_difference;
         let menu = ContextMenuBuilder::new(
             PopupBuilder::new(
                 WidgetBuilder::new()
@@ -96,6 +101,11 @@ impl CanvasContextMenu {
                         .with_child({
                             create_blend_space = create_menu_item("Blend Space", vec![], ctx);
                             create_blend_space
+                        })
+                        .with_child({
+                            create_pose_difference =
+                                create_menu_item("Pose Difference", vec![], ctx);
+                            create_pose_difference
                         }),
                 )
                 .build(ctx),
@@ -109,6 +119,7 @@ impl CanvasContextMenu {
             create_blend_animations,
             create_blend_by_index,
             create_blend_space,
+            create_pose_difference,
             menu,
             canvas: Default::default(),
             node_context_menu: Default::default(),
@@ -145,6 +156,7 @@ impl CanvasContextMenu {
                         parent_state: current_state,
                     },
                     pose_sources: Default::default(),
+                    sync_mode: Default::default(),
                     output_pose: Default::default(),
                 }))
             } else if message.destination() == self.create_blend_by_index {
@@ -184,6 +196,14 @@ impl CanvasContextMenu {
                 ]);
 
                 Some(PoseNode::BlendSpace(blend_space))
+            } else if message.destination() == self.create_pose_difference {
+                let mut pose_difference =
+                    PoseDifference::new(Default::default(), Default::default());
+
+                pose_difference.position = position;
+                pose_difference.parent_state = current_state;
+
+                Some(PoseNode::PoseDifference(pose_difference))
             } else {
                 None
             };
@@ -381,6 +401,15 @@ impl ConnectionContextMenu {
                         index,
                         value: Default::default(),
                     }),
+                    PoseNode::PoseDifference(_) => {
+                        sender.do_command(SetPoseDifferenceSourceCommand {
+                            node_handle: absm_node_handle,
+                            layer_index,
+                            handle: model_handle,
+                            index,
+                            value: Default::default(),
+                        })
+                    }
                 }
             }
         } else if let Some(PopupMessage::Placement(Placement::Cursor(target))) = message.data() {
@@ -43,7 +43,7 @@ use crate::plugins::absm::{
     command::{
         blend::{
             SetBlendAnimationByIndexInputPoseSourceCommand, SetBlendAnimationsPoseSourceCommand,
-            SetBlendSpacePoseSourceCommand,
+            SetBlendSpacePoseSourceCommand, SetPoseDifferenceSourceCommand,
         },
         MovePoseNodeCommand,
     },
@@ -163,6 +163,7 @@ where
         PoseNode::BlendSpace(blend_space) => {
             format!("Blend Space: {:?} animations", blend_space.points().len())
         }
+        PoseNode::PoseDifference(_) => "Pose Difference".to_owned(),
     }
 }
 
@@ -376,6 +377,15 @@ impl StateViewer {
                                         value: source_node,
                                     });
                                 }
+                                PoseNode::PoseDifference(_) => {
+                                    sender.do_command(SetPoseDifferenceSourceCommand {
+                                        node_handle: absm_node_handle,
+                                        layer_index,
+                                        handle: dest_node,
+                                        index: dest_socket_ref.index,
+                                        value: source_node,
+                                    });
+                                }
                             }
                         }
                         _ => (),
@@ -512,6 +522,10 @@ impl StateViewer {
                                     PoseNode::BlendSpace(blend_space) => {
                                         (blend_space.points().len(), "Blend Space", true, true)
                                     }
+                                    PoseNode::PoseDifference(_) => {
+                                        // Pose and reference inputs.
+                                        (2, "Pose Difference", false, false)
+                                    }
                                 };
 
                             let node_view = AbsmNodeBuilder::new(
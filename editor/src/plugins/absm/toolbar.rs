@@ -21,7 +21,7 @@
 use crate::fyrox::{
     core::{pool::ErasedHandle, pool::Handle},
     fxhash::FxHashSet,
-    generic_animation::machine::{mask::LayerMask, Machine, MachineLayer},
+    generic_animation::machine::{mask::LayerMask, LayerBlendMode, Machine, MachineLayer},
     graph::{BaseSceneGraph, PrefabData, SceneGraph, SceneGraphNode},
     gui::{
         button::{ButtonBuilder, ButtonMessage},
@@ -40,7 +40,10 @@ use crate::fyrox::{
 };
 use crate::plugins::absm::{
     animation_container_ref,
-    command::{AddLayerCommand, RemoveLayerCommand, SetLayerMaskCommand, SetLayerNameCommand},
+    command::{
+        AddLayerCommand, RemoveLayerCommand, SetLayerBlendModeCommand, SetLayerMaskCommand,
+        SetLayerNameCommand,
+    },
     fetch_selection, machine_container_ref,
     selection::AbsmSelection,
 };
@@ -58,6 +61,8 @@ use crate::{
 use fyrox::gui::style::resource::StyleResourceExt;
 use fyrox::gui::style::Style;
 use fyrox::gui::utils::make_dropdown_list_option;
+use std::str::FromStr;
+use strum::VariantNames;
 
 pub struct Toolbar {
     pub panel: Handle<UiNode>,
@@ -67,6 +72,7 @@ pub struct Toolbar {
     pub add_layer: Handle<UiNode>,
     pub remove_layer: Handle<UiNode>,
     pub edit_mask: Handle<UiNode>,
+    pub blend_mode: Handle<UiNode>,
     pub node_selector: Handle<UiNode>,
 }
 
@@ -84,6 +90,7 @@ impl Toolbar {
         let add_layer;
         let remove_layer;
         let edit_mask;
+        let blend_mode;
         let panel = StackPanelBuilder::new(
             WidgetBuilder::new()
                 .with_child({
@@ -164,6 +171,25 @@ impl Toolbar {
                     )
                     .build(ctx);
                     edit_mask
+                })
+                .with_child({
+                    blend_mode = DropdownListBuilder::new(
+                        WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(1.0))
+                            .with_width(80.0)
+                            .with_tooltip(make_simple_tooltip(
+                                ctx,
+                                "Defines how the layer is combined with the layers below it.",
+                            )),
+                    )
+                    .with_items(
+                        LayerBlendMode::VARIANTS
+                            .iter()
+                            .map(|name| make_dropdown_list_option(ctx, name))
+                            .collect(),
+                    )
+                    .build(ctx);
+                    blend_mode
                 }),
         )
         .with_orientation(Orientation::Horizontal)
@@ -177,6 +203,7 @@ impl Toolbar {
             add_layer,
             remove_layer,
             edit_mask,
+            blend_mode,
             node_selector: Handle::NONE,
         }
     }
@@ -214,6 +241,21 @@ impl Toolbar {
                 new_selection.layer = Some(*index);
                 new_selection.entities.clear();
                 sender.do_command(ChangeSelectionCommand::new(Selection::new(new_selection)));
+            } else if message.destination() == self.blend_mode
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some(layer_index) = selection.layer {
+                    if let Some(blend_mode) = LayerBlendMode::VARIANTS
+                        .get(*index)
+                        .and_then(|name| LayerBlendMode::from_str(name).ok())
+                    {
+                        sender.do_command(SetLayerBlendModeCommand {
+                            absm_node_handle: selection.absm_node_handle,
+                            layer_index,
+                            blend_mode,
+                        });
+                    }
+                }
             }
         } else if let Some(TextMessage::Text(text)) = message.data() {
             if message.destination() == self.layer_name
@@ -411,6 +453,17 @@ impl Toolbar {
                         layer.name().to_string(),
                     ),
                 );
+
+                send_sync_message(
+                    ui,
+                    DropdownListMessage::selection(
+                        self.blend_mode,
+                        MessageDirection::ToWidget,
+                        LayerBlendMode::VARIANTS
+                            .iter()
+                            .position(|name| *name == layer.blend_mode().as_ref()),
+                    ),
+                );
             }
         }
     }
@@ -401,12 +401,11 @@ impl AnimationEditor {
                             sender.do_command(AddAnimationSignal {
                                 animation_player_handle: selection.animation_player,
                                 animation_handle: selection.animation,
-                                signal: Some(AnimationSignal {
-                                    id: Uuid::new_v4(),
-                                    name: "Unnamed".to_string(),
-                                    time: *time,
-                                    enabled: true,
-                                }),
+                                signal: Some(AnimationSignal::new(
+                                    Uuid::new_v4(),
+                                    "Unnamed",
+                                    *time,
+                                )),
                             });
                         }
                         RulerMessage::RemoveSignal(id) => {
@@ -747,6 +746,7 @@ impl AnimationEditor {
                                 id: s.id,
                                 time: s.time,
                                 selected: false,
+                                sync_marker: s.sync_marker,
                             })
                             .collect(),
                     ),
@@ -121,6 +121,7 @@ pub struct SignalView {
     pub id: Uuid,
     pub time: f32,
     pub selected: bool,
+    pub sync_marker: bool,
 }
 
 impl SignalView {
@@ -263,6 +264,14 @@ impl Control for Ruler {
                 Vector2::new(x + size * 0.5, local_bounds.h() - size),
                 Vector2::new(x, local_bounds.h()),
             ]);
+            if signal.sync_marker {
+                // Sync markers are depicted as diamonds.
+                ctx.push_triangle_filled([
+                    Vector2::new(x - size * 0.5, local_bounds.h() - size),
+                    Vector2::new(x + size * 0.5, local_bounds.h() - size),
+                    Vector2::new(x, local_bounds.h() - size * 2.0),
+                ]);
+            }
             let brush = if signal.selected {
                 ctx.style.get_or_default(Style::BRUSH_BRIGHT)
             } else {
@@ -0,0 +1,246 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Behavior tree debugger shows the state of a behavior tree of a selected scene node at its last tick,
+//! the active path of the tree is highlighted. Trees must have a debug owner to be shown, see
+//! [`crate::fyrox::utils::behavior::debug`] module docs for more info.
+
+use crate::{
+    fyrox::{
+        core::{color::Color, pool::ErasedHandle, pool::Handle, some_or_return},
+        graph::BaseSceneGraph,
+        gui::{
+            brush::Brush,
+            grid::{Column, GridBuilder, Row},
+            menu::MenuItemMessage,
+            message::{MessageDirection, UiMessage},
+            scroll_viewer::ScrollViewerBuilder,
+            stack_panel::StackPanelBuilder,
+            text::{TextBuilder, TextMessage},
+            widget::{WidgetBuilder, WidgetMessage},
+            window::{WindowBuilder, WindowMessage, WindowTitle},
+            BuildContext, Thickness, UiNode, UserInterface,
+        },
+        utils::behavior::{debug::BehaviorTreeSnapshot, Status},
+    },
+    menu::create_menu_item,
+    plugin::EditorPlugin,
+    scene::GameScene,
+    send_sync_message, Editor, Mode,
+};
+
+struct BehaviorDebuggerWindow {
+    window: Handle<UiNode>,
+    owner: Handle<UiNode>,
+    nodes: Handle<UiNode>,
+    rows: Vec<Handle<UiNode>>,
+}
+
+fn status_color(status: Option<Status>) -> Color {
+    match status {
+        None => Color::opaque(120, 120, 120),
+        Some(Status::Success) => Color::opaque(80, 200, 80),
+        Some(Status::Failure) => Color::opaque(220, 70, 70),
+        Some(Status::Running) => Color::opaque(240, 200, 40),
+    }
+}
+
+impl BehaviorDebuggerWindow {
+    fn new(ctx: &mut BuildContext) -> Self {
+        let owner = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_text("Select a scene node with a behavior tree.")
+        .build(ctx);
+
+        let nodes = StackPanelBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(350.0)
+                .with_height(400.0)
+                .with_name("BehaviorTreeDebugger"),
+        )
+        .open(false)
+        .with_title(WindowTitle::text("Behavior Tree Debugger"))
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new().with_child(owner).with_child(
+                    ScrollViewerBuilder::new(
+                        WidgetBuilder::new()
+                            .on_row(1)
+                            .with_margin(Thickness::uniform(1.0)),
+                    )
+                    .with_content(nodes)
+                    .build(ctx),
+                ),
+            )
+            .add_row(Row::auto())
+            .add_row(Row::stretch())
+            .add_column(Column::stretch())
+            .build(ctx),
+        )
+        .build(ctx);
+
+        Self {
+            window,
+            owner,
+            nodes,
+            rows: Default::default(),
+        }
+    }
+
+    fn sync_to_model(
+        &mut self,
+        owner_name: &str,
+        snapshot: Option<&BehaviorTreeSnapshot>,
+        ui: &mut UserInterface,
+    ) {
+        let text = match snapshot {
+            Some(_) => format!("Behavior tree of {owner_name}"),
+            None => format!("{owner_name} does not have a behavior tree with a debug owner."),
+        };
+        send_sync_message(
+            ui,
+            TextMessage::text(self.owner, MessageDirection::ToWidget, text),
+        );
+
+        for row in self.rows.drain(..) {
+            ui.send_message(WidgetMessage::remove(row, MessageDirection::ToWidget));
+        }
+
+        for node in snapshot.iter().flat_map(|snapshot| snapshot.nodes.iter()) {
+            let status = match node.status {
+                None => "-",
+                Some(Status::Success) => "Success",
+                Some(Status::Failure) => "Failure",
+                Some(Status::Running) => "Running",
+            };
+            let row = TextBuilder::new(
+                WidgetBuilder::new()
+                    .with_margin(Thickness {
+                        left: 2.0 + 14.0 * node.depth as f32,
+                        top: 1.0,
+                        right: 2.0,
+                        bottom: 1.0,
+                    })
+                    .with_foreground(Brush::Solid(status_color(node.status)).into()),
+            )
+            .with_text(format!("[{status}] {}", node.label))
+            .build(&mut ui.build_ctx());
+            ui.send_message(WidgetMessage::link(
+                row,
+                MessageDirection::ToWidget,
+                self.nodes,
+            ));
+            self.rows.push(row);
+        }
+    }
+}
+
+/// Editor plugin, that shows the state of behavior trees (`Utils -> Behavior Tree Debugger`).
+#[derive(Default)]
+pub struct BehaviorDebuggerPlugin {
+    open_tool: Handle<UiNode>,
+    window: Option<BehaviorDebuggerWindow>,
+    owner: ErasedHandle,
+    snapshot: Option<BehaviorTreeSnapshot>,
+}
+
+impl EditorPlugin for BehaviorDebuggerPlugin {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ui = editor.engine.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+        self.open_tool = create_menu_item("Behavior Tree Debugger", vec![], ctx);
+        ui.send_message(MenuItemMessage::add_item(
+            editor.menu.utils_menu.menu,
+            MessageDirection::ToWidget,
+            self.open_tool,
+        ));
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if message.destination() == self.open_tool && self.window.is_none() {
+                let ui = editor.engine.user_interfaces.first_mut();
+                let window = BehaviorDebuggerWindow::new(&mut ui.build_ctx());
+                ui.send_message(WindowMessage::open(
+                    window.window,
+                    MessageDirection::ToWidget,
+                    true,
+                    true,
+                ));
+                self.window = Some(window);
+                // Force synchronization on the next update.
+                self.owner = ErasedHandle::none();
+                self.snapshot = None;
+            }
+        } else if let Some(WindowMessage::Close) = message.data() {
+            let window = some_or_return!(self.window.as_ref());
+            if message.destination() == window.window
+                && message.direction() == MessageDirection::FromWidget
+            {
+                editor
+                    .engine
+                    .user_interfaces
+                    .first_mut()
+                    .send_message(WidgetMessage::remove(
+                        window.window,
+                        MessageDirection::ToWidget,
+                    ));
+                self.window = None;
+            }
+        }
+    }
+
+    fn on_update(&mut self, editor: &mut Editor) {
+        let window = some_or_return!(self.window.as_mut());
+        let entry = some_or_return!(editor.scenes.current_scene_entry_ref());
+        let game_scene = some_or_return!(entry.controller.downcast_ref::<GameScene>());
+        let selected = some_or_return!(entry
+            .selection
+            .as_graph()
+            .and_then(|selection| selection.nodes().first().cloned()));
+        let graph = &editor.engine.scenes[game_scene.scene].graph;
+        let owner_name = some_or_return!(graph.try_get(selected)).name().to_string();
+
+        let owner = ErasedHandle::from(selected);
+        let snapshot = match editor.mode {
+            Mode::Play {
+                behavior_debugger: Some(ref mut debugger),
+                ..
+            } => {
+                debugger.poll();
+                debugger.snapshot(owner).cloned()
+            }
+            _ => None,
+        };
+        if owner != self.owner || snapshot != self.snapshot {
+            window.sync_to_model(
+                &owner_name,
+                snapshot.as_ref(),
+                editor.engine.user_interfaces.first_mut(),
+            );
+            self.owner = owner;
+            self.snapshot = snapshot;
+        }
+    }
+}
@@ -0,0 +1,359 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Humanoid mapping tool allows to create mappings of humanoid bones to nodes of a character rig, which are
+//! then used for animation retargeting between skeletons with different proportions.
+
+use crate::{
+    fyrox::{
+        asset::ResourceData,
+        core::{log::Log, pool::Handle, some_or_return},
+        gui::{
+            button::{ButtonBuilder, ButtonMessage},
+            grid::{Column, GridBuilder, Row},
+            inspector::{InspectorBuilder, InspectorContext, InspectorMessage, PropertyAction},
+            menu::MenuItemMessage,
+            message::{MessageDirection, UiMessage},
+            scroll_viewer::ScrollViewerBuilder,
+            text::{TextBuilder, TextMessage},
+            text_box::TextBoxBuilder,
+            utils::make_simple_tooltip,
+            widget::{WidgetBuilder, WidgetMessage},
+            window::{WindowBuilder, WindowMessage, WindowTitle},
+            BuildContext, Thickness, UiNode, UserInterface, VerticalAlignment,
+        },
+        resource::humanoid::HumanoidMapping,
+    },
+    menu::create_menu_item,
+    message::MessageSender,
+    plugin::EditorPlugin,
+    plugins::inspector::editors::make_property_editors_container,
+    scene::GameScene,
+    send_sync_message, Editor, MSG_SYNC_FLAG,
+};
+use std::{path::PathBuf, sync::Arc};
+
+struct HumanoidMappingWindow {
+    window: Handle<UiNode>,
+    inspector: Handle<UiNode>,
+    auto_detect: Handle<UiNode>,
+    path: Handle<UiNode>,
+    save: Handle<UiNode>,
+    status: Handle<UiNode>,
+}
+
+fn make_button(text: &str, tooltip: &str, column: usize, ctx: &mut BuildContext) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .on_column(column)
+            .with_margin(Thickness::uniform(1.0))
+            .with_tooltip(make_simple_tooltip(ctx, tooltip)),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_inspector_context(
+    mapping: &HumanoidMapping,
+    sender: MessageSender,
+    ctx: &mut BuildContext,
+) -> InspectorContext {
+    InspectorContext::from_object(
+        mapping,
+        ctx,
+        Arc::new(make_property_editors_container(sender)),
+        None,
+        MSG_SYNC_FLAG,
+        0,
+        true,
+        Default::default(),
+        150.0,
+    )
+}
+
+impl HumanoidMappingWindow {
+    fn new(
+        mapping: &HumanoidMapping,
+        path: &str,
+        sender: MessageSender,
+        ctx: &mut BuildContext,
+    ) -> Self {
+        let auto_detect = make_button(
+            "Auto Detect",
+            "Creates a new mapping using names of the selected node and its descendants.",
+            0,
+            ctx,
+        );
+        let save = make_button("Save", "Saves the mapping to the specified file.", 2, ctx);
+
+        let path = TextBoxBuilder::new(
+            WidgetBuilder::new()
+                .on_column(1)
+                .with_margin(Thickness::uniform(1.0))
+                .with_tooltip(make_simple_tooltip(
+                    ctx,
+                    "A path to a file with .humanoid extension.",
+                )),
+        )
+        .with_vertical_text_alignment(VerticalAlignment::Center)
+        .with_text(path)
+        .build(ctx);
+
+        let status = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(2)
+                .with_margin(Thickness::uniform(1.0)),
+        )
+        .build(ctx);
+
+        let inspector = InspectorBuilder::new(WidgetBuilder::new())
+            .with_context(make_inspector_context(mapping, sender, ctx))
+            .build(ctx);
+
+        let window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(400.0)
+                .with_height(500.0)
+                .with_name("HumanoidMapping"),
+        )
+        .open(false)
+        .with_title(WindowTitle::text("Humanoid Mapping"))
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(
+                        GridBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(0)
+                                .with_child(auto_detect)
+                                .with_child(path)
+                                .with_child(save),
+                        )
+                        .add_row(Row::stretch())
+                        .add_column(Column::strict(90.0))
+                        .add_column(Column::stretch())
+                        .add_column(Column::strict(60.0))
+                        .build(ctx),
+                    )
+                    .with_child(
+                        ScrollViewerBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(1)
+                                .with_margin(Thickness::uniform(1.0)),
+                        )
+                        .with_content(inspector)
+                        .build(ctx),
+                    )
+                    .with_child(status),
+            )
+            .add_row(Row::strict(24.0))
+            .add_row(Row::stretch())
+            .add_row(Row::auto())
+            .add_column(Column::stretch())
+            .build(ctx),
+        )
+        .build(ctx);
+
+        Self {
+            window,
+            inspector,
+            auto_detect,
+            path,
+            save,
+            status,
+        }
+    }
+
+    fn sync_to_model(
+        &self,
+        mapping: &HumanoidMapping,
+        sender: MessageSender,
+        ui: &mut UserInterface,
+    ) {
+        let context = make_inspector_context(mapping, sender, &mut ui.build_ctx());
+        ui.send_message(InspectorMessage::context(
+            self.inspector,
+            MessageDirection::ToWidget,
+            context,
+        ));
+
+        let unbound = mapping.unbound_bones();
+        let status = if unbound.is_empty() {
+            "All bones are bound.".to_string()
+        } else {
+            format!(
+                "Unbound bones: {}",
+                unbound
+                    .iter()
+                    .map(|bone| bone.as_ref())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+        send_sync_message(
+            ui,
+            TextMessage::text(self.status, MessageDirection::ToWidget, status),
+        );
+    }
+}
+
+/// Editor plugin, that allows to create humanoid mappings (`Utils -> Humanoid Mapping`) for animation retargeting.
+#[derive(Default)]
+pub struct HumanoidMappingPlugin {
+    open_tool: Handle<UiNode>,
+    window: Option<HumanoidMappingWindow>,
+    mapping: HumanoidMapping,
+    path: PathBuf,
+}
+
+impl HumanoidMappingPlugin {
+    fn auto_detect(&mut self, editor: &mut Editor) {
+        let entry = some_or_return!(editor.scenes.current_scene_entry_ref());
+        let game_scene = some_or_return!(entry.controller.downcast_ref::<GameScene>());
+        let Some(root) = entry
+            .selection
+            .as_graph()
+            .and_then(|selection| selection.nodes().first().cloned())
+        else {
+            Log::warn("Select a root node of a character to auto-detect its humanoid mapping.");
+            return;
+        };
+
+        let graph = &editor.engine.scenes[game_scene.scene].graph;
+        self.mapping = HumanoidMapping::auto_detect(graph, root);
+        self.path
+            .set_file_name(format!("{}.humanoid", graph[root].name()));
+
+        if let Some(window) = self.window.as_ref() {
+            let ui = editor.engine.user_interfaces.first_mut();
+            window.sync_to_model(&self.mapping, editor.message_sender.clone(), ui);
+            send_sync_message(
+                ui,
+                TextMessage::text(
+                    window.path,
+                    MessageDirection::ToWidget,
+                    self.path.to_string_lossy().to_string(),
+                ),
+            );
+        }
+    }
+
+    fn save(&mut self) {
+        if self.path.as_os_str().is_empty() {
+            Log::warn("Specify a path to save the humanoid mapping to.");
+            return;
+        }
+        if self.path.extension().is_none() {
+            self.path.set_extension("humanoid");
+        }
+        match self.mapping.save(&self.path) {
+            Ok(()) => Log::info(format!(
+                "Humanoid mapping was saved to {}",
+                self.path.display()
+            )),
+            Err(err) => Log::err(format!(
+                "Unable to save humanoid mapping to {}. Reason: {err}",
+                self.path.display()
+            )),
+        }
+    }
+}
+
+impl EditorPlugin for HumanoidMappingPlugin {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ui = editor.engine.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+        self.open_tool = create_menu_item("Humanoid Mapping", vec![], ctx);
+        ui.send_message(MenuItemMessage::add_item(
+            editor.menu.utils_menu.menu,
+            MessageDirection::ToWidget,
+            self.open_tool,
+        ));
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if message.destination() == self.open_tool {
+                if self.window.is_none() {
+                    let ui = editor.engine.user_interfaces.first_mut();
+                    let window = HumanoidMappingWindow::new(
+                        &self.mapping,
+                        &self.path.to_string_lossy(),
+                        editor.message_sender.clone(),
+                        &mut ui.build_ctx(),
+                    );
+                    ui.send_message(WindowMessage::open(
+                        window.window,
+                        MessageDirection::ToWidget,
+                        true,
+                        true,
+                    ));
+                    window.sync_to_model(&self.mapping, editor.message_sender.clone(), ui);
+                    self.window = Some(window);
+                }
+                return;
+            }
+        }
+
+        let window = some_or_return!(self.window.as_ref());
+
+        if let Some(WindowMessage::Close) = message.data() {
+            if message.destination() == window.window
+                && message.direction() == MessageDirection::FromWidget
+            {
+                editor
+                    .engine
+                    .user_interfaces
+                    .first_mut()
+                    .send_message(WidgetMessage::remove(
+                        window.window,
+                        MessageDirection::ToWidget,
+                    ));
+                self.window = None;
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == window.auto_detect {
+                self.auto_detect(editor);
+            } else if message.destination() == window.save {
+                self.save();
+            }
+        } else if let Some(TextMessage::Text(text)) = message.data() {
+            if message.destination() == window.path
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.path = PathBuf::from(text);
+            }
+        } else if let Some(InspectorMessage::PropertyChanged(args)) = message.data() {
+            if message.destination() == window.inspector
+                && message.direction() == MessageDirection::FromWidget
+            {
+                PropertyAction::from_field_kind(&args.value).apply(
+                    &args.path(),
+                    &mut self.mapping,
+                    &mut |result| {
+                        Log::verify(result);
+                    },
+                );
+                let ui = editor.engine.user_interfaces.first_mut();
+                window.sync_to_model(&self.mapping, editor.message_sender.clone(), ui);
+            }
+        }
+    }
+}
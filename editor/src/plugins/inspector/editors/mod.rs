@@ -42,7 +42,10 @@ use crate::plugins::{
 use crate::{
     fyrox::{
         asset::{untyped::UntypedResource, Resource},
-        core::pool::{ErasedHandle, Handle},
+        core::{
+            pool::{ErasedHandle, Handle},
+            visitor::VisitorFormat,
+        },
         gui::{
             self,
             font::FontResource,
@@ -59,8 +62,10 @@ use crate::{
         material::shader::{Shader, ShaderResource},
         renderer::framework::PolygonFillMode,
         resource::{
+            behavior::{BehaviorNodeDefinition, BehaviorNodeKind, BehaviorTreeDefinition},
             curve::{CurveResource, CurveResourceState},
-            model::{MaterialSearchOptions, Model, ModelResource},
+            humanoid::{HumanoidBone, HumanoidBoneBinding},
+            model::{AnimationCompressionOptions, MaterialSearchOptions, Model, ModelResource},
             texture::{
                 CompressionOptions, MipFilter, TextureMagnificationFilter,
                 TextureMinificationFilter, TextureResource, TextureWrapMode,
@@ -68,6 +73,7 @@ use crate::{
         },
         scene::{
             self,
+            animation::ik::{ChainIk, ChainIkMethod, IkSolver, IkTarget, LookAtIk, TwoBoneIk},
             base::{
                 Base, LevelOfDetail, LodGroup, Mobility, Property, PropertyValue, ScriptRecord,
             },
@@ -75,14 +81,19 @@ use crate::{
                 ColorGradingLut, Exposure, OrthographicProjection, PerspectiveProjection,
                 Projection, SkyBox,
             },
+            cloth::{ClothPinAttribute, ClothPinChannel},
             collider::{
                 BallShape, BitMask, CapsuleShape, ColliderShape, ConeShape, ConvexPolyhedronShape,
                 CuboidShape, CylinderShape, GeometrySource, HeightfieldShape, InteractionGroups,
                 SegmentShape, TriangleShape, TrimeshShape,
             },
+            decal::DecalChannelBlending,
             dim2,
-            graph::physics::CoefficientCombineRule,
+            graph::physics::{
+                CoefficientCombineRule, PhysicsDebugColoring, PhysicsDebugDrawOptions,
+            },
             joint::*,
+            layer::LayerMask,
             light::{
                 directional::{CsmOptions, FrustumSplitOptions},
                 BaseLight,
@@ -118,6 +129,15 @@ use crate::{
             tilemap::{tileset::TileSet, Tile},
             transform::Transform,
         },
+        utils::{
+            behavior::{
+                blackboard::{Blackboard, BlackboardValue},
+                composite::CompositeNodeKind,
+                decorator::DecoratorKind,
+                parallel::ParallelPolicy,
+            },
+            navmesh::generation::NavmeshGenerationSettings,
+        },
     },
     message::MessageSender,
 };
@@ -150,6 +170,18 @@ pub fn make_status_enum_editor_definition() -> EnumPropertyEditorDefinition<Stat
     }
 }
 
+pub fn make_visitor_format_enum_editor_definition() -> EnumPropertyEditorDefinition<VisitorFormat> {
+    EnumPropertyEditorDefinition {
+        variant_generator: |i| match i {
+            0 => VisitorFormat::Binary,
+            1 => VisitorFormat::Text,
+            _ => unreachable!(),
+        },
+        index_generator: |v| *v as usize,
+        names_generator: || vec!["Binary".to_string(), "Text".to_string()],
+    }
+}
+
 fn register_absm_property_editors<T>(container: &PropertyEditorDefinitionContainer)
 where
     T: 'static,
@@ -157,12 +189,13 @@ where
     use crate::fyrox::generic_animation::machine::{
         node::{
             blendspace::{BlendSpace, BlendSpacePoint},
+            difference::PoseDifference,
             BasePoseNode,
         },
         state::{StateAction, StateActionWrapper},
         transition::{AndNode, LogicNode, NotNode, OrNode, XorNode},
-        BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput, Machine,
-        PlayAnimation, PoseNode, PoseWeight, State,
+        BlendAnimations, BlendAnimationsByIndex, BlendPose, BlendSyncMode, IndexedBlendInput,
+        LayerBlendMode, Machine, PlayAnimation, PoseNode, PoseWeight, State,
     };
 
     container.insert(InspectablePropertyEditorDefinition::<BasePoseNode<Handle<T>>>::new());
@@ -198,6 +231,11 @@ where
     container.insert(InspectablePropertyEditorDefinition::<
         PlayAnimation<Handle<T>>,
     >::new());
+    container.insert(InspectablePropertyEditorDefinition::<
+        PoseDifference<Handle<T>>,
+    >::new());
+    container.insert(EnumPropertyEditorDefinition::<LayerBlendMode>::new());
+    container.insert(EnumPropertyEditorDefinition::<BlendSyncMode>::new());
 
     container.insert(InspectablePropertyEditorDefinition::<
         Handle<PoseNode<Handle<T>>>,
@@ -337,6 +375,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
 
     container.register_inheritable_inspectable::<ColorGradingLut>();
     container.register_inheritable_inspectable::<InteractionGroups>();
+    container.register_inheritable_inspectable::<DecalChannelBlending>();
 
     container.register_inheritable_enum::<JointParams, _>();
     container.register_inheritable_enum::<dim2::joint::JointParams, _>();
@@ -403,9 +442,33 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_enum::<Exposure, _>();
     container.register_inheritable_enum::<FrustumSplitOptions, _>();
     container.register_inheritable_enum::<MaterialSearchOptions, _>();
+    container.register_inheritable_inspectable::<AnimationCompressionOptions>();
     container.register_inheritable_enum::<DistanceModel, _>();
     container.register_inheritable_enum::<sound::Renderer, _>();
     container.register_inheritable_enum::<RenderPath, _>();
+    container.register_inheritable_enum::<ClothPinAttribute, _>();
+    container.register_inheritable_enum::<ClothPinChannel, _>();
+    container.register_inheritable_enum::<PhysicsDebugColoring, _>();
+    container.register_inheritable_enum::<IkTarget, _>();
+    container.register_inheritable_enum::<ChainIkMethod, _>();
+    container.register_inheritable_enum::<IkSolver, _>();
+    container.register_inheritable_inspectable::<TwoBoneIk>();
+    container.register_inheritable_inspectable::<ChainIk>();
+    container.register_inheritable_inspectable::<LookAtIk>();
+    container.register_inheritable_vec_collection::<IkSolver>();
+    container.register_inheritable_enum::<HumanoidBone, _>();
+    container.register_inheritable_inspectable::<HumanoidBoneBinding>();
+    container.register_inheritable_vec_collection::<HumanoidBoneBinding>();
+    container.register_inheritable_enum::<CompositeNodeKind, _>();
+    container.register_inheritable_enum::<ParallelPolicy, _>();
+    container.register_inheritable_enum::<DecoratorKind, _>();
+    container.register_inheritable_enum::<BlackboardValue, _>();
+    container.register_inheritable_enum::<BehaviorNodeKind, _>();
+    container.register_inheritable_inspectable::<BehaviorNodeDefinition>();
+    container.register_inheritable_vec_collection::<BehaviorNodeDefinition>();
+    container.register_inheritable_inspectable::<Blackboard>();
+    container.register_inheritable_inspectable::<BehaviorTreeDefinition>();
+    container.register_inheritable_inspectable::<PhysicsDebugDrawOptions>();
     container.register_inheritable_enum::<CoordinateSystem, _>();
 
     container.insert(EnumPropertyEditorDefinition::<Vec<ScriptRecord>>::new_optional());
@@ -414,6 +477,9 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.insert(ScriptPropertyEditorDefinition {});
 
     container.insert(BitFieldPropertyEditorDefinition::<BitMask>::new());
+    container.insert(BitFieldPropertyEditorDefinition::<LayerMask>::new());
+    container.insert(InheritablePropertyEditorDefinition::<LayerMask>::new());
+    container.insert(InheritablePropertyEditorDefinition::<Vec<String>>::new());
 
     container.register_inheritable_inspectable::<BallShape>();
     container.register_inheritable_inspectable::<dim2::collider::BallShape>();
@@ -458,6 +524,10 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     >::new());
     container.insert(AnimationPropertyEditorDefinition::<gui::animation::Animation>::default());
 
+    container.insert(EnumPropertyEditorDefinition::<
+        crate::fyrox::generic_animation::SignalPayload,
+    >::new());
+
     container.insert(AnimationContainerPropertyEditorDefinition::<
         scene::animation::AnimationContainer,
     >::default());
@@ -486,5 +556,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
 
     container.register_inheritable_enum::<TileCollider, _>();
 
+    container.register_inheritable_inspectable::<NavmeshGenerationSettings>();
+
     container
 }
@@ -20,11 +20,14 @@
 
 pub mod absm;
 pub mod animation;
+pub mod behavior_debugger;
 pub mod collider;
 pub mod curve_editor;
+pub mod humanoid_mapping;
 pub mod inspector;
 pub mod material;
 pub mod path_fixer;
+pub mod physics_debugger;
 pub mod ragdoll;
 pub mod settings;
 pub mod stats;
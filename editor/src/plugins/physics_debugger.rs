@@ -0,0 +1,707 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Physics debugger allows to run physics simulation of a scene right in the editor, pause it, step
+//! it frame-by-frame and scrub back through recorded frames.
+
+use crate::{
+    fyrox::{
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            log::Log,
+            pool::Handle,
+            reflect::prelude::*,
+            some_or_return,
+        },
+        graph::{SceneGraph, SceneGraphNode},
+        gui::{
+            button::{ButtonBuilder, ButtonMessage},
+            grid::{Column, GridBuilder, Row},
+            inspector::{InspectorBuilder, InspectorContext, InspectorMessage, PropertyAction},
+            menu::MenuItemMessage,
+            message::{MessageDirection, UiMessage},
+            scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+            scroll_viewer::ScrollViewerBuilder,
+            text::{TextBuilder, TextMessage},
+            utils::make_simple_tooltip,
+            widget::{WidgetBuilder, WidgetMessage},
+            window::{WindowBuilder, WindowMessage, WindowTitle},
+            BuildContext, Thickness, UiNode, UserInterface, VerticalAlignment,
+        },
+        scene::{
+            dim2,
+            graph::{
+                physics::{ContactPoint, PhysicsDebugDrawOptions},
+                Graph,
+            },
+            node::Node,
+            rigidbody::RigidBody,
+            Scene,
+        },
+    },
+    menu::create_menu_item,
+    message::MessageSender,
+    plugin::EditorPlugin,
+    plugins::inspector::editors::make_property_editors_container,
+    scene::GameScene,
+    send_sync_message, Editor, FIXED_TIMESTEP, MSG_SYNC_FLAG,
+};
+use std::{collections::VecDeque, sync::Arc};
+
+#[derive(Reflect, Debug, Clone)]
+struct PhysicsDebuggerSettings {
+    #[reflect(description = "Defines what parts of the physics world will be drawn.")]
+    draw_options: PhysicsDebugDrawOptions,
+    #[reflect(
+        description = "Maximum amount of recorded physics frames. Older frames will be discarded.",
+        min_value = 1.0,
+        step = 1.0
+    )]
+    max_recorded_frames: usize,
+}
+
+impl Default for PhysicsDebuggerSettings {
+    fn default() -> Self {
+        Self {
+            draw_options: Default::default(),
+            max_recorded_frames: 600,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RecordedBody {
+    handle: Handle<Node>,
+    position: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    lin_vel: Vector3<f32>,
+    ang_vel: Vector3<f32>,
+}
+
+/// State of every rigid body of a scene at some physics frame.
+#[derive(Clone, Debug, Default)]
+struct RecordedFrame {
+    bodies: Vec<RecordedBody>,
+    contacts: Vec<ContactPoint>,
+}
+
+impl RecordedFrame {
+    fn capture(graph: &Graph) -> Self {
+        let bodies = graph
+            .pair_iter()
+            .filter_map(|(handle, node)| {
+                let (lin_vel, ang_vel) = if let Some(body) = node.component_ref::<RigidBody>() {
+                    (body.lin_vel(), body.ang_vel())
+                } else if let Some(body) = node.component_ref::<dim2::rigidbody::RigidBody>() {
+                    let lin_vel = body.lin_vel();
+                    (
+                        Vector3::new(lin_vel.x, lin_vel.y, 0.0),
+                        Vector3::new(0.0, 0.0, body.ang_vel()),
+                    )
+                } else {
+                    return None;
+                };
+
+                let transform = node.local_transform();
+                Some(RecordedBody {
+                    handle,
+                    position: **transform.position(),
+                    rotation: **transform.rotation(),
+                    lin_vel,
+                    ang_vel,
+                })
+            })
+            .collect();
+
+        let mut contacts = graph.physics.contact_points();
+        contacts.extend(graph.physics2d.contact_points());
+
+        Self { bodies, contacts }
+    }
+
+    fn apply(&self, graph: &mut Graph) {
+        for recorded in self.bodies.iter() {
+            let Some(node) = graph.try_get_mut(recorded.handle) else {
+                continue;
+            };
+            node.local_transform_mut()
+                .set_position(recorded.position)
+                .set_rotation(recorded.rotation);
+            if let Some(body) = node.component_mut::<RigidBody>() {
+                body.set_lin_vel(recorded.lin_vel);
+                body.set_ang_vel(recorded.ang_vel);
+                body.wake_up();
+            } else if let Some(body) = node.component_mut::<dim2::rigidbody::RigidBody>() {
+                body.set_lin_vel(recorded.lin_vel.xy());
+                body.set_ang_vel(recorded.ang_vel.z);
+                body.wake_up();
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum SimulationState {
+    Stopped,
+    Running,
+    Paused,
+}
+
+struct PhysicsDebuggerWindow {
+    window: Handle<UiNode>,
+    inspector: Handle<UiNode>,
+    simulate: Handle<UiNode>,
+    pause: Handle<UiNode>,
+    step: Handle<UiNode>,
+    stop: Handle<UiNode>,
+    timeline: Handle<UiNode>,
+    frame: Handle<UiNode>,
+}
+
+fn make_button(text: &str, tooltip: &str, column: usize, ctx: &mut BuildContext) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .on_column(column)
+            .with_margin(Thickness::uniform(1.0))
+            .with_tooltip(make_simple_tooltip(ctx, tooltip)),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+impl PhysicsDebuggerWindow {
+    fn new(
+        settings: &PhysicsDebuggerSettings,
+        sender: MessageSender,
+        ctx: &mut BuildContext,
+    ) -> Self {
+        let container = Arc::new(make_property_editors_container(sender));
+
+        let simulate = make_button(
+            "Simulate",
+            "Runs physics simulation of the current scene. All changes will be discarded when \
+            the simulation is stopped.",
+            0,
+            ctx,
+        );
+        let pause = make_button("Pause", "Pauses the simulation.", 1, ctx);
+        let step = make_button(
+            "Step",
+            "Pauses the simulation and performs exactly one physics step.",
+            2,
+            ctx,
+        );
+        let stop = make_button(
+            "Stop",
+            "Stops the simulation and restores the scene to its original state.",
+            3,
+            ctx,
+        );
+
+        let timeline = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_column(1)
+                .with_margin(Thickness::uniform(1.0)),
+        )
+        .with_min(0.0)
+        .with_max(0.0)
+        .with_step(1.0)
+        .build(ctx);
+
+        let frame = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_column(2)
+                .with_width(80.0)
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .with_margin(Thickness::uniform(1.0)),
+        )
+        .with_text("0 / 0")
+        .build(ctx);
+
+        let inspector = InspectorBuilder::new(WidgetBuilder::new())
+            .with_context(InspectorContext::from_object(
+                settings,
+                ctx,
+                container,
+                None,
+                MSG_SYNC_FLAG,
+                0,
+                true,
+                Default::default(),
+                150.0,
+            ))
+            .build(ctx);
+
+        let window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(350.0)
+                .with_height(450.0)
+                .with_name("PhysicsDebugger"),
+        )
+        .open(false)
+        .with_title(WindowTitle::text("Physics Debugger"))
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(
+                        GridBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(0)
+                                .with_child(simulate)
+                                .with_child(pause)
+                                .with_child(step)
+                                .with_child(stop),
+                        )
+                        .add_row(Row::stretch())
+                        .add_column(Column::stretch())
+                        .add_column(Column::stretch())
+                        .add_column(Column::stretch())
+                        .add_column(Column::stretch())
+                        .build(ctx),
+                    )
+                    .with_child(
+                        GridBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(1)
+                                .with_child(
+                                    TextBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_vertical_alignment(VerticalAlignment::Center)
+                                            .with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .with_text("Frame")
+                                    .build(ctx),
+                                )
+                                .with_child(timeline)
+                                .with_child(frame),
+                        )
+                        .add_row(Row::stretch())
+                        .add_column(Column::auto())
+                        .add_column(Column::stretch())
+                        .add_column(Column::auto())
+                        .build(ctx),
+                    )
+                    .with_child(
+                        ScrollViewerBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(2)
+                                .with_margin(Thickness::uniform(1.0)),
+                        )
+                        .with_content(inspector)
+                        .build(ctx),
+                    ),
+            )
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(22.0))
+            .add_row(Row::stretch())
+            .add_column(Column::stretch())
+            .build(ctx),
+        )
+        .build(ctx);
+
+        Self {
+            window,
+            inspector,
+            simulate,
+            pause,
+            step,
+            stop,
+            timeline,
+            frame,
+        }
+    }
+
+    fn sync_timeline(&self, frame_count: usize, current_frame: usize, ui: &UserInterface) {
+        let max = frame_count.saturating_sub(1) as f32;
+        send_sync_message(
+            ui,
+            ScrollBarMessage::max_value(self.timeline, MessageDirection::ToWidget, max),
+        );
+        send_sync_message(
+            ui,
+            ScrollBarMessage::value(
+                self.timeline,
+                MessageDirection::ToWidget,
+                current_frame as f32,
+            ),
+        );
+        send_sync_message(
+            ui,
+            TextMessage::text(
+                self.frame,
+                MessageDirection::ToWidget,
+                format!(
+                    "{} / {}",
+                    if frame_count == 0 {
+                        0
+                    } else {
+                        current_frame + 1
+                    },
+                    frame_count
+                ),
+            ),
+        );
+    }
+}
+
+/// Editor plugin, that allows to simulate physics of a scene in the editor, pause the simulation, step it
+/// frame-by-frame, see contacts, joint limits, sleep states and scrub back through the recorded frames.
+pub struct PhysicsDebuggerPlugin {
+    open_debugger: Handle<UiNode>,
+    window: Option<PhysicsDebuggerWindow>,
+    settings: PhysicsDebuggerSettings,
+    state: SimulationState,
+    scene: Handle<Scene>,
+    initial_frame: Option<RecordedFrame>,
+    frames: VecDeque<RecordedFrame>,
+    // Index of the recorded frame the user is looking at. `None` means the latest frame.
+    scrubbed_frame: Option<usize>,
+    step_requested: bool,
+}
+
+impl Default for PhysicsDebuggerPlugin {
+    fn default() -> Self {
+        Self {
+            open_debugger: Default::default(),
+            window: None,
+            settings: Default::default(),
+            state: SimulationState::Stopped,
+            scene: Default::default(),
+            initial_frame: None,
+            frames: Default::default(),
+            scrubbed_frame: None,
+            step_requested: false,
+        }
+    }
+}
+
+fn game_scene_mut(editor: &mut Editor, scene: Handle<Scene>) -> Option<&mut GameScene> {
+    editor
+        .scenes
+        .entries
+        .iter_mut()
+        .filter_map(|e| e.controller.downcast_mut::<GameScene>())
+        .find(|s| s.scene == scene)
+}
+
+fn current_game_scene_mut(editor: &mut Editor) -> Option<&mut GameScene> {
+    editor
+        .scenes
+        .current_scene_controller_mut()
+        .and_then(|c| c.downcast_mut::<GameScene>())
+}
+
+fn set_time_step(scene: &mut Scene, dt: Option<f32>) {
+    scene.graph.physics.integration_parameters.dt = dt;
+    scene.graph.physics2d.integration_parameters.dt = dt;
+}
+
+impl PhysicsDebuggerPlugin {
+    fn draw_options(&self) -> PhysicsDebugDrawOptions {
+        let mut options = self.settings.draw_options.clone();
+        if self.scrubbed_frame.is_some() {
+            // Contacts of the recorded frame are drawn instead of the live ones.
+            options.contacts = false;
+            options.contact_normals = false;
+            options.contact_impulses = false;
+        }
+        options
+    }
+
+    fn sync_draw_options(&self, editor: &mut Editor) {
+        let is_open = self.window.is_some();
+        let options = self.draw_options();
+        if let Some(game_scene) = current_game_scene_mut(editor) {
+            game_scene.physics_debug_draw_options = if is_open { Some(options) } else { None };
+        }
+    }
+
+    fn sync_timeline(&self, editor: &Editor) {
+        if let Some(window) = self.window.as_ref() {
+            let current = self
+                .scrubbed_frame
+                .unwrap_or_else(|| self.frames.len().saturating_sub(1));
+            window.sync_timeline(
+                self.frames.len(),
+                current,
+                editor.engine.user_interfaces.first(),
+            );
+        }
+    }
+
+    fn start(&mut self, editor: &mut Editor) {
+        match self.state {
+            SimulationState::Stopped => {
+                let game_scene = some_or_return!(editor
+                    .scenes
+                    .current_scene_controller_mut()
+                    .and_then(|c| c.downcast_mut::<GameScene>()));
+                let scene_handle = game_scene.scene;
+                let graph = &editor.engine.scenes[scene_handle].graph;
+                let initial_frame = RecordedFrame::capture(graph);
+
+                let node_overrides = game_scene.graph_switches.node_overrides.as_mut().unwrap();
+                for body in initial_frame.bodies.iter() {
+                    node_overrides.insert(body.handle);
+                }
+
+                self.scene = scene_handle;
+                self.frames.clear();
+                self.frames.push_back(initial_frame.clone());
+                self.initial_frame = Some(initial_frame);
+            }
+            SimulationState::Paused => {
+                // Continue from the frame the user is looking at, the rest of the recording is
+                // discarded.
+                if let Some(scrubbed_frame) = self.scrubbed_frame.take() {
+                    self.frames.truncate(scrubbed_frame + 1);
+                }
+            }
+            SimulationState::Running => return,
+        }
+
+        set_time_step(&mut editor.engine.scenes[self.scene], None);
+        self.state = SimulationState::Running;
+        self.sync_draw_options(editor);
+    }
+
+    fn pause(&mut self, editor: &mut Editor) {
+        if self.state == SimulationState::Running {
+            set_time_step(&mut editor.engine.scenes[self.scene], Some(0.0));
+            self.state = SimulationState::Paused;
+        }
+    }
+
+    fn step(&mut self, editor: &mut Editor) {
+        if self.state == SimulationState::Stopped {
+            self.start(editor);
+        }
+        if self.state == SimulationState::Running {
+            self.pause(editor);
+        }
+        if let Some(scrubbed_frame) = self.scrubbed_frame.take() {
+            self.frames.truncate(scrubbed_frame + 1);
+            self.sync_draw_options(editor);
+        }
+        set_time_step(&mut editor.engine.scenes[self.scene], Some(FIXED_TIMESTEP));
+        self.step_requested = true;
+    }
+
+    fn stop(&mut self, editor: &mut Editor) {
+        if self.state == SimulationState::Stopped {
+            return;
+        }
+
+        if editor.engine.scenes.is_valid_handle(self.scene) {
+            let scene = &mut editor.engine.scenes[self.scene];
+            set_time_step(scene, Some(0.0));
+            if let Some(initial_frame) = self.initial_frame.take() {
+                initial_frame.apply(&mut scene.graph);
+
+                if let Some(game_scene) = game_scene_mut(editor, self.scene) {
+                    let node_overrides = game_scene.graph_switches.node_overrides.as_mut().unwrap();
+                    for body in initial_frame.bodies.iter() {
+                        node_overrides.remove(&body.handle);
+                    }
+                }
+            }
+        }
+
+        self.state = SimulationState::Stopped;
+        self.initial_frame = None;
+        self.frames.clear();
+        self.scrubbed_frame = None;
+        self.step_requested = false;
+        self.sync_draw_options(editor);
+        self.sync_timeline(editor);
+    }
+
+    fn scrub(&mut self, value: f32, editor: &mut Editor) {
+        if self.state == SimulationState::Stopped || self.frames.is_empty() {
+            return;
+        }
+
+        self.pause(editor);
+
+        let index = (value.max(0.0) as usize).min(self.frames.len() - 1);
+        if index + 1 == self.frames.len() {
+            self.scrubbed_frame = None;
+        } else {
+            self.scrubbed_frame = Some(index);
+        }
+        self.frames[index].apply(&mut editor.engine.scenes[self.scene].graph);
+        self.sync_draw_options(editor);
+        self.sync_timeline(editor);
+    }
+
+    fn record_frame(&mut self, editor: &mut Editor) {
+        let graph = &editor.engine.scenes[self.scene].graph;
+        self.frames.push_back(RecordedFrame::capture(graph));
+        while self.frames.len() > self.settings.max_recorded_frames.max(1) {
+            self.frames.pop_front();
+        }
+        self.sync_timeline(editor);
+    }
+
+    fn close(&mut self, editor: &mut Editor) {
+        self.stop(editor);
+        if let Some(window) = self.window.take() {
+            editor
+                .engine
+                .user_interfaces
+                .first_mut()
+                .send_message(WidgetMessage::remove(
+                    window.window,
+                    MessageDirection::ToWidget,
+                ));
+        }
+        self.sync_draw_options(editor);
+    }
+}
+
+impl EditorPlugin for PhysicsDebuggerPlugin {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ui = editor.engine.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+        self.open_debugger = create_menu_item("Physics Debugger", vec![], ctx);
+        ui.send_message(MenuItemMessage::add_item(
+            editor.menu.utils_menu.menu,
+            MessageDirection::ToWidget,
+            self.open_debugger,
+        ));
+    }
+
+    fn on_scene_changed(&mut self, editor: &mut Editor) {
+        self.stop(editor);
+        self.sync_draw_options(editor);
+    }
+
+    fn on_leave_preview_mode(&mut self, editor: &mut Editor) {
+        self.stop(editor);
+    }
+
+    fn is_in_preview_mode(&self, _editor: &Editor) -> bool {
+        self.state != SimulationState::Stopped
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if message.destination() == self.open_debugger {
+                if self.window.is_none() {
+                    let ui = editor.engine.user_interfaces.first_mut();
+                    let window = PhysicsDebuggerWindow::new(
+                        &self.settings,
+                        editor.message_sender.clone(),
+                        &mut ui.build_ctx(),
+                    );
+                    ui.send_message(WindowMessage::open(
+                        window.window,
+                        MessageDirection::ToWidget,
+                        true,
+                        true,
+                    ));
+                    self.window = Some(window);
+                    self.sync_draw_options(editor);
+                }
+                return;
+            }
+        }
+
+        let window = some_or_return!(self.window.as_ref());
+
+        if let Some(WindowMessage::Close) = message.data() {
+            if message.destination() == window.window
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.close(editor);
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            let destination = message.destination();
+            if destination == window.simulate {
+                self.start(editor);
+            } else if destination == window.pause {
+                self.pause(editor);
+            } else if destination == window.step {
+                self.step(editor);
+            } else if destination == window.stop {
+                self.stop(editor);
+            }
+        } else if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == window.timeline
+                && message.direction() == MessageDirection::FromWidget
+                && message.flags != MSG_SYNC_FLAG
+            {
+                self.scrub(*value, editor);
+            }
+        } else if let Some(InspectorMessage::PropertyChanged(args)) = message.data() {
+            if message.destination() == window.inspector
+                && message.direction() == MessageDirection::FromWidget
+            {
+                PropertyAction::from_field_kind(&args.value).apply(
+                    &args.path(),
+                    &mut self.settings,
+                    &mut |result| {
+                        Log::verify(result);
+                    },
+                );
+                self.sync_draw_options(editor);
+            }
+        }
+    }
+
+    fn on_update(&mut self, editor: &mut Editor) {
+        if self.state == SimulationState::Stopped {
+            return;
+        }
+
+        if !editor.engine.scenes.is_valid_handle(self.scene) {
+            // The scene was closed.
+            self.state = SimulationState::Stopped;
+            self.initial_frame = None;
+            self.frames.clear();
+            self.scrubbed_frame = None;
+            return;
+        }
+
+        if self.step_requested {
+            // The step was done by the engine in this frame, freeze the simulation again.
+            self.step_requested = false;
+            set_time_step(&mut editor.engine.scenes[self.scene], Some(0.0));
+            self.record_frame(editor);
+        } else if self.state == SimulationState::Running {
+            self.record_frame(editor);
+        }
+    }
+
+    fn on_post_update(&mut self, editor: &mut Editor) {
+        if self.window.is_none() || self.state == SimulationState::Stopped {
+            return;
+        }
+
+        let frame = some_or_return!(self.scrubbed_frame.and_then(|i| self.frames.get(i)));
+        if !editor.engine.scenes.is_valid_handle(self.scene) {
+            return;
+        }
+        let scene = &mut editor.engine.scenes[self.scene];
+        self.settings
+            .draw_options
+            .draw_contact_points(&mut scene.drawing_context, &frame.contacts);
+    }
+}
@@ -48,6 +48,7 @@ use crate::{
             HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
         },
         renderer::{CsmSettings, QualitySettings, ShadowMapPrecision},
+        scene::layer::{LayerDefinition, TagLayerRegistry},
     },
     menu::create_menu_item,
     message::MessageSender,
@@ -80,6 +81,8 @@ fn make_property_editors_container(
     container.insert(InspectablePropertyEditorDefinition::<SelectionSettings>::new());
     container.insert(EnumPropertyEditorDefinition::<ShadowMapPrecision>::new());
     container.insert(EnumPropertyEditorDefinition::<ScriptEditor>::new());
+    container
+        .insert(crate::plugins::inspector::editors::make_visitor_format_enum_editor_definition());
     container.insert(EnumPropertyEditorDefinition::<EditorStyle>::new());
     container.insert(InspectablePropertyEditorDefinition::<DebuggingSettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<CsmSettings>::new());
@@ -103,6 +106,9 @@ fn make_property_editors_container(
     container.insert(VecCollectionPropertyEditorDefinition::<CommandDescriptor>::new());
     container.insert(InspectablePropertyEditorDefinition::<CommandDescriptor>::new());
     container.insert(HotKeyPropertyEditorDefinition);
+    container.insert(InspectablePropertyEditorDefinition::<TagLayerRegistry>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<LayerDefinition>::new());
+    container.insert(InspectablePropertyEditorDefinition::<LayerDefinition>::new());
     Arc::new(container)
 }
 
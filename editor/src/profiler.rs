@@ -0,0 +1,370 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! CPU profiler window, it shows the last frame captured by the [`Profiler`] as a flame graph.
+
+use crate::fyrox::{
+    core::{
+        algebra::Vector2,
+        color::Color,
+        log::{category, Log},
+        pool::Handle,
+        profiler::{Profiler, ProfilerFrame, INSTRUMENTATION_ENABLED},
+    },
+    fxhash::FxHashMap,
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        button::{ButtonBuilder, ButtonContent, ButtonMessage},
+        canvas::CanvasBuilder,
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+};
+
+const ROW_HEIGHT: f32 = 18.0;
+const THREAD_HEADER_HEIGHT: f32 = 18.0;
+const GRAPH_WIDTH: f32 = 1200.0;
+const REFRESH_INTERVAL: f32 = 0.5;
+const TRACE_PATH: &str = "profiler_trace.json";
+
+pub struct ProfilerWindow {
+    pub window: Handle<UiNode>,
+    pause: Handle<UiNode>,
+    save: Handle<UiNode>,
+    status: Handle<UiNode>,
+    details: Handle<UiNode>,
+    canvas: Handle<UiNode>,
+    items: Vec<Handle<UiNode>>,
+    descriptions: FxHashMap<Handle<UiNode>, String>,
+    paused: bool,
+    refresh_timer: f32,
+}
+
+pub enum ProfilerWindowAction {
+    None,
+    Remove,
+}
+
+fn category_color(category: &str) -> Color {
+    match category.split('.').next().unwrap_or_default() {
+        category::RENDERER => Color::opaque(190, 95, 70),
+        category::PHYSICS => Color::opaque(70, 140, 190),
+        category::SCENE => Color::opaque(90, 160, 90),
+        category::UI => Color::opaque(180, 140, 60),
+        category::ANIMATION => Color::opaque(150, 95, 180),
+        category::SCRIPT => Color::opaque(190, 170, 70),
+        category::RESOURCE => Color::opaque(80, 160, 160),
+        category::TASK => Color::opaque(120, 120, 170),
+        _ => Color::opaque(120, 120, 120),
+    }
+}
+
+fn millis(duration: std::time::Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
+
+impl ProfilerWindow {
+    pub fn new(ctx: &mut BuildContext, anchor: Handle<UiNode>) -> Self {
+        let pause;
+        let save;
+        let status;
+        let details;
+        let canvas;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(700.0).with_height(350.0))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_child({
+                                        pause = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(80.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Pause")
+                                        .build(ctx);
+                                        pause
+                                    })
+                                    .with_child({
+                                        save = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(80.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Save Trace")
+                                        .build(ctx);
+                                        save
+                                    })
+                                    .with_child({
+                                        status = TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(2.0)),
+                                        )
+                                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                                        .build(ctx);
+                                        status
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        )
+                        .with_child({
+                            details = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_margin(Thickness::uniform(2.0)),
+                            )
+                            .build(ctx);
+                            details
+                        })
+                        .with_child(
+                            ScrollViewerBuilder::new(WidgetBuilder::new().on_row(2))
+                                .with_content({
+                                    canvas = CanvasBuilder::new(WidgetBuilder::new()).build(ctx);
+                                    canvas
+                                })
+                                .build(ctx),
+                        ),
+                )
+                .add_row(Row::auto())
+                .add_row(Row::auto())
+                .add_row(Row::stretch())
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .with_title(WindowTitle::text("CPU Profiler"))
+            .build(ctx);
+
+        ctx.send_message(WindowMessage::open_and_align(
+            window,
+            MessageDirection::ToWidget,
+            anchor,
+            HorizontalAlignment::Center,
+            VerticalAlignment::Bottom,
+            Thickness::uniform(2.0),
+            false,
+            false,
+        ));
+
+        Profiler::set_enabled(true);
+
+        Self {
+            window,
+            pause,
+            save,
+            status,
+            details,
+            canvas,
+            items: Default::default(),
+            descriptions: Default::default(),
+            paused: false,
+            refresh_timer: REFRESH_INTERVAL,
+        }
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        ui: &UserInterface,
+    ) -> ProfilerWindowAction {
+        if let Some(WindowMessage::Close) = message.data() {
+            if message.destination() == self.window {
+                Profiler::set_enabled(false);
+
+                ui.send_message(WidgetMessage::remove(
+                    self.window,
+                    MessageDirection::ToWidget,
+                ));
+
+                return ProfilerWindowAction::Remove;
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.pause {
+                self.paused = !self.paused;
+                Profiler::set_enabled(!self.paused);
+                ui.send_message(ButtonMessage::content(
+                    self.pause,
+                    MessageDirection::ToWidget,
+                    ButtonContent::text(if self.paused { "Resume" } else { "Pause" }),
+                ));
+            } else if message.destination() == self.save {
+                match Profiler::save_chrome_trace(TRACE_PATH) {
+                    Ok(_) => Log::info(format!(
+                        "Profiler trace was saved to {TRACE_PATH}. Open it in chrome://tracing \
+                        or https://ui.perfetto.dev"
+                    )),
+                    Err(err) => Log::err(format!(
+                        "Unable to save profiler trace to {TRACE_PATH}. Reason: {err}"
+                    )),
+                }
+            }
+        } else if let Some(WidgetMessage::MouseEnter) = message.data() {
+            if let Some(description) = self.descriptions.get(&message.destination()) {
+                ui.send_message(TextMessage::text(
+                    self.details,
+                    MessageDirection::ToWidget,
+                    description.clone(),
+                ));
+            }
+        }
+        ProfilerWindowAction::None
+    }
+
+    pub fn update(&mut self, dt: f32, ui: &mut UserInterface) {
+        if self.paused {
+            return;
+        }
+
+        self.refresh_timer += dt;
+        if self.refresh_timer < REFRESH_INTERVAL {
+            return;
+        }
+        self.refresh_timer = 0.0;
+
+        if !INSTRUMENTATION_ENABLED {
+            ui.send_message(TextMessage::text(
+                self.status,
+                MessageDirection::ToWidget,
+                "Instrumentation is compiled out. Build the editor with `enable_profiler` feature."
+                    .to_string(),
+            ));
+            return;
+        }
+
+        if let Some(frame) = Profiler::last_frame() {
+            ui.send_message(TextMessage::text(
+                self.status,
+                MessageDirection::ToWidget,
+                format!(
+                    "Frame {}: {:.3} ms, {} scopes",
+                    frame.index,
+                    millis(frame.duration),
+                    frame.scopes.len()
+                ),
+            ));
+            self.rebuild_flame_graph(&frame, ui);
+        }
+    }
+
+    fn rebuild_flame_graph(&mut self, frame: &ProfilerFrame, ui: &mut UserInterface) {
+        for item in self.items.drain(..) {
+            ui.send_message(WidgetMessage::remove(item, MessageDirection::ToWidget));
+        }
+        self.descriptions.clear();
+
+        let frame_duration = frame.duration.as_secs_f32().max(f32::EPSILON);
+        let threads = Profiler::threads();
+        let mut thread_ids = frame
+            .scopes
+            .iter()
+            .map(|scope| scope.thread)
+            .collect::<Vec<_>>();
+        thread_ids.sort_unstable();
+        thread_ids.dedup();
+
+        let ctx = &mut ui.build_ctx();
+        let mut lane_y = 0.0;
+        for thread_id in thread_ids {
+            let thread_name = threads
+                .iter()
+                .find(|thread| thread.id == thread_id)
+                .map_or_else(
+                    || format!("Thread {thread_id}"),
+                    |thread| thread.name.clone(),
+                );
+            self.items.push(
+                TextBuilder::new(
+                    WidgetBuilder::new().with_desired_position(Vector2::new(2.0, lane_y)),
+                )
+                .with_text(thread_name)
+                .build(ctx),
+            );
+            lane_y += THREAD_HEADER_HEIGHT;
+
+            let mut max_depth = 0;
+            for scope in frame.thread_scopes(thread_id) {
+                max_depth = max_depth.max(scope.depth);
+
+                let start = scope.start.saturating_sub(frame.start).as_secs_f32();
+                let x = (start / frame_duration).min(1.0) * GRAPH_WIDTH;
+                let width = (scope.duration.as_secs_f32() / frame_duration * GRAPH_WIDTH)
+                    .min(GRAPH_WIDTH - x);
+                if width < 1.0 {
+                    continue;
+                }
+
+                let block = BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_desired_position(Vector2::new(
+                            x,
+                            lane_y + scope.depth as f32 * ROW_HEIGHT,
+                        ))
+                        .with_width(width)
+                        .with_height(ROW_HEIGHT - 1.0)
+                        .with_clip_to_bounds(true)
+                        .with_background(Brush::Solid(category_color(&scope.category)).into())
+                        .with_child(
+                            TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_hit_test_visibility(false)
+                                    .with_margin(Thickness::left(2.0)),
+                            )
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .with_text(scope.name.clone())
+                            .build(ctx),
+                        ),
+                )
+                .build(ctx);
+
+                self.descriptions.insert(
+                    block,
+                    format!(
+                        "{} [{}]: {:.3} ms",
+                        scope.name,
+                        scope.category,
+                        millis(scope.duration)
+                    ),
+                );
+                self.items.push(block);
+            }
+
+            lane_y += (max_depth + 1) as f32 * ROW_HEIGHT;
+        }
+
+        for item in self.items.iter() {
+            ui.send_message(WidgetMessage::link(
+                *item,
+                MessageDirection::ToWidget,
+                self.canvas,
+            ));
+        }
+    }
+}
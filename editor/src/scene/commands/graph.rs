@@ -25,10 +25,13 @@ use crate::{
             algebra::{UnitQuaternion, Vector3},
             pool::{Handle, Ticket},
         },
-        graph::{BaseSceneGraph, LinkScheme, SceneGraphNode},
+        graph::{BaseSceneGraph, LinkScheme, SceneGraph, SceneGraphNode},
         scene::{
             base::Base,
+            collider::{Collider, InteractionGroups},
+            dim2,
             graph::{Graph, SubGraph},
+            layer::LayerInteractions,
             node::Node,
             transform::Transform,
         },
@@ -587,3 +590,66 @@ impl CommandTrait for SetGraphRootCommand {
         self.root = std::mem::replace(context.scene_content_root, self.root);
     }
 }
+
+/// Enables or disables layer interactions of the graph (see [`Graph::set_layer_interactions`]).
+/// Enabling them overwrites collision groups of the colliders, so the command remembers the
+/// previous groups and restores them on revert.
+#[derive(Debug)]
+pub struct SetLayerInteractionsCommand {
+    interactions: Option<LayerInteractions>,
+    collision_groups: Vec<(Handle<Node>, InteractionGroups)>,
+}
+
+impl SetLayerInteractionsCommand {
+    pub fn new(interactions: Option<LayerInteractions>) -> Self {
+        Self {
+            interactions,
+            collision_groups: Default::default(),
+        }
+    }
+}
+
+impl CommandTrait for SetLayerInteractionsCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        if self.interactions.is_some() {
+            "Enable Layer Interactions".to_string()
+        } else {
+            "Disable Layer Interactions".to_string()
+        }
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        let graph = &mut context.get_mut::<GameSceneContext>().scene.graph;
+        self.collision_groups = graph
+            .pair_iter()
+            .filter_map(|(handle, node)| {
+                if let Some(collider) = node.cast::<Collider>() {
+                    Some((handle, collider.collision_groups()))
+                } else {
+                    node.cast::<dim2::collider::Collider>()
+                        .map(|collider| (handle, collider.collision_groups()))
+                }
+            })
+            .collect();
+        let previous = graph.layer_interactions().cloned();
+        graph.set_layer_interactions(self.interactions.take());
+        self.interactions = previous;
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        let graph = &mut context.get_mut::<GameSceneContext>().scene.graph;
+        let previous = graph.layer_interactions().cloned();
+        graph.set_layer_interactions(None);
+        for (handle, groups) in self.collision_groups.drain(..) {
+            if let Some(collider) = graph.try_get_mut_of_type::<Collider>(handle) {
+                collider.set_collision_groups(groups);
+            } else if let Some(collider) =
+                graph.try_get_mut_of_type::<dim2::collider::Collider>(handle)
+            {
+                collider.set_collision_groups(groups);
+            }
+        }
+        graph.set_layer_interactions(self.interactions.take());
+        self.interactions = previous;
+    }
+}
@@ -44,7 +44,7 @@ use crate::{
             math::{aabb::AxisAlignedBoundingBox, plane::Plane, Rect},
             pool::{ErasedHandle, Handle},
             reflect::Reflect,
-            visitor::Visitor,
+            visitor::{Visitor, VisitorFormat},
         },
         engine::{Engine, SerializationContext},
         fxhash::FxHashSet,
@@ -66,7 +66,8 @@ use crate::{
             base::BaseBuilder,
             camera::{Camera, Projection},
             debug::{Line, SceneDrawingContext},
-            graph::{Graph, GraphUpdateSwitches},
+            graph::{physics::PhysicsDebugDrawOptions, Graph, GraphUpdateSwitches},
+            layer::TagLayerRegistry,
             light::{point::PointLight, spot::SpotLight},
             mesh::RenderPath,
             mesh::{
@@ -148,6 +149,8 @@ pub struct GameScene {
     pub serialization_context: Arc<SerializationContext>,
     pub grid: Handle<Node>,
     pub settings_receiver: Receiver<SettingsMessage>,
+    /// Physics debug drawing options, that override the `show_physics` debugging setting when set.
+    pub physics_debug_draw_options: Option<PhysicsDebugDrawOptions>,
 }
 
 lazy_static! {
@@ -165,6 +168,18 @@ fn make_grid_material() -> MaterialResource {
     MaterialResource::new_ok(Default::default(), material)
 }
 
+/// Keeps layer interactions of the graph in sync with the project registry. Layer interactions
+/// overwrite collision groups of the colliders, so only the scenes that use them already (either
+/// loaded with them or enabled via [`commands::graph::SetLayerInteractionsCommand`]) are updated.
+fn sync_layer_interactions(graph: &mut Graph, registry: &TagLayerRegistry) {
+    if let Some(current) = graph.layer_interactions() {
+        let interactions = registry.interactions();
+        if current != &interactions {
+            graph.set_layer_interactions(Some(interactions));
+        }
+    }
+}
+
 impl GameScene {
     pub fn from_native_scene(
         mut scene: Scene,
@@ -242,6 +257,7 @@ impl GameScene {
             serialization_context: engine.serialization_context.clone(),
             grid,
             settings_receiver,
+            physics_debug_draw_options: None,
         }
     }
 
@@ -270,7 +286,7 @@ impl GameScene {
         let mut visitor = Visitor::new();
         pure_scene.save("Scene", &mut visitor).unwrap();
 
-        if let Err(e) = visitor.save_binary(path) {
+        if let Err(e) = visitor.save_with_format(settings.general.scene_save_format, path) {
             Err(format!("Failed to save scene! Reason: {e}"))
         } else {
             if settings.debugging.save_scene_in_text_form {
@@ -309,7 +325,16 @@ impl GameScene {
             }
         }
 
-        if debug_settings.show_physics {
+        if let Some(options) = self.physics_debug_draw_options.as_ref() {
+            scene
+                .graph
+                .physics
+                .draw_with_options(&mut scene.drawing_context, options);
+            scene
+                .graph
+                .physics2d
+                .draw_with_options(&mut scene.drawing_context, options);
+        } else if debug_settings.show_physics {
             scene.graph.physics.draw(&mut scene.drawing_context);
             scene.graph.physics2d.draw(&mut scene.drawing_context);
         }
@@ -433,7 +458,13 @@ impl GameScene {
         false
     }
 
-    fn try_save_selection_as_prefab(&self, path: &Path, selection: &Selection, engine: &Engine) {
+    pub fn try_save_selection_as_prefab(
+        &self,
+        path: &Path,
+        selection: &Selection,
+        engine: &Engine,
+        format: VisitorFormat,
+    ) {
         let source_scene = &engine.scenes[self.scene];
         let mut dest_scene = Scene::new();
         if let Some(graph_selection) = selection.as_graph() {
@@ -451,7 +482,7 @@ impl GameScene {
             match dest_scene.save("Scene", &mut visitor) {
                 Err(e) => Log::err(format!("Failed to save selection as prefab! Reason: {e:?}")),
                 Ok(_) => {
-                    if let Err(e) = visitor.save_binary(path) {
+                    if let Err(e) = visitor.save_with_format(format, path) {
                         Log::err(format!("Failed to save selection as prefab! Reason: {e:?}"));
                     } else {
                         Log::info(format!(
@@ -871,6 +902,8 @@ impl SceneController for GameScene {
             match message {
                 SettingsMessage::Changed => {
                     scene.graph[self.grid].set_visibility(settings.graphics.draw_grid);
+
+                    sync_layer_interactions(&mut scene.graph, &settings.tags_and_layers);
                 }
             }
         }
@@ -967,14 +1000,10 @@ impl SceneController for GameScene {
     fn on_message(
         &mut self,
         message: &Message,
-        selection: &Selection,
+        _selection: &Selection,
         engine: &mut Engine,
     ) -> bool {
         match message {
-            Message::SaveSelectionAsPrefab(path) => {
-                self.try_save_selection_as_prefab(path, selection, engine);
-                false
-            }
             Message::SetEditorCameraProjection(projection) => {
                 self.camera_controller
                     .set_projection(&mut engine.scenes[self.scene].graph, projection.clone());
@@ -1435,3 +1464,88 @@ impl Selection {
 
     define_downcast!(UiSelection, as_ui, as_ui_mut, is_ui);
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        fyrox::{
+            asset::manager::ResourceManager,
+            core::{futures::executor::block_on, task::TaskPool, visitor::prelude::*},
+            engine::{Engine, EngineInitParams},
+            graph::SceneGraph,
+            scene::{
+                base::BaseBuilder,
+                collider::{BitMask, Collider, ColliderBuilder, InteractionGroups},
+                layer::LayerMask,
+                Scene, SceneLoader,
+            },
+        },
+        message::MessageSender,
+        scene::{sync_layer_interactions, GameScene},
+        Settings,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn test_collision_groups_are_kept_on_load() {
+        let task_pool = Arc::new(TaskPool::default());
+        let mut engine = Engine::new(EngineInitParams {
+            graphics_context_params: Default::default(),
+            serialization_context: Arc::new(Default::default()),
+            widget_constructors: Arc::new(Default::default()),
+            resource_manager: ResourceManager::new(task_pool.clone()),
+            task_pool,
+        })
+        .unwrap();
+
+        let groups = InteractionGroups::new(BitMask(0b10), BitMask(0b100));
+        let mut scene = Scene::new();
+        let collider = ColliderBuilder::new(BaseBuilder::new().with_layers(LayerMask(0b1000)))
+            .with_collision_groups(groups)
+            .build(&mut scene.graph);
+
+        let mut visitor = Visitor::new();
+        scene.save("Scene", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+        let mut visitor = Visitor::load_from_memory(&data).unwrap();
+        let loader = SceneLoader::load(
+            "Scene",
+            engine.serialization_context.clone(),
+            engine.resource_manager.clone(),
+            &mut visitor,
+            None,
+        )
+        .unwrap();
+        let scene = block_on(loader.finish());
+
+        let mut settings = Settings::default();
+        let game_scene = GameScene::from_native_scene(
+            scene,
+            &mut engine,
+            None,
+            &mut settings,
+            MessageSender::default(),
+            None,
+        );
+
+        let graph = &mut engine.scenes[game_scene.scene].graph;
+        assert!(graph.layer_interactions().is_none());
+        assert_eq!(
+            graph
+                .try_get_of_type::<Collider>(collider)
+                .unwrap()
+                .collision_groups(),
+            groups
+        );
+
+        // Changes of the project settings do not affect the scenes without layer interactions.
+        sync_layer_interactions(graph, &settings.tags_and_layers);
+        assert_eq!(
+            graph
+                .try_get_of_type::<Collider>(collider)
+                .unwrap()
+                .collision_groups(),
+            groups
+        );
+    }
+}
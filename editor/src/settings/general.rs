@@ -18,7 +18,9 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use crate::fyrox::core::{reflect::prelude::*, type_traits::prelude::*, uuid_provider};
+use crate::fyrox::core::{
+    reflect::prelude::*, type_traits::prelude::*, uuid_provider, visitor::VisitorFormat,
+};
 use serde::{Deserialize, Serialize};
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
@@ -87,6 +89,14 @@ pub struct GeneralSettings {
 
     #[serde(default = "default_style")]
     pub style: EditorStyle,
+
+    #[reflect(
+        description = "Defines the format of scenes and prefabs saved by the editor. Text format is \
+    human-readable and could be merged by version control systems, but it takes more space and \
+    loads slower. Both formats are detected automatically on load."
+    )]
+    #[serde(default)]
+    pub scene_save_format: VisitorFormat,
 }
 
 fn default_style() -> EditorStyle {
@@ -149,6 +159,7 @@ impl Default for GeneralSettings {
             generate_previews: default_generate_previews(),
             max_log_entries: default_max_log_entries(),
             style: EditorStyle::Dark,
+            scene_save_format: Default::default(),
         }
     }
 }
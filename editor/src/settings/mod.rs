@@ -20,7 +20,10 @@
 
 use crate::settings::build::BuildSettings;
 use crate::{
-    fyrox::core::{log::Log, reflect::prelude::*},
+    fyrox::{
+        core::{log::Log, reflect::prelude::*},
+        scene::layer::TagLayerRegistry,
+    },
     settings::{
         camera::CameraSettings, debugging::DebuggingSettings, general::GeneralSettings,
         graphics::GraphicsSettings, keys::KeyBindings, model::ModelSettings,
@@ -70,6 +73,8 @@ pub struct SettingsData {
     pub camera: CameraSettings,
     pub navmesh: NavmeshSettings,
     pub key_bindings: KeyBindings,
+    #[serde(default)]
+    pub tags_and_layers: TagLayerRegistry,
     #[reflect(hidden)]
     pub scene_settings: HashMap<PathBuf, SceneSettings>,
     #[reflect(hidden)]
@@ -177,6 +182,12 @@ impl SettingsData {
 
         file.write_all(ron::ser::to_string_pretty(self, PrettyConfig::default())?.as_bytes())?;
 
+        // Tags and layers are needed by the game as well, so export them to a separate file that
+        // could be loaded by the game without the editor.
+        if let Err(err) = self.tags_and_layers.save(TagLayerRegistry::DEFAULT_PATH) {
+            Log::err(format!("Unable to save tags and layers. Reason: {err}"));
+        }
+
         Log::info("Settings were successfully saved!");
         Ok(())
     }
@@ -38,6 +38,7 @@ use crate::{
             math::Rect,
             pool::{ErasedHandle, Handle},
             reflect::Reflect,
+            visitor::{Visit, Visitor},
         },
         engine::Engine,
         fxhash::FxHashSet,
@@ -264,8 +265,13 @@ impl SceneController for UiScene {
         settings: &Settings,
         _engine: &mut Engine,
     ) -> Result<String, String> {
-        match self.ui.save(path) {
-            Ok(visitor) => {
+        let mut visitor = Visitor::new();
+        let result = self
+            .ui
+            .visit("Ui", &mut visitor)
+            .and_then(|_| visitor.save_with_format(settings.general.scene_save_format, path));
+        match result {
+            Ok(_) => {
                 if settings.debugging.save_scene_in_text_form {
                     let text = visitor.save_text();
                     let mut path = path.to_path_buf();
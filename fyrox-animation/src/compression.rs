@@ -0,0 +1,258 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Animation compression reduces the amount of memory occupied by animation tracks. See [`compress_tracks`]
+//! docs for more info.
+
+use crate::{container::TrackValueKind, Animation, AnimationTracksData, EntityId};
+use std::{
+    fmt::{Display, Formatter},
+    ops::AddAssign,
+};
+
+/// A set of parameters that defines how animation tracks will be compressed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompressionSettings {
+    /// Whether to remove keys, that can be reproduced by interpolation between their neighbours or not.
+    pub reduce_keys: bool,
+    /// Maximum allowed error for every track except rotation tracks, in units of the animated property.
+    pub tolerance: f32,
+    /// Maximum allowed angular error (in radians) for rotation tracks.
+    pub rotation_tolerance: f32,
+    /// Whether to convert rotation tracks to quantized quaternion keys or not. See
+    /// [`crate::container::TrackDataContainer::quantize_rotations`] for more info.
+    pub quantize_rotations: bool,
+}
+
+impl Default for CompressionSettings {
+    fn default() -> Self {
+        Self {
+            reduce_keys: true,
+            tolerance: 0.001,
+            rotation_tolerance: 0.001,
+            quantize_rotations: false,
+        }
+    }
+}
+
+/// Statistics of a compression process.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionReport {
+    /// Total amount of keys before compression.
+    pub keys_before: usize,
+    /// Total amount of keys after compression.
+    pub keys_after: usize,
+    /// Approximate size of the keys before compression, in bytes.
+    pub size_before: usize,
+    /// Approximate size of the keys after compression, in bytes.
+    pub size_after: usize,
+}
+
+impl CompressionReport {
+    /// Returns the amount of bytes saved by the compression.
+    pub fn saved_bytes(&self) -> usize {
+        self.size_before.saturating_sub(self.size_after)
+    }
+
+    /// Returns the ratio between the compressed size and the original size. `1.0` means that nothing was
+    /// compressed.
+    pub fn ratio(&self) -> f32 {
+        if self.size_before == 0 {
+            1.0
+        } else {
+            self.size_after as f32 / self.size_before as f32
+        }
+    }
+}
+
+impl AddAssign for CompressionReport {
+    fn add_assign(&mut self, rhs: Self) {
+        self.keys_before += rhs.keys_before;
+        self.keys_after += rhs.keys_after;
+        self.size_before += rhs.size_before;
+        self.size_after += rhs.size_after;
+    }
+}
+
+impl Display for CompressionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "keys: {} -> {}, size: {} -> {} bytes ({} bytes saved, {:.1}% of the original size)",
+            self.keys_before,
+            self.keys_after,
+            self.size_before,
+            self.size_after,
+            self.saved_bytes(),
+            self.ratio() * 100.0
+        )
+    }
+}
+
+/// Compresses every track in the given tracks data using the given settings. Rotation tracks use
+/// [`CompressionSettings::rotation_tolerance`], all other tracks use [`CompressionSettings::tolerance`].
+pub fn compress_tracks(
+    tracks_data: &mut AnimationTracksData,
+    settings: &CompressionSettings,
+) -> CompressionReport {
+    let mut report = CompressionReport::default();
+
+    for track in tracks_data.tracks.iter_mut() {
+        let container = track.data_container_mut();
+
+        report.keys_before += container.keys_count();
+        report.size_before += container.keys_size();
+
+        let is_rotation = container.value_kind() == TrackValueKind::UnitQuaternion;
+        if is_rotation && settings.quantize_rotations {
+            let tolerance = if settings.reduce_keys {
+                settings.rotation_tolerance
+            } else {
+                0.0
+            };
+            container.quantize_rotations(tolerance);
+        } else if settings.reduce_keys {
+            container.reduce_keys(if is_rotation {
+                settings.rotation_tolerance
+            } else {
+                settings.tolerance
+            });
+        }
+
+        report.keys_after += container.keys_count();
+        report.size_after += container.keys_size();
+    }
+
+    report
+}
+
+/// Compresses the tracks of the given animation, see [`compress_tracks`] for more info. Returns `None` if
+/// the tracks data of the animation is not loaded.
+pub fn compress_animation<T: EntityId>(
+    animation: &mut Animation<T>,
+    settings: &CompressionSettings,
+) -> Option<CompressionReport> {
+    let mut state = animation.tracks_data().state();
+    let tracks_data = state.data()?;
+    Some(compress_tracks(tracks_data, settings))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        compression::{compress_tracks, CompressionSettings},
+        container::TrackValueKind,
+        core::math::curve::{CurveKey, CurveKeyKind},
+        track::Track,
+        value::TrackValue,
+        AnimationTracksData,
+    };
+
+    fn make_tracks() -> AnimationTracksData {
+        let mut position = Track::new_position();
+        let mut rotation = Track::new_rotation();
+        for i in 0..=60 {
+            let time = i as f32 / 30.0;
+            for (n, curve) in position
+                .data_container_mut()
+                .curves_mut()
+                .iter_mut()
+                .enumerate()
+            {
+                curve.add_key(CurveKey::new(time, time * n as f32, CurveKeyKind::Linear));
+            }
+            let curves = rotation.data_container_mut().curves_mut();
+            curves[0].add_key(CurveKey::new(time, 0.0, CurveKeyKind::Linear));
+            curves[1].add_key(CurveKey::new(time, time.sin(), CurveKeyKind::Linear));
+            curves[2].add_key(CurveKey::new(time, 0.0, CurveKeyKind::Linear));
+        }
+        AnimationTracksData {
+            tracks: vec![position, rotation],
+        }
+    }
+
+    #[test]
+    fn test_compression() {
+        let reference = make_tracks();
+
+        for quantize_rotations in [false, true] {
+            let mut tracks = make_tracks();
+            let settings = CompressionSettings {
+                quantize_rotations,
+                ..Default::default()
+            };
+            let report = compress_tracks(&mut tracks, &settings);
+
+            assert_eq!(report.keys_before, 6 * 61);
+            assert!(report.keys_after < report.keys_before);
+            assert!(report.size_after < report.size_before);
+            assert_eq!(
+                tracks.tracks[1].data_container().is_quantized(),
+                quantize_rotations
+            );
+            assert_eq!(
+                tracks.tracks[1].data_container().value_kind(),
+                TrackValueKind::UnitQuaternion
+            );
+
+            // Linear position track must be reduced to two keys per curve.
+            for curve in tracks.tracks[0].data_container().curves_ref() {
+                assert_eq!(curve.keys().len(), 2);
+            }
+
+            for i in 0..=120 {
+                let time = i as f32 / 60.0;
+
+                let (Some(TrackValue::Vector3(a)), Some(TrackValue::Vector3(b))) = (
+                    tracks.tracks[0].data_container().fetch(time),
+                    reference.tracks[0].data_container().fetch(time),
+                ) else {
+                    unreachable!()
+                };
+                assert!(a.metric_distance(&b) <= 0.01);
+
+                let (Some(TrackValue::UnitQuaternion(a)), Some(TrackValue::UnitQuaternion(b))) = (
+                    tracks.tracks[1].data_container().fetch(time),
+                    reference.tracks[1].data_container().fetch(time),
+                ) else {
+                    unreachable!()
+                };
+                assert!(a.angle_to(&b) <= 0.01);
+            }
+
+            assert_eq!(
+                tracks.tracks[1].time_length(),
+                reference.tracks[1].time_length()
+            );
+        }
+
+        // Disabled reduction must not remove any keys.
+        let mut tracks = make_tracks();
+        let report = compress_tracks(
+            &mut tracks,
+            &CompressionSettings {
+                reduce_keys: false,
+                ..Default::default()
+            },
+        );
+        assert_eq!(report.keys_before, report.keys_after);
+        assert_eq!(report.saved_bytes(), 0);
+    }
+}
@@ -23,7 +23,7 @@
 
 use crate::{
     core::{
-        algebra::{Vector2, Vector3, Vector4},
+        algebra::{Quaternion, UnitQuaternion, Vector2, Vector3, Vector4},
         math::curve::Curve,
         math::{quat_from_euler, RotationOrder},
         reflect::prelude::*,
@@ -87,6 +87,53 @@ pub enum InterpolationMode {
     ShortPath,
 }
 
+/// A rotation key that stores a unit quaternion with each component quantized to a signed 16-bit integer.
+/// It takes 12 bytes, which is much less than three keys of Euler angle curves.
+#[derive(Visit, Reflect, Debug, Clone, Default, PartialEq)]
+pub struct QuantizedRotationKey {
+    /// Location of the key on the time axis.
+    pub location: f32,
+    /// Quantized `X`, `Y`, `Z`, `W` components of the rotation.
+    pub components: [i16; 4],
+}
+
+impl QuantizedRotationKey {
+    /// Creates a new key at the given location with quantized version of the given rotation.
+    pub fn new(location: f32, rotation: UnitQuaternion<f32>) -> Self {
+        let quantize = |v: f32| (v.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+        let coords = rotation.coords;
+        Self {
+            location,
+            components: [
+                quantize(coords.x),
+                quantize(coords.y),
+                quantize(coords.z),
+                quantize(coords.w),
+            ],
+        }
+    }
+
+    /// Restores the rotation from its quantized form.
+    pub fn rotation(&self) -> UnitQuaternion<f32> {
+        let [x, y, z, w] = self.components.map(|c| c as f32 / i16::MAX as f32);
+        UnitQuaternion::new_normalize(Quaternion::new(w, x, y, z))
+    }
+}
+
+fn nlerp_rotations(
+    a: &UnitQuaternion<f32>,
+    b: &UnitQuaternion<f32>,
+    t: f32,
+) -> UnitQuaternion<f32> {
+    // Make sure that the interpolation goes by the shortest path.
+    let b = if a.coords.dot(&b.coords) < 0.0 {
+        -b.coords
+    } else {
+        b.coords
+    };
+    UnitQuaternion::new_normalize(Quaternion::from(a.coords.lerp(&b, t)))
+}
+
 /// Container for a track data. Strictly speaking, it is just a set of parametric curves which can be
 /// fetched at a given time position simultaneously, producing a value of desired type. Which type of
 /// value is produced is defined by [`TrackValueKind`] enumeration. Usually a container contains up to
@@ -101,6 +148,10 @@ pub struct TrackDataContainer {
     /// Interpolation mode.
     #[visit(optional)] // Backward compatibility.
     pub mode: InterpolationMode,
+    /// Quantized rotation keys. When the container is quantized (see [`Self::quantize_rotations`]) its
+    /// curves are empty and rotation is fetched from these keys instead.
+    #[visit(optional)]
+    quantized_rotations: Vec<QuantizedRotationKey>,
 }
 
 impl TrackDataContainer {
@@ -118,6 +169,7 @@ impl TrackDataContainer {
                 .map(|_| Curve::default())
                 .collect(),
             mode: Default::default(),
+            quantized_rotations: Default::default(),
         }
     }
 
@@ -205,8 +257,149 @@ impl TrackDataContainer {
         }
     }
 
+    /// Returns quantized rotation keys of the container. The slice is empty if the container is not quantized.
+    pub fn quantized_rotations(&self) -> &[QuantizedRotationKey] {
+        &self.quantized_rotations
+    }
+
+    /// Returns `true` if the container stores rotation as quantized quaternion keys instead of Euler angle
+    /// curves.
+    pub fn is_quantized(&self) -> bool {
+        !self.quantized_rotations.is_empty()
+    }
+
+    /// Returns total amount of keys in the container.
+    pub fn keys_count(&self) -> usize {
+        self.curves.iter().map(|c| c.keys().len()).sum::<usize>() + self.quantized_rotations.len()
+    }
+
+    /// Returns approximate amount of memory (in bytes) occupied by the keys of the container.
+    pub fn keys_size(&self) -> usize {
+        self.curves
+            .iter()
+            .map(|c| std::mem::size_of_val(c.keys()))
+            .sum::<usize>()
+            + std::mem::size_of_val(self.quantized_rotations.as_slice())
+    }
+
+    /// Removes every key, that can be reproduced by interpolation between its neighbours with an error that
+    /// does not exceed the given `tolerance`. Returns the amount of removed keys. Quantized rotations are
+    /// reduced when they're created, see [`Self::quantize_rotations`].
+    pub fn reduce_keys(&mut self, tolerance: f32) -> usize {
+        let short_path = self.kind == TrackValueKind::UnitQuaternion
+            && self.mode == InterpolationMode::ShortPath;
+        self.curves
+            .iter_mut()
+            .map(|curve| {
+                if short_path {
+                    curve.reduce_angle_keys(tolerance)
+                } else {
+                    curve.reduce_keys(tolerance)
+                }
+            })
+            .sum()
+    }
+
+    /// Converts Euler angle curves of a rotation container to a set of quantized quaternion keys, removing every
+    /// key that can be reproduced by interpolation between its neighbours with an angular error (in radians)
+    /// less than the given `tolerance`. Returns `false` if the container does not hold rotation or it is
+    /// already quantized.
+    ///
+    /// # Important notes
+    ///
+    /// Quantized containers cannot be edited in the curve editor, since they do not have curves anymore.
+    pub fn quantize_rotations(&mut self, tolerance: f32) -> bool {
+        if self.kind != TrackValueKind::UnitQuaternion || self.is_quantized() {
+            return false;
+        }
+
+        let mut locations = self
+            .curves
+            .iter()
+            .flat_map(|curve| curve.keys().iter().map(|key| key.location))
+            .collect::<Vec<_>>();
+        locations.sort_by(|a, b| a.total_cmp(b));
+        locations.dedup_by(|a, b| (*a - *b).abs() <= f32::EPSILON);
+
+        let fetch = |time: f32| match self.fetch_quaternion(time) {
+            Some(TrackValue::UnitQuaternion(rotation)) => rotation,
+            _ => UnitQuaternion::identity(),
+        };
+
+        let mut samples = Vec::<UnitQuaternion<f32>>::with_capacity(locations.len());
+        for &location in locations.iter() {
+            let mut rotation = fetch(location);
+            // Keep the neighbouring samples in the same hemisphere.
+            if let Some(prev) = samples.last() {
+                if prev.coords.dot(&rotation.coords) < 0.0 {
+                    rotation = UnitQuaternion::new_unchecked(-rotation.into_inner());
+                }
+            }
+            samples.push(rotation);
+        }
+
+        if samples.is_empty() {
+            return false;
+        }
+
+        let span_fits = |left: usize, right: usize| -> bool {
+            let length = locations[right] - locations[left];
+            let error = |location: f32, expected: &UnitQuaternion<f32>| {
+                let t = (location - locations[left]) / length;
+                nlerp_rotations(&samples[left], &samples[right], t).angle_to(expected)
+            };
+            (left + 1..=right).all(|i| {
+                let middle = (locations[i - 1] + locations[i]) * 0.5;
+                error(locations[i], &samples[i]) <= tolerance
+                    && error(middle, &fetch(middle)) <= tolerance
+            })
+        };
+
+        let mut keys = vec![QuantizedRotationKey::new(locations[0], samples[0])];
+        let mut anchor = 0;
+        for i in 1..samples.len().saturating_sub(1) {
+            if !span_fits(anchor, i + 1) {
+                keys.push(QuantizedRotationKey::new(locations[i], samples[i]));
+                anchor = i;
+            }
+        }
+        if samples.len() > 1 {
+            let last = samples.len() - 1;
+            keys.push(QuantizedRotationKey::new(locations[last], samples[last]));
+        }
+
+        for curve in self.curves.iter_mut() {
+            curve.clear();
+        }
+        self.quantized_rotations = keys;
+
+        true
+    }
+
+    #[inline(always)]
+    fn fetch_quantized_rotation(&self, time: f32) -> Option<TrackValue> {
+        let keys = &self.quantized_rotations;
+        let (first, last) = (keys.first()?, keys.last()?);
+        let rotation = if time <= first.location {
+            first.rotation()
+        } else if time >= last.location {
+            last.rotation()
+        } else {
+            let pos = keys.partition_point(|k| k.location < time);
+            let left = &keys[pos.saturating_sub(1)];
+            let right = &keys[pos];
+            let t = (time - left.location) / (right.location - left.location);
+            nlerp_rotations(&left.rotation(), &right.rotation(), t)
+        };
+        Some(TrackValue::UnitQuaternion(rotation))
+    }
+
     #[inline(always)]
     fn fetch_quaternion(&self, time: f32) -> Option<TrackValue> {
+        if !self.quantized_rotations.is_empty() {
+            return self.fetch_quantized_rotation(time);
+        }
+
         if self.curves.len() < 3 {
             return None;
         }
@@ -261,6 +454,9 @@ impl TrackDataContainer {
                 length = max_location;
             }
         }
+        if let Some(last) = self.quantized_rotations.last() {
+            length = length.max(last.location);
+        }
         length
     }
 }
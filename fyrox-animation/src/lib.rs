@@ -54,8 +54,9 @@ use crate::track::TrackBinding;
 pub use fyrox_core as core;
 use fyrox_resource::untyped::ResourceKind;
 pub use pose::{AnimationPose, NodePose};
-pub use signal::{AnimationEvent, AnimationSignal};
+pub use signal::{AnimationEvent, AnimationSignal, SignalPayload};
 
+pub mod compression;
 pub mod container;
 pub mod machine;
 pub mod pose;
@@ -547,6 +548,7 @@ impl<T: EntityId> Animation<T> {
                 self.events.push_back(AnimationEvent {
                     signal_id: signal.id,
                     name: signal.name.clone(),
+                    payload: signal.payload.clone(),
                 });
             }
         }
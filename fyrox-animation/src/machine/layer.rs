@@ -26,6 +26,7 @@ use crate::{
         log::{Log, MessageKind},
         pool::{Handle, Pool},
         reflect::prelude::*,
+        uuid_provider,
         visitor::prelude::*,
     },
     machine::{
@@ -35,6 +36,23 @@ use crate::{
     Animation, AnimationContainer, AnimationEvent, AnimationPose, EntityId,
 };
 use fyrox_core::{find_by_name_mut, find_by_name_ref, NameProvider};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how a pose of a layer is combined with the poses of the layers above it.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum LayerBlendMode {
+    /// The pose of the layer replaces the pose of the layers above it in proportion to the weight of the layer.
+    #[default]
+    Override,
+    /// The pose of the layer is considered as a difference pose (see [`crate::machine::node::difference::PoseDifference`])
+    /// and it is added on top of the pose of the layers above it, scaled by the weight of the layer. It is
+    /// useful for aim offsets, breathing, hit reactions, etc.
+    Additive,
+}
+
+uuid_provider!(LayerBlendMode = "2b9a7a1c-5e63-4c1a-bb1d-6b7c3f0e9d48");
 
 /// Layer is a separate state graph. Layers mainly used to animate different parts of humanoid (but not only) characters. For
 /// example there could a layer for upper body and a layer for lower body. Upper body layer could contain animations for aiming,
@@ -87,6 +105,9 @@ pub struct MachineLayer<T: EntityId> {
 
     weight: f32,
 
+    #[visit(optional)]
+    blend_mode: LayerBlendMode,
+
     mask: LayerMask<T>,
 
     #[reflect(hidden)]
@@ -178,6 +199,7 @@ impl<T: EntityId> MachineLayer<T> {
             entry_state: Default::default(),
             active_transition: Default::default(),
             weight: 1.0,
+            blend_mode: Default::default(),
             events: FixedEventQueue::new(2048),
             debug: false,
             mask: Default::default(),
@@ -536,6 +558,18 @@ impl<T: EntityId> MachineLayer<T> {
         self.weight
     }
 
+    /// Sets new blend mode of the layer. See docs of [`LayerBlendMode`] for more info.
+    #[inline]
+    pub fn set_blend_mode(&mut self, blend_mode: LayerBlendMode) -> LayerBlendMode {
+        std::mem::replace(&mut self.blend_mode, blend_mode)
+    }
+
+    /// Returns current blend mode of the layer.
+    #[inline]
+    pub fn blend_mode(&self) -> LayerBlendMode {
+        self.blend_mode
+    }
+
     /// Sets new layer mask. See docs of [`LayerMask`] for more info about layer masks.
     #[inline]
     pub fn set_mask(&mut self, mask: LayerMask<T>) -> LayerMask<T> {
@@ -585,6 +619,26 @@ impl<T: EntityId> MachineLayer<T> {
             .all(|a| a.has_ended())
     }
 
+    /// Synchronizes the animations blended by the nodes of the active state(s), see
+    /// [`crate::machine::BlendSyncMode`] docs for more info.
+    pub(super) fn synchronize_animations(
+        &self,
+        animations: &mut AnimationContainer<T>,
+        parameters: &ParameterContainer,
+    ) {
+        let mut active_states = [self.active_state, Handle::NONE, Handle::NONE];
+        if let Some(transition) = self.transitions.try_borrow(self.active_transition) {
+            active_states[1] = transition.source();
+            active_states[2] = transition.dest();
+        }
+
+        for node in self.nodes.iter() {
+            if node.parent_state.is_some() && active_states.contains(&node.parent_state) {
+                node.synchronize_animations(&self.nodes, parameters, animations);
+            }
+        }
+    }
+
     #[inline]
     pub(super) fn evaluate_pose(
         &mut self,
@@ -37,15 +37,17 @@ use fxhash::FxHashSet;
 pub use event::Event;
 use fyrox_core::pool::Handle;
 use fyrox_core::{find_by_name_mut, find_by_name_ref};
-pub use layer::MachineLayer;
+pub use layer::{LayerBlendMode, MachineLayer};
 pub use mask::LayerMask;
 pub use node::{
     blend::{BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput},
+    difference::PoseDifference,
     play::PlayAnimation,
     AnimationPoseSource, PoseNode,
 };
 pub use parameter::{Parameter, ParameterContainer, PoseWeight};
 pub use state::State;
+pub use sync::BlendSyncMode;
 pub use transition::Transition;
 
 pub mod event;
@@ -54,6 +56,7 @@ pub mod mask;
 pub mod node;
 pub mod parameter;
 pub mod state;
+pub mod sync;
 pub mod transition;
 
 /// Animation blending state machine is used to blend multiple animation as well as perform automatic smooth transitions
@@ -73,6 +76,7 @@ pub mod transition;
 /// `Parameter` - is a named variable of a fixed type (see `Parameters` section for more info).
 /// `Layer` - is a separate state graph, there could be any number of layers - each with its own mask.
 /// `Mask` - a set of handles to nodes which will be excluded from animation on a layer.
+/// `Blend mode` - defines how a pose of a layer is combined with the poses of the previous layers, see [`LayerBlendMode`].
 /// `Pose` - a final result of blending multiple animation into one.
 ///
 /// Summarizing everything of this, we can describe animation blending state machine as a state graph, where each state has its
@@ -345,6 +349,12 @@ impl<T: EntityId> Machine<T> {
             }
         }
 
+        // Align the phases of blended animations before advancing them, so the poses will be sampled at the
+        // synchronized positions.
+        for layer in self.layers.iter() {
+            layer.synchronize_animations(animations, &self.parameters);
+        }
+
         for animation_handle in self.animations_cache.iter() {
             if let Some(animation) = animations.try_get_mut(*animation_handle) {
                 if animation.is_enabled() {
@@ -355,9 +365,13 @@ impl<T: EntityId> Machine<T> {
 
         for layer in self.layers.iter_mut() {
             let weight = layer.weight();
+            let blend_mode = layer.blend_mode();
             let pose = layer.evaluate_pose(animations, &self.parameters, dt);
 
-            self.final_pose.blend_with(pose, weight);
+            match blend_mode {
+                LayerBlendMode::Override => self.final_pose.blend_with(pose, weight),
+                LayerBlendMode::Additive => self.final_pose.add_weighted(pose, weight),
+            }
         }
 
         &self.final_pose
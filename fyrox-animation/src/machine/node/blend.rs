@@ -27,8 +27,8 @@ use crate::{
         visitor::{Visit, VisitResult, Visitor},
     },
     machine::{
-        node::AnimationEventCollectionStrategy, node::BasePoseNode, AnimationPoseSource, Parameter,
-        ParameterContainer, PoseNode, PoseWeight,
+        node::AnimationEventCollectionStrategy, node::BasePoseNode, AnimationPoseSource,
+        BlendSyncMode, Parameter, ParameterContainer, PoseNode, PoseWeight,
     },
     Animation, AnimationContainer, AnimationEvent, AnimationPose, EntityId,
 };
@@ -101,6 +101,11 @@ pub struct BlendAnimations<T: EntityId> {
     /// A list of pose sources. See [`BlendPose`] docs for more info.
     pub pose_sources: Vec<BlendPose<T>>,
 
+    /// Defines how the animations of the pose sources are synchronized with each other. See
+    /// [`BlendSyncMode`] docs for more info.
+    #[visit(optional)]
+    pub sync_mode: BlendSyncMode,
+
     /// Output pose of the node, contains final result of blending all input poses.
     #[visit(skip)]
     #[reflect(hidden)]
@@ -127,6 +132,7 @@ impl<T: EntityId> BlendAnimations<T> {
         Self {
             base: Default::default(),
             pose_sources: poses,
+            sync_mode: Default::default(),
             output_pose: Default::default(),
         }
     }
@@ -135,6 +141,14 @@ impl<T: EntityId> BlendAnimations<T> {
     pub fn children(&self) -> Vec<Handle<PoseNode<T>>> {
         self.pose_sources.iter().map(|s| s.pose_source).collect()
     }
+
+    /// Returns a set of pose sources with their current weights.
+    pub fn sync_inputs(&self, params: &ParameterContainer) -> Vec<(Handle<PoseNode<T>>, f32)> {
+        self.pose_sources
+            .iter()
+            .filter_map(|s| s.weight.value(params).map(|w| (s.pose_source, w)))
+            .collect()
+    }
 }
 
 impl<T: EntityId> AnimationPoseSource<T> for BlendAnimations<T> {
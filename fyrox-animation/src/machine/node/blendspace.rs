@@ -29,8 +29,8 @@ use crate::{
         visitor::prelude::*,
     },
     machine::{
-        node::AnimationEventCollectionStrategy, node::BasePoseNode, AnimationPoseSource, Parameter,
-        ParameterContainer, PoseNode,
+        node::AnimationEventCollectionStrategy, node::BasePoseNode, AnimationPoseSource,
+        BlendSyncMode, Parameter, ParameterContainer, PoseNode,
     },
     Animation, AnimationContainer, AnimationEvent, AnimationPose, EntityId,
 };
@@ -83,6 +83,10 @@ pub struct BlendSpace<T: EntityId> {
     #[reflect(setter = "set_sampling_parameter")]
     sampling_parameter: String,
 
+    #[visit(optional)]
+    #[reflect(setter = "set_sync_mode")]
+    sync_mode: BlendSyncMode,
+
     #[reflect(hidden)]
     #[visit(skip)]
     pose: RefCell<AnimationPose<T>>,
@@ -100,6 +104,7 @@ impl<T: EntityId> Default for BlendSpace<T> {
             max_values: Vector2::new(1.0, 1.0),
             snap_step: Vector2::new(0.1, 0.1),
             sampling_parameter: Default::default(),
+            sync_mode: Default::default(),
             pose: Default::default(),
         }
     }
@@ -330,6 +335,34 @@ impl<T: EntityId> BlendSpace<T> {
         &self.y_axis_name
     }
 
+    /// Sets new synchronization mode of the animations of the blend space. See [`BlendSyncMode`] docs for more
+    /// info.
+    pub fn set_sync_mode(&mut self, mode: BlendSyncMode) -> BlendSyncMode {
+        std::mem::replace(&mut self.sync_mode, mode)
+    }
+
+    /// Returns current synchronization mode of the animations of the blend space.
+    pub fn sync_mode(&self) -> BlendSyncMode {
+        self.sync_mode
+    }
+
+    /// Returns a set of pose sources of the points that are used for sampling at the current sampling point,
+    /// along with their weights.
+    pub fn sync_inputs(&self, params: &ParameterContainer) -> Vec<(Handle<PoseNode<T>>, f32)> {
+        if let Some(Parameter::SamplingPoint(sampling_point)) = params.get(&self.sampling_parameter)
+        {
+            if let Some(weights) = self.fetch_weights(*sampling_point) {
+                return weights
+                    .iter()
+                    .filter_map(|(index, weight)| {
+                        self.points.get(*index).map(|p| (p.pose_source, *weight))
+                    })
+                    .collect();
+            }
+        }
+        Default::default()
+    }
+
     pub fn try_snap_points(&mut self) {
         for point in self.points.iter_mut() {
             let x = math::round_to_step(point.position.x, self.snap_step.x)
@@ -0,0 +1,257 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! A node that computes a difference between two poses. See [`PoseDifference`] docs for more info.
+
+use crate::{
+    core::{
+        pool::{Handle, Pool},
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    machine::{
+        node::AnimationEventCollectionStrategy,
+        node::{AnimationPoseSource, BasePoseNode},
+        ParameterContainer, PoseNode,
+    },
+    Animation, AnimationContainer, AnimationEvent, AnimationPose, EntityId,
+};
+use std::{
+    cell::{Ref, RefCell},
+    ops::{Deref, DerefMut},
+};
+
+/// A node that computes a difference between an input pose and a reference pose. The result is an additive pose,
+/// that contains only changes relative to the reference pose. For example, a difference between "aim up" animation
+/// and a single frame of "aim forward" animation contains only the rotations needed to raise the arms and the torso.
+/// Such pose could then be added on top of any other pose (idle, walk, run, etc.) using a layer with
+/// [`crate::machine::LayerBlendMode::Additive`] blend mode.
+///
+/// Rotations and translations are subtracted, scales are divided. Node poses that are missing in the reference pose
+/// are ignored.
+#[derive(Default, Debug, Visit, Clone, Reflect, PartialEq)]
+pub struct PoseDifference<T: EntityId> {
+    /// Base node.
+    pub base: BasePoseNode<T>,
+
+    /// A source of the input pose.
+    #[reflect(hidden)]
+    pub pose_source: Handle<PoseNode<T>>,
+
+    /// A source of the reference pose. Usually it is a single frame of an animation.
+    #[reflect(hidden)]
+    pub reference_source: Handle<PoseNode<T>>,
+
+    /// Output pose of the node, contains the difference between the input and the reference poses.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub output_pose: RefCell<AnimationPose<T>>,
+}
+
+impl<T: EntityId> Deref for PoseDifference<T> {
+    type Target = BasePoseNode<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<T: EntityId> DerefMut for PoseDifference<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl<T: EntityId> PoseDifference<T> {
+    /// Creates new pose difference node with the given input and reference pose sources.
+    pub fn new(pose_source: Handle<PoseNode<T>>, reference_source: Handle<PoseNode<T>>) -> Self {
+        Self {
+            base: Default::default(),
+            pose_source,
+            reference_source,
+            output_pose: Default::default(),
+        }
+    }
+
+    /// Returns a set of handles to children pose nodes. The first one is the input pose source, the second one is
+    /// the reference pose source.
+    pub fn children(&self) -> Vec<Handle<PoseNode<T>>> {
+        vec![self.pose_source, self.reference_source]
+    }
+}
+
+impl<T: EntityId> AnimationPoseSource<T> for PoseDifference<T> {
+    fn eval_pose(
+        &self,
+        nodes: &Pool<PoseNode<T>>,
+        params: &ParameterContainer,
+        animations: &AnimationContainer<T>,
+        dt: f32,
+    ) -> Ref<AnimationPose<T>> {
+        let mut output_pose = self.output_pose.borrow_mut();
+        output_pose.reset();
+
+        if let Some(pose) = nodes
+            .try_borrow(self.pose_source)
+            .map(|source| source.eval_pose(nodes, params, animations, dt))
+        {
+            if self.reference_source == self.pose_source {
+                pose.difference_into(&pose, &mut output_pose);
+            } else if let Some(reference) = nodes
+                .try_borrow(self.reference_source)
+                .map(|source| source.eval_pose(nodes, params, animations, dt))
+            {
+                pose.difference_into(&reference, &mut output_pose);
+            }
+        }
+
+        drop(output_pose);
+        self.output_pose.borrow()
+    }
+
+    fn pose(&self) -> Ref<AnimationPose<T>> {
+        self.output_pose.borrow()
+    }
+
+    fn collect_animation_events(
+        &self,
+        nodes: &Pool<PoseNode<T>>,
+        params: &ParameterContainer,
+        animations: &AnimationContainer<T>,
+        strategy: AnimationEventCollectionStrategy,
+    ) -> Vec<(Handle<Animation<T>>, AnimationEvent)> {
+        // Events of the reference pose are ignored, it is usually a static pose.
+        nodes
+            .try_borrow(self.pose_source)
+            .map(|source| source.collect_animation_events(nodes, params, animations, strategy))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        container::{TrackDataContainer, TrackValueKind},
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            math::curve::{Curve, CurveKey, CurveKeyKind},
+            pool::{ErasedHandle, Handle},
+        },
+        machine::{
+            node::difference::PoseDifference, LayerBlendMode, Machine, MachineLayer, PlayAnimation,
+            PoseNode, State,
+        },
+        track::{Track, TrackBinding},
+        value::{TrackValue, ValueBinding},
+        Animation, AnimationContainer,
+    };
+
+    fn constant_track(binding: ValueBinding, value: Vector3<f32>) -> Track {
+        let mut container = TrackDataContainer::new(TrackValueKind::Vector3);
+        for (curve, component) in container.curves_mut().iter_mut().zip(value.iter()) {
+            *curve = Curve::from(vec![CurveKey::new(0.0, *component, CurveKeyKind::Constant)]);
+        }
+        if binding == ValueBinding::Rotation {
+            container.set_value_kind(TrackValueKind::UnitQuaternion);
+        }
+        Track::new(container, binding)
+    }
+
+    fn make_animation(
+        node: ErasedHandle,
+        position: Vector3<f32>,
+        rotation: Vector3<f32>,
+    ) -> Animation<ErasedHandle> {
+        let mut animation = Animation::default();
+        animation.add_track_with_binding(
+            TrackBinding::new(node),
+            constant_track(ValueBinding::Position, position),
+        );
+        animation.add_track_with_binding(
+            TrackBinding::new(node),
+            constant_track(ValueBinding::Rotation, rotation),
+        );
+        animation
+    }
+
+    fn add_play_state(
+        layer: &mut MachineLayer<ErasedHandle>,
+        animation: Handle<Animation<ErasedHandle>>,
+    ) -> Handle<PoseNode<ErasedHandle>> {
+        layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(animation)))
+    }
+
+    #[test]
+    fn test_additive_layer() {
+        let node = ErasedHandle::new(1, 1);
+
+        let mut animations = AnimationContainer::new();
+        let base = animations.add(make_animation(
+            node,
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 0.2),
+        ));
+        let aim = animations.add(make_animation(
+            node,
+            Vector3::new(0.0, 2.0, 1.0),
+            Vector3::new(0.4, 0.0, 0.0),
+        ));
+        let reference = animations.add(make_animation(
+            node,
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::zeros(),
+        ));
+
+        let mut machine = Machine::new();
+
+        let base_layer = &mut machine.layers_mut()[0];
+        let play_base = add_play_state(base_layer, base);
+        base_layer.add_state(State::new("Base", play_base));
+
+        let mut additive_layer = MachineLayer::new();
+        additive_layer.set_blend_mode(LayerBlendMode::Additive);
+        additive_layer.set_weight(0.5);
+        let play_aim = add_play_state(&mut additive_layer, aim);
+        let play_reference = add_play_state(&mut additive_layer, reference);
+        let difference = additive_layer.add_node(PoseNode::PoseDifference(PoseDifference::new(
+            play_aim,
+            play_reference,
+        )));
+        additive_layer.add_state(State::new("Aim", difference));
+        machine.add_layer(additive_layer);
+
+        let pose = machine.evaluate_pose(&mut animations, 0.0);
+        let values = &pose.poses()[&node].values.values;
+        assert_eq!(values.len(), 2);
+        for value in values {
+            match (&value.binding, &value.value) {
+                (ValueBinding::Position, TrackValue::Vector3(position)) => {
+                    assert!((position - Vector3::new(1.0, 1.0, 0.0)).norm() < 1.0e-5);
+                }
+                (ValueBinding::Rotation, TrackValue::UnitQuaternion(rotation)) => {
+                    let expected = UnitQuaternion::from_euler_angles(0.0, 0.0, 0.2)
+                        * UnitQuaternion::from_euler_angles(0.2, 0.0, 0.0);
+                    assert!(rotation.angle_to(&expected) < 1.0e-3);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+}
@@ -29,7 +29,11 @@ use crate::{
         visitor::prelude::*,
     },
     machine::{
-        node::{blend::BlendAnimations, blendspace::BlendSpace, play::PlayAnimation},
+        node::{
+            blend::BlendAnimations, blendspace::BlendSpace, difference::PoseDifference,
+            play::PlayAnimation,
+        },
+        sync::{synchronize_animations, BlendSyncMode},
         BlendAnimationsByIndex, BlendPose, IndexedBlendInput, ParameterContainer, State,
     },
     Animation, AnimationContainer, AnimationEvent, AnimationPose, EntityId,
@@ -42,6 +46,7 @@ use std::{
 
 pub mod blend;
 pub mod blendspace;
+pub mod difference;
 pub mod play;
 
 /// A set of common data fields that is used in every node.
@@ -69,6 +74,9 @@ pub enum PoseNode<T: EntityId> {
 
     /// See doc for [`BlendSpace`]
     BlendSpace(BlendSpace<T>),
+
+    /// See docs for [`PoseDifference`].
+    PoseDifference(PoseDifference<T>),
 }
 
 impl<T: EntityId> Default for PoseNode<T> {
@@ -97,6 +105,14 @@ impl<T: EntityId> PoseNode<T> {
         Self::BlendAnimationsByIndex(BlendAnimationsByIndex::new(index_parameter, inputs))
     }
 
+    /// Creates new node that computes a difference between the input pose and the reference pose.
+    pub fn make_pose_difference(
+        pose_source: Handle<PoseNode<T>>,
+        reference_source: Handle<PoseNode<T>>,
+    ) -> Self {
+        Self::PoseDifference(PoseDifference::new(pose_source, reference_source))
+    }
+
     /// Returns a set of handles to children pose nodes.
     pub fn children(&self) -> Vec<Handle<PoseNode<T>>> {
         match self {
@@ -107,6 +123,7 @@ impl<T: EntityId> PoseNode<T> {
             Self::BlendAnimations(blend_animations) => blend_animations.children(),
             Self::BlendAnimationsByIndex(blend_by_index) => blend_by_index.children(),
             Self::BlendSpace(blend_space) => blend_space.children(),
+            Self::PoseDifference(pose_difference) => pose_difference.children(),
         }
     }
 
@@ -141,7 +158,49 @@ impl<T: EntityId> PoseNode<T> {
                     }
                 }
             }
+            PoseNode::PoseDifference(pose_difference) => {
+                for child in pose_difference.children() {
+                    if let Some(source) = nodes.try_borrow(child) {
+                        source.collect_animations(nodes, animations)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Synchronizes the animations blended by this node, see [`BlendSyncMode`] docs for more info. Does nothing
+    /// for the nodes that do not support synchronization.
+    pub fn synchronize_animations(
+        &self,
+        nodes: &Pool<PoseNode<T>>,
+        params: &ParameterContainer,
+        animations: &mut AnimationContainer<T>,
+    ) {
+        let (mode, inputs) = match self {
+            PoseNode::BlendAnimations(blend_animations) => (
+                blend_animations.sync_mode,
+                blend_animations.sync_inputs(params),
+            ),
+            PoseNode::BlendSpace(blend_space) => {
+                (blend_space.sync_mode(), blend_space.sync_inputs(params))
+            }
+            _ => return,
+        };
+
+        if mode == BlendSyncMode::None {
+            return;
         }
+
+        let mut group = Vec::new();
+        for (input, weight) in inputs {
+            if let Some(source) = nodes.try_borrow(input) {
+                let mut input_animations = FxHashSet::default();
+                source.collect_animations(nodes, &mut input_animations);
+                group.extend(input_animations.into_iter().map(|a| (a, weight)));
+            }
+        }
+
+        synchronize_animations(animations, &group, mode);
     }
 }
 
@@ -152,6 +211,7 @@ macro_rules! static_dispatch {
             PoseNode::BlendAnimations(v) => v.$func($($args),*),
             PoseNode::BlendAnimationsByIndex(v) => v.$func($($args),*),
             PoseNode::BlendSpace(v) => v.$func($($args),*),
+            PoseNode::PoseDifference(v) => v.$func($($args),*),
         }
     };
 }
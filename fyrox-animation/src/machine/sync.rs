@@ -0,0 +1,273 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Phase synchronization of blended animations. See [`BlendSyncMode`] docs for more info.
+
+use crate::{
+    core::{math::wrapf, pool::Handle, reflect::prelude::*, uuid_provider, visitor::prelude::*},
+    Animation, AnimationContainer, EntityId,
+};
+use std::ops::Range;
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how the animations blended by a node (see [`crate::machine::BlendAnimations`] and
+/// [`crate::machine::node::blendspace::BlendSpace`]) are synchronized with each other. Synchronization is
+/// required to blend cyclic animations of different length, such as walk and run cycles, otherwise the
+/// feet of the blended animations will be out of phase. The animation with the highest weight is the leader,
+/// the playback position of every other animation is adjusted to match the phase of the leader.
+#[derive(
+    Default, Debug, Visit, Reflect, Clone, Copy, PartialEq, Eq, AsRefStr, EnumString, VariantNames,
+)]
+pub enum BlendSyncMode {
+    /// Animations are played independently.
+    #[default]
+    None,
+
+    /// The playback position of every animation is set to the same normalized time (in `[0; 1]` range)
+    /// as the position of the leader.
+    NormalizedTime,
+
+    /// The playback position of every animation is set using sync markers (see
+    /// [`crate::AnimationSignal::sync_marker`]). The leader's position is expressed as a fraction of the way
+    /// between its previous and next markers, then the same fraction is applied between the markers with
+    /// the same names in every other animation. For example, if a walk cycle and a run cycle both have
+    /// `LeftFoot` and `RightFoot` markers, their foot plants will always happen at the same time. Falls
+    /// back to [`Self::NormalizedTime`] for animations without matching markers.
+    SyncMarkers,
+}
+
+uuid_provider!(BlendSyncMode = "e3b1d2c4-6f5a-4b8e-9d7c-0a1f2e3d4b5c");
+
+fn sync_markers<T: EntityId>(animation: &Animation<T>) -> Vec<(&str, f32)> {
+    let slice = animation.time_slice();
+    let mut markers = animation
+        .signals()
+        .iter()
+        .filter(|s| s.enabled && s.sync_marker && s.time >= slice.start && s.time <= slice.end)
+        .map(|s| (s.name.as_str(), s.time))
+        .collect::<Vec<_>>();
+    markers.sort_by(|a, b| a.1.total_cmp(&b.1));
+    markers
+}
+
+fn slice_length(slice: &Range<f32>) -> f32 {
+    slice.end - slice.start
+}
+
+// Returns the time between a marker and the next one, markers are treated as cyclic.
+fn marker_span(markers: &[(&str, f32)], index: usize, length: f32) -> f32 {
+    match markers.get(index + 1) {
+        Some(next) => next.1 - markers[index].1,
+        None => markers[0].1 + length - markers[index].1,
+    }
+}
+
+// Returns the index of the previous marker and the fraction of the way to the next marker.
+fn marker_phase(markers: &[(&str, f32)], time: f32, slice: &Range<f32>) -> Option<(usize, f32)> {
+    let length = slice_length(slice);
+    if markers.is_empty() || length <= 0.0 {
+        return None;
+    }
+    let (prev, start) = match markers.iter().rposition(|(_, t)| *t <= time) {
+        Some(index) => (index, markers[index].1),
+        // The position is before the first marker, so the previous one is the last marker of the
+        // previous cycle.
+        None => (markers.len() - 1, markers[markers.len() - 1].1 - length),
+    };
+    let span = marker_span(markers, prev, length);
+    let fraction = if span > 0.0 {
+        ((time - start) / span).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    Some((prev, fraction))
+}
+
+fn circular_distance(a: f32, b: f32, length: f32) -> f32 {
+    let distance = (a - b).abs();
+    distance.min(length - distance)
+}
+
+// Finds a time position of the animation that has the given phase relative to the markers with the given name.
+// If there are multiple markers with the same name, the closest position to the current one is used.
+fn marker_time<T: EntityId>(animation: &Animation<T>, name: &str, fraction: f32) -> Option<f32> {
+    let slice = animation.time_slice();
+    let length = slice_length(&slice);
+    let current = animation.time_position();
+    let markers = sync_markers(animation);
+    markers
+        .iter()
+        .enumerate()
+        .filter(|(_, (marker_name, _))| *marker_name == name)
+        .map(|(index, (_, time))| {
+            wrapf(
+                time + fraction * marker_span(&markers, index, length),
+                slice.start,
+                slice.end,
+            )
+        })
+        .min_by(|a, b| {
+            circular_distance(*a, current, length)
+                .total_cmp(&circular_distance(*b, current, length))
+        })
+}
+
+/// Synchronizes the playback position of every animation in the group with the position of the animation with
+/// the highest weight. The group is a set of pairs `(animation, weight)`. See [`BlendSyncMode`] docs for more info.
+pub fn synchronize_animations<T: EntityId>(
+    animations: &mut AnimationContainer<T>,
+    group: &[(Handle<Animation<T>>, f32)],
+    mode: BlendSyncMode,
+) {
+    if mode == BlendSyncMode::None || group.len() < 2 {
+        return;
+    }
+
+    let Some(&(leader, leader_weight)) = group.iter().max_by(|a, b| a.1.total_cmp(&b.1)) else {
+        return;
+    };
+
+    if leader_weight <= 0.0 {
+        return;
+    }
+
+    let Some(leader_ref) = animations.try_get(leader) else {
+        return;
+    };
+
+    let leader_slice = leader_ref.time_slice();
+    let leader_length = slice_length(&leader_slice);
+    let normalized_time = if leader_length > 0.0 {
+        (leader_ref.time_position() - leader_slice.start) / leader_length
+    } else {
+        0.0
+    };
+
+    let leader_phase = if mode == BlendSyncMode::SyncMarkers {
+        let markers = sync_markers(leader_ref);
+        marker_phase(&markers, leader_ref.time_position(), &leader_slice)
+            .map(|(index, fraction)| (markers[index].0.to_owned(), fraction))
+    } else {
+        None
+    };
+
+    for (handle, _) in group {
+        if *handle == leader {
+            continue;
+        }
+
+        let Some(follower) = animations.try_get_mut(*handle) else {
+            continue;
+        };
+
+        let time = leader_phase
+            .as_ref()
+            .and_then(|(name, fraction)| marker_time(follower, name, *fraction))
+            .unwrap_or_else(|| {
+                let slice = follower.time_slice();
+                slice.start + normalized_time * slice_length(&slice)
+            });
+
+        follower.set_time_position(time);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::pool::Handle,
+        machine::sync::{synchronize_animations, BlendSyncMode},
+        Animation, AnimationContainer, AnimationSignal,
+    };
+    use fyrox_core::uuid::Uuid;
+
+    fn make_cycle(length: f32, markers: &[(&str, f32)]) -> Animation<Handle<()>> {
+        let mut animation = Animation::default();
+        animation.set_time_slice(0.0..length);
+        animation.set_loop(true);
+        for (name, time) in markers {
+            animation.add_signal(
+                AnimationSignal::new(Uuid::new_v4(), name, *time).with_sync_marker(true),
+            );
+        }
+        animation
+    }
+
+    #[test]
+    fn test_synchronization() {
+        let mut animations = AnimationContainer::new();
+        // Walk cycle with foot plants at 0.1 and 0.6.
+        let walk = animations.add(make_cycle(1.0, &[("Left", 0.1), ("Right", 0.6)]));
+        // Run cycle with foot plants at 0.05 and 0.45 - not at the same normalized positions.
+        let run = animations.add(make_cycle(0.6, &[("Left", 0.05), ("Right", 0.45)]));
+
+        // Leader (walk) is half way between the left and the right foot plants.
+        animations[walk].set_time_position(0.35);
+
+        synchronize_animations(
+            &mut animations,
+            &[(walk, 0.4), (run, 0.6)],
+            BlendSyncMode::None,
+        );
+        assert_eq!(animations[run].time_position(), 0.0);
+
+        // Run is the leader here, so walk must be moved.
+        synchronize_animations(
+            &mut animations,
+            &[(walk, 0.4), (run, 0.6)],
+            BlendSyncMode::NormalizedTime,
+        );
+        assert_eq!(animations[walk].time_position(), 0.0);
+
+        animations[walk].set_time_position(0.35);
+        synchronize_animations(
+            &mut animations,
+            &[(walk, 0.6), (run, 0.4)],
+            BlendSyncMode::NormalizedTime,
+        );
+        assert!((animations[run].time_position() - 0.35 * 0.6).abs() < 1.0e-5);
+
+        synchronize_animations(
+            &mut animations,
+            &[(walk, 0.6), (run, 0.4)],
+            BlendSyncMode::SyncMarkers,
+        );
+        assert!((animations[run].time_position() - 0.25).abs() < 1.0e-5);
+
+        // Wrap around - the leader is between the right foot plant and the left foot plant of the next cycle.
+        animations[walk].set_time_position(0.85);
+        synchronize_animations(
+            &mut animations,
+            &[(walk, 0.6), (run, 0.4)],
+            BlendSyncMode::SyncMarkers,
+        );
+        // Half way from 0.45 to 0.05 + 0.6 = 0.65 is 0.55.
+        assert!((animations[run].time_position() - 0.55).abs() < 1.0e-5);
+
+        // An animation without markers falls back to normalized time.
+        let idle = animations.add(make_cycle(2.0, &[]));
+        synchronize_animations(
+            &mut animations,
+            &[(walk, 0.6), (idle, 0.4)],
+            BlendSyncMode::SyncMarkers,
+        );
+        assert!((animations[idle].time_position() - 1.7).abs() < 1.0e-5);
+    }
+}
@@ -41,6 +41,12 @@ impl<T: EntityId> NodePose<T> {
     pub fn blend_with(&mut self, other: &NodePose<T>, weight: f32) {
         self.values.blend_with(&other.values, weight)
     }
+
+    /// Adds a difference pose (see [`AnimationPose::difference_into`]) scaled by the given weight to the current
+    /// pose. See [`super::value::BoundValue::add_weighted`] docs for more info.
+    pub fn add_weighted(&mut self, delta: &NodePose<T>, weight: f32) {
+        self.values.add_weighted(&delta.values, weight)
+    }
 }
 
 /// Animations pose is a set of node poses. See [`NodePose`] docs for more info.
@@ -87,6 +93,33 @@ impl<T: EntityId> AnimationPose<T> {
             .blend_with(&other.root_motion.clone().unwrap_or_default(), weight);
     }
 
+    /// Adds an additive (difference) pose scaled by the given weight to the current animation pose. Node poses that
+    /// are missing in the current pose are ignored, because a difference can only be applied on top of some base
+    /// pose. Root motion of the current pose is left intact.
+    pub fn add_weighted(&mut self, additive: &AnimationPose<T>, weight: f32) {
+        for (handle, delta) in additive.poses.iter() {
+            if let Some(current_pose) = self.poses.get_mut(handle) {
+                current_pose.add_weighted(delta, weight);
+            }
+        }
+    }
+
+    /// Computes a difference between the current pose and the `reference` pose and writes it to `dest`. The result
+    /// is an additive pose, that can be added on top of any other pose using [`Self::add_weighted`]. Node poses
+    /// that are missing in the reference pose are ignored. The difference pose has no root motion.
+    pub fn difference_into(&self, reference: &AnimationPose<T>, dest: &mut AnimationPose<T>) {
+        dest.reset();
+        for (handle, local_pose) in self.poses.iter() {
+            if let Some(reference_pose) = reference.poses.get(handle) {
+                dest.add_node_pose(NodePose {
+                    node: *handle,
+                    values: local_pose.values.difference(&reference_pose.values),
+                });
+            }
+        }
+        dest.root_motion = None;
+    }
+
     fn add_node_pose(&mut self, local_pose: NodePose<T>) {
         self.poses.insert(local_pose.node, local_pose);
     }
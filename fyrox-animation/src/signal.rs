@@ -21,7 +21,8 @@
 //! Signal is a named marker on specific time position on the animation timeline. See [`AnimationSignal`] docs for more info.
 
 use crate::core::{reflect::prelude::*, uuid::Uuid, visitor::prelude::*};
-use fyrox_core::NameProvider;
+use fyrox_core::{uuid_provider, NameProvider};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 /// An event happened in an animation.
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -31,8 +32,106 @@ pub struct AnimationEvent {
 
     /// Name of the signal emitted the event.
     pub name: String,
+
+    /// A copy of the payload of the signal emitted the event. See [`SignalPayload`] docs for more info.
+    pub payload: SignalPayload,
+}
+
+/// Typed data attached to an animation signal. It is copied to every event produced by the signal, so
+/// game systems could react to the event without parsing signal names.
+///
+/// Numeric fields are compared bitwise, so the payload (and [`AnimationEvent`]) implements [`Eq`].
+#[derive(Clone, Debug, Default, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum SignalPayload {
+    /// No payload.
+    #[default]
+    None,
+
+    /// A foot touched the ground. Could be used to play a footstep sound or to spawn a dust particle
+    /// effect.
+    Footstep {
+        /// Type of the surface (for example: `Grass`, `Gravel`, etc.). Leave it empty if the surface type
+        /// should be detected at runtime.
+        surface: String,
+        /// Name of the bone (foot) that touched the ground.
+        bone: String,
+        /// Strength of the step in `[0; 1]` range.
+        #[reflect(min_value = 0.0, max_value = 1.0)]
+        strength: f32,
+    },
+
+    /// A visual or sound effect that should be spawned at the given bone.
+    Effect {
+        /// Name of the effect.
+        name: String,
+        /// Name of the bone at which the effect should be spawned.
+        bone: String,
+        /// Strength (intensity) of the effect.
+        #[reflect(min_value = 0.0)]
+        strength: f32,
+    },
+
+    /// User-defined data.
+    Custom {
+        /// A tag that identifies the data.
+        tag: String,
+        /// A numeric value.
+        value: f32,
+    },
+}
+
+uuid_provider!(SignalPayload = "c6f3e1a2-7b4d-4f8e-a5c9-2d1b0e8f6a37");
+
+impl PartialEq for SignalPayload {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::None, Self::None) => true,
+            (
+                Self::Footstep {
+                    surface,
+                    bone,
+                    strength,
+                },
+                Self::Footstep {
+                    surface: other_surface,
+                    bone: other_bone,
+                    strength: other_strength,
+                },
+            ) => {
+                surface == other_surface
+                    && bone == other_bone
+                    && strength.to_bits() == other_strength.to_bits()
+            }
+            (
+                Self::Effect {
+                    name,
+                    bone,
+                    strength,
+                },
+                Self::Effect {
+                    name: other_name,
+                    bone: other_bone,
+                    strength: other_strength,
+                },
+            ) => {
+                name == other_name
+                    && bone == other_bone
+                    && strength.to_bits() == other_strength.to_bits()
+            }
+            (
+                Self::Custom { tag, value },
+                Self::Custom {
+                    tag: other_tag,
+                    value: other_value,
+                },
+            ) => tag == other_tag && value.to_bits() == other_value.to_bits(),
+            _ => false,
+        }
+    }
 }
 
+impl Eq for SignalPayload {}
+
 /// Signal is a named marker on specific time position on the animation timeline. Signal will emit an event if the animation playback
 /// time passes signal's position from left-to-right (or vice versa depending on playback direction). Signals are usually used to
 /// attach some specific actions to a position in time. For example, you can have a walking animation and you want to emit sounds
@@ -52,6 +151,17 @@ pub struct AnimationSignal {
 
     /// The flag defines whether the signal is enabled or not. Disabled signals won't produce any events.
     pub enabled: bool,
+
+    /// Typed data attached to the signal, it will be copied to every event produced by the signal. See
+    /// [`SignalPayload`] docs for more info.
+    #[visit(optional)]
+    pub payload: SignalPayload,
+
+    /// The flag defines whether the signal is a sync marker or not. Sync markers are used by blending nodes to
+    /// align phases of the blended animations (for example, foot plants of walk and run cycles). Markers are
+    /// matched by their names. See [`crate::machine::BlendSyncMode`] docs for more info.
+    #[visit(optional)]
+    pub sync_marker: bool,
 }
 
 impl NameProvider for AnimationSignal {
@@ -68,8 +178,22 @@ impl AnimationSignal {
             name: name.to_owned(),
             time,
             enabled: true,
+            payload: Default::default(),
+            sync_marker: false,
         }
     }
+
+    /// Sets new payload of the signal. See [`SignalPayload`] docs for more info.
+    pub fn with_payload(mut self, payload: SignalPayload) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    /// Marks the signal as a sync marker. See [`Self::sync_marker`] docs for more info.
+    pub fn with_sync_marker(mut self, sync_marker: bool) -> Self {
+        self.sync_marker = sync_marker;
+        self
+    }
 }
 
 impl Default for AnimationSignal {
@@ -79,6 +203,8 @@ impl Default for AnimationSignal {
             name: Default::default(),
             time: 0.0,
             enabled: true,
+            payload: Default::default(),
+            sync_marker: false,
         }
     }
 }
@@ -233,6 +233,37 @@ impl TrackValue {
         }
     }
 
+    /// Computes a difference between the current value and the `reference` value, such that adding the difference to
+    /// the reference value (see [`Self::add_weighted`]) gives the current value. Rotations are subtracted in local
+    /// space of the reference rotation. Returns `None` if the types of the values are different.
+    pub fn difference(&self, reference: &Self) -> Option<Self> {
+        match (self, reference) {
+            (Self::Real(a), Self::Real(b)) => Some(Self::Real(*a - *b)),
+            (Self::Vector2(a), Self::Vector2(b)) => Some(Self::Vector2(a - b)),
+            (Self::Vector3(a), Self::Vector3(b)) => Some(Self::Vector3(a - b)),
+            (Self::Vector4(a), Self::Vector4(b)) => Some(Self::Vector4(a - b)),
+            (Self::UnitQuaternion(a), Self::UnitQuaternion(b)) => {
+                Some(Self::UnitQuaternion(b.inverse() * a))
+            }
+            _ => None,
+        }
+    }
+
+    /// Adds a difference (see [`Self::difference`]) scaled by the given weight to the current value. Adding is possible
+    /// only if the types are the same.
+    pub fn add_weighted(&mut self, delta: &Self, weight: f32) {
+        match (self, delta) {
+            (Self::Real(a), Self::Real(b)) => *a += *b * weight,
+            (Self::Vector2(a), Self::Vector2(b)) => *a += b.scale(weight),
+            (Self::Vector3(a), Self::Vector3(b)) => *a += b.scale(weight),
+            (Self::Vector4(a), Self::Vector4(b)) => *a += b.scale(weight),
+            (Self::UnitQuaternion(a), Self::UnitQuaternion(b)) => {
+                *a *= nlerp(UnitQuaternion::identity(), b, weight)
+            }
+            _ => (),
+        }
+    }
+
     /// Tries to perform a numeric type casting of the current value to some other and returns a boxed value, that can
     /// be used to set the value using reflection.
     pub fn apply_to_any(&self, any: &mut dyn Any, value_type: ValueType) {
@@ -401,6 +432,39 @@ impl BoundValue {
         self.value.blend_with(&other.value, weight);
     }
 
+    /// Computes a difference between the current value and the `reference` value. Unlike [`TrackValue::difference`],
+    /// scale is treated multiplicatively, so the difference of scales is a ratio of the scales. Returns `None` if the
+    /// values are bound to different properties or have different types.
+    pub fn difference(&self, reference: &Self) -> Option<Self> {
+        if self.binding != reference.binding {
+            return None;
+        }
+        let value = match (&self.binding, &self.value, &reference.value) {
+            (ValueBinding::Scale, TrackValue::Vector3(a), TrackValue::Vector3(b)) => {
+                TrackValue::Vector3(a.zip_map(b, |a, b| if b == 0.0 { 1.0 } else { a / b }))
+            }
+            _ => self.value.difference(&reference.value)?,
+        };
+        Some(Self {
+            binding: self.binding.clone(),
+            value,
+        })
+    }
+
+    /// Adds a difference (see [`Self::difference`]) scaled by the given weight to the current value. The value is left
+    /// unchanged if the difference is bound to other property or has different type.
+    pub fn add_weighted(&mut self, delta: &Self, weight: f32) {
+        if self.binding != delta.binding {
+            return;
+        }
+        match (&self.binding, &mut self.value, &delta.value) {
+            (ValueBinding::Scale, TrackValue::Vector3(a), TrackValue::Vector3(b)) => {
+                a.component_mul_assign(&Vector3::repeat(1.0).lerp(b, weight))
+            }
+            (_, value, delta) => value.add_weighted(delta, weight),
+        }
+    }
+
     /// Sets a property of the given object.
     pub fn apply_to_object(
         &self,
@@ -440,6 +504,33 @@ impl BoundValueCollection {
             }
         }
     }
+    /// Computes differences between each value of the current collection and a respective (by binding) value in the
+    /// reference collection. Values that are missing in the reference collection are ignored.
+    pub fn difference(&self, reference: &Self) -> Self {
+        Self {
+            values: self
+                .values
+                .iter()
+                .filter_map(|value| {
+                    reference
+                        .values
+                        .iter()
+                        .find(|v| v.binding == value.binding)
+                        .and_then(|reference_value| value.difference(reference_value))
+                })
+                .collect(),
+        }
+    }
+
+    /// Adds each difference from the other collection scaled by the given weight to a respective (by binding) value of
+    /// the current collection. See [`BoundValue::add_weighted`] docs for more info.
+    pub fn add_weighted(&mut self, deltas: &Self, weight: f32) {
+        for value in self.values.iter_mut() {
+            if let Some(delta) = deltas.values.iter().find(|v| v.binding == value.binding) {
+                value.add_weighted(delta, weight);
+            }
+        }
+    }
 }
 
 /// Interpolates from `a` to `b` using nlerp, including an additional check to ensure
@@ -458,8 +549,11 @@ pub fn negate_unit_quaternion(a: &UnitQuaternion<f32>) -> UnitQuaternion<f32> {
 
 #[cfg(test)]
 mod test {
-    use crate::value::{BoundValue, TrackValue, ValueBinding, ValueType};
-    use fyrox_core::reflect::prelude::*;
+    use crate::value::{BoundValue, BoundValueCollection, TrackValue, ValueBinding, ValueType};
+    use fyrox_core::{
+        algebra::{UnitQuaternion, Vector3},
+        reflect::prelude::*,
+    };
 
     #[derive(Default, Reflect, Debug, PartialEq)]
     struct OtherStruct {
@@ -510,4 +604,82 @@ mod test {
         field_value.apply_to_object(&mut object, "other_struct.field", ValueType::U32);
         assert_eq!(object.other_struct.field, 123);
     }
+
+    #[test]
+    fn test_difference_and_add() {
+        let bound = |binding: ValueBinding, value: TrackValue| BoundValue { binding, value };
+
+        let reference = BoundValueCollection {
+            values: vec![
+                bound(
+                    ValueBinding::Position,
+                    TrackValue::Vector3(Vector3::new(1.0, 2.0, 3.0)),
+                ),
+                bound(
+                    ValueBinding::Rotation,
+                    TrackValue::UnitQuaternion(UnitQuaternion::from_euler_angles(0.3, 0.0, 0.0)),
+                ),
+                bound(
+                    ValueBinding::Scale,
+                    TrackValue::Vector3(Vector3::new(2.0, 2.0, 2.0)),
+                ),
+            ],
+        };
+        let pose = BoundValueCollection {
+            values: vec![
+                bound(
+                    ValueBinding::Position,
+                    TrackValue::Vector3(Vector3::new(2.0, 2.0, 3.0)),
+                ),
+                bound(
+                    ValueBinding::Rotation,
+                    TrackValue::UnitQuaternion(UnitQuaternion::from_euler_angles(0.3, 0.5, 0.0)),
+                ),
+                bound(
+                    ValueBinding::Scale,
+                    TrackValue::Vector3(Vector3::new(4.0, 2.0, 1.0)),
+                ),
+            ],
+        };
+
+        let difference = pose.difference(&reference);
+        assert_eq!(difference.values.len(), 3);
+
+        // Adding the full difference to the reference gives the original pose.
+        let mut restored = reference.clone();
+        restored.add_weighted(&difference, 1.0);
+        for (restored, original) in restored.values.iter().zip(pose.values.iter()) {
+            match (&restored.value, &original.value) {
+                (TrackValue::Vector3(a), TrackValue::Vector3(b)) => {
+                    assert!((a - b).norm() < 1.0e-5)
+                }
+                (TrackValue::UnitQuaternion(a), TrackValue::UnitQuaternion(b)) => {
+                    assert!(a.angle_to(b) < 1.0e-5)
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        // Adding the difference with zero weight keeps the base pose intact.
+        let mut base = pose.clone();
+        base.add_weighted(&difference, 0.0);
+        assert_eq!(base, pose);
+
+        // Half of the difference.
+        let mut half = reference.clone();
+        half.add_weighted(&difference, 0.5);
+        assert_eq!(
+            half.values[0].value,
+            TrackValue::Vector3(Vector3::new(1.5, 2.0, 3.0))
+        );
+        assert_eq!(
+            half.values[2].value,
+            TrackValue::Vector3(Vector3::new(3.0, 2.0, 1.5))
+        );
+
+        // Mismatched bindings are ignored.
+        let mut position = pose.values[0].clone();
+        position.add_weighted(&difference.values[2], 1.0);
+        assert_eq!(position, pose.values[0]);
+    }
 }
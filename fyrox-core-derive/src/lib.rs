@@ -131,6 +131,17 @@ use syn::{parse_macro_input, DeriveInput};
 /// }
 /// # fn main() {}
 /// ```
+///
+/// ## Attributes
+///
+/// - `#[visit(optional)]` on a type or a field - ignore missing fields on read.
+/// - `#[visit(skip)]` on a field - do not read nor write the field.
+/// - `#[visit(rename = "Name")]` on a field - use the given name instead of the field name.
+/// - `#[visit(rename_from = "OldName")]` on a field - read the field from the old name if there's
+///   no data with the current name. Could be repeated to list multiple old names.
+/// - `#[visit(version = N)]` on a type - store the version of the type along with its data and
+///   apply the migrations registered with `fyrox_core::visitor::migration::register_migration`
+///   when reading data of an older version.
 #[proc_macro_derive(Visit, attributes(visit))]
 pub fn visit(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
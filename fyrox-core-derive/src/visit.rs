@@ -51,11 +51,14 @@ fn impl_visit_struct(
             field_args.style,
         );
 
+        let version_visit = create_version_visit(ty_args);
+
         quote! {
             let mut region = match visitor.enter_region(name) {
                 Ok(x) => x,
                 Err(err) => return Err(err),
             };
+            #version_visit
             #(#field_visits)*
             Ok(())
         }
@@ -64,6 +67,18 @@ fn impl_visit_struct(
     utils::create_impl(ty_args, field_args.iter().cloned(), visit_fn_body)
 }
 
+/// `region.visit_version(..)?;` for types with `#[visit(version = N)]`
+fn create_version_visit(ty_args: &args::TypeArgs) -> TokenStream2 {
+    match ty_args.version {
+        Some(version) => quote! {
+            if let Err(err) = region.visit_version(std::any::type_name::<Self>(), #version) {
+                return Err(err);
+            }
+        },
+        None => quote! {},
+    }
+}
+
 /// impl `Visit` for `enum`
 fn impl_visit_enum(ty_args: &args::TypeArgs, variant_args: &[args::VariantArgs]) -> TokenStream2 {
     let ty_ident = &ty_args.ident;
@@ -211,6 +226,8 @@ fn impl_visit_enum(ty_args: &args::TypeArgs, variant_args: &[args::VariantArgs])
         }
     });
 
+    let version_visit = create_version_visit(ty_args);
+
     utils::create_impl(
         ty_args,
         variant_args.iter().flat_map(|v| v.fields.iter()).cloned(),
@@ -220,6 +237,8 @@ fn impl_visit_enum(ty_args: &args::TypeArgs, variant_args: &[args::VariantArgs])
                  Err(err) => return Err(err),
              };
 
+             #version_visit
+
              let mut id = id(self);
              if let Err(err) = id.visit("Id", &mut region) {
                  return Err(err);
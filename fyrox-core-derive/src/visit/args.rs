@@ -29,6 +29,11 @@ pub struct TypeArgs {
     pub data: ast::Data<VariantArgs, FieldArgs>,
     #[darling(default)]
     pub optional: bool,
+    /// `#[visit(version = 2)]`
+    ///
+    /// Store the version of the type and apply registered migrations on read
+    #[darling(default)]
+    pub version: Option<u32>,
 }
 
 /// Parsed from struct's or enum variant's field
@@ -52,6 +57,12 @@ pub struct FieldArgs {
     #[darling(default)]
     pub rename: Option<String>,
 
+    /// `#[visit(rename_from = "..")]`
+    ///
+    /// Read from this name, if there's no data with the current name. Could be repeated
+    #[darling(multiple)]
+    pub rename_from: Vec<String>,
+
     /// `#[visit(optional)]`
     ///
     /// Ignore missing field
@@ -122,12 +122,12 @@ pub fn create_field_visits<'a>(
                 None => name,
             };
 
-            (ident, name, field.optional)
+            (ident, name, field.optional, &field.rename_from)
         })
         .collect::<Vec<_>>();
 
     let mut no_dup = FxHashSet::default();
-    for name in visit_args.iter().map(|(_, name, _)| name) {
+    for name in visit_args.iter().map(|(_, name, _, _)| name) {
         if !no_dup.insert(name) {
             panic!("duplicate visiting names detected!");
         }
@@ -137,7 +137,27 @@ pub fn create_field_visits<'a>(
 
     visit_args
         .iter()
-        .map(|(ident, name, optional)| {
+        .map(|(ident, name, optional, rename_from)| {
+            // Pick the first existing old name, if there's no data with the current name.
+            let name = if rename_from.is_empty() {
+                quote!(#name)
+            } else {
+                quote! {
+                    {
+                        let mut name = #name;
+                        if region.is_reading() && !region.has_field(name) && !region.has_region(name) {
+                            for old_name in [#(#rename_from),*] {
+                                if region.has_field(old_name) || region.has_region(old_name) {
+                                    name = old_name;
+                                    break;
+                                }
+                            }
+                        }
+                        name
+                    }
+                }
+            };
+
             if optional_override || *optional {
                 quote! {
                     #prefix #ident.visit(#name, &mut region).ok();
@@ -45,3 +45,50 @@ fn rename() {
 
     assert_eq!(data, data_default);
 }
+
+#[derive(Debug, Clone, PartialEq, Visit)]
+pub struct BeforeRenameFrom {
+    pub old_x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Visit)]
+pub struct RenameFrom {
+    #[visit(rename_from = "VeryOldX", rename_from = "OldX")]
+    pub x: f32,
+    #[visit(rename_from = "OldY")]
+    pub y: f32,
+}
+
+#[test]
+fn rename_from() {
+    let mut visitor = Visitor::new();
+    BeforeRenameFrom { old_x: 1.0, y: 2.0 }
+        .visit("Data", &mut visitor)
+        .unwrap();
+
+    let data = visitor.save_binary_to_vec().unwrap();
+    let mut visitor = Visitor::load_from_memory(&data).unwrap();
+    let mut data = RenameFrom { x: 0.0, y: 0.0 };
+    data.visit("Data", &mut visitor).unwrap();
+
+    // The current name has priority over the old ones.
+    assert_eq!(data, RenameFrom { x: 1.0, y: 2.0 });
+}
+
+#[derive(Debug, Clone, PartialEq, Visit)]
+#[visit(version = 1)]
+pub enum Versioned {
+    A(u32),
+    B { x: f32 },
+}
+
+#[test]
+fn versioned_enum() {
+    let mut data = Versioned::B { x: 1.0 };
+    let mut data_default = Versioned::A(0);
+
+    super::save_load("versioned_enum", &mut data, &mut data_default);
+
+    assert_eq!(data, data_default);
+}
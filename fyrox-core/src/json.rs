@@ -0,0 +1,709 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Minimal JSON support: a self-describing [`Value`], that could be written to and parsed from
+//! JSON text, and serialized or deserialized using serde. It is used by the serde bridge of the
+//! reflection system, the log sinks and the profiler. See [`Value`] docs for more info.
+
+use serde::{
+    de::{MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter, Write},
+    ops::Index,
+    str::FromStr,
+};
+
+/// A map of named values, keys are sorted to produce stable output.
+pub type Map = BTreeMap<String, Value>;
+
+/// A number, integers are stored separately from floating-point numbers to not lose precision.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Number {
+    /// Non-negative integer.
+    PosInt(u64),
+    /// Negative integer.
+    NegInt(i64),
+    /// Floating-point number, always finite.
+    Float(f64),
+}
+
+impl Number {
+    /// Returns the number as `i64`, if it is an integer that fits in `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Number::PosInt(n) => i64::try_from(n).ok(),
+            Number::NegInt(n) => Some(n),
+            Number::Float(_) => None,
+        }
+    }
+
+    /// Returns the number as `u64`, if it is a non-negative integer.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Number::PosInt(n) => Some(n),
+            Number::NegInt(_) | Number::Float(_) => None,
+        }
+    }
+
+    /// Returns the number as `f64`, integers could lose precision.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Number::PosInt(n) => Some(n as f64),
+            Number::NegInt(n) => Some(n as f64),
+            Number::Float(n) => Some(n),
+        }
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::PosInt(n) => write!(f, "{n}"),
+            Number::NegInt(n) => write!(f, "{n}"),
+            // Debug output always has a fractional part or an exponent, so the number is read back
+            // as a floating-point number.
+            Number::Float(n) => write!(f, "{n:?}"),
+        }
+    }
+}
+
+/// Any value of the serde data model. It is very similar to JSON values, and its text form
+/// ([`Display`] and [`FromStr`] implementations) is JSON. Alternate formatting (`{:#}`) produces
+/// pretty-printed JSON.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Value {
+    /// Absence of a value.
+    #[default]
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A number.
+    Number(Number),
+    /// A string.
+    String(String),
+    /// A sequence of values.
+    Array(Vec<Value>),
+    /// A map of named values.
+    Object(Map),
+}
+
+static NULL: Value = Value::Null;
+
+impl Value {
+    /// Returns a reference to the string, if the value is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the items, if the value is an array.
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the map, if the value is an object.
+    pub fn as_object(&self) -> Option<&Map> {
+        match self {
+            Value::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the value is `null`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+}
+
+macro_rules! impl_from_unsigned {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Value::Number(Number::PosInt(value as u64))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_unsigned!(u8, u16, u32, u64, usize);
+
+macro_rules! impl_from_signed {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    if value < 0 {
+                        Value::Number(Number::NegInt(value as i64))
+                    } else {
+                        Value::Number(Number::PosInt(value as u64))
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_signed!(i8, i16, i32, i64, isize);
+
+impl From<f64> for Value {
+    /// Non-finite numbers are converted to `null`.
+    fn from(value: f64) -> Self {
+        if value.is_finite() {
+            Value::Number(Number::Float(value))
+        } else {
+            Value::Null
+        }
+    }
+}
+
+impl From<f32> for Value {
+    /// The number is converted using its shortest decimal representation, so `0.1f32` becomes
+    /// `0.1` and not `0.10000000149011612`. Non-finite numbers are converted to `null`.
+    fn from(value: f32) -> Self {
+        // Shortest representation of a finite f32 always parses back to the same f32.
+        Value::from(value.to_string().parse::<f64>().unwrap_or(f64::NAN))
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(value: Vec<Value>) -> Self {
+        Value::Array(value)
+    }
+}
+
+impl From<Map> for Value {
+    fn from(value: Map) -> Self {
+        Value::Object(value)
+    }
+}
+
+impl<K: Into<String>, const N: usize> From<[(K, Value); N]> for Value {
+    /// Creates an object from the given pairs of names and values.
+    fn from(value: [(K, Value); N]) -> Self {
+        Value::Object(value.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+}
+
+impl Index<&str> for Value {
+    type Output = Value;
+
+    /// Returns a value of the field with the given name, or `null` if there's no such field.
+    fn index(&self, index: &str) -> &Self::Output {
+        self.as_object()
+            .and_then(|map| map.get(index))
+            .unwrap_or(&NULL)
+    }
+}
+
+impl Index<usize> for Value {
+    type Output = Value;
+
+    /// Returns an item with the given index, or `null` if there's no such item.
+    fn index(&self, index: usize) -> &Self::Output {
+        self.as_array()
+            .and_then(|array| array.get(index))
+            .unwrap_or(&NULL)
+    }
+}
+
+fn write_string(f: &mut Formatter<'_>, string: &str) -> std::fmt::Result {
+    f.write_char('"')?;
+    for c in string.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+fn write_indent(f: &mut Formatter<'_>, depth: usize) -> std::fmt::Result {
+    for _ in 0..depth {
+        f.write_str("  ")?;
+    }
+    Ok(())
+}
+
+fn write_value(
+    f: &mut Formatter<'_>,
+    value: &Value,
+    pretty: bool,
+    depth: usize,
+) -> std::fmt::Result {
+    fn write_items<T>(
+        f: &mut Formatter<'_>,
+        items: impl ExactSizeIterator<Item = T>,
+        brackets: [char; 2],
+        pretty: bool,
+        depth: usize,
+        mut write_item: impl FnMut(&mut Formatter<'_>, T) -> std::fmt::Result,
+    ) -> std::fmt::Result {
+        f.write_char(brackets[0])?;
+        let is_empty = items.len() == 0;
+        for (i, item) in items.enumerate() {
+            if i > 0 {
+                f.write_char(',')?;
+            }
+            if pretty {
+                f.write_char('\n')?;
+                write_indent(f, depth + 1)?;
+            }
+            write_item(f, item)?;
+        }
+        if pretty && !is_empty {
+            f.write_char('\n')?;
+            write_indent(f, depth)?;
+        }
+        f.write_char(brackets[1])
+    }
+
+    match value {
+        Value::Null => f.write_str("null"),
+        Value::Bool(value) => write!(f, "{value}"),
+        Value::Number(number) => write!(f, "{number}"),
+        Value::String(string) => write_string(f, string),
+        Value::Array(array) => {
+            write_items(f, array.iter(), ['[', ']'], pretty, depth, |f, item| {
+                write_value(f, item, pretty, depth + 1)
+            })
+        }
+        Value::Object(map) => write_items(
+            f,
+            map.iter(),
+            ['{', '}'],
+            pretty,
+            depth,
+            |f, (key, item)| {
+                write_string(f, key)?;
+                f.write_str(if pretty { ": " } else { ":" })?;
+                write_value(f, item, pretty, depth + 1)
+            },
+        ),
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_value(f, self, f.alternate(), 0)
+    }
+}
+
+/// An error, that may occur when parsing a [`Value`] from JSON.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValueParseError {
+    /// Byte offset of the problem in the source string.
+    pub position: usize,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl Display for ValueParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ValueParseError {}
+
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ValueParseError> {
+        Err(ValueParseError {
+            position: self.position,
+            message: message.into(),
+        })
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.source.as_bytes().get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ValueParseError> {
+        self.skip_whitespace();
+        if self.peek() == Some(byte) {
+            self.position += 1;
+            Ok(())
+        } else {
+            self.error(format!("expected '{}'", byte as char))
+        }
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: Value) -> Result<Value, ValueParseError> {
+        if self.source[self.position..].starts_with(literal) {
+            self.position += literal.len();
+            Ok(value)
+        } else {
+            self.error("unexpected token")
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, ValueParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => self.error("unexpected end of data"),
+            Some(b'n') => self.parse_literal("null", Value::Null),
+            Some(b't') => self.parse_literal("true", Value::Bool(true)),
+            Some(b'f') => self.parse_literal("false", Value::Bool(false)),
+            Some(b'"') => self.parse_string().map(Value::String),
+            Some(b'[') => self.parse_array(),
+            Some(b'{') => self.parse_object(),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(_) => self.error("unexpected token"),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Value, ValueParseError> {
+        let start = self.position;
+        let mut is_float = false;
+        while let Some(byte) = self.peek() {
+            match byte {
+                b'0'..=b'9' | b'-' | b'+' => (),
+                b'.' | b'e' | b'E' => is_float = true,
+                _ => break,
+            }
+            self.position += 1;
+        }
+        let text = &self.source[start..self.position];
+        let number = if is_float {
+            text.parse::<f64>().ok().map(Number::Float)
+        } else if text.starts_with('-') {
+            text.parse::<i64>().ok().map(Number::NegInt)
+        } else {
+            text.parse::<u64>().ok().map(Number::PosInt)
+        };
+        match number {
+            Some(number) => Ok(Value::Number(number)),
+            // Integers out of range are read as floating-point numbers.
+            None => match text.parse::<f64>() {
+                Ok(number) if number.is_finite() => Ok(Value::Number(Number::Float(number))),
+                _ => self.error(format!("{text} is not a valid number")),
+            },
+        }
+    }
+
+    fn parse_hex(&mut self) -> Result<u32, ValueParseError> {
+        let digits = self
+            .source
+            .get(self.position..self.position + 4)
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok());
+        match digits {
+            Some(code) => {
+                self.position += 4;
+                Ok(code)
+            }
+            None => self.error("invalid unicode escape"),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, ValueParseError> {
+        self.expect(b'"')?;
+        let mut string = String::new();
+        loop {
+            let Some(c) = self.source[self.position..].chars().next() else {
+                return self.error("unterminated string");
+            };
+            self.position += c.len_utf8();
+            match c {
+                '"' => return Ok(string),
+                '\\' => {
+                    let escape = self.peek();
+                    self.position += 1;
+                    match escape {
+                        Some(b'"') => string.push('"'),
+                        Some(b'\\') => string.push('\\'),
+                        Some(b'/') => string.push('/'),
+                        Some(b'b') => string.push('\u{8}'),
+                        Some(b'f') => string.push('\u{c}'),
+                        Some(b'n') => string.push('\n'),
+                        Some(b'r') => string.push('\r'),
+                        Some(b't') => string.push('\t'),
+                        Some(b'u') => {
+                            let mut code = self.parse_hex()?;
+                            // Characters outside of the basic multilingual plane are written as
+                            // surrogate pairs.
+                            if (0xD800..0xDC00).contains(&code)
+                                && self.source[self.position..].starts_with("\\u")
+                            {
+                                self.position += 2;
+                                let low = self.parse_hex()?;
+                                code =
+                                    0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00));
+                            }
+                            match char::from_u32(code) {
+                                Some(c) => string.push(c),
+                                None => return self.error("invalid unicode escape"),
+                            }
+                        }
+                        _ => return self.error("invalid escape sequence"),
+                    }
+                }
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn parse_items(
+        &mut self,
+        close: u8,
+        mut parse_item: impl FnMut(&mut Self) -> Result<(), ValueParseError>,
+    ) -> Result<(), ValueParseError> {
+        self.position += 1;
+        self.skip_whitespace();
+        if self.peek() == Some(close) {
+            self.position += 1;
+            return Ok(());
+        }
+        loop {
+            parse_item(self)?;
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(byte) if byte == close => {
+                    self.position += 1;
+                    return Ok(());
+                }
+                _ => return self.error(format!("expected ',' or '{}'", close as char)),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Value, ValueParseError> {
+        let mut array = Vec::new();
+        self.parse_items(b']', |parser| {
+            array.push(parser.parse_value()?);
+            Ok(())
+        })?;
+        Ok(Value::Array(array))
+    }
+
+    fn parse_object(&mut self) -> Result<Value, ValueParseError> {
+        let mut map = Map::new();
+        self.parse_items(b'}', |parser| {
+            parser.skip_whitespace();
+            let key = parser.parse_string()?;
+            parser.expect(b':')?;
+            map.insert(key, parser.parse_value()?);
+            Ok(())
+        })?;
+        Ok(Value::Object(map))
+    }
+}
+
+impl FromStr for Value {
+    type Err = ValueParseError;
+
+    /// Parses a value from JSON.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            source: s,
+            position: 0,
+        };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        if parser.position < s.len() {
+            return parser.error("trailing characters");
+        }
+        Ok(value)
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(value) => serializer.serialize_bool(*value),
+            Value::Number(Number::PosInt(value)) => serializer.serialize_u64(*value),
+            Value::Number(Number::NegInt(value)) => serializer.serialize_i64(*value),
+            Value::Number(Number::Float(value)) => serializer.serialize_f64(*value),
+            Value::String(value) => serializer.serialize_str(value),
+            Value::Array(array) => serializer.collect_seq(array),
+            Value::Object(map) => serializer.collect_map(map),
+        }
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut array = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            array.push(item);
+        }
+        Ok(Value::Array(array))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut object = Map::new();
+        while let Some((key, value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_json_text() {
+        let source =
+            r#"{ "a": [1, -2, 0.5, 1e3, true, null], "b": "q\"\n\u00e9\ud83d\ude00", "c": {} }"#;
+        let value = source.parse::<Value>().unwrap();
+        assert_eq!(value["a"][0], Value::from(1u32));
+        assert_eq!(value["a"][1], Value::from(-2));
+        assert_eq!(value["a"][2], Value::from(0.5));
+        assert_eq!(value["a"][3], Value::from(1000.0));
+        assert_eq!(value["b"], Value::from("q\"\n\u{e9}\u{1f600}"));
+        assert_eq!(value["missing"], Value::Null);
+
+        assert_eq!(
+            value.to_string(),
+            "{\"a\":[1,-2,0.5,1000.0,true,null],\"b\":\"q\\\"\\n\u{e9}\u{1f600}\",\"c\":{}}"
+        );
+        assert_eq!(value.to_string().parse::<Value>().unwrap(), value);
+        assert_eq!(format!("{value:#}").parse::<Value>().unwrap(), value);
+
+        assert!("[1, 2".parse::<Value>().is_err());
+        assert!("{\"a\" 1}".parse::<Value>().is_err());
+        assert!("1 2".parse::<Value>().is_err());
+    }
+
+    #[test]
+    fn test_f32_precision() {
+        assert_eq!(Value::from(0.1f32).to_string(), "0.1");
+        assert_eq!(Value::from(-1.5e-7f32).to_string(), "-1.5e-7");
+        assert_eq!(Value::from(f32::NAN), Value::Null);
+        let Value::Number(number) = Value::from(0.1f32) else {
+            unreachable!()
+        };
+        assert_eq!(number.as_f64().unwrap() as f32, 0.1f32);
+    }
+}
@@ -57,11 +57,13 @@ pub mod color;
 pub mod color_gradient;
 pub mod early;
 pub mod io;
+pub mod json;
 pub mod log;
 pub mod math;
 pub mod net;
 pub mod numeric_range;
 pub mod pool;
+pub mod profiler;
 pub mod quadtree;
 pub mod rectpack;
 pub mod reflect;
@@ -17,17 +17,47 @@
 // LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
-
-//! Simple logger. By default, it writes in the console only. To enable logging into a file, call
+//! Structured logger. By default, it writes in the console only. To enable logging into a file, call
 //! [`Log::set_file_name`] somewhere in your `main` function.
+//!
+//! Every message has a kind (severity), an optional category and optional key-value fields.
+//! Categories are hierarchical, their levels are separated by dots (for example `renderer.shadows`),
+//! and messages of every category could be filtered at runtime using [`Log::set_category_verbosity`]
+//! and [`Log::set_category_enabled`]. See [`category`] for the categories used by the engine.
+//!
+//! ```rust
+//! use fyrox_core::{info, log::{category, Log, MessageKind}, warn};
+//!
+//! // Plain messages.
+//! info!("Level {} loaded", 1);
+//!
+//! // Categorized messages with key-value fields.
+//! let width = 4096;
+//! warn!(category: category::RENDERER, "Texture is too large"; width = width, height = 4096);
+//!
+//! // Hide everything except errors from the physics.
+//! Log::set_category_verbosity(category::PHYSICS, MessageKind::Error);
+//! ```
+//!
+//! Besides messages, the logger could measure execution time of code blocks using spans (see
+//! [`Log::span`] and [`crate::log_span`]). Spans are passed to log sinks (see [`LogSink`]), which
+//! could be registered using [`Log::add_sink`]. See [`sink`] module for built-in sinks and [`bridge`]
+//! module for integration with `log` and `tracing` crates.
 
 use crate::parking_lot::Mutex;
-use std::fmt::{Debug, Display};
+use crate::profiler::{ProfileScope, Profiler};
+use fxhash::FxHashMap;
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    fmt::{Debug, Display, Write as _},
+};
 
 use crate::instant::Instant;
 #[cfg(not(target_arch = "wasm32"))]
 use std::io::{self, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::Sender;
 use std::sync::LazyLock;
 use std::time::Duration;
@@ -35,6 +65,9 @@ use std::time::Duration;
 #[cfg(target_arch = "wasm32")]
 use crate::wasm_bindgen::{self, prelude::*};
 
+pub mod bridge;
+pub mod sink;
+
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
@@ -44,7 +77,44 @@ extern "C" {
     fn log(s: &str);
 }
 
+/// Well-known categories of the engine messages. Games could use any other categories, nested
+/// categories could be created by joining the levels with dots, for example `game.ai`.
+pub mod category {
+    /// Messages of the renderer.
+    pub const RENDERER: &str = "renderer";
+    /// Messages of 2D and 3D physics.
+    pub const PHYSICS: &str = "physics";
+    /// Messages of the resource manager and resource loaders.
+    pub const RESOURCE: &str = "resource";
+    /// Messages of the sound engine.
+    pub const SOUND: &str = "sound";
+    /// Messages of scenes and scene graphs.
+    pub const SCENE: &str = "scene";
+    /// Messages of the user interface.
+    pub const UI: &str = "ui";
+    /// Messages of the networking.
+    pub const NET: &str = "net";
+    /// Messages of the game code.
+    pub const GAME: &str = "game";
+    /// Messages of the animation system.
+    pub const ANIMATION: &str = "animation";
+    /// Messages of scripts and plugins.
+    pub const SCRIPT: &str = "script";
+    /// Messages of the task pool.
+    pub const TASK: &str = "task";
+}
+
+/// A key-value pair attached to a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogField {
+    /// Name of the field.
+    pub key: Cow<'static, str>,
+    /// Formatted value of the field.
+    pub value: String,
+}
+
 /// A message that could be sent by the logger to all listeners.
+#[derive(Clone, Debug)]
 pub struct LogMessage {
     /// Kind of the message: information, warning or error.
     pub kind: MessageKind,
@@ -53,6 +123,71 @@ pub struct LogMessage {
     /// Time point at which the message was recorded. It is relative to the moment when the
     /// logger was initialized.
     pub time: Duration,
+    /// Category of the message. Empty string means that the message has no category.
+    pub category: Cow<'static, str>,
+    /// Key-value fields of the message.
+    pub fields: Vec<LogField>,
+}
+
+impl LogMessage {
+    /// Formats the message the same way as it is written to the console. It does not include the
+    /// time.
+    pub fn format(&self) -> String {
+        let content = self.content.trim_end_matches('\n');
+        let mut text = String::from(self.kind.as_str());
+        if !self.category.is_empty() {
+            let _ = write!(text, "[{}] ", self.category);
+        }
+        text.push_str(content);
+        for field in self.fields.iter() {
+            let _ = write!(text, " {}={}", field.key, field.value);
+        }
+        if content.len() != self.content.len() {
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// A measured execution time of a code block. See [`Log::span`] for more info.
+#[derive(Clone, Debug)]
+pub struct SpanRecord {
+    /// Name of the span.
+    pub name: Cow<'static, str>,
+    /// Category of the span.
+    pub category: Cow<'static, str>,
+    /// Time point at which the span has started. It is relative to the moment when the logger was
+    /// initialized.
+    pub start: Duration,
+    /// Execution time of the span.
+    pub duration: Duration,
+    /// Nesting level of the span on its thread. Top-level spans have zero depth.
+    pub depth: usize,
+    /// Identifier of the thread, on which the span was executed.
+    pub thread: std::thread::ThreadId,
+}
+
+/// A destination of log messages and spans. Sinks are called under the logger lock, so they must
+/// not use the logger themselves.
+pub trait LogSink: Send {
+    /// Writes a message. It is called only for the messages that passed the filters.
+    fn write(&mut self, message: &LogMessage);
+
+    /// Writes a finished span. Default implementation ignores spans.
+    fn write_span(&mut self, #[allow(unused_variables)] span: &SpanRecord) {}
+
+    /// Flushes buffered data, if any.
+    fn flush(&mut self) {}
+}
+
+/// Unique identifier of a sink. See [`Log::add_sink`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LogSinkId(u64);
+
+#[derive(Default, Clone, Copy)]
+struct CategorySettings {
+    verbosity: Option<MessageKind>,
+    enabled: Option<bool>,
 }
 
 static LOG: LazyLock<Mutex<Log>> = LazyLock::new(|| {
@@ -62,11 +197,22 @@ static LOG: LazyLock<Mutex<Log>> = LazyLock::new(|| {
         verbosity: MessageKind::Information,
         listeners: Default::default(),
         time_origin: Instant::now(),
+        categories: Default::default(),
+        sinks: Default::default(),
+        next_sink_id: 0,
     })
 });
 
+// Amount of registered sinks, it is used to skip span measurements when there's no one to receive
+// them.
+static SINK_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static SPAN_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
 /// A kind of message.
-#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Ord, Hash)]
+#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Ord, Hash, Debug)]
 #[repr(u32)]
 pub enum MessageKind {
     /// Some useful information.
@@ -85,6 +231,82 @@ impl MessageKind {
             MessageKind::Error => "[ERROR]: ",
         }
     }
+
+    /// Returns a short lowercase name of the kind.
+    pub fn name(self) -> &'static str {
+        match self {
+            MessageKind::Information => "info",
+            MessageKind::Warning => "warning",
+            MessageKind::Error => "error",
+        }
+    }
+}
+
+/// A builder of a structured message. Use [`Log::event`] or the logging macros to create one.
+#[must_use = "the event is written only when `write` is called"]
+pub struct LogEvent {
+    kind: MessageKind,
+    content: String,
+    category: Cow<'static, str>,
+    fields: Vec<LogField>,
+}
+
+impl LogEvent {
+    /// Sets category of the message.
+    pub fn with_category(mut self, category: impl Into<Cow<'static, str>>) -> Self {
+        self.category = category.into();
+        self
+    }
+
+    /// Adds a key-value field to the message.
+    pub fn with_field(mut self, key: impl Into<Cow<'static, str>>, value: impl Display) -> Self {
+        self.fields.push(LogField {
+            key: key.into(),
+            value: value.to_string(),
+        });
+        self
+    }
+
+    /// Writes the message into the log.
+    pub fn write(self) {
+        LOG.lock().write_event(self);
+    }
+
+    pub(crate) fn into_message(self, time: Duration) -> LogMessage {
+        LogMessage {
+            kind: self.kind,
+            content: self.content,
+            time,
+            category: self.category,
+            fields: self.fields,
+        }
+    }
+}
+
+/// Measures execution time of a code block, the time is measured from the creation of the span
+/// till its drop. See [`Log::span`] for more info.
+#[must_use = "the span is finished when dropped"]
+pub struct LogSpan {
+    data: Option<(Cow<'static, str>, Cow<'static, str>, Instant, usize)>,
+    profile_scope: ProfileScope,
+}
+
+impl LogSpan {
+    /// Returns `true` if the span is measured by the sinks or by the profiler, `false` - if it was
+    /// filtered out.
+    pub fn is_active(&self) -> bool {
+        self.data.is_some() || self.profile_scope.is_active()
+    }
+}
+
+impl Drop for LogSpan {
+    fn drop(&mut self) {
+        if let Some((name, category, start, depth)) = self.data.take() {
+            let end = Instant::now();
+            SPAN_DEPTH.with(|current| current.set(depth));
+            LOG.lock().write_span(name, category, start, end, depth);
+        }
+    }
 }
 
 /// See module docs.
@@ -94,6 +316,9 @@ pub struct Log {
     verbosity: MessageKind,
     listeners: Vec<Sender<LogMessage>>,
     time_origin: Instant,
+    categories: FxHashMap<String, CategorySettings>,
+    sinks: Vec<(LogSinkId, Box<dyn LogSink>)>,
+    next_sink_id: u64,
 }
 
 impl Log {
@@ -115,45 +340,89 @@ impl Log {
         }
     }
 
-    fn write_internal<S>(&mut self, kind: MessageKind, message: S)
-    where
-        S: AsRef<str>,
-    {
-        let mut msg = message.as_ref().to_owned();
-        if kind as u32 >= self.verbosity as u32 {
-            // Notify listeners about the message and remove all disconnected listeners.
-            self.listeners.retain(|listener| {
-                listener
-                    .send(LogMessage {
-                        kind,
-                        content: msg.clone(),
-                        time: Instant::now() - self.time_origin,
-                    })
-                    .is_ok()
-            });
-
-            msg.insert_str(0, kind.as_str());
-
-            #[cfg(target_arch = "wasm32")]
-            {
-                log(&msg);
+    fn is_enabled_internal(&self, kind: MessageKind, category: &str) -> bool {
+        let mut verbosity = None;
+        let mut enabled = None;
+        let mut level = category;
+        while !level.is_empty() {
+            if let Some(settings) = self.categories.get(level) {
+                verbosity = verbosity.or(settings.verbosity);
+                enabled = enabled.or(settings.enabled);
             }
+            level = level.rfind('.').map_or("", |position| &level[..position]);
+        }
+        enabled.unwrap_or(true) && kind >= verbosity.unwrap_or(self.verbosity)
+    }
 
-            #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
-            {
-                let _ = io::stdout().write_all(msg.as_bytes());
+    fn write_event(&mut self, event: LogEvent) {
+        if !self.is_enabled_internal(event.kind, &event.category) {
+            return;
+        }
 
-                if let Some(log_file) = self.file.as_mut() {
-                    let _ = log_file.write_all(msg.as_bytes());
-                    let _ = log_file.flush();
-                }
-            }
+        let message = event.into_message(Instant::now() - self.time_origin);
 
-            #[cfg(target_os = "android")]
-            {
-                let _ = io::stdout().write_all(msg.as_bytes());
+        // Notify listeners about the message and remove all disconnected listeners.
+        self.listeners
+            .retain(|listener| listener.send(message.clone()).is_ok());
+
+        for (_, sink) in self.sinks.iter_mut() {
+            sink.write(&message);
+        }
+
+        let msg = message.format();
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            log(&msg);
+        }
+
+        #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+        {
+            let _ = io::stdout().write_all(msg.as_bytes());
+
+            if let Some(log_file) = self.file.as_mut() {
+                let _ = log_file.write_all(msg.as_bytes());
+                let _ = log_file.flush();
             }
         }
+
+        #[cfg(target_os = "android")]
+        {
+            let _ = io::stdout().write_all(msg.as_bytes());
+        }
+    }
+
+    fn write_span(
+        &mut self,
+        name: Cow<'static, str>,
+        category: Cow<'static, str>,
+        start: Instant,
+        end: Instant,
+        depth: usize,
+    ) {
+        let span = SpanRecord {
+            name,
+            category,
+            start: start.saturating_duration_since(self.time_origin),
+            duration: end - start,
+            depth,
+            thread: std::thread::current().id(),
+        };
+        for (_, sink) in self.sinks.iter_mut() {
+            sink.write_span(&span);
+        }
+    }
+
+    fn write_internal<S>(&mut self, kind: MessageKind, message: S)
+    where
+        S: AsRef<str>,
+    {
+        self.write_event(LogEvent {
+            kind,
+            content: message.as_ref().to_owned(),
+            category: Cow::Borrowed(""),
+            fields: Default::default(),
+        });
     }
 
     fn writeln_internal<S>(&mut self, kind: MessageKind, message: S)
@@ -205,16 +474,145 @@ impl Log {
         Self::writeln(MessageKind::Error, msg)
     }
 
+    /// Creates a new structured message, that could be extended with a category and key-value
+    /// fields before writing. The message is written as a line.
+    ///
+    /// ```rust
+    /// use fyrox_core::log::{category, Log, MessageKind};
+    ///
+    /// Log::event(MessageKind::Warning, "Frame took too long")
+    ///     .with_category(category::RENDERER)
+    ///     .with_field("time_ms", 40)
+    ///     .write();
+    /// ```
+    pub fn event(kind: MessageKind, content: impl Into<String>) -> LogEvent {
+        let mut content = content.into();
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        LogEvent {
+            kind,
+            content,
+            category: Cow::Borrowed(""),
+            fields: Default::default(),
+        }
+    }
+
+    /// Returns `true` if a message of the given kind and category will be written. It could be
+    /// used to skip expensive formatting of messages, that will be filtered out anyway.
+    pub fn is_enabled(kind: MessageKind, category: &str) -> bool {
+        LOG.lock().is_enabled_internal(kind, category)
+    }
+
     /// Sets verbosity level.
     pub fn set_verbosity(kind: MessageKind) {
         LOG.lock().verbosity = kind;
     }
 
+    /// Sets verbosity level of the given category and all its subcategories, that don't have
+    /// their own verbosity level. It overrides the global verbosity level.
+    pub fn set_category_verbosity(category: &str, kind: MessageKind) {
+        LOG.lock()
+            .categories
+            .entry(category.to_string())
+            .or_default()
+            .verbosity = Some(kind);
+    }
+
+    /// Enables or disables every message of the given category and all its subcategories, that
+    /// are not enabled or disabled explicitly.
+    pub fn set_category_enabled(category: &str, enabled: bool) {
+        LOG.lock()
+            .categories
+            .entry(category.to_string())
+            .or_default()
+            .enabled = Some(enabled);
+    }
+
+    /// Removes verbosity level and enabled flag of the given category, so it will inherit them
+    /// from its parent category.
+    pub fn reset_category(category: &str) {
+        LOG.lock().categories.remove(category);
+    }
+
     /// Adds a listener that will receive a copy of every message passed into the log.
     pub fn add_listener(listener: Sender<LogMessage>) {
         LOG.lock().listeners.push(listener)
     }
 
+    /// Adds a new sink that will receive every message passed into the log and every finished
+    /// span. Returns an id of the sink, that could be used to remove it.
+    pub fn add_sink<S: LogSink + 'static>(sink: S) -> LogSinkId {
+        let mut log = LOG.lock();
+        let id = LogSinkId(log.next_sink_id);
+        log.next_sink_id += 1;
+        log.sinks.push((id, Box::new(sink)));
+        SINK_COUNT.store(log.sinks.len(), Ordering::Relaxed);
+        id
+    }
+
+    /// Removes a sink with the given id and returns it.
+    pub fn remove_sink(id: LogSinkId) -> Option<Box<dyn LogSink>> {
+        let mut log = LOG.lock();
+        let position = log.sinks.iter().position(|(sink_id, _)| *sink_id == id)?;
+        let (_, mut sink) = log.sinks.remove(position);
+        SINK_COUNT.store(log.sinks.len(), Ordering::Relaxed);
+        sink.flush();
+        Some(sink)
+    }
+
+    /// Flushes all the sinks.
+    pub fn flush() {
+        for (_, sink) in LOG.lock().sinks.iter_mut() {
+            sink.flush();
+        }
+    }
+
+    /// Starts a new span, that measures execution time of a code block till the returned span is
+    /// dropped. Finished spans are passed to the sinks (see [`LogSink::write_span`]) and recorded
+    /// as scopes of [`Profiler`], if it is enabled. Spans could be nested, the span is not passed
+    /// to the sinks if there's no sinks or its category is disabled. See also [`crate::log_span`]
+    /// macro.
+    ///
+    /// ```rust
+    /// use fyrox_core::log::{category, Log};
+    ///
+    /// fn render_shadows() {
+    ///     let _span = Log::span(category::RENDERER, "Shadows");
+    ///     // ...
+    /// }
+    /// ```
+    pub fn span(
+        category: impl Into<Cow<'static, str>>,
+        name: impl Into<Cow<'static, str>>,
+    ) -> LogSpan {
+        let category = category.into();
+        let name = name.into();
+
+        let profile_scope = if Profiler::is_enabled() {
+            Profiler::scope(category.clone(), name.clone())
+        } else {
+            ProfileScope::inactive()
+        };
+
+        if SINK_COUNT.load(Ordering::Relaxed) == 0
+            || !LOG
+                .lock()
+                .is_enabled_internal(MessageKind::Error, &category)
+        {
+            return LogSpan {
+                data: None,
+                profile_scope,
+            };
+        }
+
+        let depth = SPAN_DEPTH.with(|depth| depth.replace(depth.get() + 1));
+        LogSpan {
+            data: Some((name, category, Instant::now(), depth)),
+            profile_scope,
+        }
+    }
+
     /// Allows you to verify that the result of operation is Ok, or print the error in the log.
     ///
     /// # Use cases
@@ -250,8 +648,44 @@ impl Log {
     }
 }
 
+/// Writes a categorized message with optional key-value fields. It is used by [`crate::info`],
+/// [`crate::warn`] and [`crate::err`] macros, the message is formatted only if it passes the
+/// filters.
+#[macro_export]
+macro_rules! log_event {
+    ($kind:expr, $category:expr, $fmt:literal $(, $arg:expr)* $(; $($key:ident = $value:expr),+ $(,)?)?) => {
+        if $crate::log::Log::is_enabled($kind, $category) {
+            $crate::log::Log::event($kind, format!($fmt $(, $arg)*))
+                .with_category($category)
+                $($(.with_field(stringify!($key), &$value))+)?
+                .write()
+        }
+    };
+}
+
+/// Starts a span, that lasts till the end of the current scope. See [`crate::log::Log::span`] for
+/// more info.
+///
+/// ```rust
+/// use fyrox_core::{log::category, log_span};
+///
+/// fn update_physics() {
+///     log_span!(category::PHYSICS, "Step");
+///     // ...
+/// }
+/// ```
+#[macro_export]
+macro_rules! log_span {
+    ($category:expr, $name:expr) => {
+        let _span = $crate::log::Log::span($category, $name);
+    };
+}
+
 #[macro_export]
 macro_rules! info {
+    (category: $category:expr, $($arg:tt)*) => {
+        $crate::log_event!($crate::log::MessageKind::Information, $category, $($arg)*)
+    };
     ($($arg:tt)*) => {
         $crate::log::Log::info(format!($($arg)*))
     };
@@ -259,6 +693,9 @@ macro_rules! info {
 
 #[macro_export]
 macro_rules! warn {
+    (category: $category:expr, $($arg:tt)*) => {
+        $crate::log_event!($crate::log::MessageKind::Warning, $category, $($arg)*)
+    };
     ($($arg:tt)*) => {
         $crate::log::Log::warn(format!($($arg)*))
     };
@@ -266,7 +703,111 @@ macro_rules! warn {
 
 #[macro_export]
 macro_rules! err {
+    (category: $category:expr, $($arg:tt)*) => {
+        $crate::log_event!($crate::log::MessageKind::Error, $category, $($arg)*)
+    };
     ($($arg:tt)*) => {
         $crate::log::Log::err(format!($($arg)*))
     };
 }
+
+#[cfg(test)]
+mod test {
+    use crate::log::{Log, LogMessage, LogSink, MessageKind, SpanRecord};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default, Clone)]
+    struct Collector {
+        messages: Arc<Mutex<Vec<LogMessage>>>,
+        spans: Arc<Mutex<Vec<SpanRecord>>>,
+    }
+
+    impl LogSink for Collector {
+        fn write(&mut self, message: &LogMessage) {
+            self.messages.lock().unwrap().push(message.clone());
+        }
+
+        fn write_span(&mut self, span: &SpanRecord) {
+            self.spans.lock().unwrap().push(span.clone());
+        }
+    }
+
+    impl Collector {
+        fn messages(&self, category: &str) -> Vec<LogMessage> {
+            self.messages
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|m| m.category.starts_with(category))
+                .cloned()
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_categories_and_fields() {
+        let collector = Collector::default();
+        let id = Log::add_sink(collector.clone());
+
+        Log::set_category_verbosity("test_categories", MessageKind::Warning);
+        Log::set_category_enabled("test_categories.muted", false);
+
+        info!(category: "test_categories", "Hidden");
+        warn!(category: "test_categories.child", "Visible {}", 1; width = 10, name = "foo");
+        err!(category: "test_categories.muted.child", "Muted");
+        assert!(!Log::is_enabled(
+            MessageKind::Information,
+            "test_categories.child"
+        ));
+
+        Log::reset_category("test_categories");
+        info!(category: "test_categories", "Visible");
+
+        let messages = collector.messages("test_categories");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, "Visible 1\n");
+        assert_eq!(messages[0].category, "test_categories.child");
+        assert_eq!(
+            messages[0].format(),
+            "[WARNING]: [test_categories.child] Visible 1 width=10 name=foo\n"
+        );
+        assert_eq!(messages[1].kind, MessageKind::Information);
+
+        Log::reset_category("test_categories.muted");
+        Log::remove_sink(id);
+    }
+
+    #[test]
+    fn test_spans() {
+        let collector = Collector::default();
+        let id = Log::add_sink(collector.clone());
+
+        {
+            log_span!("test_spans", "Outer");
+            {
+                let span = Log::span("test_spans", "Inner");
+                assert!(span.is_active());
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+        }
+
+        Log::remove_sink(id);
+
+        let spans = collector
+            .spans
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|s| s.category == "test_spans")
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].name, "Inner");
+        assert_eq!(spans[0].depth, 1);
+        assert_eq!(spans[1].name, "Outer");
+        assert_eq!(spans[1].depth, 0);
+        assert!(spans[1].duration >= spans[0].duration);
+        assert!(spans[0].duration.as_millis() >= 5);
+        assert!(spans[1].start <= spans[0].start);
+    }
+}
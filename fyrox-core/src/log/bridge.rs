@@ -0,0 +1,305 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Bridges, that redirect messages of `log` and `tracing` crates into the engine logger, so the
+//! output of third-party crates is captured by the log sinks as well. The bridges are available
+//! with `log_bridge` and `tracing_bridge` features respectively.
+//!
+//! Targets of the messages are converted to categories by replacing `::` with `.`, so module
+//! hierarchy becomes category hierarchy (for example `wgpu_core::device` becomes
+//! `wgpu_core.device`). Debug and trace levels are mapped to [`super::MessageKind::Information`].
+
+#[cfg(feature = "log_bridge")]
+pub use log_bridge::*;
+#[cfg(feature = "tracing_bridge")]
+pub use tracing_bridge::*;
+
+#[cfg(any(feature = "log_bridge", feature = "tracing_bridge"))]
+fn target_to_category(target: &str) -> String {
+    target.replace("::", ".")
+}
+
+#[cfg(feature = "log_bridge")]
+mod log_bridge {
+    use crate::log::{Log, MessageKind};
+
+    fn kind(level: log::Level) -> MessageKind {
+        match level {
+            log::Level::Error => MessageKind::Error,
+            log::Level::Warn => MessageKind::Warning,
+            log::Level::Info | log::Level::Debug | log::Level::Trace => MessageKind::Information,
+        }
+    }
+
+    /// An implementation of [`log::Log`], that writes every record to the engine logger.
+    pub struct LogBridge;
+
+    impl log::Log for LogBridge {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            Log::is_enabled(
+                kind(metadata.level()),
+                &super::target_to_category(metadata.target()),
+            )
+        }
+
+        fn log(&self, record: &log::Record) {
+            let category = super::target_to_category(record.target());
+            let kind = kind(record.level());
+            if Log::is_enabled(kind, &category) {
+                Log::event(kind, record.args().to_string())
+                    .with_category(category)
+                    .write();
+            }
+        }
+
+        fn flush(&self) {
+            Log::flush()
+        }
+    }
+
+    static BRIDGE: LogBridge = LogBridge;
+
+    /// Installs [`LogBridge`] as the global logger of `log` crate. Records with the level above the
+    /// given one are ignored. Fails if there's a logger installed already.
+    pub fn install_log_bridge(max_level: log::LevelFilter) -> Result<(), log::SetLoggerError> {
+        log::set_logger(&BRIDGE)?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tracing_bridge")]
+mod tracing_bridge {
+    use crate::log::{Log, LogSpan, MessageKind};
+    use crate::parking_lot::Mutex;
+    use fxhash::FxHashMap;
+    use std::{
+        cell::RefCell,
+        fmt::Debug,
+        sync::atomic::{AtomicU64, Ordering},
+    };
+    use tracing_core::{
+        field::{Field, Visit},
+        span, Event, Level, LevelFilter, Metadata, Subscriber,
+    };
+
+    fn kind(level: &Level) -> MessageKind {
+        if *level == Level::ERROR {
+            MessageKind::Error
+        } else if *level == Level::WARN {
+            MessageKind::Warning
+        } else {
+            MessageKind::Information
+        }
+    }
+
+    struct SpanData {
+        metadata: &'static Metadata<'static>,
+        ref_count: usize,
+    }
+
+    thread_local! {
+        static ENTERED: RefCell<Vec<(u64, LogSpan)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    #[derive(Default)]
+    struct EventVisitor {
+        message: String,
+        fields: Vec<(&'static str, String)>,
+    }
+
+    impl Visit for EventVisitor {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "message" {
+                self.message = value.to_string();
+            } else {
+                self.fields.push((field.name(), value.to_string()));
+            }
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            if field.name() == "message" {
+                self.message = format!("{value:?}");
+            } else {
+                self.fields.push((field.name(), format!("{value:?}")));
+            }
+        }
+    }
+
+    /// An implementation of [`Subscriber`], that writes every event to the engine logger and
+    /// measures spans using [`Log::span`]. Span fields are ignored.
+    pub struct TracingBridge {
+        max_level: LevelFilter,
+        next_id: AtomicU64,
+        spans: Mutex<FxHashMap<u64, SpanData>>,
+    }
+
+    impl TracingBridge {
+        /// Creates a new bridge, that ignores events and spans with the level above the given one.
+        pub fn new(max_level: LevelFilter) -> Self {
+            Self {
+                max_level,
+                next_id: AtomicU64::new(1),
+                spans: Default::default(),
+            }
+        }
+    }
+
+    impl Subscriber for TracingBridge {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            self.max_level >= *metadata.level()
+                && Log::is_enabled(
+                    kind(metadata.level()),
+                    &super::target_to_category(metadata.target()),
+                )
+        }
+
+        fn max_level_hint(&self) -> Option<LevelFilter> {
+            Some(self.max_level)
+        }
+
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            self.spans.lock().insert(
+                id,
+                SpanData {
+                    metadata: span.metadata(),
+                    ref_count: 1,
+                },
+            );
+            span::Id::from_u64(id)
+        }
+
+        fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let metadata = event.metadata();
+            let mut visitor = EventVisitor::default();
+            event.record(&mut visitor);
+
+            let mut log_event = Log::event(kind(metadata.level()), visitor.message)
+                .with_category(super::target_to_category(metadata.target()));
+            for (key, value) in visitor.fields {
+                log_event = log_event.with_field(key, value);
+            }
+            log_event.write();
+        }
+
+        fn enter(&self, span: &span::Id) {
+            let Some(metadata) = self
+                .spans
+                .lock()
+                .get(&span.into_u64())
+                .map(|data| data.metadata)
+            else {
+                return;
+            };
+
+            let log_span = Log::span(
+                super::target_to_category(metadata.target()),
+                metadata.name(),
+            );
+            ENTERED.with(|entered| entered.borrow_mut().push((span.into_u64(), log_span)));
+        }
+
+        fn exit(&self, span: &span::Id) {
+            let log_span = ENTERED.with(|entered| {
+                let mut entered = entered.borrow_mut();
+                let position = entered.iter().rposition(|(id, _)| *id == span.into_u64())?;
+                Some(entered.remove(position).1)
+            });
+            // Finish the span outside of the borrow.
+            drop(log_span);
+        }
+
+        fn clone_span(&self, id: &span::Id) -> span::Id {
+            if let Some(data) = self.spans.lock().get_mut(&id.into_u64()) {
+                data.ref_count += 1;
+            }
+            id.clone()
+        }
+
+        fn try_close(&self, id: span::Id) -> bool {
+            let mut spans = self.spans.lock();
+            let Some(data) = spans.get_mut(&id.into_u64()) else {
+                return false;
+            };
+            data.ref_count -= 1;
+            if data.ref_count == 0 {
+                spans.remove(&id.into_u64());
+                true
+            } else {
+                false
+            }
+        }
+    }
+
+    /// Installs [`TracingBridge`] as the global default subscriber of `tracing` crate. Fails if
+    /// there's a global subscriber installed already.
+    pub fn install_tracing_bridge(
+        max_level: LevelFilter,
+    ) -> Result<(), tracing_core::dispatcher::SetGlobalDefaultError> {
+        tracing_core::dispatcher::set_global_default(tracing_core::Dispatch::new(
+            TracingBridge::new(max_level),
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "log_bridge"))]
+mod test {
+    use super::LogBridge;
+    use crate::log::{Log, LogMessage, LogSink, MessageKind};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default, Clone)]
+    struct Collector(Arc<Mutex<Vec<LogMessage>>>);
+
+    impl LogSink for Collector {
+        fn write(&mut self, message: &LogMessage) {
+            self.0.lock().unwrap().push(message.clone());
+        }
+    }
+
+    #[test]
+    fn test_log_bridge() {
+        let collector = Collector::default();
+        let id = Log::add_sink(collector.clone());
+
+        log::Log::log(
+            &LogBridge,
+            &log::Record::builder()
+                .args(format_args!("Adapter {} selected", 0))
+                .level(log::Level::Warn)
+                .target("wgpu_core::instance")
+                .build(),
+        );
+
+        Log::remove_sink(id);
+
+        let messages = collector.0.lock().unwrap();
+        let message = messages
+            .iter()
+            .find(|m| m.category == "wgpu_core.instance")
+            .unwrap();
+        assert_eq!(message.kind, MessageKind::Warning);
+        assert_eq!(message.content, "Adapter 0 selected\n");
+    }
+}
@@ -0,0 +1,273 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Built-in log sinks. See [`LogSink`] docs for more info.
+
+use crate::json::{Map, Value};
+use crate::log::{LogMessage, LogSink, SpanRecord};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// A sink, that writes messages into a text file and starts a new file when the current one
+/// exceeds the size limit. Old files are renamed by adding an index to their names (`game.log.1`,
+/// `game.log.2`, etc.), the oldest files are removed.
+pub struct RotatingFileSink {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    writer: BufWriter<File>,
+    size: u64,
+}
+
+impl RotatingFileSink {
+    /// Creates a new sink, that writes to the file at the given path. `max_size` is a size limit of
+    /// a single file in bytes and `max_files` is the maximum amount of old files to keep.
+    pub fn new<P: AsRef<Path>>(path: P, max_size: u64, max_files: usize) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::create(&path)?;
+        Ok(Self {
+            path,
+            max_size,
+            max_files,
+            writer: BufWriter::new(file),
+            size: 0,
+        })
+    }
+
+    fn indexed_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+
+        if self.max_files == 0 {
+            self.writer = BufWriter::new(File::create(&self.path)?);
+        } else {
+            let _ = fs::remove_file(self.indexed_path(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let from = self.indexed_path(index);
+                if from.exists() {
+                    fs::rename(from, self.indexed_path(index + 1))?;
+                }
+            }
+            // Close the current file first, because Windows does not allow renaming opened files.
+            self.writer = BufWriter::new(File::create(self.indexed_path(0))?);
+            fs::rename(&self.path, self.indexed_path(1))?;
+            fs::rename(self.indexed_path(0), &self.path)?;
+        }
+
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl LogSink for RotatingFileSink {
+    fn write(&mut self, message: &LogMessage) {
+        let mut line = format!("[{:.3}s] {}", message.time.as_secs_f32(), message.format());
+        if !line.ends_with('\n') {
+            line.push('\n');
+        }
+
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            if let Err(err) = self.rotate() {
+                // The logger is locked, so the error could be reported only to stderr.
+                eprintln!(
+                    "Unable to rotate log file {}. Reason: {err}",
+                    self.path.display()
+                );
+            }
+        }
+
+        if self.writer.write_all(line.as_bytes()).is_ok() {
+            self.size += line.len() as u64;
+        }
+    }
+
+    fn flush(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+impl Drop for RotatingFileSink {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+/// A sink, that writes every message (and optionally every span) as a JSON object on a separate
+/// line. Such format is easy to process by external tools. Messages have the following layout:
+///
+/// ```json
+/// {"time":1.25,"kind":"warning","category":"renderer","message":"Text","fields":{"width":"10"}}
+/// ```
+///
+/// Spans have the following layout (times are in seconds):
+///
+/// ```json
+/// {"time":1.25,"span":"Shadows","category":"renderer","duration":0.001,"depth":1}
+/// ```
+pub struct JsonLinesSink<W: Write + Send> {
+    writer: W,
+    write_spans: bool,
+}
+
+impl JsonLinesSink<BufWriter<File>> {
+    /// Creates a new sink, that writes to the file at the given path.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write + Send> JsonLinesSink<W> {
+    /// Creates a new sink, that writes to the given writer.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            write_spans: false,
+        }
+    }
+
+    /// Enables or disables writing of spans. Spans are not written by default.
+    pub fn with_spans(mut self, write_spans: bool) -> Self {
+        self.write_spans = write_spans;
+        self
+    }
+
+    /// Returns a reference to the inner writer.
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    fn write_line(&mut self, value: Value) {
+        let _ = writeln!(self.writer, "{value}");
+    }
+}
+
+impl<W: Write + Send> LogSink for JsonLinesSink<W> {
+    fn write(&mut self, message: &LogMessage) {
+        let fields = message
+            .fields
+            .iter()
+            .map(|field| (field.key.to_string(), Value::String(field.value.clone())))
+            .collect::<Map>();
+        self.write_line(Value::from([
+            ("time", Value::from(message.time.as_secs_f64())),
+            ("kind", Value::from(message.kind.name())),
+            ("category", Value::from(message.category.as_ref())),
+            (
+                "message",
+                Value::from(message.content.trim_end_matches('\n')),
+            ),
+            ("fields", Value::Object(fields)),
+        ]));
+    }
+
+    fn write_span(&mut self, span: &SpanRecord) {
+        if self.write_spans {
+            self.write_line(Value::from([
+                ("time", Value::from(span.start.as_secs_f64())),
+                ("span", Value::from(span.name.as_ref())),
+                ("category", Value::from(span.category.as_ref())),
+                ("duration", Value::from(span.duration.as_secs_f64())),
+                ("depth", Value::from(span.depth)),
+            ]));
+        }
+    }
+
+    fn flush(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::log::{Log, MessageKind};
+    use std::time::Duration;
+
+    fn message(content: &str) -> LogMessage {
+        Log::event(MessageKind::Warning, content)
+            .with_category("test")
+            .with_field("id", 42)
+            .into_message(Duration::from_secs(1))
+    }
+
+    #[test]
+    fn test_rotating_file_sink() {
+        let dir = std::env::temp_dir().join(format!("fyrox_log_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("game.log");
+
+        let mut sink = RotatingFileSink::new(&path, 100, 2).unwrap();
+        for i in 0..10 {
+            sink.write(&message(&format!("Message {i}")));
+        }
+        sink.flush();
+
+        let current = fs::read_to_string(&path).unwrap();
+        assert!(current.ends_with("[WARNING]: [test] Message 9 id=42\n"));
+        assert!(current.len() <= 100);
+        assert!(dir.join("game.log.1").exists());
+        assert!(dir.join("game.log.2").exists());
+        assert!(!dir.join("game.log.3").exists());
+
+        drop(sink);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_json_lines_sink() {
+        let mut sink = JsonLinesSink::new(Vec::new()).with_spans(true);
+        sink.write(&message("Hello \"world\""));
+        sink.write_span(&SpanRecord {
+            name: "Update".into(),
+            category: "test".into(),
+            start: Duration::from_millis(500),
+            duration: Duration::from_millis(250),
+            depth: 0,
+            thread: std::thread::current().id(),
+        });
+
+        let text = String::from_utf8(sink.writer().clone()).unwrap();
+        let lines = text
+            .lines()
+            .map(|line| line.parse::<Value>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            r#"{
+                "time": 1.0,
+                "kind": "warning",
+                "category": "test",
+                "message": "Hello \"world\"",
+                "fields": { "id": "42" }
+            }"#
+            .parse::<Value>()
+            .unwrap()
+        );
+        assert_eq!(lines[1]["span"], Value::from("Update"));
+        assert_eq!(lines[1]["duration"], Value::from(0.25));
+    }
+}
@@ -26,6 +26,8 @@ use std::{
     net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
 };
 
+pub mod udp;
+
 pub struct NetListener {
     listener: TcpListener,
 }
@@ -0,0 +1,1059 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! UDP transport with reliable and unreliable channels and connection management. See [`UdpServer`]
+//! and [`UdpClient`] docs for more info.
+//!
+//! Every packet starts with a protocol id and a packet kind. Unreliable messages are sent as is,
+//! they could be lost, duplicated or reordered. Reliable messages are split into fragments, every
+//! fragment has a sequence number and is re-sent until the other side acknowledges it. Reliable
+//! messages are delivered exactly once and in the order they were sent. Connections are kept alive
+//! by heartbeats and are dropped if the other side is silent for too long.
+
+use crate::log::category;
+use fxhash::FxHashMap;
+use instant::Instant;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::{Display, Formatter},
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::Duration,
+};
+
+/// Identifier of the protocol, packets without it are ignored.
+pub const PROTOCOL_ID: u32 = 0x4659_5255;
+
+/// Maximum size of a packet. It is chosen to fit into the smallest MTU of common networks.
+pub const MAX_PACKET_SIZE: usize = 1200;
+
+const HEADER_SIZE: usize = 5;
+const RELIABLE_HEADER_SIZE: usize = HEADER_SIZE + 5;
+
+/// Maximum size of an unreliable message. Reliable messages could be of any size, they're split
+/// into fragments automatically.
+pub const MAX_UNRELIABLE_MESSAGE_SIZE: usize = MAX_PACKET_SIZE - HEADER_SIZE;
+
+const MAX_FRAGMENT_SIZE: usize = MAX_PACKET_SIZE - RELIABLE_HEADER_SIZE;
+
+/// Maximum amount of reliable packets, that could be received ahead of the next expected one.
+/// Packets beyond the window are dropped without an acknowledgement, so they will be resent later.
+const RECEIVE_WINDOW: u32 = 1024;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+enum PacketKind {
+    Connect = 0,
+    Accept = 1,
+    Reject = 2,
+    Disconnect = 3,
+    Heartbeat = 4,
+    Unreliable = 5,
+    Reliable = 6,
+    Ack = 7,
+}
+
+impl PacketKind {
+    fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            0 => Self::Connect,
+            1 => Self::Accept,
+            2 => Self::Reject,
+            3 => Self::Disconnect,
+            4 => Self::Heartbeat,
+            5 => Self::Unreliable,
+            6 => Self::Reliable,
+            7 => Self::Ack,
+            _ => return None,
+        })
+    }
+}
+
+fn packet(kind: PacketKind, capacity: usize) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(HEADER_SIZE + capacity);
+    packet.extend_from_slice(&PROTOCOL_ID.to_le_bytes());
+    packet.push(kind as u8);
+    packet
+}
+
+fn parse_packet(data: &[u8]) -> Option<(PacketKind, &[u8])> {
+    if data.len() < HEADER_SIZE || data[..4] != PROTOCOL_ID.to_le_bytes() {
+        return None;
+    }
+    PacketKind::from_u8(data[4]).map(|kind| (kind, &data[HEADER_SIZE..]))
+}
+
+fn read_u32(data: &[u8]) -> Option<u32> {
+    data.get(..4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u64(data: &[u8]) -> Option<u64> {
+    data.get(..8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// A channel of a message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// Messages could be lost, duplicated or reordered. Suitable for frequently updated state,
+    /// where only the latest value matters.
+    Unreliable,
+    /// Messages are guaranteed to be delivered exactly once and in the order they were sent.
+    Reliable,
+}
+
+/// Unique identifier of a connection. Client side uses [`ConnectionId::SERVER`] for its only
+/// connection.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ConnectionId(pub u64);
+
+impl ConnectionId {
+    /// Identifier of the connection to the server on client side.
+    pub const SERVER: Self = Self(0);
+}
+
+impl Display for ConnectionId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A reason of disconnection.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DisconnectReason {
+    /// The other side was silent for too long.
+    Timeout,
+    /// The other side closed the connection.
+    Closed,
+    /// The server rejected the connection, because it has no free slots.
+    Rejected,
+}
+
+/// An event of a server or a client.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NetEvent {
+    /// A new connection was established.
+    Connected(ConnectionId),
+    /// A connection was closed.
+    Disconnected {
+        /// Identifier of the connection.
+        connection: ConnectionId,
+        /// A reason of disconnection.
+        reason: DisconnectReason,
+    },
+    /// A message was received.
+    Message {
+        /// Identifier of the connection, that has sent the message.
+        connection: ConnectionId,
+        /// A channel of the message.
+        channel: Channel,
+        /// Contents of the message.
+        data: Vec<u8>,
+    },
+}
+
+/// Configuration of the transport.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UdpConfig {
+    /// Interval of keep-alive packets, that are sent when there's nothing else to send.
+    pub heartbeat_interval: Duration,
+    /// A connection is considered lost, if there were no packets from the other side for this time.
+    pub timeout: Duration,
+    /// Interval of re-sending of unacknowledged reliable packets.
+    pub resend_interval: Duration,
+    /// Interval of connection requests of a client.
+    pub connect_interval: Duration,
+    /// Maximum amount of connections of a server.
+    pub max_connections: usize,
+    /// Probability of dropping an outgoing packet in `[0; 1]` range. It is meant to be used for
+    /// testing only.
+    pub packet_loss: f32,
+}
+
+impl Default for UdpConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_millis(250),
+            timeout: Duration::from_secs(5),
+            resend_interval: Duration::from_millis(100),
+            connect_interval: Duration::from_millis(250),
+            max_connections: 64,
+            packet_loss: 0.0,
+        }
+    }
+}
+
+fn send_packet(socket: &UdpSocket, config: &UdpConfig, address: SocketAddr, packet: &[u8]) {
+    if config.packet_loss > 0.0 && rand::random::<f32>() < config.packet_loss {
+        return;
+    }
+
+    if let Err(err) = socket.send_to(packet, address) {
+        if err.kind() != ErrorKind::WouldBlock {
+            crate::warn!(
+                category: category::NET,
+                "Unable to send a packet to {address}. Reason: {err}"
+            );
+        }
+    }
+}
+
+struct PendingPacket {
+    sequence: u32,
+    data: Vec<u8>,
+    last_sent: Instant,
+}
+
+/// State of a connection, that is shared between clients and servers.
+struct Connection {
+    id: ConnectionId,
+    address: SocketAddr,
+    /// Random number chosen by the client for the handshake. It identifies the connection among
+    /// the connections from the same address.
+    salt: u64,
+    last_received: Instant,
+    last_sent: Instant,
+    next_send_sequence: u32,
+    pending: VecDeque<PendingPacket>,
+    next_receive_sequence: u32,
+    out_of_order: BTreeMap<u32, (bool, Vec<u8>)>,
+    partial_message: Vec<u8>,
+    acks: Vec<u32>,
+}
+
+impl Connection {
+    fn new(id: ConnectionId, address: SocketAddr, salt: u64, now: Instant) -> Self {
+        Self {
+            id,
+            address,
+            salt,
+            last_received: now,
+            last_sent: now,
+            next_send_sequence: 0,
+            pending: Default::default(),
+            next_receive_sequence: 0,
+            out_of_order: Default::default(),
+            partial_message: Default::default(),
+            acks: Default::default(),
+        }
+    }
+
+    fn send_raw(&mut self, socket: &UdpSocket, config: &UdpConfig, packet: &[u8], now: Instant) {
+        send_packet(socket, config, self.address, packet);
+        self.last_sent = now;
+    }
+
+    fn send(
+        &mut self,
+        socket: &UdpSocket,
+        config: &UdpConfig,
+        channel: Channel,
+        data: &[u8],
+        now: Instant,
+    ) -> io::Result<()> {
+        match channel {
+            Channel::Unreliable => {
+                if data.len() > MAX_UNRELIABLE_MESSAGE_SIZE {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "Unreliable message is too large ({} bytes, max is {MAX_UNRELIABLE_MESSAGE_SIZE})",
+                            data.len()
+                        ),
+                    ));
+                }
+                let mut packet = packet(PacketKind::Unreliable, data.len());
+                packet.extend_from_slice(data);
+                self.send_raw(socket, config, &packet, now);
+            }
+            Channel::Reliable => {
+                let mut chunks = data.chunks(MAX_FRAGMENT_SIZE).peekable();
+                // Empty messages are still sent as a single empty fragment.
+                let empty: &[u8] = &[];
+                let mut first = true;
+                while let Some(chunk) = chunks.next().or(first.then_some(empty)) {
+                    first = false;
+                    let last = chunks.peek().is_none();
+                    let sequence = self.next_send_sequence;
+                    self.next_send_sequence = self.next_send_sequence.wrapping_add(1);
+
+                    let mut packet = packet(PacketKind::Reliable, 5 + chunk.len());
+                    packet.extend_from_slice(&sequence.to_le_bytes());
+                    packet.push(last as u8);
+                    packet.extend_from_slice(chunk);
+                    self.send_raw(socket, config, &packet, now);
+                    self.pending.push_back(PendingPacket {
+                        sequence,
+                        data: packet,
+                        last_sent: now,
+                    });
+
+                    if last {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles a packet of an established connection and returns received messages.
+    fn receive(
+        &mut self,
+        kind: PacketKind,
+        body: &[u8],
+        now: Instant,
+        messages: &mut Vec<NetEvent>,
+    ) {
+        self.last_received = now;
+
+        match kind {
+            PacketKind::Unreliable => messages.push(NetEvent::Message {
+                connection: self.id,
+                channel: Channel::Unreliable,
+                data: body.to_vec(),
+            }),
+            PacketKind::Reliable => {
+                let (Some(sequence), Some(&last)) = (read_u32(body), body.get(4)) else {
+                    return;
+                };
+                let distance = sequence.wrapping_sub(self.next_receive_sequence);
+                if (RECEIVE_WINDOW..u32::MAX / 2).contains(&distance) {
+                    // Too far ahead, the sender will resend it when the gap is filled.
+                    return;
+                }
+
+                // Acknowledge every packet, even duplicates, because previous ack could be lost.
+                self.acks.push(sequence);
+
+                if distance >= u32::MAX / 2 {
+                    // Old duplicate.
+                    return;
+                }
+
+                self.out_of_order
+                    .insert(sequence, (last != 0, body[5..].to_vec()));
+
+                while let Some((last, fragment)) =
+                    self.out_of_order.remove(&self.next_receive_sequence)
+                {
+                    self.next_receive_sequence = self.next_receive_sequence.wrapping_add(1);
+                    self.partial_message.extend_from_slice(&fragment);
+                    if last {
+                        messages.push(NetEvent::Message {
+                            connection: self.id,
+                            channel: Channel::Reliable,
+                            data: std::mem::take(&mut self.partial_message),
+                        });
+                    }
+                }
+            }
+            PacketKind::Ack => {
+                for sequence in body.chunks_exact(4).filter_map(read_u32) {
+                    self.pending.retain(|packet| packet.sequence != sequence);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Sends acknowledgements, re-sends lost packets and keeps the connection alive.
+    fn update(&mut self, socket: &UdpSocket, config: &UdpConfig, now: Instant) {
+        while !self.acks.is_empty() {
+            let count = self.acks.len().min((MAX_PACKET_SIZE - HEADER_SIZE) / 4);
+            let mut packet = packet(PacketKind::Ack, count * 4);
+            for sequence in self.acks.drain(..count) {
+                packet.extend_from_slice(&sequence.to_le_bytes());
+            }
+            self.send_raw(socket, config, &packet, now);
+        }
+
+        for pending in self.pending.iter_mut() {
+            if now.duration_since(pending.last_sent) >= config.resend_interval {
+                send_packet(socket, config, self.address, &pending.data);
+                pending.last_sent = now;
+                self.last_sent = now;
+            }
+        }
+
+        if now.duration_since(self.last_sent) >= config.heartbeat_interval {
+            self.send_raw(socket, config, &packet(PacketKind::Heartbeat, 0), now);
+        }
+    }
+
+    fn is_timed_out(&self, config: &UdpConfig, now: Instant) -> bool {
+        now.duration_since(self.last_received) >= config.timeout
+    }
+}
+
+fn receive_packets(socket: &UdpSocket, mut func: impl FnMut(SocketAddr, &[u8])) {
+    let mut buffer = [0; MAX_PACKET_SIZE];
+    loop {
+        match socket.recv_from(&mut buffer) {
+            Ok((size, address)) => func(address, &buffer[..size]),
+            Err(err) => match err.kind() {
+                ErrorKind::WouldBlock => break,
+                // Some platforms report ICMP "port unreachable" as errors of the next read, this
+                // is not fatal for connection-less sockets.
+                ErrorKind::Interrupted | ErrorKind::ConnectionReset => (),
+                _ => {
+                    crate::err!(
+                        category: category::NET,
+                        "Unable to receive a packet. Reason: {err}"
+                    );
+                    break;
+                }
+            },
+        }
+    }
+}
+
+/// A server, that accepts connections from multiple [`UdpClient`]s. The server is non-blocking,
+/// [`UdpServer::poll`] must be called periodically (for example, every frame) to receive messages
+/// and to maintain the connections.
+///
+/// ```rust,no_run
+/// use fyrox_core::net::udp::{Channel, NetEvent, UdpConfig, UdpServer};
+///
+/// let mut server = UdpServer::bind("127.0.0.1:7777", UdpConfig::default()).unwrap();
+/// loop {
+///     for event in server.poll() {
+///         if let NetEvent::Message { connection, data, .. } = event {
+///             // Echo the message back.
+///             server.send(connection, Channel::Reliable, &data).unwrap();
+///         }
+///     }
+///     # break;
+/// }
+/// ```
+pub struct UdpServer {
+    socket: UdpSocket,
+    config: UdpConfig,
+    connections: FxHashMap<SocketAddr, Connection>,
+    addresses: FxHashMap<ConnectionId, SocketAddr>,
+    next_id: u64,
+}
+
+impl UdpServer {
+    /// Creates a new server, that listens at the given address.
+    pub fn bind<A: ToSocketAddrs>(address: A, config: UdpConfig) -> io::Result<Self> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            config,
+            connections: Default::default(),
+            addresses: Default::default(),
+            next_id: 1,
+        })
+    }
+
+    /// Returns the address the server is listening at.
+    pub fn local_address(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Returns the configuration of the server.
+    pub fn config(&self) -> &UdpConfig {
+        &self.config
+    }
+
+    /// Returns an iterator over all active connections.
+    pub fn connections(&self) -> impl Iterator<Item = ConnectionId> + '_ {
+        self.addresses.keys().copied()
+    }
+
+    /// Returns an address of the client with the given connection.
+    pub fn connection_address(&self, connection: ConnectionId) -> Option<SocketAddr> {
+        self.addresses.get(&connection).copied()
+    }
+
+    /// Receives all incoming packets, maintains connections and returns all the events happened
+    /// since the last call.
+    pub fn poll(&mut self) -> Vec<NetEvent> {
+        let now = Instant::now();
+        let mut events = Vec::new();
+
+        let Self {
+            socket,
+            config,
+            connections,
+            addresses,
+            next_id,
+        } = self;
+
+        receive_packets(socket, |address, data| {
+            let Some((kind, body)) = parse_packet(data) else {
+                return;
+            };
+
+            match kind {
+                PacketKind::Connect => {
+                    let Some(salt) = read_u64(body) else {
+                        return;
+                    };
+
+                    let existing = connections.get(&address).map(|c| (c.id, c.salt));
+                    let accepted = if existing.is_some_and(|(_, s)| s == salt) {
+                        // Accept packet was lost, the client is still trying to connect.
+                        true
+                    } else {
+                        if let Some((id, _)) = existing {
+                            // The client reconnected from the same address (for example, it was
+                            // restarted), the state of the old connection is stale.
+                            connections.remove(&address);
+                            addresses.remove(&id);
+                            events.push(NetEvent::Disconnected {
+                                connection: id,
+                                reason: DisconnectReason::Closed,
+                            });
+                        }
+
+                        if connections.len() < config.max_connections {
+                            let id = ConnectionId(*next_id);
+                            *next_id += 1;
+                            connections.insert(address, Connection::new(id, address, salt, now));
+                            addresses.insert(id, address);
+                            events.push(NetEvent::Connected(id));
+                            true
+                        } else {
+                            false
+                        }
+                    };
+
+                    let kind = if accepted {
+                        PacketKind::Accept
+                    } else {
+                        PacketKind::Reject
+                    };
+                    let mut reply = packet(kind, 8);
+                    reply.extend_from_slice(&salt.to_le_bytes());
+                    send_packet(socket, config, address, &reply);
+                }
+                PacketKind::Disconnect => {
+                    if let Some(connection) = connections.remove(&address) {
+                        addresses.remove(&connection.id);
+                        events.push(NetEvent::Disconnected {
+                            connection: connection.id,
+                            reason: DisconnectReason::Closed,
+                        });
+                    }
+                }
+                _ => {
+                    if let Some(connection) = connections.get_mut(&address) {
+                        connection.receive(kind, body, now, &mut events);
+                    }
+                }
+            }
+        });
+
+        connections.retain(|_, connection| {
+            if connection.is_timed_out(config, now) {
+                addresses.remove(&connection.id);
+                events.push(NetEvent::Disconnected {
+                    connection: connection.id,
+                    reason: DisconnectReason::Timeout,
+                });
+                false
+            } else {
+                connection.update(socket, config, now);
+                true
+            }
+        });
+
+        events
+    }
+
+    /// Sends a message to the given connection.
+    pub fn send(
+        &mut self,
+        connection: ConnectionId,
+        channel: Channel,
+        data: &[u8],
+    ) -> io::Result<()> {
+        let address = self
+            .addresses
+            .get(&connection)
+            .ok_or_else(|| io::Error::new(ErrorKind::NotConnected, "No such connection"))?;
+        let connection = self.connections.get_mut(address).unwrap();
+        connection.send(&self.socket, &self.config, channel, data, Instant::now())
+    }
+
+    /// Serializes the message using `bincode` and sends it to the given connection.
+    pub fn send_message<T: Serialize>(
+        &mut self,
+        connection: ConnectionId,
+        channel: Channel,
+        message: &T,
+    ) -> io::Result<()> {
+        let data = bincode::serialize(message).map_err(io::Error::other)?;
+        self.send(connection, channel, &data)
+    }
+
+    /// Sends a message to every connection.
+    pub fn broadcast(&mut self, channel: Channel, data: &[u8]) -> io::Result<()> {
+        let now = Instant::now();
+        for connection in self.connections.values_mut() {
+            connection.send(&self.socket, &self.config, channel, data, now)?;
+        }
+        Ok(())
+    }
+
+    /// Closes the given connection. Unacknowledged reliable messages will be lost.
+    pub fn disconnect(&mut self, connection: ConnectionId) {
+        if let Some(address) = self.addresses.remove(&connection) {
+            self.connections.remove(&address);
+            send_packet(
+                &self.socket,
+                &self.config,
+                address,
+                &packet(PacketKind::Disconnect, 0),
+            );
+        }
+    }
+}
+
+impl Drop for UdpServer {
+    fn drop(&mut self) {
+        let connections = self.addresses.keys().copied().collect::<Vec<_>>();
+        for connection in connections {
+            self.disconnect(connection);
+        }
+    }
+}
+
+/// State of a [`UdpClient`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClientState {
+    /// The client is waiting for a reply from the server.
+    Connecting,
+    /// The connection is established.
+    Connected,
+    /// The connection is closed or could not be established.
+    Disconnected(DisconnectReason),
+}
+
+/// A client of a [`UdpServer`]. Just as the server, the client is non-blocking and
+/// [`UdpClient::poll`] must be called periodically.
+pub struct UdpClient {
+    socket: UdpSocket,
+    config: UdpConfig,
+    connection: Connection,
+    state: ClientState,
+    last_connect_attempt: Option<Instant>,
+}
+
+impl UdpClient {
+    /// Starts connecting to the server at the given address. The connection is established
+    /// asynchronously, [`NetEvent::Connected`] event will be emitted by [`UdpClient::poll`] when it is
+    /// done.
+    pub fn connect<A: ToSocketAddrs>(address: A, config: UdpConfig) -> io::Result<Self> {
+        let address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "No address to connect to"))?;
+        let local_address = if address.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        Self::from_socket(UdpSocket::bind(local_address)?, address, config)
+    }
+
+    fn from_socket(socket: UdpSocket, address: SocketAddr, config: UdpConfig) -> io::Result<Self> {
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            config,
+            connection: Connection::new(
+                ConnectionId::SERVER,
+                address,
+                rand::random(),
+                Instant::now(),
+            ),
+            state: ClientState::Connecting,
+            last_connect_attempt: None,
+        })
+    }
+
+    /// Returns current state of the client.
+    pub fn state(&self) -> ClientState {
+        self.state
+    }
+
+    /// Returns `true` if the connection is established.
+    pub fn is_connected(&self) -> bool {
+        self.state == ClientState::Connected
+    }
+
+    /// Returns an address of the server.
+    pub fn server_address(&self) -> SocketAddr {
+        self.connection.address
+    }
+
+    /// Returns the local address of the client.
+    pub fn local_address(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Receives all incoming packets, maintains the connection and returns all the events happened
+    /// since the last call.
+    pub fn poll(&mut self) -> Vec<NetEvent> {
+        let now = Instant::now();
+        let mut events = Vec::new();
+
+        if matches!(self.state, ClientState::Disconnected(_)) {
+            return events;
+        }
+
+        let Self {
+            socket,
+            connection,
+            state,
+            ..
+        } = self;
+
+        receive_packets(socket, |address, data| {
+            if address != connection.address {
+                return;
+            }
+
+            let Some((kind, body)) = parse_packet(data) else {
+                return;
+            };
+
+            match (kind, *state) {
+                (PacketKind::Accept, ClientState::Connecting)
+                    if read_u64(body) == Some(connection.salt) =>
+                {
+                    *state = ClientState::Connected;
+                    connection.last_received = now;
+                    events.push(NetEvent::Connected(ConnectionId::SERVER));
+                }
+                (PacketKind::Reject, ClientState::Connecting)
+                    if read_u64(body) == Some(connection.salt) =>
+                {
+                    *state = ClientState::Disconnected(DisconnectReason::Rejected);
+                    events.push(NetEvent::Disconnected {
+                        connection: ConnectionId::SERVER,
+                        reason: DisconnectReason::Rejected,
+                    });
+                }
+                (PacketKind::Disconnect, ClientState::Connected) => {
+                    *state = ClientState::Disconnected(DisconnectReason::Closed);
+                    events.push(NetEvent::Disconnected {
+                        connection: ConnectionId::SERVER,
+                        reason: DisconnectReason::Closed,
+                    });
+                }
+                (_, ClientState::Connected) => connection.receive(kind, body, now, &mut events),
+                _ => (),
+            }
+        });
+
+        match self.state {
+            ClientState::Connecting => {
+                if self.connection.is_timed_out(&self.config, now) {
+                    self.set_timed_out(&mut events);
+                } else if self.last_connect_attempt.map_or(true, |last| {
+                    now.duration_since(last) >= self.config.connect_interval
+                }) {
+                    let mut request = packet(PacketKind::Connect, 8);
+                    request.extend_from_slice(&self.connection.salt.to_le_bytes());
+                    send_packet(
+                        &self.socket,
+                        &self.config,
+                        self.connection.address,
+                        &request,
+                    );
+                    self.last_connect_attempt = Some(now);
+                }
+            }
+            ClientState::Connected => {
+                if self.connection.is_timed_out(&self.config, now) {
+                    self.set_timed_out(&mut events);
+                } else {
+                    self.connection.update(&self.socket, &self.config, now);
+                }
+            }
+            ClientState::Disconnected(_) => (),
+        }
+
+        events
+    }
+
+    fn set_timed_out(&mut self, events: &mut Vec<NetEvent>) {
+        self.state = ClientState::Disconnected(DisconnectReason::Timeout);
+        events.push(NetEvent::Disconnected {
+            connection: ConnectionId::SERVER,
+            reason: DisconnectReason::Timeout,
+        });
+    }
+
+    /// Sends a message to the server. Fails if the connection is not established.
+    pub fn send(&mut self, channel: Channel, data: &[u8]) -> io::Result<()> {
+        if !self.is_connected() {
+            return Err(io::Error::new(
+                ErrorKind::NotConnected,
+                "The client is not connected",
+            ));
+        }
+        self.connection
+            .send(&self.socket, &self.config, channel, data, Instant::now())
+    }
+
+    /// Serializes the message using `bincode` and sends it to the server.
+    pub fn send_message<T: Serialize>(&mut self, channel: Channel, message: &T) -> io::Result<()> {
+        let data = bincode::serialize(message).map_err(io::Error::other)?;
+        self.send(channel, &data)
+    }
+
+    /// Closes the connection. Unacknowledged reliable messages will be lost.
+    pub fn disconnect(&mut self) {
+        if self.is_connected() {
+            send_packet(
+                &self.socket,
+                &self.config,
+                self.connection.address,
+                &packet(PacketKind::Disconnect, 0),
+            );
+        }
+        self.state = ClientState::Disconnected(DisconnectReason::Closed);
+    }
+}
+
+impl Drop for UdpClient {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pump(
+        server: &mut UdpServer,
+        clients: &mut [&mut UdpClient],
+        mut until: impl FnMut(&[NetEvent], &[NetEvent]) -> bool,
+    ) -> (Vec<NetEvent>, Vec<NetEvent>) {
+        let mut server_events = Vec::new();
+        let mut client_events = Vec::new();
+        for _ in 0..2000 {
+            server_events.extend(server.poll());
+            for client in clients.iter_mut() {
+                client_events.extend(client.poll());
+            }
+            if until(&server_events, &client_events) {
+                return (server_events, client_events);
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("Timed out: {server_events:?} {client_events:?}");
+    }
+
+    fn config() -> UdpConfig {
+        UdpConfig {
+            resend_interval: Duration::from_millis(10),
+            connect_interval: Duration::from_millis(10),
+            ..Default::default()
+        }
+    }
+
+    fn messages(events: &[NetEvent], channel: Channel) -> Vec<Vec<u8>> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                NetEvent::Message {
+                    channel: c, data, ..
+                } if *c == channel => Some(data.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_connect_and_exchange() {
+        let mut server = UdpServer::bind("127.0.0.1:0", config()).unwrap();
+        let mut client = UdpClient::connect(server.local_address().unwrap(), config()).unwrap();
+
+        let (server_events, _) = pump(&mut server, &mut [&mut client], |_, c| {
+            c.contains(&NetEvent::Connected(ConnectionId::SERVER))
+        });
+        let NetEvent::Connected(id) = server_events[0] else {
+            panic!("{server_events:?}")
+        };
+        assert_eq!(server.connections().collect::<Vec<_>>(), vec![id]);
+
+        // Large message must be split into fragments and assembled back.
+        let large = (0..10000).map(|i| i as u8).collect::<Vec<_>>();
+        client.send(Channel::Reliable, b"Hello").unwrap();
+        client.send(Channel::Reliable, &large).unwrap();
+        client.send(Channel::Reliable, &[]).unwrap();
+        client.send(Channel::Unreliable, b"Unreliable").unwrap();
+        assert!(client.send(Channel::Unreliable, &large).is_err());
+
+        let (server_events, _) = pump(&mut server, &mut [&mut client], |s, _| {
+            messages(s, Channel::Reliable).len() == 3
+        });
+        assert_eq!(
+            messages(&server_events, Channel::Reliable),
+            vec![b"Hello".to_vec(), large, vec![]]
+        );
+
+        server.send(id, Channel::Reliable, b"Reply").unwrap();
+        let (_, client_events) = pump(&mut server, &mut [&mut client], |_, c| {
+            !messages(c, Channel::Reliable).is_empty()
+        });
+        assert_eq!(
+            messages(&client_events, Channel::Reliable),
+            vec![b"Reply".to_vec()]
+        );
+
+        client.disconnect();
+        pump(&mut server, &mut [], |s, _| {
+            s.contains(&NetEvent::Disconnected {
+                connection: id,
+                reason: DisconnectReason::Closed,
+            })
+        });
+        assert_eq!(server.connections().count(), 0);
+    }
+
+    #[test]
+    fn test_reliable_with_packet_loss() {
+        let lossy = UdpConfig {
+            packet_loss: 0.3,
+            ..config()
+        };
+        let mut server = UdpServer::bind("127.0.0.1:0", lossy.clone()).unwrap();
+        let mut client = UdpClient::connect(server.local_address().unwrap(), lossy).unwrap();
+
+        pump(&mut server, &mut [&mut client], |s, c| {
+            !s.is_empty() && c.contains(&NetEvent::Connected(ConnectionId::SERVER))
+        });
+
+        let sent = (0..100u32)
+            .map(|i| i.to_le_bytes().repeat(i as usize * 10))
+            .collect::<Vec<_>>();
+        for message in sent.iter() {
+            client.send(Channel::Reliable, message).unwrap();
+        }
+
+        let (server_events, _) = pump(&mut server, &mut [&mut client], |s, _| {
+            messages(s, Channel::Reliable).len() == sent.len()
+        });
+        assert_eq!(messages(&server_events, Channel::Reliable), sent);
+    }
+
+    #[test]
+    fn test_max_connections_and_timeout() {
+        let mut server = UdpServer::bind(
+            "127.0.0.1:0",
+            UdpConfig {
+                max_connections: 1,
+                timeout: Duration::from_millis(300),
+                ..config()
+            },
+        )
+        .unwrap();
+        let address = server.local_address().unwrap();
+        let mut first = UdpClient::connect(address, config()).unwrap();
+        pump(&mut server, &mut [&mut first], |_, c| !c.is_empty());
+        assert!(first.is_connected());
+
+        let mut second = UdpClient::connect(address, config()).unwrap();
+        pump(&mut server, &mut [&mut second], |_, c| !c.is_empty());
+        assert_eq!(
+            second.state(),
+            ClientState::Disconnected(DisconnectReason::Rejected)
+        );
+
+        // The first client stops responding, so the server must drop it.
+        let (server_events, _) = pump(&mut server, &mut [], |s, _| !s.is_empty());
+        assert!(matches!(
+            server_events[0],
+            NetEvent::Disconnected {
+                reason: DisconnectReason::Timeout,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_reconnect_from_same_address() {
+        let mut server = UdpServer::bind("127.0.0.1:0", config()).unwrap();
+        let address = server.local_address().unwrap();
+        let mut first = UdpClient::connect(address, config()).unwrap();
+        let (server_events, _) = pump(&mut server, &mut [&mut first], |s, c| {
+            !s.is_empty() && !c.is_empty()
+        });
+        let NetEvent::Connected(first_id) = server_events[0] else {
+            panic!("{server_events:?}")
+        };
+        for _ in 0..3 {
+            first.send(Channel::Reliable, b"First").unwrap();
+        }
+        pump(&mut server, &mut [&mut first], |s, _| {
+            messages(s, Channel::Reliable).len() == 3
+        });
+
+        // Emulate a crash of the client: it does not send a disconnect packet and a new client
+        // is started at the same local address.
+        let local_address = first.local_address().unwrap();
+        first.state = ClientState::Disconnected(DisconnectReason::Closed);
+        drop(first);
+        let mut second =
+            UdpClient::from_socket(UdpSocket::bind(local_address).unwrap(), address, config())
+                .unwrap();
+
+        let (server_events, _) = pump(&mut server, &mut [&mut second], |s, c| {
+            s.len() == 2 && !c.is_empty()
+        });
+        assert!(second.is_connected());
+        assert_eq!(
+            server_events[0],
+            NetEvent::Disconnected {
+                connection: first_id,
+                reason: DisconnectReason::Closed,
+            }
+        );
+        let NetEvent::Connected(second_id) = server_events[1] else {
+            panic!("{server_events:?}")
+        };
+        assert_ne!(first_id, second_id);
+
+        // Sequence numbers of the new connection start from scratch, the message must not be
+        // treated as an old duplicate.
+        second.send(Channel::Reliable, b"Second").unwrap();
+        let (server_events, _) = pump(&mut server, &mut [&mut second], |s, _| {
+            !messages(s, Channel::Reliable).is_empty()
+        });
+        assert_eq!(
+            server_events,
+            vec![NetEvent::Message {
+                connection: second_id,
+                channel: Channel::Reliable,
+                data: b"Second".to_vec(),
+            }]
+        );
+    }
+}
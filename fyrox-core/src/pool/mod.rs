@@ -692,6 +692,49 @@ where
         })
     }
 
+    /// Borrows mutable references of objects with the given handles at the same time. Unlike
+    /// [`borrow_two_mut`](Self::borrow_two_mut) and similar methods, the amount of handles is
+    /// arbitrary and the method does not panic - invalid and duplicate handles are skipped.
+    /// Returned pairs are sorted by the indices of the handles.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fyrox_core::pool::Pool;
+    /// let mut pool = Pool::<u32>::new();
+    /// let a = pool.spawn(1);
+    /// let b = pool.spawn(2);
+    /// for (_, value) in pool.try_borrow_many_mut(&[b, a, b]) {
+    ///     *value += 10;
+    /// }
+    /// assert_eq!((pool[a], pool[b]), (11, 12));
+    /// ```
+    #[inline]
+    pub fn try_borrow_many_mut(&mut self, handles: &[Handle<T>]) -> Vec<(Handle<T>, &mut T)> {
+        let mut handles = handles
+            .iter()
+            .filter(|handle| self.is_valid_handle(**handle))
+            .copied()
+            .collect::<Vec<_>>();
+        handles.sort_unstable_by_key(|handle| handle.index);
+        // Valid handles with the same index are equal.
+        handles.dedup();
+
+        let mut result = Vec::with_capacity(handles.len());
+        let mut records = self.records.iter_mut();
+        let mut next_index = 0;
+        for handle in handles {
+            let Some(record) = records.nth((handle.index - next_index) as usize) else {
+                break;
+            };
+            next_index = handle.index + 1;
+            if let Some(payload) = record.payload.as_mut() {
+                result.push((handle, payload));
+            }
+        }
+        result
+    }
+
     /// Borrows mutable references of objects at the same time. This method will succeed only
     /// if handles are unique (not equal). Borrowing multiple mutable references at the same
     /// time is useful in case if you need to mutate some objects at the same time.
@@ -0,0 +1,551 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Lightweight instrumentation profiler. It measures execution time of code blocks (scopes) on every
+//! thread and groups them into frames, the last N frames are stored in a ring buffer. Captured frames
+//! could be exported to [Chrome trace format](https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU),
+//! which could be viewed in `chrome://tracing`, [Perfetto](https://ui.perfetto.dev) and many other tools.
+//!
+//! Code blocks are instrumented using [`crate::profile_scope`] macro. The instrumentation is compiled in
+//! only in debug builds or when `enable_profiler` feature is enabled, otherwise the macro expands to
+//! nothing. Even when compiled in, scopes are not recorded until the profiler is enabled using
+//! [`Profiler::set_enabled`]. Log spans (see [`crate::log::Log::span`]) are recorded as scopes as well.
+//!
+//! ```rust
+//! use fyrox_core::{log::category, profile_scope, profiler::Profiler};
+//!
+//! fn update_ai() {
+//!     profile_scope!(category: category::GAME, "AI");
+//!     // ...
+//! }
+//!
+//! Profiler::set_enabled(true);
+//! for _ in 0..10 {
+//!     update_ai();
+//!     // The engine does this at the end of every rendered frame.
+//!     Profiler::new_frame();
+//! }
+//! Profiler::save_chrome_trace("trace.json").unwrap();
+//! # std::fs::remove_file("trace.json").unwrap();
+//! ```
+
+use crate::json::Value;
+use crate::{instant::Instant, parking_lot::Mutex};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::VecDeque,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        LazyLock,
+    },
+    time::Duration,
+};
+
+/// Default amount of frames stored in the profiler. See [`Profiler::set_frame_capacity`].
+pub const DEFAULT_FRAME_CAPACITY: usize = 120;
+
+/// `true` if the instrumentation ([`crate::profile_scope`] macro) is compiled in.
+pub const INSTRUMENTATION_ENABLED: bool = cfg!(any(feature = "enable_profiler", debug_assertions));
+
+/// Measured execution time of a code block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScopeRecord {
+    /// Name of the scope.
+    pub name: Cow<'static, str>,
+    /// Category of the scope, the engine uses the same categories as the log (see [`crate::log::category`]).
+    pub category: Cow<'static, str>,
+    /// Start time of the scope, relative to the start of the profiler.
+    pub start: Duration,
+    /// Execution time of the scope.
+    pub duration: Duration,
+    /// Nesting depth of the scope, top-level scopes have zero depth.
+    pub depth: u32,
+    /// Id of the thread on which the scope was executed. See [`ThreadInfo`].
+    pub thread: u32,
+}
+
+impl ScopeRecord {
+    /// Returns end time of the scope, relative to the start of the profiler.
+    pub fn end(&self) -> Duration {
+        self.start + self.duration
+    }
+}
+
+/// All scopes that were finished during a frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProfilerFrame {
+    /// Sequential index of the frame.
+    pub index: u64,
+    /// Start time of the frame, relative to the start of the profiler.
+    pub start: Duration,
+    /// Duration of the frame.
+    pub duration: Duration,
+    /// Id of the thread that has finished the frame (see [`Profiler::new_frame`]).
+    pub thread: u32,
+    /// Scopes of the frame sorted by thread and start time. Scopes that were started on other threads
+    /// during previous frames could start earlier than the frame itself.
+    pub scopes: Vec<ScopeRecord>,
+}
+
+impl ProfilerFrame {
+    /// Returns an iterator over the scopes that were executed on the given thread.
+    pub fn thread_scopes(&self, thread: u32) -> impl Iterator<Item = &ScopeRecord> {
+        self.scopes
+            .iter()
+            .filter(move |scope| scope.thread == thread)
+    }
+
+    /// Returns total execution time of all the scopes with the given name.
+    pub fn total_time(&self, name: &str) -> Duration {
+        self.scopes
+            .iter()
+            .filter(|scope| scope.name == name)
+            .map(|scope| scope.duration)
+            .sum()
+    }
+}
+
+/// A thread that has executed at least one scope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThreadInfo {
+    /// Id of the thread, it is assigned sequentially by the profiler.
+    pub id: u32,
+    /// Name of the thread.
+    pub name: String,
+}
+
+/// A snapshot of the profiler data. See [`Profiler::capture`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProfilerCapture {
+    /// Known threads.
+    pub threads: Vec<ThreadInfo>,
+    /// Captured frames, from the oldest to the newest.
+    pub frames: Vec<ProfilerFrame>,
+}
+
+fn micros(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / 1000.0
+}
+
+impl ProfilerCapture {
+    /// Converts the capture to Chrome trace JSON. Every thread gets its own timeline, frames are
+    /// represented as `Frame N` events on the timeline of the thread that has finished them.
+    pub fn to_chrome_trace(&self) -> String {
+        let mut events = Vec::new();
+
+        for thread in self.threads.iter() {
+            events.push(Value::from([
+                ("name", Value::from("thread_name")),
+                ("ph", Value::from("M")),
+                ("pid", Value::from(1)),
+                ("tid", Value::from(thread.id)),
+                (
+                    "args",
+                    Value::from([("name", Value::from(thread.name.as_str()))]),
+                ),
+            ]));
+        }
+
+        for frame in self.frames.iter() {
+            events.push(Value::from([
+                ("name", Value::from(format!("Frame {}", frame.index))),
+                ("cat", Value::from("frame")),
+                ("ph", Value::from("X")),
+                ("ts", Value::from(micros(frame.start))),
+                ("dur", Value::from(micros(frame.duration))),
+                ("pid", Value::from(1)),
+                ("tid", Value::from(frame.thread)),
+            ]));
+
+            for scope in frame.scopes.iter() {
+                events.push(Value::from([
+                    ("name", Value::from(scope.name.as_ref())),
+                    ("cat", Value::from(scope.category.as_ref())),
+                    ("ph", Value::from("X")),
+                    ("ts", Value::from(micros(scope.start))),
+                    ("dur", Value::from(micros(scope.duration))),
+                    ("pid", Value::from(1)),
+                    ("tid", Value::from(scope.thread)),
+                ]));
+            }
+        }
+
+        Value::from([
+            ("traceEvents", Value::Array(events)),
+            ("displayTimeUnit", Value::from("ms")),
+        ])
+        .to_string()
+    }
+
+    /// Writes the capture in Chrome trace format to the given file.
+    pub fn save_chrome_trace<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_chrome_trace())
+    }
+}
+
+struct ProfilerState {
+    frame_capacity: usize,
+    frames: VecDeque<ProfilerFrame>,
+    current: Vec<ScopeRecord>,
+    frame_index: u64,
+    frame_start: Duration,
+    threads: Vec<ThreadInfo>,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static NEXT_THREAD_ID: AtomicU32 = AtomicU32::new(0);
+
+static EPOCH: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+static STATE: LazyLock<Mutex<ProfilerState>> = LazyLock::new(|| {
+    Mutex::new(ProfilerState {
+        frame_capacity: DEFAULT_FRAME_CAPACITY,
+        frames: Default::default(),
+        current: Default::default(),
+        frame_index: 0,
+        frame_start: Duration::default(),
+        threads: Default::default(),
+    })
+});
+
+fn now() -> Duration {
+    Instant::now() - *EPOCH
+}
+
+// Scopes are collected per thread and moved to the shared state only when a top-level scope is
+// finished, so nested scopes do not lock the shared state.
+struct ThreadBuffer {
+    id: u32,
+    depth: u32,
+    records: Vec<ScopeRecord>,
+}
+
+impl ThreadBuffer {
+    fn new() -> Self {
+        let id = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+        let name = std::thread::current()
+            .name()
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| format!("Thread {id}"));
+        STATE.lock().threads.push(ThreadInfo { id, name });
+        Self {
+            id,
+            depth: 0,
+            records: Default::default(),
+        }
+    }
+}
+
+thread_local! {
+    static THREAD_BUFFER: RefCell<ThreadBuffer> = RefCell::new(ThreadBuffer::new());
+}
+
+/// Measures execution time of a code block, the time is measured from the creation of the scope
+/// till its drop. See [`Profiler::scope`] for more info.
+#[must_use = "the scope is finished when dropped"]
+pub struct ProfileScope {
+    data: Option<(Cow<'static, str>, Cow<'static, str>, Duration)>,
+}
+
+impl ProfileScope {
+    pub(crate) fn inactive() -> Self {
+        Self { data: None }
+    }
+
+    /// Returns `true` if the scope is measured, `false` - if the profiler is disabled.
+    pub fn is_active(&self) -> bool {
+        self.data.is_some()
+    }
+}
+
+impl Drop for ProfileScope {
+    fn drop(&mut self) {
+        if let Some((category, name, start)) = self.data.take() {
+            let end = now();
+            THREAD_BUFFER.with_borrow_mut(|buffer| {
+                buffer.depth = buffer.depth.saturating_sub(1);
+                let record = ScopeRecord {
+                    name,
+                    category,
+                    start,
+                    duration: end.saturating_sub(start),
+                    depth: buffer.depth,
+                    thread: buffer.id,
+                };
+                buffer.records.push(record);
+                if buffer.depth == 0 {
+                    STATE.lock().current.append(&mut buffer.records);
+                }
+            });
+        }
+    }
+}
+
+/// See module docs.
+pub struct Profiler;
+
+impl Profiler {
+    /// Enables or disables recording of scopes. Enabling the profiler starts a new frame.
+    pub fn set_enabled(enabled: bool) {
+        if enabled && !ENABLED.load(Ordering::Relaxed) {
+            let mut state = STATE.lock();
+            state.current.clear();
+            state.frame_start = now();
+        }
+        ENABLED.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the profiler records scopes, `false` - otherwise.
+    pub fn is_enabled() -> bool {
+        ENABLED.load(Ordering::Relaxed)
+    }
+
+    /// Sets the maximum amount of frames stored in the profiler, the oldest frames are discarded.
+    pub fn set_frame_capacity(capacity: usize) {
+        let mut state = STATE.lock();
+        state.frame_capacity = capacity;
+        while state.frames.len() > capacity {
+            state.frames.pop_front();
+        }
+    }
+
+    /// Returns the maximum amount of frames stored in the profiler.
+    pub fn frame_capacity() -> usize {
+        STATE.lock().frame_capacity
+    }
+
+    /// Starts a new scope with the given category and name. The scope is finished when the returned
+    /// guard is dropped. Usually there's no need to call this method directly, [`crate::profile_scope`]
+    /// macro should be used instead, because it could be compiled out.
+    pub fn scope(
+        category: impl Into<Cow<'static, str>>,
+        name: impl Into<Cow<'static, str>>,
+    ) -> ProfileScope {
+        if !Self::is_enabled() {
+            return ProfileScope { data: None };
+        }
+
+        THREAD_BUFFER.with_borrow_mut(|buffer| buffer.depth += 1);
+
+        ProfileScope {
+            data: Some((category.into(), name.into(), now())),
+        }
+    }
+
+    /// Finishes current frame and puts it in the ring buffer of frames. The engine calls this method
+    /// at the end of [`Engine::render`](https://docs.rs/fyrox/latest/fyrox/engine/struct.Engine.html#method.render),
+    /// so there's no need to call it manually, unless the engine is used without graphics context.
+    pub fn new_frame() {
+        let thread = THREAD_BUFFER.with_borrow(|buffer| buffer.id);
+        let end = now();
+        let enabled = Self::is_enabled();
+
+        let mut state = STATE.lock();
+        let mut scopes = std::mem::take(&mut state.current);
+        let start = std::mem::replace(&mut state.frame_start, end);
+
+        if !enabled && scopes.is_empty() {
+            return;
+        }
+
+        scopes.sort_by_key(|scope| (scope.thread, scope.start, scope.depth));
+
+        let frame = ProfilerFrame {
+            index: state.frame_index,
+            start,
+            duration: end.saturating_sub(start),
+            thread,
+            scopes,
+        };
+        state.frame_index += 1;
+
+        state.frames.push_back(frame);
+        while state.frames.len() > state.frame_capacity {
+            state.frames.pop_front();
+        }
+    }
+
+    /// Returns a copy of the stored frames, from the oldest to the newest.
+    pub fn frames() -> Vec<ProfilerFrame> {
+        STATE.lock().frames.iter().cloned().collect()
+    }
+
+    /// Returns a copy of the newest stored frame.
+    pub fn last_frame() -> Option<ProfilerFrame> {
+        STATE.lock().frames.back().cloned()
+    }
+
+    /// Returns all the threads that have executed at least one scope.
+    pub fn threads() -> Vec<ThreadInfo> {
+        STATE.lock().threads.clone()
+    }
+
+    /// Returns a snapshot of all stored frames and known threads.
+    pub fn capture() -> ProfilerCapture {
+        let state = STATE.lock();
+        ProfilerCapture {
+            threads: state.threads.clone(),
+            frames: state.frames.iter().cloned().collect(),
+        }
+    }
+
+    /// Removes all stored frames.
+    pub fn clear() {
+        let mut state = STATE.lock();
+        state.frames.clear();
+        state.current.clear();
+    }
+
+    /// Writes all stored frames in Chrome trace format to the given file.
+    pub fn save_chrome_trace<P: AsRef<Path>>(path: P) -> std::io::Result<()> {
+        Self::capture().save_chrome_trace(path)
+    }
+}
+
+/// Measures execution time of the rest of the current block. The scope has an optional category
+/// and a name, see [`crate::profiler`] module docs for more info.
+///
+/// ```rust
+/// use fyrox_core::{log::category, profile_scope};
+///
+/// fn update() {
+///     profile_scope!("Update");
+///
+///     for _ in 0..10 {
+///         profile_scope!(category: category::PHYSICS, "Physics");
+///         // ...
+///     }
+/// }
+/// ```
+#[cfg(any(feature = "enable_profiler", debug_assertions))]
+#[macro_export]
+macro_rules! profile_scope {
+    (category: $category:expr, $name:expr) => {
+        let _profile_scope = $crate::profiler::Profiler::scope($category, $name);
+    };
+    ($name:expr) => {
+        $crate::profile_scope!(category: "", $name)
+    };
+}
+
+/// Measures execution time of the rest of the current block. The instrumentation is compiled out,
+/// use `enable_profiler` feature to enable it in release builds.
+#[cfg(not(any(feature = "enable_profiler", debug_assertions)))]
+#[macro_export]
+macro_rules! profile_scope {
+    (category: $category:expr, $name:expr) => {
+        // Keeps the arguments type-checked and prevents "unused" warnings, never executed.
+        if false {
+            let _ = ($category, $name);
+        }
+    };
+    ($name:expr) => {
+        $crate::profile_scope!(category: "", $name)
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::log::{category, Log};
+
+    #[test]
+    fn test_profiler_frames() {
+        Profiler::set_frame_capacity(3);
+        Profiler::set_enabled(true);
+
+        for _ in 0..5 {
+            {
+                let _outer = Profiler::scope(category::GAME, "Outer");
+                let _inner = Profiler::scope(category::GAME, "Inner");
+                let _span = Log::span(category::GAME, "Span");
+            }
+            std::thread::spawn(|| {
+                let _job = Profiler::scope(category::TASK, "Job");
+            })
+            .join()
+            .unwrap();
+            Profiler::new_frame();
+        }
+
+        Profiler::set_enabled(false);
+
+        let frames = Profiler::frames();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[1].index, frames[0].index + 1);
+        assert_eq!(frames[2].index, frames[1].index + 1);
+
+        let frame = frames.last().unwrap();
+        let find = |name: &str| frame.scopes.iter().find(|s| s.name == name).unwrap();
+        let outer = find("Outer");
+        let inner = find("Inner");
+        let job = find("Job");
+        let span = find("Span");
+        assert_eq!(outer.depth, 0);
+        assert_eq!(inner.depth, 1);
+        assert_eq!(span.depth, 2);
+        assert!(inner.start >= outer.start && inner.end() <= outer.end());
+        assert_eq!(outer.thread, frame.thread);
+        assert_ne!(job.thread, outer.thread);
+        assert!(Profiler::threads().iter().any(|t| t.id == job.thread));
+
+        Profiler::set_frame_capacity(1);
+        assert_eq!(Profiler::frames().len(), 1);
+        Profiler::set_frame_capacity(DEFAULT_FRAME_CAPACITY);
+    }
+
+    #[test]
+    fn test_chrome_trace() {
+        let capture = ProfilerCapture {
+            threads: vec![ThreadInfo {
+                id: 0,
+                name: "main".to_string(),
+            }],
+            frames: vec![ProfilerFrame {
+                index: 0,
+                start: Duration::from_millis(1),
+                duration: Duration::from_millis(16),
+                thread: 0,
+                scopes: vec![ScopeRecord {
+                    name: "Update \"scene\"".into(),
+                    category: category::SCENE.into(),
+                    start: Duration::from_millis(2),
+                    duration: Duration::from_micros(1500),
+                    depth: 0,
+                    thread: 0,
+                }],
+            }],
+        };
+
+        let trace = capture.to_chrome_trace().parse::<Value>().unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0]["ph"], Value::from("M"));
+        assert_eq!(events[0]["args"]["name"], Value::from("main"));
+        assert_eq!(events[1]["name"], Value::from("Frame 0"));
+        assert_eq!(events[1]["dur"], Value::from(16000.0));
+        assert_eq!(events[2]["name"], Value::from("Update \"scene\""));
+        assert_eq!(events[2]["cat"], Value::from("scene"));
+        assert_eq!(events[2]["ts"], Value::from(2000.0));
+        assert_eq!(events[2]["dur"], Value::from(1500.0));
+    }
+}
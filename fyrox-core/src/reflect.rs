@@ -23,6 +23,10 @@
 mod external_impls;
 mod std_impls;
 
+pub mod patch;
+mod registry;
+pub mod serde_bridge;
+
 pub use fyrox_core_derive::Reflect;
 use std::{
     any::{Any, TypeId},
@@ -333,6 +337,11 @@ pub trait ReflectList: ReflectArray {
         index: usize,
         value: Box<dyn Reflect>,
     ) -> Result<(), Box<dyn Reflect>>;
+
+    /// Returns type id of the items of the list, if known.
+    fn reflect_item_type_id(&self) -> Option<TypeId> {
+        None
+    }
 }
 
 pub trait ReflectHashMap: Reflect {
@@ -353,6 +362,16 @@ pub trait ReflectHashMap: Reflect {
     fn reflect_get_at(&self, index: usize) -> Option<(&dyn Reflect, &dyn Reflect)>;
     fn reflect_get_at_mut(&mut self, index: usize) -> Option<(&dyn Reflect, &mut dyn Reflect)>;
     fn reflect_remove(&mut self, key: &dyn Reflect, func: &mut dyn FnMut(Option<Box<dyn Reflect>>));
+
+    /// Returns type id of the keys of the map, if known.
+    fn reflect_key_type_id(&self) -> Option<TypeId> {
+        None
+    }
+
+    /// Returns type id of the values of the map, if known.
+    fn reflect_value_type_id(&self) -> Option<TypeId> {
+        None
+    }
 }
 
 pub trait ReflectInheritableVariable: Reflect + Debug {
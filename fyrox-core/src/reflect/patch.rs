@@ -0,0 +1,887 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Reflection-driven diffing and patching of arbitrary object graphs. See [`diff`] and [`apply_patch`]
+//! for more info.
+
+use crate::{
+    algebra::{Matrix2, Matrix3, Matrix4, UnitComplex, UnitQuaternion, Vector2, Vector3, Vector4},
+    color::Color,
+    pool::ErasedHandle,
+    reflect::{
+        prelude::*,
+        registry::{self, TypeMap},
+    },
+    sstorage::ImmutableString,
+    uuid::Uuid,
+};
+use std::{
+    any::{Any, TypeId},
+    fmt::{Display, Formatter},
+    path::PathBuf,
+    time::Duration,
+};
+
+/// A single operation of a [`PatchChange`].
+#[derive(Debug)]
+pub enum PatchOp {
+    /// Replaces a value at the path with the new one.
+    Set(Box<dyn Reflect>),
+    /// Pushes a new item at the end of a list at the path.
+    Push(Box<dyn Reflect>),
+    /// Removes an item with the given index from a list at the path.
+    Remove(usize),
+    /// Inserts (or replaces) a key-value pair in a hash map at the path.
+    Insert {
+        /// A key of the pair.
+        key: Box<dyn Reflect>,
+        /// A value of the pair.
+        value: Box<dyn Reflect>,
+    },
+    /// Removes a key-value pair with the given key from a hash map at the path.
+    RemoveKey(Box<dyn Reflect>),
+}
+
+/// A change of a value at some path. Paths use the same syntax as [`ResolvePath`], an empty path
+/// means the root object itself.
+#[derive(Debug)]
+pub struct PatchChange {
+    /// A path to the changed value.
+    pub path: String,
+    /// An operation to be done at the path.
+    pub op: PatchOp,
+}
+
+/// A set of changes that transforms one object to another. Could be created using [`diff`] and
+/// applied using [`apply_patch`].
+#[derive(Debug, Default)]
+pub struct Patch {
+    /// A list of changes in the order of their application.
+    pub changes: Vec<PatchChange>,
+    /// A list of paths with differences, that cannot be expressed as a change. Usually it means that
+    /// the type of the value at the path is not registered by [`register_value_type`], so it cannot
+    /// be cloned.
+    pub unsupported: Vec<String>,
+}
+
+impl Patch {
+    /// Returns `true` if the patch has no changes and no unsupported differences, which means that
+    /// the compared objects are equal.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.unsupported.is_empty()
+    }
+
+    /// Returns an iterator over the paths of every change of the patch.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.changes.iter().map(|change| change.path.as_str())
+    }
+
+    /// Applies the patch to the given object. See [`apply_patch`] for more info.
+    pub fn apply(&self, target: &mut dyn Reflect) -> Result<(), PatchError> {
+        apply_patch(target, self)
+    }
+}
+
+/// An error, that may occur during patch application.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// A value of a change cannot be cloned, because its type is not registered.
+    NotCloneable {
+        /// A path of the change.
+        path: String,
+        /// A name of the type of the value.
+        type_name: String,
+    },
+    /// A path of a change cannot be resolved.
+    InvalidPath {
+        /// A path of the change.
+        path: String,
+        /// A reason of the failure.
+        reason: String,
+    },
+    /// A value at the path refused to accept a new value (most likely because of a type mismatch).
+    InvalidValue {
+        /// A path of the change.
+        path: String,
+    },
+    /// A list operation was applied to a value, that is not a list.
+    NotAList {
+        /// A path of the change.
+        path: String,
+    },
+    /// A hash map operation was applied to a value, that is not a hash map.
+    NotAHashMap {
+        /// A path of the change.
+        path: String,
+    },
+    /// An item with the given index does not exist in a list.
+    NoSuchItem {
+        /// A path of the change.
+        path: String,
+        /// An index of the item.
+        index: usize,
+    },
+}
+
+impl Display for PatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatchError::NotCloneable { path, type_name } => {
+                write!(
+                    f,
+                    "Value of type {type_name} at {path} cannot be cloned. Register it using \
+                    register_value_type."
+                )
+            }
+            PatchError::InvalidPath { path, reason } => {
+                write!(f, "Unable to resolve path {path}. Reason: {reason}")
+            }
+            PatchError::InvalidValue { path } => {
+                write!(f, "Value at {path} does not accept the new value.")
+            }
+            PatchError::NotAList { path } => {
+                write!(f, "Value at {path} is not a list.")
+            }
+            PatchError::NotAHashMap { path } => {
+                write!(f, "Value at {path} is not a hash map.")
+            }
+            PatchError::NoSuchItem { path, index } => {
+                write!(f, "There's no item with index {index} in a list at {path}.")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct ValueType {
+    clone: fn(&dyn Any) -> Option<Box<dyn Reflect>>,
+    eq: fn(&dyn Any, &dyn Any) -> bool,
+}
+
+fn value_type<T: Reflect + Clone + PartialEq>() -> ValueType {
+    ValueType {
+        clone: |value| {
+            value
+                .downcast_ref::<T>()
+                .map(|value| Box::new(value.clone()) as Box<dyn Reflect>)
+        },
+        eq: |a, b| match (a.downcast_ref::<T>(), b.downcast_ref::<T>()) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        },
+    }
+}
+
+fn add_value_type<T: Reflect + Clone + PartialEq>(types: &mut TypeMap) {
+    types.entry(TypeId::of::<T>()).or_default().value_type = Some(value_type::<T>());
+}
+
+/// Registers built-in value types in the shared type registry.
+pub(crate) fn register_default_types(types: &mut TypeMap) {
+    macro_rules! value_types {
+        ($($ty:ty),* $(,)?) => {
+            $(add_value_type::<$ty>(types);)*
+        };
+    }
+
+    value_types![
+        f32,
+        f64,
+        usize,
+        u8,
+        u16,
+        u32,
+        u64,
+        isize,
+        i8,
+        i16,
+        i32,
+        i64,
+        bool,
+        char,
+        String,
+        ImmutableString,
+        PathBuf,
+        Duration,
+        Uuid,
+        Color,
+        ErasedHandle,
+        Vector2<f32>,
+        Vector3<f32>,
+        Vector4<f32>,
+        Vector2<f64>,
+        Vector3<f64>,
+        Vector4<f64>,
+        Vector2<i32>,
+        Vector3<i32>,
+        Vector4<i32>,
+        Vector2<u32>,
+        Vector3<u32>,
+        Vector4<u32>,
+        Vector2<usize>,
+        Vector3<usize>,
+        Vector4<usize>,
+        UnitQuaternion<f32>,
+        UnitQuaternion<f64>,
+        UnitComplex<f32>,
+        UnitComplex<f64>,
+        Matrix2<f32>,
+        Matrix3<f32>,
+        Matrix4<f32>,
+        Matrix2<f64>,
+        Matrix3<f64>,
+        Matrix4<f64>,
+    ];
+}
+
+/// Registers a type, whose values will be compared and copied as a whole by [`diff`] and
+/// [`apply_patch`]. Primitives, strings, Uuid, colors, vectors, quaternions and matrices are
+/// registered by default. Register your own types, if they're opaque to reflection (have no
+/// reflected fields), or if they're enums whose variant could change.
+///
+/// ```rust
+/// use fyrox_core::reflect::{patch::register_value_type, prelude::*};
+///
+/// #[derive(Reflect, Clone, PartialEq, Debug)]
+/// enum Shape {
+///     Sphere { radius: f32 },
+///     Cuboid { size: f32 },
+/// }
+///
+/// register_value_type::<Shape>();
+/// ```
+pub fn register_value_type<T: Reflect + Clone + PartialEq>() {
+    registry::write(add_value_type::<T>);
+}
+
+/// Returns `true` if the given type was registered by [`register_value_type`].
+pub fn is_value_type_registered<T: Reflect>() -> bool {
+    registry::read(TypeId::of::<T>(), |entry| entry.value_type).is_some()
+}
+
+/// Returns type id of the actual value behind the reflection. It is not the same as `type_id` of
+/// the `dyn Reflect`, because some wrappers (such as inheritable variables) are transparent for
+/// reflection.
+fn actual_type_id(value: &dyn Reflect) -> TypeId {
+    let mut type_id = TypeId::of::<()>();
+    value.as_any(&mut |any| type_id = any.type_id());
+    type_id
+}
+
+/// Tries to clone the given value using the registered value types.
+pub fn try_clone_value(value: &dyn Reflect) -> Option<Box<dyn Reflect>> {
+    let mut result = None;
+    value.as_any(&mut |any| {
+        if let Some(value_type) = registry::read(any.type_id(), |entry| entry.value_type) {
+            result = (value_type.clone)(any);
+        }
+    });
+    result
+}
+
+/// Compares values of the same registered type, returns `None` if the type is not registered.
+fn values_equal(a: &dyn Reflect, b: &dyn Reflect) -> Option<bool> {
+    let mut result = None;
+    a.as_any(&mut |a| {
+        b.as_any(&mut |b| {
+            if let Some(value_type) = registry::read(a.type_id(), |entry| entry.value_type) {
+                result = Some((value_type.eq)(a, b));
+            }
+        })
+    });
+    result
+}
+
+fn is_string_key(key: &dyn Reflect) -> bool {
+    let mut is_string = false;
+    key.downcast_ref::<String>(&mut |string| is_string |= string.is_some());
+    key.downcast_ref::<ImmutableString>(&mut |string| is_string |= string.is_some());
+    is_string
+}
+
+fn field_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+struct Differ {
+    patch: Patch,
+}
+
+impl Differ {
+    fn set(&mut self, path: &str, value: &dyn Reflect) {
+        match try_clone_value(value) {
+            Some(value) => self.patch.changes.push(PatchChange {
+                path: path.to_string(),
+                op: PatchOp::Set(value),
+            }),
+            None => self.patch.unsupported.push(path.to_string()),
+        }
+    }
+
+    fn diff(&mut self, path: &str, a: &dyn Reflect, b: &dyn Reflect) {
+        if actual_type_id(a) != actual_type_id(b) {
+            self.set(path, b);
+            return;
+        }
+
+        if let Some(equal) = values_equal(a, b) {
+            if !equal {
+                self.set(path, b);
+            }
+            return;
+        }
+
+        let mut done = false;
+        a.as_inheritable_variable(&mut |a_variable| {
+            b.as_inheritable_variable(&mut |b_variable| match (a_variable, b_variable) {
+                (Some(a), Some(b)) => {
+                    self.diff(path, a.inner_value_ref(), b.inner_value_ref());
+                    done = true;
+                }
+                (Some(a), None) => {
+                    self.diff(path, a.inner_value_ref(), b);
+                    done = true;
+                }
+                (None, Some(b)) => {
+                    self.diff(path, a, b.inner_value_ref());
+                    done = true;
+                }
+                (None, None) => (),
+            })
+        });
+        if done {
+            return;
+        }
+
+        a.as_array(&mut |a_array| {
+            b.as_array(&mut |b_array| {
+                if let (Some(a_array), Some(b_array)) = (a_array, b_array) {
+                    self.diff_arrays(path, a, a_array, b_array);
+                    done = true;
+                }
+            })
+        });
+        if done {
+            return;
+        }
+
+        a.as_hash_map(&mut |a_map| {
+            b.as_hash_map(&mut |b_map| {
+                if let (Some(a_map), Some(b_map)) = (a_map, b_map) {
+                    self.diff_hash_maps(path, a_map, b_map);
+                    done = true;
+                }
+            })
+        });
+        if done {
+            return;
+        }
+
+        a.fields_info(&mut |a_fields| {
+            b.fields_info(&mut |b_fields| {
+                let same_layout = a_fields.len() == b_fields.len()
+                    && a_fields
+                        .iter()
+                        .zip(b_fields.iter())
+                        .all(|(a, b)| a.name == b.name);
+
+                if !same_layout {
+                    // Different set of fields means different enum variants.
+                    self.set(path, b);
+                } else if a_fields.is_empty() {
+                    // Opaque value, the only thing that can be done here is to compare debug
+                    // representations.
+                    if format!("{a:?}") != format!("{b:?}") {
+                        self.set(path, b);
+                    }
+                } else {
+                    for (a_field, b_field) in a_fields.iter().zip(b_fields.iter()) {
+                        self.diff(
+                            &field_path(path, a_field.name),
+                            a_field.reflect_value,
+                            b_field.reflect_value,
+                        );
+                    }
+                }
+            })
+        });
+    }
+
+    fn diff_arrays(
+        &mut self,
+        path: &str,
+        a: &dyn Reflect,
+        a_array: &dyn ReflectArray,
+        b_array: &dyn ReflectArray,
+    ) {
+        let a_len = a_array.reflect_len();
+        let b_len = b_array.reflect_len();
+
+        for i in 0..a_len.min(b_len) {
+            if let (Some(a_item), Some(b_item)) =
+                (a_array.reflect_index(i), b_array.reflect_index(i))
+            {
+                self.diff(&format!("{path}[{i}]"), a_item, b_item);
+            }
+        }
+
+        if a_len == b_len {
+            return;
+        }
+
+        let mut is_list = false;
+        a.as_list(&mut |list| is_list = list.is_some());
+        if !is_list {
+            self.patch.unsupported.push(path.to_string());
+            return;
+        }
+
+        for i in b_len..a_len {
+            // Remove from the end, so the indices of the rest of the items will stay the same.
+            self.patch.changes.push(PatchChange {
+                path: path.to_string(),
+                op: PatchOp::Remove(a_len - 1 - (i - b_len)),
+            });
+        }
+
+        for i in a_len..b_len {
+            let Some(item) = b_array.reflect_index(i) else {
+                continue;
+            };
+
+            match try_clone_value(item) {
+                Some(item) => self.patch.changes.push(PatchChange {
+                    path: path.to_string(),
+                    op: PatchOp::Push(item),
+                }),
+                None => self.patch.unsupported.push(format!("{path}[{i}]")),
+            }
+        }
+    }
+
+    fn insert(&mut self, path: &str, key: &dyn Reflect, value: &dyn Reflect) {
+        match (try_clone_value(key), try_clone_value(value)) {
+            (Some(key), Some(value)) => self.patch.changes.push(PatchChange {
+                path: path.to_string(),
+                op: PatchOp::Insert { key, value },
+            }),
+            _ => self.patch.unsupported.push(format!("{path}[{key:?}]")),
+        }
+    }
+
+    fn diff_hash_maps(
+        &mut self,
+        path: &str,
+        a_map: &dyn ReflectHashMap,
+        b_map: &dyn ReflectHashMap,
+    ) {
+        for i in 0..a_map.reflect_len() {
+            let Some((key, _)) = a_map.reflect_get_at(i) else {
+                continue;
+            };
+
+            let mut exists = false;
+            b_map.reflect_get(key, &mut |value| exists = value.is_some());
+            if !exists {
+                match try_clone_value(key) {
+                    Some(key) => self.patch.changes.push(PatchChange {
+                        path: path.to_string(),
+                        op: PatchOp::RemoveKey(key),
+                    }),
+                    None => self.patch.unsupported.push(format!("{path}[{key:?}]")),
+                }
+            }
+        }
+
+        for i in 0..b_map.reflect_len() {
+            let Some((key, b_value)) = b_map.reflect_get_at(i) else {
+                continue;
+            };
+
+            a_map.reflect_get(key, &mut |a_value| match a_value {
+                Some(a_value) => {
+                    let mut key_path = None;
+                    if is_string_key(key) {
+                        let key_str = format!("{key:?}");
+                        // Strip quotes of the Debug representation.
+                        let key_str = &key_str[1..key_str.len() - 1];
+                        if !key_str.contains([']', '\\']) {
+                            key_path = Some(format!("{path}[{key_str}]"));
+                        }
+                    }
+
+                    match key_path {
+                        Some(key_path) => self.diff(&key_path, a_value, b_value),
+                        None => {
+                            // Paths cannot address items with such keys, so compare the values
+                            // separately and replace the entire pair if there's any difference.
+                            if !diff(a_value, b_value).is_empty() {
+                                self.insert(path, key, b_value);
+                            }
+                        }
+                    }
+                }
+                None => self.insert(path, key, b_value),
+            });
+        }
+    }
+}
+
+/// Compares two objects of the same type and returns a patch, that transforms `a` to `b` when
+/// applied using [`apply_patch`]. The comparison is done recursively using reflection: structures
+/// are compared field-by-field, lists and arrays item-by-item, hash maps key-by-key and inheritable
+/// variables are compared by their inner values.
+///
+/// Values of types registered by [`register_value_type`] are compared and copied as a whole.
+/// Differences that cannot be expressed as a change (for example, a changed value of unregistered
+/// opaque type) are listed in [`Patch::unsupported`].
+///
+/// ```rust
+/// use fyrox_core::reflect::{patch, prelude::*};
+///
+/// #[derive(Reflect, Clone, Debug, Default)]
+/// struct Foo {
+///     name: String,
+///     values: Vec<u32>,
+/// }
+///
+/// let a = Foo::default();
+/// let b = Foo {
+///     name: "Foo".to_string(),
+///     values: vec![1, 2, 3],
+/// };
+///
+/// let patch = patch::diff(&a, &b);
+///
+/// let mut c = a.clone();
+/// patch::apply_patch(&mut c, &patch).unwrap();
+/// assert_eq!(c.name, b.name);
+/// assert_eq!(c.values, b.values);
+/// ```
+pub fn diff(a: &dyn Reflect, b: &dyn Reflect) -> Patch {
+    let mut differ = Differ {
+        patch: Patch::default(),
+    };
+    differ.diff("", a, b);
+    differ.patch
+}
+
+fn clone_for_path(path: &str, value: &dyn Reflect) -> Result<Box<dyn Reflect>, PatchError> {
+    try_clone_value(value).ok_or_else(|| PatchError::NotCloneable {
+        path: path.to_string(),
+        type_name: value.type_name().to_string(),
+    })
+}
+
+fn with_value_at_path(
+    target: &mut dyn Reflect,
+    path: &str,
+    func: &mut dyn FnMut(&mut dyn Reflect) -> Result<(), PatchError>,
+) -> Result<(), PatchError> {
+    if path.is_empty() {
+        return func(target);
+    }
+
+    let mut result = Ok(());
+    target.resolve_path_mut(path, &mut |value| {
+        result = match value {
+            Ok(value) => func(value),
+            Err(reason) => Err(PatchError::InvalidPath {
+                path: path.to_string(),
+                reason: reason.to_string(),
+            }),
+        }
+    });
+    result
+}
+
+fn apply_change(target: &mut dyn Reflect, change: &PatchChange) -> Result<(), PatchError> {
+    let path = change.path.as_str();
+    match &change.op {
+        PatchOp::Set(value) => {
+            let value = clone_for_path(path, &**value)?;
+            if path.is_empty() || path.ends_with(']') {
+                let mut value = Some(value);
+                with_value_at_path(target, path, &mut |target| {
+                    target.set(value.take().unwrap()).map(|_| ()).map_err(|_| {
+                        PatchError::InvalidValue {
+                            path: path.to_string(),
+                        }
+                    })
+                })
+            } else {
+                // Use field setter, so custom property setters will be called.
+                let mut result = Ok(());
+                target.set_field_by_path(path, value, &mut |r| {
+                    result = match r {
+                        Ok(_) => Ok(()),
+                        Err(SetFieldByPathError::InvalidPath { reason, .. }) => {
+                            Err(PatchError::InvalidPath {
+                                path: path.to_string(),
+                                reason: reason.to_string(),
+                            })
+                        }
+                        Err(SetFieldByPathError::InvalidValue(_)) => {
+                            Err(PatchError::InvalidValue {
+                                path: path.to_string(),
+                            })
+                        }
+                    }
+                });
+                result
+            }
+        }
+        PatchOp::Push(value) => {
+            let mut value = Some(clone_for_path(path, &**value)?);
+            with_value_at_path(target, path, &mut |target| {
+                let mut result = Err(PatchError::NotAList {
+                    path: path.to_string(),
+                });
+                target.as_list_mut(&mut |list| {
+                    if let Some(list) = list {
+                        result = list.reflect_push(value.take().unwrap()).map_err(|_| {
+                            PatchError::InvalidValue {
+                                path: path.to_string(),
+                            }
+                        });
+                    }
+                });
+                result
+            })
+        }
+        PatchOp::Remove(index) => with_value_at_path(target, path, &mut |target| {
+            let mut result = Err(PatchError::NotAList {
+                path: path.to_string(),
+            });
+            target.as_list_mut(&mut |list| {
+                if let Some(list) = list {
+                    result = match list.reflect_remove(*index) {
+                        Some(_) => Ok(()),
+                        None => Err(PatchError::NoSuchItem {
+                            path: path.to_string(),
+                            index: *index,
+                        }),
+                    };
+                }
+            });
+            result
+        }),
+        PatchOp::Insert { key, value } => {
+            let mut pair = Some((
+                clone_for_path(path, &**key)?,
+                clone_for_path(path, &**value)?,
+            ));
+            with_value_at_path(target, path, &mut |target| {
+                let mut result = Err(PatchError::NotAHashMap {
+                    path: path.to_string(),
+                });
+                target.as_hash_map_mut(&mut |map| {
+                    if let Some(map) = map {
+                        let (key, value) = pair.take().unwrap();
+                        map.reflect_insert(key, value);
+                        result = Ok(());
+                    }
+                });
+                result
+            })
+        }
+        PatchOp::RemoveKey(key) => with_value_at_path(target, path, &mut |target| {
+            let mut result = Err(PatchError::NotAHashMap {
+                path: path.to_string(),
+            });
+            target.as_hash_map_mut(&mut |map| {
+                if let Some(map) = map {
+                    map.reflect_remove(&**key, &mut |_| ());
+                    result = Ok(());
+                }
+            });
+            result
+        }),
+    }
+}
+
+/// Applies every change of the patch to the given object in order. The application stops on the
+/// first error, leaving the object partially patched. Differences listed in [`Patch::unsupported`]
+/// are ignored.
+pub fn apply_patch(target: &mut dyn Reflect, patch: &Patch) -> Result<(), PatchError> {
+    for change in patch.changes.iter() {
+        apply_change(target, change)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{pool::Handle, variable::InheritableVariable};
+    use std::collections::HashMap;
+
+    #[derive(Reflect, Clone, Debug, PartialEq, Default)]
+    struct Item {
+        payload: u32,
+        name: String,
+    }
+
+    #[derive(Reflect, Clone, Debug, PartialEq)]
+    enum Shape {
+        Sphere { radius: f32 },
+        Cuboid { size: Vector3<f32> },
+    }
+
+    impl Default for Shape {
+        fn default() -> Self {
+            Self::Sphere { radius: 1.0 }
+        }
+    }
+
+    #[derive(Reflect, Clone, Debug, PartialEq, Default)]
+    struct Foo {
+        position: Vector3<f32>,
+        visible: InheritableVariable<bool>,
+        items: Vec<Item>,
+        numbers: InheritableVariable<Vec<u32>>,
+        named: HashMap<String, Item>,
+        indexed: HashMap<u32, u32>,
+        shape: Shape,
+        handle: Handle<Item>,
+        optional: Option<Item>,
+    }
+
+    fn make_b() -> Foo {
+        Foo {
+            position: Vector3::new(1.0, 2.0, 3.0),
+            visible: false.into(),
+            items: vec![
+                Item {
+                    payload: 1,
+                    name: "First".to_string(),
+                },
+                Item {
+                    payload: 2,
+                    name: "Second".to_string(),
+                },
+            ],
+            numbers: vec![1].into(),
+            named: [
+                ("Foo".to_string(), Item::default()),
+                (
+                    "Bar".to_string(),
+                    Item {
+                        payload: 123,
+                        name: Default::default(),
+                    },
+                ),
+            ]
+            .into(),
+            indexed: [(1, 2), (3, 4)].into(),
+            shape: Shape::Cuboid {
+                size: Vector3::repeat(2.0),
+            },
+            handle: Handle::new(1, 2),
+            optional: Some(Item::default()),
+        }
+    }
+
+    #[test]
+    fn test_diff_equal() {
+        let a = make_b();
+        assert!(diff(&a, &a.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_and_apply() {
+        register_value_type::<Shape>();
+        register_value_type::<Option<Item>>();
+
+        let mut a = Foo {
+            visible: true.into(),
+            items: vec![Item::default(); 3],
+            numbers: vec![1, 2, 3].into(),
+            named: [
+                ("Foo".to_string(), Item::default()),
+                ("Bar".to_string(), Item::default()),
+                ("Baz".to_string(), Item::default()),
+            ]
+            .into(),
+            indexed: [(1, 1), (2, 2)].into(),
+            ..Default::default()
+        };
+        let b = make_b();
+
+        let patch = diff(&a, &b);
+        assert!(patch.unsupported.is_empty(), "{:?}", patch.unsupported);
+        assert!(patch.paths().any(|p| p == "position"));
+        assert!(patch.paths().any(|p| p == "items[1].name"));
+        assert!(patch.paths().any(|p| p == "named[Bar].payload"));
+        assert!(patch.paths().any(|p| p == "handle.index"));
+
+        apply_patch(&mut a, &patch).unwrap();
+        assert_eq!(a, b);
+        assert!(a.visible.is_modified());
+        assert!(diff(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn test_diff_unsupported() {
+        #[derive(Reflect, Clone, Debug, PartialEq)]
+        enum Unregistered {
+            A(u32),
+            B(u32),
+        }
+
+        let a = Unregistered::A(1);
+        let patch = diff(&a, &Unregistered::B(1));
+        assert_eq!(patch.unsupported, vec![String::new()]);
+
+        let patch = diff(&a, &Unregistered::A(2));
+        assert_eq!(patch.paths().collect::<Vec<_>>(), vec!["A@0"]);
+    }
+
+    #[test]
+    fn test_apply_errors() {
+        let mut item = Item::default();
+        let patch = Patch {
+            changes: vec![PatchChange {
+                path: "foo".to_string(),
+                op: PatchOp::Set(Box::new(1u32)),
+            }],
+            unsupported: Default::default(),
+        };
+        assert!(matches!(
+            apply_patch(&mut item, &patch),
+            Err(PatchError::InvalidValue { .. })
+        ));
+
+        let patch = Patch {
+            changes: vec![PatchChange {
+                path: "payload".to_string(),
+                op: PatchOp::Remove(0),
+            }],
+            unsupported: Default::default(),
+        };
+        assert!(matches!(
+            apply_patch(&mut item, &patch),
+            Err(PatchError::NotAList { .. })
+        ));
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Registry of types, that is shared by [`super::patch`] and [`super::serde_bridge`]. Both of them
+//! need to handle values of some types as a whole (clone, compare, convert, create from scratch),
+//! so everything known about a type is stored in a single entry.
+
+use crate::reflect::{
+    patch::{self, ValueType},
+    serde_bridge::{self, Converter, EnumAdapter, OptionAdapter},
+    Reflect,
+};
+use fxhash::FxHashMap;
+use std::{
+    any::TypeId,
+    sync::{LazyLock, RwLock},
+};
+
+#[derive(Default)]
+pub(crate) struct TypeEntry {
+    /// Clones and compares values, see [`patch::register_value_type`].
+    pub value_type: Option<ValueType>,
+    /// Converts values to serde values and back, see [`serde_bridge::register_converter`].
+    pub converter: Option<Converter>,
+    /// Creates default values, see [`serde_bridge::register_type`].
+    pub constructor: Option<fn() -> Box<dyn Reflect>>,
+    /// Switches `Option<T>` between `None` and `Some`.
+    pub option: Option<OptionAdapter>,
+    /// Switches enumeration variants by name, see [`serde_bridge::register_enum`].
+    pub enumeration: Option<EnumAdapter>,
+    /// See [`serde_bridge::register_transparent_field_type`].
+    pub transparent: bool,
+}
+
+pub(crate) type TypeMap = FxHashMap<TypeId, TypeEntry>;
+
+static TYPES: LazyLock<RwLock<TypeMap>> = LazyLock::new(|| {
+    let mut types = TypeMap::default();
+    patch::register_default_types(&mut types);
+    serde_bridge::register_default_types(&mut types);
+    RwLock::new(types)
+});
+
+/// Fetches something from an entry of the given type. The lock is released before the result is
+/// returned, so the result could call registry functions.
+pub(crate) fn read<R>(type_id: TypeId, func: impl FnOnce(&TypeEntry) -> Option<R>) -> Option<R> {
+    TYPES.read().unwrap().get(&type_id).and_then(func)
+}
+
+/// Modifies the registry.
+pub(crate) fn write(func: impl FnOnce(&mut TypeMap)) {
+    func(&mut TYPES.write().unwrap())
+}
@@ -0,0 +1,1495 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! A bridge between [`Reflect`] and serde data model. It allows to serialize any reflectable value to
+//! any serde format (JSON, RON, YAML, etc.) and to deserialize it back into an existing value through
+//! the field-setting API without any hand-written `Serialize`/`Deserialize` implementations.
+//!
+//! The data model is represented by [`Value`]. Structures are written as maps of their reflected
+//! fields, lists and arrays as sequences, hash maps as maps with stringified keys, inheritable
+//! variables as their inner values and `Option`s as either `null` or their inner value. Leaf types
+//! (numbers, strings, vectors, etc.) are handled by converters, see [`register_converter`].
+//!
+//! Deserialization always modifies an existing value, so it works only with self-describing
+//! formats. Values that must be created from scratch (new list items, new hash map entries, changed
+//! `Option`s and enum variants) require their types to be registered using [`register_type`] or
+//! [`register_enum`].
+
+use crate::json::{Map, Value};
+use crate::{
+    algebra::{Quaternion, SMatrix, Scalar, UnitQuaternion},
+    reflect::{
+        prelude::*,
+        registry::{self, TypeMap},
+    },
+    sstorage::ImmutableString,
+    type_traits::TypeUuidProvider,
+    uuid::Uuid,
+    visitor::Blackboard,
+};
+use serde::{de::DeserializeSeed, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    any::{Any, TypeId},
+    fmt::{Display, Formatter},
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+/// An error, that may occur during deserialization of a reflectable value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReflectDeserializeError {
+    /// Source data is malformed.
+    Parse(String),
+    /// A value at the path cannot be converted from the source data.
+    Conversion {
+        /// A path to the value.
+        path: String,
+        /// A description of the problem.
+        message: String,
+    },
+    /// Source data has a field, that does not exist in the value.
+    UnknownField {
+        /// A path to the value.
+        path: String,
+        /// A name of the field.
+        field: String,
+    },
+    /// A type of the value at the path must be registered to be deserialized.
+    NotRegistered {
+        /// A path to the value.
+        path: String,
+        /// A name of the type.
+        type_name: String,
+    },
+    /// An enumeration does not have the variant with the given name.
+    UnknownVariant {
+        /// A path to the value.
+        path: String,
+        /// A type uuid of the enumeration.
+        type_uuid: Uuid,
+        /// A name of the variant.
+        variant: String,
+    },
+    /// Fixed-size array in the source data has different length.
+    LengthMismatch {
+        /// A path to the value.
+        path: String,
+        /// Length of the array.
+        expected: usize,
+        /// Length of the array in the source data.
+        actual: usize,
+    },
+    /// A value refused to accept a new value (most likely because of a type mismatch).
+    InvalidValue {
+        /// A path to the value.
+        path: String,
+    },
+    /// A path passed to [`apply_value_at_path`] does not point to a value.
+    InvalidPath {
+        /// The path.
+        path: String,
+        /// A description of the problem.
+        message: String,
+    },
+}
+
+impl Display for ReflectDeserializeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReflectDeserializeError::Parse(message) => {
+                write!(f, "Unable to parse the data. Reason: {message}")
+            }
+            ReflectDeserializeError::Conversion { path, message } => {
+                write!(
+                    f,
+                    "Unable to convert the value at {path}. Reason: {message}"
+                )
+            }
+            ReflectDeserializeError::UnknownField { path, field } => {
+                write!(f, "Value at {path} does not have the field {field}.")
+            }
+            ReflectDeserializeError::NotRegistered { path, type_name } => {
+                write!(
+                    f,
+                    "Type {type_name} of the value at {path} must be registered to be deserialized."
+                )
+            }
+            ReflectDeserializeError::UnknownVariant {
+                path,
+                type_uuid,
+                variant,
+            } => {
+                write!(
+                    f,
+                    "Enumeration {type_uuid} at {path} does not have the variant {variant}."
+                )
+            }
+            ReflectDeserializeError::LengthMismatch {
+                path,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "Array at {path} has length {expected}, but the source data has {actual} items."
+                )
+            }
+            ReflectDeserializeError::InvalidValue { path } => {
+                write!(f, "Value at {path} does not accept the new value.")
+            }
+            ReflectDeserializeError::InvalidPath { path, message } => {
+                write!(f, "Unable to resolve the path {path}. Reason: {message}")
+            }
+        }
+    }
+}
+
+type ToValueFn = dyn Fn(&dyn Any) -> Option<Value> + Send + Sync;
+type FromValueFn = dyn Fn(&Value, &Blackboard) -> Result<Box<dyn Reflect>, String> + Send + Sync;
+
+#[derive(Clone)]
+pub(crate) struct Converter {
+    to_value: Arc<ToValueFn>,
+    from_value: Arc<FromValueFn>,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct OptionAdapter {
+    none: fn() -> Box<dyn Reflect>,
+    some: fn() -> Box<dyn Reflect>,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct EnumAdapter {
+    type_uuid: Uuid,
+    variant_name: fn(&dyn Any) -> Option<String>,
+    from_variant_name: fn(&str) -> Option<Box<dyn Reflect>>,
+}
+
+fn add_converter<T: Reflect>(
+    types: &mut TypeMap,
+    to_value: impl Fn(&T) -> Value + Send + Sync + 'static,
+    from_value: impl Fn(&Value, &Blackboard) -> Result<T, String> + Send + Sync + 'static,
+) {
+    types.entry(TypeId::of::<T>()).or_default().converter = Some(Converter {
+        to_value: Arc::new(move |any| any.downcast_ref::<T>().map(&to_value)),
+        from_value: Arc::new(move |value, blackboard| {
+            from_value(value, blackboard).map(|value| Box::new(value) as Box<dyn Reflect>)
+        }),
+    });
+}
+
+fn add_type<T: Reflect + Default>(types: &mut TypeMap) {
+    types.entry(TypeId::of::<T>()).or_default().constructor = Some(|| Box::new(T::default()));
+    let option = types.entry(TypeId::of::<Option<T>>()).or_default();
+    option.constructor = Some(|| Box::new(None::<T>));
+    option.option = Some(OptionAdapter {
+        none: || Box::new(None::<T>),
+        some: || Box::new(Some(T::default())),
+    });
+}
+
+fn add_leaf<T: Reflect + Default + Leaf>(types: &mut TypeMap) {
+    add_converter::<T>(types, T::to_value, |value, _| T::from_value(value));
+    add_type::<T>(types);
+}
+
+fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn unexpected(expected: &str, value: &Value) -> String {
+    format!("expected {expected}, got {}", value_kind(value))
+}
+
+/// Built-in leaf types. Numbers could also be parsed from strings, because it is quite common for
+/// data exported from spreadsheets.
+trait Leaf: Sized {
+    fn to_value(&self) -> Value;
+    fn from_value(value: &Value) -> Result<Self, String>;
+}
+
+fn value_to_i128(value: &Value) -> Result<i128, String> {
+    match value {
+        Value::Number(number) => number
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| number.as_u64().map(i128::from))
+            .ok_or_else(|| format!("{number} is not an integer")),
+        Value::String(string) => string
+            .trim()
+            .parse::<i128>()
+            .map_err(|err| format!("{string} is not an integer: {err}")),
+        _ => Err(unexpected("an integer", value)),
+    }
+}
+
+fn value_to_f64(value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Number(number) => number
+            .as_f64()
+            .ok_or_else(|| format!("{number} is not a number")),
+        Value::String(string) => string
+            .trim()
+            .parse::<f64>()
+            .map_err(|err| format!("{string} is not a number: {err}")),
+        _ => Err(unexpected("a number", value)),
+    }
+}
+
+fn value_to_str(value: &Value) -> Result<&str, String> {
+    value.as_str().ok_or_else(|| unexpected("a string", value))
+}
+
+macro_rules! impl_integer_leaf {
+    ($($ty:ty),*) => {
+        $(
+            impl Leaf for $ty {
+                fn to_value(&self) -> Value {
+                    Value::from(*self)
+                }
+
+                fn from_value(value: &Value) -> Result<Self, String> {
+                    let integer = value_to_i128(value)?;
+                    <$ty>::try_from(integer)
+                        .map_err(|_| format!("{integer} is out of range of {}", stringify!($ty)))
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_leaf!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+macro_rules! impl_float_leaf {
+    ($($ty:ty),*) => {
+        $(
+            impl Leaf for $ty {
+                fn to_value(&self) -> Value {
+                    Value::from(*self)
+                }
+
+                fn from_value(value: &Value) -> Result<Self, String> {
+                    value_to_f64(value).map(|value| value as $ty)
+                }
+            }
+        )*
+    };
+}
+
+impl_float_leaf!(f32, f64);
+
+impl Leaf for bool {
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Bool(value) => Ok(*value),
+            Value::Number(_) => Ok(value_to_i128(value)? != 0),
+            Value::String(string) => string
+                .trim()
+                .parse::<bool>()
+                .map_err(|err| format!("{string} is not a boolean: {err}")),
+            _ => Err(unexpected("a boolean", value)),
+        }
+    }
+}
+
+impl Leaf for char {
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        let string = value_to_str(value)?;
+        let mut chars = string.chars();
+        match (chars.next(), chars.next()) {
+            (Some(char), None) => Ok(char),
+            _ => Err(format!("{string} is not a single character")),
+        }
+    }
+}
+
+impl Leaf for String {
+    fn to_value(&self) -> Value {
+        Value::String(self.clone())
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::String(string) => Ok(string.clone()),
+            // Spreadsheets tend to convert everything that looks like a number to numbers.
+            Value::Number(number) => Ok(number.to_string()),
+            _ => Err(unexpected("a string", value)),
+        }
+    }
+}
+
+impl Leaf for ImmutableString {
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        String::from_value(value).map(ImmutableString::new)
+    }
+}
+
+impl Leaf for PathBuf {
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string_lossy().replace('\\', "/"))
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        value_to_str(value).map(PathBuf::from)
+    }
+}
+
+impl Leaf for Uuid {
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        let string = value_to_str(value)?;
+        Uuid::parse_str(string).map_err(|err| format!("{string} is not a uuid: {err}"))
+    }
+}
+
+impl Leaf for Duration {
+    fn to_value(&self) -> Value {
+        Value::from(self.as_secs_f64())
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        Duration::try_from_secs_f64(value_to_f64(value)?).map_err(|err| err.to_string())
+    }
+}
+
+/// Vectors and matrices are written as flat arrays of their components in column-major order.
+impl<T, const R: usize, const C: usize> Leaf for SMatrix<T, R, C>
+where
+    T: Scalar + Copy + Leaf,
+{
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(|component| component.to_value()).collect())
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        let array = value
+            .as_array()
+            .ok_or_else(|| unexpected("an array", value))?;
+        if array.len() != R * C {
+            return Err(format!(
+                "expected {} components, got {}",
+                R * C,
+                array.len()
+            ));
+        }
+        let components = array
+            .iter()
+            .map(T::from_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_column_slice(&components))
+    }
+}
+
+/// Rotations are written as `[i, j, k, w]`.
+impl<T> Leaf for UnitQuaternion<T>
+where
+    T: nalgebra::RealField + Copy + Leaf,
+{
+    fn to_value(&self) -> Value {
+        self.coords.to_value()
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        let coords = nalgebra::Vector4::<T>::from_value(value)?;
+        Ok(UnitQuaternion::from_quaternion(Quaternion::from(coords)))
+    }
+}
+
+/// Registers built-in leaf types in the shared type registry.
+pub(crate) fn register_default_types(types: &mut TypeMap) {
+    macro_rules! leaves {
+        ($($ty:ty),* $(,)?) => {
+            $(add_leaf::<$ty>(types);)*
+        };
+    }
+
+    leaves!(
+        bool,
+        char,
+        u8,
+        u16,
+        u32,
+        u64,
+        usize,
+        i8,
+        i16,
+        i32,
+        i64,
+        isize,
+        f32,
+        f64,
+        String,
+        ImmutableString,
+        PathBuf,
+        Uuid,
+        Duration,
+    );
+
+    macro_rules! math {
+        ($($ty:ty),*) => {
+            $(
+                leaves!(
+                    SMatrix<$ty, 2, 1>,
+                    SMatrix<$ty, 3, 1>,
+                    SMatrix<$ty, 4, 1>,
+                    SMatrix<$ty, 2, 2>,
+                    SMatrix<$ty, 3, 3>,
+                    SMatrix<$ty, 4, 4>,
+                );
+            )*
+        };
+    }
+
+    math!(f32, f64, i32, u32, usize);
+    leaves!(UnitQuaternion<f32>, UnitQuaternion<f64>);
+}
+
+/// Registers a converter for a leaf type, that will be used to convert its values to [`Value`] and
+/// back. The conversion from a value has access to the blackboard, that was passed to the
+/// deserialization functions (it could be used to fetch a resource manager, for example). Primitives,
+/// strings, paths, uuids, vectors, matrices and quaternions have built-in converters. Registration of
+/// a converter also registers the type itself, see [`register_type`].
+pub fn register_converter<T: Reflect + Default>(
+    to_value: impl Fn(&T) -> Value + Send + Sync + 'static,
+    from_value: impl Fn(&Value, &Blackboard) -> Result<T, String> + Send + Sync + 'static,
+) {
+    registry::write(|types| {
+        add_converter(types, to_value, from_value);
+        add_type::<T>(types);
+    });
+}
+
+/// Registers a type, so its values could be created from scratch during deserialization, which is
+/// needed for new list items, new hash map entries and `Option<T>` values. `Option<T>` is registered
+/// as well.
+pub fn register_type<T: Reflect + Default>() {
+    registry::write(add_type::<T>);
+}
+
+/// Registers an enumeration. Enumerations are written as the variant name, if the variant has no
+/// fields, or as a map with a single entry, where the key is the variant name and the value is a map
+/// of the variant fields. `FromStr` and `AsRef<str>` implementations are used to create a variant by
+/// its name and to fetch the name of the current variant (both could be derived using `strum`).
+///
+/// ```rust
+/// use fyrox_core::{
+///     reflect::{prelude::*, serde_bridge},
+///     uuid_provider,
+/// };
+/// use std::str::FromStr;
+///
+/// #[derive(Reflect, Debug, Default)]
+/// enum Rarity {
+///     #[default]
+///     Common,
+///     Rare {
+///         drop_chance: f32,
+///     },
+/// }
+///
+/// uuid_provider!(Rarity = "0bd5ccf8-8d09-4a92-8f3a-7d3fb6ba3f3b");
+///
+/// impl AsRef<str> for Rarity {
+///     fn as_ref(&self) -> &str {
+///         match self {
+///             Rarity::Common => "Common",
+///             Rarity::Rare { .. } => "Rare",
+///         }
+///     }
+/// }
+///
+/// impl FromStr for Rarity {
+///     type Err = ();
+///
+///     fn from_str(s: &str) -> Result<Self, Self::Err> {
+///         match s {
+///             "Common" => Ok(Rarity::Common),
+///             "Rare" => Ok(Rarity::Rare { drop_chance: 0.0 }),
+///             _ => Err(()),
+///         }
+///     }
+/// }
+///
+/// serde_bridge::register_enum::<Rarity>();
+///
+/// let value = serde_bridge::to_value(&Rarity::Rare { drop_chance: 0.5 });
+/// assert_eq!(value.to_string(), r#"{"Rare":{"drop_chance":0.5}}"#);
+/// ```
+pub fn register_enum<T>()
+where
+    T: Reflect + Default + TypeUuidProvider + FromStr + AsRef<str>,
+{
+    registry::write(|types| {
+        types.entry(TypeId::of::<T>()).or_default().enumeration = Some(EnumAdapter {
+            type_uuid: T::type_uuid(),
+            variant_name: |any| any.downcast_ref::<T>().map(|v| v.as_ref().to_string()),
+            from_variant_name: |name| {
+                T::from_str(name)
+                    .ok()
+                    .map(|v| Box::new(v) as Box<dyn Reflect>)
+            },
+        });
+        add_type::<T>(types);
+    });
+}
+
+/// Marks a type as transparent for wrappers: any structure, that has only one reflected field of
+/// this type, will be written as the value of the field. For example, typed resources are just
+/// wrappers over untyped resources, so they could be written as a path to the resource.
+pub fn register_transparent_field_type<T: Reflect>() {
+    registry::write(|types| types.entry(TypeId::of::<T>()).or_default().transparent = true);
+}
+
+fn actual_type_id(value: &dyn Reflect) -> TypeId {
+    let mut type_id = TypeId::of::<()>();
+    value.as_any(&mut |any| type_id = any.type_id());
+    type_id
+}
+
+fn converter(type_id: TypeId) -> Option<Converter> {
+    registry::read(type_id, |entry| entry.converter.clone())
+}
+
+fn enum_adapter(type_id: TypeId) -> Option<EnumAdapter> {
+    registry::read(type_id, |entry| entry.enumeration)
+}
+
+fn option_adapter(type_id: TypeId) -> Option<OptionAdapter> {
+    registry::read(type_id, |entry| entry.option)
+}
+
+fn construct(type_id: TypeId) -> Option<Box<dyn Reflect>> {
+    registry::read(type_id, |entry| entry.constructor).map(|constructor| constructor())
+}
+
+fn is_transparent(value: &dyn Reflect) -> bool {
+    registry::read(actual_type_id(value), |entry| {
+        entry.transparent.then_some(())
+    })
+    .is_some()
+}
+
+fn variant_name(adapter: &EnumAdapter, value: &dyn Reflect) -> String {
+    let mut name = String::new();
+    value.as_any(&mut |any| name = (adapter.variant_name)(any).unwrap_or_default());
+    name
+}
+
+fn is_option_some(fields: &[FieldInfo]) -> bool {
+    fields.len() == 1 && fields[0].name == "Some@0"
+}
+
+/// Describes the shape of the fields of a value.
+enum Shape {
+    /// No reflected fields.
+    Opaque,
+    /// `Option::Some`.
+    Some,
+    /// A structure with exactly one field of transparent type.
+    Transparent(String),
+    /// A structure or an enumeration variant.
+    Fields,
+}
+
+fn shape(value: &dyn Reflect) -> Shape {
+    let mut shape = Shape::Fields;
+    value.fields_info(&mut |fields| {
+        shape = if fields.is_empty() {
+            Shape::Opaque
+        } else if is_option_some(fields) {
+            Shape::Some
+        } else if fields.len() == 1 && is_transparent(fields[0].reflect_value) {
+            Shape::Transparent(fields[0].name.to_string())
+        } else {
+            Shape::Fields
+        }
+    });
+    shape
+}
+
+fn key_to_string(key: &dyn Reflect) -> String {
+    match to_value(key) {
+        Value::String(string) => string,
+        value => value.to_string(),
+    }
+}
+
+fn fields_to_value(value: &dyn Reflect, strip_prefix: Option<&str>) -> Value {
+    let mut map = Map::new();
+    value.fields_info(&mut |fields| {
+        for field in fields {
+            let name = strip_prefix
+                .and_then(|prefix| field.name.strip_prefix(prefix))
+                .unwrap_or(field.name);
+            map.insert(name.to_string(), to_value(field.reflect_value));
+        }
+    });
+    Value::Object(map)
+}
+
+/// Converts a reflectable value to [`Value`]. Opaque values of types without converters are written
+/// as `null`.
+pub fn to_value(value: &dyn Reflect) -> Value {
+    let type_id = actual_type_id(value);
+
+    if let Some(converter) = converter(type_id) {
+        let mut result = Value::Null;
+        value.as_any(&mut |any| result = (converter.to_value)(any).unwrap_or_default());
+        return result;
+    }
+
+    if let Some(adapter) = enum_adapter(type_id) {
+        let name = variant_name(&adapter, value);
+        let prefix = format!("{name}@");
+        let fields = fields_to_value(value, Some(&prefix));
+        return if fields.as_object().is_some_and(|fields| fields.is_empty()) {
+            Value::String(name)
+        } else {
+            let mut map = Map::new();
+            map.insert(name, fields);
+            Value::Object(map)
+        };
+    }
+
+    let mut result = None;
+
+    value.as_inheritable_variable(&mut |variable| {
+        if let Some(variable) = variable {
+            result = Some(to_value(variable.inner_value_ref()));
+        }
+    });
+
+    if result.is_none() {
+        value.as_array(&mut |array| {
+            if let Some(array) = array {
+                result = Some(Value::Array(
+                    (0..array.reflect_len())
+                        .filter_map(|i| array.reflect_index(i))
+                        .map(to_value)
+                        .collect(),
+                ));
+            }
+        });
+    }
+
+    if result.is_none() {
+        value.as_hash_map(&mut |map| {
+            if let Some(map) = map {
+                result = Some(Value::Object(
+                    (0..map.reflect_len())
+                        .filter_map(|i| map.reflect_get_at(i))
+                        .map(|(key, value)| (key_to_string(key), to_value(value)))
+                        .collect(),
+                ));
+            }
+        });
+    }
+
+    if let Some(result) = result {
+        return result;
+    }
+
+    match shape(value) {
+        Shape::Opaque => Value::Null,
+        Shape::Some => {
+            let mut result = Value::Null;
+            value.field("Some@0", &mut |inner| {
+                result = inner.map(to_value).unwrap_or_default()
+            });
+            result
+        }
+        Shape::Transparent(name) => {
+            let mut result = Value::Null;
+            value.field(&name, &mut |inner| {
+                result = inner.map(to_value).unwrap_or_default()
+            });
+            result
+        }
+        Shape::Fields => fields_to_value(value, None),
+    }
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+fn not_registered(path: &str, value: &dyn Reflect) -> ReflectDeserializeError {
+    ReflectDeserializeError::NotRegistered {
+        path: path.to_string(),
+        type_name: value.type_name().to_string(),
+    }
+}
+
+fn conversion_error(path: &str, message: String) -> ReflectDeserializeError {
+    ReflectDeserializeError::Conversion {
+        path: path.to_string(),
+        message,
+    }
+}
+
+fn invalid_value(path: &str) -> ReflectDeserializeError {
+    ReflectDeserializeError::InvalidValue {
+        path: path.to_string(),
+    }
+}
+
+fn set(
+    target: &mut dyn Reflect,
+    path: &str,
+    value: Box<dyn Reflect>,
+) -> Result<(), ReflectDeserializeError> {
+    target
+        .set(value)
+        .map(|_| ())
+        .map_err(|_| invalid_value(path))
+}
+
+enum FieldUpdate {
+    Unchanged,
+    Leaf(Result<Box<dyn Reflect>, ReflectDeserializeError>),
+    Compound,
+}
+
+struct Applier<'a> {
+    blackboard: &'a Blackboard,
+}
+
+impl Applier<'_> {
+    /// Converts a value of a leaf type, returns `None` if the type has no converter.
+    fn convert(
+        &self,
+        path: &str,
+        type_id: TypeId,
+        value: &Value,
+    ) -> Option<Result<Box<dyn Reflect>, ReflectDeserializeError>> {
+        converter(type_id).map(|converter| {
+            (converter.from_value)(value, self.blackboard)
+                .map_err(|message| conversion_error(path, message))
+        })
+    }
+
+    /// Applies a value to a field of the `owner`. Leaf values are set using [`Reflect::set_field`],
+    /// so custom property setters will be called.
+    fn apply_field(
+        &self,
+        owner: &mut dyn Reflect,
+        owner_path: &str,
+        name: &str,
+        value: &Value,
+    ) -> Result<(), ReflectDeserializeError> {
+        let path = join(owner_path, name);
+
+        let mut update = None;
+        owner.field(name, &mut |field| {
+            if let Some(field) = field {
+                update = Some(match converter(actual_type_id(field)) {
+                    // Skip unchanged values to not mark inheritable variables as modified.
+                    Some(_) if to_value(field) == *value => FieldUpdate::Unchanged,
+                    Some(converter) => FieldUpdate::Leaf(
+                        (converter.from_value)(value, self.blackboard)
+                            .map_err(|message| conversion_error(&path, message)),
+                    ),
+                    None => FieldUpdate::Compound,
+                });
+            }
+        });
+
+        match update {
+            None => Err(ReflectDeserializeError::UnknownField {
+                path: owner_path.to_string(),
+                field: name.to_string(),
+            }),
+            Some(FieldUpdate::Unchanged) => Ok(()),
+            Some(FieldUpdate::Leaf(new_value)) => {
+                let mut result = Ok(());
+                owner.set_field(name, new_value?, &mut |r| {
+                    if r.is_err() {
+                        result = Err(invalid_value(&path));
+                    }
+                });
+                result
+            }
+            Some(FieldUpdate::Compound) => {
+                let mut result = Ok(());
+                owner.field_mut(name, &mut |field| {
+                    if let Some(field) = field {
+                        result = self.apply(field, &path, value);
+                    }
+                });
+                result
+            }
+        }
+    }
+
+    fn apply(
+        &self,
+        target: &mut dyn Reflect,
+        path: &str,
+        value: &Value,
+    ) -> Result<(), ReflectDeserializeError> {
+        let type_id = actual_type_id(target);
+
+        if let Some(new_value) = self.convert(path, type_id, value) {
+            return set(target, path, new_value?);
+        }
+
+        if let Some(adapter) = enum_adapter(type_id) {
+            return self.apply_enum(target, path, &adapter, value);
+        }
+
+        let mut result = None;
+
+        target.as_inheritable_variable_mut(&mut |variable| {
+            if let Some(variable) = variable {
+                let old = to_value(variable.inner_value_ref());
+                result = Some(self.apply(variable.inner_value_mut(), path, value));
+                if old != to_value(variable.inner_value_ref()) {
+                    variable.mark_modified();
+                }
+            }
+        });
+        if let Some(result) = result {
+            return result;
+        }
+
+        target.as_list_mut(&mut |list| {
+            if let Some(list) = list {
+                result = Some(self.apply_list(list, path, value));
+            }
+        });
+        if let Some(result) = result {
+            return result;
+        }
+
+        target.as_array_mut(&mut |array| {
+            if let Some(array) = array {
+                result = Some(self.apply_array(array, path, value));
+            }
+        });
+        if let Some(result) = result {
+            return result;
+        }
+
+        target.as_hash_map_mut(&mut |map| {
+            if let Some(map) = map {
+                result = Some(self.apply_hash_map(map, path, value));
+            }
+        });
+        if let Some(result) = result {
+            return result;
+        }
+
+        let option = option_adapter(type_id);
+        match shape(target) {
+            Shape::Opaque => match (value, option) {
+                (Value::Null, _) => Ok(()),
+                (_, Some(option)) => {
+                    set(target, path, (option.some)())?;
+                    self.apply_field(target, path, "Some@0", value)
+                }
+                _ => Err(not_registered(path, target)),
+            },
+            Shape::Some => match (value, option) {
+                (Value::Null, Some(option)) => set(target, path, (option.none)()),
+                (Value::Null, None) => Err(not_registered(path, target)),
+                _ => self.apply_field(target, path, "Some@0", value),
+            },
+            Shape::Transparent(name) => self.apply_field(target, path, &name, value),
+            Shape::Fields => self.apply_fields(target, path, value, None),
+        }
+    }
+
+    fn apply_fields(
+        &self,
+        target: &mut dyn Reflect,
+        path: &str,
+        value: &Value,
+        prefix: Option<&str>,
+    ) -> Result<(), ReflectDeserializeError> {
+        match value {
+            Value::Null => Ok(()),
+            Value::Object(map) => {
+                for (name, value) in map {
+                    let name = match prefix {
+                        Some(prefix) => format!("{prefix}{name}"),
+                        None => name.clone(),
+                    };
+                    self.apply_field(target, path, &name, value)?;
+                }
+                Ok(())
+            }
+            _ => Err(conversion_error(path, unexpected("an object", value))),
+        }
+    }
+
+    fn apply_enum(
+        &self,
+        target: &mut dyn Reflect,
+        path: &str,
+        adapter: &EnumAdapter,
+        value: &Value,
+    ) -> Result<(), ReflectDeserializeError> {
+        let (name, fields) = match value {
+            Value::String(name) => (name.as_str(), None),
+            Value::Object(map) if map.len() == 1 => {
+                let (name, fields) = map.iter().next().unwrap();
+                (name.as_str(), Some(fields))
+            }
+            _ => {
+                return Err(conversion_error(
+                    path,
+                    unexpected("a variant name or an object with single entry", value),
+                ))
+            }
+        };
+
+        if variant_name(adapter, target) != name {
+            let variant = (adapter.from_variant_name)(name).ok_or_else(|| {
+                ReflectDeserializeError::UnknownVariant {
+                    path: path.to_string(),
+                    type_uuid: adapter.type_uuid,
+                    variant: name.to_string(),
+                }
+            })?;
+            set(target, path, variant)?;
+        }
+
+        match fields {
+            Some(fields) => self.apply_fields(target, path, fields, Some(&format!("{name}@"))),
+            None => Ok(()),
+        }
+    }
+
+    fn apply_items(
+        &self,
+        array: &mut dyn ReflectArray,
+        path: &str,
+        items: &[Value],
+    ) -> Result<(), ReflectDeserializeError> {
+        for (i, item) in items.iter().enumerate() {
+            if let Some(target) = array.reflect_index_mut(i) {
+                self.apply(target, &format!("{path}[{i}]"), item)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_list(
+        &self,
+        list: &mut dyn ReflectList,
+        path: &str,
+        value: &Value,
+    ) -> Result<(), ReflectDeserializeError> {
+        let items = value
+            .as_array()
+            .ok_or_else(|| conversion_error(path, unexpected("an array", value)))?;
+
+        while list.reflect_len() > items.len() {
+            list.reflect_pop();
+        }
+
+        while list.reflect_len() < items.len() {
+            let item = list
+                .reflect_item_type_id()
+                .and_then(construct)
+                .ok_or_else(|| ReflectDeserializeError::NotRegistered {
+                    path: path.to_string(),
+                    type_name: format!("item of {}", list.type_name()),
+                })?;
+            list.reflect_push(item).map_err(|_| invalid_value(path))?;
+        }
+
+        self.apply_items(list, path, items)
+    }
+
+    fn apply_array(
+        &self,
+        array: &mut dyn ReflectArray,
+        path: &str,
+        value: &Value,
+    ) -> Result<(), ReflectDeserializeError> {
+        let items = value
+            .as_array()
+            .ok_or_else(|| conversion_error(path, unexpected("an array", value)))?;
+
+        if array.reflect_len() != items.len() {
+            return Err(ReflectDeserializeError::LengthMismatch {
+                path: path.to_string(),
+                expected: array.reflect_len(),
+                actual: items.len(),
+            });
+        }
+
+        self.apply_items(array, path, items)
+    }
+
+    fn make(
+        &self,
+        path: &str,
+        type_id: Option<TypeId>,
+        what: &str,
+        map: &dyn ReflectHashMap,
+        value: &Value,
+    ) -> Result<Box<dyn Reflect>, ReflectDeserializeError> {
+        let mut item =
+            type_id
+                .and_then(construct)
+                .ok_or_else(|| ReflectDeserializeError::NotRegistered {
+                    path: path.to_string(),
+                    type_name: format!("{what} of {}", map.type_name()),
+                })?;
+        self.apply(&mut *item, path, value)?;
+        Ok(item)
+    }
+
+    fn apply_hash_map(
+        &self,
+        map: &mut dyn ReflectHashMap,
+        path: &str,
+        value: &Value,
+    ) -> Result<(), ReflectDeserializeError> {
+        let entries = value
+            .as_object()
+            .ok_or_else(|| conversion_error(path, unexpected("an object", value)))?;
+
+        let key_type_id = map.reflect_key_type_id();
+
+        // Remove entries, that does not exist in the source data.
+        let stale = (0..map.reflect_len())
+            .filter_map(|i| map.reflect_get_at(i))
+            .map(|(key, _)| key_to_string(key))
+            .filter(|key| !entries.contains_key(key))
+            .collect::<Vec<_>>();
+        for key in stale {
+            let key = self.make(path, key_type_id, "key", map, &Value::String(key))?;
+            map.reflect_remove(&*key, &mut |_| ());
+        }
+
+        for (key_str, value) in entries {
+            let item_path = format!("{path}[{key_str}]");
+            let key = self.make(
+                &item_path,
+                key_type_id,
+                "key",
+                map,
+                &Value::String(key_str.clone()),
+            )?;
+
+            let mut result = None;
+            map.reflect_get_mut(&*key, &mut |existing| {
+                if let Some(existing) = existing {
+                    result = Some(self.apply(existing, &item_path, value));
+                }
+            });
+
+            match result {
+                Some(result) => result?,
+                None => {
+                    let value =
+                        self.make(&item_path, map.reflect_value_type_id(), "value", map, value)?;
+                    map.reflect_insert(key, value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Applies the given [`Value`] to a reflectable value. Fields that are missing in the source data
+/// are left untouched, while lists and hash maps are replaced entirely. The blackboard is passed to
+/// the converters, it could be used to pass some context (a resource manager, for example).
+pub fn apply_value(
+    target: &mut dyn Reflect,
+    value: &Value,
+    blackboard: &Blackboard,
+) -> Result<(), ReflectDeserializeError> {
+    Applier { blackboard }.apply(target, "", value)
+}
+
+/// Serializes a reflectable value as a pretty-printed JSON string.
+pub fn to_json(value: &dyn Reflect) -> String {
+    format!("{:#}", to_value(value))
+}
+
+/// Deserializes the given JSON string into a reflectable value. See [`apply_value`] for more info.
+pub fn from_json(
+    target: &mut dyn Reflect,
+    json: &str,
+    blackboard: &Blackboard,
+) -> Result<(), ReflectDeserializeError> {
+    let value = json
+        .parse::<Value>()
+        .map_err(|err| ReflectDeserializeError::Parse(err.to_string()))?;
+    apply_value(target, &value, blackboard)
+}
+
+/// Applies the value to a nested property of the target at the given path (see
+/// [`Reflect::resolve_path_mut`] for path syntax). If the property is a field, it is assigned using
+/// [`Reflect::set_field`] of its owner, so custom property setters will be called. An empty path
+/// means the target itself.
+pub fn apply_value_at_path(
+    target: &mut dyn Reflect,
+    path: &str,
+    value: &Value,
+    blackboard: &Blackboard,
+) -> Result<(), ReflectDeserializeError> {
+    let applier = Applier { blackboard };
+
+    // Items of collections don't have an owner with setters, so they're resolved directly.
+    let (owner_path, field) = match path.rfind('.') {
+        _ if path.is_empty() || path.ends_with(']') => (path, None),
+        Some(position) => (&path[..position], Some(&path[position + 1..])),
+        None => ("", Some(path)),
+    };
+
+    let apply = |owner: &mut dyn Reflect| match field {
+        Some(field) => applier.apply_field(owner, owner_path, field, value),
+        None => applier.apply(owner, path, value),
+    };
+
+    if owner_path.is_empty() {
+        return apply(target);
+    }
+
+    let mut result = Ok(());
+    target.resolve_path_mut(owner_path, &mut |owner| {
+        result = match owner {
+            Ok(owner) => apply(owner),
+            Err(err) => Err(ReflectDeserializeError::InvalidPath {
+                path: path.to_string(),
+                message: err.to_string(),
+            }),
+        }
+    });
+    result
+}
+
+/// A wrapper, that implements [`Serialize`] for any reflectable value, so it could be serialized
+/// using any serde format.
+pub struct SerializeReflect<'a>(pub &'a dyn Reflect);
+
+impl Serialize for SerializeReflect<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        to_value(self.0).serialize(serializer)
+    }
+}
+
+/// A [`DeserializeSeed`], that deserializes data into an existing reflectable value using any
+/// self-describing serde format. See [`apply_value`] for more info.
+pub struct DeserializeReflect<'a> {
+    /// A value to deserialize into.
+    pub target: &'a mut dyn Reflect,
+    /// A blackboard, that will be passed to the converters.
+    pub blackboard: &'a Blackboard,
+}
+
+impl<'de> DeserializeSeed<'de> for DeserializeReflect<'_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        apply_value(self.target, &value, self.blackboard).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        algebra::{Vector2, Vector3},
+        pool::Handle,
+        uuid_provider,
+        variable::InheritableVariable,
+    };
+    use std::collections::HashMap;
+
+    #[derive(Reflect, Clone, Debug, PartialEq, Default)]
+    enum Damage {
+        #[default]
+        None,
+        Physical {
+            amount: f32,
+        },
+        Elemental(u32, String),
+    }
+
+    uuid_provider!(Damage = "a7b2f1a4-6c47-4e0c-bb6a-ff1a3c8a9e59");
+
+    impl AsRef<str> for Damage {
+        fn as_ref(&self) -> &str {
+            match self {
+                Damage::None => "None",
+                Damage::Physical { .. } => "Physical",
+                Damage::Elemental(..) => "Elemental",
+            }
+        }
+    }
+
+    impl FromStr for Damage {
+        type Err = ();
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "None" => Ok(Damage::None),
+                "Physical" => Ok(Damage::Physical { amount: 0.0 }),
+                "Elemental" => Ok(Damage::Elemental(0, Default::default())),
+                _ => Err(()),
+            }
+        }
+    }
+
+    #[derive(Reflect, Clone, Debug, PartialEq, Default)]
+    struct Stat {
+        name: String,
+        value: f32,
+    }
+
+    #[derive(Reflect, Clone, Debug, PartialEq, Default)]
+    struct Weapon {
+        id: Uuid,
+        name: InheritableVariable<String>,
+        damage: Damage,
+        offset: Vector3<f32>,
+        stats: Vec<Stat>,
+        tags: HashMap<String, u32>,
+        by_id: HashMap<u32, Stat>,
+        target: Handle<Weapon>,
+        parent: Option<Stat>,
+        size: [Vector2<f32>; 2],
+        #[reflect(setter = "set_cooldown")]
+        cooldown: f32,
+    }
+
+    impl Weapon {
+        fn set_cooldown(&mut self, cooldown: f32) -> f32 {
+            std::mem::replace(&mut self.cooldown, cooldown.max(0.0))
+        }
+    }
+
+    fn register() {
+        register_enum::<Damage>();
+        register_type::<Stat>();
+    }
+
+    fn make_weapon() -> Weapon {
+        Weapon {
+            id: Uuid::new_v4(),
+            name: "Sword".to_string().into(),
+            damage: Damage::Elemental(123, "Fire".to_string()),
+            offset: Vector3::new(1.0, 2.0, 3.0),
+            stats: vec![
+                Stat {
+                    name: "Weight".to_string(),
+                    value: 2.5,
+                },
+                Stat {
+                    name: "Speed".to_string(),
+                    value: 1.0,
+                },
+            ],
+            tags: [("Melee".to_string(), 1), ("Rare".to_string(), 2)].into(),
+            by_id: [(
+                7,
+                Stat {
+                    name: "Sharpness".to_string(),
+                    value: 0.5,
+                },
+            )]
+            .into(),
+            target: Handle::new(3, 1),
+            parent: Some(Stat::default()),
+            size: [Vector2::new(1.0, 2.0), Vector2::new(3.0, 4.0)],
+            cooldown: 1.5,
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        register();
+
+        let weapon = make_weapon();
+        let json = to_json(&weapon);
+
+        let mut loaded = Weapon::default();
+        from_json(&mut loaded, &json, &Blackboard::new()).unwrap();
+        assert_eq!(loaded, weapon);
+        assert!(loaded.name.is_modified());
+
+        // Deserialization in existing value must remove items, that are not in the source data.
+        let mut other = make_weapon();
+        other.stats.push(Stat::default());
+        other.tags.insert("Extra".to_string(), 3);
+        other.parent = None;
+        from_json(&mut other, &json, &Blackboard::new()).unwrap();
+        assert_eq!(other.stats, weapon.stats);
+        assert_eq!(other.tags, weapon.tags);
+        assert_eq!(other.parent, weapon.parent);
+    }
+
+    #[test]
+    fn test_data_layout() {
+        register();
+
+        let value = to_value(&make_weapon());
+        assert_eq!(value["name"], Value::from("Sword"));
+        assert_eq!(
+            value["offset"],
+            Value::from(vec![Value::from(1.0), Value::from(2.0), Value::from(3.0)])
+        );
+        assert_eq!(
+            value["damage"],
+            r#"{ "Elemental": { "0": 123, "1": "Fire" } }"#.parse::<Value>().unwrap()
+        );
+        assert_eq!(value["tags"]["Rare"], Value::from(2));
+        assert_eq!(value["by_id"]["7"]["name"], Value::from("Sharpness"));
+        assert_eq!(
+            value["target"],
+            r#"{ "index": 3, "generation": 1 }"#.parse::<Value>().unwrap()
+        );
+        assert_eq!(to_value(&Damage::None), Value::from("None"));
+        assert_eq!(to_value(&None::<Stat>), Value::Null);
+    }
+
+    #[test]
+    fn test_partial_and_lenient_data() {
+        register();
+
+        let mut weapon = Weapon {
+            name: InheritableVariable::new_non_modified("Sword".to_string()),
+            ..Default::default()
+        };
+        from_json(
+            &mut weapon,
+            r#"{ "stats": [{ "name": "Weight", "value": "2.5" }], "damage": "Physical", "cooldown": -1 }"#,
+            &Blackboard::new(),
+        )
+        .unwrap();
+        assert_eq!(weapon.stats[0].value, 2.5);
+        assert_eq!(weapon.damage, Damage::Physical { amount: 0.0 });
+        // Must go through the setter.
+        assert_eq!(weapon.cooldown, 0.0);
+        // Untouched.
+        assert!(!weapon.name.is_modified());
+    }
+
+    #[test]
+    fn test_errors() {
+        register();
+
+        let mut weapon = Weapon::default();
+        let blackboard = Blackboard::new();
+        assert!(matches!(
+            from_json(&mut weapon, r#"{ "foo": 1 }"#, &blackboard),
+            Err(ReflectDeserializeError::UnknownField { .. })
+        ));
+        assert!(matches!(
+            from_json(&mut weapon, r#"{ "damage": "Magic" }"#, &blackboard),
+            Err(ReflectDeserializeError::UnknownVariant { .. })
+        ));
+        assert!(matches!(
+            from_json(&mut weapon, r#"{ "size": [[1, 2]] }"#, &blackboard),
+            Err(ReflectDeserializeError::LengthMismatch { .. })
+        ));
+        assert!(matches!(
+            from_json(&mut weapon, r#"{ "target": { "index": -1 } }"#, &blackboard),
+            Err(ReflectDeserializeError::Conversion { .. })
+        ));
+        assert!(matches!(
+            from_json(&mut weapon, "{", &blackboard),
+            Err(ReflectDeserializeError::Parse(..))
+        ));
+    }
+
+    #[test]
+    fn test_apply_value_at_path() {
+        register();
+
+        let mut weapon = make_weapon();
+        let blackboard = Blackboard::new();
+
+        apply_value_at_path(&mut weapon, "cooldown", &Value::from(-1.0), &blackboard).unwrap();
+        assert_eq!(weapon.cooldown, 0.0);
+
+        apply_value_at_path(
+            &mut weapon,
+            "stats[1].value",
+            &Value::from(4.0),
+            &blackboard,
+        )
+        .unwrap();
+        assert_eq!(weapon.stats[1].value, 4.0);
+
+        let stat = to_value(&Stat {
+            name: "Range".to_string(),
+            value: 3.0,
+        });
+        apply_value_at_path(&mut weapon, "stats[0]", &stat, &blackboard).unwrap();
+        assert_eq!(weapon.stats[0].name, "Range");
+
+        apply_value_at_path(
+            &mut weapon,
+            "offset",
+            &to_value(&Vector3::new(4.0f32, 5.0, 6.0)),
+            &blackboard,
+        )
+        .unwrap();
+        assert_eq!(weapon.offset, Vector3::new(4.0, 5.0, 6.0));
+
+        assert!(matches!(
+            apply_value_at_path(&mut weapon, "stats[5].value", &Value::from(1), &blackboard),
+            Err(ReflectDeserializeError::InvalidPath { .. })
+        ));
+    }
+}
@@ -28,7 +28,7 @@ use crate::{
 };
 use fyrox_core_derive::impl_reflect;
 use std::{
-    any::Any,
+    any::{Any, TypeId},
     cell::{Cell, RefCell},
     collections::HashMap,
     fmt::Debug,
@@ -166,6 +166,10 @@ impl<T: Reflect + 'static> ReflectList for Vec<T> {
         self.insert(index, *value.downcast::<T>()?);
         Ok(())
     }
+
+    fn reflect_item_type_id(&self) -> Option<TypeId> {
+        Some(TypeId::of::<T>())
+    }
 }
 
 impl<K, V, S> Reflect for HashMap<K, V, S>
@@ -211,6 +215,14 @@ where
         self.len()
     }
 
+    fn reflect_key_type_id(&self) -> Option<TypeId> {
+        Some(TypeId::of::<K>())
+    }
+
+    fn reflect_value_type_id(&self) -> Option<TypeId> {
+        Some(TypeId::of::<V>())
+    }
+
     fn reflect_get(&self, key: &dyn Reflect, func: &mut dyn FnMut(Option<&dyn Reflect>)) {
         key.downcast_ref::<K>(&mut |result| match result {
             Some(key) => match self.get(key) {
@@ -19,7 +19,7 @@
 // SOFTWARE.
 
 #[cfg(not(target_arch = "wasm32"))]
-use crate::futures::executor::ThreadPool;
+use crate::{futures::executor::ThreadPool, log::category};
 use parking_lot::Mutex;
 use std::{
     any::Any,
@@ -106,7 +106,10 @@ impl TaskPool {
         let (sender, receiver) = mpsc::channel();
         Self {
             #[cfg(not(target_arch = "wasm32"))]
-            thread_pool: ThreadPool::new().unwrap(),
+            thread_pool: ThreadPool::builder()
+                .name_prefix("fyrox-task-")
+                .create()
+                .unwrap(),
             sender,
             receiver: Mutex::new(receiver),
         }
@@ -127,7 +130,14 @@ impl TaskPool {
     where
         F: Future<Output = ()> + Send + 'static,
     {
-        self.thread_pool.spawn_ok(future);
+        // Every poll of the task is measured separately, because the task could be polled on
+        // different threads of the pool.
+        let mut future = Box::pin(future);
+        self.thread_pool
+            .spawn_ok(std::future::poll_fn(move |context| {
+                crate::profile_scope!(category: category::TASK, "Task");
+                future.as_mut().poll(context)
+            }));
     }
 
     #[inline]
@@ -30,6 +30,9 @@
 
 pub use fyrox_core_derive::Visit;
 
+pub mod migration;
+mod text;
+
 pub mod prelude {
     //! Types to use `#[derive(Visit)]`
     pub use super::{Visit, VisitError, VisitResult, Visitor};
@@ -43,13 +46,15 @@ use crate::{
     array_as_u8_slice_mut,
     io::{self, FileLoadError},
     pool::{Handle, Pool},
-    replace_slashes,
+    reflect::prelude::*,
+    replace_slashes, uuid_provider,
 };
 
 use base64::Engine;
 use bitflags::bitflags;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use fxhash::FxHashMap;
+use serde::{Deserialize, Serialize};
 use std::any::TypeId;
 use std::error::Error;
 use std::{
@@ -74,6 +79,7 @@ use uuid::Uuid;
 /// of these types.
 /// Fields can be accessed from a visitor using [Visit::visit] on a variable with the
 /// same type as the field.
+#[derive(Debug, Clone, PartialEq)]
 pub enum FieldKind {
     Bool(bool),
     U8(u8),
@@ -647,6 +653,23 @@ pub enum VisitError {
     PoisonedMutex,
     /// A FileLoadError was encountered while trying to decode Visitor data from a file.
     FileLoadError(FileLoadError),
+    /// Visitor data in text format (see [Visitor::save_text_stable]) is malformed.
+    InvalidText {
+        /// Line (starting from 1) at which the error was found.
+        line: usize,
+        /// Description of the error.
+        message: String,
+    },
+    /// The version of a type stored in Visitor data is newer than the version supported by the
+    /// type, see [migration::register_migration] for more info.
+    UnsupportedVersion {
+        /// Name of the type.
+        type_name: String,
+        /// Stored version.
+        version: u32,
+        /// The latest version supported by the type.
+        supported: u32,
+    },
 }
 
 impl Error for VisitError {}
@@ -671,6 +694,17 @@ impl Display for VisitError {
             Self::UnexpectedRcNullIndex => write!(f, "unexpected rc null index"),
             Self::PoisonedMutex => write!(f, "attempt to lock poisoned mutex"),
             Self::FileLoadError(e) => write!(f, "file load error: {e:?}"),
+            Self::InvalidText { line, message } => {
+                write!(f, "invalid text data at line {line}: {message}")
+            }
+            Self::UnsupportedVersion {
+                type_name,
+                version,
+                supported,
+            } => write!(
+                f,
+                "unsupported version {version} of {type_name}, the latest supported version is {supported}"
+            ),
         }
     }
 }
@@ -976,7 +1010,7 @@ impl Field {
         Ok(())
     }
 
-    fn load(file: &mut dyn Read) -> Result<Field, VisitError> {
+    fn load(file: &mut dyn Read, version: u32) -> Result<Field, VisitError> {
         fn read_vec_n<T, S, const N: usize>(
             file: &mut dyn Read,
         ) -> Result<Matrix<T, Const<N>, U1, S>, VisitError>
@@ -1026,7 +1060,11 @@ impl Field {
                     for n in &mut f {
                         *n = file.read_f32::<LittleEndian>()?;
                     }
-                    Matrix4::from_row_slice(&f)
+                    if version >= Visitor::COLUMN_MAJOR_MATRICES_VERSION {
+                        Matrix4::from_column_slice(&f)
+                    } else {
+                        Matrix4::from_row_slice(&f)
+                    }
                 }),
                 14 => FieldKind::BinaryBlob({
                     let len = file.read_u32::<LittleEndian>()? as usize;
@@ -1040,7 +1078,11 @@ impl Field {
                     for n in &mut f {
                         *n = file.read_f32::<LittleEndian>()?;
                     }
-                    Matrix3::from_row_slice(&f)
+                    if version >= Visitor::COLUMN_MAJOR_MATRICES_VERSION {
+                        Matrix3::from_column_slice(&f)
+                    } else {
+                        Matrix3::from_row_slice(&f)
+                    }
                 }),
                 17 => FieldKind::Vector2F32({
                     let x = file.read_f32::<LittleEndian>()?;
@@ -1077,11 +1119,15 @@ impl Field {
                     }
                 }
                 22 => FieldKind::Matrix2({
-                    let mut f = [0.0f32; 3];
+                    let mut f = [0.0f32; 4];
                     for n in &mut f {
                         *n = file.read_f32::<LittleEndian>()?;
                     }
-                    Matrix2::from_row_slice(&f)
+                    if version >= Visitor::COLUMN_MAJOR_MATRICES_VERSION {
+                        Matrix2::from_column_slice(&f)
+                    } else {
+                        Matrix2::from_row_slice(&f)
+                    }
                 }),
                 23 => FieldKind::Vector2F64(read_vec_n(file)?),
                 24 => FieldKind::Vector3F64(read_vec_n(file)?),
@@ -1261,6 +1307,20 @@ pub struct Visitor {
     pub flags: VisitorFlags,
 }
 
+/// Format of encoded [Visitor] data.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect, Visit,
+)]
+pub enum VisitorFormat {
+    /// Compact binary format, see [Visitor::save_binary].
+    #[default]
+    Binary,
+    /// Human-readable text format, see [Visitor::save_text_stable].
+    Text,
+}
+
+uuid_provider!(VisitorFormat = "4f0a3d4e-97a3-4c51-8d0f-0f6ad6a0b6c2");
+
 /// Trait of types that can be read from a [Visitor] or written to a Visitor.
 pub trait Visit {
     /// Read or write this value, depending on whether [Visitor::is_reading()] is true or false.
@@ -1320,6 +1380,24 @@ impl Visitor {
     /// of the given slice.
     pub const MAGIC: &'static str = "RG3D";
 
+    /// Sequence of characters that is written at the start of the text representation of a visitor,
+    /// produced by [Visitor::save_text_stable]. It is used by [Visitor::load_from_memory] and
+    /// [Visitor::load_binary] to tell apart text and binary data.
+    pub const TEXT_MAGIC: &'static str = "RG3D_TEXT";
+
+    /// Current version of the binary format. It is written right after [Visitor::MAGIC], preceded
+    /// by [Visitor::VERSION_MARKER]. Data without the marker is treated as version 0.
+    pub const BINARY_VERSION: u32 = 1;
+
+    /// A value that cannot be a length of the root node name, it tells apart versioned binary data
+    /// from the data written before versioning was introduced.
+    pub const VERSION_MARKER: u32 = u32::MAX;
+
+    /// Version 0 wrote matrices in column-major order, but read them back in row-major order, so
+    /// every matrix was transposed on load. Such data is still loaded the same way to not change
+    /// existing assets, starting from this version matrices are read in column-major order.
+    const COLUMN_MAJOR_MATRICES_VERSION: u32 = 1;
+
     /// Creates a Visitor containing only a single node called "`__ROOT__`" which will be the
     /// current region of the visitor.
     pub fn new() -> Self {
@@ -1458,10 +1536,62 @@ impl Visitor {
         out_string
     }
 
+    /// Create a String containing all the data of this Visitor in a human-readable format that
+    /// can be read back using [Visitor::load_text] (or [Visitor::load_from_memory]). Unlike
+    /// [Visitor::save_text], the output is stable: fields and regions are sorted by their names and
+    /// identifiers of shared values ([Rc], [Arc]) are replaced with sequential numbers, so saving the
+    /// same data twice produces the same text. This makes the format suitable for version control
+    /// systems.
+    ///
+    /// Keep in mind that the order of items of hash maps still depends on the hasher used by
+    /// the map, use hash maps with deterministic hashers if stable output is required.
+    pub fn save_text_stable(&self) -> String {
+        text::write(self)
+    }
+
+    /// Create a file at the given path and write the data of this visitor into it in the text format,
+    /// see [Visitor::save_text_stable] for more info.
+    pub fn save_text_stable_to_file<P: AsRef<Path>>(&self, path: P) -> VisitResult {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(self.save_text_stable().as_bytes())?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Create a file at the given path and write the data of this visitor into it in the given
+    /// format.
+    pub fn save_with_format<P: AsRef<Path>>(&self, format: VisitorFormat, path: P) -> VisitResult {
+        match format {
+            VisitorFormat::Binary => self.save_binary(path),
+            VisitorFormat::Text => self.save_text_stable_to_file(path),
+        }
+    }
+
+    /// Detects the format of the given encoded visitor data. Returns `None` if the data starts with
+    /// neither [Visitor::MAGIC] nor [Visitor::TEXT_MAGIC].
+    pub fn detect_format(data: &[u8]) -> Option<VisitorFormat> {
+        if text::is_text(data) {
+            Some(VisitorFormat::Text)
+        } else if data.starts_with(Self::MAGIC.as_bytes()) {
+            Some(VisitorFormat::Binary)
+        } else {
+            None
+        }
+    }
+
+    /// Create a visitor by parsing the given text, assuming that it was produced by
+    /// [Visitor::save_text_stable]. Return [VisitError::NotSupportedFormat] if the text does not
+    /// start with [Visitor::TEXT_MAGIC] and [VisitError::InvalidText] if the text is malformed.
+    pub fn load_text(text: &str) -> Result<Self, VisitError> {
+        text::read(text)
+    }
+
     /// Write the data of this Visitor to the given writer.
-    /// Begin by writing [Visitor::MAGIC].
+    /// Begin by writing [Visitor::MAGIC] and [Visitor::BINARY_VERSION].
     pub fn save_binary_to_memory<W: Write>(&self, mut writer: W) -> VisitResult {
         writer.write_all(Self::MAGIC.as_bytes())?;
+        writer.write_u32::<LittleEndian>(Self::VERSION_MARKER)?;
+        writer.write_u32::<LittleEndian>(Self::BINARY_VERSION)?;
         let mut stack = vec![self.root];
         while let Some(node_handle) = stack.pop() {
             let node = self.nodes.borrow(node_handle);
@@ -1482,7 +1612,7 @@ impl Visitor {
 
     /// Encode the data of this visitor into bytes and push the bytes
     /// into the given `Vec<u8>`.
-    /// Begin by writing [Visitor::MAGIC].
+    /// Begin by writing [Visitor::MAGIC] and [Visitor::BINARY_VERSION].
     pub fn save_binary_to_vec(&self) -> Result<Vec<u8>, VisitError> {
         let mut writer = Cursor::new(Vec::new());
         self.save_binary_to_memory(&mut writer)?;
@@ -1492,14 +1622,18 @@ impl Visitor {
     /// Create a file at the given path and write the data of this visitor
     /// into that file in a non-human-readable binary format so that the data
     /// can be reconstructed using [Visitor::load_binary].
-    /// Begin by writing [Visitor::MAGIC].
+    /// Begin by writing [Visitor::MAGIC] and [Visitor::BINARY_VERSION].
     pub fn save_binary<P: AsRef<Path>>(&self, path: P) -> VisitResult {
         let writer = BufWriter::new(File::create(path)?);
         self.save_binary_to_memory(writer)
     }
 
-    fn load_node_binary(&mut self, file: &mut dyn Read) -> Result<Handle<VisitorNode>, VisitError> {
-        let name_len = file.read_u32::<LittleEndian>()? as usize;
+    fn load_node_binary(
+        &mut self,
+        file: &mut dyn Read,
+        version: u32,
+        name_len: usize,
+    ) -> Result<Handle<VisitorNode>, VisitError> {
         let mut raw_name = vec![Default::default(); name_len];
         file.read_exact(raw_name.as_mut_slice())?;
 
@@ -1510,14 +1644,15 @@ impl Visitor {
 
         let field_count = file.read_u32::<LittleEndian>()? as usize;
         for _ in 0..field_count {
-            let field = Field::load(file)?;
+            let field = Field::load(file, version)?;
             node.fields.push(field);
         }
 
         let child_count = file.read_u32::<LittleEndian>()? as usize;
         let mut children = Vec::with_capacity(child_count);
         for _ in 0..child_count {
-            children.push(self.load_node_binary(file)?);
+            let name_len = file.read_u32::<LittleEndian>()? as usize;
+            children.push(self.load_node_binary(file, version, name_len)?);
         }
 
         node.children.clone_from(&children);
@@ -1532,17 +1667,32 @@ impl Visitor {
     }
 
     /// Create a visitor by reading data from the file at the given path,
-    /// assuming that the file was created using [Visitor::save_binary].
-    /// Return a [VisitError::NotSupportedFormat] if [Visitor::MAGIC] is not the first bytes read from the file.
+    /// assuming that the file was created using [Visitor::save_binary] or
+    /// [Visitor::save_text_stable_to_file]. The format is detected automatically, see
+    /// [Visitor::load_from_memory] for more info.
     pub async fn load_binary<P: AsRef<Path>>(path: P) -> Result<Self, VisitError> {
         Self::load_from_memory(&io::load_file(path).await?)
     }
 
     /// Create a visitor by decoding data from the given byte slice,
     /// assuming that the bytes are in the format that would be produced
-    /// by [Visitor::save_binary_to_vec].
-    /// Return a [VisitError::NotSupportedFormat] if [Visitor::MAGIC] is not the first bytes read from the slice.
+    /// by [Visitor::save_binary_to_vec] or [Visitor::save_text_stable]. Data that starts with
+    /// [Visitor::TEXT_MAGIC] is parsed as text, otherwise it is decoded as binary.
+    /// Return a [VisitError::NotSupportedFormat] if neither [Visitor::MAGIC] nor
+    /// [Visitor::TEXT_MAGIC] is at the beginning of the slice.
     pub fn load_from_memory(data: &[u8]) -> Result<Self, VisitError> {
+        if Self::detect_format(data) == Some(VisitorFormat::Text) {
+            let text = std::str::from_utf8(data).map_err(|e| VisitError::InvalidText {
+                line: data[..e.valid_up_to()]
+                    .iter()
+                    .filter(|b| **b == b'\n')
+                    .count()
+                    + 1,
+                message: "invalid UTF-8 sequence".to_string(),
+            })?;
+            return Self::load_text(text);
+        }
+
         let mut reader = Cursor::new(data);
         let mut magic: [u8; 4] = Default::default();
         reader.read_exact(&mut magic)?;
@@ -1559,7 +1709,17 @@ impl Visitor {
             blackboard: Blackboard::new(),
             flags: VisitorFlags::NONE,
         };
-        visitor.root = visitor.load_node_binary(&mut reader)?;
+        let (version, name_len) = match reader.read_u32::<LittleEndian>()? {
+            Self::VERSION_MARKER => {
+                let version = reader.read_u32::<LittleEndian>()?;
+                if version > Self::BINARY_VERSION {
+                    return Err(VisitError::NotSupportedFormat);
+                }
+                (version, reader.read_u32::<LittleEndian>()? as usize)
+            }
+            name_len => (0, name_len as usize),
+        };
+        visitor.root = visitor.load_node_binary(&mut reader, version, name_len)?;
         visitor.current_node = visitor.root;
         Ok(visitor)
     }
@@ -2459,4 +2619,81 @@ mod test {
             "<vec4i64 = 0; 0; 0; 0>, ".to_string()
         );
     }
+
+    #[test]
+    fn visitor_binary_matrix_round_trip() {
+        let mut mat4 = Matrix4::new_rotation(Vector3::new(0.3, 0.2, 0.1));
+        mat4[(0, 3)] = 5.0;
+        let mut mat3 = Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0);
+        let mut mat2 = Matrix2::new(1.0, 2.0, 3.0, 4.0);
+        // Makes sure that the data after the matrices is read correctly too.
+        let mut after = 123u32;
+
+        let mut visitor = Visitor::new();
+        mat4.visit("Mat4", &mut visitor).unwrap();
+        mat3.visit("Mat3", &mut visitor).unwrap();
+        mat2.visit("Mat2", &mut visitor).unwrap();
+        after.visit("After", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(&data).unwrap();
+        let mut loaded_mat4 = Matrix4::<f32>::default();
+        let mut loaded_mat3 = Matrix3::<f32>::default();
+        let mut loaded_mat2 = Matrix2::<f32>::default();
+        let mut loaded_after = 0u32;
+        loaded_mat4.visit("Mat4", &mut visitor).unwrap();
+        loaded_mat3.visit("Mat3", &mut visitor).unwrap();
+        loaded_mat2.visit("Mat2", &mut visitor).unwrap();
+        loaded_after.visit("After", &mut visitor).unwrap();
+
+        assert_eq!(loaded_mat4, mat4);
+        assert_eq!(loaded_mat3, mat3);
+        assert_eq!(loaded_mat2, mat2);
+        assert_eq!(loaded_after, after);
+    }
+
+    #[test]
+    fn visitor_binary_legacy_matrices() {
+        let mut mat4 = Matrix4::new_rotation(Vector3::new(0.3, 0.2, 0.1));
+        mat4[(0, 3)] = 5.0;
+        let mut mat3 = Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0);
+        let mut mat2 = Matrix2::new(1.0, 2.0, 3.0, 4.0);
+        let mut after = 123u32;
+
+        let mut visitor = Visitor::new();
+        mat4.visit("Mat4", &mut visitor).unwrap();
+        mat3.visit("Mat3", &mut visitor).unwrap();
+        mat2.visit("Mat2", &mut visitor).unwrap();
+        after.visit("After", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        // Data written before versioning has the same layout, except the version header.
+        let mut legacy = Visitor::MAGIC.as_bytes().to_vec();
+        legacy.extend_from_slice(&data[Visitor::MAGIC.len() + 8..]);
+
+        // Legacy matrices must be loaded the same way as before - transposed.
+        let mut visitor = Visitor::load_from_memory(&legacy).unwrap();
+        let mut loaded_mat4 = Matrix4::<f32>::default();
+        let mut loaded_mat3 = Matrix3::<f32>::default();
+        let mut loaded_mat2 = Matrix2::<f32>::default();
+        let mut loaded_after = 0u32;
+        loaded_mat4.visit("Mat4", &mut visitor).unwrap();
+        loaded_mat3.visit("Mat3", &mut visitor).unwrap();
+        loaded_mat2.visit("Mat2", &mut visitor).unwrap();
+        loaded_after.visit("After", &mut visitor).unwrap();
+
+        assert_eq!(loaded_mat4, mat4.transpose());
+        assert_eq!(loaded_mat3, mat3.transpose());
+        assert_eq!(loaded_mat2, mat2.transpose());
+        assert_eq!(loaded_after, after);
+
+        // Data of unknown future versions is rejected.
+        let mut future = data.clone();
+        future[Visitor::MAGIC.len() + 4..Visitor::MAGIC.len() + 8]
+            .copy_from_slice(&(Visitor::BINARY_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            Visitor::load_from_memory(&future),
+            Err(VisitError::NotSupportedFormat)
+        ));
+    }
 }
@@ -0,0 +1,333 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Schema versioning for [`Visit`](super::Visit)-serialized data. See [`register_migration`] for
+//! more info.
+
+use crate::visitor::{Field, FieldKind, Handle, Visit, VisitError, VisitResult, Visitor};
+use fxhash::FxHashMap;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, LazyLock, RwLock},
+};
+
+/// A function that transforms the data of a type stored in a [`Visitor`] from one version to the
+/// next one. The visitor is in reading mode and its current region is the region of the value
+/// being migrated, use [`Visitor::rename_field`], [`Visitor::set_field`],
+/// [`Visitor::enter_region`] and other methods to modify the data.
+pub type Migration = dyn Fn(&mut Visitor) -> VisitResult + Send + Sync;
+
+type MigrationMap = FxHashMap<String, BTreeMap<u32, Arc<Migration>>>;
+
+static MIGRATIONS: LazyLock<RwLock<MigrationMap>> = LazyLock::new(Default::default);
+
+/// The name of the field that stores the version of a type in its region.
+pub const VERSION_FIELD: &str = "__Version";
+
+/// Registers a migration function that upgrades the data of the type `T` from `from_version` to
+/// `from_version + 1`. The type must be marked with `#[visit(version = N)]` attribute (or call
+/// [`Visitor::visit_version`] in its manual [`Visit`] implementation). Migrations are applied in
+/// sequence when the stored version is older than the current one, right before the fields of the
+/// type are read. Data without the version field is considered to have version 0.
+///
+/// Registering a migration for the same type and version replaces the previous one.
+///
+/// ```rust
+/// use fyrox_core::visitor::{migration::register_migration, prelude::*, FieldKind};
+///
+/// #[derive(Visit, Default)]
+/// #[visit(version = 1)]
+/// struct Health {
+///     // Was stored as `u8` in the version 0.
+///     amount: f32,
+/// }
+///
+/// register_migration::<Health>(0, |visitor| {
+///     if let Some(FieldKind::U8(amount)) = visitor.remove_field("Amount") {
+///         visitor.set_field("Amount", FieldKind::F32(amount as f32));
+///     }
+///     Ok(())
+/// });
+/// ```
+pub fn register_migration<T: ?Sized>(
+    from_version: u32,
+    migration: impl Fn(&mut Visitor) -> VisitResult + Send + Sync + 'static,
+) {
+    register_migration_by_name(std::any::type_name::<T>(), from_version, migration)
+}
+
+/// Same as [`register_migration`], but uses the given type name (as returned by
+/// [`std::any::type_name`]) instead of a type parameter.
+pub fn register_migration_by_name(
+    type_name: &str,
+    from_version: u32,
+    migration: impl Fn(&mut Visitor) -> VisitResult + Send + Sync + 'static,
+) {
+    MIGRATIONS
+        .write()
+        .unwrap()
+        .entry(type_name.to_string())
+        .or_default()
+        .insert(from_version, Arc::new(migration));
+}
+
+/// Removes all migrations registered for the type `T`.
+pub fn unregister_migrations<T: ?Sized>() {
+    MIGRATIONS
+        .write()
+        .unwrap()
+        .remove(std::any::type_name::<T>());
+}
+
+fn find_migrations(type_name: &str, from: u32, to: u32) -> Vec<Arc<Migration>> {
+    MIGRATIONS
+        .read()
+        .unwrap()
+        .get(type_name)
+        .map(|migrations| migrations.range(from..to).map(|(_, m)| m.clone()).collect())
+        .unwrap_or_default()
+}
+
+impl Visitor {
+    /// Writes (or reads) the version of a type into the current region. In reading mode, applies
+    /// all migrations registered for the type (see [`register_migration`]) to bring the data of
+    /// the current region to the given version. This method is called automatically by the
+    /// `Visit` derive macro for types with `#[visit(version = N)]` attribute and must be called
+    /// right after entering the region of a type and before visiting its fields.
+    ///
+    /// Returns [`VisitError::UnsupportedVersion`] if the stored version is newer than the given one.
+    pub fn visit_version(&mut self, type_name: &str, version: u32) -> VisitResult {
+        if !self.reading {
+            let mut version = version;
+            return version.visit(VERSION_FIELD, self);
+        }
+
+        let stored = match self.field(VERSION_FIELD) {
+            Some(FieldKind::U32(stored)) => *stored,
+            Some(_) => return Err(VisitError::FieldTypeDoesNotMatch),
+            None => 0,
+        };
+
+        if stored > version {
+            return Err(VisitError::UnsupportedVersion {
+                type_name: type_name.to_string(),
+                version: stored,
+                supported: version,
+            });
+        }
+
+        if stored < version {
+            let node = self.current_node;
+            for migration in find_migrations(type_name, stored, version) {
+                migration(self)?;
+                // Migrations may move around the current node, restore it.
+                self.current_node = node;
+            }
+            self.set_field(VERSION_FIELD, FieldKind::U32(version));
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if the current region has a field with the given name.
+    pub fn has_field(&self, name: &str) -> bool {
+        self.field(name).is_some()
+    }
+
+    /// Returns `true` if the current region has a child region with the given name.
+    pub fn has_region(&self, name: &str) -> bool {
+        self.find_region(name).is_some()
+    }
+
+    /// Returns the value of the field with the given name in the current region.
+    pub fn field(&self, name: &str) -> Option<&FieldKind> {
+        self.nodes[self.current_node]
+            .fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| &field.kind)
+    }
+
+    /// Sets the value of the field with the given name in the current region. Adds a new field,
+    /// if there's no such field.
+    pub fn set_field(&mut self, name: &str, kind: FieldKind) {
+        let node = &mut self.nodes[self.current_node];
+        if let Some(field) = node.fields.iter_mut().find(|field| field.name == name) {
+            field.kind = kind;
+        } else {
+            node.fields.push(Field::new(name, kind));
+        }
+    }
+
+    /// Removes the field with the given name from the current region and returns its value.
+    pub fn remove_field(&mut self, name: &str) -> Option<FieldKind> {
+        let fields = &mut self.nodes[self.current_node].fields;
+        let index = fields.iter().position(|field| field.name == name)?;
+        Some(fields.remove(index).kind)
+    }
+
+    /// Renames the field of the current region. Returns `false` if there's no field with the
+    /// old name or if a field with the new name already exists.
+    pub fn rename_field(&mut self, old_name: &str, new_name: &str) -> bool {
+        if self.has_field(new_name) {
+            return false;
+        }
+        match self.nodes[self.current_node]
+            .fields
+            .iter_mut()
+            .find(|field| field.name == old_name)
+        {
+            Some(field) => {
+                field.name = new_name.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Renames a child region of the current region. Returns `false` if there's no region with the
+    /// old name or if a region with the new name already exists.
+    pub fn rename_region(&mut self, old_name: &str, new_name: &str) -> bool {
+        if self.has_region(new_name) {
+            return false;
+        }
+        match self.find_region(old_name) {
+            Some(region) => {
+                self.nodes[region].name = new_name.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes a child region (with all its descendants) of the current region. Returns `false`
+    /// if there's no such region.
+    pub fn remove_region(&mut self, name: &str) -> bool {
+        let Some(region) = self.find_region(name) else {
+            return false;
+        };
+        self.nodes[self.current_node]
+            .children
+            .retain(|child| *child != region);
+        let mut stack = vec![region];
+        while let Some(handle) = stack.pop() {
+            if let Some(node) = self.nodes.try_free(handle) {
+                stack.extend(node.children);
+            }
+        }
+        true
+    }
+
+    fn find_region(&self, name: &str) -> Option<Handle<crate::visitor::VisitorNode>> {
+        self.nodes[self.current_node]
+            .children
+            .iter()
+            .find(|child| self.nodes[**child].name == name)
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::visitor::{
+        migration::{register_migration, VERSION_FIELD},
+        prelude::*,
+        FieldKind,
+    };
+
+    #[derive(Visit, Default, Debug, PartialEq)]
+    struct Old {
+        hp: u8,
+        name: String,
+    }
+
+    #[derive(Visit, Default, Debug, PartialEq)]
+    #[visit(version = 2)]
+    struct New {
+        // Changed type in version 1.
+        health: f32,
+        // Renamed in version 2.
+        #[visit(rename_from = "Name")]
+        title: String,
+    }
+
+    #[test]
+    fn test_migration() {
+        register_migration::<New>(0, |visitor| {
+            if let Some(FieldKind::U8(hp)) = visitor.remove_field("Hp") {
+                visitor.set_field("Health", FieldKind::F32(hp as f32));
+            }
+            Ok(())
+        });
+
+        let mut old = Old {
+            hp: 42,
+            name: "Foo".to_string(),
+        };
+        let mut visitor = Visitor::new();
+        old.visit("Data", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(&data).unwrap();
+        let mut new = New::default();
+        new.visit("Data", &mut visitor).unwrap();
+        assert_eq!(
+            new,
+            New {
+                health: 42.0,
+                title: "Foo".to_string()
+            }
+        );
+
+        // The version is written back.
+        let mut visitor = Visitor::new();
+        new.visit("Data", &mut visitor).unwrap();
+        let text = visitor.save_text_stable();
+        let current_version = format!("{VERSION_FIELD}: u32 = 2");
+        assert!(text.contains(&current_version));
+
+        // Data of a newer version cannot be loaded.
+        let text = text.replace(&current_version, &format!("{VERSION_FIELD}: u32 = 3"));
+        let mut visitor = Visitor::load_text(&text).unwrap();
+        assert!(matches!(
+            New::default().visit("Data", &mut visitor),
+            Err(VisitError::UnsupportedVersion { version: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_node_editing() {
+        let mut a = 1u32;
+        let mut b = vec![1u8, 2, 3];
+        let mut visitor = Visitor::new();
+        a.visit("A", &mut visitor).unwrap();
+        b.visit("B", &mut visitor).unwrap();
+
+        assert!(visitor.has_field("A"));
+        assert!(visitor.has_region("B"));
+        assert!(visitor.rename_field("A", "C"));
+        assert!(!visitor.rename_field("A", "C"));
+        assert!(visitor.rename_region("B", "D"));
+        assert_eq!(visitor.field("C"), Some(&FieldKind::U32(1)));
+        assert!(visitor.remove_region("D"));
+        assert!(!visitor.has_region("D"));
+        assert_eq!(visitor.remove_field("C"), Some(FieldKind::U32(1)));
+        assert!(!visitor.has_field("C"));
+    }
+}
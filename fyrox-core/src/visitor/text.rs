@@ -0,0 +1,961 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Human-readable text representation of [`Visitor`] data that can be read back, see
+//! [`Visitor::save_text_stable`] and [`Visitor::load_text`] for more info.
+//!
+//! The format is a tree of named blocks, each block contains typed fields and child blocks:
+//!
+//! ```text
+//! RG3D_TEXT 1
+//! __ROOT__ {
+//!     Camera {
+//!         Fov: f32 = 1.5707964
+//!         Name: str = "Main Camera"
+//!         Position: vec3f32 = [0.0, 1.0, -2.5]
+//!     }
+//! }
+//! ```
+//!
+//! Fields always go before child blocks and both are sorted by their names (numeric parts of the
+//! names are compared as numbers, so `Item2` goes before `Item10`). Identifiers of shared
+//! ([`std::rc::Rc`] and [`std::sync::Arc`]) values are replaced with sequential numbers in order of
+//! appearance, so saving the same data twice gives the same text. Lines starting with `#` are
+//! comments.
+
+use crate::{
+    algebra::{
+        Matrix2, Matrix3, Matrix4, Quaternion, SVector, Scalar, UnitComplex, UnitQuaternion,
+    },
+    pool::{Handle, Pool},
+    visitor::{Blackboard, Field, FieldKind, VisitError, Visitor, VisitorFlags, VisitorNode},
+};
+use base64::Engine;
+use fxhash::{FxHashMap, FxHashSet};
+use std::{cmp::Ordering, fmt::Write, iter::Peekable, str::CharIndices, str::FromStr};
+use uuid::Uuid;
+
+/// Version of the text format, it is written right after [`Visitor::TEXT_MAGIC`].
+const VERSION: u32 = 1;
+
+const INDENT: &str = "    ";
+
+/// Returns `true` if the given data starts with [`Visitor::TEXT_MAGIC`]. Leading UTF-8 byte order
+/// mark and whitespace are ignored, since text editors tend to add them.
+pub(super) fn is_text(data: &[u8]) -> bool {
+    let data = data.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(data);
+    let start = data
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    data[start..].starts_with(Visitor::TEXT_MAGIC.as_bytes())
+}
+
+trait TextScalar: FromStr + Copy {
+    fn write_text(&self, out: &mut String);
+}
+
+macro_rules! impl_text_scalar {
+    ($fmt:literal, $($ty:ty),*) => {
+        $(
+            impl TextScalar for $ty {
+                fn write_text(&self, out: &mut String) {
+                    let _ = write!(out, $fmt, self);
+                }
+            }
+        )*
+    };
+}
+
+impl_text_scalar!("{}", bool, u8, i8, u16, i16, u32, i32, u64, i64);
+// Debug formatting of floats gives the shortest representation that parses back to the same value.
+impl_text_scalar!("{:?}", f32, f64);
+
+/// Compares names so that numeric parts are compared by their value.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    loop {
+        match (a.first(), b.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) if ca.is_ascii_digit() && cb.is_ascii_digit() => {
+                let a_len = a.iter().take_while(|c| c.is_ascii_digit()).count();
+                let b_len = b.iter().take_while(|c| c.is_ascii_digit()).count();
+                let (a_num, b_num) = (&a[..a_len], &b[..b_len]);
+                let a_trimmed = &a_num[a_num.iter().take_while(|c| **c == b'0').count()..];
+                let b_trimmed = &b_num[b_num.iter().take_while(|c| **c == b'0').count()..];
+                let ordering = a_trimmed
+                    .len()
+                    .cmp(&b_trimmed.len())
+                    .then_with(|| a_trimmed.cmp(b_trimmed))
+                    .then_with(|| a_len.cmp(&b_len));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a = &a[a_len..];
+                b = &b[b_len..];
+            }
+            (Some(ca), Some(cb)) => {
+                if ca != cb {
+                    return ca.cmp(cb);
+                }
+                a = &a[1..];
+                b = &b[1..];
+            }
+        }
+    }
+}
+
+fn is_bare_word(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+' | '.'))
+}
+
+fn write_quoted(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{{{:x}}}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_name(name: &str, out: &mut String) {
+    if is_bare_word(name) {
+        out.push_str(name);
+    } else {
+        write_quoted(name, out);
+    }
+}
+
+fn write_array<T: TextScalar>(values: &[T], out: &mut String) {
+    out.push('[');
+    for (i, value) in values.iter().enumerate() {
+        if i != 0 {
+            out.push_str(", ");
+        }
+        value.write_text(out);
+    }
+    out.push(']');
+}
+
+struct Writer<'a> {
+    visitor: &'a Visitor,
+    /// Raw identifiers of shared values, i.e. values of `Id` fields of regions with `RcData` or
+    /// `ArcData`.
+    shared_ids: FxHashSet<u64>,
+    /// Maps raw identifiers of shared values to their stable counterparts.
+    id_map: FxHashMap<u64, u64>,
+    out: String,
+}
+
+impl Writer<'_> {
+    fn collect_shared_ids(&mut self) {
+        for node in self.visitor.nodes.iter() {
+            let is_shared_data = |name: &str| name == "RcData" || name == "ArcData";
+            let has_data = node.fields.iter().any(|field| is_shared_data(&field.name))
+                || node
+                    .children
+                    .iter()
+                    .any(|child| is_shared_data(&self.visitor.nodes[*child].name));
+            if has_data {
+                for field in node.fields.iter() {
+                    if let (FieldKind::U64(id), "Id") = (&field.kind, field.name.as_str()) {
+                        self.shared_ids.insert(*id);
+                    }
+                }
+            }
+        }
+    }
+
+    fn stable_id(&mut self, raw: u64) -> u64 {
+        if raw == 0 || !self.shared_ids.contains(&raw) {
+            return raw;
+        }
+        let next = self.id_map.len() as u64 + 1;
+        *self.id_map.entry(raw).or_insert(next)
+    }
+
+    fn write_node(&mut self, handle: Handle<VisitorNode>, depth: usize) {
+        let visitor = self.visitor;
+        let node = &visitor.nodes[handle];
+
+        let mut fields = node.fields.iter().collect::<Vec<_>>();
+        fields.sort_by(|a, b| natural_cmp(&a.name, &b.name));
+        let mut children = node.children.clone();
+        children.sort_by(|a, b| natural_cmp(&visitor.nodes[*a].name, &visitor.nodes[*b].name));
+
+        for _ in 0..depth {
+            self.out.push_str(INDENT);
+        }
+        write_name(&node.name, &mut self.out);
+        if fields.is_empty() && children.is_empty() {
+            self.out.push_str(" {}\n");
+            return;
+        }
+        self.out.push_str(" {\n");
+        for field in fields {
+            self.write_field(field, depth + 1);
+        }
+        for child in children {
+            self.write_node(child, depth + 1);
+        }
+        for _ in 0..depth {
+            self.out.push_str(INDENT);
+        }
+        self.out.push_str("}\n");
+    }
+
+    fn write_field(&mut self, field: &Field, depth: usize) {
+        for _ in 0..depth {
+            self.out.push_str(INDENT);
+        }
+        write_name(&field.name, &mut self.out);
+        self.out.push_str(": ");
+
+        let stable_id;
+        let kind = match &field.kind {
+            FieldKind::U64(id) if field.name == "Id" => {
+                stable_id = FieldKind::U64(self.stable_id(*id));
+                &stable_id
+            }
+            kind => kind,
+        };
+
+        let out = &mut self.out;
+        match kind {
+            FieldKind::Bool(v) => write_scalar("bool", v, out),
+            FieldKind::U8(v) => write_scalar("u8", v, out),
+            FieldKind::I8(v) => write_scalar("i8", v, out),
+            FieldKind::U16(v) => write_scalar("u16", v, out),
+            FieldKind::I16(v) => write_scalar("i16", v, out),
+            FieldKind::U32(v) => write_scalar("u32", v, out),
+            FieldKind::I32(v) => write_scalar("i32", v, out),
+            FieldKind::U64(v) => write_scalar("u64", v, out),
+            FieldKind::I64(v) => write_scalar("i64", v, out),
+            FieldKind::F32(v) => write_scalar("f32", v, out),
+            FieldKind::F64(v) => write_scalar("f64", v, out),
+            FieldKind::UnitQuaternion(v) => write_values("quat", v.coords.as_slice(), out),
+            FieldKind::Matrix4(v) => write_values("mat4", v.as_slice(), out),
+            FieldKind::Matrix3(v) => write_values("mat3", v.as_slice(), out),
+            FieldKind::Matrix2(v) => write_values("mat2", v.as_slice(), out),
+            FieldKind::UnitComplex(v) => write_values("complex", &[v.re, v.im], out),
+            FieldKind::Uuid(v) => {
+                let _ = write!(out, "uuid = {v}");
+            }
+            FieldKind::BinaryBlob(data) => match std::str::from_utf8(data) {
+                Ok(s) => {
+                    out.push_str("str = ");
+                    write_quoted(s, out);
+                }
+                Err(_) => {
+                    out.push_str("blob = ");
+                    write_quoted(&base64::engine::general_purpose::STANDARD.encode(data), out);
+                }
+            },
+            FieldKind::PodArray {
+                type_id,
+                element_size,
+                bytes,
+            } => {
+                let _ = write!(out, "pod({type_id}, {element_size}) = ");
+                write_quoted(
+                    &base64::engine::general_purpose::STANDARD.encode(bytes),
+                    out,
+                );
+            }
+            FieldKind::Vector2F32(v) => write_values("vec2f32", v.as_slice(), out),
+            FieldKind::Vector3F32(v) => write_values("vec3f32", v.as_slice(), out),
+            FieldKind::Vector4F32(v) => write_values("vec4f32", v.as_slice(), out),
+            FieldKind::Vector2F64(v) => write_values("vec2f64", v.as_slice(), out),
+            FieldKind::Vector3F64(v) => write_values("vec3f64", v.as_slice(), out),
+            FieldKind::Vector4F64(v) => write_values("vec4f64", v.as_slice(), out),
+            FieldKind::Vector2U8(v) => write_values("vec2u8", v.as_slice(), out),
+            FieldKind::Vector3U8(v) => write_values("vec3u8", v.as_slice(), out),
+            FieldKind::Vector4U8(v) => write_values("vec4u8", v.as_slice(), out),
+            FieldKind::Vector2I8(v) => write_values("vec2i8", v.as_slice(), out),
+            FieldKind::Vector3I8(v) => write_values("vec3i8", v.as_slice(), out),
+            FieldKind::Vector4I8(v) => write_values("vec4i8", v.as_slice(), out),
+            FieldKind::Vector2U16(v) => write_values("vec2u16", v.as_slice(), out),
+            FieldKind::Vector3U16(v) => write_values("vec3u16", v.as_slice(), out),
+            FieldKind::Vector4U16(v) => write_values("vec4u16", v.as_slice(), out),
+            FieldKind::Vector2I16(v) => write_values("vec2i16", v.as_slice(), out),
+            FieldKind::Vector3I16(v) => write_values("vec3i16", v.as_slice(), out),
+            FieldKind::Vector4I16(v) => write_values("vec4i16", v.as_slice(), out),
+            FieldKind::Vector2U32(v) => write_values("vec2u32", v.as_slice(), out),
+            FieldKind::Vector3U32(v) => write_values("vec3u32", v.as_slice(), out),
+            FieldKind::Vector4U32(v) => write_values("vec4u32", v.as_slice(), out),
+            FieldKind::Vector2I32(v) => write_values("vec2i32", v.as_slice(), out),
+            FieldKind::Vector3I32(v) => write_values("vec3i32", v.as_slice(), out),
+            FieldKind::Vector4I32(v) => write_values("vec4i32", v.as_slice(), out),
+            FieldKind::Vector2U64(v) => write_values("vec2u64", v.as_slice(), out),
+            FieldKind::Vector3U64(v) => write_values("vec3u64", v.as_slice(), out),
+            FieldKind::Vector4U64(v) => write_values("vec4u64", v.as_slice(), out),
+            FieldKind::Vector2I64(v) => write_values("vec2i64", v.as_slice(), out),
+            FieldKind::Vector3I64(v) => write_values("vec3i64", v.as_slice(), out),
+            FieldKind::Vector4I64(v) => write_values("vec4i64", v.as_slice(), out),
+        }
+        self.out.push('\n');
+    }
+}
+
+fn write_scalar<T: TextScalar>(type_name: &str, value: &T, out: &mut String) {
+    out.push_str(type_name);
+    out.push_str(" = ");
+    value.write_text(out);
+}
+
+fn write_values<T: TextScalar>(type_name: &str, values: &[T], out: &mut String) {
+    out.push_str(type_name);
+    out.push_str(" = ");
+    write_array(values, out);
+}
+
+pub(super) fn write(visitor: &Visitor) -> String {
+    let mut writer = Writer {
+        visitor,
+        shared_ids: Default::default(),
+        id_map: Default::default(),
+        out: String::new(),
+    };
+    writer.collect_shared_ids();
+    let _ = writeln!(writer.out, "{} {}", Visitor::TEXT_MAGIC, VERSION);
+    writer.write_node(visitor.root, 0);
+    writer.out
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Punct(char),
+    Eof,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(word) => format!("`{word}`"),
+            Token::Str(_) => "string".to_string(),
+            Token::Punct(c) => format!("`{c}`"),
+            Token::Eof => "end of file".to_string(),
+        }
+    }
+}
+
+struct Lexer<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    source: &'a str,
+    line: usize,
+    peeked: Option<(Token, usize)>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        let source = source.strip_prefix('\u{feff}').unwrap_or(source);
+        Self {
+            chars: source.char_indices().peekable(),
+            source,
+            line: 1,
+            peeked: None,
+        }
+    }
+
+    fn error(&self, line: usize, message: impl Into<String>) -> VisitError {
+        VisitError::InvalidText {
+            line,
+            message: message.into(),
+        }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some((_, c)) = self.chars.peek().copied() {
+            if c == '\n' {
+                self.line += 1;
+                self.chars.next();
+            } else if c.is_whitespace() {
+                self.chars.next();
+            } else if c == '#' {
+                while let Some((_, c)) = self.chars.peek() {
+                    if *c == '\n' {
+                        break;
+                    }
+                    self.chars.next();
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn read_token(&mut self) -> Result<(Token, usize), VisitError> {
+        self.skip_whitespace_and_comments();
+        let line = self.line;
+        let Some((start, c)) = self.chars.next() else {
+            return Ok((Token::Eof, line));
+        };
+        if c == '"' {
+            let mut string = String::new();
+            loop {
+                match self.chars.next() {
+                    None => return Err(self.error(line, "unterminated string")),
+                    Some((_, '"')) => break,
+                    Some((_, '\\')) => match self.chars.next() {
+                        Some((_, '"')) => string.push('"'),
+                        Some((_, '\\')) => string.push('\\'),
+                        Some((_, 'n')) => string.push('\n'),
+                        Some((_, 'r')) => string.push('\r'),
+                        Some((_, 't')) => string.push('\t'),
+                        Some((_, 'u')) => string.push(self.read_unicode_escape(line)?),
+                        _ => return Err(self.error(self.line, "invalid escape sequence")),
+                    },
+                    Some((_, c)) => {
+                        if c == '\n' {
+                            self.line += 1;
+                        }
+                        string.push(c);
+                    }
+                }
+            }
+            Ok((Token::Str(string), line))
+        } else if is_bare_word(c.encode_utf8(&mut [0; 4])) {
+            let mut end = start + c.len_utf8();
+            while let Some((i, c)) = self.chars.peek().copied() {
+                if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+' | '.') {
+                    end = i + c.len_utf8();
+                    self.chars.next();
+                } else {
+                    break;
+                }
+            }
+            Ok((Token::Word(self.source[start..end].to_string()), line))
+        } else if matches!(c, '{' | '}' | '[' | ']' | '(' | ')' | ':' | '=' | ',') {
+            Ok((Token::Punct(c), line))
+        } else {
+            Err(self.error(line, format!("unexpected character `{c}`")))
+        }
+    }
+
+    fn read_unicode_escape(&mut self, line: usize) -> Result<char, VisitError> {
+        if !matches!(self.chars.next(), Some((_, '{'))) {
+            return Err(self.error(line, "invalid unicode escape sequence"));
+        }
+        let mut code = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '}')) => break,
+                Some((_, c)) if c.is_ascii_hexdigit() => code.push(c),
+                _ => return Err(self.error(line, "invalid unicode escape sequence")),
+            }
+        }
+        u32::from_str_radix(&code, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error(line, "invalid unicode escape sequence"))
+    }
+
+    fn next(&mut self) -> Result<(Token, usize), VisitError> {
+        match self.peeked.take() {
+            Some(token) => Ok(token),
+            None => self.read_token(),
+        }
+    }
+
+    fn peek(&mut self) -> Result<&Token, VisitError> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.read_token()?);
+        }
+        Ok(&self.peeked.as_ref().unwrap().0)
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), VisitError> {
+        match self.next()? {
+            (Token::Punct(p), _) if p == c => Ok(()),
+            (token, line) => {
+                Err(self.error(line, format!("expected `{c}`, got {}", token.describe())))
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<(String, usize), VisitError> {
+        match self.next()? {
+            (Token::Word(name) | Token::Str(name), line) => Ok((name, line)),
+            (token, line) => {
+                Err(self.error(line, format!("expected a name, got {}", token.describe())))
+            }
+        }
+    }
+
+    fn word(&mut self) -> Result<(String, usize), VisitError> {
+        match self.next()? {
+            (Token::Word(word), line) => Ok((word, line)),
+            (token, line) => {
+                Err(self.error(line, format!("expected a value, got {}", token.describe())))
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<(String, usize), VisitError> {
+        match self.next()? {
+            (Token::Str(string), line) => Ok((string, line)),
+            (token, line) => {
+                Err(self.error(line, format!("expected a string, got {}", token.describe())))
+            }
+        }
+    }
+
+    fn scalar<T: TextScalar>(&mut self) -> Result<T, VisitError> {
+        let (word, line) = self.word()?;
+        word.parse::<T>()
+            .map_err(|_| self.error(line, format!("invalid value `{word}`")))
+    }
+
+    fn array<T: TextScalar>(&mut self, count: usize) -> Result<Vec<T>, VisitError> {
+        self.expect('[')?;
+        let mut values = Vec::with_capacity(count);
+        for i in 0..count {
+            if i != 0 {
+                self.expect(',')?;
+            }
+            values.push(self.scalar()?);
+        }
+        self.expect(']')?;
+        Ok(values)
+    }
+
+    fn vector<T: TextScalar + Scalar, const N: usize>(
+        &mut self,
+    ) -> Result<SVector<T, N>, VisitError> {
+        Ok(SVector::from_column_slice(&self.array::<T>(N)?))
+    }
+
+    fn base64(&mut self) -> Result<Vec<u8>, VisitError> {
+        let (string, line) = self.string()?;
+        base64::engine::general_purpose::STANDARD
+            .decode(string)
+            .map_err(|e| self.error(line, format!("invalid base64 data: {e}")))
+    }
+
+    fn field(&mut self, name: String) -> Result<Field, VisitError> {
+        let (type_name, line) = self.word()?;
+        let pod_layout = if type_name == "pod" {
+            self.expect('(')?;
+            let type_id = self.scalar::<u8>()?;
+            self.expect(',')?;
+            let element_size = self.scalar::<u32>()?;
+            self.expect(')')?;
+            Some((type_id, element_size))
+        } else {
+            None
+        };
+        self.expect('=')?;
+
+        let kind = match type_name.as_str() {
+            "bool" => FieldKind::Bool(self.scalar()?),
+            "u8" => FieldKind::U8(self.scalar()?),
+            "i8" => FieldKind::I8(self.scalar()?),
+            "u16" => FieldKind::U16(self.scalar()?),
+            "i16" => FieldKind::I16(self.scalar()?),
+            "u32" => FieldKind::U32(self.scalar()?),
+            "i32" => FieldKind::I32(self.scalar()?),
+            "u64" => FieldKind::U64(self.scalar()?),
+            "i64" => FieldKind::I64(self.scalar()?),
+            "f32" => FieldKind::F32(self.scalar()?),
+            "f64" => FieldKind::F64(self.scalar()?),
+            "quat" => FieldKind::UnitQuaternion(UnitQuaternion::new_unchecked(Quaternion::from(
+                self.vector::<f32, 4>()?,
+            ))),
+            "mat4" => FieldKind::Matrix4(Matrix4::from_column_slice(&self.array(16)?)),
+            "mat3" => FieldKind::Matrix3(Matrix3::from_column_slice(&self.array(9)?)),
+            "mat2" => FieldKind::Matrix2(Matrix2::from_column_slice(&self.array(4)?)),
+            "complex" => {
+                let values = self.array::<f32>(2)?;
+                FieldKind::UnitComplex(UnitComplex::from_cos_sin_unchecked(values[0], values[1]))
+            }
+            "uuid" => {
+                let (word, line) = self.word()?;
+                FieldKind::Uuid(
+                    Uuid::parse_str(&word)
+                        .map_err(|_| self.error(line, format!("invalid uuid `{word}`")))?,
+                )
+            }
+            "str" => FieldKind::BinaryBlob(self.string()?.0.into_bytes()),
+            "blob" => FieldKind::BinaryBlob(self.base64()?),
+            "pod" => {
+                let (type_id, element_size) = pod_layout.unwrap();
+                FieldKind::PodArray {
+                    type_id,
+                    element_size,
+                    bytes: self.base64()?,
+                }
+            }
+            "vec2f32" => FieldKind::Vector2F32(self.vector()?),
+            "vec3f32" => FieldKind::Vector3F32(self.vector()?),
+            "vec4f32" => FieldKind::Vector4F32(self.vector()?),
+            "vec2f64" => FieldKind::Vector2F64(self.vector()?),
+            "vec3f64" => FieldKind::Vector3F64(self.vector()?),
+            "vec4f64" => FieldKind::Vector4F64(self.vector()?),
+            "vec2u8" => FieldKind::Vector2U8(self.vector()?),
+            "vec3u8" => FieldKind::Vector3U8(self.vector()?),
+            "vec4u8" => FieldKind::Vector4U8(self.vector()?),
+            "vec2i8" => FieldKind::Vector2I8(self.vector()?),
+            "vec3i8" => FieldKind::Vector3I8(self.vector()?),
+            "vec4i8" => FieldKind::Vector4I8(self.vector()?),
+            "vec2u16" => FieldKind::Vector2U16(self.vector()?),
+            "vec3u16" => FieldKind::Vector3U16(self.vector()?),
+            "vec4u16" => FieldKind::Vector4U16(self.vector()?),
+            "vec2i16" => FieldKind::Vector2I16(self.vector()?),
+            "vec3i16" => FieldKind::Vector3I16(self.vector()?),
+            "vec4i16" => FieldKind::Vector4I16(self.vector()?),
+            "vec2u32" => FieldKind::Vector2U32(self.vector()?),
+            "vec3u32" => FieldKind::Vector3U32(self.vector()?),
+            "vec4u32" => FieldKind::Vector4U32(self.vector()?),
+            "vec2i32" => FieldKind::Vector2I32(self.vector()?),
+            "vec3i32" => FieldKind::Vector3I32(self.vector()?),
+            "vec4i32" => FieldKind::Vector4I32(self.vector()?),
+            "vec2u64" => FieldKind::Vector2U64(self.vector()?),
+            "vec3u64" => FieldKind::Vector3U64(self.vector()?),
+            "vec4u64" => FieldKind::Vector4U64(self.vector()?),
+            "vec2i64" => FieldKind::Vector2I64(self.vector()?),
+            "vec3i64" => FieldKind::Vector3I64(self.vector()?),
+            "vec4i64" => FieldKind::Vector4I64(self.vector()?),
+            _ => {
+                return Err(self.error(line, format!("unknown field type `{type_name}`")));
+            }
+        };
+
+        Ok(Field::new(&name, kind))
+    }
+
+    /// Reads the contents of a node, assuming that its name and opening brace were already read.
+    fn node_body(
+        &mut self,
+        name: String,
+        nodes: &mut Pool<VisitorNode>,
+    ) -> Result<Handle<VisitorNode>, VisitError> {
+        let mut node = VisitorNode {
+            name,
+            ..VisitorNode::default()
+        };
+        loop {
+            if self.peek()? == &Token::Punct('}') {
+                self.next()?;
+                break;
+            }
+            let (name, line) = self.name()?;
+            match self.next()? {
+                (Token::Punct(':'), _) => {
+                    if node.fields.iter().any(|f| f.name == name) {
+                        return Err(self.error(line, format!("duplicate field `{name}`")));
+                    }
+                    node.fields.push(self.field(name)?);
+                }
+                (Token::Punct('{'), _) => {
+                    if node.children.iter().any(|c| nodes[*c].name == name) {
+                        return Err(self.error(line, format!("duplicate region `{name}`")));
+                    }
+                    node.children.push(self.node_body(name, nodes)?);
+                }
+                (token, line) => {
+                    return Err(self.error(
+                        line,
+                        format!("expected `:` or `{{`, got {}", token.describe()),
+                    ))
+                }
+            }
+        }
+
+        let children = node.children.clone();
+        let handle = nodes.spawn(node);
+        for child in children {
+            nodes[child].parent = handle;
+        }
+        Ok(handle)
+    }
+}
+
+pub(super) fn read(text: &str) -> Result<Visitor, VisitError> {
+    let mut lexer = Lexer::new(text);
+
+    match lexer.next()? {
+        (Token::Word(magic), _) if magic == Visitor::TEXT_MAGIC => (),
+        _ => return Err(VisitError::NotSupportedFormat),
+    }
+    let (version, line) = lexer.word()?;
+    if version.parse::<u32>().ok() != Some(VERSION) {
+        return Err(lexer.error(line, format!("unsupported version `{version}`")));
+    }
+
+    let mut nodes = Pool::new();
+    let (name, _) = lexer.name()?;
+    lexer.expect('{')?;
+    let root = lexer.node_body(name, &mut nodes)?;
+    match lexer.next()? {
+        (Token::Eof, _) => (),
+        (token, line) => {
+            return Err(lexer.error(
+                line,
+                format!("expected end of file, got {}", token.describe()),
+            ))
+        }
+    }
+
+    Ok(Visitor {
+        nodes,
+        rc_map: Default::default(),
+        arc_map: Default::default(),
+        reading: true,
+        current_node: root,
+        root,
+        blackboard: Blackboard::new(),
+        flags: VisitorFlags::NONE,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        algebra::{
+            Matrix2, Matrix3, Matrix4, UnitComplex, UnitQuaternion, Vector2, Vector3, Vector4,
+        },
+        visitor::{prelude::*, BinaryBlob, PodVecView},
+    };
+    use fxhash::FxHashMap;
+    use std::{cmp::Ordering, rc::Rc, sync::Arc};
+    use uuid::{uuid, Uuid};
+
+    use super::natural_cmp;
+
+    #[derive(Visit, Default, Debug, PartialEq)]
+    struct Everything {
+        bool: bool,
+        u8: u8,
+        i8: i8,
+        u16: u16,
+        i16: i16,
+        u32: u32,
+        i32: i32,
+        u64: u64,
+        i64: i64,
+        f32: f32,
+        f64: f64,
+        quat: UnitQuaternion<f32>,
+        mat4: Matrix4<f32>,
+        mat3: Matrix3<f32>,
+        mat2: Matrix2<f32>,
+        complex: UnitComplex<f32>,
+        uuid: Uuid,
+        string: String,
+        v2f32: Vector2<f32>,
+        v3f64: Vector3<f64>,
+        v4u8: Vector4<u8>,
+        v2i16: Vector2<i16>,
+        v3u32: Vector3<u32>,
+        v4i64: Vector4<i64>,
+        list: Vec<Option<String>>,
+        map: FxHashMap<String, f32>,
+    }
+
+    fn everything() -> Everything {
+        Everything {
+            bool: true,
+            u8: 255,
+            i8: -128,
+            u16: 65535,
+            i16: -32768,
+            u32: u32::MAX,
+            i32: i32::MIN,
+            u64: u64::MAX,
+            i64: i64::MIN,
+            f32: 0.1,
+            f64: -1.0e-300,
+            quat: UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3),
+            mat4: Matrix4::new_rotation(Vector3::new(0.3, 0.2, 0.1)),
+            mat3: Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.5),
+            mat2: Matrix2::new(1.0, -2.0, f32::INFINITY, f32::MIN_POSITIVE),
+            complex: UnitComplex::new(1.234),
+            uuid: uuid!("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+            string: "Multi-line\n \"quoted\" \\ string with ünicode and \u{1} control".to_string(),
+            v2f32: Vector2::new(1.0 / 3.0, -0.0),
+            v3f64: Vector3::new(std::f64::consts::PI, 2.0, 3.0),
+            v4u8: Vector4::new(1, 2, 3, 4),
+            v2i16: Vector2::new(-1, 1),
+            v3u32: Vector3::new(7, 8, 9),
+            v4i64: Vector4::new(-1, 0, 1, i64::MAX),
+            list: vec![Some("a".to_string()), None, Some(String::new())],
+            map: [("b".to_string(), 2.0), ("a".to_string(), 1.0)]
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let mut value = everything();
+        let mut blob = vec![0u8, 159, 146, 150, 255];
+        let mut pod = vec![1.5f32, -2.0, 1.0e10];
+
+        let mut visitor = Visitor::new();
+        value.visit("Everything", &mut visitor).unwrap();
+        BinaryBlob { vec: &mut blob }
+            .visit("Blob", &mut visitor)
+            .unwrap();
+        PodVecView::from_pod_vec(&mut pod)
+            .visit("Pod", &mut visitor)
+            .unwrap();
+        let text = visitor.save_text_stable();
+
+        let mut visitor = Visitor::load_text(&text).unwrap();
+        let mut loaded = Everything::default();
+        loaded.visit("Everything", &mut visitor).unwrap();
+        let mut loaded_blob = Vec::<u8>::new();
+        BinaryBlob {
+            vec: &mut loaded_blob,
+        }
+        .visit("Blob", &mut visitor)
+        .unwrap();
+        let mut loaded_pod = Vec::<f32>::new();
+        PodVecView::from_pod_vec(&mut loaded_pod)
+            .visit("Pod", &mut visitor)
+            .unwrap();
+
+        assert_eq!(loaded, value);
+        assert_eq!(loaded_blob, blob);
+        assert_eq!(loaded_pod, pod);
+        assert_eq!(visitor.save_text_stable(), text);
+    }
+
+    #[test]
+    fn test_text_auto_detection() {
+        let mut value = everything();
+        let mut visitor = Visitor::new();
+        value.visit("Everything", &mut visitor).unwrap();
+
+        let binary = visitor.save_binary_to_vec().unwrap();
+        let text = visitor.save_text_stable();
+
+        for data in [binary.as_slice(), text.as_bytes()] {
+            let mut visitor = Visitor::load_from_memory(data).unwrap();
+            let mut loaded = Everything::default();
+            loaded.visit("Everything", &mut visitor).unwrap();
+            assert_eq!(loaded, value);
+        }
+
+        assert!(matches!(
+            Visitor::load_from_memory(b"garbage"),
+            Err(VisitError::NotSupportedFormat)
+        ));
+    }
+
+    #[derive(Visit, Default)]
+    struct Shared {
+        rc: Option<Rc<u32>>,
+        another_rc: Option<Rc<u32>>,
+        arc: Option<Arc<String>>,
+    }
+
+    fn shared() -> Shared {
+        let rc = Rc::new(123);
+        Shared {
+            rc: Some(rc.clone()),
+            another_rc: Some(rc),
+            arc: Some(Arc::new("Foo".to_string())),
+        }
+    }
+
+    #[test]
+    fn test_text_stable_output() {
+        // Different allocations must give the same text.
+        let save = |value: &mut Shared| {
+            let mut visitor = Visitor::new();
+            value.visit("Shared", &mut visitor).unwrap();
+            visitor.save_text_stable()
+        };
+        let text = save(&mut shared());
+        assert_eq!(text, save(&mut shared()));
+        assert!(text.contains("Id: u64 = 1\n"));
+        assert!(text.contains("Id: u64 = 2\n"));
+
+        let mut visitor = Visitor::load_text(&text).unwrap();
+        let mut loaded = Shared::default();
+        loaded.visit("Shared", &mut visitor).unwrap();
+        assert!(Rc::ptr_eq(
+            loaded.rc.as_ref().unwrap(),
+            loaded.another_rc.as_ref().unwrap()
+        ));
+        assert_eq!(**loaded.rc.as_ref().unwrap(), 123);
+        assert_eq!(loaded.arc.as_deref().map(|s| s.as_str()), Some("Foo"));
+
+        // Order of visiting does not affect the output.
+        let mut a = 1u32;
+        let mut b = 2u32;
+        let mut first = Visitor::new();
+        a.visit("A", &mut first).unwrap();
+        b.visit("B", &mut first).unwrap();
+        let mut second = Visitor::new();
+        b.visit("B", &mut second).unwrap();
+        a.visit("A", &mut second).unwrap();
+        assert_eq!(first.save_text_stable(), second.save_text_stable());
+    }
+
+    #[test]
+    fn test_text_errors() {
+        let error = |text: &str| match Visitor::load_text(text) {
+            Err(VisitError::InvalidText { line, .. }) => line,
+            Err(e) => panic!("unexpected error {e}"),
+            Ok(_) => panic!("malformed text was parsed"),
+        };
+
+        assert_eq!(error("RG3D_TEXT 1\n__ROOT__ {\n    A: u8 = 256\n}\n"), 3);
+        assert_eq!(error("RG3D_TEXT 1\n__ROOT__ {\n\n    A: foo = 1\n}\n"), 4);
+        assert_eq!(error("RG3D_TEXT 1\n__ROOT__ {\n    A: u8 = 1\n"), 4);
+        assert_eq!(error("RG3D_TEXT 2\n__ROOT__ {}\n"), 1);
+        assert_eq!(
+            error("RG3D_TEXT 1\n__ROOT__ {\n    A: u8 = 1\n    A: u8 = 2\n}\n"),
+            4
+        );
+
+        // Comments and extra whitespace are fine.
+        let mut visitor =
+            Visitor::load_text("# comment\nRG3D_TEXT 1 __ROOT__ {\n  A:u8=\n7 # seven\n}").unwrap();
+        let mut a = 0u8;
+        a.visit("A", &mut visitor).unwrap();
+        assert_eq!(a, 7);
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("Item2", "Item10"), Ordering::Less);
+        assert_eq!(natural_cmp("Item10", "Item10"), Ordering::Equal);
+        assert_eq!(natural_cmp("Item010", "Item10"), Ordering::Greater);
+        assert_eq!(natural_cmp("A", "B"), Ordering::Less);
+        assert_eq!(natural_cmp("Item", "Item0"), Ordering::Less);
+    }
+}
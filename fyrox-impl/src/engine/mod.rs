@@ -39,15 +39,17 @@ use crate::{
         event::ResourceEvent,
         manager::{ResourceManager, ResourceWaitContext},
         state::ResourceState,
-        untyped::{ResourceKind, UntypedResource},
+        untyped::{self, ResourceKind, UntypedResource},
         Resource,
     },
     core::{
         algebra::Vector2,
         futures::{executor::block_on, future::join_all},
         instant,
-        log::Log,
+        log::{category, Log},
         pool::Handle,
+        profile_scope,
+        profiler::Profiler,
         reflect::Reflect,
         task::TaskPool,
         variable::try_inherit_properties,
@@ -75,7 +77,9 @@ use crate::{
     },
     renderer::{framework::error::FrameworkError, Renderer},
     resource::{
+        behavior::{loader::BehaviorTreeLoader, BehaviorTreeDefinition},
         curve::{loader::CurveLoader, CurveResourceState},
+        humanoid::{loader::HumanoidMappingLoader, HumanoidMapping},
         model::{loader::ModelLoader, Model, ModelResource},
         texture::{self, loader::TextureLoader, Texture, TextureKind},
     },
@@ -1279,6 +1283,8 @@ pub(crate) fn initialize_resource_manager_loaders(
     state.constructors_container.add::<Shader>();
     state.constructors_container.add::<Model>();
     state.constructors_container.add::<CurveResourceState>();
+    state.constructors_container.add::<HumanoidMapping>();
+    state.constructors_container.add::<BehaviorTreeDefinition>();
     state.constructors_container.add::<SoundBuffer>();
     state.constructors_container.add::<HrirSphereResourceData>();
     state.constructors_container.add::<Material>();
@@ -1302,6 +1308,8 @@ pub(crate) fn initialize_resource_manager_loaders(
     });
     loaders.set(ShaderLoader);
     loaders.set(CurveLoader);
+    loaders.set(HumanoidMappingLoader);
+    loaders.set(BehaviorTreeLoader);
     loaders.set(HrirSphereLoader);
     loaders.set(MaterialLoader {
         resource_manager: resource_manager.clone(),
@@ -1375,6 +1383,9 @@ impl Engine {
 
         initialize_resource_manager_loaders(&resource_manager, serialization_context.clone());
 
+        // Allows resources to be written as paths by the serde bridge of the reflection system.
+        untyped::register_serde_bridge();
+
         let (rx, tx) = channel();
         resource_manager.state().event_broadcaster.add(rx);
 
@@ -1569,6 +1580,8 @@ impl Engine {
         lag: &mut f32,
         switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
     ) {
+        profile_scope!("Engine::update");
+
         self.handle_async_scene_loading(dt, lag, window_target);
         self.pre_update(dt, window_target, lag, switches);
         self.post_update(dt, &Default::default(), lag, window_target);
@@ -1796,8 +1809,13 @@ impl Engine {
         lag: &mut f32,
         switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
     ) {
-        self.resource_manager.state().update(dt);
-        self.handle_model_events();
+        profile_scope!("Engine::pre_update");
+
+        {
+            profile_scope!(category: category::RESOURCE, "ResourceManager::update");
+            self.resource_manager.state().update(dt);
+            self.handle_model_events();
+        }
 
         let window_size = if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
             let inner_size = ctx.window.inner_size();
@@ -1844,6 +1862,8 @@ impl Engine {
         lag: &mut f32,
         window_target: &EventLoopWindowTarget<()>,
     ) {
+        profile_scope!("Engine::post_update");
+
         if let GraphicsContext::Initialized(ref ctx) = self.graphics_context {
             let inner_size = ctx.window.inner_size();
             let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
@@ -1871,6 +1891,8 @@ impl Engine {
     }
 
     fn handle_scripts(&mut self, dt: f32) {
+        profile_scope!(category: category::SCRIPT, "Scripts::update");
+
         let time = instant::Instant::now();
 
         self.script_processor.handle_scripts(
@@ -1986,6 +2008,8 @@ impl Engine {
         window_target: &EventLoopWindowTarget<()>,
         lag: &mut f32,
     ) {
+        profile_scope!(category: category::SCRIPT, "Plugins::update");
+
         let time = instant::Instant::now();
 
         if self.plugins_enabled {
@@ -2059,6 +2083,8 @@ impl Engine {
         window_target: &EventLoopWindowTarget<()>,
         lag: &mut f32,
     ) {
+        profile_scope!(category: category::SCRIPT, "Plugins::post_update");
+
         let time = instant::Instant::now();
 
         if self.plugins_enabled {
@@ -2276,9 +2302,18 @@ impl Engine {
     }
 
     /// Performs rendering of single frame, must be called from your game loop, otherwise you won't
-    /// see anything.
+    /// see anything. This method also finishes current frame of the [`Profiler`].
     #[inline]
     pub fn render(&mut self) -> Result<(), FrameworkError> {
+        let result = self.render_internal();
+        // Rendered frame is the natural boundary of the profiler frames.
+        Profiler::new_frame();
+        result
+    }
+
+    fn render_internal(&mut self) -> Result<(), FrameworkError> {
+        profile_scope!(category: category::RENDERER, "Engine::render");
+
         for ui in self.user_interfaces.iter_mut() {
             ui.set_time(self.elapsed_time);
             ui.draw();
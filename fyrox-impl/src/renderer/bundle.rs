@@ -61,6 +61,7 @@ use crate::{
     resource::texture::TextureResource,
     scene::{
         graph::Graph,
+        layer::LayerMask,
         light::{
             directional::{CsmOptions, DirectionalLight},
             point::PointLight,
@@ -738,12 +739,15 @@ pub struct RenderDataBundleStorage {
 
 pub struct RenderDataBundleStorageOptions {
     pub collect_lights: bool,
+    /// Only objects that belong to at least one layer of the mask will be collected.
+    pub layer_mask: LayerMask,
 }
 
 impl Default for RenderDataBundleStorageOptions {
     fn default() -> Self {
         Self {
             collect_lights: true,
+            layer_mask: LayerMask::ALL,
         }
     }
 }
@@ -864,19 +868,33 @@ impl RenderDataBundleStorage {
             node_handle: Handle<Node>,
             graph: &Graph,
             lod_filter: &[bool],
+            layer_mask: LayerMask,
             ctx: &mut RenderContext,
         ) {
             if lod_filter[node_handle.index() as usize] {
                 let node = graph.node(node_handle);
-                if let RdcControlFlow::Continue = node.collect_render_data(ctx) {
+                // Layers are not hierarchical, so the descendants of a filtered node must still
+                // be visited.
+                let control_flow = if node.layers().intersects(layer_mask) {
+                    node.collect_render_data(ctx)
+                } else {
+                    RdcControlFlow::Continue
+                };
+                if let RdcControlFlow::Continue = control_flow {
                     for child in node.children() {
-                        iterate_recursive(*child, graph, lod_filter, ctx);
+                        iterate_recursive(*child, graph, lod_filter, layer_mask, ctx);
                     }
                 }
             }
         }
 
-        iterate_recursive(graph.root(), graph, &lod_filter, &mut ctx);
+        iterate_recursive(
+            graph.root(),
+            graph,
+            &lod_filter,
+            options.layer_mask,
+            &mut ctx,
+        );
 
         storage.sort();
 
@@ -26,12 +26,14 @@
 //! RT3: RGBA8 - Metallic (x) + Roughness (y) + Ambient Occlusion (z)
 //! RT4: R8UI - Decal mask (x)
 //!
+//! Decals are blended into RT0-RT3 after the geometry pass.
+//!
 //! Every alpha channel is used for layer blending for terrains. This is inefficient, but for
 //! now I don't know better solution.
 
 use crate::{
     core::{
-        algebra::{Matrix4, Vector2},
+        algebra::{Matrix4, Vector2, Vector4},
         color::Color,
         math::Rect,
         sstorage::ImmutableString,
@@ -54,7 +56,7 @@ use crate::{
             gpu_texture::{GpuTexture, PixelKind},
             server::GraphicsServer,
             uniform::StaticUniformBuffer,
-            BlendFactor, BlendFunc, BlendParameters, DrawParameters, ElementRange,
+            BlendFactor, BlendFunc, BlendParameters, ColorMask, DrawParameters, ElementRange,
             GeometryBufferExt,
         },
         gbuffer::decal::DecalShader,
@@ -109,6 +111,8 @@ impl GBuffer {
     ) -> Result<Self, FrameworkError> {
         let diffuse_texture = server.create_2d_render_target(PixelKind::RGBA8, width, height)?;
         let normal_texture = server.create_2d_render_target(PixelKind::RGBA8, width, height)?;
+        let ambient_texture = server.create_2d_render_target(PixelKind::RGBA16F, width, height)?;
+        let material_texture = server.create_2d_render_target(PixelKind::RGBA8, width, height)?;
         let framebuffer = server.create_frame_buffer(
             Some(Attachment {
                 kind: AttachmentKind::DepthStencil,
@@ -125,11 +129,11 @@ impl GBuffer {
                 },
                 Attachment {
                     kind: AttachmentKind::Color,
-                    texture: server.create_2d_render_target(PixelKind::RGBA16F, width, height)?,
+                    texture: ambient_texture.clone(),
                 },
                 Attachment {
                     kind: AttachmentKind::Color,
-                    texture: server.create_2d_render_target(PixelKind::RGBA8, width, height)?,
+                    texture: material_texture.clone(),
                 },
                 Attachment {
                     kind: AttachmentKind::Color,
@@ -149,6 +153,14 @@ impl GBuffer {
                     kind: AttachmentKind::Color,
                     texture: normal_texture,
                 },
+                Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: ambient_texture,
+                },
+                Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: material_texture,
+                },
             ],
         )?;
 
@@ -289,9 +301,14 @@ impl GBuffer {
 
         // Render decals after because we need to modify diffuse texture of G-Buffer and use depth texture
         // for rendering. We'll render in the G-Buffer, but depth will be used from final frame, since
-        // decals do not modify depth (only diffuse and normal maps).
+        // decals do not modify depth (only diffuse, normal, ambient and material maps).
         let unit_cube = &self.cube;
         for decal in graph.linear_iter().filter_map(|n| n.cast::<Decal>()) {
+            let lifetime_fade = decal.lifetime_fade();
+            if lifetime_fade <= 0.0 {
+                continue;
+            }
+
             let shader = &self.decal_shader;
             let program = &*self.decal_shader.program;
 
@@ -309,50 +326,104 @@ impl GBuffer {
                 .unwrap_or(&fallback_resources.normal_dummy)
                 .clone();
 
-            statistics += self.decal_framebuffer.draw(
-                &**unit_cube,
-                viewport,
-                program,
-                &DrawParameters {
-                    cull_face: None,
-                    color_write: Default::default(),
-                    depth_write: false,
-                    stencil_test: None,
-                    depth_test: None,
-                    blend: Some(BlendParameters {
-                        func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
-                        ..Default::default()
-                    }),
-                    stencil_op: Default::default(),
-                    scissor_box: None,
+            let uv_rect = decal.uv_rect();
+            let blending = decal.channel_blending();
+            // Cosines of the fade angles, fading is disabled if the end angle is not greater than
+            // the start angle.
+            let angle_fade = decal.angle_fade();
+            let angle_fade = if angle_fade.y > angle_fade.x {
+                Vector2::new(
+                    angle_fade.x.to_radians().cos(),
+                    angle_fade.y.to_radians().cos(),
+                )
+            } else {
+                Vector2::new(-2.0, -3.0)
+            };
+
+            // The blending is premultiplied, so a zero output keeps the G-Buffer as is. Material
+            // map is blended in a separate pass, that writes metallic and roughness only, to keep
+            // ambient occlusion (stored in the blue channel) intact. Alpha channels are never written.
+            let main_pass = (
+                Vector4::new(blending.albedo, blending.normal, 0.0, blending.emission),
+                ColorMask {
+                    alpha: false,
+                    ..ColorMask::all(true)
                 },
-                &[ResourceBindGroup {
-                    bindings: &[
-                        ResourceBinding::texture(&depth, &shader.scene_depth),
-                        ResourceBinding::texture(&diffuse_texture, &shader.diffuse_texture),
-                        ResourceBinding::texture(&normal_texture, &shader.normal_texture),
-                        ResourceBinding::texture(&decal_mask, &shader.decal_mask),
-                        ResourceBinding::Buffer {
-                            buffer: uniform_buffer_cache.write(
-                                StaticUniformBuffer::<256>::new()
-                                    .with(&world_view_proj)
-                                    .with(&inv_view_proj)
-                                    .with(
-                                        &decal.global_transform().try_inverse().unwrap_or_default(),
-                                    )
-                                    .with(&resolution)
-                                    .with(&decal.color().srgb_to_linear_f32())
-                                    .with(&(decal.layer() as u32)),
-                            )?,
-                            binding: BufferLocation::Auto {
-                                shader_location: shader.uniform_buffer_binding,
+            );
+            let material_pass = (
+                Vector4::new(0.0, 0.0, blending.material, 0.0),
+                ColorMask {
+                    red: true,
+                    green: true,
+                    ..ColorMask::all(false)
+                },
+            );
+            for (blend_factors, color_write) in
+                std::iter::once(main_pass).chain((blending.material > 0.0).then_some(material_pass))
+            {
+                statistics += self.decal_framebuffer.draw(
+                    &**unit_cube,
+                    viewport,
+                    program,
+                    &DrawParameters {
+                        cull_face: None,
+                        color_write,
+                        depth_write: false,
+                        stencil_test: None,
+                        depth_test: None,
+                        blend: Some(BlendParameters {
+                            func: BlendFunc::new(BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+                            ..Default::default()
+                        }),
+                        stencil_op: Default::default(),
+                        scissor_box: None,
+                    },
+                    &[ResourceBindGroup {
+                        bindings: &[
+                            ResourceBinding::texture(&depth, &shader.scene_depth),
+                            ResourceBinding::texture(&diffuse_texture, &shader.diffuse_texture),
+                            ResourceBinding::texture(&normal_texture, &shader.normal_texture),
+                            ResourceBinding::texture(&decal_mask, &shader.decal_mask),
+                            ResourceBinding::Buffer {
+                                buffer: uniform_buffer_cache.write(
+                                    StaticUniformBuffer::<512>::new()
+                                        .with(&world_view_proj)
+                                        .with(&inv_view_proj)
+                                        .with(
+                                            &decal
+                                                .global_transform()
+                                                .try_inverse()
+                                                .unwrap_or_default(),
+                                        )
+                                        .with(&resolution)
+                                        .with(&decal.color().srgb_to_linear_f32())
+                                        .with(&(decal.layer() as u32))
+                                        .with(&Vector4::new(
+                                            uv_rect.position.x,
+                                            uv_rect.position.y,
+                                            uv_rect.size.x,
+                                            uv_rect.size.y,
+                                        ))
+                                        .with(&blend_factors)
+                                        .with(&Vector4::new(
+                                            blending.metallic,
+                                            blending.roughness,
+                                            blending.emission_strength,
+                                            lifetime_fade,
+                                        ))
+                                        .with(&decal.global_transform().column(1).xyz().normalize())
+                                        .with(&angle_fade),
+                                )?,
+                                binding: BufferLocation::Auto {
+                                    shader_location: shader.uniform_buffer_binding,
+                                },
+                                data_usage: Default::default(),
                             },
-                            data_usage: Default::default(),
-                        },
-                    ],
-                }],
-                ElementRange::Full,
-            )?;
+                        ],
+                    }],
+                    ElementRange::Full,
+                )?;
+            }
         }
 
         Ok(statistics)
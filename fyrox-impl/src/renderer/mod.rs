@@ -58,9 +58,10 @@ use crate::{
         array_as_u8_slice,
         color::Color,
         instant,
-        log::{Log, MessageKind},
+        log::{category, Log, MessageKind},
         math::Rect,
         pool::Handle,
+        profile_scope,
         reflect::prelude::*,
         sstorage::ImmutableString,
         uuid_provider,
@@ -1291,6 +1292,8 @@ impl Renderer {
         elapsed_time: f32,
         dt: f32,
     ) -> Result<&AssociatedSceneData, FrameworkError> {
+        profile_scope!(category: category::RENDERER, "Renderer::render_scene");
+
         let graph = &scene.graph;
 
         let backbuffer_width = self.frame_size.0 as f32;
@@ -1387,6 +1390,7 @@ impl Renderer {
                 GBUFFER_PASS_NAME.clone(),
                 RenderDataBundleStorageOptions {
                     collect_lights: true,
+                    layer_mask: camera.culling_mask(),
                 },
             );
 
@@ -1646,6 +1650,7 @@ impl Renderer {
 
         // Render UI on top of everything without gamma correction.
         for drawing_context in drawing_contexts {
+            profile_scope!(category: category::RENDERER, "Renderer::render_ui");
             self.statistics += self.ui_renderer.render(UiRenderContext {
                 server: &*self.server,
                 viewport: window_viewport,
@@ -1687,7 +1692,10 @@ impl Renderer {
         self.render_frame(scenes, elapsed_time, drawing_contexts)?;
         self.statistics.end_frame();
         window.pre_present_notify();
-        self.graphics_server().swap_buffers()?;
+        {
+            profile_scope!(category: category::RENDERER, "Renderer::swap_buffers");
+            self.graphics_server().swap_buffers()?;
+        }
         self.statistics.finalize();
         self.statistics.pipeline = self.server.pipeline_statistics();
         Ok(())
@@ -10,10 +10,21 @@ layout (std140) uniform Uniforms {
     vec2 resolution;
     vec4 color;
     uint layerIndex;
+    // Position (xy) and size (zw) of the texture rectangle.
+    vec4 uvRect;
+    // Blend factors of albedo (x), normal (y), material (z) and emission (w).
+    vec4 blendFactors;
+    // Metallic (x), roughness (y), emission strength (z) and lifetime fade (w).
+    vec4 materialParams;
+    vec3 projectionDirection;
+    // Cosines of the angles at which the angle fade starts (x) and ends (y).
+    vec2 angleFade;
 };
 
 layout (location = 0) out vec4 outDiffuseMap;
 layout (location = 1) out vec4 outNormalMap;
+layout (location = 2) out vec4 outAmbientMap;
+layout (location = 3) out vec4 outMaterialMap;
 
 in vec4 clipSpacePosition;
 
@@ -45,9 +56,7 @@ void main()
         discard;
     }
 
-    vec2 decalTexCoord = decalSpacePosition.xz + 0.5;
-
-    outDiffuseMap = color * texture(diffuseTexture, decalTexCoord);
+    vec2 decalTexCoord = uvRect.xy + (decalSpacePosition.xz + 0.5) * uvRect.zw;
 
     vec3 fragmentTangent = dFdx(sceneWorldPosition);
     vec3 fragmentBinormal = dFdy(sceneWorldPosition);
@@ -58,7 +67,23 @@ void main()
     tangentToWorld[1] = normalize(fragmentBinormal); // Binormal
     tangentToWorld[2] = normalize(fragmentNormal); // Normal
 
+    // Fade out on the surfaces that are not facing the projection direction.
+    float cosAngle = abs(dot(tangentToWorld[2], projectionDirection));
+    float fade = clamp((cosAngle - angleFade.y) / (angleFade.x - angleFade.y), 0.0, 1.0);
+
+    vec4 decalColor = color * texture(diffuseTexture, decalTexCoord);
+    float alpha = decalColor.a * fade * materialParams.w;
+
+    // Outputs are premultiplied by their blend factors, see the decal rendering code.
+    outDiffuseMap = vec4(decalColor.rgb, 1.0) * (alpha * blendFactors.x);
+
     vec3 rawNormal = (texture(normalTexture, decalTexCoord) * 2.0 - 1.0).xyz;
     vec3 worldSpaceNormal = tangentToWorld * rawNormal;
-    outNormalMap = vec4(worldSpaceNormal * 0.5 + 0.5, outDiffuseMap.a);
+    outNormalMap = vec4(worldSpaceNormal * 0.5 + 0.5, 1.0) * (alpha * blendFactors.y);
+
+    // Ambient occlusion (z) is not written, so it is kept as is.
+    outMaterialMap = vec4(materialParams.x, materialParams.y, 0.0, 1.0) * (alpha * blendFactors.z);
+
+    // Zero alpha makes the emission to be added on top of the ambient term.
+    outAmbientMap = vec4(decalColor.rgb * materialParams.z * alpha * blendFactors.w, 0.0);
 }
\ No newline at end of file
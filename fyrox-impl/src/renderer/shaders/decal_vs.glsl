@@ -7,6 +7,15 @@ layout (std140) uniform Uniforms {
     vec2 resolution;
     vec4 color;
     uint layerIndex;
+    // Position (xy) and size (zw) of the texture rectangle.
+    vec4 uvRect;
+    // Blend factors of albedo (x), normal (y), material (z) and emission (w).
+    vec4 blendFactors;
+    // Metallic (x), roughness (y), emission strength (z) and lifetime fade (w).
+    vec4 materialParams;
+    vec3 projectionDirection;
+    // Cosines of the angles at which the angle fade starts (x) and ends (y).
+    vec2 angleFade;
 };
 
 out vec4 clipSpacePosition;
@@ -263,6 +263,7 @@ impl CsmRenderer {
                 DIRECTIONAL_SHADOW_PASS_NAME.clone(),
                 RenderDataBundleStorageOptions {
                     collect_lights: false,
+                    ..Default::default()
                 },
             );
 
@@ -231,6 +231,7 @@ impl PointShadowMapRenderer {
                 POINT_SHADOW_PASS_NAME.clone(),
                 RenderDataBundleStorageOptions {
                     collect_lights: false,
+                    ..Default::default()
                 },
             );
 
@@ -154,6 +154,7 @@ impl SpotShadowMapRenderer {
             SPOT_SHADOW_PASS_NAME.clone(),
             RenderDataBundleStorageOptions {
                 collect_lights: false,
+                ..Default::default()
             },
         );
 
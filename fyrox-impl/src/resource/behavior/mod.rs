@@ -0,0 +1,358 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Behavior tree definition is a data-driven description of a behavior tree, which could be stored in
+//! a file (with `.behavior` extension) and edited without recompilation. Leaves of the definition refer
+//! to actions by their names, the actual actions are created by the game when the definition is
+//! instantiated, see [`BehaviorTreeDefinition::instantiate`].
+
+use crate::{
+    asset::{io::ResourceIo, Resource, ResourceData},
+    core::{
+        io::FileLoadError, pool::Handle, reflect::prelude::*, uuid_provider, visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    utils::behavior::{
+        blackboard::Blackboard,
+        composite::{CompositeNode, CompositeNodeKind},
+        decorator::{Decorator, DecoratorKind},
+        inverter::Inverter,
+        leaf::LeafNode,
+        parallel::{ParallelNode, ParallelPolicy},
+        BehaviorNode, BehaviorTree,
+    },
+};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    path::Path,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+pub mod loader;
+
+/// An error that may occur during behavior tree definition loading or instantiation.
+#[derive(Debug)]
+pub enum BehaviorTreeError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// An error that may occur due to version incompatibilities.
+    Visit(VisitError),
+
+    /// A leaf refers to an action, that is unknown to the action factory.
+    UnknownAction(String),
+
+    /// A node refers to a child, that does not exist.
+    InvalidChild {
+        /// Index of the node.
+        node: usize,
+        /// Index of the child.
+        child: usize,
+    },
+
+    /// A node has wrong amount of children (for example, a decorator with two children).
+    InvalidChildrenCount {
+        /// Index of the node.
+        node: usize,
+    },
+
+    /// A node is an ancestor of itself.
+    Cycle {
+        /// Index of the node.
+        node: usize,
+    },
+}
+
+impl Display for BehaviorTreeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BehaviorTreeError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            BehaviorTreeError::Visit(v) => {
+                write!(
+                    f,
+                    "An error that may occur due to version incompatibilities. {v:?}"
+                )
+            }
+            BehaviorTreeError::UnknownAction(v) => {
+                write!(f, "Unknown action {v}")
+            }
+            BehaviorTreeError::InvalidChild { node, child } => {
+                write!(f, "Node {node} refers to non-existent child {child}")
+            }
+            BehaviorTreeError::InvalidChildrenCount { node } => {
+                write!(f, "Node {node} has invalid amount of children")
+            }
+            BehaviorTreeError::Cycle { node } => {
+                write!(f, "Node {node} is an ancestor of itself")
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for BehaviorTreeError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<VisitError> for BehaviorTreeError {
+    fn from(e: VisitError) -> Self {
+        Self::Visit(e)
+    }
+}
+
+/// Kind of a node of a behavior tree definition.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum BehaviorNodeKind {
+    /// A composite node (sequence or one of the selectors).
+    Composite(CompositeNodeKind),
+    /// A parallel node.
+    Parallel {
+        /// Defines when the node finishes with success.
+        success_policy: ParallelPolicy,
+        /// Defines when the node finishes with failure.
+        failure_policy: ParallelPolicy,
+    },
+    /// An inverter, must have exactly one child.
+    Inverter,
+    /// A decorator, must have exactly one child.
+    Decorator(DecoratorKind),
+    /// A leaf with an action, must not have any children.
+    Leaf {
+        /// Name of the action.
+        action: String,
+    },
+}
+
+uuid_provider!(BehaviorNodeKind = "3e5f7a9b-1c2d-4e6f-8a0b-2c4d6e8f0a1b");
+
+impl Default for BehaviorNodeKind {
+    fn default() -> Self {
+        Self::Composite(Default::default())
+    }
+}
+
+/// A node of a behavior tree definition.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct BehaviorNodeDefinition {
+    /// Name of the node, it is used only for debugging purposes.
+    pub name: String,
+    /// Kind of the node.
+    pub kind: BehaviorNodeKind,
+    /// Indices of children nodes.
+    pub children: Vec<u32>,
+}
+
+uuid_provider!(BehaviorNodeDefinition = "6b8d0f2a-4c6e-4a1b-9d3f-5e7a9c1b3d5f");
+
+/// See module docs. The first node of the definition is the entry node of the tree.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct BehaviorTreeDefinition {
+    /// Nodes of the tree.
+    pub nodes: Vec<BehaviorNodeDefinition>,
+    /// Initial values of the blackboard.
+    pub blackboard: Blackboard,
+}
+
+uuid_provider!(BehaviorTreeDefinition = "d1a3c5e7-9b2d-4f6a-8c0e-1b3d5f7a9c2e");
+
+/// A resource with behavior tree definition.
+pub type BehaviorTreeResource = Resource<BehaviorTreeDefinition>;
+
+impl ResourceData for BehaviorTreeDefinition {
+    fn type_uuid(&self) -> crate::core::uuid::Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut visitor = Visitor::new();
+        self.visit("BehaviorTree", &mut visitor)?;
+        visitor.save_binary(path)?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+impl BehaviorTreeDefinition {
+    /// Load a behavior tree definition from the specific file path.
+    pub async fn from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, BehaviorTreeError> {
+        let bytes = io.load_file(path).await?;
+        let mut visitor = Visitor::load_from_memory(&bytes)?;
+        let mut definition = Self::default();
+        definition.visit("BehaviorTree", &mut visitor)?;
+        Ok(definition)
+    }
+
+    /// Adds a new node to the definition and returns its index.
+    pub fn add_node(
+        &mut self,
+        name: impl Into<String>,
+        kind: BehaviorNodeKind,
+        children: Vec<u32>,
+    ) -> u32 {
+        self.nodes.push(BehaviorNodeDefinition {
+            name: name.into(),
+            kind,
+            children,
+        });
+        self.nodes.len() as u32 - 1
+    }
+
+    /// Creates a new behavior tree using the definition. Leaf actions are created by the given
+    /// factory, which receives names of the actions.
+    pub fn instantiate<B, F>(&self, mut factory: F) -> Result<BehaviorTree<B>, BehaviorTreeError>
+    where
+        B: Clone + 'static,
+        F: FnMut(&str) -> Option<B>,
+    {
+        let mut tree = BehaviorTree::new();
+        *tree.blackboard_mut() = self.blackboard.clone();
+        if !self.nodes.is_empty() {
+            let mut stack = Vec::new();
+            let entry = self.instantiate_node(0, &mut tree, &mut factory, &mut stack)?;
+            tree.set_entry_node(entry);
+        }
+        Ok(tree)
+    }
+
+    fn instantiate_node<B, F>(
+        &self,
+        index: usize,
+        tree: &mut BehaviorTree<B>,
+        factory: &mut F,
+        stack: &mut Vec<usize>,
+    ) -> Result<Handle<BehaviorNode<B>>, BehaviorTreeError>
+    where
+        B: Clone + 'static,
+        F: FnMut(&str) -> Option<B>,
+    {
+        if stack.contains(&index) {
+            return Err(BehaviorTreeError::Cycle { node: index });
+        }
+        stack.push(index);
+
+        let definition = &self.nodes[index];
+        let mut children = Vec::with_capacity(definition.children.len());
+        for child in definition.children.iter() {
+            let child = *child as usize;
+            if child >= self.nodes.len() {
+                return Err(BehaviorTreeError::InvalidChild { node: index, child });
+            }
+            children.push(self.instantiate_node(child, tree, factory, stack)?);
+        }
+
+        stack.pop();
+
+        let single_child = |children: &[Handle<BehaviorNode<B>>]| match children {
+            [child] => Ok(*child),
+            _ => Err(BehaviorTreeError::InvalidChildrenCount { node: index }),
+        };
+
+        let node = match definition.kind {
+            BehaviorNodeKind::Composite(ref kind) => {
+                BehaviorNode::Composite(CompositeNode::new(kind.clone(), children))
+            }
+            BehaviorNodeKind::Parallel {
+                success_policy,
+                failure_policy,
+            } => BehaviorNode::Parallel(
+                ParallelNode::new(children).with_policies(success_policy, failure_policy),
+            ),
+            BehaviorNodeKind::Inverter => {
+                BehaviorNode::Inverter(Inverter::new(single_child(&children)?))
+            }
+            BehaviorNodeKind::Decorator(ref kind) => {
+                BehaviorNode::Decorator(Decorator::new(kind.clone(), single_child(&children)?))
+            }
+            BehaviorNodeKind::Leaf { ref action } => {
+                if !children.is_empty() {
+                    return Err(BehaviorTreeError::InvalidChildrenCount { node: index });
+                }
+                let behavior = factory(action)
+                    .ok_or_else(|| BehaviorTreeError::UnknownAction(action.clone()))?;
+                BehaviorNode::Leaf(LeafNode::new(behavior))
+            }
+        };
+
+        Ok(tree.add_node(node))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        resource::behavior::{BehaviorNodeKind, BehaviorTreeDefinition, BehaviorTreeError},
+        utils::behavior::{composite::CompositeNodeKind, decorator::DecoratorKind, BehaviorNode},
+    };
+
+    #[test]
+    fn test_instantiate() {
+        let mut definition = BehaviorTreeDefinition::default();
+        definition.add_node(
+            "Root",
+            BehaviorNodeKind::Composite(CompositeNodeKind::Selector),
+            vec![1, 3],
+        );
+        definition.add_node(
+            "Repeat",
+            BehaviorNodeKind::Decorator(DecoratorKind::Repeat { count: 2 }),
+            vec![2],
+        );
+        definition.add_node(
+            "Attack",
+            BehaviorNodeKind::Leaf {
+                action: "Attack".to_string(),
+            },
+            vec![],
+        );
+        definition.add_node(
+            "Idle",
+            BehaviorNodeKind::Leaf {
+                action: "Idle".to_string(),
+            },
+            vec![],
+        );
+
+        let tree = definition
+            .instantiate(|action| Some(action.to_string()))
+            .unwrap();
+        let entry = tree[tree.root()].children()[0];
+        let BehaviorNode::Composite(selector) = &tree[entry] else {
+            panic!("entry must be a selector")
+        };
+        assert_eq!(selector.children.len(), 2);
+
+        assert!(matches!(
+            definition.instantiate(|action| (action == "Idle").then(|| action.to_string())),
+            Err(BehaviorTreeError::UnknownAction(action)) if action == "Attack"
+        ));
+
+        definition.nodes[2].children.push(0);
+        assert!(matches!(
+            definition.instantiate(|action| Some(action.to_string())),
+            Err(BehaviorTreeError::Cycle { node: 0 })
+        ));
+    }
+}
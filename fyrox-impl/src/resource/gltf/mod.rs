@@ -39,7 +39,7 @@ use crate::graph::BaseSceneGraph;
 use crate::graph::NodeMapping;
 use crate::gui::core::io::FileLoadError;
 use crate::material::MaterialResource;
-use crate::resource::model::{MaterialSearchOptions, Model, ModelImportOptions};
+use crate::resource::model::{self, MaterialSearchOptions, Model, ModelImportOptions};
 use crate::resource::texture::{TextureError, TextureResource};
 use crate::scene::animation::{AnimationContainer, AnimationPlayerBuilder};
 use crate::scene::base::BaseBuilder;
@@ -332,6 +332,7 @@ async fn load(
     scene.graph[root].set_name(root_name.clone());
     import_from_path(&mut scene.graph, &context).await?;
     node_names::resolve_name_conflicts(context.model_path.as_path(), &mut scene.graph);
+    model::compress_imported_animations(&mut scene, &options.animation_compression, &path);
     Ok(Model::new(NodeMapping::UseNames, scene))
 }
 
@@ -0,0 +1,481 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Humanoid mapping resource binds standard humanoid bones to scene nodes of a particular character rig.
+//! It is used by animation retargeting, see [`crate::scene::animation::retarget`] module docs for more info.
+
+use crate::{
+    asset::{io::ResourceIo, Resource, ResourceData},
+    core::{
+        io::FileLoadError, pool::Handle, reflect::prelude::*, uuid_provider, visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    graph::SceneGraph,
+    scene::{graph::Graph, node::Node},
+};
+use fxhash::FxHashMap;
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    path::Path,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+pub mod loader;
+
+/// An error that may occur during humanoid mapping resource loading.
+#[derive(Debug)]
+pub enum HumanoidMappingError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// An error that may occur due to version incompatibilities.
+    Visit(VisitError),
+}
+
+impl Display for HumanoidMappingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HumanoidMappingError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            HumanoidMappingError::Visit(v) => {
+                write!(
+                    f,
+                    "An error that may occur due to version incompatibilities. {v:?}"
+                )
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for HumanoidMappingError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<VisitError> for HumanoidMappingError {
+    fn from(e: VisitError) -> Self {
+        Self::Visit(e)
+    }
+}
+
+/// A standard bone of a humanoid skeleton.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+#[allow(missing_docs)]
+pub enum HumanoidBone {
+    #[default]
+    Hips,
+    Spine,
+    Chest,
+    UpperChest,
+    Neck,
+    Head,
+    LeftShoulder,
+    LeftUpperArm,
+    LeftLowerArm,
+    LeftHand,
+    RightShoulder,
+    RightUpperArm,
+    RightLowerArm,
+    RightHand,
+    LeftUpperLeg,
+    LeftLowerLeg,
+    LeftFoot,
+    LeftToes,
+    RightUpperLeg,
+    RightLowerLeg,
+    RightFoot,
+    RightToes,
+}
+
+uuid_provider!(HumanoidBone = "f6f1e8f6-1b1c-4d3e-9f80-8c4d3c6f1e25");
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Side {
+    Center,
+    Left,
+    Right,
+}
+
+impl HumanoidBone {
+    /// All humanoid bones, parents go before their children.
+    pub const ALL: [HumanoidBone; 22] = [
+        HumanoidBone::Hips,
+        HumanoidBone::Spine,
+        HumanoidBone::Chest,
+        HumanoidBone::UpperChest,
+        HumanoidBone::Neck,
+        HumanoidBone::Head,
+        HumanoidBone::LeftShoulder,
+        HumanoidBone::LeftUpperArm,
+        HumanoidBone::LeftLowerArm,
+        HumanoidBone::LeftHand,
+        HumanoidBone::RightShoulder,
+        HumanoidBone::RightUpperArm,
+        HumanoidBone::RightLowerArm,
+        HumanoidBone::RightHand,
+        HumanoidBone::LeftUpperLeg,
+        HumanoidBone::LeftLowerLeg,
+        HumanoidBone::LeftFoot,
+        HumanoidBone::LeftToes,
+        HumanoidBone::RightUpperLeg,
+        HumanoidBone::RightLowerLeg,
+        HumanoidBone::RightFoot,
+        HumanoidBone::RightToes,
+    ];
+
+    /// Returns a parent of the bone in the standard humanoid hierarchy. Keep in mind, that a rig may skip some
+    /// of the bones (for example, [`HumanoidBone::UpperChest`]).
+    pub fn parent(self) -> Option<HumanoidBone> {
+        match self {
+            HumanoidBone::Hips => None,
+            HumanoidBone::Spine | HumanoidBone::LeftUpperLeg | HumanoidBone::RightUpperLeg => {
+                Some(HumanoidBone::Hips)
+            }
+            HumanoidBone::Chest => Some(HumanoidBone::Spine),
+            HumanoidBone::UpperChest => Some(HumanoidBone::Chest),
+            HumanoidBone::Neck | HumanoidBone::LeftShoulder | HumanoidBone::RightShoulder => {
+                Some(HumanoidBone::UpperChest)
+            }
+            HumanoidBone::Head => Some(HumanoidBone::Neck),
+            HumanoidBone::LeftUpperArm => Some(HumanoidBone::LeftShoulder),
+            HumanoidBone::LeftLowerArm => Some(HumanoidBone::LeftUpperArm),
+            HumanoidBone::LeftHand => Some(HumanoidBone::LeftLowerArm),
+            HumanoidBone::RightUpperArm => Some(HumanoidBone::RightShoulder),
+            HumanoidBone::RightLowerArm => Some(HumanoidBone::RightUpperArm),
+            HumanoidBone::RightHand => Some(HumanoidBone::RightLowerArm),
+            HumanoidBone::LeftLowerLeg => Some(HumanoidBone::LeftUpperLeg),
+            HumanoidBone::LeftFoot => Some(HumanoidBone::LeftLowerLeg),
+            HumanoidBone::LeftToes => Some(HumanoidBone::LeftFoot),
+            HumanoidBone::RightLowerLeg => Some(HumanoidBone::RightUpperLeg),
+            HumanoidBone::RightFoot => Some(HumanoidBone::RightLowerLeg),
+            HumanoidBone::RightToes => Some(HumanoidBone::RightFoot),
+        }
+    }
+
+    fn side(self) -> Side {
+        let name: &str = self.as_ref();
+        if name.starts_with("Left") {
+            Side::Left
+        } else if name.starts_with("Right") {
+            Side::Right
+        } else {
+            Side::Center
+        }
+    }
+
+    // Names (lowercase, without separators and side markers) used by common rigs (Mixamo, Unreal, Blender
+    // Rigify, 3ds Max Biped) for the bone.
+    fn common_names(self) -> &'static [&'static str] {
+        match self {
+            HumanoidBone::Hips => &["hips", "pelvis", "hip"],
+            HumanoidBone::Spine => &["spine", "spine01", "spine0"],
+            HumanoidBone::Chest => &["chest", "spine1", "spine02"],
+            HumanoidBone::UpperChest => &["upperchest", "spine2", "spine03"],
+            HumanoidBone::Neck => &["neck", "neck01", "neck1"],
+            HumanoidBone::Head => &["head"],
+            HumanoidBone::LeftShoulder | HumanoidBone::RightShoulder => {
+                &["shoulder", "clavicle", "collar"]
+            }
+            HumanoidBone::LeftUpperArm | HumanoidBone::RightUpperArm => {
+                &["arm", "upperarm", "uparm"]
+            }
+            HumanoidBone::LeftLowerArm | HumanoidBone::RightLowerArm => {
+                &["forearm", "lowerarm", "elbow"]
+            }
+            HumanoidBone::LeftHand | HumanoidBone::RightHand => &["hand", "wrist"],
+            HumanoidBone::LeftUpperLeg | HumanoidBone::RightUpperLeg => {
+                &["upleg", "upperleg", "thigh"]
+            }
+            HumanoidBone::LeftLowerLeg | HumanoidBone::RightLowerLeg => {
+                &["leg", "lowerleg", "calf", "shin", "knee"]
+            }
+            HumanoidBone::LeftFoot | HumanoidBone::RightFoot => &["foot", "ankle"],
+            HumanoidBone::LeftToes | HumanoidBone::RightToes => &["toebase", "toes", "toe", "ball"],
+        }
+    }
+}
+
+// Splits a node name into a side and a "core" name, for example `mixamorig:LeftUpLeg` is split into
+// `(Left, "upleg")` and `thigh_r` into `(Right, "thigh")`.
+fn classify_name(name: &str) -> (Side, String) {
+    let name = name.rsplit(':').next().unwrap_or(name).to_lowercase();
+    let mut tokens = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>();
+
+    // Remove common prefixes of rigs.
+    tokens.retain(|t| {
+        !matches!(
+            t.as_str(),
+            "mixamorig" | "bip01" | "bip001" | "def" | "bone"
+        )
+    });
+    if let Some(first) = tokens.first_mut() {
+        if let Some(stripped) = first.strip_prefix("mixamorig") {
+            *first = stripped.to_string();
+        }
+    }
+
+    let mut side = Side::Center;
+    tokens.retain(|t| match t.as_str() {
+        "l" | "left" => {
+            side = Side::Left;
+            false
+        }
+        "r" | "right" => {
+            side = Side::Right;
+            false
+        }
+        _ => true,
+    });
+
+    let mut core = tokens.concat();
+    if side == Side::Center {
+        for (marker, marker_side) in [("left", Side::Left), ("right", Side::Right)] {
+            if let Some(stripped) = core.strip_prefix(marker) {
+                core = stripped.to_string();
+                side = marker_side;
+                break;
+            } else if let Some(stripped) = core.strip_suffix(marker) {
+                core = stripped.to_string();
+                side = marker_side;
+                break;
+            }
+        }
+    }
+
+    (side, core)
+}
+
+/// A binding of a humanoid bone to a scene node.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct HumanoidBoneBinding {
+    /// A humanoid bone.
+    pub bone: HumanoidBone,
+    /// Name of a scene node, that corresponds to the bone.
+    pub node_name: String,
+}
+
+uuid_provider!(HumanoidBoneBinding = "8f4a5b2e-3c7d-4e1f-9a6b-0d2c8e7f5a13");
+
+/// Humanoid mapping binds standard humanoid bones to scene nodes of a particular character rig by their names.
+/// Two rigs with mappings can exchange animations using retargeting, even if their bones have different names,
+/// orientations and lengths.
+///
+/// Mappings could be created by hand, using [`Self::auto_detect`], or in the editor (`Utils -> Humanoid Mapping`).
+/// They're stored in `.humanoid` files.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct HumanoidMapping {
+    /// A list of bone bindings.
+    pub bindings: Vec<HumanoidBoneBinding>,
+}
+
+uuid_provider!(HumanoidMapping = "1c0cd8b8-08e6-4f0b-a1c5-bb6a5e2f4b8e");
+
+impl ResourceData for HumanoidMapping {
+    fn type_uuid(&self) -> crate::core::uuid::Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut visitor = Visitor::new();
+        self.visit("HumanoidMapping", &mut visitor)?;
+        visitor.save_binary(path)?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+impl HumanoidMapping {
+    /// Load a humanoid mapping from the specific file path.
+    pub async fn from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, HumanoidMappingError> {
+        let bytes = io.load_file(path).await?;
+        let mut visitor = Visitor::load_from_memory(&bytes)?;
+        let mut mapping = Self::default();
+        mapping.visit("HumanoidMapping", &mut visitor)?;
+        Ok(mapping)
+    }
+
+    /// Returns a name of a scene node bound to the given bone.
+    pub fn node_name(&self, bone: HumanoidBone) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|b| b.bone == bone)
+            .map(|b| b.node_name.as_str())
+    }
+
+    /// Binds the bone to a scene node with the given name, replacing previous binding of the bone.
+    pub fn set_node_name(&mut self, bone: HumanoidBone, node_name: impl Into<String>) {
+        let node_name = node_name.into();
+        if let Some(binding) = self.bindings.iter_mut().find(|b| b.bone == bone) {
+            binding.node_name = node_name;
+        } else {
+            self.bindings.push(HumanoidBoneBinding { bone, node_name });
+        }
+    }
+
+    /// Returns a list of bones, that are not bound to any node.
+    pub fn unbound_bones(&self) -> Vec<HumanoidBone> {
+        HumanoidBone::ALL
+            .into_iter()
+            .filter(|bone| self.node_name(*bone).is_none())
+            .collect()
+    }
+
+    /// Finds scene nodes of the bound bones in the hierarchy starting from `root` (included).
+    pub fn find_nodes(
+        &self,
+        graph: &Graph,
+        root: Handle<Node>,
+    ) -> FxHashMap<HumanoidBone, Handle<Node>> {
+        self.bindings
+            .iter()
+            .filter_map(|binding| {
+                graph
+                    .find_by_name(root, &binding.node_name)
+                    .map(|(handle, _)| (binding.bone, handle))
+            })
+            .collect()
+    }
+
+    /// Tries to create a mapping automatically using names of the nodes in the hierarchy starting from `root`.
+    /// It recognizes naming conventions of the most common rigs (Mixamo, Unreal, Blender, 3ds Max Biped), but
+    /// the result should be checked anyway.
+    pub fn auto_detect(graph: &Graph, root: Handle<Node>) -> Self {
+        let candidates = graph
+            .traverse_iter(root)
+            .map(|(_, node)| {
+                let (side, core) = classify_name(node.name());
+                (node.name().to_string(), side, core)
+            })
+            .collect::<Vec<_>>();
+
+        let mut mapping = Self::default();
+        for bone in HumanoidBone::ALL {
+            let found = bone.common_names().iter().find_map(|common_name| {
+                candidates.iter().find(|(name, side, core)| {
+                    *side == bone.side()
+                        && core == common_name
+                        && !mapping.bindings.iter().any(|b| &b.node_name == name)
+                })
+            });
+            if let Some((name, _, _)) = found {
+                mapping.set_node_name(bone, name.clone());
+            }
+        }
+        mapping
+    }
+}
+
+/// Type alias for humanoid mapping resources.
+pub type HumanoidMappingResource = Resource<HumanoidMapping>;
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::pool::Handle,
+        graph::BaseSceneGraph,
+        resource::humanoid::{classify_name, HumanoidBone, HumanoidMapping, Side},
+        scene::{base::BaseBuilder, graph::Graph, node::Node, pivot::PivotBuilder},
+    };
+
+    #[test]
+    fn test_classify_name() {
+        let check = |name: &str, side: Side, core: &str| {
+            let (actual_side, actual_core) = classify_name(name);
+            assert!(actual_side == side, "{name}");
+            assert_eq!(actual_core, core, "{name}");
+        };
+        check("mixamorig:LeftUpLeg", Side::Left, "upleg");
+        check("mixamorig:Spine1", Side::Center, "spine1");
+        check("thigh_r", Side::Right, "thigh");
+        check("spine_02", Side::Center, "spine02");
+        check("Bip01 L Forearm", Side::Left, "forearm");
+        check("upper_arm.L", Side::Left, "upperarm");
+        check("Hips", Side::Center, "hips");
+    }
+
+    #[test]
+    fn test_auto_detect() {
+        let mut graph = Graph::new();
+        let names = [
+            "mixamorig:Hips",
+            "mixamorig:Spine",
+            "mixamorig:Spine1",
+            "mixamorig:Spine2",
+            "mixamorig:Neck",
+            "mixamorig:Head",
+            "mixamorig:LeftShoulder",
+            "mixamorig:LeftArm",
+            "mixamorig:LeftForeArm",
+            "mixamorig:LeftHand",
+            "mixamorig:RightShoulder",
+            "mixamorig:RightArm",
+            "mixamorig:RightForeArm",
+            "mixamorig:RightHand",
+            "mixamorig:LeftUpLeg",
+            "mixamorig:LeftLeg",
+            "mixamorig:LeftFoot",
+            "mixamorig:LeftToeBase",
+            "mixamorig:RightUpLeg",
+            "mixamorig:RightLeg",
+            "mixamorig:RightFoot",
+            "mixamorig:RightToeBase",
+        ];
+        let root = graph.get_root();
+        let mut parent: Handle<Node> = root;
+        for name in names {
+            let node = PivotBuilder::new(BaseBuilder::new().with_name(name)).build(&mut graph);
+            graph.link_nodes(node, parent);
+            parent = node;
+        }
+
+        let mapping = HumanoidMapping::auto_detect(&graph, root);
+        assert!(mapping.unbound_bones().is_empty());
+        for (bone, name) in HumanoidBone::ALL.into_iter().zip(names) {
+            assert_eq!(mapping.node_name(bone), Some(name));
+        }
+        assert_eq!(
+            mapping.find_nodes(&graph, root).len(),
+            HumanoidBone::ALL.len()
+        );
+    }
+}
@@ -22,9 +22,11 @@
 
 #![warn(missing_docs)]
 
+pub mod behavior;
 pub mod curve;
 pub mod fbx;
 #[cfg(feature = "gltf")]
 pub mod gltf;
+pub mod humanoid;
 pub mod model;
 pub mod texture;
@@ -55,11 +55,18 @@ use crate::{
         NameProvider, TypeUuidProvider,
     },
     engine::SerializationContext,
-    generic_animation::AnimationContainer,
+    generic_animation::{
+        compression::{compress_animation, CompressionReport, CompressionSettings},
+        AnimationContainer,
+    },
     graph::{BaseSceneGraph, NodeHandleMap, NodeMapping, PrefabData, SceneGraph, SceneGraphNode},
     resource::fbx::{self, error::FbxError},
     scene::{
-        animation::Animation, base::SceneNodeId, graph::Graph, node::Node, transform::Transform,
+        animation::{Animation, AnimationPlayer},
+        base::SceneNodeId,
+        graph::Graph,
+        node::Node,
+        transform::Transform,
         Scene, SceneLoader,
     },
 };
@@ -707,7 +714,11 @@ impl MaterialSearchOptions {
 ///
 /// ```text
 /// (
-///     material_search_options: RecursiveUp
+///     material_search_options: RecursiveUp,
+///     animation_compression: (
+///         enabled: true,
+///         quantize_rotations: true,
+///     ),
 /// )
 /// ```
 ///
@@ -717,6 +728,97 @@ pub struct ModelImportOptions {
     /// See [`MaterialSearchOptions`] docs for more info.
     #[serde(default)]
     pub material_search_options: MaterialSearchOptions,
+    /// See [`AnimationCompressionOptions`] docs for more info.
+    #[serde(default)]
+    pub animation_compression: AnimationCompressionOptions,
+}
+
+/// A set of options that defines how the animations of a model will be compressed on import. Imported
+/// animations (especially motion capture ones) usually have a key for every frame, most of which could be
+/// reproduced by interpolation. Compression removes such keys and, optionally, stores rotations as quantized
+/// quaternions. Compression statistics is written to the log when a model is loaded.
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+#[serde(default)]
+pub struct AnimationCompressionOptions {
+    /// Whether the compression is enabled or not. Disabled by default.
+    pub enabled: bool,
+    /// Whether to remove keys, that can be reproduced by interpolation between their neighbours with an
+    /// error less than the tolerance.
+    pub reduce_keys: bool,
+    /// Maximum allowed error for position and scale tracks.
+    #[reflect(min_value = 0.0, step = 0.0001)]
+    pub tolerance: f32,
+    /// Maximum allowed angular error (in radians) for rotation tracks.
+    #[reflect(min_value = 0.0, step = 0.0001)]
+    pub rotation_tolerance: f32,
+    /// Whether to store rotations as quantized quaternions or not. Quantized rotations take much less memory,
+    /// but cannot be edited in the animation editor.
+    pub quantize_rotations: bool,
+}
+
+uuid_provider!(AnimationCompressionOptions = "5d3c1b0e-8a7f-4e2d-9c6b-1f0a2e3d4c5b");
+
+impl PartialEq for AnimationCompressionOptions {
+    fn eq(&self, other: &Self) -> bool {
+        // Tolerances are compared bitwise, this allows the options to be `Eq`.
+        self.enabled == other.enabled
+            && self.reduce_keys == other.reduce_keys
+            && self.tolerance.to_bits() == other.tolerance.to_bits()
+            && self.rotation_tolerance.to_bits() == other.rotation_tolerance.to_bits()
+            && self.quantize_rotations == other.quantize_rotations
+    }
+}
+
+impl Eq for AnimationCompressionOptions {}
+
+impl Default for AnimationCompressionOptions {
+    fn default() -> Self {
+        let settings = CompressionSettings::default();
+        Self {
+            enabled: false,
+            reduce_keys: settings.reduce_keys,
+            tolerance: settings.tolerance,
+            rotation_tolerance: settings.rotation_tolerance,
+            quantize_rotations: settings.quantize_rotations,
+        }
+    }
+}
+
+impl AnimationCompressionOptions {
+    /// Returns compression settings defined by the options.
+    pub fn settings(&self) -> CompressionSettings {
+        CompressionSettings {
+            reduce_keys: self.reduce_keys,
+            tolerance: self.tolerance,
+            rotation_tolerance: self.rotation_tolerance,
+            quantize_rotations: self.quantize_rotations,
+        }
+    }
+
+    /// Compresses every animation of every animation player in the given scene, if the compression is enabled.
+    /// Returns `None` if the compression is disabled, otherwise returns total compression statistics.
+    pub fn apply(&self, scene: &mut Scene) -> Option<CompressionReport> {
+        if !self.enabled {
+            return None;
+        }
+
+        let settings = self.settings();
+        let mut report = CompressionReport::default();
+        for node in scene.graph.linear_iter_mut() {
+            if let Some(animation_player) = node.cast_mut::<AnimationPlayer>() {
+                for animation in animation_player
+                    .animations_mut()
+                    .get_value_mut_silent()
+                    .iter_mut()
+                {
+                    if let Some(animation_report) = compress_animation(animation, &settings) {
+                        report += animation_report;
+                    }
+                }
+            }
+        }
+        Some(report)
+    }
 }
 
 impl ImportOptions for ModelImportOptions {}
@@ -759,6 +861,20 @@ impl From<VisitError> for ModelLoadError {
     }
 }
 
+/// Compresses the animations of an imported model and writes compression statistics to the log.
+pub(crate) fn compress_imported_animations(
+    scene: &mut Scene,
+    options: &AnimationCompressionOptions,
+    path: &Path,
+) {
+    if let Some(report) = options.apply(scene) {
+        Log::info(format!(
+            "Animations of {} model were compressed: {report}",
+            path.display()
+        ));
+    }
+}
+
 impl Model {
     /// Creates a new Model instance using the given node mapping and the given scene. It could be
     /// used to create your own Model resources.
@@ -795,6 +911,11 @@ impl Model {
                     &model_import_options,
                 )
                 .await?;
+                compress_imported_animations(
+                    &mut scene,
+                    &model_import_options.animation_compression,
+                    path.as_ref(),
+                );
                 // Set NodeMapping::UseNames as mapping here because FBX does not have
                 // any persistent unique ids, and we have to use names.
                 (scene, NodeMapping::UseNames)
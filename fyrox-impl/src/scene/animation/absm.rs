@@ -24,8 +24,10 @@
 use crate::scene::node::constructor::NodeConstructor;
 use crate::{
     core::{
+        log::category,
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
+        profile_scope,
         reflect::prelude::*,
         type_traits::prelude::*,
         uuid::{uuid, Uuid},
@@ -33,7 +35,7 @@ use crate::{
         visitor::prelude::*,
     },
     scene::{
-        animation::prelude::*,
+        animation::{ik::IkSolver, prelude::*},
         base::{Base, BaseBuilder},
         graph::Graph,
         node::{Node, NodeTrait, UpdateContext},
@@ -66,6 +68,9 @@ pub type BlendSpace = crate::generic_animation::machine::node::blendspace::Blend
 /// Scene specific animation blending state machine blend space point.
 pub type BlendSpacePoint =
     crate::generic_animation::machine::node::blendspace::BlendSpacePoint<Handle<Node>>;
+/// Scene specific animation blending state machine pose difference node.
+pub type PoseDifference =
+    crate::generic_animation::machine::node::difference::PoseDifference<Handle<Node>>;
 /// Scene specific animation blending state machine layer mask.
 pub type LayerMask = crate::generic_animation::machine::mask::LayerMask<Handle<Node>>;
 /// Scene specific animation blending state machine layer mask.
@@ -108,12 +113,14 @@ pub mod prelude {
         AndNode, AnimationBlendingStateMachine, AnimationBlendingStateMachineBuilder,
         AnimationEventsSource, BasePoseNode, BlendAnimations, BlendAnimationsByIndex, BlendPose,
         BlendSpace, BlendSpacePoint, Event, IndexedBlendInput, LayerAnimationEventsCollection,
-        LayerMask, LogicNode, Machine, MachineLayer, NotNode, OrNode, PlayAnimation, PoseNode,
-        RootMotionSettings, State, StateAction, StateActionWrapper, Transition, XorNode,
+        LayerMask, LogicNode, Machine, MachineLayer, NotNode, OrNode, PlayAnimation,
+        PoseDifference, PoseNode, RootMotionSettings, State, StateAction, StateActionWrapper,
+        Transition, XorNode,
     };
     pub use crate::generic_animation::machine::{
         node::AnimationEventCollectionStrategy,
         parameter::{Parameter, ParameterContainer, ParameterDefinition, PoseWeight},
+        BlendSyncMode, LayerBlendMode,
     };
 }
 
@@ -224,6 +231,12 @@ pub struct AnimationBlendingStateMachine {
     machine: InheritableVariable<Machine>,
     #[component(include)]
     animation_player: InheritableVariable<Handle<Node>>,
+    #[visit(optional)]
+    #[reflect(
+        description = "A list of inverse kinematics solvers, that will be applied to the pose produced by \
+        the state machine. Solvers are executed in order."
+    )]
+    ik_solvers: InheritableVariable<Vec<IkSolver>>,
 }
 
 impl AnimationBlendingStateMachine {
@@ -253,6 +266,22 @@ impl AnimationBlendingStateMachine {
     pub fn animation_player(&self) -> Handle<Node> {
         *self.animation_player
     }
+
+    /// Sets new set of inverse kinematics solvers, that will be applied to the pose produced by the state
+    /// machine. See [`IkSolver`] docs for more info.
+    pub fn set_ik_solvers(&mut self, ik_solvers: Vec<IkSolver>) {
+        self.ik_solvers.set_value_and_mark_modified(ik_solvers);
+    }
+
+    /// Returns a reference to the inverse kinematics solvers used by the node.
+    pub fn ik_solvers(&self) -> &[IkSolver] {
+        &self.ik_solvers
+    }
+
+    /// Returns a mutable reference to the inverse kinematics solvers used by the node.
+    pub fn ik_solvers_mut(&mut self) -> &mut Vec<IkSolver> {
+        self.ik_solvers.get_value_mut_and_mark_modified()
+    }
 }
 
 impl TypeUuidProvider for AnimationBlendingStateMachine {
@@ -311,6 +340,11 @@ impl NodeTrait for AnimationBlendingStateMachine {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
+        profile_scope!(
+            category: category::ANIMATION,
+            "AnimationBlendingStateMachine::update"
+        );
+
         if let Some(animation_player) = context
             .nodes
             .try_borrow_mut(*self.animation_player)
@@ -326,6 +360,10 @@ impl NodeTrait for AnimationBlendingStateMachine {
             );
 
             pose.apply_internal(context.nodes);
+
+            for solver in self.ik_solvers.iter() {
+                solver.solve(context.nodes, self.machine.parameters());
+            }
         }
     }
 
@@ -352,6 +390,7 @@ pub struct AnimationBlendingStateMachineBuilder {
     base_builder: BaseBuilder,
     machine: Machine,
     animation_player: Handle<Node>,
+    ik_solvers: Vec<IkSolver>,
 }
 
 impl AnimationBlendingStateMachineBuilder {
@@ -361,6 +400,7 @@ impl AnimationBlendingStateMachineBuilder {
             base_builder,
             machine: Default::default(),
             animation_player: Default::default(),
+            ik_solvers: Default::default(),
         }
     }
 
@@ -376,12 +416,19 @@ impl AnimationBlendingStateMachineBuilder {
         self
     }
 
+    /// Sets the inverse kinematics solvers, that will be applied to the pose produced by the state machine.
+    pub fn with_ik_solvers(mut self, ik_solvers: Vec<IkSolver>) -> Self {
+        self.ik_solvers = ik_solvers;
+        self
+    }
+
     /// Creates new node.
     pub fn build_node(self) -> Node {
         Node::new(AnimationBlendingStateMachine {
             base: self.base_builder.build_base(),
             machine: self.machine.into(),
             animation_player: self.animation_player.into(),
+            ik_solvers: self.ik_solvers.into(),
         })
     }
 
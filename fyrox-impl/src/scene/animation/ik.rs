@@ -0,0 +1,687 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Inverse kinematics (IK) solvers, that adjust a pose produced by an animation blending state machine.
+//! See [`IkSolver`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix3, Matrix4, UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid_provider,
+        visitor::prelude::*,
+    },
+    generic_animation::machine::parameter::{ParameterContainer, PoseWeight},
+    scene::{graph::NodePool, node::Node},
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// A target of an IK solver.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, Default, AsRefStr, EnumString, VariantNames)]
+pub enum IkTarget {
+    /// No target, a solver with such target does nothing.
+    #[default]
+    None,
+    /// A scene node, its global position is used as a target.
+    Node(Handle<Node>),
+    /// A fixed position in world coordinates.
+    Position(Vector3<f32>),
+}
+
+uuid_provider!(IkTarget = "4e6f7bc0-8d14-4fcb-9a3c-1bd5e36e7e0f");
+
+impl IkTarget {
+    fn position(&self, nodes: &NodePool) -> Option<Vector3<f32>> {
+        match self {
+            IkTarget::None => None,
+            IkTarget::Node(handle) => {
+                nodes.try_borrow(*handle)?;
+                Some(position_of(&global_transform(nodes, *handle)))
+            }
+            IkTarget::Position(position) => Some(*position),
+        }
+    }
+}
+
+/// Analytic solver for chains of exactly two bones (three joints), such as arms and legs. It is the best
+/// choice for foot placement and hand IK, because it is fast and stable.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, Default)]
+pub struct TwoBoneIk {
+    /// The first joint of the chain (for example, a thigh or an upper arm).
+    pub upper: Handle<Node>,
+    /// The middle joint of the chain (for example, a knee or an elbow).
+    pub middle: Handle<Node>,
+    /// The last joint of the chain (for example, a foot or a hand). The solver moves this joint to the target.
+    pub end: Handle<Node>,
+    /// A target for the end joint.
+    pub target: IkTarget,
+    /// An optional pole target, that defines the direction in which the middle joint will bend. If not set, the
+    /// current bending direction is preserved.
+    pub pole: IkTarget,
+    /// Weight of the solver, where 0.0 means no effect and 1.0 means full effect.
+    pub weight: PoseWeight,
+}
+
+/// Iterative method used by [`ChainIk`].
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Visit, Reflect, Default, AsRefStr, EnumString, VariantNames,
+)]
+pub enum ChainIkMethod {
+    /// Forward And Backward Reaching Inverse Kinematics. Produces natural-looking results for long chains.
+    #[default]
+    Fabrik,
+    /// Cyclic Coordinate Descent. Tends to curl the end of a chain, but is cheaper per iteration.
+    Ccd,
+}
+
+uuid_provider!(ChainIkMethod = "0a6a4f5b-8f37-4cbc-a3f0-0c93b7b3a2a9");
+
+/// Iterative solver for chains of arbitrary length, such as tails, tentacles or spines.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct ChainIk {
+    /// The first joint of the chain.
+    pub root: Handle<Node>,
+    /// The last joint of the chain, it must be a descendant of the root joint. The solver moves this joint to
+    /// the target.
+    pub end: Handle<Node>,
+    /// A target for the end joint.
+    pub target: IkTarget,
+    /// Iterative method used to solve the chain.
+    pub method: ChainIkMethod,
+    /// Maximum amount of iterations.
+    #[reflect(min_value = 1.0)]
+    pub iterations: u32,
+    /// Distance between the end joint and the target, at which the solver stops iterating.
+    #[reflect(min_value = 0.0)]
+    pub tolerance: f32,
+    /// Weight of the solver, where 0.0 means no effect and 1.0 means full effect.
+    pub weight: PoseWeight,
+}
+
+impl Default for ChainIk {
+    fn default() -> Self {
+        Self {
+            root: Default::default(),
+            end: Default::default(),
+            target: Default::default(),
+            method: Default::default(),
+            iterations: 10,
+            tolerance: 0.001,
+            weight: Default::default(),
+        }
+    }
+}
+
+/// Rotates a single joint so its forward axis points to the target, for example a head or eyes.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct LookAtIk {
+    /// A joint to rotate.
+    pub joint: Handle<Node>,
+    /// A target to look at.
+    pub target: IkTarget,
+    /// An axis in the local space of the joint, that should point to the target.
+    pub forward_axis: Vector3<f32>,
+    /// Maximum angle (in radians) at which the joint can be rotated away from its animated orientation.
+    #[reflect(min_value = 0.0)]
+    pub max_angle: f32,
+    /// Weight of the solver, where 0.0 means no effect and 1.0 means full effect.
+    pub weight: PoseWeight,
+}
+
+impl Default for LookAtIk {
+    fn default() -> Self {
+        Self {
+            joint: Default::default(),
+            target: Default::default(),
+            forward_axis: Vector3::z(),
+            max_angle: std::f32::consts::FRAC_PI_2,
+            weight: Default::default(),
+        }
+    }
+}
+
+/// IK solver is a pose post-processor, that adjusts local rotations of scene nodes after an animation
+/// blending state machine applied its pose. Solvers are stored in an
+/// [`super::absm::AnimationBlendingStateMachine`] node and executed in order, so, for example, a look-at
+/// solver for a head could be executed after a chain solver for a spine.
+///
+/// Every solver has a weight, that can be either a constant or a reference to a `Weight` parameter of the
+/// state machine. This allows to smoothly enable or disable solvers from game code (for example, disable foot
+/// placement when a character is jumping). If the parameter does not exist, the solver is skipped.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum IkSolver {
+    /// See [`TwoBoneIk`] docs.
+    TwoBone(TwoBoneIk),
+    /// See [`ChainIk`] docs.
+    Chain(ChainIk),
+    /// See [`LookAtIk`] docs.
+    LookAt(LookAtIk),
+}
+
+uuid_provider!(IkSolver = "c5d2b0ed-2a2a-4a5f-a1a6-2df0d9b0f3d1");
+
+impl Default for IkSolver {
+    fn default() -> Self {
+        Self::TwoBone(Default::default())
+    }
+}
+
+impl IkSolver {
+    /// Adjusts local rotations of the nodes affected by the solver.
+    pub fn solve(&self, nodes: &mut NodePool, parameters: &ParameterContainer) {
+        match self {
+            IkSolver::TwoBone(solver) => solver.solve(nodes, parameters),
+            IkSolver::Chain(solver) => solver.solve(nodes, parameters),
+            IkSolver::LookAt(solver) => solver.solve(nodes, parameters),
+        }
+    }
+}
+
+// Calculates global transform of a node from local transforms of its ancestors. Global transforms of
+// the nodes cannot be used here, because they're updated only on the next frame.
+fn global_transform(nodes: &NodePool, handle: Handle<Node>) -> Matrix4<f32> {
+    let mut transform = Matrix4::identity();
+    let mut current = handle;
+    while let Some(node) = nodes.try_borrow(current) {
+        transform = node.local_transform().matrix() * transform;
+        current = node.parent();
+    }
+    transform
+}
+
+fn position_of(transform: &Matrix4<f32>) -> Vector3<f32> {
+    Vector3::new(transform[12], transform[13], transform[14])
+}
+
+fn rotation_of(transform: &Matrix4<f32>) -> UnitQuaternion<f32> {
+    let basis = Matrix3::from_columns(&[
+        transform
+            .column(0)
+            .xyz()
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::x),
+        transform
+            .column(1)
+            .xyz()
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y),
+        transform
+            .column(2)
+            .xyz()
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::z),
+    ]);
+    UnitQuaternion::from_matrix_eps(&basis, f32::EPSILON, 16, UnitQuaternion::identity())
+}
+
+fn global_position(nodes: &NodePool, handle: Handle<Node>) -> Vector3<f32> {
+    position_of(&global_transform(nodes, handle))
+}
+
+// Applies the given world-space rotation to a node, the node rotates around its own origin.
+fn rotate_in_world_space(
+    nodes: &mut NodePool,
+    handle: Handle<Node>,
+    rotation: UnitQuaternion<f32>,
+) {
+    let global_rotation = rotation_of(&global_transform(nodes, handle));
+    if let Some(node) = nodes.try_borrow_mut(handle) {
+        let local_rotation = **node.local_transform().rotation();
+        node.local_transform_mut()
+            .set_rotation(local_rotation * global_rotation.inverse() * rotation * global_rotation);
+    }
+}
+
+fn local_rotations(nodes: &NodePool, chain: &[Handle<Node>]) -> Vec<UnitQuaternion<f32>> {
+    chain
+        .iter()
+        .map(|handle| **nodes[*handle].local_transform().rotation())
+        .collect()
+}
+
+// Blends solved rotations with the animated ones using the weight of a solver.
+fn blend_with_original(
+    nodes: &mut NodePool,
+    chain: &[Handle<Node>],
+    original: &[UnitQuaternion<f32>],
+    weight: f32,
+) {
+    if weight >= 1.0 {
+        return;
+    }
+    for (handle, original) in chain.iter().zip(original) {
+        let transform = nodes[*handle].local_transform_mut();
+        let solved = **transform.rotation();
+        transform.set_rotation(original.slerp(&solved, weight));
+    }
+}
+
+fn rotation_between(from: Vector3<f32>, to: Vector3<f32>) -> Option<UnitQuaternion<f32>> {
+    UnitQuaternion::rotation_between(&from, &to).or_else(|| {
+        // Vectors are opposite, pick any perpendicular axis.
+        let axis = from
+            .cross(&Vector3::x())
+            .try_normalize(f32::EPSILON)
+            .or_else(|| from.cross(&Vector3::y()).try_normalize(f32::EPSILON))?;
+        Some(UnitQuaternion::from_axis_angle(
+            &crate::core::algebra::Unit::new_unchecked(axis),
+            std::f32::consts::PI,
+        ))
+    })
+}
+
+fn weight_of(weight: &PoseWeight, parameters: &ParameterContainer) -> Option<f32> {
+    weight
+        .value(parameters)
+        .map(|w| w.clamp(0.0, 1.0))
+        .filter(|w| *w > 0.0)
+}
+
+impl TwoBoneIk {
+    fn solve(&self, nodes: &mut NodePool, parameters: &ParameterContainer) {
+        let Some(weight) = weight_of(&self.weight, parameters) else {
+            return;
+        };
+        let Some(target) = self.target.position(nodes) else {
+            return;
+        };
+        let chain = [self.upper, self.middle, self.end];
+        if chain.iter().any(|h| nodes.try_borrow(*h).is_none()) {
+            return;
+        }
+
+        let original = local_rotations(nodes, &chain[..2]);
+
+        let a = global_position(nodes, self.upper);
+        let b = global_position(nodes, self.middle);
+        let c = global_position(nodes, self.end);
+
+        let eps = 0.0001;
+        let lab = (b - a).norm();
+        let lcb = (c - b).norm();
+        if lab < eps || lcb < eps {
+            return;
+        }
+        let lat = (target - a).norm().clamp(eps, lab + lcb - eps);
+
+        let (Some(ac), Some(at)) = (
+            (c - a).try_normalize(f32::EPSILON),
+            (target - a).try_normalize(f32::EPSILON),
+        ) else {
+            return;
+        };
+        let ab = (b - a) / lab;
+        let ba = -ab;
+        let bc = (c - b) / lcb;
+
+        let safe_acos = |v: f32| v.clamp(-1.0, 1.0).acos();
+
+        // Current angles of the triangle.
+        let ac_ab_0 = safe_acos(ac.dot(&ab));
+        let ba_bc_0 = safe_acos(ba.dot(&bc));
+        let ac_at_0 = safe_acos(ac.dot(&at));
+
+        // Desired angles of the triangle, from the law of cosines.
+        let ac_ab_1 = safe_acos((lcb * lcb - lab * lab - lat * lat) / (-2.0 * lab * lat));
+        let ba_bc_1 = safe_acos((lat * lat - lab * lab - lcb * lcb) / (-2.0 * lab * lcb));
+
+        let bend_direction = self.pole.position(nodes).map(|pole| pole - a).unwrap_or(ab);
+        let Some(axis0) = ac
+            .cross(&bend_direction)
+            .try_normalize(f32::EPSILON)
+            .or_else(|| ac.cross(&Vector3::x()).try_normalize(f32::EPSILON))
+        else {
+            return;
+        };
+        let axis1 = ac.cross(&at).try_normalize(f32::EPSILON);
+
+        let axis0 = crate::core::algebra::Unit::new_unchecked(axis0);
+        let r0 = UnitQuaternion::from_axis_angle(&axis0, ac_ab_1 - ac_ab_0);
+        let r1 = UnitQuaternion::from_axis_angle(&axis0, ba_bc_1 - ba_bc_0);
+        let r2 = axis1
+            .map(|axis| {
+                UnitQuaternion::from_axis_angle(
+                    &crate::core::algebra::Unit::new_unchecked(axis),
+                    ac_at_0,
+                )
+            })
+            .unwrap_or_default();
+
+        // Middle joint first, because its rotation is calculated in the original frame of the chain.
+        rotate_in_world_space(nodes, self.middle, r1);
+        rotate_in_world_space(nodes, self.upper, r2 * r0);
+
+        blend_with_original(nodes, &chain[..2], &original, weight);
+    }
+}
+
+impl ChainIk {
+    fn collect_chain(&self, nodes: &NodePool) -> Option<Vec<Handle<Node>>> {
+        let mut chain = vec![self.end];
+        let mut current = self.end;
+        while current != self.root {
+            current = nodes.try_borrow(current)?.parent();
+            chain.push(current);
+        }
+        chain.reverse();
+        nodes.try_borrow(self.root)?;
+        (chain.len() >= 2).then_some(chain)
+    }
+
+    fn solve(&self, nodes: &mut NodePool, parameters: &ParameterContainer) {
+        let Some(weight) = weight_of(&self.weight, parameters) else {
+            return;
+        };
+        let Some(target) = self.target.position(nodes) else {
+            return;
+        };
+        let Some(chain) = self.collect_chain(nodes) else {
+            return;
+        };
+
+        let original = local_rotations(nodes, &chain);
+
+        match self.method {
+            ChainIkMethod::Fabrik => self.solve_fabrik(nodes, &chain, target),
+            ChainIkMethod::Ccd => self.solve_ccd(nodes, &chain, target),
+        }
+
+        blend_with_original(nodes, &chain, &original, weight);
+    }
+
+    fn solve_fabrik(&self, nodes: &mut NodePool, chain: &[Handle<Node>], target: Vector3<f32>) {
+        let mut positions = chain
+            .iter()
+            .map(|handle| global_position(nodes, *handle))
+            .collect::<Vec<_>>();
+        let lengths = positions
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).norm())
+            .collect::<Vec<_>>();
+        let total_length = lengths.iter().sum::<f32>();
+        let root = positions[0];
+
+        if (target - root).norm() >= total_length {
+            // The target is unreachable, stretch the chain towards it.
+            let direction = (target - root)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::y);
+            for i in 1..positions.len() {
+                positions[i] = positions[i - 1] + direction * lengths[i - 1];
+            }
+        } else {
+            let last = positions.len() - 1;
+            for _ in 0..self.iterations.max(1) {
+                if (positions[last] - target).norm() <= self.tolerance {
+                    break;
+                }
+
+                // Backward pass - from the end to the root.
+                positions[last] = target;
+                for i in (0..last).rev() {
+                    let direction = (positions[i] - positions[i + 1])
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::y);
+                    positions[i] = positions[i + 1] + direction * lengths[i];
+                }
+
+                // Forward pass - from the root to the end.
+                positions[0] = root;
+                for i in 0..last {
+                    let direction = (positions[i + 1] - positions[i])
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::y);
+                    positions[i + 1] = positions[i] + direction * lengths[i];
+                }
+            }
+        }
+
+        // Convert new positions of the joints to rotations, starting from the root, because rotation of a
+        // parent joint moves its descendants.
+        for i in 0..chain.len() - 1 {
+            let current_direction =
+                global_position(nodes, chain[i + 1]) - global_position(nodes, chain[i]);
+            let desired_direction = positions[i + 1] - positions[i];
+            if let Some(rotation) = rotation_between(current_direction, desired_direction) {
+                rotate_in_world_space(nodes, chain[i], rotation);
+            }
+        }
+    }
+
+    fn solve_ccd(&self, nodes: &mut NodePool, chain: &[Handle<Node>], target: Vector3<f32>) {
+        let end = chain[chain.len() - 1];
+        for _ in 0..self.iterations.max(1) {
+            if (global_position(nodes, end) - target).norm() <= self.tolerance {
+                break;
+            }
+
+            for &joint in chain[..chain.len() - 1].iter().rev() {
+                let joint_position = global_position(nodes, joint);
+                let end_position = global_position(nodes, end);
+                if let Some(rotation) =
+                    rotation_between(end_position - joint_position, target - joint_position)
+                {
+                    rotate_in_world_space(nodes, joint, rotation);
+                }
+            }
+        }
+    }
+}
+
+impl LookAtIk {
+    fn solve(&self, nodes: &mut NodePool, parameters: &ParameterContainer) {
+        let Some(weight) = weight_of(&self.weight, parameters) else {
+            return;
+        };
+        let Some(target) = self.target.position(nodes) else {
+            return;
+        };
+        if nodes.try_borrow(self.joint).is_none() {
+            return;
+        }
+
+        let transform = global_transform(nodes, self.joint);
+        let forward = rotation_of(&transform) * self.forward_axis;
+        let Some(rotation) = rotation_between(forward, target - position_of(&transform)) else {
+            return;
+        };
+
+        let angle = rotation.angle();
+        let mut fraction = weight;
+        if angle > self.max_angle {
+            fraction *= self.max_angle / angle;
+        }
+        let rotation = UnitQuaternion::identity().slerp(&rotation, fraction);
+
+        rotate_in_world_space(nodes, self.joint, rotation);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+        },
+        graph::BaseSceneGraph,
+        scene::{
+            animation::{
+                absm::prelude::*,
+                ik::{
+                    rotation_of, ChainIk, ChainIkMethod, IkSolver, IkTarget, LookAtIk, TwoBoneIk,
+                },
+                AnimationPlayerBuilder,
+            },
+            base::BaseBuilder,
+            graph::Graph,
+            node::Node,
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    // Creates a vertical chain of joints, each joint is a child of the previous one.
+    fn make_chain(graph: &mut Graph, count: usize) -> Vec<Handle<Node>> {
+        let mut chain = Vec::new();
+        let mut parent = Handle::NONE;
+        for i in 0..count {
+            let offset = if i == 0 { 0.0 } else { -1.0 };
+            let joint = PivotBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, offset, 0.0))
+                        .build(),
+                ),
+            )
+            .build(graph);
+            if parent.is_some() {
+                graph.link_nodes(joint, parent);
+            }
+            chain.push(joint);
+            parent = joint;
+        }
+        chain
+    }
+
+    // Runs the solvers through a state machine node, the same way as they're used in games.
+    fn solve(graph: &mut Graph, solvers: Vec<IkSolver>) {
+        let animation_player = AnimationPlayerBuilder::new(BaseBuilder::new()).build(graph);
+        let mut machine = Machine::new();
+        machine.set_parameter("IkWeight", Parameter::Weight(1.0));
+        let absm = AnimationBlendingStateMachineBuilder::new(BaseBuilder::new())
+            .with_machine(machine)
+            .with_animation_player(animation_player)
+            .with_ik_solvers(solvers)
+            .build(graph);
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        graph.update_hierarchical_data();
+        graph.remove_node(absm);
+        graph.remove_node(animation_player);
+    }
+
+    fn forward_of(graph: &Graph, node: Handle<Node>) -> Vector3<f32> {
+        rotation_of(&graph[node].global_transform()) * Vector3::z()
+    }
+
+    #[test]
+    fn test_two_bone_ik() {
+        let mut graph = Graph::new();
+        let chain = make_chain(&mut graph, 3);
+        let target = Vector3::new(0.6, -1.2, 0.3);
+
+        let solver = IkSolver::TwoBone(TwoBoneIk {
+            upper: chain[0],
+            middle: chain[1],
+            end: chain[2],
+            target: IkTarget::Position(target),
+            pole: IkTarget::Position(Vector3::new(0.0, -1.0, 1.0)),
+            weight: PoseWeight::Parameter("IkWeight".to_string()),
+        });
+        solve(&mut graph, vec![solver]);
+
+        let end = graph[chain[2]].global_position();
+        assert!((end - target).norm() < 0.001, "{end:?}");
+        // Bones must keep their lengths.
+        let middle = graph[chain[1]].global_position();
+        assert!(((middle - graph[chain[0]].global_position()).norm() - 1.0).abs() < 0.001);
+        assert!(((end - middle).norm() - 1.0).abs() < 0.001);
+        // The knee must bend towards the pole.
+        assert!(middle.z > 0.0);
+    }
+
+    #[test]
+    fn test_missing_weight_parameter_disables_solver() {
+        let mut graph = Graph::new();
+        let chain = make_chain(&mut graph, 3);
+
+        let solver = IkSolver::TwoBone(TwoBoneIk {
+            upper: chain[0],
+            middle: chain[1],
+            end: chain[2],
+            target: IkTarget::Position(Vector3::new(0.6, -1.2, 0.3)),
+            pole: IkTarget::None,
+            weight: PoseWeight::Parameter("Unknown".to_string()),
+        });
+        solve(&mut graph, vec![solver]);
+
+        assert_eq!(
+            graph[chain[2]].global_position(),
+            Vector3::new(0.0, -2.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_chain_ik() {
+        for method in [ChainIkMethod::Fabrik, ChainIkMethod::Ccd] {
+            let mut graph = Graph::new();
+            let chain = make_chain(&mut graph, 5);
+            let target_node = PivotBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(1.5, -2.5, 0.5))
+                        .build(),
+                ),
+            )
+            .build(&mut graph);
+
+            let solver = IkSolver::Chain(ChainIk {
+                root: chain[0],
+                end: chain[4],
+                target: IkTarget::Node(target_node),
+                method,
+                iterations: 50,
+                tolerance: 0.001,
+                weight: PoseWeight::Constant(1.0),
+            });
+            solve(&mut graph, vec![solver]);
+
+            let end = graph[chain[4]].global_position();
+            assert!(
+                (end - Vector3::new(1.5, -2.5, 0.5)).norm() < 0.01,
+                "{method:?}: {end:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_look_at_ik() {
+        let mut graph = Graph::new();
+        let chain = make_chain(&mut graph, 1);
+
+        let mut solver = LookAtIk {
+            joint: chain[0],
+            target: IkTarget::Position(Vector3::new(1.0, 0.0, 0.0)),
+            weight: PoseWeight::Constant(1.0),
+            ..Default::default()
+        };
+        solve(&mut graph, vec![IkSolver::LookAt(solver.clone())]);
+        let forward = forward_of(&graph, chain[0]);
+        assert!((forward - Vector3::x()).norm() < 0.001);
+
+        // Limited rotation.
+        solver.target = IkTarget::Position(Vector3::new(0.0, 0.0, -1.0));
+        solver.max_angle = 0.5;
+        solve(&mut graph, vec![IkSolver::LookAt(solver)]);
+        let new_forward = forward_of(&graph, chain[0]);
+        assert!((forward.angle(&new_forward) - 0.5).abs() < 0.001);
+    }
+}
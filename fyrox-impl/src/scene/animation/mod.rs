@@ -24,9 +24,10 @@
 use crate::scene::node::constructor::NodeConstructor;
 use crate::{
     core::{
-        log::{Log, MessageKind},
+        log::{category, Log, MessageKind},
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
+        profile_scope,
         reflect::prelude::*,
         type_traits::prelude::*,
         uuid::{uuid, Uuid},
@@ -45,6 +46,8 @@ use fyrox_graph::BaseSceneGraph;
 use std::ops::{Deref, DerefMut};
 
 pub mod absm;
+pub mod ik;
+pub mod retarget;
 pub mod spritesheet;
 
 /// Scene specific animation.
@@ -67,7 +70,7 @@ pub mod prelude {
     };
     pub use crate::generic_animation::{
         container::{TrackDataContainer, TrackValueKind},
-        signal::AnimationSignal,
+        signal::{AnimationSignal, SignalPayload},
         value::{BoundValueCollection, TrackValue, ValueBinding, ValueType},
         AnimationEvent,
     };
@@ -345,6 +348,8 @@ impl NodeTrait for AnimationPlayer {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
+        profile_scope!(category: category::ANIMATION, "AnimationPlayer::update");
+
         if self.auto_apply {
             self.animations
                 .get_value_mut_silent()
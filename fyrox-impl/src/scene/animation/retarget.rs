@@ -0,0 +1,759 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Animation retargeting between humanoid skeletons with different proportions and bone orientations.
+//!
+//! The simplest way of sharing animations between models is [`crate::resource::model::AnimationSource::retarget_animations`],
+//! it binds animation tracks to nodes with the same names and copies the animation as is. It works only if both
+//! skeletons are identical: same bone names, same rest orientations of the bones and same lengths. This module
+//! removes these limitations for humanoid characters. Each rig describes its bones using a
+//! [`HumanoidMapping`], and the retargeter ([`HumanoidRetargeter`]) transfers the motion from bone to bone
+//! using bind (rest) poses of both skeletons:
+//!
+//! - Rotations are transferred as world-space deltas relative to the bind pose, so the bones may have completely
+//! different local orientations.
+//! - Translation of the hips is scaled by a ratio of leg lengths of the skeletons, so a character with short legs
+//! won't "slide" when playing a walk cycle of a tall character. Translations of other bones are dropped,
+//! because the target skeleton keeps its own proportions.
+//!
+//! Both skeletons must have similar bind poses (for example, both should be in T-pose or in A-pose), use
+//! [`HumanoidRetargeter::compare`] to check that.
+//!
+//! ```rust
+//! # use fyrox_impl::{
+//! #     core::pool::Handle,
+//! #     resource::{humanoid::HumanoidMapping, model::Model},
+//! #     scene::{animation::retarget::retarget_humanoid_animations, graph::Graph, node::Node},
+//! # };
+//! fn retarget(
+//!     source: &Model,
+//!     target_root: Handle<Node>,
+//!     target_graph: &mut Graph,
+//! ) {
+//!     let source_root = source.get_scene().graph.get_root();
+//!     let source_mapping = HumanoidMapping::auto_detect(&source.get_scene().graph, source_root);
+//!     let target_mapping = HumanoidMapping::auto_detect(target_graph, target_root);
+//!     let animations = retarget_humanoid_animations(
+//!         source,
+//!         &source_mapping,
+//!         target_graph,
+//!         target_root,
+//!         &target_mapping,
+//!     );
+//!     // Add the animations to an animation player of the target model.
+//! }
+//! ```
+
+use crate::{
+    core::{
+        algebra::{Matrix3, Matrix4, UnitQuaternion, Vector3},
+        log::Log,
+        math::curve::{Curve, CurveKey, CurveKeyKind},
+        pool::Handle,
+        variable::InheritableVariable,
+    },
+    generic_animation::{
+        container::{TrackDataContainer, TrackValueKind},
+        track::TrackBinding,
+        value::{TrackValue, ValueBinding},
+    },
+    graph::{BaseSceneGraph, SceneGraphNode},
+    resource::{
+        humanoid::{HumanoidBone, HumanoidMapping},
+        model::Model,
+    },
+    scene::{
+        animation::{Animation, AnimationContainer, Track},
+        graph::Graph,
+        node::Node,
+    },
+};
+use fxhash::FxHashMap;
+
+/// Default amount of samples per second, that is used to bake retargeted tracks.
+pub const DEFAULT_SAMPLE_RATE: f32 = 30.0;
+
+/// Bind (rest) pose of a single humanoid bone.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoneBindPose {
+    /// A handle of the scene node of the bone.
+    pub handle: Handle<Node>,
+    /// Local position of the bone in the bind pose.
+    pub local_position: Vector3<f32>,
+    /// Local rotation of the bone in the bind pose.
+    pub local_rotation: UnitQuaternion<f32>,
+    /// World-space rotation of the bone in the bind pose.
+    pub global_rotation: UnitQuaternion<f32>,
+    /// World-space position of the bone in the bind pose.
+    pub global_position: Vector3<f32>,
+    /// World-space rotation of the frame in which the local rotation of the bone is applied. It is
+    /// a rotation of the parent bone combined with the pre-rotation of the bone.
+    pub parent_frame: UnitQuaternion<f32>,
+    /// A rotation that is applied after the local rotation of the bone (post-rotation).
+    pub post_rotation: UnitQuaternion<f32>,
+    /// World-space basis (rotation and scale) of the parent bone.
+    pub parent_basis: Matrix3<f32>,
+}
+
+/// Bind (rest) pose of a humanoid skeleton.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SkeletonBindPose {
+    /// Bind poses of the mapped bones.
+    pub bones: FxHashMap<HumanoidBone, BoneBindPose>,
+    /// Length of the legs of the skeleton in world units. It is used to scale the motion of the hips.
+    pub leg_length: f32,
+}
+
+fn global_transform(graph: &Graph, handle: Handle<Node>) -> Matrix4<f32> {
+    let mut transform = Matrix4::identity();
+    let mut current = handle;
+    while let Some(node) = graph.try_get(current) {
+        transform = node.local_transform().matrix() * transform;
+        current = node.parent();
+    }
+    transform
+}
+
+fn basis_of(transform: &Matrix4<f32>) -> Matrix3<f32> {
+    transform.fixed_view::<3, 3>(0, 0).into_owned()
+}
+
+fn rotation_of(basis: &Matrix3<f32>) -> UnitQuaternion<f32> {
+    let mut rotation = *basis;
+    for mut column in rotation.column_iter_mut() {
+        let norm = column.norm();
+        if norm > f32::EPSILON {
+            column /= norm;
+        }
+    }
+    UnitQuaternion::from_matrix(&rotation)
+}
+
+impl SkeletonBindPose {
+    /// Captures the bind pose of a skeleton in the hierarchy starting from `root`. Current local transforms of the
+    /// bones are considered as the bind pose.
+    pub fn new(graph: &Graph, root: Handle<Node>, mapping: &HumanoidMapping) -> Self {
+        let bones = mapping
+            .find_nodes(graph, root)
+            .into_iter()
+            .map(|(bone, handle)| {
+                let node = &graph[handle];
+                let local_transform = node.local_transform();
+                let global = global_transform(graph, handle);
+                let parent = global_transform(graph, node.parent());
+                let global_rotation = rotation_of(&basis_of(&global));
+                let parent_frame =
+                    rotation_of(&basis_of(&parent)) * **local_transform.pre_rotation();
+                let post_rotation =
+                    (parent_frame * **local_transform.rotation()).inverse() * global_rotation;
+                (
+                    bone,
+                    BoneBindPose {
+                        handle,
+                        local_position: **local_transform.position(),
+                        local_rotation: **local_transform.rotation(),
+                        global_rotation,
+                        global_position: global.column(3).xyz(),
+                        parent_frame,
+                        post_rotation,
+                        parent_basis: basis_of(&parent),
+                    },
+                )
+            })
+            .collect::<FxHashMap<_, _>>();
+
+        let position = |bone| bones.get(&bone).map(|b: &BoneBindPose| b.global_position);
+        let leg_lengths = [
+            [
+                HumanoidBone::LeftUpperLeg,
+                HumanoidBone::LeftLowerLeg,
+                HumanoidBone::LeftFoot,
+            ],
+            [
+                HumanoidBone::RightUpperLeg,
+                HumanoidBone::RightLowerLeg,
+                HumanoidBone::RightFoot,
+            ],
+        ]
+        .into_iter()
+        .filter_map(|[upper, lower, foot]| {
+            let (upper, lower, foot) = (position(upper)?, position(lower)?, position(foot)?);
+            Some(upper.metric_distance(&lower) + lower.metric_distance(&foot))
+        })
+        .collect::<Vec<_>>();
+
+        let leg_length = if leg_lengths.is_empty() {
+            // Use the height of the hips as a rough approximation.
+            position(HumanoidBone::Hips)
+                .map(|hips| hips.y.abs())
+                .filter(|height| *height > f32::EPSILON)
+                .unwrap_or(1.0)
+        } else {
+            leg_lengths.iter().sum::<f32>() / leg_lengths.len() as f32
+        };
+
+        Self { bones, leg_length }
+    }
+
+    /// Returns bind pose of the given bone, if it is mapped.
+    pub fn bone(&self, bone: HumanoidBone) -> Option<&BoneBindPose> {
+        self.bones.get(&bone)
+    }
+}
+
+/// A result of comparison of bind poses of two skeletons. See [`HumanoidRetargeter::compare`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BindPoseComparison {
+    /// Ratio of the leg length of the target skeleton to the leg length of the source skeleton.
+    pub leg_length_ratio: f32,
+    /// Bones, that are mapped in the target skeleton, but not in the source skeleton. These bones won't be animated.
+    pub missing_in_source: Vec<HumanoidBone>,
+    /// Bones, that are mapped in the source skeleton, but not in the target skeleton. Animation of these bones will
+    /// be lost.
+    pub missing_in_target: Vec<HumanoidBone>,
+    /// Angles (in radians) between world-space directions from a bone to its child bone in both skeletons.
+    /// Large angles mean that the skeletons have different bind poses (for example, T-pose and A-pose) and the
+    /// retargeted animation will look distorted.
+    pub direction_mismatch: Vec<(HumanoidBone, f32)>,
+}
+
+impl BindPoseComparison {
+    /// Returns the largest direction mismatch between the skeletons (in radians).
+    pub fn max_mismatch(&self) -> f32 {
+        self.direction_mismatch
+            .iter()
+            .map(|(_, angle)| *angle)
+            .fold(0.0, f32::max)
+    }
+}
+
+/// Transfers animations from one humanoid skeleton to another. See [module docs](self) for more info.
+pub struct HumanoidRetargeter {
+    source: SkeletonBindPose,
+    target: SkeletonBindPose,
+    sample_rate: f32,
+}
+
+impl HumanoidRetargeter {
+    /// Creates a new retargeter using the current state of both skeletons as bind poses.
+    pub fn new(
+        source_graph: &Graph,
+        source_root: Handle<Node>,
+        source_mapping: &HumanoidMapping,
+        target_graph: &Graph,
+        target_root: Handle<Node>,
+        target_mapping: &HumanoidMapping,
+    ) -> Self {
+        Self::from_bind_poses(
+            SkeletonBindPose::new(source_graph, source_root, source_mapping),
+            SkeletonBindPose::new(target_graph, target_root, target_mapping),
+        )
+    }
+
+    /// Creates a new retargeter from already captured bind poses.
+    pub fn from_bind_poses(source: SkeletonBindPose, target: SkeletonBindPose) -> Self {
+        Self {
+            source,
+            target,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+        }
+    }
+
+    /// Sets the amount of samples per second, that will be used to bake retargeted tracks. Keys of source
+    /// tracks are always preserved, the samples are added in between them.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate.max(0.0);
+    }
+
+    /// Returns the current sample rate.
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// Returns bind pose of the source skeleton.
+    pub fn source(&self) -> &SkeletonBindPose {
+        &self.source
+    }
+
+    /// Returns bind pose of the target skeleton.
+    pub fn target(&self) -> &SkeletonBindPose {
+        &self.target
+    }
+
+    /// Ratio of the leg length of the target skeleton to the leg length of the source skeleton.
+    pub fn leg_length_ratio(&self) -> f32 {
+        self.target.leg_length / self.source.leg_length.max(f32::EPSILON)
+    }
+
+    /// Compares bind poses of the skeletons and reports differences, that may affect the quality of retargeting.
+    pub fn compare(&self) -> BindPoseComparison {
+        let mut comparison = BindPoseComparison {
+            leg_length_ratio: self.leg_length_ratio(),
+            ..Default::default()
+        };
+
+        for bone in HumanoidBone::ALL {
+            match (self.source.bone(bone), self.target.bone(bone)) {
+                (Some(_), None) => comparison.missing_in_target.push(bone),
+                (None, Some(_)) => comparison.missing_in_source.push(bone),
+                _ => (),
+            }
+        }
+
+        for bone in HumanoidBone::ALL {
+            let Some(parent) = bone.parent() else {
+                continue;
+            };
+            let direction = |pose: &SkeletonBindPose| {
+                Some(pose.bone(bone)?.global_position - pose.bone(parent)?.global_position)
+                    .filter(|d| d.norm() > f32::EPSILON)
+            };
+            if let (Some(source), Some(target)) = (direction(&self.source), direction(&self.target))
+            {
+                comparison
+                    .direction_mismatch
+                    .push((parent, source.angle(&target)));
+            }
+        }
+
+        comparison
+    }
+
+    fn retarget_rotation(
+        &self,
+        bone: HumanoidBone,
+        source_local: UnitQuaternion<f32>,
+    ) -> Option<UnitQuaternion<f32>> {
+        let source = self.source.bone(bone)?;
+        let target = self.target.bone(bone)?;
+        // World-space rotation of the source bone relative to its bind pose is applied to the bind pose of the
+        // target bone. Parent bones are retargeted the same way, so their deltas cancel out.
+        let delta = source.parent_frame
+            * source_local
+            * source.post_rotation
+            * source.global_rotation.inverse();
+        Some(
+            target.parent_frame.inverse()
+                * delta
+                * target.global_rotation
+                * target.post_rotation.inverse(),
+        )
+    }
+
+    fn retarget_hips_position(&self, source_local: Vector3<f32>) -> Option<Vector3<f32>> {
+        let source = self.source.bone(HumanoidBone::Hips)?;
+        let target = self.target.bone(HumanoidBone::Hips)?;
+        let world_offset =
+            source.parent_basis * (source_local - source.local_position) * self.leg_length_ratio();
+        let local_offset = target
+            .parent_basis
+            .try_inverse()
+            .map(|inv| inv * world_offset)
+            .unwrap_or(world_offset);
+        Some(target.local_position + local_offset)
+    }
+
+    /// Retargets the given animation of the source skeleton to the target skeleton. The new animation will
+    /// contain rotation tracks of all bones mapped in both skeletons and position track of the hips; all other
+    /// tracks are dropped. Name, looping, speed, time slice and signals are copied from the source animation.
+    pub fn retarget(&self, animation: &Animation) -> Animation {
+        let mut result = Animation::default();
+        result.set_name(animation.name());
+        result.set_loop(animation.is_loop());
+        result.set_speed(animation.speed());
+        for signal in animation.signals() {
+            result.add_signal(signal.clone());
+        }
+
+        let bones_by_handle = self
+            .source
+            .bones
+            .iter()
+            .map(|(bone, pose)| (pose.handle, *bone))
+            .collect::<FxHashMap<_, _>>();
+
+        let tracks_data = animation.tracks_data().state();
+        let Some(tracks_data) = tracks_data.data_ref() else {
+            Log::err(format!(
+                "Unable to retarget animation {}: its tracks data is not loaded!",
+                animation.name()
+            ));
+            return result;
+        };
+
+        for track in tracks_data.tracks.iter() {
+            let Some(source_binding) = animation.track_bindings().get(&track.id()) else {
+                continue;
+            };
+            let Some(&bone) = bones_by_handle.get(&source_binding.target) else {
+                continue;
+            };
+            let Some(target) = self.target.bone(bone) else {
+                continue;
+            };
+
+            let retargeted = match track.value_binding() {
+                ValueBinding::Rotation => self.bake_track(track, |value| match value {
+                    TrackValue::UnitQuaternion(rotation) => self
+                        .retarget_rotation(bone, rotation)
+                        .map(|r| quaternion_to_euler(&r)),
+                    _ => None,
+                }),
+                ValueBinding::Position if bone == HumanoidBone::Hips => {
+                    self.bake_track(track, |value| match value {
+                        TrackValue::Vector3(position) => self.retarget_hips_position(position),
+                        _ => None,
+                    })
+                }
+                _ => None,
+            };
+
+            if let Some(retargeted) = retargeted {
+                let mut binding = TrackBinding::new(target.handle);
+                binding.set_enabled(source_binding.is_enabled());
+                result.add_track_with_binding(binding, retargeted);
+            }
+        }
+
+        result.set_time_slice(animation.time_slice());
+
+        result
+    }
+
+    fn sample_times(&self, track: &Track) -> Vec<f32> {
+        let container = track.data_container();
+        let mut times = container
+            .curves_ref()
+            .iter()
+            .flat_map(|curve| curve.keys().iter().map(|key| key.location()))
+            .chain(
+                container
+                    .quantized_rotations()
+                    .iter()
+                    .map(|key| key.location),
+            )
+            .collect::<Vec<_>>();
+        let length = times.iter().cloned().fold(0.0, f32::max);
+        let start = times.iter().cloned().fold(length, f32::min);
+        if self.sample_rate > 0.0 {
+            let count = ((length - start) * self.sample_rate).ceil() as usize;
+            times.extend((0..count).map(|i| start + i as f32 / self.sample_rate));
+        }
+        times.sort_by(|a, b| a.total_cmp(b));
+        times.dedup_by(|a, b| (*a - *b).abs() <= f32::EPSILON);
+        times
+    }
+
+    fn bake_track<F>(&self, track: &Track, mut map: F) -> Option<Track>
+    where
+        F: FnMut(TrackValue) -> Option<Vector3<f32>>,
+    {
+        let kind = track.data_container().value_kind();
+        let mut keys: [Vec<CurveKey>; 3] = Default::default();
+        let mut previous: Option<Vector3<f32>> = None;
+        for time in self.sample_times(track) {
+            let mut value = map(track.fetch(time)?.value)?;
+            if kind == TrackValueKind::UnitQuaternion {
+                // Keep Euler angles continuous, otherwise interpolation between the keys will take the long way.
+                if let Some(previous) = previous {
+                    for (angle, previous) in value.iter_mut().zip(previous.iter()) {
+                        *angle = unwrap_angle(*angle, *previous);
+                    }
+                }
+                previous = Some(value);
+            }
+            for (keys, component) in keys.iter_mut().zip(value.iter()) {
+                keys.push(CurveKey::new(time, *component, CurveKeyKind::Linear));
+            }
+        }
+
+        let mut container = TrackDataContainer::new(kind);
+        for (curve, keys) in container.curves_mut().iter_mut().zip(keys) {
+            *curve = Curve::from(keys);
+        }
+        Some(Track::new(container, track.value_binding().clone()))
+    }
+}
+
+fn quaternion_to_euler(rotation: &UnitQuaternion<f32>) -> Vector3<f32> {
+    let (x, y, z) = rotation.euler_angles();
+    Vector3::new(x, y, z)
+}
+
+fn unwrap_angle(angle: f32, previous: f32) -> f32 {
+    use std::f32::consts::{PI, TAU};
+    let mut angle = angle;
+    while angle - previous > PI {
+        angle -= TAU;
+    }
+    while angle - previous < -PI {
+        angle += TAU;
+    }
+    angle
+}
+
+/// Retargets all animations of the given model (for example, an animation-only FBX file) to a humanoid skeleton
+/// in the hierarchy starting from `target_root`. Both skeletons must be in their bind poses. See
+/// [`HumanoidRetargeter`] docs for more info.
+pub fn retarget_humanoid_animations(
+    source: &Model,
+    source_mapping: &HumanoidMapping,
+    target_graph: &Graph,
+    target_root: Handle<Node>,
+    target_mapping: &HumanoidMapping,
+) -> Vec<Animation> {
+    let source_graph = &source.get_scene().graph;
+    let retargeter = HumanoidRetargeter::new(
+        source_graph,
+        source_graph.get_root(),
+        source_mapping,
+        target_graph,
+        target_root,
+        target_mapping,
+    );
+
+    source_graph
+        .linear_iter()
+        .filter_map(|node| node.component_ref::<InheritableVariable<AnimationContainer>>())
+        .flat_map(|animations| animations.iter())
+        .map(|animation| retargeter.retarget(animation))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            math::curve::{Curve, CurveKey, CurveKeyKind},
+            pool::Handle,
+        },
+        generic_animation::{
+            track::TrackBinding,
+            value::{TrackValue, ValueBinding},
+        },
+        graph::{BaseSceneGraph, SceneGraph},
+        resource::humanoid::{HumanoidBone, HumanoidMapping},
+        scene::{
+            animation::{
+                retarget::{global_transform, rotation_of, HumanoidRetargeter},
+                Animation, Track,
+            },
+            base::BaseBuilder,
+            graph::Graph,
+            node::Node,
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+    use std::f32::consts::FRAC_PI_2;
+
+    // World-space offsets of the bones relative to their parents (the hips are relative to the origin).
+    const OFFSETS: [(HumanoidBone, Vector3<f32>); 5] = [
+        (HumanoidBone::Hips, Vector3::new(0.0, 1.0, 0.0)),
+        (HumanoidBone::Spine, Vector3::new(0.0, 0.2, 0.0)),
+        (HumanoidBone::LeftUpperLeg, Vector3::new(0.1, -0.05, 0.0)),
+        (HumanoidBone::LeftLowerLeg, Vector3::new(0.0, -0.45, 0.0)),
+        (HumanoidBone::LeftFoot, Vector3::new(0.0, -0.45, 0.0)),
+    ];
+
+    // Builds a skeleton with the same world-space bind pose, but with the given scale, local bone rotations and names.
+    fn build_skeleton(
+        graph: &mut Graph,
+        scale: f32,
+        rotations: [UnitQuaternion<f32>; 5],
+        names: [&str; 5],
+    ) -> (Handle<Node>, HumanoidMapping) {
+        let mut mapping = HumanoidMapping::default();
+        let mut handles = Vec::new();
+        let mut globals = Vec::<UnitQuaternion<f32>>::new();
+        for (i, ((bone, offset), (rotation, name))) in OFFSETS
+            .iter()
+            .zip(rotations.iter().zip(names.iter()))
+            .enumerate()
+        {
+            let parent = match i {
+                0 => None,
+                1 | 2 => Some(0),
+                _ => Some(i - 1),
+            };
+            let parent_rotation = parent.map(|p| globals[p]).unwrap_or_default();
+            let handle = PivotBuilder::new(
+                BaseBuilder::new().with_name(*name).with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(parent_rotation.inverse() * offset.scale(scale))
+                        .with_local_rotation(*rotation)
+                        .build(),
+                ),
+            )
+            .build(graph);
+            if let Some(parent) = parent {
+                graph.link_nodes(handle, handles[parent]);
+            }
+            handles.push(handle);
+            globals.push(parent_rotation * rotation);
+            mapping.set_node_name(*bone, *name);
+        }
+        (handles[0], mapping)
+    }
+
+    fn fill_track(mut track: Track, keys: &[(f32, Vector3<f32>)]) -> Track {
+        for (i, curve) in track
+            .data_container_mut()
+            .curves_mut()
+            .iter_mut()
+            .enumerate()
+        {
+            *curve = Curve::from(
+                keys.iter()
+                    .map(|(t, v)| CurveKey::new(*t, v[i], CurveKeyKind::Linear))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        track
+    }
+
+    fn rotation_track(keys: &[(f32, Vector3<f32>)]) -> Track {
+        fill_track(Track::new_rotation(), keys)
+    }
+
+    fn position_track(keys: &[(f32, Vector3<f32>)]) -> Track {
+        fill_track(Track::new_position(), keys)
+    }
+
+    fn apply(animation: &Animation, graph: &mut Graph, time: f32) {
+        let data = animation.tracks_data().state();
+        for track in data.data_ref().unwrap().tracks.iter() {
+            let node = animation.track_bindings()[&track.id()].target;
+            let transform = graph[node].local_transform_mut();
+            match (track.value_binding(), track.fetch(time).unwrap().value) {
+                (ValueBinding::Rotation, TrackValue::UnitQuaternion(rotation)) => {
+                    transform.set_rotation(rotation);
+                }
+                (ValueBinding::Position, TrackValue::Vector3(position)) => {
+                    transform.set_position(position);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_retarget_different_proportions_and_orientations() {
+        let mut source_graph = Graph::new();
+        let (source_root, source_mapping) = build_skeleton(
+            &mut source_graph,
+            1.0,
+            [UnitQuaternion::identity(); 5],
+            ["Hips", "Spine", "LeftUpLeg", "LeftLeg", "LeftFoot"],
+        );
+
+        let mut target_graph = Graph::new();
+        let (target_root, target_mapping) = build_skeleton(
+            &mut target_graph,
+            2.0,
+            [
+                UnitQuaternion::from_euler_angles(0.0, FRAC_PI_2, 0.0),
+                UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2),
+                UnitQuaternion::from_euler_angles(FRAC_PI_2, 0.0, 0.3),
+                UnitQuaternion::from_euler_angles(-0.4, 0.2, 0.0),
+                UnitQuaternion::from_euler_angles(0.0, 0.5, 1.0),
+            ],
+            ["pelvis", "spine_01", "thigh_l", "calf_l", "foot_l"],
+        );
+
+        let retargeter = HumanoidRetargeter::new(
+            &source_graph,
+            source_root,
+            &source_mapping,
+            &target_graph,
+            target_root,
+            &target_mapping,
+        );
+
+        let comparison = retargeter.compare();
+        assert!((comparison.leg_length_ratio - 2.0).abs() < 1.0e-4);
+        assert!(comparison.max_mismatch() < 1.0e-4);
+        assert!(comparison.missing_in_source.is_empty());
+        assert!(comparison.missing_in_target.is_empty());
+
+        let source_handle = |name| source_graph.find_by_name_from_root(name).unwrap().0;
+        let mut source_animation = Animation::default();
+        source_animation.set_name("Walk");
+        source_animation.add_track_with_binding(
+            TrackBinding::new(source_handle("Hips")),
+            position_track(&[
+                (0.0, Vector3::new(0.0, 1.0, 0.0)),
+                (1.0, Vector3::new(0.0, 1.1, 1.0)),
+            ]),
+        );
+        source_animation.add_track_with_binding(
+            TrackBinding::new(source_handle("Spine")),
+            rotation_track(&[(0.0, Vector3::zeros()), (1.0, Vector3::new(0.8, 0.0, 0.0))]),
+        );
+        source_animation.add_track_with_binding(
+            TrackBinding::new(source_handle("LeftUpLeg")),
+            rotation_track(&[(0.0, Vector3::zeros()), (1.0, Vector3::new(0.0, 0.3, 0.6))]),
+        );
+        source_animation.add_track_with_binding(
+            TrackBinding::new(source_handle("LeftLeg")),
+            rotation_track(&[(0.0, Vector3::zeros()), (1.0, Vector3::new(-1.2, 0.0, 0.0))]),
+        );
+
+        let target_animation = retargeter.retarget(&source_animation);
+        assert_eq!(target_animation.name(), "Walk");
+        assert_eq!(target_animation.track_bindings().len(), 4);
+
+        let hips_rest = |graph: &Graph, root| global_transform(graph, root).column(3).xyz();
+        let source_hips_rest = hips_rest(&source_graph, source_root);
+        let target_hips_rest = hips_rest(&target_graph, target_root);
+
+        for time in [0.2, 0.5, 1.0] {
+            apply(&source_animation, &mut source_graph, time);
+            apply(&target_animation, &mut target_graph, time);
+
+            // Hips should move twice as far, because the legs of the target skeleton are twice as long.
+            let source_motion = hips_rest(&source_graph, source_root) - source_hips_rest;
+            let target_motion = hips_rest(&target_graph, target_root) - target_hips_rest;
+            assert!((target_motion - source_motion.scale(2.0)).norm() < 1.0e-3);
+
+            // Every bone should have the same world-space rotation relative to its bind pose.
+            for bone in [
+                HumanoidBone::Spine,
+                HumanoidBone::LeftUpperLeg,
+                HumanoidBone::LeftLowerLeg,
+                HumanoidBone::LeftFoot,
+            ] {
+                let delta = |graph: &Graph, retargeter_pose: &super::SkeletonBindPose| {
+                    let pose = retargeter_pose.bone(bone).unwrap();
+                    let global =
+                        rotation_of(&super::basis_of(&global_transform(graph, pose.handle)));
+                    global * pose.global_rotation.inverse()
+                };
+                let source_delta = delta(&source_graph, retargeter.source());
+                let target_delta = delta(&target_graph, retargeter.target());
+                assert!(
+                    source_delta.angle_to(&target_delta) < 1.0e-2,
+                    "{bone:?} at {time}: {source_delta:?} != {target_delta:?}"
+                );
+            }
+        }
+    }
+}
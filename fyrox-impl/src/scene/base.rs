@@ -37,7 +37,7 @@ use crate::{
     engine::SerializationContext,
     graph::BaseSceneGraph,
     resource::model::ModelResource,
-    scene::{node::Node, transform::Transform},
+    scene::{layer::LayerMask, node::Node, transform::Transform},
     script::{Script, ScriptTrait},
 };
 use fyrox_core::algebra::UnitQuaternion;
@@ -353,6 +353,9 @@ pub(crate) enum NodeMessageKind {
     TransformChanged,
     VisibilityChanged,
     EnabledFlagChanged,
+    TagsChanged,
+    ComponentsChanged,
+    BoundsChanged,
 }
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
@@ -476,6 +479,18 @@ pub struct Base {
     #[reflect(setter = "set_tag")]
     tag: InheritableVariable<String>,
 
+    #[reflect(
+        setter = "set_tags",
+        description = "A set of tags of the node. Tags could be used to quickly find nodes of a particular kind."
+    )]
+    tags: InheritableVariable<Vec<String>>,
+
+    #[reflect(
+        setter = "set_layers",
+        description = "A set of layers the node belongs to. Layers are used for camera culling and physics filtering."
+    )]
+    layers: InheritableVariable<LayerMask>,
+
     #[reflect(setter = "set_cast_shadows")]
     cast_shadows: InheritableVariable<bool>,
 
@@ -487,6 +502,12 @@ pub struct Base {
     #[reflect(setter = "set_frustum_culling")]
     frustum_culling: InheritableVariable<bool>,
 
+    #[reflect(
+        setter = "set_networked",
+        description = "Defines whether the node state should be replicated from server to clients or not."
+    )]
+    networked: InheritableVariable<bool>,
+
     // When `true` it means that this node is instance of `resource`.
     // More precisely - this node is root of whole descendant nodes
     // hierarchy which was instantiated from resource.
@@ -594,6 +615,9 @@ impl Base {
         self.enabled.set_message_data(message_sender, self_handle);
         // Kick off initial hierarchical property propagation.
         self.notify(self.self_handle, NodeMessageKind::TransformChanged);
+        // Make sure the node is registered in the tag and layer index.
+        self.notify(self.self_handle, NodeMessageKind::TagsChanged);
+        self.notify(self.self_handle, NodeMessageKind::ComponentsChanged);
         self.notify(self.self_handle, NodeMessageKind::VisibilityChanged);
         self.notify(self.self_handle, NodeMessageKind::EnabledFlagChanged);
     }
@@ -605,6 +629,14 @@ impl Base {
         Log::verify(sender.send(NodeMessage::new(node, kind)));
     }
 
+    /// Notifies the scene graph, that the bounding box of the node has changed without moving the
+    /// node (for example - vertices of a mesh were modified). The spatial index of the graph will
+    /// update the entry of the node on next synchronization.
+    #[inline]
+    pub fn invalidate_bounds(&self) {
+        self.notify(self.self_handle, NodeMessageKind::BoundsChanged);
+    }
+
     /// Returns mutable reference to local transform of a node, can be used to set
     /// some local spatial properties, such as position, rotation, scale, etc.
     #[inline]
@@ -834,9 +866,75 @@ impl Base {
     /// Sets new tag.
     #[inline]
     pub fn set_tag(&mut self, tag: String) -> String {
+        self.notify(self.self_handle, NodeMessageKind::TagsChanged);
         self.tag.set_value_and_mark_modified(tag)
     }
 
+    /// Returns a set of tags of the node. Keep in mind, that this set does not include the tag
+    /// set by [`Self::set_tag`], use [`Self::has_tag`] to check both.
+    #[inline]
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Sets a new set of tags of the node. Tag index of the graph will be updated on the next
+    /// graph update.
+    #[inline]
+    pub fn set_tags(&mut self, tags: Vec<String>) -> Vec<String> {
+        self.notify(self.self_handle, NodeMessageKind::TagsChanged);
+        self.tags.set_value_and_mark_modified(tags)
+    }
+
+    /// Adds a new tag to the node, does nothing if the node already has the tag.
+    #[inline]
+    pub fn add_tag(&mut self, tag: impl Into<String>) {
+        let tag = tag.into();
+        if !self.tags.contains(&tag) {
+            self.notify(self.self_handle, NodeMessageKind::TagsChanged);
+            self.tags.get_value_mut_and_mark_modified().push(tag);
+        }
+    }
+
+    /// Removes the given tag from the node. Returns `true` if the tag was removed, `false` -
+    /// otherwise.
+    #[inline]
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        if let Some(position) = self.tags.iter().position(|t| t == tag) {
+            self.notify(self.self_handle, NodeMessageKind::TagsChanged);
+            self.tags.get_value_mut_and_mark_modified().remove(position);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the node has the given tag (either in its set of tags or as its main
+    /// tag), `false` - otherwise.
+    #[inline]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        *self.tag == tag || self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Returns a set of layers the node belongs to.
+    #[inline]
+    pub fn layers(&self) -> LayerMask {
+        *self.layers
+    }
+
+    /// Sets a new set of layers the node belongs to. Layer index of the graph will be updated on
+    /// the next graph update.
+    #[inline]
+    pub fn set_layers(&mut self, layers: LayerMask) -> LayerMask {
+        self.notify(self.self_handle, NodeMessageKind::TagsChanged);
+        self.layers.set_value_and_mark_modified(layers)
+    }
+
+    /// Returns `true` if the node belongs to the given layer, `false` - otherwise.
+    #[inline]
+    pub fn is_in_layer(&self, layer: usize) -> bool {
+        self.layers.contains_layer(layer)
+    }
+
     /// Return the frustum_culling flag
     #[inline]
     pub fn frustum_culling(&self) -> bool {
@@ -862,6 +960,19 @@ impl Base {
         self.cast_shadows.set_value_and_mark_modified(cast_shadows)
     }
 
+    /// Returns `true` if the node state is replicated from server to clients, `false` - otherwise.
+    /// See [`crate::scene::replication`] module docs for more info.
+    #[inline]
+    pub fn is_networked(&self) -> bool {
+        *self.networked
+    }
+
+    /// Sets whether the node state should be replicated from server to clients or not.
+    #[inline]
+    pub fn set_networked(&mut self, networked: bool) -> bool {
+        self.networked.set_value_and_mark_modified(networked)
+    }
+
     /// Returns current instance id.
     pub fn instance_id(&self) -> SceneNodeId {
         self.instance_id
@@ -872,6 +983,10 @@ impl Base {
     /// update tick of the parent graph.
     pub fn remove_script(&mut self, index: usize) {
         // Send script to the graph to destroy script instances correctly.
+        if index < self.scripts.len() {
+            self.notify(self.self_handle, NodeMessageKind::ComponentsChanged);
+        }
+
         if let Some(entry) = self.scripts.get_mut(index) {
             entry.should_be_deleted = true;
 
@@ -911,6 +1026,8 @@ impl Base {
     pub fn replace_script(&mut self, index: usize, script: Option<Script>) {
         self.remove_script(index);
 
+        self.notify(self.self_handle, NodeMessageKind::ComponentsChanged);
+
         if let Some(entry) = self.scripts.get_mut(index) {
             entry.script = script;
             if let Some(sender) = self.script_message_sender.as_ref() {
@@ -934,6 +1051,7 @@ impl Base {
     {
         let script_index = self.scripts.len();
         self.scripts.push(ScriptRecord::new(Script::new(script)));
+        self.notify(self.self_handle, NodeMessageKind::ComponentsChanged);
         if let Some(sender) = self.script_message_sender.as_ref() {
             Log::verify(sender.send(NodeScriptMessage::InitializeScript {
                 handle: self.self_handle,
@@ -1204,6 +1322,9 @@ impl Visit for Base {
         let _ = self.cast_shadows.visit("CastShadows", &mut region);
         let _ = self.instance_id.visit("InstanceId", &mut region);
         let _ = self.enabled.visit("Enabled", &mut region);
+        let _ = self.networked.visit("Networked", &mut region);
+        let _ = self.tags.visit("Tags", &mut region);
+        let _ = self.layers.visit("Layers", &mut region);
 
         // Script visiting may fail for various reasons:
         //
@@ -1240,11 +1361,14 @@ pub struct BaseBuilder {
     mobility: Mobility,
     inv_bind_pose_transform: Matrix4<f32>,
     tag: String,
+    tags: Vec<String>,
+    layers: LayerMask,
     frustum_culling: bool,
     cast_shadows: bool,
     scripts: Vec<ScriptRecord>,
     instance_id: SceneNodeId,
     enabled: bool,
+    networked: bool,
 }
 
 impl Default for BaseBuilder {
@@ -1267,11 +1391,14 @@ impl BaseBuilder {
             mobility: Default::default(),
             inv_bind_pose_transform: Matrix4::identity(),
             tag: Default::default(),
+            tags: Default::default(),
+            layers: LayerMask::DEFAULT,
             frustum_culling: true,
             cast_shadows: true,
             scripts: vec![],
             instance_id: SceneNodeId(Uuid::new_v4()),
             enabled: true,
+            networked: false,
         }
     }
 
@@ -1351,6 +1478,20 @@ impl BaseBuilder {
         self
     }
 
+    /// Sets desired set of tags.
+    #[inline]
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Sets desired set of layers.
+    #[inline]
+    pub fn with_layers(mut self, layers: LayerMask) -> Self {
+        self.layers = layers;
+        self
+    }
+
     /// Sets desired frustum_culling flag.
     #[inline]
     pub fn with_frustum_culling(mut self, frustum_culling: bool) -> Self {
@@ -1381,6 +1522,13 @@ impl BaseBuilder {
         self
     }
 
+    /// Sets whether the node state should be replicated from server to clients or not.
+    #[inline]
+    pub fn with_networked(mut self, networked: bool) -> Self {
+        self.networked = networked;
+        self
+    }
+
     /// Creates an instance of [`Base`].
     #[inline]
     pub fn build_base(self) -> Base {
@@ -1413,11 +1561,14 @@ impl BaseBuilder {
             lod_group: self.lod_group.into(),
             mobility: self.mobility.into(),
             tag: self.tag.into(),
+            tags: self.tags.into(),
+            layers: self.layers.into(),
             properties: Default::default(),
             frustum_culling: self.frustum_culling.into(),
             cast_shadows: self.cast_shadows.into(),
+            networked: self.networked.into(),
             scripts: self.scripts,
-            instance_id: SceneNodeId(Uuid::new_v4()),
+            instance_id: self.instance_id,
 
             global_enabled: Cell::new(true),
         }
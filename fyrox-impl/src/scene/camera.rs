@@ -47,6 +47,7 @@ use crate::{
         base::{Base, BaseBuilder},
         debug::SceneDrawingContext,
         graph::Graph,
+        layer::LayerMask,
         node::{Node, NodeTrait, UpdateContext},
     },
 };
@@ -382,6 +383,13 @@ pub struct Camera {
     #[reflect(setter = "set_color_grading_enabled")]
     color_grading_enabled: InheritableVariable<bool>,
 
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_culling_mask",
+        description = "A set of layers rendered by the camera. Objects that do not belong to any of the layers are not rendered."
+    )]
+    culling_mask: InheritableVariable<LayerMask>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     view_matrix: Matrix4<f32>,
@@ -734,6 +742,18 @@ impl Camera {
     pub fn exposure(&self) -> Exposure {
         *self.exposure
     }
+
+    /// Sets a new set of layers rendered by the camera. Objects that do not belong to any of the
+    /// layers of the mask will not be rendered by the camera. Light sources are not affected by
+    /// the mask. See [`crate::scene::layer`] module docs for more info about layers.
+    pub fn set_culling_mask(&mut self, mask: LayerMask) -> LayerMask {
+        self.culling_mask.set_value_and_mark_modified(mask)
+    }
+
+    /// Returns current set of layers rendered by the camera.
+    pub fn culling_mask(&self) -> LayerMask {
+        *self.culling_mask
+    }
 }
 
 impl ConstructorProvider<Node, Graph> for Camera {
@@ -1075,6 +1095,7 @@ pub struct CameraBuilder {
     color_grading_lut: Option<ColorGradingLut>,
     color_grading_enabled: bool,
     projection: Projection,
+    culling_mask: LayerMask,
 }
 
 impl CameraBuilder {
@@ -1093,6 +1114,7 @@ impl CameraBuilder {
             color_grading_lut: None,
             color_grading_enabled: false,
             projection: Projection::default(),
+            culling_mask: LayerMask::ALL,
         }
     }
 
@@ -1168,6 +1190,12 @@ impl CameraBuilder {
         self
     }
 
+    /// Sets desired set of layers rendered by the camera.
+    pub fn with_culling_mask(mut self, mask: LayerMask) -> Self {
+        self.culling_mask = mask;
+        self
+    }
+
     /// Creates new instance of camera.
     pub fn build_camera(self) -> Camera {
         Camera {
@@ -1188,6 +1216,7 @@ impl CameraBuilder {
             exposure: self.exposure.into(),
             color_grading_lut: self.color_grading_lut.into(),
             color_grading_enabled: self.color_grading_enabled.into(),
+            culling_mask: self.culling_mask.into(),
         }
     }
 
@@ -0,0 +1,1363 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Cloth is a scene node that simulates a surface of a mesh as a piece of soft fabric. See [`Cloth`]
+//! docs for more info and usage examples.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::BaseSceneGraph,
+    scene::{
+        base::{Base, BaseBuilder},
+        collider::{Collider, ColliderShape},
+        graph::Graph,
+        mesh::{
+            buffer::{
+                VertexAttributeDataType, VertexAttributeUsage, VertexFetchError, VertexReadTrait,
+                VertexWriteTrait,
+            },
+            surface::SurfaceData,
+            Mesh,
+        },
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+    },
+};
+use fxhash::FxHashMap;
+use fyrox_graph::constructor::ConstructorProvider;
+use fyrox_graph::SceneGraphNode;
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// A vertex attribute, that holds pin weights of a cloth.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "e26d83b4-68f8-44ae-8d5f-1a75c5184b21")]
+pub enum ClothPinAttribute {
+    /// No vertices are pinned, the entire cloth is simulated.
+    None,
+    /// Vertex color. This is the most convenient option, since vertex colors can be painted in
+    /// pretty much any 3D modelling software.
+    #[default]
+    Color,
+    /// First custom vertex attribute.
+    Custom0,
+    /// Second custom vertex attribute.
+    Custom1,
+    /// Third custom vertex attribute.
+    Custom2,
+    /// Fourth custom vertex attribute.
+    Custom3,
+}
+
+impl ClothPinAttribute {
+    /// Returns vertex attribute usage that corresponds to the pin attribute.
+    pub fn usage(self) -> Option<VertexAttributeUsage> {
+        match self {
+            ClothPinAttribute::None => None,
+            ClothPinAttribute::Color => Some(VertexAttributeUsage::Color),
+            ClothPinAttribute::Custom0 => Some(VertexAttributeUsage::Custom0),
+            ClothPinAttribute::Custom1 => Some(VertexAttributeUsage::Custom1),
+            ClothPinAttribute::Custom2 => Some(VertexAttributeUsage::Custom2),
+            ClothPinAttribute::Custom3 => Some(VertexAttributeUsage::Custom3),
+        }
+    }
+}
+
+/// A component of the pin attribute, that holds pin weights of a cloth.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "00e86f61-98e5-4c08-a648-7820690e5a45")]
+pub enum ClothPinChannel {
+    /// First component (red for vertex colors).
+    #[default]
+    X,
+    /// Second component (green for vertex colors).
+    Y,
+    /// Third component (blue for vertex colors).
+    Z,
+    /// Fourth component (alpha for vertex colors).
+    W,
+}
+
+impl ClothPinChannel {
+    fn index(self) -> usize {
+        match self {
+            ClothPinChannel::X => 0,
+            ClothPinChannel::Y => 1,
+            ClothPinChannel::Z => 2,
+            ClothPinChannel::W => 3,
+        }
+    }
+}
+
+/// A simplified collision shape, that is used to resolve collisions of cloth particles.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClothCollider {
+    /// A sphere in world coordinates.
+    Sphere {
+        /// Center of the sphere.
+        center: Vector3<f32>,
+        /// Radius of the sphere.
+        radius: f32,
+    },
+    /// A capsule in world coordinates.
+    Capsule {
+        /// Begin point of the capsule axis.
+        begin: Vector3<f32>,
+        /// End point of the capsule axis.
+        end: Vector3<f32>,
+        /// Radius of the capsule.
+        radius: f32,
+    },
+}
+
+impl ClothCollider {
+    /// Tries to create a cloth collider from the given collider node. Only ball and capsule shapes
+    /// are supported, `None` will be returned for any other shape.
+    pub fn from_collider(collider: &Collider) -> Option<Self> {
+        let transform = collider.global_transform();
+        let scale = transform
+            .fixed_view::<3, 3>(0, 0)
+            .column_iter()
+            .map(|c| c.norm())
+            .fold(0.0f32, f32::max);
+        match collider.shape() {
+            ColliderShape::Ball(ball) => Some(Self::Sphere {
+                center: transform.transform_point(&Point3::origin()).coords,
+                radius: ball.radius * scale,
+            }),
+            ColliderShape::Capsule(capsule) => Some(Self::Capsule {
+                begin: transform
+                    .transform_point(&Point3::from(capsule.begin))
+                    .coords,
+                end: transform.transform_point(&Point3::from(capsule.end)).coords,
+                radius: capsule.radius * scale,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Pushes the given point out of the shape. `margin` defines additional distance from the
+    /// surface of the shape.
+    pub fn resolve(&self, point: &mut Vector3<f32>, margin: f32) {
+        let (closest, radius) = match *self {
+            ClothCollider::Sphere { center, radius } => (center, radius),
+            ClothCollider::Capsule { begin, end, radius } => {
+                let axis = end - begin;
+                let length_sqr = axis.norm_squared();
+                let t = if length_sqr > f32::EPSILON {
+                    ((*point - begin).dot(&axis) / length_sqr).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                (begin + axis.scale(t), radius)
+            }
+        };
+
+        let radius = radius + margin;
+        let delta = *point - closest;
+        let distance = delta.norm();
+        if distance < radius {
+            if distance > f32::EPSILON {
+                *point = closest + delta.scale(radius / distance);
+            } else {
+                *point = closest + Vector3::new(0.0, radius, 0.0);
+            }
+        }
+    }
+}
+
+/// Parameters of a single simulation step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClothStepParameters {
+    /// Amount of solver iterations.
+    pub iterations: u32,
+    /// Stiffness of stretching constraints in `[0; 1]` range.
+    pub stretch_stiffness: f32,
+    /// Stiffness of bending constraints in `[0; 1]` range.
+    pub bend_stiffness: f32,
+    /// Fraction of velocity, that will be removed every step.
+    pub damping: f32,
+    /// Gravity acceleration.
+    pub gravity: Vector3<f32>,
+    /// Velocity of the air.
+    pub wind: Vector3<f32>,
+    /// Aerodynamic drag coefficient.
+    pub drag: f32,
+    /// Additional distance between particles and colliders.
+    pub collision_margin: f32,
+}
+
+#[derive(Clone, Debug, Default)]
+struct ClothParticle {
+    position: Vector3<f32>,
+    prev_position: Vector3<f32>,
+    // Position, that the particle will follow if it is pinned.
+    target: Vector3<f32>,
+    pin: f32,
+}
+
+#[derive(Clone, Debug)]
+struct DistanceConstraint {
+    a: u32,
+    b: u32,
+    rest_length: f32,
+}
+
+#[derive(Clone, Debug, Default)]
+struct VertexSkin {
+    indices: [u8; 4],
+    weights: [f32; 4],
+}
+
+/// Simulation state of a cloth. It is built from a mesh surface: vertices with the same position
+/// (for example, vertices on UV seams) are welded together into a single particle, edges of triangles
+/// become stretching constraints and pairs of triangles sharing an edge become bending constraints.
+#[derive(Clone, Debug, Default)]
+pub struct ClothState {
+    particles: Vec<ClothParticle>,
+    vertex_to_particle: Vec<u32>,
+    triangles: Vec<[u32; 3]>,
+    stretch: Vec<DistanceConstraint>,
+    bend: Vec<DistanceConstraint>,
+    forces: Vec<Vector3<f32>>,
+    normals: Vec<Vector3<f32>>,
+    bounding_box: AxisAlignedBoundingBox,
+    // Data from the surface, that is needed to convert particle positions back to mesh space.
+    rest_positions: Vec<Vector3<f32>>,
+    skin: Vec<VertexSkin>,
+    // Defines the surface, that was used to build the state.
+    key: Option<(Handle<Node>, u32, ClothPinAttribute, ClothPinChannel)>,
+}
+
+fn weld_key(position: &Vector3<f32>) -> [i32; 3] {
+    const PRECISION: f32 = 10000.0;
+    [
+        (position.x * PRECISION).round() as i32,
+        (position.y * PRECISION).round() as i32,
+        (position.z * PRECISION).round() as i32,
+    ]
+}
+
+fn edge(a: u32, b: u32) -> (u32, u32) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+impl ClothState {
+    /// Creates new simulation state from the given set of vertices in world coordinates, pin
+    /// weights of each vertex and a set of triangles.
+    pub fn new(positions: &[Vector3<f32>], pins: &[f32], triangles: &[[u32; 3]]) -> Self {
+        let mut particles = Vec::<ClothParticle>::new();
+        let mut vertex_to_particle = Vec::with_capacity(positions.len());
+        let mut welded = FxHashMap::default();
+        for (i, position) in positions.iter().enumerate() {
+            let pin = pins.get(i).cloned().unwrap_or_default().clamp(0.0, 1.0);
+            let index = *welded.entry(weld_key(position)).or_insert_with(|| {
+                particles.push(ClothParticle {
+                    position: *position,
+                    prev_position: *position,
+                    target: *position,
+                    pin: 0.0,
+                });
+                particles.len() as u32 - 1
+            });
+            // Welded vertices could have different weights, the strongest one wins.
+            let particle = &mut particles[index as usize];
+            particle.pin = particle.pin.max(pin);
+            vertex_to_particle.push(index);
+        }
+
+        let triangles = triangles
+            .iter()
+            .filter_map(|triangle| {
+                let mapped = [
+                    *vertex_to_particle.get(triangle[0] as usize)?,
+                    *vertex_to_particle.get(triangle[1] as usize)?,
+                    *vertex_to_particle.get(triangle[2] as usize)?,
+                ];
+                // Skip degenerated triangles.
+                if mapped[0] == mapped[1] || mapped[1] == mapped[2] || mapped[0] == mapped[2] {
+                    None
+                } else {
+                    Some(mapped)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let distance = |a: u32, b: u32| DistanceConstraint {
+            a,
+            b,
+            rest_length: (particles[a as usize].position - particles[b as usize].position).norm(),
+        };
+
+        // Edge -> vertex opposite to the edge in the first triangle that uses the edge.
+        let mut edges = FxHashMap::<(u32, u32), Option<u32>>::default();
+        let mut stretch = Vec::new();
+        let mut bend = Vec::new();
+        for triangle in triangles.iter() {
+            for i in 0..3 {
+                let a = triangle[i];
+                let b = triangle[(i + 1) % 3];
+                let opposite = triangle[(i + 2) % 3];
+                match edges.get_mut(&edge(a, b)) {
+                    Some(other) => {
+                        if let Some(other) = other.take() {
+                            if other != opposite {
+                                bend.push(distance(other, opposite));
+                            }
+                        }
+                    }
+                    None => {
+                        edges.insert(edge(a, b), Some(opposite));
+                        stretch.push(distance(a, b));
+                    }
+                }
+            }
+        }
+
+        Self {
+            forces: vec![Vector3::default(); particles.len()],
+            normals: vec![Vector3::default(); particles.len()],
+            bounding_box: AxisAlignedBoundingBox::from_points(
+                &particles.iter().map(|p| p.position).collect::<Vec<_>>(),
+            ),
+            particles,
+            vertex_to_particle,
+            triangles,
+            stretch,
+            bend,
+            rest_positions: Default::default(),
+            skin: Default::default(),
+            key: None,
+        }
+    }
+
+    /// Returns amount of simulated particles.
+    pub fn particle_count(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// Returns position of a particle, that corresponds to the given vertex of the source surface.
+    pub fn vertex_position(&self, vertex: usize) -> Option<Vector3<f32>> {
+        self.vertex_to_particle
+            .get(vertex)
+            .map(|p| self.particles[*p as usize].position)
+    }
+
+    /// Returns normal of a particle, that corresponds to the given vertex of the source surface.
+    /// Normals are smooth across welded vertices and updated after every simulation step.
+    pub fn vertex_normal(&self, vertex: usize) -> Option<Vector3<f32>> {
+        self.vertex_to_particle
+            .get(vertex)
+            .map(|p| self.normals[*p as usize])
+    }
+
+    /// Returns world-space bounding box of the particles, it is updated after every simulation step.
+    pub fn bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.bounding_box
+    }
+
+    /// Sets new target position of a particle, that corresponds to the given vertex. Pinned
+    /// particles follow their targets.
+    pub fn set_vertex_target(&mut self, vertex: usize, target: Vector3<f32>) {
+        if let Some(particle) = self.vertex_to_particle.get(vertex) {
+            self.particles[*particle as usize].target = target;
+        }
+    }
+
+    fn accumulate_wind_forces(&mut self, dt: f32, wind: Vector3<f32>, drag: f32) {
+        for force in self.forces.iter_mut() {
+            *force = Vector3::default();
+        }
+
+        if drag <= 0.0 || dt <= 0.0 {
+            return;
+        }
+
+        for triangle in self.triangles.iter() {
+            let [a, b, c] = triangle.map(|i| &self.particles[i as usize]);
+            let cross = (b.position - a.position).cross(&(c.position - a.position));
+            let double_area = cross.norm();
+            if double_area <= f32::EPSILON {
+                continue;
+            }
+            let normal = cross.scale(1.0 / double_area);
+            let velocity = (a.position - a.prev_position + b.position - b.prev_position
+                + c.position
+                - c.prev_position)
+                .scale(1.0 / (3.0 * dt));
+            let relative_velocity = wind - velocity;
+            // Only the normal component of the flow pushes the triangle.
+            let force = normal.scale(drag * 0.5 * double_area * normal.dot(&relative_velocity));
+            let per_vertex = force.scale(1.0 / 3.0);
+            for &i in triangle {
+                self.forces[i as usize] += per_vertex;
+            }
+        }
+    }
+
+    fn solve_constraints(
+        particles: &mut [ClothParticle],
+        constraints: &[DistanceConstraint],
+        stiffness: f32,
+    ) {
+        if stiffness <= 0.0 {
+            return;
+        }
+
+        for constraint in constraints {
+            let (a, b) = (constraint.a as usize, constraint.b as usize);
+            let wa = 1.0 - particles[a].pin;
+            let wb = 1.0 - particles[b].pin;
+            let total = wa + wb;
+            if total <= f32::EPSILON {
+                continue;
+            }
+            let delta = particles[b].position - particles[a].position;
+            let length = delta.norm();
+            if length <= f32::EPSILON {
+                continue;
+            }
+            let correction =
+                delta.scale(stiffness * (length - constraint.rest_length) / (length * total));
+            particles[a].position += correction.scale(wa);
+            particles[b].position -= correction.scale(wb);
+        }
+    }
+
+    /// Performs a single simulation step using position-based dynamics.
+    pub fn step(&mut self, dt: f32, parameters: &ClothStepParameters, colliders: &[ClothCollider]) {
+        self.accumulate_wind_forces(dt, parameters.wind, parameters.drag);
+
+        let damping = 1.0 - parameters.damping.clamp(0.0, 1.0);
+        for (particle, force) in self.particles.iter_mut().zip(self.forces.iter()) {
+            if particle.pin >= 1.0 {
+                particle.prev_position = particle.target;
+                particle.position = particle.target;
+                continue;
+            }
+
+            let velocity = (particle.position - particle.prev_position).scale(damping);
+            particle.prev_position = particle.position;
+            particle.position += velocity + (parameters.gravity + force).scale(dt * dt);
+        }
+
+        let stretch_stiffness = parameters.stretch_stiffness.clamp(0.0, 1.0);
+        let bend_stiffness = parameters.bend_stiffness.clamp(0.0, 1.0);
+        for _ in 0..parameters.iterations.max(1) {
+            Self::solve_constraints(&mut self.particles, &self.stretch, stretch_stiffness);
+            Self::solve_constraints(&mut self.particles, &self.bend, bend_stiffness);
+
+            for particle in self.particles.iter_mut() {
+                if particle.pin < 1.0 {
+                    for collider in colliders {
+                        collider.resolve(&mut particle.position, parameters.collision_margin);
+                    }
+                }
+            }
+        }
+
+        // Partially pinned particles are pulled towards their targets.
+        for particle in self.particles.iter_mut() {
+            if particle.pin > 0.0 && particle.pin < 1.0 {
+                particle.position = particle.position.lerp(&particle.target, particle.pin);
+            }
+        }
+
+        self.update_normals_and_bounds();
+    }
+
+    fn update_normals_and_bounds(&mut self) {
+        for normal in self.normals.iter_mut() {
+            *normal = Vector3::default();
+        }
+        // Non-normalized cross product makes the normals area-weighted.
+        for triangle in self.triangles.iter() {
+            let [a, b, c] = triangle.map(|i| self.particles[i as usize].position);
+            let cross = (b - a).cross(&(c - a));
+            for &i in triangle {
+                self.normals[i as usize] += cross;
+            }
+        }
+        for normal in self.normals.iter_mut() {
+            *normal = normal
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::y);
+        }
+
+        self.bounding_box = AxisAlignedBoundingBox::default();
+        for particle in self.particles.iter() {
+            self.bounding_box.add_point(particle.position);
+        }
+    }
+}
+
+fn read_pin_weight<V: VertexReadTrait>(
+    vertex: &V,
+    usage: VertexAttributeUsage,
+    data_type: VertexAttributeDataType,
+    size: u8,
+    channel: usize,
+) -> Result<f32, VertexFetchError> {
+    match (data_type, size) {
+        (VertexAttributeDataType::U8, 4) => Ok(vertex.read_4_u8(usage)?[channel] as f32 / 255.0),
+        (VertexAttributeDataType::F32, 4) => Ok(vertex.read_4_f32(usage)?[channel]),
+        (VertexAttributeDataType::F32, 3) => Ok(vertex
+            .read_3_f32(usage)?
+            .get(channel)
+            .cloned()
+            .unwrap_or_default()),
+        (VertexAttributeDataType::F32, 2) => Ok(vertex
+            .read_2_f32(usage)?
+            .get(channel)
+            .cloned()
+            .unwrap_or_default()),
+        _ => Err(VertexFetchError::NoSuchAttribute(usage)),
+    }
+}
+
+fn vertex_matrix(
+    skin: Option<&VertexSkin>,
+    bone_matrices: &[Matrix4<f32>],
+    mesh_transform: &Matrix4<f32>,
+) -> Matrix4<f32> {
+    match skin {
+        Some(skin) if !bone_matrices.is_empty() => {
+            let mut matrix = Matrix4::zeros();
+            for (index, weight) in skin.indices.iter().zip(skin.weights) {
+                if let Some(bone_matrix) = bone_matrices.get(*index as usize) {
+                    matrix += bone_matrix.scale(weight);
+                }
+            }
+            matrix
+        }
+        _ => *mesh_transform,
+    }
+}
+
+/// Cloth is a scene node, that takes a surface of a [`Mesh`] and simulates it as a piece of soft
+/// fabric. Simulation is done on CPU using position-based dynamics: every unique vertex of the
+/// surface is a particle, edges of triangles keep particles at fixed distance and pairs of adjacent
+/// triangles resist bending.
+///
+/// ## Pinning
+///
+/// Some parts of a cloth are usually attached to something - a cape is attached to shoulders of a
+/// character, a flag is attached to a pole and so on. Such vertices are "pinned" using a vertex
+/// attribute: weight `1.0` means that the vertex strictly follows the mesh (including skinning),
+/// weight `0.0` means that the vertex is fully simulated. Values in-between are blended. By default,
+/// the red channel of vertex color is used, see [`ClothPinAttribute`] and [`ClothPinChannel`].
+///
+/// ## Collisions
+///
+/// Cloth can collide with a set of [`Collider`] nodes with ball or capsule shapes. Colliders of
+/// other shapes are ignored. Collisions are one-way - cloth does not affect the physics.
+///
+/// ## Important notes
+///
+/// The simulated vertices are written back into the vertex buffer of the surface every frame. If
+/// the surface data is shared with other meshes, all of them will be deformed, so make sure that
+/// the mesh has its own copy of the data.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, pool::Handle},
+/// #     scene::{base::BaseBuilder, cloth::ClothBuilder, graph::Graph, node::Node},
+/// # };
+/// fn create_cape(character_mesh: Handle<Node>, body: Handle<Node>, graph: &mut Graph) -> Handle<Node> {
+///     ClothBuilder::new(BaseBuilder::new().with_name("Cape"))
+///         .with_mesh(character_mesh)
+///         .with_colliders(vec![body])
+///         .with_wind(Vector3::new(1.0, 0.0, 0.5))
+///         .build(graph)
+/// }
+/// ```
+#[derive(Clone, Reflect, Visit, Debug, ComponentProvider)]
+#[visit(optional)]
+pub struct Cloth {
+    base: Base,
+    /// A handle of a mesh, whose surface will be simulated.
+    pub mesh: InheritableVariable<Handle<Node>>,
+    /// Index of the surface of the mesh, that will be simulated.
+    pub surface: InheritableVariable<u32>,
+    /// A vertex attribute, that contains pin weights.
+    pub pin_attribute: InheritableVariable<ClothPinAttribute>,
+    /// A component of [`Self::pin_attribute`], that contains pin weights.
+    pub pin_channel: InheritableVariable<ClothPinChannel>,
+    /// Amount of solver iterations per frame. More iterations - stiffer cloth, but slower simulation.
+    #[reflect(min_value = 1.0, max_value = 64.0, step = 1.0)]
+    pub iterations: InheritableVariable<u32>,
+    /// Resistance to stretching in `[0; 1]` range.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub stretch_stiffness: InheritableVariable<f32>,
+    /// Resistance to bending in `[0; 1]` range.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub bend_stiffness: InheritableVariable<f32>,
+    /// Fraction of velocity, that will be removed every frame.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.001)]
+    pub damping: InheritableVariable<f32>,
+    /// Gravity acceleration in world coordinates.
+    pub gravity: InheritableVariable<Vector3<f32>>,
+    /// Velocity of the wind in world coordinates.
+    pub wind: InheritableVariable<Vector3<f32>>,
+    /// Aerodynamic drag coefficient, defines how strong the wind affects the cloth.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub drag: InheritableVariable<f32>,
+    /// A set of collider nodes with ball or capsule shapes, that the cloth will collide with.
+    pub colliders: InheritableVariable<Vec<Handle<Node>>>,
+    /// Additional distance between cloth particles and colliders, helps to prevent clipping.
+    #[reflect(min_value = 0.0, step = 0.001)]
+    pub collision_margin: InheritableVariable<f32>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    state: ClothState,
+    // Positions of the vertices of the surface before the simulation has started. The simulation
+    // writes deformed positions to the same vertex buffer, so they're captured only once per surface.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    rest_positions: Option<(Handle<Node>, u32, Vec<Vector3<f32>>)>,
+}
+
+impl Default for Cloth {
+    fn default() -> Self {
+        ClothBuilder::new(BaseBuilder::new()).build_cloth()
+    }
+}
+
+impl Deref for Cloth {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Cloth {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Cloth {
+    fn type_uuid() -> Uuid {
+        uuid!("ecfc34e1-57fa-4316-b99f-77e008bd62a1")
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for Cloth {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Cloth", |_| {
+                ClothBuilder::new(BaseBuilder::new().with_name("Cloth"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Physics")
+    }
+}
+
+impl Cloth {
+    /// Returns current simulation state.
+    pub fn state(&self) -> &ClothState {
+        &self.state
+    }
+
+    /// Discards current simulation state, it will be rebuilt from the source surface on next update.
+    pub fn reset(&mut self) {
+        self.state = Default::default();
+    }
+
+    fn step_parameters(&self) -> ClothStepParameters {
+        ClothStepParameters {
+            iterations: *self.iterations,
+            stretch_stiffness: *self.stretch_stiffness,
+            bend_stiffness: *self.bend_stiffness,
+            damping: *self.damping,
+            gravity: *self.gravity,
+            wind: *self.wind,
+            drag: *self.drag,
+            collision_margin: *self.collision_margin,
+        }
+    }
+
+    fn build_state(
+        &mut self,
+        data: &SurfaceData,
+        bone_matrices: &[Matrix4<f32>],
+        mesh_transform: &Matrix4<f32>,
+    ) -> Result<ClothState, VertexFetchError> {
+        let vertex_buffer = &data.vertex_buffer;
+
+        let pin_source = self.pin_attribute.usage().and_then(|usage| {
+            vertex_buffer
+                .layout_descriptor()
+                .find(|d| d.usage == usage)
+                .map(|d| (usage, d.data_type, d.size))
+        });
+        let skinned = !bone_matrices.is_empty()
+            && vertex_buffer.has_attribute(VertexAttributeUsage::BoneIndices)
+            && vertex_buffer.has_attribute(VertexAttributeUsage::BoneWeight);
+
+        let vertex_count = vertex_buffer.vertex_count() as usize;
+        let rest_positions = match self.rest_positions.take() {
+            Some((mesh, surface, positions))
+                if mesh == *self.mesh
+                    && surface == *self.surface
+                    && positions.len() == vertex_count =>
+            {
+                positions
+            }
+            _ => vertex_buffer
+                .iter()
+                .map(|vertex| vertex.read_3_f32(VertexAttributeUsage::Position))
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+        self.rest_positions = Some((*self.mesh, *self.surface, rest_positions.clone()));
+
+        let mut pins = Vec::with_capacity(vertex_count);
+        let mut skin = Vec::new();
+        for vertex in vertex_buffer.iter() {
+            pins.push(match pin_source {
+                Some((usage, data_type, size)) => {
+                    read_pin_weight(&vertex, usage, data_type, size, self.pin_channel.index())?
+                }
+                None => 0.0,
+            });
+            if skinned {
+                let weights = vertex.read_4_f32(VertexAttributeUsage::BoneWeight)?;
+                skin.push(VertexSkin {
+                    indices: vertex.read_4_u8(VertexAttributeUsage::BoneIndices)?.into(),
+                    weights: weights.into(),
+                });
+            }
+        }
+
+        let world_positions = rest_positions
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                vertex_matrix(skin.get(i), bone_matrices, mesh_transform)
+                    .transform_point(&Point3::from(*p))
+                    .coords
+            })
+            .collect::<Vec<_>>();
+
+        let triangles = data.geometry_buffer.iter().map(|t| t.0).collect::<Vec<_>>();
+
+        let mut state = ClothState::new(&world_positions, &pins, &triangles);
+        state.rest_positions = rest_positions;
+        state.skin = skin;
+        state.key = Some((
+            *self.mesh,
+            *self.surface,
+            *self.pin_attribute,
+            *self.pin_channel,
+        ));
+        Ok(state)
+    }
+}
+
+impl NodeTrait for Cloth {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        if self.state.particle_count() > 0 {
+            self.state.bounding_box()
+        } else {
+            self.base.world_bounding_box()
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        let surface_index = *self.surface as usize;
+
+        let Some(mesh) = ctx
+            .nodes
+            .try_borrow(*self.mesh)
+            .and_then(|n| n.component_ref::<Mesh>())
+        else {
+            return;
+        };
+        let Some(surface) = mesh.surfaces().get(surface_index) else {
+            return;
+        };
+        if !surface.data_ref().is_ok() {
+            return;
+        }
+
+        let mesh_transform = mesh.global_transform();
+        let bone_matrices = surface
+            .bones()
+            .iter()
+            .map(|bone| {
+                ctx.nodes
+                    .try_borrow(*bone)
+                    .map(|bone| bone.global_transform() * bone.inv_bind_pose_transform())
+                    .unwrap_or_else(Matrix4::identity)
+            })
+            .collect::<Vec<_>>();
+
+        let key = Some((
+            *self.mesh,
+            *self.surface,
+            *self.pin_attribute,
+            *self.pin_channel,
+        ));
+        {
+            let data = surface.data_ref().data_ref();
+            if self.state.key != key
+                || self.state.rest_positions.len() != data.vertex_buffer.vertex_count() as usize
+            {
+                match self.build_state(&data, &bone_matrices, &mesh_transform) {
+                    Ok(state) => self.state = state,
+                    Err(_) => return,
+                }
+            }
+        }
+
+        let colliders = self
+            .colliders
+            .iter()
+            .filter_map(|handle| {
+                ctx.nodes
+                    .try_borrow(*handle)
+                    .and_then(|n| n.component_ref::<Collider>())
+                    .and_then(ClothCollider::from_collider)
+            })
+            .collect::<Vec<_>>();
+
+        // Update targets of pinned particles, so they will follow the mesh.
+        let state = &mut self.state;
+        for (vertex, rest_position) in state.rest_positions.iter().enumerate() {
+            let particle = &mut state.particles[state.vertex_to_particle[vertex] as usize];
+            if particle.pin > 0.0 {
+                particle.target =
+                    vertex_matrix(state.skin.get(vertex), &bone_matrices, &mesh_transform)
+                        .transform_point(&Point3::from(*rest_position))
+                        .coords;
+            }
+        }
+
+        // Large time steps make the simulation unstable, so the step is limited.
+        let parameters = self.step_parameters();
+        self.state
+            .step(ctx.dt.min(1.0 / 30.0), &parameters, &colliders);
+
+        // Write the simulated positions back to the surface in the mesh space.
+        let Some(mesh) = ctx
+            .nodes
+            .try_borrow_mut(*self.mesh)
+            .and_then(|n| n.component_mut::<Mesh>())
+        else {
+            return;
+        };
+        let Some(surface) = mesh.surfaces_mut().get_mut(surface_index) else {
+            return;
+        };
+        let mut data = surface.data_ref().data_ref();
+        let has_normals = data
+            .vertex_buffer
+            .has_attribute(VertexAttributeUsage::Normal);
+        let state = &self.state;
+        let mut vertex_buffer = data.vertex_buffer.modify();
+        for (vertex_index, mut vertex) in vertex_buffer.iter_mut().enumerate() {
+            let (Some(world_position), Some(world_normal)) = (
+                state.vertex_position(vertex_index),
+                state.vertex_normal(vertex_index),
+            ) else {
+                break;
+            };
+            let matrix = vertex_matrix(
+                state.skin.get(vertex_index),
+                &bone_matrices,
+                &mesh_transform,
+            );
+            let local_position = matrix
+                .try_inverse()
+                .unwrap_or_else(Matrix4::identity)
+                .transform_point(&Point3::from(world_position))
+                .coords;
+            if vertex
+                .write_3_f32(VertexAttributeUsage::Position, local_position)
+                .is_err()
+            {
+                break;
+            }
+            if has_normals {
+                // Transposed matrix is the inverse of the normal matrix.
+                let local_normal = matrix
+                    .transpose()
+                    .transform_vector(&world_normal)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or(world_normal);
+                if vertex
+                    .write_3_f32(VertexAttributeUsage::Normal, local_normal)
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        }
+        drop(vertex_buffer);
+        drop(data);
+
+        // Refresh the bounds of the mesh right away to prevent wrong culling of the deformed mesh,
+        // and let the spatial index know that both the mesh and the cloth changed their bounds.
+        if let Some(mesh) = ctx
+            .nodes
+            .try_borrow(*self.mesh)
+            .and_then(|n| n.component_ref::<Mesh>())
+        {
+            mesh.update_world_bounding_box(&mesh_transform, ctx.nodes);
+            mesh.invalidate_bounds();
+        }
+        self.invalidate_bounds();
+    }
+}
+
+/// Cloth builder allows you to create [`Cloth`] scene nodes in declarative manner.
+pub struct ClothBuilder {
+    base_builder: BaseBuilder,
+    mesh: Handle<Node>,
+    surface: u32,
+    pin_attribute: ClothPinAttribute,
+    pin_channel: ClothPinChannel,
+    iterations: u32,
+    stretch_stiffness: f32,
+    bend_stiffness: f32,
+    damping: f32,
+    gravity: Vector3<f32>,
+    wind: Vector3<f32>,
+    drag: f32,
+    colliders: Vec<Handle<Node>>,
+    collision_margin: f32,
+}
+
+impl ClothBuilder {
+    /// Creates new cloth builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            mesh: Default::default(),
+            surface: 0,
+            pin_attribute: Default::default(),
+            pin_channel: Default::default(),
+            iterations: 8,
+            stretch_stiffness: 1.0,
+            bend_stiffness: 0.2,
+            damping: 0.01,
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            wind: Default::default(),
+            drag: 0.5,
+            colliders: Default::default(),
+            collision_margin: 0.01,
+        }
+    }
+
+    /// Sets the desired mesh, whose surface will be simulated.
+    pub fn with_mesh(mut self, mesh: Handle<Node>) -> Self {
+        self.mesh = mesh;
+        self
+    }
+
+    /// Sets the desired index of the surface of the mesh.
+    pub fn with_surface(mut self, surface: u32) -> Self {
+        self.surface = surface;
+        self
+    }
+
+    /// Sets the desired vertex attribute and its component, that contains pin weights.
+    pub fn with_pin_attribute(
+        mut self,
+        attribute: ClothPinAttribute,
+        channel: ClothPinChannel,
+    ) -> Self {
+        self.pin_attribute = attribute;
+        self.pin_channel = channel;
+        self
+    }
+
+    /// Sets the desired amount of solver iterations.
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Sets the desired stretching stiffness.
+    pub fn with_stretch_stiffness(mut self, stiffness: f32) -> Self {
+        self.stretch_stiffness = stiffness;
+        self
+    }
+
+    /// Sets the desired bending stiffness.
+    pub fn with_bend_stiffness(mut self, stiffness: f32) -> Self {
+        self.bend_stiffness = stiffness;
+        self
+    }
+
+    /// Sets the desired damping.
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Sets the desired gravity.
+    pub fn with_gravity(mut self, gravity: Vector3<f32>) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Sets the desired wind velocity.
+    pub fn with_wind(mut self, wind: Vector3<f32>) -> Self {
+        self.wind = wind;
+        self
+    }
+
+    /// Sets the desired aerodynamic drag coefficient.
+    pub fn with_drag(mut self, drag: f32) -> Self {
+        self.drag = drag;
+        self
+    }
+
+    /// Sets the desired set of colliders.
+    pub fn with_colliders(mut self, colliders: Vec<Handle<Node>>) -> Self {
+        self.colliders = colliders;
+        self
+    }
+
+    /// Sets the desired collision margin.
+    pub fn with_collision_margin(mut self, margin: f32) -> Self {
+        self.collision_margin = margin;
+        self
+    }
+
+    /// Creates new cloth instance.
+    pub fn build_cloth(self) -> Cloth {
+        Cloth {
+            base: self.base_builder.build_base(),
+            mesh: self.mesh.into(),
+            surface: self.surface.into(),
+            pin_attribute: self.pin_attribute.into(),
+            pin_channel: self.pin_channel.into(),
+            iterations: self.iterations.into(),
+            stretch_stiffness: self.stretch_stiffness.into(),
+            bend_stiffness: self.bend_stiffness.into(),
+            damping: self.damping.into(),
+            gravity: self.gravity.into(),
+            wind: self.wind.into(),
+            drag: self.drag.into(),
+            colliders: self.colliders.into(),
+            collision_margin: self.collision_margin.into(),
+            state: Default::default(),
+            rest_positions: None,
+        }
+    }
+
+    /// Creates new cloth node, but does not add it to a graph.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_cloth())
+    }
+
+    /// Creates new cloth node and adds it to the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::algebra::Vector2,
+        scene::mesh::{
+            surface::{SurfaceBuilder, SurfaceResource},
+            MeshBuilder,
+        },
+    };
+    use fyrox_resource::untyped::ResourceKind;
+
+    // A square grid of `n * n` cells in XZ plane, vertices are duplicated on the diagonal seam to
+    // check welding.
+    fn grid(n: u32, size: f32) -> (Vec<Vector3<f32>>, Vec<[u32; 3]>) {
+        let mut positions = Vec::new();
+        for z in 0..=n {
+            for x in 0..=n {
+                positions.push(Vector3::new(
+                    x as f32 * size / n as f32,
+                    0.0,
+                    z as f32 * size / n as f32,
+                ));
+            }
+        }
+        let mut triangles = Vec::new();
+        for z in 0..n {
+            for x in 0..n {
+                let i = z * (n + 1) + x;
+                triangles.push([i, i + n + 1, i + 1]);
+                triangles.push([i + 1, i + n + 1, i + n + 2]);
+            }
+        }
+        (positions, triangles)
+    }
+
+    fn parameters() -> ClothStepParameters {
+        ClothStepParameters {
+            iterations: 16,
+            stretch_stiffness: 1.0,
+            bend_stiffness: 0.2,
+            damping: 0.01,
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            wind: Default::default(),
+            drag: 0.0,
+            collision_margin: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_welding_and_constraints() {
+        let (mut positions, mut triangles) = grid(1, 1.0);
+        // Duplicate the first vertex, as it would be on a UV seam.
+        positions.push(positions[0]);
+        triangles[0][0] = 4;
+
+        let state = ClothState::new(&positions, &[], &triangles);
+        assert_eq!(state.particle_count(), 4);
+        assert_eq!(state.vertex_to_particle[4], state.vertex_to_particle[0]);
+        // 4 border edges + 1 diagonal.
+        assert_eq!(state.stretch.len(), 5);
+        assert_eq!(state.bend.len(), 1);
+    }
+
+    #[test]
+    fn test_pinned_cloth_hangs() {
+        let (positions, triangles) = grid(8, 1.0);
+        // Pin the first row.
+        let pins = positions
+            .iter()
+            .map(|p| if p.z == 0.0 { 1.0 } else { 0.0 })
+            .collect::<Vec<_>>();
+
+        let mut state = ClothState::new(&positions, &pins, &triangles);
+        for _ in 0..120 {
+            state.step(1.0 / 60.0, &parameters(), &[]);
+        }
+
+        for (i, position) in positions.iter().enumerate() {
+            let simulated = state.vertex_position(i).unwrap();
+            if position.z == 0.0 {
+                assert_eq!(simulated, *position);
+            } else {
+                assert!(simulated.y < 0.0);
+                // Cloth must not stretch much.
+                assert!(simulated.y > -1.1);
+            }
+        }
+
+        for constraint in state.stretch.iter() {
+            let length = (state.particles[constraint.a as usize].position
+                - state.particles[constraint.b as usize].position)
+                .norm();
+            assert!((length - constraint.rest_length).abs() < 0.05 * constraint.rest_length);
+        }
+    }
+
+    #[test]
+    fn test_normals_and_bounds() {
+        let (positions, triangles) = grid(4, 1.0);
+        let pins = positions
+            .iter()
+            .map(|p| if p.z == 0.0 { 1.0 } else { 0.0 })
+            .collect::<Vec<_>>();
+
+        let mut state = ClothState::new(&positions, &pins, &triangles);
+        state.step(0.0, &parameters(), &[]);
+        for i in 0..positions.len() {
+            assert!((state.vertex_normal(i).unwrap().y.abs() - 1.0).abs() < 1.0e-5);
+        }
+        let bounds = state.bounding_box();
+        assert_eq!(bounds.min, Vector3::new(0.0, 0.0, 0.0));
+        assert_eq!(bounds.max, Vector3::new(1.0, 0.0, 1.0));
+
+        for _ in 0..60 {
+            state.step(1.0 / 60.0, &parameters(), &[]);
+        }
+        assert!(state.bounding_box().min.y < -0.1);
+    }
+
+    #[test]
+    fn test_pinned_vertices_follow_targets() {
+        let (positions, triangles) = grid(2, 1.0);
+        let pins = vec![1.0; positions.len()];
+        let mut state = ClothState::new(&positions, &pins, &triangles);
+        let offset = Vector3::new(0.0, 2.0, 0.0);
+        for (i, position) in positions.iter().enumerate() {
+            state.set_vertex_target(i, position + offset);
+        }
+        state.step(1.0 / 60.0, &parameters(), &[]);
+        for (i, position) in positions.iter().enumerate() {
+            assert_eq!(state.vertex_position(i).unwrap(), position + offset);
+        }
+    }
+
+    #[test]
+    fn test_sphere_collision() {
+        let (positions, triangles) = grid(10, 2.0);
+        let positions = positions
+            .iter()
+            .map(|p| p + Vector3::new(-1.0, 1.1, -1.0))
+            .collect::<Vec<_>>();
+        let mut state = ClothState::new(&positions, &[], &triangles);
+        let sphere = ClothCollider::Sphere {
+            center: Default::default(),
+            radius: 1.0,
+        };
+        for _ in 0..60 {
+            state.step(1.0 / 60.0, &parameters(), &[sphere]);
+        }
+        for particle in state.particles.iter() {
+            assert!(particle.position.norm() >= 1.0 - 1.0e-4);
+        }
+    }
+
+    #[test]
+    fn test_capsule_resolve() {
+        let capsule = ClothCollider::Capsule {
+            begin: Vector3::new(0.0, -1.0, 0.0),
+            end: Vector3::new(0.0, 1.0, 0.0),
+            radius: 0.5,
+        };
+        let mut point = Vector3::new(0.25, 0.5, 0.0);
+        capsule.resolve(&mut point, 0.0);
+        assert!((point - Vector3::new(0.5, 0.5, 0.0)).norm() < 1.0e-5);
+
+        let mut point = Vector3::new(0.0, 1.25, 0.0);
+        capsule.resolve(&mut point, 0.0);
+        assert!((point - Vector3::new(0.0, 1.5, 0.0)).norm() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_wind_pushes_cloth() {
+        let (positions, triangles) = grid(4, 1.0);
+        let mut state = ClothState::new(&positions, &[], &triangles);
+        let parameters = ClothStepParameters {
+            gravity: Default::default(),
+            wind: Vector3::new(0.0, 5.0, 0.0),
+            drag: 1.0,
+            ..parameters()
+        };
+        for _ in 0..10 {
+            state.step(1.0 / 60.0, &parameters, &[]);
+        }
+        assert!(state.particles.iter().all(|p| p.position.y > 0.0));
+    }
+
+    #[test]
+    fn test_spatial_index_follows_deformed_mesh() {
+        let mut graph = Graph::new();
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                SurfaceData::make_quad(&Matrix4::identity()),
+            ))
+            .build()])
+            .build(&mut graph);
+        ClothBuilder::new(BaseBuilder::new())
+            .with_mesh(mesh)
+            .build(&mut graph);
+
+        let origin =
+            AxisAlignedBoundingBox::from_min_max(Vector3::repeat(-0.1), Vector3::repeat(0.1));
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        assert_eq!(
+            graph.spatial_query().of_type::<Mesh>().aabb(&origin),
+            vec![mesh]
+        );
+
+        // The cloth is not pinned, so it falls and moves the vertices of the mesh, while the mesh
+        // node itself stays in place.
+        for _ in 0..60 {
+            graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        }
+        graph.update_spatial_index();
+
+        let bounds = graph[mesh].world_bounding_box();
+        assert!(bounds.max.y < -1.0);
+        assert_eq!(graph[mesh].global_position(), Vector3::default());
+        assert!(graph
+            .spatial_query()
+            .of_type::<Mesh>()
+            .aabb(&origin)
+            .is_empty());
+        assert_eq!(
+            graph
+                .spatial_query()
+                .of_type::<Mesh>()
+                .aabb(&AxisAlignedBoundingBox::from_point(bounds.center())),
+            vec![mesh]
+        );
+    }
+}
@@ -24,12 +24,14 @@
 
 use crate::{
     core::{
+        algebra::Vector2,
         color::Color,
-        math::aabb::AxisAlignedBoundingBox,
+        math::{aabb::AxisAlignedBoundingBox, Rect},
         pool::Handle,
         reflect::prelude::*,
         type_traits::prelude::*,
         uuid::{uuid, Uuid},
+        uuid_provider,
         variable::InheritableVariable,
         visitor::prelude::*,
     },
@@ -42,8 +44,11 @@ use crate::{
     },
 };
 use fyrox_graph::constructor::ConstructorProvider;
-use fyrox_graph::BaseSceneGraph;
-use std::ops::{Deref, DerefMut};
+use fyrox_graph::{BaseSceneGraph, SceneGraph};
+use std::{
+    collections::VecDeque,
+    ops::{Deref, DerefMut},
+};
 
 /// Decal is an image that gets projected to a geometry of a scene. Blood splatters, bullet holes, scratches
 /// etc. are done via decals.
@@ -67,6 +72,25 @@ use std::ops::{Deref, DerefMut};
 /// Currently, only diffuse and normal maps are supported. Diffuse and normal maps will be automatically projected
 /// on the data stored in G-Buffer.
 ///
+/// # Blending
+///
+/// A decal could affect albedo, normals, material properties (metallic and roughness) and emission of the
+/// surfaces. Contribution to each of the channels is defined by its own blend factor, see
+/// [`DecalChannelBlending`] docs for more info. For example, a puddle could set albedo blend factor to zero
+/// and only make the surface smooth by overriding its roughness. Emission is added on top of the ambient
+/// lighting of the surfaces and ambient occlusion of the surfaces is never changed.
+///
+/// # Fading
+///
+/// A decal could fade out on the surfaces that are not facing its projection direction (local Y axis) to
+/// prevent stretching on steep surfaces, see [`Decal::set_angle_fade`]. A decal with limited lifetime (see
+/// [`Base::set_lifetime`]) could also fade out smoothly before removal, see [`Decal::set_fade_out_time`].
+///
+/// # Atlases
+///
+/// Thousands of decals could share a single texture atlas, each decal defines a rectangle in the atlas
+/// (in normalized texture coordinates) that will be projected, see [`Decal::set_uv_rect`].
+///
 /// # Limitations
 ///
 /// Current implementation works only with Deferred render path. Custom materials that uses Forward pass should
@@ -75,7 +99,8 @@ use std::ops::{Deref, DerefMut};
 /// # Performance
 ///
 /// It should be noted that decals are not cheap, keep amount (and size) of decals at reasonable values! This
-/// means that unused decals (bullet holes for example) must be removed after some time.
+/// means that unused decals (bullet holes for example) must be removed after some time. [`DecalPool`] could
+/// be used to limit the amount of decals spawned at runtime.
 ///
 /// # Example
 ///
@@ -106,7 +131,7 @@ use std::ops::{Deref, DerefMut};
 ///         .build(graph)
 /// }
 /// ```
-#[derive(Debug, Visit, Default, Clone, Reflect, ComponentProvider)]
+#[derive(Debug, Visit, Clone, Reflect, ComponentProvider)]
 pub struct Decal {
     base: Base,
 
@@ -122,6 +147,106 @@ pub struct Decal {
     #[reflect(min_value = 0.0)]
     #[reflect(setter = "set_layer")]
     layer: InheritableVariable<u8>,
+
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_channel_blending",
+        description = "Contribution of the decal to each of the G-Buffer channels."
+    )]
+    channel_blending: InheritableVariable<DecalChannelBlending>,
+
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_angle_fade",
+        description = "Angles (in degrees) between surface normal and projection direction at which the \
+        decal starts and ends fading out."
+    )]
+    angle_fade: InheritableVariable<Vector2<f32>>,
+
+    #[visit(optional)]
+    #[reflect(
+        min_value = 0.0,
+        setter = "set_fade_out_time",
+        description = "Amount of time (in seconds) before the end of the node's lifetime during which \
+        the decal fades out."
+    )]
+    fade_out_time: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_uv_rect",
+        description = "Rectangle in normalized texture coordinates that will be projected. Allows \
+        decals to use texture atlases."
+    )]
+    uv_rect: InheritableVariable<Rect<f32>>,
+}
+
+/// Defines how much a decal affects each of the G-Buffer channels. Every factor is in `[0; 1]` range,
+/// where 0 means that the channel is left intact and 1 means that the channel is fully replaced
+/// (modulated by the alpha of the diffuse texture).
+#[derive(Debug, Visit, Clone, Copy, PartialEq, Reflect)]
+pub struct DecalChannelBlending {
+    /// Blend factor of the albedo (diffuse color).
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub albedo: f32,
+    /// Blend factor of the normals.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub normal: f32,
+    /// Blend factor of the material properties (metallic and roughness).
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub material: f32,
+    /// Blend factor of the emission.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub emission: f32,
+    /// Metallic value that will be written to the surfaces if material blend factor is non-zero.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub metallic: f32,
+    /// Roughness value that will be written to the surfaces if material blend factor is non-zero.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub roughness: f32,
+    /// Multiplier of the decal color that will be written as emission if emission blend factor is non-zero.
+    #[reflect(min_value = 0.0)]
+    pub emission_strength: f32,
+}
+
+uuid_provider!(DecalChannelBlending = "0b3d0897-788b-4570-9554-f4d4e290efa4");
+
+impl Default for DecalChannelBlending {
+    fn default() -> Self {
+        Self {
+            albedo: 1.0,
+            normal: 1.0,
+            material: 0.0,
+            emission: 0.0,
+            metallic: 0.0,
+            roughness: 1.0,
+            emission_strength: 1.0,
+        }
+    }
+}
+
+fn default_angle_fade() -> Vector2<f32> {
+    Vector2::new(90.0, 90.0)
+}
+
+fn default_uv_rect() -> Rect<f32> {
+    Rect::new(0.0, 0.0, 1.0, 1.0)
+}
+
+impl Default for Decal {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            diffuse_texture: Default::default(),
+            normal_texture: Default::default(),
+            color: Default::default(),
+            layer: Default::default(),
+            channel_blending: Default::default(),
+            angle_fade: default_angle_fade().into(),
+            fade_out_time: Default::default(),
+            uv_rect: default_uv_rect().into(),
+        }
+    }
 }
 
 impl Deref for Decal {
@@ -210,6 +335,63 @@ impl Decal {
     pub fn layer(&self) -> u8 {
         *self.layer
     }
+
+    /// Sets new blend factors for every G-Buffer channel.
+    pub fn set_channel_blending(&mut self, blending: DecalChannelBlending) -> DecalChannelBlending {
+        self.channel_blending.set_value_and_mark_modified(blending)
+    }
+
+    /// Returns current blend factors for every G-Buffer channel.
+    pub fn channel_blending(&self) -> DecalChannelBlending {
+        *self.channel_blending
+    }
+
+    /// Sets angles (in degrees) between surface normal and projection direction (local Y axis) at which
+    /// the decal starts (`x`) and ends (`y`) fading out. Surfaces with angle greater than `y` are not
+    /// affected by the decal. If `y <= x`, then the fading is disabled. Default is `(90.0, 90.0)`, which
+    /// means no fading at all.
+    pub fn set_angle_fade(&mut self, angle_fade: Vector2<f32>) -> Vector2<f32> {
+        self.angle_fade.set_value_and_mark_modified(angle_fade)
+    }
+
+    /// Returns angles (in degrees) at which the decal starts and ends fading out.
+    pub fn angle_fade(&self) -> Vector2<f32> {
+        *self.angle_fade
+    }
+
+    /// Sets amount of time (in seconds) before the end of the lifetime of the node during which the
+    /// decal fades out. It has no effect if the node has unlimited lifetime.
+    pub fn set_fade_out_time(&mut self, time: f32) -> f32 {
+        self.fade_out_time
+            .set_value_and_mark_modified(time.max(0.0))
+    }
+
+    /// Returns amount of time (in seconds) during which the decal fades out.
+    pub fn fade_out_time(&self) -> f32 {
+        *self.fade_out_time
+    }
+
+    /// Returns current opacity of the decal defined by its remaining lifetime, see
+    /// [`Self::set_fade_out_time`].
+    pub fn lifetime_fade(&self) -> f32 {
+        match self.base.lifetime() {
+            Some(lifetime) if *self.fade_out_time > 0.0 => {
+                (lifetime / *self.fade_out_time).clamp(0.0, 1.0)
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// Sets a rectangle in normalized texture coordinates that will be projected. Default is the
+    /// whole texture (`0, 0, 1, 1`). Use it to pick a decal from a texture atlas.
+    pub fn set_uv_rect(&mut self, uv_rect: Rect<f32>) -> Rect<f32> {
+        self.uv_rect.set_value_and_mark_modified(uv_rect)
+    }
+
+    /// Returns current rectangle in normalized texture coordinates that will be projected.
+    pub fn uv_rect(&self) -> Rect<f32> {
+        *self.uv_rect
+    }
 }
 
 impl ConstructorProvider<Node, Graph> for Decal {
@@ -247,6 +429,10 @@ pub struct DecalBuilder {
     normal_texture: Option<TextureResource>,
     color: Color,
     layer: u8,
+    channel_blending: DecalChannelBlending,
+    angle_fade: Vector2<f32>,
+    fade_out_time: f32,
+    uv_rect: Rect<f32>,
 }
 
 impl DecalBuilder {
@@ -258,6 +444,10 @@ impl DecalBuilder {
             normal_texture: None,
             color: Color::opaque(255, 255, 255),
             layer: 0,
+            channel_blending: Default::default(),
+            angle_fade: default_angle_fade(),
+            fade_out_time: 0.0,
+            uv_rect: default_uv_rect(),
         }
     }
 
@@ -285,6 +475,30 @@ impl DecalBuilder {
         self
     }
 
+    /// Sets desired blend factors for every G-Buffer channel.
+    pub fn with_channel_blending(mut self, channel_blending: DecalChannelBlending) -> Self {
+        self.channel_blending = channel_blending;
+        self
+    }
+
+    /// Sets desired angles (in degrees) at which the decal starts and ends fading out.
+    pub fn with_angle_fade(mut self, angle_fade: Vector2<f32>) -> Self {
+        self.angle_fade = angle_fade;
+        self
+    }
+
+    /// Sets desired amount of time during which the decal fades out before the end of its lifetime.
+    pub fn with_fade_out_time(mut self, fade_out_time: f32) -> Self {
+        self.fade_out_time = fade_out_time.max(0.0);
+        self
+    }
+
+    /// Sets desired rectangle in normalized texture coordinates that will be projected.
+    pub fn with_uv_rect(mut self, uv_rect: Rect<f32>) -> Self {
+        self.uv_rect = uv_rect;
+        self
+    }
+
     /// Creates new Decal node.
     pub fn build_decal(self) -> Decal {
         Decal {
@@ -293,6 +507,10 @@ impl DecalBuilder {
             normal_texture: self.normal_texture.into(),
             color: self.color.into(),
             layer: self.layer.into(),
+            channel_blending: self.channel_blending.into(),
+            angle_fade: self.angle_fade.into(),
+            fade_out_time: self.fade_out_time.into(),
+            uv_rect: self.uv_rect.into(),
         }
     }
 
@@ -306,3 +524,143 @@ impl DecalBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+/// A pool of decals with limited capacity, that is used to spawn lots of decals at runtime (bullet holes,
+/// blood splatters, footprints, etc.). When the pool is full, the oldest decal is removed from the graph
+/// to make room for the new one.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::algebra::Vector3,
+/// #     scene::{
+/// #         base::BaseBuilder,
+/// #         decal::{DecalBuilder, DecalPool},
+/// #         graph::Graph,
+/// #         transform::TransformBuilder,
+/// #     },
+/// # };
+/// fn spawn_bullet_hole(pool: &mut DecalPool, graph: &mut Graph, position: Vector3<f32>) {
+///     pool.spawn(
+///         DecalBuilder::new(
+///             BaseBuilder::new().with_local_transform(
+///                 TransformBuilder::new()
+///                     .with_local_position(position)
+///                     .build(),
+///             ),
+///         ),
+///         graph,
+///     );
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DecalPool {
+    capacity: usize,
+    decals: VecDeque<Handle<Node>>,
+}
+
+impl DecalPool {
+    /// Creates new decal pool with the given capacity.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            decals: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Sets new capacity of the pool. If the new capacity is less than the amount of spawned
+    /// decals, the oldest decals are removed from the graph.
+    pub fn set_capacity(&mut self, capacity: usize, graph: &mut Graph) {
+        self.capacity = capacity;
+        self.shrink_to(capacity, graph);
+    }
+
+    /// Returns current capacity of the pool.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns amount of decals that were spawned by the pool and still alive.
+    pub fn len(&self) -> usize {
+        self.decals.len()
+    }
+
+    /// Returns `true` if there's no decals spawned by the pool.
+    pub fn is_empty(&self) -> bool {
+        self.decals.is_empty()
+    }
+
+    /// Returns an iterator over the handles of the decals, starting from the oldest one.
+    pub fn iter(&self) -> impl Iterator<Item = &Handle<Node>> {
+        self.decals.iter()
+    }
+
+    fn shrink_to(&mut self, len: usize, graph: &mut Graph) {
+        // Decals could be removed from the graph by other means (for example, when their lifetime has
+        // ended), so forget about them first.
+        self.decals
+            .retain(|handle| graph.try_get_of_type::<Decal>(*handle).is_some());
+        while self.decals.len() > len {
+            if let Some(oldest) = self.decals.pop_front() {
+                graph.remove_node(oldest);
+            }
+        }
+    }
+
+    /// Builds a new decal using the given builder and adds it to the graph. If the pool is full,
+    /// the oldest decal is removed from the graph. Returns a handle of the new decal, or
+    /// [`Handle::NONE`] if the capacity of the pool is zero.
+    pub fn spawn(&mut self, builder: DecalBuilder, graph: &mut Graph) -> Handle<Node> {
+        if self.capacity == 0 {
+            return Handle::NONE;
+        }
+        self.shrink_to(self.capacity - 1, graph);
+        let handle = builder.build(graph);
+        self.decals.push_back(handle);
+        handle
+    }
+
+    /// Removes every decal spawned by the pool from the graph.
+    pub fn clear(&mut self, graph: &mut Graph) {
+        for handle in self.decals.drain(..) {
+            if graph.try_get_of_type::<Decal>(handle).is_some() {
+                graph.remove_node(handle);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::{
+        base::BaseBuilder,
+        decal::{DecalBuilder, DecalPool},
+        graph::Graph,
+    };
+    use fyrox_graph::BaseSceneGraph;
+
+    #[test]
+    fn test_decal_pool_recycles_oldest() {
+        let mut graph = Graph::new();
+        let mut pool = DecalPool::new(2);
+        let a = pool.spawn(DecalBuilder::new(BaseBuilder::new()), &mut graph);
+        let b = pool.spawn(DecalBuilder::new(BaseBuilder::new()), &mut graph);
+        let c = pool.spawn(DecalBuilder::new(BaseBuilder::new()), &mut graph);
+        assert_eq!(pool.len(), 2);
+        assert!(!graph.is_valid_handle(a));
+        assert!(graph.is_valid_handle(b));
+        assert!(graph.is_valid_handle(c));
+
+        // Externally removed decals do not occupy the pool.
+        graph.remove_node(b);
+        let d = pool.spawn(DecalBuilder::new(BaseBuilder::new()), &mut graph);
+        assert!(graph.is_valid_handle(c));
+        assert_eq!(pool.iter().copied().collect::<Vec<_>>(), vec![c, d]);
+
+        pool.clear(&mut graph);
+        assert!(pool.is_empty());
+        assert!(!graph.is_valid_handle(c));
+        assert!(!graph.is_valid_handle(d));
+    }
+}
@@ -27,6 +27,7 @@ use crate::{
             UnitComplex, UnitQuaternion, UnitVector2, Vector2, Vector3,
         },
         arrayvec::ArrayVec,
+        color::Color,
         instant,
         log::{Log, MessageKind},
         math::Matrix4Ext,
@@ -41,14 +42,18 @@ use crate::{
     scene::{
         self,
         collider::{self},
-        debug::SceneDrawingContext,
+        debug::{Line, SceneDrawingContext},
         dim2::{
             self, collider::ColliderShape, collider::TileMapShape, joint::JointLocalFrames,
             joint::JointParams, rigidbody::ApplyAction,
         },
         graph::{
             isometric_global_transform,
-            physics::{FeatureId, IntegrationParameters, PhysicsPerformanceStatistics},
+            physics::{
+                interaction_groups_debug_color, sleep_state_debug_color, ContactPoint, FeatureId,
+                IntegrationParameters, PhysicsDebugColoring, PhysicsDebugDrawOptions,
+                PhysicsPerformanceStatistics, PhysicsSnapshotError, PhysicsWorldSnapshot,
+            },
             Graph, NodePool,
         },
         node::{Node, NodeTrait},
@@ -68,8 +73,12 @@ use rapier2d::{
         InteractionGroups, NarrowPhase, Ray, SharedShape,
     },
     parry::query::ShapeCastOptions,
-    pipeline::{DebugRenderPipeline, EventHandler, PhysicsPipeline, QueryPipeline},
+    pipeline::{
+        DebugRenderBackend, DebugRenderMode, DebugRenderObject, DebugRenderPipeline, EventHandler,
+        PhysicsPipeline, QueryPipeline,
+    },
 };
+use serde::{Deserialize, Serialize};
 use std::{
     cell::RefCell,
     cmp::Ordering,
@@ -279,6 +288,45 @@ pub struct IntersectionPair {
     pub has_any_active_contact: bool,
 }
 
+#[derive(Serialize)]
+struct NativeStateRef<'a> {
+    islands: &'a IslandManager,
+    broad_phase: &'a DefaultBroadPhase,
+    narrow_phase: &'a NarrowPhase,
+    bodies: &'a RigidBodySet,
+    colliders: &'a ColliderSet,
+    joints: &'a ImpulseJointSet,
+    joints_map: Vec<(ImpulseJointHandle, Handle<Node>)>,
+    multibody_joints: &'a MultibodyJointSet,
+    multibody_joints_map: Vec<(MultibodyJointHandle, Handle<Node>)>,
+    ccd_solver: &'a CCDSolver,
+}
+
+#[derive(Deserialize)]
+struct NativeState {
+    islands: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    joints: ImpulseJointSet,
+    joints_map: Vec<(ImpulseJointHandle, Handle<Node>)>,
+    multibody_joints: MultibodyJointSet,
+    multibody_joints_map: Vec<(MultibodyJointHandle, Handle<Node>)>,
+    ccd_solver: CCDSolver,
+}
+
+fn sorted_map<K: Hash + Eq + Clone>(map: &BiDirHashMap<K, Handle<Node>>) -> Vec<(K, Handle<Node>)> {
+    let mut pairs = map
+        .forward_map()
+        .iter()
+        .map(|(k, v)| (k.clone(), *v))
+        .collect::<Vec<_>>();
+    // Hash map iteration order is not stable, sorting makes snapshots of equal worlds equal.
+    pairs.sort_by_key(|(_, node)| *node);
+    pairs
+}
+
 pub(super) struct Container<S, A>
 where
     A: Hash + Eq + Clone,
@@ -515,6 +563,106 @@ fn calculate_local_frames(
     )
 }
 
+// Overrides colors of debug lines according to the coloring mode.
+struct DebugRenderContext<'a> {
+    context: &'a mut SceneDrawingContext,
+    bodies: &'a RigidBodySet,
+    coloring: PhysicsDebugColoring,
+}
+
+impl DebugRenderContext<'_> {
+    fn body_color(&self, body: Option<&RigidBody>, color: [f32; 4]) -> [f32; 4] {
+        match body {
+            Some(body) => sleep_state_debug_color(body.is_dynamic(), body.is_sleeping()),
+            None => color,
+        }
+    }
+}
+
+impl DebugRenderBackend for DebugRenderContext<'_> {
+    fn draw_line(
+        &mut self,
+        object: DebugRenderObject,
+        a: rapier2d::math::Point<rapier2d::math::Real>,
+        b: rapier2d::math::Point<rapier2d::math::Real>,
+        color: [f32; 4],
+    ) {
+        let color = match (self.coloring, &object) {
+            (PhysicsDebugColoring::SleepState, DebugRenderObject::Collider(_, collider)) => {
+                self.body_color(collider.parent().and_then(|h| self.bodies.get(h)), color)
+            }
+            (PhysicsDebugColoring::SleepState, DebugRenderObject::RigidBody(_, body)) => {
+                self.body_color(Some(body), color)
+            }
+            (PhysicsDebugColoring::InteractionGroups, DebugRenderObject::Collider(_, collider)) => {
+                interaction_groups_debug_color(collider.collision_groups().memberships.bits())
+            }
+            _ => color,
+        };
+        self.context.draw_line(object, a, b, color)
+    }
+}
+
+fn draw_joint_limits(
+    context: &mut SceneDrawingContext,
+    bodies: &RigidBodySet,
+    joints: &ImpulseJointSet,
+) {
+    const RADIUS: f32 = 0.25;
+    const SEGMENTS: usize = 16;
+
+    let color = Color::opaque(255, 165, 0);
+    let to_3d = |v: Vector2<f32>| Vector3::new(v.x, v.y, 0.0);
+    for (_, joint) in joints.iter() {
+        let Some(body1) = bodies.get(joint.body1) else {
+            continue;
+        };
+        let frame = body1.position() * joint.data.local_frame1;
+        let origin = frame.translation.vector;
+        let axes = [
+            frame.rotation * Vector2::x_axis(),
+            frame.rotation * Vector2::y_axis(),
+        ];
+
+        for (i, axis) in [JointAxis::LinX, JointAxis::LinY].into_iter().enumerate() {
+            if let Some(limits) = joint.data.limits(axis) {
+                let direction = axes[i].into_inner();
+                context.add_line(Line {
+                    begin: to_3d(origin + direction.scale(limits.min)),
+                    end: to_3d(origin + direction.scale(limits.max)),
+                    color,
+                });
+            }
+        }
+
+        if let Some(limits) = joint.data.limits(JointAxis::AngX) {
+            let reference = axes[0].into_inner().scale(RADIUS);
+            let point_at = |angle: f32| to_3d(origin + UnitComplex::new(angle) * reference);
+            let mut prev = point_at(limits.min);
+            context.add_line(Line {
+                begin: to_3d(origin),
+                end: prev,
+                color,
+            });
+            for k in 1..=SEGMENTS {
+                let angle = limits.min + (limits.max - limits.min) * k as f32 / SEGMENTS as f32;
+                let next = point_at(angle);
+                context.add_line(Line {
+                    begin: prev,
+                    end: next,
+                    color,
+                });
+                prev = next;
+            }
+            context.add_line(Line {
+                begin: prev,
+                end: to_3d(origin),
+                color,
+            });
+        }
+    }
+}
+
 fn u32_to_group(v: u32) -> rapier2d::geometry::Group {
     rapier2d::geometry::Group::from_bits(v).unwrap_or_else(rapier2d::geometry::Group::all)
 }
@@ -711,6 +859,114 @@ impl PhysicsWorld {
         );
     }
 
+    /// Draws physics world using the given set of options. Unlike [`Self::draw`], it allows you to
+    /// see contact points with their normals and impulses, joint limits and to color colliders by
+    /// sleep state or interaction groups.
+    pub fn draw_with_options(
+        &self,
+        context: &mut SceneDrawingContext,
+        options: &PhysicsDebugDrawOptions,
+    ) {
+        let mut pipeline = self.debug_render_pipeline.lock();
+        let prev_mode = pipeline.mode;
+        let mut mode = DebugRenderMode::empty();
+        mode.set(DebugRenderMode::COLLIDER_SHAPES, options.shapes);
+        mode.set(DebugRenderMode::RIGID_BODY_AXES, options.rigid_body_axes);
+        mode.set(DebugRenderMode::COLLIDER_AABBS, options.aabbs);
+        mode.set(DebugRenderMode::JOINTS, options.joints);
+        pipeline.mode = mode;
+        pipeline.render(
+            &mut DebugRenderContext {
+                context: &mut *context,
+                bodies: &self.bodies,
+                coloring: options.coloring,
+            },
+            &self.bodies,
+            &self.colliders,
+            &self.joints.set,
+            &self.multibody_joints.set,
+            &self.narrow_phase,
+        );
+        pipeline.mode = prev_mode;
+
+        if options.joint_limits {
+            draw_joint_limits(context, &self.bodies, &self.joints.set);
+        }
+
+        options.draw_contact_points(context, &self.contact_points());
+    }
+
+    /// Returns all contact points generated in this frame in world coordinates (Z is always zero).
+    pub fn contact_points(&self) -> Vec<ContactPoint> {
+        let mut points = Vec::new();
+        for pair in self.narrow_phase.contact_pairs() {
+            let (Some(collider1), Some(collider2)) = (
+                self.colliders.get(pair.collider1),
+                self.colliders.get(pair.collider2),
+            ) else {
+                continue;
+            };
+            for manifold in pair.manifolds.iter() {
+                for point in manifold.points.iter() {
+                    let position = collider1.position() * point.local_p1;
+                    points.push(ContactPoint {
+                        collider1: Handle::decode_from_u128(collider1.user_data),
+                        collider2: Handle::decode_from_u128(collider2.user_data),
+                        position: Vector3::new(position.x, position.y, 0.0),
+                        normal: Vector3::new(manifold.data.normal.x, manifold.data.normal.y, 0.0),
+                        distance: point.dist,
+                        impulse: point.data.impulse,
+                    });
+                }
+            }
+        }
+        points
+    }
+
+    /// Takes a snapshot of the entire simulation state of the world. See [`PhysicsWorldSnapshot`] docs
+    /// for more info.
+    pub fn snapshot(&self) -> Result<PhysicsWorldSnapshot, PhysicsSnapshotError> {
+        let state = NativeStateRef {
+            islands: &self.islands,
+            broad_phase: &self.broad_phase,
+            narrow_phase: &self.narrow_phase,
+            bodies: &self.bodies,
+            colliders: &self.colliders,
+            joints: &self.joints.set,
+            joints_map: sorted_map(&self.joints.map),
+            multibody_joints: &self.multibody_joints.set,
+            multibody_joints_map: sorted_map(&self.multibody_joints.map),
+            ccd_solver: &self.ccd_solver,
+        };
+        Ok(PhysicsWorldSnapshot::from_bytes(bincode::serialize(
+            &state,
+        )?))
+    }
+
+    /// Restores the simulation state of the world from the given snapshot. The snapshot must be restored
+    /// into the world of the same scene with the same set of physical entities. Scene nodes are not touched
+    /// by this method, use [`Graph::restore_physics_snapshot`] to restore both the world and the scene nodes.
+    pub fn restore(&mut self, snapshot: &PhysicsWorldSnapshot) -> Result<(), PhysicsSnapshotError> {
+        let state: NativeState = bincode::deserialize(snapshot.as_bytes())?;
+        self.islands = state.islands;
+        self.broad_phase = state.broad_phase;
+        self.narrow_phase = state.narrow_phase;
+        self.bodies = state.bodies;
+        self.colliders = state.colliders;
+        self.joints = Container {
+            set: state.joints,
+            map: state.joints_map.into_iter().collect(),
+        };
+        self.multibody_joints = Container {
+            set: state.multibody_joints,
+            map: state.multibody_joints_map.into_iter().collect(),
+        };
+        self.ccd_solver = state.ccd_solver;
+        // Query pipeline is not a part of the simulation state and will be rebuilt on demand.
+        *self.query.borrow_mut() = Default::default();
+        Ok(())
+    }
+
     /// Casts a ray with given options.
     pub fn cast_ray<S: QueryResultsStorage>(&self, opts: RayCastOptions, query_buffer: &mut S) {
         let time = instant::Instant::now();
@@ -1350,3 +1606,84 @@ impl Debug for PhysicsWorld {
         write!(f, "PhysicsWorld")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::core::algebra::{Vector2, Vector3};
+    use crate::scene::{
+        base::BaseBuilder,
+        debug::SceneDrawingContext,
+        dim2::{
+            collider::{ColliderBuilder, ColliderShape},
+            rigidbody::RigidBodyBuilder,
+        },
+        graph::{
+            physics::{PhysicsDebugColoring, PhysicsDebugDrawOptions},
+            Graph,
+        },
+        rigidbody::RigidBodyType,
+        transform::TransformBuilder,
+    };
+
+    #[test]
+    fn test_contact_points_and_debug_drawing() {
+        let mut graph = Graph::new();
+
+        let ground_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(5.0, 0.5))
+            .build(&mut graph);
+        RigidBodyBuilder::new(BaseBuilder::new().with_children(&[ground_collider]))
+            .with_body_type(RigidBodyType::Static)
+            .build(&mut graph);
+
+        let ball_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.5))
+            .build(&mut graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                        .build(),
+                )
+                .with_children(&[ball_collider]),
+        )
+        .with_body_type(RigidBodyType::Dynamic)
+        .build(&mut graph);
+
+        for _ in 0..10 {
+            graph.update(Vector2::new(800.0, 600.0), 1.0 / 60.0, Default::default());
+        }
+
+        let points = graph.physics2d.contact_points();
+        assert!(!points.is_empty());
+        for point in points.iter() {
+            assert!(point.collider1 == ball_collider || point.collider1 == ground_collider);
+            assert!(point.normal.y.abs() > 0.9);
+            assert_eq!(point.position.z, 0.0);
+        }
+
+        let mut options = PhysicsDebugDrawOptions {
+            shapes: false,
+            rigid_body_axes: false,
+            joints: false,
+            ..Default::default()
+        };
+        let mut context = SceneDrawingContext::default();
+        graph.physics2d.draw_with_options(&mut context, &options);
+        // A cross and a normal for every point at least.
+        assert!(context.lines.len() >= points.len() * 4);
+
+        options.contacts = false;
+        options.contact_normals = false;
+        options.contact_impulses = false;
+        context.clear_lines();
+        graph.physics2d.draw_with_options(&mut context, &options);
+        assert!(context.lines.is_empty());
+
+        options.shapes = true;
+        options.coloring = PhysicsDebugColoring::SleepState;
+        graph.physics2d.draw_with_options(&mut context, &options);
+        assert!(!context.lines.is_empty());
+    }
+}
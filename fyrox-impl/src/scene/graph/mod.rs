@@ -48,10 +48,12 @@ use crate::{
     core::{
         algebra::{Matrix4, Rotation3, UnitQuaternion, Vector2, Vector3},
         instant,
-        log::{Log, MessageKind},
+        log::{category, Log, MessageKind},
         math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
         pool::{ErasedHandle, Handle, MultiBorrowContext, Pool, Ticket},
+        profile_scope,
         reflect::prelude::*,
+        variable::VariableFlags,
         visitor::{Visit, VisitResult, Visitor},
     },
     graph::{AbstractSceneGraph, AbstractSceneNode, BaseSceneGraph, NodeHandleMap, SceneGraph},
@@ -60,15 +62,24 @@ use crate::{
     scene::{
         base::{NodeMessage, NodeScriptMessage, SceneNodeId},
         camera::Camera,
+        collider::Collider,
         dim2::{self},
         graph::{
             event::{GraphEvent, GraphEventBroadcaster},
-            physics::{PhysicsPerformanceStatistics, PhysicsWorld},
+            physics::{
+                PhysicsPerformanceStatistics, PhysicsSnapshot, PhysicsSnapshotError, PhysicsWorld,
+                RigidBodyNodeState,
+            },
+            query::{GraphQuery, Query, QueryCache, QueryError},
+            spatial::{SpatialIndex, SpatialQuery},
+            tags::TagIndex,
         },
+        layer::LayerInteractions,
         mesh::Mesh,
         navmesh,
         node::{container::NodeContainer, Node, NodeTrait, SyncContext, UpdateContext},
         pivot::Pivot,
+        rigidbody,
         sound::context::SoundContext,
         transform::TransformBuilder,
     },
@@ -88,6 +99,9 @@ use std::{
 
 pub mod event;
 pub mod physics;
+pub mod query;
+pub mod spatial;
+mod tags;
 
 /// Graph performance statistics. Allows you to find out "hot" parts of the scene graph, which
 /// parts takes the most time to update.
@@ -109,6 +123,9 @@ pub struct GraphPerformanceStatistics {
 
     /// A time which was required to render sounds.
     pub sound_update_time: Duration,
+
+    /// A time which was required to synchronize the spatial index with the state of the nodes.
+    pub spatial_index_time: Duration,
 }
 
 impl GraphPerformanceStatistics {
@@ -119,6 +136,7 @@ impl GraphPerformanceStatistics {
             + self.physics.total()
             + self.physics2d.total()
             + self.sound_update_time
+            + self.spatial_index_time
     }
 }
 
@@ -168,6 +186,18 @@ pub struct Graph {
     pub(crate) message_receiver: Receiver<NodeMessage>,
 
     instance_id_map: FxHashMap<SceneNodeId, Handle<Node>>,
+
+    #[reflect(hidden)]
+    spatial_index: SpatialIndex,
+
+    #[reflect(hidden)]
+    tag_index: TagIndex,
+
+    #[reflect(hidden)]
+    layer_interactions: Option<LayerInteractions>,
+
+    #[reflect(hidden)]
+    query_cache: QueryCache,
 }
 
 impl Default for Graph {
@@ -190,6 +220,10 @@ impl Default for Graph {
             lightmap: None,
             instance_id_map: Default::default(),
             message_receiver,
+            spatial_index: Default::default(),
+            tag_index: Default::default(),
+            layer_interactions: None,
+            query_cache: Default::default(),
         }
     }
 }
@@ -321,6 +355,10 @@ impl Graph {
             lightmap: None,
             instance_id_map,
             message_receiver,
+            spatial_index: Default::default(),
+            tag_index: Default::default(),
+            layer_interactions: None,
+            query_cache: Default::default(),
         }
     }
 
@@ -969,6 +1007,7 @@ impl Graph {
 
         let mut visited_flags = vec![Flags::NONE; self.pool.get_capacity() as usize];
         let mut roots = FxHashMap::default();
+        let mut moved = Vec::new();
 
         while let Ok(message) = self.message_receiver.try_recv() {
             if let NodeMessageKind::TransformChanged = message.kind {
@@ -988,6 +1027,19 @@ impl Graph {
                 NodeMessageKind::TransformChanged => Flags::TRANSFORM_CHANGED,
                 NodeMessageKind::VisibilityChanged => Flags::VISIBILITY_CHANGED,
                 NodeMessageKind::EnabledFlagChanged => Flags::ENABLED_FLAG_CHANGED,
+                // Tags and layers are not hierarchical, so there's nothing to propagate.
+                NodeMessageKind::TagsChanged => {
+                    self.reindex_node(message.node);
+                    continue;
+                }
+                NodeMessageKind::ComponentsChanged => {
+                    self.query_cache.mark_dirty(message.node);
+                    continue;
+                }
+                NodeMessageKind::BoundsChanged => {
+                    self.spatial_index.mark_dirty(message.node);
+                    continue;
+                }
             };
 
             let visit_flags = &mut visited_flags[message.node.index() as usize];
@@ -1020,6 +1072,11 @@ impl Graph {
             traverse_recursive(self, message.node, &mut |h| {
                 visited_flags[h.index() as usize].insert(message_flag);
 
+                // Global transform of every descendant changes, so their bounds must be updated too.
+                if message_flag == Flags::TRANSFORM_CHANGED {
+                    moved.push(h);
+                }
+
                 // Remove a descendant from the list of potential roots.
                 if h != message.node {
                     if let Some(flags) = roots.get_mut(&h) {
@@ -1033,6 +1090,10 @@ impl Graph {
             })
         }
 
+        for handle in moved {
+            self.spatial_index.mark_dirty(handle);
+        }
+
         for (node, flags) in roots {
             if flags.contains(Flags::TRANSFORM_CHANGED) {
                 Self::update_global_transform_recursively(
@@ -1119,6 +1180,8 @@ impl Graph {
             return;
         }
 
+        profile_scope!(category: category::SCENE, "Graph::update");
+
         let last_time = instant::Instant::now();
         self.process_node_messages(Some(&switches));
         self.performance_statistics.hierarchical_properties_time =
@@ -1129,12 +1192,14 @@ impl Graph {
         self.performance_statistics.sync_time = instant::Instant::now() - last_time;
 
         if switches.physics {
+            profile_scope!(category: category::PHYSICS, "Physics::update");
             self.physics.performance_statistics.reset();
             self.physics.update(dt);
             self.performance_statistics.physics = self.physics.performance_statistics.clone();
         }
 
         if switches.physics2d {
+            profile_scope!(category: category::PHYSICS, "Physics2D::update");
             self.physics2d.performance_statistics.reset();
             self.physics2d.update(dt);
             self.performance_statistics.physics2d = self.physics2d.performance_statistics.clone();
@@ -1143,22 +1208,243 @@ impl Graph {
         self.performance_statistics.sound_update_time =
             self.sound_context.state().full_render_duration();
 
-        if let Some(overrides) = switches.node_overrides.as_ref() {
-            for handle in overrides {
-                self.update_node(*handle, frame_size, dt, switches.delete_dead_nodes);
+        {
+            profile_scope!(category: category::SCENE, "Graph::update_nodes");
+
+            if let Some(overrides) = switches.node_overrides.as_ref() {
+                for handle in overrides {
+                    self.update_node(*handle, frame_size, dt, switches.delete_dead_nodes);
+                }
+            } else {
+                for i in 0..self.pool.get_capacity() {
+                    self.update_node(
+                        self.pool.handle_from_index(i),
+                        frame_size,
+                        dt,
+                        switches.delete_dead_nodes,
+                    );
+                }
+            }
+        }
+
+        let last_time = instant::Instant::now();
+        self.sync_spatial_index();
+        self.performance_statistics.spatial_index_time = instant::Instant::now() - last_time;
+    }
+
+    fn sync_spatial_index(&mut self) {
+        profile_scope!(category: category::SCENE, "Graph::sync_spatial_index");
+        self.spatial_index.sync(&self.pool, self.root);
+    }
+
+    /// Returns the spatial index of the graph. See [`SpatialIndex`] docs for more info.
+    #[inline]
+    pub fn spatial_index(&self) -> &SpatialIndex {
+        &self.spatial_index
+    }
+
+    /// Synchronizes the spatial index with the actual state of the nodes. The index is synchronized
+    /// automatically at the end of [`Self::update`], use this method only if you need to query nodes
+    /// that were added or moved after the update (for example - by scripts).
+    pub fn update_spatial_index(&mut self) {
+        self.process_node_messages(None);
+        self.sync_spatial_index();
+    }
+
+    /// Forces the spatial index to update the entry of the node on next synchronization. The index
+    /// tracks movement of the nodes automatically, use this method only if bounds of the node were
+    /// changed without moving it and the node did not report it via [`crate::scene::base::Base::invalidate_bounds`].
+    pub fn invalidate_spatial_bounds(&mut self, handle: Handle<Node>) {
+        self.spatial_index.mark_dirty(handle);
+    }
+
+    /// Creates a new spatial query, that allows you to quickly find nodes in a region of space. See
+    /// [`SpatialQuery`] docs for more info and examples.
+    #[inline]
+    pub fn spatial_query(&self) -> SpatialQuery<'_> {
+        SpatialQuery::new(self)
+    }
+
+    // Updates the tag and layer index entry of the node and applies layer interactions to it.
+    fn reindex_node(&mut self, handle: Handle<Node>) {
+        if let Some(node) = self.pool.try_borrow_mut(handle) {
+            self.tag_index.insert(handle, node);
+            if let Some(interactions) = self.layer_interactions.as_ref() {
+                Self::apply_layer_interactions(interactions, node);
             }
         } else {
-            for i in 0..self.pool.get_capacity() {
-                self.update_node(
-                    self.pool.handle_from_index(i),
-                    frame_size,
-                    dt,
-                    switches.delete_dead_nodes,
-                );
+            self.tag_index.remove(handle);
+        }
+    }
+
+    fn apply_layer_interactions(interactions: &LayerInteractions, node: &mut Node) {
+        let groups = interactions.interaction_groups(node.layers());
+        if let Some(collider) = node.cast_mut::<Collider>() {
+            if collider.collision_groups() != groups {
+                collider.set_collision_groups(groups);
+            }
+        } else if let Some(collider) = node.cast_mut::<dim2::collider::Collider>() {
+            if collider.collision_groups() != groups {
+                collider.set_collision_groups(groups);
             }
         }
     }
 
+    /// Returns an iterator over the nodes with the given tag. This method uses tag index of the
+    /// graph and does not iterate over the entire graph. Both the main tag of a node (see
+    /// [`crate::scene::base::Base::set_tag`]) and the set of tags (see [`crate::scene::base::Base::set_tags`])
+    /// are indexed.
+    ///
+    /// The index is updated immediately when a node is added to the graph, changes of tags of
+    /// existing nodes are indexed on the next [`Self::update`] call. The order of the nodes is
+    /// unspecified.
+    pub fn nodes_with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = Handle<Node>> + 'a {
+        self.tag_index
+            .tagged(tag)
+            .filter(move |handle| self.try_get(*handle).is_some_and(|node| node.has_tag(tag)))
+    }
+
+    /// Returns an iterator over the nodes in the given layer. This method uses layer index of the
+    /// graph and does not iterate over the entire graph. See [`Self::nodes_with_tag`] docs for more
+    /// info about index update rules.
+    pub fn nodes_in_layer(&self, layer: usize) -> impl Iterator<Item = Handle<Node>> + '_ {
+        self.tag_index.in_layer(layer).filter(move |handle| {
+            self.try_get(*handle)
+                .is_some_and(|node| node.is_in_layer(layer))
+        })
+    }
+
+    /// Sets a new layer interaction matrix. When the matrix is set, collision groups of every 3D
+    /// and 2D collider in the graph are calculated from the layers of the collider (see
+    /// [`LayerInteractions::interaction_groups`]) and kept in sync with them, overwriting manually
+    /// set collision groups. Pass `None` to stop the synchronization, in this case collision groups
+    /// of the colliders remain unchanged. The matrix is saved together with the graph.
+    pub fn set_layer_interactions(&mut self, interactions: Option<LayerInteractions>) {
+        self.layer_interactions = interactions;
+        if let Some(interactions) = self.layer_interactions.as_ref() {
+            for node in self.pool.iter_mut() {
+                Self::apply_layer_interactions(interactions, node);
+            }
+        }
+    }
+
+    /// Returns current layer interaction matrix. See [`Self::set_layer_interactions`] docs for
+    /// more info.
+    pub fn layer_interactions(&self) -> Option<&LayerInteractions> {
+        self.layer_interactions.as_ref()
+    }
+
+    /// Creates a new entity-component style query over the nodes of the graph and their scripts.
+    /// For example, `graph.query::<(&Transform, &mut MyScript)>()` matches every node, that has
+    /// a script of `MyScript` type. See [`GraphQuery`] docs for more info and examples.
+    ///
+    /// Matching nodes are cached and the cache is updated incrementally - only the nodes that were
+    /// added, removed or had their scripts changed since the last call are re-checked. The first
+    /// call with a particular set of components iterates over the entire graph.
+    ///
+    /// Returns [`QueryError::ConflictingAccess`] if the terms of the query can never be borrowed
+    /// together. Nodes, that provide conflicting components, are skipped by the query (see
+    /// "Access rules" section of [`GraphQuery`] docs).
+    pub fn query<Q: Query>(&mut self) -> Result<GraphQuery<'_, Q>, QueryError> {
+        self.process_node_messages(None);
+        let terms = Q::terms();
+        query::validate_terms(&terms)?;
+        let key = query::query_key(&terms);
+        self.query_cache.prepare(&key, &self.pool);
+        Ok(GraphQuery::new(
+            &mut self.pool,
+            &self.query_cache,
+            &self.script_message_sender,
+            terms,
+            key,
+        ))
+    }
+
+    /// Takes a snapshot of the physics state of the graph. The snapshot includes the full state of both
+    /// 3D and 2D physics worlds as well as the state of every rigid body scene node. Restoring the snapshot
+    /// using [`Self::restore_physics_snapshot`] puts the graph into the exact same physics state, which is
+    /// the basis for rollback networking and replays.
+    ///
+    /// # Determinism
+    ///
+    /// Physics simulation is deterministic on the same platform as long as every step is performed with the
+    /// same time step. This means that [`physics::IntegrationParameters::dt`] (and its 2D counterpart) must
+    /// be set to a fixed value, otherwise the time step will be taken from the frame time. Enable
+    /// `enhanced-determinism` feature of the engine to get bit-identical results across different platforms.
+    pub fn physics_snapshot(&self) -> Result<PhysicsSnapshot, PhysicsSnapshotError> {
+        let mut bodies = Vec::new();
+        for (handle, node) in self.pool.pair_iter() {
+            let (lin_vel, ang_vel, sleeping) =
+                if let Some(body) = node.component_ref::<rigidbody::RigidBody>() {
+                    (*body.lin_vel, *body.ang_vel, body.sleeping)
+                } else if let Some(body) = node.component_ref::<dim2::rigidbody::RigidBody>() {
+                    (
+                        Vector3::new(body.lin_vel.x, body.lin_vel.y, 0.0),
+                        Vector3::new(0.0, 0.0, *body.ang_vel),
+                        body.sleeping,
+                    )
+                } else {
+                    continue;
+                };
+
+            bodies.push(RigidBodyNodeState {
+                handle,
+                position: **node.local_transform().position(),
+                rotation: **node.local_transform().rotation(),
+                global_transform: node.global_transform(),
+                lin_vel,
+                ang_vel,
+                sleeping,
+            });
+        }
+
+        Ok(PhysicsSnapshot {
+            physics: self.physics.snapshot()?,
+            physics2d: self.physics2d.snapshot()?,
+            bodies,
+        })
+    }
+
+    /// Restores the physics state of the graph from the given snapshot. The snapshot must be taken from
+    /// the same graph (or its exact copy) with the same set of physical entities (rigid bodies, colliders,
+    /// joints). See [`Self::physics_snapshot`] for more info.
+    pub fn restore_physics_snapshot(
+        &mut self,
+        snapshot: &PhysicsSnapshot,
+    ) -> Result<(), PhysicsSnapshotError> {
+        self.physics.restore(&snapshot.physics)?;
+        self.physics2d.restore(&snapshot.physics2d)?;
+
+        for state in snapshot.bodies.iter() {
+            let Some(node) = self.pool.try_borrow_mut(state.handle) else {
+                continue;
+            };
+
+            // Restore the cached global transform as well, so the following update of the hierarchy
+            // will treat the node exactly as it was treated when the snapshot was taken.
+            node.local_transform_mut()
+                .set_position(state.position)
+                .set_rotation(state.rotation);
+            node.global_transform.set(state.global_transform);
+
+            if let Some(body) = node.component_mut::<rigidbody::RigidBody>() {
+                body.lin_vel
+                    .set_value_with_flags(state.lin_vel, VariableFlags::MODIFIED);
+                body.ang_vel
+                    .set_value_with_flags(state.ang_vel, VariableFlags::MODIFIED);
+                body.sleeping = state.sleeping;
+            } else if let Some(body) = node.component_mut::<dim2::rigidbody::RigidBody>() {
+                body.lin_vel
+                    .set_value_with_flags(state.lin_vel.xy(), VariableFlags::MODIFIED);
+                body.ang_vel
+                    .set_value_with_flags(state.ang_vel.z, VariableFlags::MODIFIED);
+                body.sleeping = state.sleeping;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Returns capacity of internal pool. Can be used to iterate over all **potentially**
     /// available indices and try to convert them to handles.
     ///
@@ -1240,6 +1526,9 @@ impl Graph {
     pub(crate) fn take_reserve_internal(&mut self, handle: Handle<Node>) -> (Ticket<Node>, Node) {
         let (ticket, mut node) = self.pool.take_reserve(handle);
         self.instance_id_map.remove(&node.instance_id);
+        self.tag_index.remove(handle);
+        self.query_cache.mark_dirty(handle);
+        self.spatial_index.mark_dirty(handle);
         node.on_removed_from_graph(self);
         (ticket, node)
     }
@@ -1256,6 +1545,9 @@ impl Graph {
         let instance_id = node.instance_id;
         let handle = self.pool.put_back(ticket, node);
         self.instance_id_map.insert(instance_id, handle);
+        self.reindex_node(handle);
+        self.query_cache.mark_dirty(handle);
+        self.spatial_index.mark_dirty(handle);
         handle
     }
 
@@ -1296,7 +1588,10 @@ impl Graph {
     #[inline]
     pub fn put_sub_graph_back(&mut self, sub_graph: SubGraph) -> Handle<Node> {
         for (ticket, node) in sub_graph.descendants {
-            self.pool.put_back(ticket, node);
+            let handle = self.pool.put_back(ticket, node);
+            self.reindex_node(handle);
+            self.query_cache.mark_dirty(handle);
+            self.spatial_index.mark_dirty(handle);
         }
 
         let (ticket, node) = sub_graph.root;
@@ -1342,6 +1637,7 @@ impl Graph {
             sound_context: self.sound_context.deep_clone(),
             physics: self.physics.clone(),
             physics2d: self.physics2d.clone(),
+            layer_interactions: self.layer_interactions.clone(),
             ..Default::default()
         };
 
@@ -1635,6 +1931,9 @@ impl Visit for Graph {
         self.physics.visit("PhysicsWorld", &mut region)?;
         self.physics2d.visit("PhysicsWorld2D", &mut region)?;
         let _ = self.lightmap.visit("Lightmap", &mut region);
+        let _ = self
+            .layer_interactions
+            .visit("LayerInteractions", &mut region);
 
         Ok(())
     }
@@ -1710,6 +2009,10 @@ impl BaseSceneGraph for Graph {
 
         self.instance_id_map.insert(node.instance_id, handle);
 
+        self.reindex_node(handle);
+        self.query_cache.mark_dirty(handle);
+        self.spatial_index.mark_dirty(handle);
+
         handle
     }
 
@@ -1727,6 +2030,9 @@ impl BaseSceneGraph for Graph {
             // Remove associated entities.
             let mut node = self.pool.free(handle);
             self.instance_id_map.remove(&node.instance_id);
+            self.tag_index.remove(handle);
+            self.query_cache.mark_dirty(handle);
+            self.spatial_index.mark_dirty(handle);
             node.on_removed_from_graph(self);
 
             self.event_broadcaster
@@ -1807,6 +2113,7 @@ mod test {
         core::{
             algebra::{Matrix4, Vector3},
             futures::executor::block_on,
+            math::ray::Ray,
             pool::Handle,
             reflect::prelude::*,
             type_traits::prelude::*,
@@ -1816,22 +2123,24 @@ mod test {
         graph::{BaseSceneGraph, SceneGraph},
         resource::model::{Model, ModelResourceExtension},
         scene::{
-            base::BaseBuilder,
-            graph::Graph,
+            base::{Base, BaseBuilder},
+            collider::{Collider, ColliderBuilder},
+            graph::{physics::PhysicsSnapshot, Graph},
+            layer::{LayerInteractions, LayerMask},
             mesh::{
                 surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
-                MeshBuilder,
+                Mesh, MeshBuilder,
             },
             node::Node,
             pivot::{Pivot, PivotBuilder},
-            transform::TransformBuilder,
+            transform::{Transform, TransformBuilder},
             Scene, SceneLoader,
         },
         script::ScriptTrait,
     };
     use fyrox_core::algebra::Vector2;
     use fyrox_resource::untyped::ResourceKind;
-    use std::{fs, path::Path, sync::Arc};
+    use std::{collections::HashSet, fs, path::Path, sync::Arc};
 
     #[derive(Clone, Debug, PartialEq, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
     #[type_uuid(id = "722feb80-a10b-4ee0-8cef-5d1473df8457")]
@@ -2241,4 +2550,382 @@ mod test {
         assert!(!graph[c].is_globally_enabled());
         assert!(!graph[d].is_globally_enabled());
     }
+
+    fn build_physics_scene(graph: &mut Graph) -> Vec<Handle<Node>> {
+        use crate::scene::{collider, dim2, rigidbody};
+
+        let dt = Some(1.0 / 60.0);
+        graph.physics.integration_parameters.dt = dt;
+        graph.physics2d.integration_parameters.dt = dt;
+
+        let ground = collider::ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(collider::ColliderShape::cuboid(10.0, 0.5, 10.0))
+            .build(graph);
+        rigidbody::RigidBodyBuilder::new(BaseBuilder::new().with_children(&[ground]))
+            .with_body_type(rigidbody::RigidBodyType::Static)
+            .build(graph);
+
+        let ground2d = dim2::collider::ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(dim2::collider::ColliderShape::cuboid(10.0, 0.5))
+            .build(graph);
+        dim2::rigidbody::RigidBodyBuilder::new(BaseBuilder::new().with_children(&[ground2d]))
+            .with_body_type(rigidbody::RigidBodyType::Static)
+            .build(graph);
+
+        let mut bodies = Vec::new();
+        for i in 0..6 {
+            // Slightly shifted stack, so the bodies will topple and collide with each other.
+            let position = Vector3::new(i as f32 * 0.15, 1.0 + i as f32 * 1.05, i as f32 * 0.1);
+            let transform = || {
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build()
+            };
+
+            let shape = collider::ColliderBuilder::new(BaseBuilder::new())
+                .with_shape(collider::ColliderShape::cuboid(0.5, 0.5, 0.5))
+                .build(graph);
+            bodies.push(
+                rigidbody::RigidBodyBuilder::new(
+                    BaseBuilder::new()
+                        .with_local_transform(transform())
+                        .with_children(&[shape]),
+                )
+                .with_ang_vel(Vector3::new(0.0, 0.3 * i as f32, 0.0))
+                .build(graph),
+            );
+
+            let shape2d = dim2::collider::ColliderBuilder::new(BaseBuilder::new())
+                .with_shape(dim2::collider::ColliderShape::cuboid(0.5, 0.5))
+                .build(graph);
+            bodies.push(
+                dim2::rigidbody::RigidBodyBuilder::new(
+                    BaseBuilder::new()
+                        .with_local_transform(transform())
+                        .with_children(&[shape2d]),
+                )
+                .build(graph),
+            );
+        }
+        bodies
+    }
+
+    fn simulate_physics(
+        graph: &mut Graph,
+        steps: usize,
+        bodies: &[Handle<Node>],
+    ) -> Vec<Matrix4<f32>> {
+        for i in 0..steps {
+            // Frame time jitters, but the physics must use the fixed time step.
+            let frame_time = 1.0 / 60.0 + (i % 3) as f32 * 0.001;
+            graph.update(Vector2::new(1.0, 1.0), frame_time, Default::default());
+        }
+        bodies
+            .iter()
+            .map(|b| graph[*b].global_transform())
+            .collect()
+    }
+
+    #[test]
+    fn test_physics_determinism() {
+        let mut graph_a = Graph::new();
+        let bodies_a = build_physics_scene(&mut graph_a);
+        let mut graph_b = Graph::new();
+        let bodies_b = build_physics_scene(&mut graph_b);
+
+        let transforms_a = simulate_physics(&mut graph_a, 120, &bodies_a);
+        let transforms_b = simulate_physics(&mut graph_b, 120, &bodies_b);
+
+        // Bodies must move, otherwise the test is meaningless.
+        assert_ne!(
+            transforms_a[0],
+            Matrix4::new_translation(&Vector3::new(0.0, 1.0, 0.0))
+        );
+        // Bit-exact comparison.
+        assert_eq!(transforms_a, transforms_b);
+    }
+
+    #[test]
+    fn test_physics_snapshot_restore() {
+        let mut graph = Graph::new();
+        let bodies = build_physics_scene(&mut graph);
+
+        simulate_physics(&mut graph, 30, &bodies);
+        let snapshot = graph.physics_snapshot().unwrap();
+        let expected = simulate_physics(&mut graph, 60, &bodies);
+
+        // Restore from serialized bytes to make sure that the snapshot survives sending over network.
+        let bytes = snapshot.to_bytes().unwrap();
+        let snapshot = PhysicsSnapshot::from_bytes(&bytes).unwrap();
+        graph.restore_physics_snapshot(&snapshot).unwrap();
+        let actual = simulate_physics(&mut graph, 60, &bodies);
+
+        assert_eq!(expected, actual);
+
+        // Restoring twice must lead to the same result as well.
+        graph.restore_physics_snapshot(&snapshot).unwrap();
+        assert_eq!(simulate_physics(&mut graph, 60, &bodies), expected);
+    }
+
+    #[test]
+    fn test_spatial_index() {
+        let mut graph = Graph::new();
+
+        let mut pivots = Vec::new();
+        for i in 0..10 {
+            pivots.push(
+                PivotBuilder::new(
+                    BaseBuilder::new()
+                        .with_tag(if i % 2 == 0 { "Even" } else { "Odd" }.to_string())
+                        .with_local_transform(
+                            TransformBuilder::new()
+                                .with_local_position(Vector3::new(i as f32 * 2.0, 0.0, 0.0))
+                                .build(),
+                        ),
+                )
+                .build(&mut graph),
+            );
+        }
+
+        let mesh = MeshBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 5.0, 0.0))
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::identity()),
+        ))
+        .build()])
+        .build(&mut graph);
+
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        assert_eq!(graph.spatial_index().len(), 11);
+
+        let mut found = graph
+            .spatial_query()
+            .sphere(Vector3::new(4.0, 0.0, 0.0), 2.5);
+        found.sort();
+        assert_eq!(found, vec![pivots[1], pivots[2], pivots[3]]);
+
+        let found = graph
+            .spatial_query()
+            .with_tag("Even")
+            .sphere(Vector3::new(4.0, 0.0, 0.0), 2.5);
+        assert_eq!(found, vec![pivots[2]]);
+
+        // The cube has half-size of 0.5, its bottom is at 4.5.
+        let found = graph
+            .spatial_query()
+            .of_type::<Mesh>()
+            .sphere(Vector3::new(0.0, 3.0, 0.0), 1.6);
+        assert_eq!(found, vec![mesh]);
+
+        let nearest = graph
+            .spatial_query()
+            .of_type::<Pivot>()
+            .k_nearest(Vector3::new(7.0, 1.0, 0.0), 2);
+        assert_eq!(nearest.len(), 2);
+        assert_eq!(nearest[0].1, nearest[1].1);
+        let mut nearest = nearest.into_iter().map(|(h, _)| h).collect::<Vec<_>>();
+        nearest.sort();
+        assert_eq!(nearest, vec![pivots[3], pivots[4]]);
+
+        let hits = graph.spatial_query().ray(&Ray::from_two_points(
+            Vector3::new(0.0, 10.0, 0.0),
+            Vector3::new(0.0, -10.0, 0.0),
+        ));
+        assert_eq!(
+            hits.iter().map(|(h, _)| *h).collect::<Vec<_>>(),
+            vec![mesh, pivots[0]]
+        );
+
+        // Move a node and delete another one, the index must follow the changes.
+        graph[pivots[0]]
+            .local_transform_mut()
+            .set_position(Vector3::new(100.0, 0.0, 0.0));
+        graph.remove_node(pivots[1]);
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        assert_eq!(graph.spatial_index().len(), 10);
+        assert!(!graph.spatial_index().contains(pivots[1]));
+        let mut found = graph
+            .spatial_query()
+            .sphere(Vector3::new(3.0, 0.0, 0.0), 1.5);
+        found.sort();
+        assert_eq!(found, vec![pivots[2]]);
+        assert_eq!(
+            graph
+                .spatial_query()
+                .sphere(Vector3::new(100.0, 0.0, 0.0), 0.1),
+            vec![pivots[0]]
+        );
+
+        // Descendants of a moved node must follow it.
+        graph.link_nodes(pivots[2], pivots[0]);
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        graph[pivots[0]]
+            .local_transform_mut()
+            .set_position(Vector3::new(-100.0, 0.0, 0.0));
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        assert_eq!(
+            graph
+                .spatial_query()
+                .sphere(Vector3::new(-96.0, 0.0, 0.0), 0.1),
+            vec![pivots[2]]
+        );
+    }
+
+    #[test]
+    fn test_tag_and_layer_index() {
+        let mut graph = Graph::new();
+
+        let a = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_tags(vec!["Enemy".to_string(), "Flying".to_string()])
+                .with_layers(LayerMask::from_layer(2)),
+        )
+        .build(&mut graph);
+        let b =
+            PivotBuilder::new(BaseBuilder::new().with_tag("Enemy".to_string())).build(&mut graph);
+        let collider =
+            ColliderBuilder::new(BaseBuilder::new().with_layers(LayerMask::from_layer(1)))
+                .build(&mut graph);
+
+        let tagged = |graph: &Graph, tag: &str| graph.nodes_with_tag(tag).collect::<HashSet<_>>();
+        let in_layer = |graph: &Graph, layer| graph.nodes_in_layer(layer).collect::<HashSet<_>>();
+
+        assert_eq!(tagged(&graph, "Enemy"), HashSet::from([a, b]));
+        assert_eq!(tagged(&graph, "Flying"), HashSet::from([a]));
+        assert_eq!(in_layer(&graph, 2), HashSet::from([a]));
+        assert_eq!(in_layer(&graph, 1), HashSet::from([collider]));
+        assert!(in_layer(&graph, 0).contains(&b));
+        assert!(!in_layer(&graph, 0).contains(&a));
+
+        assert!(graph[a].remove_tag("Flying"));
+        graph[a].set_layers(LayerMask::from_layer(0));
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        assert!(tagged(&graph, "Flying").is_empty());
+        assert!(in_layer(&graph, 2).is_empty());
+        assert!(in_layer(&graph, 0).contains(&a));
+
+        graph.remove_node(b);
+        assert_eq!(tagged(&graph, "Enemy"), HashSet::from([a]));
+
+        let mut interactions = LayerInteractions::new();
+        interactions.set(0, 1, false);
+        graph.set_layer_interactions(Some(interactions.clone()));
+        let groups = graph[collider]
+            .cast::<Collider>()
+            .unwrap()
+            .collision_groups();
+        assert_eq!(
+            groups,
+            interactions.interaction_groups(LayerMask::from_layer(1))
+        );
+        assert_eq!(groups.filter.0 & 1, 0);
+    }
+
+    #[test]
+    fn test_query() {
+        let mut graph = Graph::new();
+
+        let a = PivotBuilder::new(BaseBuilder::new().with_script(MyScript {
+            foo: "A".to_string(),
+            bar: 1.0,
+        }))
+        .build(&mut graph);
+        let b = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        let mut query = graph.query::<(&Transform, &mut MyScript)>().unwrap();
+        assert_eq!(query.handles(), &[a]);
+        query.for_each(|_, (_, script)| script.bar += 1.0);
+        assert_eq!(graph[a].try_get_script::<MyScript>().unwrap().bar, 2.0);
+
+        // The cache must be updated when scripts and nodes are added or removed.
+        graph[b].add_script(MyScript {
+            foo: "B".to_string(),
+            bar: 10.0,
+        });
+        assert_eq!(
+            graph.query::<(&Transform, &mut MyScript)>().unwrap().len(),
+            2
+        );
+        graph.remove_node(a);
+        assert_eq!(
+            graph
+                .query::<(&Transform, &mut MyScript)>()
+                .unwrap()
+                .handles(),
+            &[b]
+        );
+
+        graph
+            .query::<(&mut Transform, &MyScript)>()
+            .unwrap()
+            .par_for_each(|_, (transform, script)| {
+                transform.set_position(Vector3::new(script.bar, 0.0, 0.0));
+            });
+        assert_eq!(
+            **graph[b].local_transform().position(),
+            Vector3::new(10.0, 0.0, 0.0)
+        );
+
+        // Root node and `b`.
+        assert_eq!(graph.query::<(&Pivot,)>().unwrap().len(), 2);
+        assert_eq!(
+            graph
+                .query::<(&MyScript,)>()
+                .unwrap()
+                .with(b, |(script,)| script.bar),
+            Some(10.0)
+        );
+        let root = graph.root();
+        assert_eq!(
+            graph
+                .query::<(&MyScript,)>()
+                .unwrap()
+                .with(root, |(script,)| script.bar),
+            None
+        );
+    }
+
+    #[test]
+    fn test_query_conflicting_access() {
+        let mut graph = Graph::new();
+        PivotBuilder::new(BaseBuilder::new().with_script(MyScript {
+            foo: "A".to_string(),
+            bar: 1.0,
+        }))
+        .build(&mut graph);
+
+        assert!(graph.query::<(&mut Transform, &Base)>().is_err());
+        assert!(graph.query::<(&Transform, &mut Base)>().is_err());
+        assert!(graph.query::<(&mut MyScript, &MyScript)>().is_err());
+        assert!(graph.query::<(&Transform, &Pivot)>().is_ok());
+        assert!(graph.query::<(&mut Transform, &mut MyScript)>().is_ok());
+
+        // Pivot is provided by the node itself, so the node is skipped.
+        assert!(graph
+            .query::<(&mut Transform, &Pivot)>()
+            .unwrap()
+            .is_empty());
+
+        // Conflicting nodes are skipped, the query does not depend on the content of the graph.
+        let mut count = 0;
+        graph
+            .query::<(&mut Transform, &Mesh)>()
+            .unwrap()
+            .for_each(|_, _| count += 1);
+        assert_eq!(count, 0);
+        let mesh = MeshBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let mut query = graph.query::<(&mut Transform, &Mesh)>().unwrap();
+        assert!(query.is_empty());
+        query.for_each(|_, _| count += 1);
+        assert_eq!(count, 0);
+        assert!(query.with(mesh, |_| ()).is_none());
+    }
 }
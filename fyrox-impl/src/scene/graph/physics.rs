@@ -27,6 +27,7 @@ use crate::{
             UnitVector3, VecStorage, Vector2, Vector3,
         },
         arrayvec::ArrayVec,
+        color::Color,
         instant,
         log::{Log, MessageKind},
         math::Matrix4Ext,
@@ -41,7 +42,7 @@ use crate::{
     scene::{
         self,
         collider::{self, ColliderShape, GeometrySource},
-        debug::SceneDrawingContext,
+        debug::{Line, SceneDrawingContext},
         graph::{isometric_global_transform, Graph, NodePool},
         joint::{JointLocalFrames, JointParams},
         mesh::{
@@ -65,9 +66,13 @@ use rapier3d::{
         InteractionGroups, NarrowPhase, Ray, SharedShape,
     },
     parry::{query::ShapeCastOptions, shape::HeightField},
-    pipeline::{DebugRenderPipeline, EventHandler, PhysicsPipeline, QueryPipeline},
+    pipeline::{
+        DebugRenderBackend, DebugRenderMode, DebugRenderObject, DebugRenderPipeline, EventHandler,
+        PhysicsPipeline, QueryPipeline,
+    },
     prelude::{HeightFieldCellStatus, JointAxis},
 };
+use serde::{Deserialize, Serialize};
 use std::{
     cell::{Cell, RefCell},
     cmp::Ordering,
@@ -400,6 +405,131 @@ pub struct IntersectionPair {
     pub has_any_active_contact: bool,
 }
 
+/// An error that may occur when taking or restoring a snapshot of a physics world.
+#[derive(Debug)]
+pub enum PhysicsSnapshotError {
+    /// Native physics state could not be serialized or deserialized.
+    Serialization(bincode::Error),
+}
+
+impl std::fmt::Display for PhysicsSnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Serialization(err) => {
+                write!(f, "Physics snapshot serialization error: {err}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PhysicsSnapshotError {}
+
+impl From<bincode::Error> for PhysicsSnapshotError {
+    fn from(err: bincode::Error) -> Self {
+        Self::Serialization(err)
+    }
+}
+
+/// Serialized state of a physics world, including rigid bodies, colliders, joints, islands, broad phase,
+/// narrow phase (with contacts and their warm-starting impulses) and the CCD solver. Restoring the snapshot
+/// puts the world into the exact same state, so the following simulation steps produce bit-identical
+/// results. It is the basis for rollback networking and replays.
+///
+/// The snapshot stores only the simulation state, settings of the world (gravity, integration parameters,
+/// etc.) are left untouched on restoration.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhysicsWorldSnapshot {
+    data: Vec<u8>,
+}
+
+impl PhysicsWorldSnapshot {
+    /// Creates a snapshot from the raw bytes previously obtained by [`Self::as_bytes`].
+    pub fn from_bytes(data: Vec<u8>) -> Self {
+        Self { data }
+    }
+
+    /// Returns raw bytes of the snapshot, they can be sent over network or written to a file.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// State of a rigid body scene node at the moment of taking a snapshot. 2D rigid bodies store their
+/// linear velocity in `xy` and their angular velocity in `z` components.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RigidBodyNodeState {
+    pub(crate) handle: Handle<Node>,
+    pub(crate) position: Vector3<f32>,
+    pub(crate) rotation: UnitQuaternion<f32>,
+    pub(crate) global_transform: Matrix4<f32>,
+    pub(crate) lin_vel: Vector3<f32>,
+    pub(crate) ang_vel: Vector3<f32>,
+    pub(crate) sleeping: bool,
+}
+
+/// Physics state of an entire scene graph - both 3D and 2D physics worlds and the rigid body scene nodes
+/// that mirror the state of the native rigid bodies. See [`Graph::physics_snapshot`] and
+/// [`Graph::restore_physics_snapshot`] for more info.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PhysicsSnapshot {
+    /// Snapshot of the 3D physics world.
+    pub physics: PhysicsWorldSnapshot,
+    /// Snapshot of the 2D physics world.
+    pub physics2d: PhysicsWorldSnapshot,
+    pub(crate) bodies: Vec<RigidBodyNodeState>,
+}
+
+impl PhysicsSnapshot {
+    /// Serializes the snapshot into a byte array, that could be sent over network or written to a file.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, PhysicsSnapshotError> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    /// Deserializes a snapshot from a byte array previously obtained by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PhysicsSnapshotError> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
+
+#[derive(Serialize)]
+struct NativeStateRef<'a> {
+    islands: &'a IslandManager,
+    broad_phase: &'a DefaultBroadPhase,
+    narrow_phase: &'a NarrowPhase,
+    bodies: &'a RigidBodySet,
+    colliders: &'a ColliderSet,
+    joints: &'a ImpulseJointSet,
+    joints_map: Vec<(ImpulseJointHandle, Handle<Node>)>,
+    multibody_joints: &'a MultibodyJointSet,
+    multibody_joints_map: Vec<(MultibodyJointHandle, Handle<Node>)>,
+    ccd_solver: &'a CCDSolver,
+}
+
+#[derive(Deserialize)]
+struct NativeState {
+    islands: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    joints: ImpulseJointSet,
+    joints_map: Vec<(ImpulseJointHandle, Handle<Node>)>,
+    multibody_joints: MultibodyJointSet,
+    multibody_joints_map: Vec<(MultibodyJointHandle, Handle<Node>)>,
+    ccd_solver: CCDSolver,
+}
+
+fn sorted_map<K: Hash + Eq + Clone>(map: &BiDirHashMap<K, Handle<Node>>) -> Vec<(K, Handle<Node>)> {
+    let mut pairs = map
+        .forward_map()
+        .iter()
+        .map(|(k, v)| (k.clone(), *v))
+        .collect::<Vec<_>>();
+    // Hash map iteration order is not stable, sorting makes snapshots of equal worlds equal.
+    pairs.sort_by_key(|(_, node)| *node);
+    pairs
+}
+
 pub(super) struct Container<S, A>
 where
     A: Hash + Eq + Clone,
@@ -785,7 +915,8 @@ fn collider_shape_into_native_shape(
 #[visit(optional)]
 pub struct IntegrationParameters {
     /// The time step length, default is None - this means that physics simulation will use engine's
-    /// time step.
+    /// time step. Set it to a fixed value if you need deterministic simulation (for example, for
+    /// rollback networking or replays).
     #[reflect(min_value = 0.0, description = "The time step length (default: None)")]
     pub dt: Option<f32>,
 
@@ -1098,6 +1229,268 @@ pub struct TOI {
     pub status: collider::TOIStatus,
 }
 
+/// Defines how collider shapes are colored by [`PhysicsWorld::draw_with_options`].
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Visit, Reflect, VariantNames, EnumString, AsRefStr, Default,
+)]
+pub enum PhysicsDebugColoring {
+    /// Colliders are colored by the type of their rigid bodies (dynamic, kinematic, fixed).
+    #[default]
+    BodyType,
+    /// Colliders of awake bodies are green, colliders of sleeping bodies are blue and colliders of
+    /// non-dynamic bodies are gray.
+    SleepState,
+    /// Colliders are colored by their collision groups, colliders with the same membership have
+    /// the same color.
+    InteractionGroups,
+}
+
+uuid_provider!(PhysicsDebugColoring = "07462379-3218-4658-b2a9-c1372bae75e8");
+
+/// A set of options for extended debug drawing of the physics world, see
+/// [`PhysicsWorld::draw_with_options`].
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct PhysicsDebugDrawOptions {
+    /// Draw shapes of colliders.
+    pub shapes: bool,
+    /// Defines how collider shapes will be colored.
+    pub coloring: PhysicsDebugColoring,
+    /// Draw local coordinate axes of rigid bodies.
+    pub rigid_body_axes: bool,
+    /// Draw bounding boxes of colliders.
+    pub aabbs: bool,
+    /// Draw anchors of joints.
+    pub joints: bool,
+    /// Draw limits of joints. Angular limits are drawn as arcs, linear limits - as segments.
+    pub joint_limits: bool,
+    /// Draw contact points.
+    pub contacts: bool,
+    /// Draw normals at contact points.
+    pub contact_normals: bool,
+    /// Draw impulses applied at contact points.
+    pub contact_impulses: bool,
+    /// Length of contact normals in meters.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub normal_length: f32,
+    /// Defines how many meters of a line represents a unit of impulse.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub impulse_scale: f32,
+}
+
+uuid_provider!(PhysicsDebugDrawOptions = "e398f075-eb9f-4886-9e29-13f31a9ab850");
+
+impl Default for PhysicsDebugDrawOptions {
+    fn default() -> Self {
+        Self {
+            shapes: true,
+            coloring: Default::default(),
+            rigid_body_axes: true,
+            aabbs: false,
+            joints: true,
+            joint_limits: true,
+            contacts: true,
+            contact_normals: true,
+            contact_impulses: true,
+            normal_length: 0.3,
+            impulse_scale: 0.1,
+        }
+    }
+}
+
+impl PhysicsDebugDrawOptions {
+    /// Draws the given set of contact points using the options. It could be used to draw contacts
+    /// that were recorded earlier, for example when scrubbing through recorded physics frames.
+    pub fn draw_contact_points(&self, context: &mut SceneDrawingContext, points: &[ContactPoint]) {
+        const CROSS_SIZE: f32 = 0.03;
+
+        for point in points {
+            if self.contacts {
+                let color = if point.distance > 0.0 {
+                    // Speculative contact.
+                    Color::opaque(255, 255, 0)
+                } else {
+                    Color::RED
+                };
+                for axis in [Vector3::x(), Vector3::y(), Vector3::z()] {
+                    context.add_line(Line {
+                        begin: point.position - axis.scale(CROSS_SIZE),
+                        end: point.position + axis.scale(CROSS_SIZE),
+                        color,
+                    });
+                }
+            }
+
+            if self.contact_normals {
+                context.add_line(Line {
+                    begin: point.position,
+                    end: point.position + point.normal.scale(self.normal_length),
+                    color: Color::opaque(0, 255, 255),
+                });
+            }
+
+            if self.contact_impulses && point.impulse != 0.0 {
+                // The impulse is applied to the first body, so it points opposite to the normal.
+                context.add_line(Line {
+                    begin: point.position,
+                    end: point.position - point.normal.scale(point.impulse * self.impulse_scale),
+                    color: Color::opaque(255, 0, 255),
+                });
+            }
+        }
+    }
+}
+
+/// A contact point between two colliders in world coordinates.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ContactPoint {
+    /// The first collider involved in the contact.
+    pub collider1: Handle<Node>,
+    /// The second collider involved in the contact.
+    pub collider2: Handle<Node>,
+    /// Position of the contact point on the first collider.
+    pub position: Vector3<f32>,
+    /// The world-space contact normal, it points from the first collider to the second.
+    pub normal: Vector3<f32>,
+    /// The distance between the colliders at the contact point. Negative values mean penetration.
+    pub distance: f32,
+    /// The impulse, along the contact normal, applied by this contact to the first collider's
+    /// rigid-body.
+    pub impulse: f32,
+}
+
+// Overrides colors of debug lines according to the coloring mode.
+struct DebugRenderContext<'a> {
+    context: &'a mut SceneDrawingContext,
+    bodies: &'a RigidBodySet,
+    coloring: PhysicsDebugColoring,
+}
+
+impl DebugRenderContext<'_> {
+    fn body_color(&self, body: Option<&RigidBody>, color: [f32; 4]) -> [f32; 4] {
+        match body {
+            Some(body) => sleep_state_debug_color(body.is_dynamic(), body.is_sleeping()),
+            None => color,
+        }
+    }
+}
+
+// Colors (in HSL) of the debug coloring modes, they're shared with the 2D physics world.
+pub(crate) fn sleep_state_debug_color(is_dynamic: bool, is_sleeping: bool) -> [f32; 4] {
+    if !is_dynamic {
+        [0.0, 0.0, 0.5, 1.0]
+    } else if is_sleeping {
+        [220.0, 1.0, 0.5, 1.0]
+    } else {
+        [120.0, 1.0, 0.4, 1.0]
+    }
+}
+
+pub(crate) fn interaction_groups_debug_color(memberships: u32) -> [f32; 4] {
+    if memberships == u32::MAX {
+        // Member of every group.
+        [0.0, 0.0, 0.9, 1.0]
+    } else {
+        // Hash the mask to get well distinguishable hues for different groups.
+        let hue = (memberships.wrapping_mul(2654435761) % 360) as f32;
+        [hue, 1.0, 0.5, 1.0]
+    }
+}
+
+impl DebugRenderBackend for DebugRenderContext<'_> {
+    fn draw_line(
+        &mut self,
+        object: DebugRenderObject,
+        a: rapier3d::math::Point<rapier3d::math::Real>,
+        b: rapier3d::math::Point<rapier3d::math::Real>,
+        color: [f32; 4],
+    ) {
+        let color = match (self.coloring, &object) {
+            (PhysicsDebugColoring::SleepState, DebugRenderObject::Collider(_, collider)) => {
+                self.body_color(collider.parent().and_then(|h| self.bodies.get(h)), color)
+            }
+            (PhysicsDebugColoring::SleepState, DebugRenderObject::RigidBody(_, body)) => {
+                self.body_color(Some(body), color)
+            }
+            (PhysicsDebugColoring::InteractionGroups, DebugRenderObject::Collider(_, collider)) => {
+                interaction_groups_debug_color(collider.collision_groups().memberships.bits())
+            }
+            _ => color,
+        };
+        self.context.draw_line(object, a, b, color)
+    }
+}
+
+fn draw_joint_limits(
+    context: &mut SceneDrawingContext,
+    bodies: &RigidBodySet,
+    joints: &ImpulseJointSet,
+) {
+    const RADIUS: f32 = 0.25;
+    const SEGMENTS: usize = 16;
+
+    for (_, joint) in joints.iter() {
+        let Some(body1) = bodies.get(joint.body1) else {
+            continue;
+        };
+        let frame = body1.position() * joint.data.local_frame1;
+        let origin = frame.translation.vector;
+        let axes = [
+            frame.rotation * Vector3::x_axis(),
+            frame.rotation * Vector3::y_axis(),
+            frame.rotation * Vector3::z_axis(),
+        ];
+
+        for (i, axis) in [JointAxis::LinX, JointAxis::LinY, JointAxis::LinZ]
+            .into_iter()
+            .enumerate()
+        {
+            if let Some(limits) = joint.data.limits(axis) {
+                let direction = axes[i].into_inner();
+                context.add_line(Line {
+                    begin: origin + direction.scale(limits.min),
+                    end: origin + direction.scale(limits.max),
+                    color: Color::opaque(255, 165, 0),
+                });
+            }
+        }
+
+        for (i, axis) in [JointAxis::AngX, JointAxis::AngY, JointAxis::AngZ]
+            .into_iter()
+            .enumerate()
+        {
+            if let Some(limits) = joint.data.limits(axis) {
+                let rotation_axis = axes[i];
+                let reference = axes[(i + 1) % 3].into_inner().scale(RADIUS);
+                let point_at = |angle: f32| {
+                    origin + UnitQuaternion::from_axis_angle(&rotation_axis, angle) * reference
+                };
+                let color = Color::opaque(255, 165, 0);
+                let mut prev = point_at(limits.min);
+                context.add_line(Line {
+                    begin: origin,
+                    end: prev,
+                    color,
+                });
+                for k in 1..=SEGMENTS {
+                    let angle = limits.min + (limits.max - limits.min) * k as f32 / SEGMENTS as f32;
+                    let next = point_at(angle);
+                    context.add_line(Line {
+                        begin: prev,
+                        end: next,
+                        color,
+                    });
+                    prev = next;
+                }
+                context.add_line(Line {
+                    begin: prev,
+                    end: origin,
+                    color,
+                });
+            }
+        }
+    }
+}
+
 impl PhysicsWorld {
     /// Creates a new instance of the physics world.
     pub(super) fn new() -> Self {
@@ -1248,6 +1641,114 @@ impl PhysicsWorld {
         );
     }
 
+    /// Draws physics world using the given set of options. Unlike [`Self::draw`], it allows you to
+    /// see contact points with their normals and impulses, joint limits and to color colliders by
+    /// sleep state or interaction groups.
+    pub fn draw_with_options(
+        &self,
+        context: &mut SceneDrawingContext,
+        options: &PhysicsDebugDrawOptions,
+    ) {
+        let mut pipeline = self.debug_render_pipeline.lock();
+        let prev_mode = pipeline.mode;
+        let mut mode = DebugRenderMode::empty();
+        mode.set(DebugRenderMode::COLLIDER_SHAPES, options.shapes);
+        mode.set(DebugRenderMode::RIGID_BODY_AXES, options.rigid_body_axes);
+        mode.set(DebugRenderMode::COLLIDER_AABBS, options.aabbs);
+        mode.set(DebugRenderMode::JOINTS, options.joints);
+        pipeline.mode = mode;
+        pipeline.render(
+            &mut DebugRenderContext {
+                context: &mut *context,
+                bodies: &self.bodies,
+                coloring: options.coloring,
+            },
+            &self.bodies,
+            &self.colliders,
+            &self.joints.set,
+            &self.multibody_joints.set,
+            &self.narrow_phase,
+        );
+        pipeline.mode = prev_mode;
+
+        if options.joint_limits {
+            draw_joint_limits(context, &self.bodies, &self.joints.set);
+        }
+
+        options.draw_contact_points(context, &self.contact_points());
+    }
+
+    /// Returns all contact points generated in this frame in world coordinates.
+    pub fn contact_points(&self) -> Vec<ContactPoint> {
+        let mut points = Vec::new();
+        for pair in self.narrow_phase.contact_pairs() {
+            let (Some(collider1), Some(collider2)) = (
+                self.colliders.get(pair.collider1),
+                self.colliders.get(pair.collider2),
+            ) else {
+                continue;
+            };
+            for manifold in pair.manifolds.iter() {
+                for point in manifold.points.iter() {
+                    points.push(ContactPoint {
+                        collider1: Handle::decode_from_u128(collider1.user_data),
+                        collider2: Handle::decode_from_u128(collider2.user_data),
+                        position: (collider1.position() * point.local_p1).coords,
+                        normal: manifold.data.normal,
+                        distance: point.dist,
+                        impulse: point.data.impulse,
+                    });
+                }
+            }
+        }
+        points
+    }
+
+    /// Takes a snapshot of the entire simulation state of the world. See [`PhysicsWorldSnapshot`] docs
+    /// for more info.
+    pub fn snapshot(&self) -> Result<PhysicsWorldSnapshot, PhysicsSnapshotError> {
+        let state = NativeStateRef {
+            islands: &self.islands,
+            broad_phase: &self.broad_phase,
+            narrow_phase: &self.narrow_phase,
+            bodies: &self.bodies,
+            colliders: &self.colliders,
+            joints: &self.joints.set,
+            joints_map: sorted_map(&self.joints.map),
+            multibody_joints: &self.multibody_joints.set,
+            multibody_joints_map: sorted_map(&self.multibody_joints.map),
+            ccd_solver: &self.ccd_solver,
+        };
+        Ok(PhysicsWorldSnapshot {
+            data: bincode::serialize(&state)?,
+        })
+    }
+
+    /// Restores the simulation state of the world from the given snapshot. Native handles of rigid bodies,
+    /// colliders and joints are restored as well, which means that the snapshot must be restored into the
+    /// world of the same scene with the same set of physical entities. Scene nodes are not touched by this
+    /// method, use [`Graph::restore_physics_snapshot`] to restore both the world and the scene nodes.
+    pub fn restore(&mut self, snapshot: &PhysicsWorldSnapshot) -> Result<(), PhysicsSnapshotError> {
+        let state: NativeState = bincode::deserialize(&snapshot.data)?;
+        self.islands = state.islands;
+        self.broad_phase = state.broad_phase;
+        self.narrow_phase = state.narrow_phase;
+        self.bodies = state.bodies;
+        self.colliders = state.colliders;
+        self.joints = Container {
+            set: state.joints,
+            map: state.joints_map.into_iter().collect(),
+        };
+        self.multibody_joints = Container {
+            set: state.multibody_joints,
+            map: state.multibody_joints_map.into_iter().collect(),
+        };
+        self.ccd_solver = state.ccd_solver;
+        // Query pipeline is not a part of the simulation state and will be rebuilt on demand.
+        *self.query.borrow_mut() = Default::default();
+        Ok(())
+    }
+
     /// Casts a ray with given options.
     pub fn cast_ray<S: QueryResultsStorage>(&self, opts: RayCastOptions, query_buffer: &mut S) {
         let time = instant::Instant::now();
@@ -1914,3 +2415,77 @@ impl Debug for PhysicsWorld {
         write!(f, "PhysicsWorld")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::core::algebra::{Vector2, Vector3};
+    use crate::scene::{
+        base::BaseBuilder,
+        collider::{ColliderBuilder, ColliderShape},
+        debug::SceneDrawingContext,
+        graph::{
+            physics::{PhysicsDebugColoring, PhysicsDebugDrawOptions},
+            Graph,
+        },
+        rigidbody::{RigidBodyBuilder, RigidBodyType},
+        transform::TransformBuilder,
+    };
+
+    #[test]
+    fn test_contact_points_and_debug_drawing() {
+        let mut graph = Graph::new();
+
+        let ground_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(5.0, 0.5, 5.0))
+            .build(&mut graph);
+        RigidBodyBuilder::new(BaseBuilder::new().with_children(&[ground_collider]))
+            .with_body_type(RigidBodyType::Static)
+            .build(&mut graph);
+
+        let ball_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.5))
+            .build(&mut graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                        .build(),
+                )
+                .with_children(&[ball_collider]),
+        )
+        .with_body_type(RigidBodyType::Dynamic)
+        .build(&mut graph);
+
+        for _ in 0..10 {
+            graph.update(Vector2::new(800.0, 600.0), 1.0 / 60.0, Default::default());
+        }
+
+        let points = graph.physics.contact_points();
+        assert!(!points.is_empty());
+        for point in points.iter() {
+            assert!(point.collider1 == ball_collider || point.collider1 == ground_collider);
+            assert!(point.normal.y.abs() > 0.9);
+        }
+
+        let mut options = PhysicsDebugDrawOptions {
+            shapes: false,
+            rigid_body_axes: false,
+            joints: false,
+            ..Default::default()
+        };
+        let mut context = SceneDrawingContext::default();
+        graph.physics.draw_with_options(&mut context, &options);
+        // A cross and a normal for every point at least.
+        assert!(context.lines.len() >= points.len() * 4);
+
+        options.contacts = false;
+        options.contact_normals = false;
+        options.contact_impulses = false;
+        options.shapes = true;
+        options.coloring = PhysicsDebugColoring::SleepState;
+        context.clear_lines();
+        graph.physics.draw_with_options(&mut context, &options);
+        assert!(!context.lines.is_empty());
+    }
+}
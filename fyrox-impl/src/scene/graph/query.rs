@@ -0,0 +1,697 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Entity-component style queries over scene nodes and their scripts. See [`Query`] and
+//! [`GraphQuery`] docs for more info.
+
+use crate::{
+    core::{log::Log, pool::Handle, type_traits::ComponentProvider},
+    scene::{
+        base::{Base, NodeScriptMessage},
+        graph::NodePool,
+        node::Node,
+        transform::Transform,
+    },
+    script::Script,
+};
+use fxhash::{FxHashMap, FxHashSet};
+use rayon::prelude::*;
+use std::{
+    any::{Any, TypeId},
+    fmt::{Display, Formatter},
+    marker::PhantomData,
+    sync::mpsc::Sender,
+};
+
+/// A source of components for a single query term. It is either a scene node or one of its
+/// scripts.
+pub enum ComponentSource<'a> {
+    /// Shared access to a scene node.
+    Node(&'a Node),
+    /// Exclusive access to a scene node.
+    NodeMut(&'a mut Node),
+    /// Shared access to a script.
+    Script(&'a Script),
+    /// Exclusive access to a script.
+    ScriptMut(&'a mut Script),
+}
+
+// Transform and Base are not exposed as components by scene nodes, but they're essential for
+// systems-style logic, so they're handled here explicitly.
+fn node_component_ref(node: &Node, type_id: TypeId) -> Option<&dyn Any> {
+    if type_id == TypeId::of::<Transform>() {
+        Some(node.local_transform())
+    } else if type_id == TypeId::of::<Base>() {
+        let base: &Base = node;
+        Some(base)
+    } else {
+        node.query_component_ref(type_id)
+    }
+}
+
+fn node_component_mut(node: &mut Node, type_id: TypeId) -> Option<&mut dyn Any> {
+    if type_id == TypeId::of::<Transform>() {
+        Some(node.local_transform_mut())
+    } else if type_id == TypeId::of::<Base>() {
+        let base: &mut Base = node;
+        Some(base)
+    } else {
+        node.query_component_mut(type_id)
+    }
+}
+
+/// A single term of a [`Query`]. It is implemented for `&T` (shared access) and `&mut T`
+/// (exclusive access), where `T` is a component of a scene node or of one of its scripts (see
+/// [`crate::core::type_traits::ComponentProvider`]). [`Transform`] and [`Base`] are available
+/// for every scene node.
+pub trait QueryTerm {
+    /// A type of the reference produced by the term.
+    type Item<'a>;
+
+    /// `true` if the term requires exclusive access to the component.
+    const MUTABLE: bool;
+
+    /// Returns type id of the component.
+    fn component_type_id() -> TypeId;
+
+    /// Returns type name of the component.
+    fn component_type_name() -> &'static str;
+
+    /// Fetches the component from the given source.
+    fn fetch(source: ComponentSource<'_>) -> Option<Self::Item<'_>>;
+}
+
+impl<T: Any> QueryTerm for &T {
+    type Item<'a> = &'a T;
+
+    const MUTABLE: bool = false;
+
+    fn component_type_id() -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn component_type_name() -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn fetch(source: ComponentSource<'_>) -> Option<Self::Item<'_>> {
+        match source {
+            ComponentSource::Node(node) => {
+                node_component_ref(node, TypeId::of::<T>())?.downcast_ref()
+            }
+            ComponentSource::Script(script) => script.query_component_ref::<T>(),
+            ComponentSource::NodeMut(_) | ComponentSource::ScriptMut(_) => None,
+        }
+    }
+}
+
+impl<T: Any> QueryTerm for &mut T {
+    type Item<'a> = &'a mut T;
+
+    const MUTABLE: bool = true;
+
+    fn component_type_id() -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn component_type_name() -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn fetch(source: ComponentSource<'_>) -> Option<Self::Item<'_>> {
+        match source {
+            ComponentSource::NodeMut(node) => {
+                node_component_mut(node, TypeId::of::<T>())?.downcast_mut()
+            }
+            ComponentSource::ScriptMut(script) => script.query_component_mut::<T>(),
+            ComponentSource::Node(_) | ComponentSource::Script(_) => None,
+        }
+    }
+}
+
+/// A description of a single query term.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct TermDescriptor {
+    /// Type id of the component.
+    pub type_id: TypeId,
+    /// `true` if the term requires exclusive access to the component.
+    pub mutable: bool,
+    /// Type name of the component.
+    pub type_name: &'static str,
+}
+
+/// An error that may occur when a query is created by [`crate::scene::graph::Graph::query`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum QueryError {
+    /// Two terms of the query are always provided by the same object (the node itself or one of
+    /// its scripts) and at least one of them is accessed mutably. See "Access rules" section of
+    /// [`GraphQuery`] docs for more info.
+    ConflictingAccess {
+        /// The first conflicting term.
+        first: TermDescriptor,
+        /// The second conflicting term.
+        second: TermDescriptor,
+    },
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ConflictingAccess { first, second } => write!(
+                f,
+                "Query has conflicting access to {} and {} components, both are provided by \
+                the same object and at least one is accessed mutably!",
+                first.type_name, second.type_name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Query is a tuple of [`QueryTerm`]s, for example `(&Transform, &mut MyScript)`. A node matches
+/// the query if every component of the query could be found either in the node itself or in one
+/// of its scripts. Single-term queries must be written as one-element tuples: `(&Mesh,)`.
+pub trait Query {
+    /// A tuple of references produced by the query.
+    type Item<'a>;
+
+    /// Returns descriptors of the query terms.
+    fn terms() -> Vec<TermDescriptor>;
+
+    /// Fetches the components from the given sources, the sources must be in the same order as
+    /// the terms.
+    fn fetch<'a>(sources: &mut impl Iterator<Item = ComponentSource<'a>>)
+        -> Option<Self::Item<'a>>;
+}
+
+macro_rules! impl_query {
+    ($($term:ident),+) => {
+        impl<$($term: QueryTerm),+> Query for ($($term,)+) {
+            type Item<'a> = ($($term::Item<'a>,)+);
+
+            fn terms() -> Vec<TermDescriptor> {
+                vec![$(TermDescriptor {
+                    type_id: $term::component_type_id(),
+                    mutable: $term::MUTABLE,
+                    type_name: $term::component_type_name(),
+                }),+]
+            }
+
+            fn fetch<'a>(
+                sources: &mut impl Iterator<Item = ComponentSource<'a>>,
+            ) -> Option<Self::Item<'a>> {
+                Some(($($term::fetch(sources.next()?)?,)+))
+            }
+        }
+    };
+}
+
+impl_query!(A);
+impl_query!(A, B);
+impl_query!(A, B, C);
+impl_query!(A, B, C, D);
+impl_query!(A, B, C, D, E);
+impl_query!(A, B, C, D, E, F);
+impl_query!(A, B, C, D, E, F, G);
+impl_query!(A, B, C, D, E, F, G, H);
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Location {
+    Node,
+    Script(usize),
+}
+
+fn locate<'a>(
+    node: &Node,
+    mut scripts: impl Iterator<Item = Option<&'a Script>>,
+    type_id: TypeId,
+) -> Option<Location> {
+    if node_component_ref(node, type_id).is_some() {
+        return Some(Location::Node);
+    }
+    scripts
+        .position(|script| {
+            script.is_some_and(|script| {
+                ComponentProvider::query_component_ref(&**script, type_id).is_some()
+            })
+        })
+        .map(Location::Script)
+}
+
+// Every location (the node or a script) could be borrowed either once exclusively or any number
+// of times shared, returns a pair of terms that violate this rule.
+fn find_conflict(
+    terms: &[TermDescriptor],
+    locations: &[Location],
+) -> Option<(TermDescriptor, TermDescriptor)> {
+    for (i, (a_term, a_location)) in terms.iter().zip(locations.iter()).enumerate() {
+        for (b_term, b_location) in terms.iter().zip(locations.iter()).skip(i + 1) {
+            if a_location == b_location && (a_term.mutable || b_term.mutable) {
+                return Some((*a_term, *b_term));
+            }
+        }
+    }
+    None
+}
+
+fn node_conflict(
+    node: &Node,
+    terms: &[TermDescriptor],
+) -> Option<(TermDescriptor, TermDescriptor)> {
+    if !terms.iter().any(|term| term.mutable) {
+        return None;
+    }
+    let locations = terms
+        .iter()
+        .map(|term| {
+            locate(
+                node,
+                node.scripts.iter().map(|entry| entry.script.as_ref()),
+                term.type_id,
+            )
+        })
+        .collect::<Option<Vec<_>>>()?;
+    find_conflict(terms, &locations)
+}
+
+// Transform and Base are provided by every scene node, see `node_component_ref`.
+fn is_provided_by_node(term: &TermDescriptor) -> bool {
+    term.type_id == TypeId::of::<Transform>() || term.type_id == TypeId::of::<Base>()
+}
+
+// Checks the terms that are always provided by the same object - the same component requested
+// twice or any pair of Transform and Base. Other conflicts depend on a particular node, such nodes
+// are skipped by the query.
+pub(crate) fn validate_terms(terms: &[TermDescriptor]) -> Result<(), QueryError> {
+    for (i, a) in terms.iter().enumerate() {
+        for b in terms.iter().skip(i + 1) {
+            let same_object =
+                a.type_id == b.type_id || (is_provided_by_node(a) && is_provided_by_node(b));
+            if same_object && (a.mutable || b.mutable) {
+                return Err(QueryError::ConflictingAccess {
+                    first: *a,
+                    second: *b,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+enum Slot<'a, T> {
+    Shared(&'a T),
+    Mut(Option<&'a mut T>),
+}
+
+impl<'a, T> Slot<'a, T> {
+    fn new(value: &'a mut T, mutable: bool) -> Self {
+        if mutable {
+            Self::Mut(Some(value))
+        } else {
+            Self::Shared(value)
+        }
+    }
+}
+
+// Borrows the components requested by the query from the node and its detached scripts. The node is
+// skipped if its components conflict, such nodes are not cached, but they still could be passed
+// here by `GraphQuery::with`.
+fn fetch<'a, Q: Query>(
+    terms: &[TermDescriptor],
+    node: &'a mut Node,
+    scripts: &'a mut [Option<Script>],
+) -> Option<Q::Item<'a>> {
+    let mut locations = Vec::with_capacity(terms.len());
+    for term in terms {
+        locations.push(locate(
+            node,
+            scripts.iter().map(Option::as_ref),
+            term.type_id,
+        )?);
+    }
+
+    if find_conflict(terms, &locations).is_some() {
+        return None;
+    }
+
+    let is_mutable = |location: Location| {
+        terms
+            .iter()
+            .zip(locations.iter())
+            .any(|(t, l)| *l == location && t.mutable)
+    };
+
+    let mut node_slot = Slot::new(node, is_mutable(Location::Node));
+    let mut script_slots = scripts
+        .iter_mut()
+        .enumerate()
+        .map(|(index, script)| {
+            script
+                .as_mut()
+                .map(|script| Slot::new(script, is_mutable(Location::Script(index))))
+        })
+        .collect::<Vec<_>>();
+
+    let mut sources = locations.into_iter().filter_map(|location| match location {
+        Location::Node => match node_slot {
+            Slot::Shared(node) => Some(ComponentSource::Node(node)),
+            Slot::Mut(ref mut node) => node.take().map(ComponentSource::NodeMut),
+        },
+        Location::Script(index) => match *script_slots.get_mut(index)?.as_mut()? {
+            Slot::Shared(script) => Some(ComponentSource::Script(script)),
+            Slot::Mut(ref mut script) => script.take().map(ComponentSource::ScriptMut),
+        },
+    });
+
+    Q::fetch(&mut sources)
+}
+
+fn node_matches(node: &Node, terms: &[TermDescriptor]) -> bool {
+    // Scripts could be temporarily detached from the node (for example - when a query is executed
+    // from a script method), in this case the node is kept in the cache to not lose it.
+    if node
+        .scripts
+        .iter()
+        .any(|e| e.script.is_none() && !e.should_be_deleted)
+    {
+        return true;
+    }
+
+    terms.iter().all(|term| {
+        node_component_ref(node, term.type_id).is_some()
+            || node
+                .scripts()
+                .any(|s| ComponentProvider::query_component_ref(&**s, term.type_id).is_some())
+    })
+}
+
+// Calls the function for a single node. Script instances are detached from the node while the
+// components are borrowed, it allows to borrow components of the node and its scripts at the same
+// time. This is the same approach that is used by the engine when it calls script methods.
+fn visit_node<Q, F>(
+    handle: Handle<Node>,
+    node: &mut Node,
+    terms: &[TermDescriptor],
+    script_message_sender: &Sender<NodeScriptMessage>,
+    func: &mut F,
+) where
+    Q: Query,
+    F: FnMut(Handle<Node>, Q::Item<'_>),
+{
+    let mut scripts = node
+        .scripts
+        .iter_mut()
+        .map(|entry| entry.script.take())
+        .collect::<Vec<_>>();
+
+    if let Some(item) = fetch::<Q>(terms, node, &mut scripts) {
+        func(handle, item);
+    }
+
+    for (script_index, script) in scripts.into_iter().enumerate() {
+        let Some(script) = script else {
+            continue;
+        };
+
+        match node.scripts.get_mut(script_index) {
+            Some(entry) if !entry.should_be_deleted => entry.script = Some(script),
+            _ => {
+                // The script was removed while its node was borrowed, it must be destroyed
+                // correctly, not silently dropped.
+                Log::verify(
+                    script_message_sender.send(NodeScriptMessage::DestroyScript {
+                        script,
+                        handle,
+                        script_index,
+                    }),
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CachedQuery {
+    handles: Vec<Handle<Node>>,
+    positions: FxHashMap<Handle<Node>, usize>,
+}
+
+impl CachedQuery {
+    fn new(terms: &[TermDescriptor], nodes: &NodePool) -> Self {
+        let mut query = Self {
+            handles: Default::default(),
+            positions: Default::default(),
+        };
+        for (handle, node) in nodes.pair_iter() {
+            query.update(handle, Some(node), terms);
+        }
+        query
+    }
+
+    fn update(&mut self, handle: Handle<Node>, node: Option<&Node>, terms: &[TermDescriptor]) {
+        match node {
+            // Nodes with conflicting components are skipped, so the query itself does not depend
+            // on the content of the graph.
+            Some(node) if node_matches(node, terms) && node_conflict(node, terms).is_none() => {
+                self.insert(handle)
+            }
+            _ => self.remove(handle),
+        }
+    }
+
+    fn insert(&mut self, handle: Handle<Node>) {
+        if !self.positions.contains_key(&handle) {
+            self.positions.insert(handle, self.handles.len());
+            self.handles.push(handle);
+        }
+    }
+
+    fn remove(&mut self, handle: Handle<Node>) {
+        if let Some(position) = self.positions.remove(&handle) {
+            self.handles.swap_remove(position);
+            if let Some(moved) = self.handles.get(position) {
+                self.positions.insert(*moved, position);
+            }
+        }
+    }
+}
+
+/// A cache of matching node handles of every query that was executed on a graph. The cache is
+/// updated incrementally, only the nodes that were added, removed or whose scripts were changed
+/// are re-checked.
+#[derive(Debug, Default)]
+pub(crate) struct QueryCache {
+    queries: FxHashMap<Vec<TermDescriptor>, CachedQuery>,
+    dirty: FxHashSet<Handle<Node>>,
+}
+
+impl QueryCache {
+    /// Marks the node as changed, it will be re-checked by every cached query on next use.
+    pub fn mark_dirty(&mut self, handle: Handle<Node>) {
+        // There's no need to track changes if there's nothing to update.
+        if !self.queries.is_empty() {
+            self.dirty.insert(handle);
+        }
+    }
+
+    fn sync(&mut self, nodes: &NodePool) {
+        for handle in self.dirty.drain() {
+            let node = nodes.try_borrow(handle);
+            for (terms, query) in self.queries.iter_mut() {
+                query.update(handle, node, terms);
+            }
+        }
+    }
+
+    /// Brings the cache up to date and creates a new cached query for the given set of terms if
+    /// needed.
+    pub fn prepare(&mut self, key: &[TermDescriptor], nodes: &NodePool) {
+        self.sync(nodes);
+        self.queries
+            .entry(key.to_vec())
+            .or_insert_with(|| CachedQuery::new(key, nodes));
+    }
+
+    fn get(&self, key: &[TermDescriptor]) -> Option<&CachedQuery> {
+        self.queries.get(key)
+    }
+}
+
+pub(crate) fn query_key(terms: &[TermDescriptor]) -> Vec<TermDescriptor> {
+    let mut key = terms.to_vec();
+    key.sort_unstable();
+    key.dedup();
+    key
+}
+
+/// A prepared query over the nodes of a graph, created by [`crate::scene::graph::Graph::query`].
+/// It holds a mutable borrow of the graph and allows to iterate over the matching nodes either
+/// sequentially or in parallel.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{reflect::prelude::*, visitor::prelude::*, type_traits::prelude::*},
+/// #     scene::{graph::{query::QueryError, Graph}, transform::Transform},
+/// #     script::ScriptTrait,
+/// # };
+/// #[derive(Clone, Debug, Default, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+/// #[type_uuid(id = "f3a1b2c4-6d7e-4f80-9a1b-2c3d4e5f6a7b")]
+/// struct Velocity {
+///     x: f32,
+/// }
+///
+/// impl ScriptTrait for Velocity {}
+///
+/// fn move_system(graph: &mut Graph, dt: f32) -> Result<(), QueryError> {
+///     graph
+///         .query::<(&mut Transform, &Velocity)>()?
+///         .for_each(|_handle, (transform, velocity)| {
+///             let position = **transform.position();
+///             transform.set_position(position.map(|c| c + velocity.x * dt));
+///         });
+///     Ok(())
+/// }
+/// ```
+///
+/// ## Access rules
+///
+/// Components of a node and components of its scripts could be borrowed mutably at the same time,
+/// for example `(&mut Transform, &mut MyScript)` is allowed. However, components that are provided
+/// by the same object (the node itself or a particular script) cannot be borrowed mutably together
+/// with any other component of the same object. For example, `(&mut Transform, &Mesh)` conflicts
+/// for mesh nodes, because both components are provided by the node itself, so mesh nodes are
+/// skipped by such query (a script with `Mesh` component would still match it). Queries, that
+/// conflict for every node (for example - `(&mut Transform, &Base)` or `(&mut MyScript, &MyScript)`)
+/// are rejected by [`crate::scene::graph::Graph::query`] with [`QueryError::ConflictingAccess`]
+/// error.
+///
+/// Script instances are temporarily detached from their node while its components are borrowed,
+/// so it is not possible to access scripts through the [`Base`] of the node inside a query.
+pub struct GraphQuery<'a, Q: Query> {
+    nodes: &'a mut NodePool,
+    cache: &'a QueryCache,
+    script_message_sender: &'a Sender<NodeScriptMessage>,
+    key: Vec<TermDescriptor>,
+    terms: Vec<TermDescriptor>,
+    phantom: PhantomData<Q>,
+}
+
+impl<'a, Q: Query> GraphQuery<'a, Q> {
+    pub(crate) fn new(
+        nodes: &'a mut NodePool,
+        cache: &'a QueryCache,
+        script_message_sender: &'a Sender<NodeScriptMessage>,
+        terms: Vec<TermDescriptor>,
+        key: Vec<TermDescriptor>,
+    ) -> Self {
+        Self {
+            nodes,
+            cache,
+            script_message_sender,
+            key,
+            terms,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns handles of the nodes matching the query. The order of the handles is unspecified.
+    pub fn handles(&self) -> &[Handle<Node>] {
+        self.cache
+            .get(&self.key)
+            .map(|q| q.handles.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Returns the amount of nodes matching the query.
+    pub fn len(&self) -> usize {
+        self.handles().len()
+    }
+
+    /// Returns `true` if there's no nodes matching the query.
+    pub fn is_empty(&self) -> bool {
+        self.handles().is_empty()
+    }
+
+    /// Calls the given function for every node matching the query.
+    pub fn for_each<F>(&mut self, mut func: F)
+    where
+        F: FnMut(Handle<Node>, Q::Item<'_>),
+    {
+        let Some(query) = self.cache.get(&self.key) else {
+            return;
+        };
+        for &handle in query.handles.iter() {
+            if let Some(node) = self.nodes.try_borrow_mut(handle) {
+                visit_node::<Q, _>(
+                    handle,
+                    node,
+                    &self.terms,
+                    self.script_message_sender,
+                    &mut func,
+                );
+            }
+        }
+    }
+
+    /// Calls the given function for the node with the given handle, if it matches the query.
+    /// Returns the result of the function or `None` if the node does not match the query.
+    pub fn with<F, R>(&mut self, handle: Handle<Node>, func: F) -> Option<R>
+    where
+        F: FnOnce(Q::Item<'_>) -> R,
+    {
+        let node = self.nodes.try_borrow_mut(handle)?;
+        let mut func = Some(func);
+        let mut result = None;
+        visit_node::<Q, _>(
+            handle,
+            node,
+            &self.terms,
+            self.script_message_sender,
+            &mut |_, item| result = func.take().map(|func| func(item)),
+        );
+        result
+    }
+
+    /// Calls the given function for every node matching the query in parallel, using a thread
+    /// pool. Keep in mind, that parallel iteration has some overhead and it makes sense only
+    /// for heavy computations over large amount of nodes.
+    pub fn par_for_each<F>(&mut self, func: F)
+    where
+        F: Fn(Handle<Node>, Q::Item<'_>) + Send + Sync,
+    {
+        let Some(query) = self.cache.get(&self.key) else {
+            return;
+        };
+        let nodes = self.nodes.try_borrow_many_mut(&query.handles);
+        let terms = &self.terms;
+        let script_message_sender = self.script_message_sender;
+        nodes.into_par_iter().for_each(|(handle, node)| {
+            visit_node::<Q, _>(
+                handle,
+                node,
+                terms,
+                script_message_sender,
+                &mut |handle, item| func(handle, item),
+            );
+        });
+    }
+}
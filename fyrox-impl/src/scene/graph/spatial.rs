@@ -0,0 +1,279 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Spatial index of scene nodes, it allows you to quickly find nodes in a region of space. See
+//! [`SpatialIndex`] and [`SpatialQuery`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Vector3,
+        math::{
+            aabb::AxisAlignedBoundingBox,
+            bvh::{distance_to_aabb, ray_aabb_intersection, DynamicBvh, ProxyId},
+            frustum::Frustum,
+            ray::Ray,
+        },
+        pool::Handle,
+    },
+    fxhash::{FxHashMap, FxHashSet},
+    graph::{BaseSceneGraph, SceneGraphNode},
+    scene::{
+        graph::{Graph, NodePool},
+        layer::LayerMask,
+        node::{Node, NodeTrait},
+    },
+};
+use std::any::Any;
+
+/// Spatial index of scene nodes, it is a dynamic bounding volume hierarchy (see [`DynamicBvh`])
+/// built from world-space bounding boxes of the nodes. The index is maintained by the [`Graph`]
+/// and synchronized with the actual state of the nodes at the end of every [`Graph::update`] call.
+/// Synchronization is incremental - only added, removed and moved nodes (including descendants of
+/// moved nodes) are updated, so static nodes cost nothing. If bounds of a node were changed without
+/// moving it (for example - surfaces of a mesh were replaced), use [`Graph::invalidate_spatial_bounds`]
+/// to update its entry. Nodes without bounding box (pivots, lights, etc.) are represented by their
+/// global positions.
+///
+/// Use [`Graph::spatial_query`] to search for nodes.
+#[derive(Default, Debug)]
+pub struct SpatialIndex {
+    bvh: DynamicBvh<Handle<Node>>,
+    proxies: FxHashMap<Handle<Node>, ProxyId>,
+    dirty: FxHashSet<Handle<Node>>,
+}
+
+/// Returns a bounding box of the node used by the spatial index.
+pub fn node_spatial_bounds(node: &Node) -> AxisAlignedBoundingBox {
+    if node.local_bounding_box().is_valid() {
+        node.world_bounding_box()
+    } else {
+        AxisAlignedBoundingBox::from_point(node.global_position())
+    }
+}
+
+impl SpatialIndex {
+    /// Returns the bounding volume hierarchy of the index. It could be used for custom queries.
+    pub fn bvh(&self) -> &DynamicBvh<Handle<Node>> {
+        &self.bvh
+    }
+
+    /// Returns the amount of nodes in the index.
+    pub fn len(&self) -> usize {
+        self.bvh.len()
+    }
+
+    /// Returns `true` if the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.bvh.is_empty()
+    }
+
+    /// Returns `true` if the node is in the index.
+    pub fn contains(&self, node: Handle<Node>) -> bool {
+        self.proxies.contains_key(&node)
+    }
+
+    /// Marks the node as changed, its entry will be updated on next synchronization.
+    pub(crate) fn mark_dirty(&mut self, handle: Handle<Node>) {
+        self.dirty.insert(handle);
+    }
+
+    pub(crate) fn sync(&mut self, nodes: &NodePool, root: Handle<Node>) {
+        for handle in self.dirty.drain() {
+            match nodes.try_borrow(handle) {
+                Some(node) if handle != root => {
+                    let aabb = node_spatial_bounds(node);
+                    match self.proxies.get(&handle) {
+                        Some(proxy) => {
+                            self.bvh.update(*proxy, aabb);
+                        }
+                        None => {
+                            self.proxies.insert(handle, self.bvh.insert(aabb, handle));
+                        }
+                    }
+                }
+                _ => {
+                    if let Some(proxy) = self.proxies.remove(&handle) {
+                        self.bvh.remove(proxy);
+                    }
+                }
+            }
+        }
+    }
+}
+
+type NodeFilter<'a> = Box<dyn Fn(&Node) -> bool + 'a>;
+
+/// Spatial query over the nodes of a graph. It could be created using [`Graph::spatial_query`],
+/// filtered by node type, component, tag or an arbitrary predicate, and executed using one of the
+/// shape methods ([`Self::sphere`], [`Self::aabb`], [`Self::frustum`], [`Self::ray`],
+/// [`Self::k_nearest`]). All the shapes are tested against the world-space bounding boxes of the
+/// nodes (see [`node_spatial_bounds`]).
+///
+/// ```rust
+/// use fyrox_impl::{
+///     core::{algebra::Vector3, pool::Handle},
+///     scene::{graph::Graph, mesh::Mesh, node::Node},
+/// };
+///
+/// fn find_enemies(graph: &Graph, position: Vector3<f32>) -> Vec<Handle<Node>> {
+///     graph
+///         .spatial_query()
+///         .of_type::<Mesh>()
+///         .with_tag("Enemy")
+///         .sphere(position, 10.0)
+/// }
+/// ```
+pub struct SpatialQuery<'a> {
+    graph: &'a Graph,
+    filters: Vec<NodeFilter<'a>>,
+}
+
+impl<'a> SpatialQuery<'a> {
+    pub(crate) fn new(graph: &'a Graph) -> Self {
+        Self {
+            graph,
+            filters: Default::default(),
+        }
+    }
+
+    /// Adds an arbitrary filter to the query, only the nodes that satisfy all the filters are returned.
+    pub fn with_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&Node) -> bool + 'a,
+    {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// Leaves only the nodes of the given type.
+    pub fn of_type<T: NodeTrait>(self) -> Self {
+        self.with_filter(|node| node.cast::<T>().is_some())
+    }
+
+    /// Leaves only the nodes that provide a component of the given type (see [`SceneGraphNode::component_ref`]).
+    pub fn with_component<T: Any>(self) -> Self {
+        self.with_filter(|node| node.component_ref::<T>().is_some())
+    }
+
+    /// Leaves only the nodes with the given tag.
+    pub fn with_tag(self, tag: &'a str) -> Self {
+        self.with_filter(move |node| node.has_tag(tag))
+    }
+
+    /// Leaves only the nodes that belong to at least one layer of the given mask.
+    pub fn in_layers(self, layers: LayerMask) -> Self {
+        self.with_filter(move |node| node.layers().intersects(layers))
+    }
+
+    /// Leaves only the nodes that are enabled globally (see [`crate::scene::base::Base::is_globally_enabled`]).
+    pub fn enabled_only(self) -> Self {
+        self.with_filter(|node| node.is_globally_enabled())
+    }
+
+    fn accept(&self, handle: Handle<Node>) -> Option<(&'a Node, AxisAlignedBoundingBox)> {
+        let node = self.graph.try_get(handle)?;
+        if self.filters.iter().all(|filter| filter(node)) {
+            Some((node, node_spatial_bounds(node)))
+        } else {
+            None
+        }
+    }
+
+    /// Returns all the nodes that intersect the given bounding box.
+    pub fn aabb(&self, aabb: &AxisAlignedBoundingBox) -> Vec<Handle<Node>> {
+        let mut result = Vec::new();
+        self.graph
+            .spatial_index()
+            .bvh
+            .query_aabb(aabb, |_, handle| {
+                if let Some((_, bounds)) = self.accept(*handle) {
+                    if bounds.is_intersects_aabb(aabb) {
+                        result.push(*handle);
+                    }
+                }
+            });
+        result
+    }
+
+    /// Returns all the nodes that intersect the given sphere.
+    pub fn sphere(&self, center: Vector3<f32>, radius: f32) -> Vec<Handle<Node>> {
+        let mut result = Vec::new();
+        self.graph
+            .spatial_index()
+            .bvh
+            .query_sphere(center, radius, |_, handle| {
+                if let Some((_, bounds)) = self.accept(*handle) {
+                    if bounds.is_intersects_sphere(center, radius) {
+                        result.push(*handle);
+                    }
+                }
+            });
+        result
+    }
+
+    /// Returns all the nodes that intersect the given frustum.
+    pub fn frustum(&self, frustum: &Frustum) -> Vec<Handle<Node>> {
+        let mut result = Vec::new();
+        self.graph
+            .spatial_index()
+            .bvh
+            .query_frustum(frustum, |_, handle| {
+                if let Some((_, bounds)) = self.accept(*handle) {
+                    if frustum.is_intersects_aabb(&bounds) {
+                        result.push(*handle);
+                    }
+                }
+            });
+        result
+    }
+
+    /// Returns all the nodes that are intersected by the given ray, the ray is treated as a segment
+    /// from its origin to `origin + dir`. Every node is returned with the parameter of the point,
+    /// where the ray enters its bounding box (zero if the origin is inside the box), the nodes are
+    /// sorted by this parameter.
+    pub fn ray(&self, ray: &Ray) -> Vec<(Handle<Node>, f32)> {
+        let mut result = Vec::new();
+        self.graph.spatial_index().bvh.query_ray(ray, |_, handle| {
+            if let Some((_, bounds)) = self.accept(*handle) {
+                if let Some((t_min, t_max)) = ray_aabb_intersection(ray, &bounds) {
+                    if t_max >= 0.0 && t_min <= 1.0 {
+                        result.push((*handle, t_min.max(0.0)));
+                    }
+                }
+            }
+        });
+        result.sort_by(|a, b| a.1.total_cmp(&b.1));
+        result
+    }
+
+    /// Returns at most `k` nodes closest to the given point, the distance is measured to the bounding
+    /// boxes of the nodes. Every node is returned with its distance, the nodes are sorted by the
+    /// distance.
+    pub fn k_nearest(&self, point: Vector3<f32>, k: usize) -> Vec<(Handle<Node>, f32)> {
+        let index = &self.graph.spatial_index().bvh;
+        index
+            .k_nearest(point, k, |_, handle| {
+                self.accept(*handle)
+                    .map(|(_, bounds)| distance_to_aabb(&bounds, point))
+            })
+            .into_iter()
+            .filter_map(|(proxy, distance)| index.data(proxy).map(|handle| (*handle, distance)))
+            .collect()
+    }
+}
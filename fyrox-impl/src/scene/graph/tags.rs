@@ -0,0 +1,99 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Tag and layer index of a graph. See [`TagIndex`] docs for more info.
+
+use crate::{
+    core::pool::Handle,
+    scene::{
+        layer::{LayerMask, LAYER_COUNT},
+        node::Node,
+    },
+};
+use fxhash::{FxHashMap, FxHashSet};
+
+#[derive(Debug)]
+struct IndexEntry {
+    tags: Vec<String>,
+    layers: LayerMask,
+}
+
+/// Tag and layer index allows to quickly find nodes with a particular tag or nodes in a particular
+/// layer, without iterating over the entire graph. The index could contain stale entries (for
+/// example - for nodes that were temporarily taken out of the graph), so the results must be
+/// validated by the caller.
+#[derive(Debug, Default)]
+pub(crate) struct TagIndex {
+    tags: FxHashMap<String, FxHashSet<Handle<Node>>>,
+    layers: [FxHashSet<Handle<Node>>; LAYER_COUNT],
+    entries: FxHashMap<Handle<Node>, IndexEntry>,
+}
+
+impl TagIndex {
+    /// Adds the node to the index or updates its entry if it is already indexed.
+    pub fn insert(&mut self, handle: Handle<Node>, node: &Node) {
+        self.remove(handle);
+
+        let mut tags = node.tags().to_vec();
+        if !node.tag().is_empty() && !tags.iter().any(|tag| tag == node.tag()) {
+            tags.push(node.tag_owned());
+        }
+
+        for tag in tags.iter() {
+            self.tags.entry(tag.clone()).or_default().insert(handle);
+        }
+
+        let layers = node.layers();
+        for layer in layers.layers() {
+            self.layers[layer].insert(handle);
+        }
+
+        self.entries.insert(handle, IndexEntry { tags, layers });
+    }
+
+    /// Removes the node from the index.
+    pub fn remove(&mut self, handle: Handle<Node>) {
+        let Some(entry) = self.entries.remove(&handle) else {
+            return;
+        };
+
+        for tag in entry.tags.iter() {
+            if let Some(set) = self.tags.get_mut(tag) {
+                set.remove(&handle);
+                if set.is_empty() {
+                    self.tags.remove(tag);
+                }
+            }
+        }
+
+        for layer in entry.layers.layers() {
+            self.layers[layer].remove(&handle);
+        }
+    }
+
+    /// Returns an iterator over the nodes with the given tag.
+    pub fn tagged<'a>(&'a self, tag: &str) -> impl Iterator<Item = Handle<Node>> + 'a {
+        self.tags.get(tag).into_iter().flatten().copied()
+    }
+
+    /// Returns an iterator over the nodes in the given layer.
+    pub fn in_layer(&self, layer: usize) -> impl Iterator<Item = Handle<Node>> + '_ {
+        self.layers.get(layer).into_iter().flatten().copied()
+    }
+}
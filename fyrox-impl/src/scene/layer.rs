@@ -0,0 +1,532 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Tags and layers allow you to classify scene nodes. See [`LayerMask`] and [`TagLayerRegistry`]
+//! docs for more info.
+//!
+//! ## Tags
+//!
+//! A tag is an arbitrary string, that can be used to mark a scene node as a part of some group
+//! (for example - `Enemy`, `Pickup`, `Door`, etc.). A node can have any number of tags. Nodes with
+//! a particular tag can be quickly fetched from a graph using [`crate::scene::graph::Graph::nodes_with_tag`].
+//!
+//! ## Layers
+//!
+//! There are 32 layers and every scene node can belong to any combination of them. Layers are
+//! used to filter objects rendered by cameras (see [`crate::scene::camera::Camera::set_culling_mask`])
+//! and to define which objects can collide with each other (see [`LayerInteractions`]). Nodes in
+//! a particular layer can be fetched from a graph using [`crate::scene::graph::Graph::nodes_in_layer`].
+
+use crate::{
+    core::{
+        num_traits::{NumCast, One, ToPrimitive, Zero},
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid_provider,
+        visitor::prelude::*,
+    },
+    scene::collider::{BitMask, InteractionGroups},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    fs::File,
+    io::Write,
+    ops::{Add, BitAnd, BitOr, Mul, Not, Shl},
+    path::Path,
+};
+
+/// Total amount of layers.
+pub const LAYER_COUNT: usize = 32;
+
+/// A set of layers packed in a 32-bit integer. Every bit of the mask corresponds to a layer with
+/// the same index.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::scene::layer::LayerMask;
+/// let mask = LayerMask::from_layer(0).with_layer(3);
+/// assert!(mask.contains_layer(3));
+/// assert!(!mask.contains_layer(1));
+/// assert!(mask.intersects(LayerMask::from_layer(3)));
+/// assert_eq!(mask.layers().collect::<Vec<_>>(), vec![0, 3]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Reflect, Serialize, Deserialize)]
+pub struct LayerMask(pub u32);
+
+uuid_provider!(LayerMask = "0e3a0c8d-8f6c-4d1b-9b3e-5c7f2a6d4e91");
+
+impl Default for LayerMask {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Visit for LayerMask {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        self.0.visit(name, visitor)
+    }
+}
+
+impl LayerMask {
+    /// A mask without any layers.
+    pub const NONE: Self = Self(0);
+
+    /// A mask with all the layers.
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// Default layer mask of every scene node - the node belongs to the first layer only.
+    pub const DEFAULT: Self = Self(1);
+
+    /// Creates a mask with a single layer. Layers with indices outside of `0..LAYER_COUNT` range
+    /// are ignored.
+    #[inline]
+    pub fn from_layer(layer: usize) -> Self {
+        Self::NONE.with_layer(layer)
+    }
+
+    /// Creates a mask from a set of layer indices. Layers with indices outside of `0..LAYER_COUNT`
+    /// range are ignored.
+    #[inline]
+    pub fn from_layers(layers: impl IntoIterator<Item = usize>) -> Self {
+        layers
+            .into_iter()
+            .fold(Self::NONE, |mask, layer| mask.with_layer(layer))
+    }
+
+    /// Returns `true` if the mask contains the given layer.
+    #[inline]
+    pub fn contains_layer(self, layer: usize) -> bool {
+        layer < LAYER_COUNT && self.0 & (1 << layer) != 0
+    }
+
+    /// Returns a copy of the mask with the given layer added.
+    #[inline]
+    #[must_use]
+    pub fn with_layer(mut self, layer: usize) -> Self {
+        self.set_layer(layer, true);
+        self
+    }
+
+    /// Returns a copy of the mask with the given layer removed.
+    #[inline]
+    #[must_use]
+    pub fn without_layer(mut self, layer: usize) -> Self {
+        self.set_layer(layer, false);
+        self
+    }
+
+    /// Adds or removes the given layer.
+    #[inline]
+    pub fn set_layer(&mut self, layer: usize, state: bool) {
+        if layer < LAYER_COUNT {
+            if state {
+                self.0 |= 1 << layer;
+            } else {
+                self.0 &= !(1 << layer);
+            }
+        }
+    }
+
+    /// Returns `true` if both masks have at least one layer in common.
+    #[inline]
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Returns `true` if the mask does not contain any layers.
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns an iterator over the indices of the layers in the mask.
+    #[inline]
+    pub fn layers(self) -> impl Iterator<Item = usize> {
+        (0..LAYER_COUNT).filter(move |layer| self.contains_layer(*layer))
+    }
+}
+
+impl BitOr for LayerMask {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for LayerMask {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Mul for LayerMask {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self(self.0 * rhs.0)
+    }
+}
+
+impl One for LayerMask {
+    fn one() -> Self {
+        Self(1)
+    }
+}
+
+impl Add for LayerMask {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Zero for LayerMask {
+    fn zero() -> Self {
+        Self(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl Shl for LayerMask {
+    type Output = Self;
+
+    fn shl(self, rhs: Self) -> Self::Output {
+        Self(self.0 << rhs.0)
+    }
+}
+
+impl Not for LayerMask {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self(!self.0)
+    }
+}
+
+impl ToPrimitive for LayerMask {
+    fn to_i64(&self) -> Option<i64> {
+        Some(self.0 as i64)
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        Some(self.0 as u64)
+    }
+}
+
+impl NumCast for LayerMask {
+    fn from<T: ToPrimitive>(n: T) -> Option<Self> {
+        n.to_u32().map(Self)
+    }
+}
+
+/// Layer interaction matrix defines which layers can collide with each other. Two colliders can
+/// interact only if **both** of their layers allow the interaction. Use
+/// [`crate::scene::graph::Graph::set_layer_interactions`] to apply the matrix to a graph, the matrix
+/// is saved together with the graph.
+#[derive(Clone, PartialEq, Eq, Debug, Visit)]
+pub struct LayerInteractions {
+    rows: [LayerMask; LAYER_COUNT],
+}
+
+impl Default for LayerInteractions {
+    fn default() -> Self {
+        Self {
+            rows: [LayerMask::ALL; LAYER_COUNT],
+        }
+    }
+}
+
+impl LayerInteractions {
+    /// Creates a new interaction matrix where every layer collides with every other layer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables or disables the interaction between two layers. The matrix is kept symmetric.
+    pub fn set(&mut self, a: usize, b: usize, interacts: bool) {
+        if a < LAYER_COUNT && b < LAYER_COUNT {
+            self.rows[a].set_layer(b, interacts);
+            self.rows[b].set_layer(a, interacts);
+        }
+    }
+
+    /// Returns `true` if the given layers can interact with each other.
+    pub fn interacts(&self, a: usize, b: usize) -> bool {
+        a < LAYER_COUNT
+            && b < LAYER_COUNT
+            && self.rows[a].contains_layer(b)
+            && self.rows[b].contains_layer(a)
+    }
+
+    /// Returns a set of layers the given layer can collide with.
+    pub fn row(&self, layer: usize) -> LayerMask {
+        self.rows.get(layer).copied().unwrap_or(LayerMask::NONE)
+    }
+
+    /// Sets a set of layers the given layer can collide with. Unlike [`Self::set`], this method
+    /// does not keep the matrix symmetric.
+    pub fn set_row(&mut self, layer: usize, mask: LayerMask) {
+        if let Some(row) = self.rows.get_mut(layer) {
+            *row = mask;
+        }
+    }
+
+    /// Returns a union of the layers, that can collide with any layer of the given mask.
+    pub fn filter_for(&self, layers: LayerMask) -> LayerMask {
+        layers
+            .layers()
+            .fold(LayerMask::NONE, |filter, layer| filter | self.rows[layer])
+    }
+
+    /// Calculates collision groups for an object with the given layers. The result is exact for
+    /// objects belonging to a single layer, objects with multiple layers collide with everything
+    /// that any of their layers can collide with.
+    pub fn interaction_groups(&self, layers: LayerMask) -> InteractionGroups {
+        InteractionGroups::new(BitMask(layers.0), BitMask(self.filter_for(layers).0))
+    }
+}
+
+/// Layer definition describes a single layer in a [`TagLayerRegistry`].
+#[derive(Clone, PartialEq, Eq, Debug, Reflect, Serialize, Deserialize, TypeUuidProvider)]
+#[type_uuid(id = "6c1d2f4e-3a5b-4e8f-a2c7-9d0b1e3f5a71")]
+pub struct LayerDefinition {
+    /// Human-readable name of the layer.
+    #[reflect(description = "Human-readable name of the layer.")]
+    pub name: String,
+
+    /// A set of layers objects in this layer can collide with.
+    #[reflect(description = "A set of layers objects in this layer can collide with.")]
+    pub collides_with: LayerMask,
+}
+
+impl Default for LayerDefinition {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            collides_with: LayerMask::ALL,
+        }
+    }
+}
+
+/// An error that may occur during tag and layer registry loading or saving.
+#[derive(Debug)]
+pub enum TagLayerRegistryError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// Unable to parse the registry.
+    Parse(ron::error::SpannedError),
+    /// Unable to serialize the registry.
+    Serialize(ron::Error),
+}
+
+impl Display for TagLayerRegistryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Io error: {err}"),
+            Self::Parse(err) => write!(f, "Parse error: {err}"),
+            Self::Serialize(err) => write!(f, "Serialization error: {err}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for TagLayerRegistryError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for TagLayerRegistryError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<ron::Error> for TagLayerRegistryError {
+    fn from(err: ron::Error) -> Self {
+        Self::Serialize(err)
+    }
+}
+
+/// A project-wide registry of known tags and layers. The registry itself does not affect scene
+/// nodes, it only gives names to layers and stores the layer interaction matrix. The editor applies
+/// the interactions to every scene it edits, so they are saved together with the scenes and applied
+/// automatically when a scene is loaded by the game. The editor also saves the registry to
+/// [`TagLayerRegistry::DEFAULT_PATH`], so the game could load it and apply the interactions to the
+/// scenes created from code using [`crate::scene::graph::Graph::set_layer_interactions`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::scene::{graph::Graph, layer::TagLayerRegistry};
+/// fn setup_physics_layers(graph: &mut Graph) {
+///     if let Ok(registry) = TagLayerRegistry::load(TagLayerRegistry::DEFAULT_PATH) {
+///         graph.set_layer_interactions(Some(registry.interactions()));
+///     }
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Reflect, Serialize, Deserialize)]
+pub struct TagLayerRegistry {
+    /// A list of known tags.
+    #[serde(default)]
+    #[reflect(description = "A list of known tags.")]
+    pub tags: Vec<String>,
+
+    /// A list of layers, index of a definition in the list is the index of the layer. Only first
+    /// [`LAYER_COUNT`] definitions are used.
+    #[serde(default)]
+    #[reflect(
+        description = "A list of layers, index of a definition in the list is the index of the layer. Only first 32 definitions are used."
+    )]
+    pub layers: Vec<LayerDefinition>,
+}
+
+impl Default for TagLayerRegistry {
+    fn default() -> Self {
+        Self {
+            tags: Default::default(),
+            layers: vec![LayerDefinition {
+                name: "Default".to_string(),
+                collides_with: LayerMask::ALL,
+            }],
+        }
+    }
+}
+
+impl TagLayerRegistry {
+    /// Default path of the registry file.
+    pub const DEFAULT_PATH: &'static str = "tags_and_layers.ron";
+
+    /// Loads the registry from the given file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TagLayerRegistryError> {
+        let file = File::open(path)?;
+        Ok(ron::de::from_reader(file)?)
+    }
+
+    /// Saves the registry to the given file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TagLayerRegistryError> {
+        let mut file = File::create(path)?;
+        file.write_all(
+            ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?.as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// Returns `true` if the registry contains the given tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Returns an index of a layer with the given name.
+    pub fn layer_index(&self, name: &str) -> Option<usize> {
+        self.layers
+            .iter()
+            .take(LAYER_COUNT)
+            .position(|layer| layer.name == name)
+    }
+
+    /// Returns a name of the layer with the given index.
+    pub fn layer_name(&self, layer: usize) -> Option<&str> {
+        self.layers
+            .get(layer)
+            .filter(|_| layer < LAYER_COUNT)
+            .map(|layer| layer.name.as_str())
+    }
+
+    /// Creates a layer mask from the given layer names. Unknown names are ignored.
+    pub fn layer_mask<'a>(&self, names: impl IntoIterator<Item = &'a str>) -> LayerMask {
+        LayerMask::from_layers(names.into_iter().filter_map(|name| self.layer_index(name)))
+    }
+
+    /// Creates layer interaction matrix from the layer definitions. Layers without a definition
+    /// collide with everything.
+    pub fn interactions(&self) -> LayerInteractions {
+        let mut interactions = LayerInteractions::new();
+        for (index, layer) in self.layers.iter().take(LAYER_COUNT).enumerate() {
+            interactions.set_row(index, layer.collides_with);
+        }
+        interactions
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::visitor::prelude::*,
+        scene::layer::{LayerDefinition, LayerInteractions, LayerMask, TagLayerRegistry},
+    };
+
+    #[test]
+    fn test_layer_interactions() {
+        let mut interactions = LayerInteractions::new();
+        assert!(interactions.interacts(0, 1));
+        interactions.set(0, 1, false);
+        assert!(!interactions.interacts(0, 1));
+        assert!(!interactions.interacts(1, 0));
+        assert!(interactions.interacts(0, 0));
+
+        let groups = interactions.interaction_groups(LayerMask::from_layer(0));
+        assert_eq!(groups.memberships.0, 1);
+        assert!(!LayerMask(groups.filter.0).contains_layer(1));
+        assert!(LayerMask(groups.filter.0).contains_layer(2));
+
+        let mut visitor = Visitor::new();
+        interactions.visit("Interactions", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+        let mut visitor = Visitor::load_from_memory(&data).unwrap();
+        let mut loaded = LayerInteractions::new();
+        loaded.visit("Interactions", &mut visitor).unwrap();
+        assert_eq!(loaded, interactions);
+    }
+
+    #[test]
+    fn test_registry() {
+        let registry = TagLayerRegistry {
+            tags: vec!["Enemy".to_string()],
+            layers: vec![
+                LayerDefinition {
+                    name: "Default".to_string(),
+                    collides_with: LayerMask::ALL,
+                },
+                LayerDefinition {
+                    name: "Ghost".to_string(),
+                    collides_with: LayerMask::from_layer(1),
+                },
+            ],
+        };
+
+        assert!(registry.has_tag("Enemy"));
+        assert_eq!(registry.layer_index("Ghost"), Some(1));
+        assert_eq!(registry.layer_name(1), Some("Ghost"));
+        assert_eq!(registry.layer_mask(["Default", "Ghost", "Unknown"]).0, 0b11);
+
+        let interactions = registry.interactions();
+        assert!(interactions.interacts(1, 1));
+        assert!(!interactions.interacts(0, 1));
+        assert!(interactions.interacts(0, 2));
+    }
+}
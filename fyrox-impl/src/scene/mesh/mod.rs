@@ -49,7 +49,7 @@ use crate::{
     scene::{
         base::{Base, BaseBuilder},
         debug::{Line, SceneDrawingContext},
-        graph::Graph,
+        graph::{Graph, NodePool},
         mesh::{
             buffer::{
                 BytesStorage, TriangleBuffer, TriangleBufferRefMut, VertexAttributeDescriptor,
@@ -58,7 +58,7 @@ use crate::{
             },
             surface::{BlendShape, Surface, SurfaceData, SurfaceResource},
         },
-        node::{Node, NodeTrait, RdcControlFlow, SyncContext},
+        node::{Node, NodeTrait, RdcControlFlow, SyncContext, UpdateContext},
     },
 };
 use fxhash::{FxHashMap, FxHasher};
@@ -355,6 +355,10 @@ pub struct Mesh {
     #[visit(skip)]
     world_bounding_box: Cell<AxisAlignedBoundingBox>,
 
+    #[reflect(hidden)]
+    #[visit(skip)]
+    vertex_buffers_modifications: Cell<u64>,
+
     #[reflect(hidden)]
     #[visit(skip)]
     batch_container: BatchContainerWrapper,
@@ -368,6 +372,7 @@ impl Default for Mesh {
             local_bounding_box: Default::default(),
             world_bounding_box: Default::default(),
             local_bounding_box_dirty: Cell::new(true),
+            vertex_buffers_modifications: Default::default(),
             render_path: InheritableVariable::new_modified(RenderPath::Deferred),
             batching_mode: Default::default(),
             blend_shapes_property_name: Mesh::DEFAULT_BLEND_SHAPES_PROPERTY_NAME.to_string(),
@@ -419,6 +424,29 @@ impl Mesh {
         self.surfaces.get_value_mut_silent()
     }
 
+    /// Recalculates world-space bounding box of the mesh using the given global transform. The box is
+    /// refreshed automatically when the global transform of the mesh changes, this method should be
+    /// used when vertices of the mesh are modified procedurally (for example, by a cloth simulation).
+    pub fn update_world_bounding_box(&self, global_transform: &Matrix4<f32>, nodes: &NodePool) {
+        if self.surfaces.iter().any(|s| !s.bones.is_empty()) {
+            let mut world_aabb = self.local_bounding_box().transform(global_transform);
+
+            // Special case for skinned meshes.
+            for surface in self.surfaces.iter() {
+                for &bone in surface.bones() {
+                    if let Some(node) = nodes.try_borrow(bone) {
+                        world_aabb.add_point(node.global_position())
+                    }
+                }
+            }
+
+            self.world_bounding_box.set(world_aabb)
+        } else {
+            self.world_bounding_box
+                .set(self.local_bounding_box().transform(global_transform));
+        }
+    }
+
     /// Removes all surfaces from mesh.
     #[inline]
     pub fn clear_surfaces(&mut self) {
@@ -643,29 +671,42 @@ impl NodeTrait for Mesh {
 
     fn on_global_transform_changed(
         &self,
-        _new_global_transform: &Matrix4<f32>,
+        new_global_transform: &Matrix4<f32>,
         context: &mut SyncContext,
     ) {
-        if self.surfaces.iter().any(|s| !s.bones.is_empty()) {
-            let mut world_aabb = self
-                .local_bounding_box()
-                .transform(&self.global_transform());
+        // Global transform of the node is not yet updated at this point, so the new one must be used.
+        self.update_world_bounding_box(new_global_transform, context.nodes);
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        // Vertices of the surfaces could be modified directly (for example - by a cloth), so the
+        // local bounding box must be refreshed in this case.
+        let modifications = self
+            .surfaces
+            .iter()
+            .filter(|surface| surface.data_ref().is_ok())
+            .map(|surface| {
+                surface
+                    .data_ref()
+                    .data_ref()
+                    .vertex_buffer
+                    .modifications_count()
+            })
+            .sum::<u64>();
+        if self.vertex_buffers_modifications.replace(modifications) != modifications {
+            self.local_bounding_box_dirty.set(true);
+        }
 
-            // Special case for skinned meshes.
-            for surface in self.surfaces.iter() {
-                for &bone in surface.bones() {
-                    if let Some(node) = context.nodes.try_borrow(bone) {
-                        world_aabb.add_point(node.global_position())
-                    }
-                }
-            }
+        let is_skinned = self.surfaces.iter().any(|s| !s.bones.is_empty());
+        if !is_skinned && !self.local_bounding_box_dirty.get() {
+            return;
+        }
 
-            self.world_bounding_box.set(world_aabb)
-        } else {
-            self.world_bounding_box.set(
-                self.local_bounding_box()
-                    .transform(&self.global_transform()),
-            );
+        let old_bounds = self.world_bounding_box.get();
+        self.update_world_bounding_box(&self.global_transform(), context.nodes);
+        let new_bounds = self.world_bounding_box.get();
+        if old_bounds.min != new_bounds.min || old_bounds.max != new_bounds.max {
+            self.invalidate_bounds();
         }
     }
 
@@ -933,6 +974,7 @@ impl MeshBuilder {
             local_bounding_box_dirty: Cell::new(true),
             render_path: self.render_path.into(),
             world_bounding_box: Default::default(),
+            vertex_buffers_modifications: Default::default(),
             batching_mode: self.batching_mode.into(),
             batch_container: Default::default(),
             blend_shapes_property_name: self.blend_shapes_property_name,
@@ -28,12 +28,14 @@ pub mod accel;
 pub mod animation;
 pub mod base;
 pub mod camera;
+pub mod cloth;
 pub mod collider;
 pub mod debug;
 pub mod decal;
 pub mod dim2;
 pub mod graph;
 pub mod joint;
+pub mod layer;
 pub mod light;
 pub mod mesh;
 pub mod navmesh;
@@ -41,6 +43,7 @@ pub mod node;
 pub mod particle_system;
 pub mod pivot;
 pub mod ragdoll;
+pub mod replication;
 pub mod rigidbody;
 pub mod sound;
 pub mod sprite;
@@ -67,7 +70,10 @@ use crate::{
         base::BaseBuilder,
         camera::Camera,
         debug::SceneDrawingContext,
-        graph::{Graph, GraphPerformanceStatistics, GraphUpdateSwitches},
+        graph::{
+            query::{GraphQuery, Query, QueryError},
+            Graph, GraphPerformanceStatistics, GraphUpdateSwitches,
+        },
         navmesh::NavigationalMeshBuilder,
         node::Node,
         sound::SoundEngine,
@@ -267,7 +273,8 @@ impl Display for PerformanceStatistics {
             \tPhysics 2D: {:?}\n\
             \t\tSimulation: {:?}\n\
             \t\tRay cast: {:?}\n\
-            \tHierarchy: {:?}",
+            \tHierarchy: {:?}\n\
+            \tSpatial Index: {:?}",
             self.graph.total(),
             self.graph.sync_time,
             self.graph.sound_update_time,
@@ -278,6 +285,7 @@ impl Display for PerformanceStatistics {
             self.graph.physics2d.step_time,
             self.graph.physics2d.total_ray_cast_time.get(),
             self.graph.hierarchical_properties_time,
+            self.graph.spatial_index_time,
         )
     }
 }
@@ -430,6 +438,13 @@ impl Scene {
         self.performance_statistics.graph = self.graph.performance_statistics.clone();
     }
 
+    /// Creates a new entity-component style query over the nodes of the scene graph. It is a
+    /// shortcut for [`Graph::query`], see its docs for more info.
+    #[inline]
+    pub fn query<Q: Query>(&mut self) -> Result<GraphQuery<'_, Q>, QueryError> {
+        self.graph.query()
+    }
+
     /// Creates deep copy of a scene, filter predicate allows you to filter out nodes
     /// by your criteria.
     pub fn clone<F, Pre, Post>(
@@ -38,9 +38,13 @@ use crate::{
         base::{Base, BaseBuilder},
         debug::{Line, SceneDrawingContext},
         graph::Graph,
-        node::{Node, NodeTrait},
+        node::{Node, NodeTrait, UpdateContext},
+    },
+    utils::navmesh::{
+        crowd::Crowd,
+        generation::{NavmeshGenerationSettings, NavmeshGeometry},
+        Navmesh,
     },
-    utils::navmesh::Navmesh,
 };
 use fyrox_core::algebra::Vector3;
 use fyrox_core::math::TriangleDefinition;
@@ -102,6 +106,27 @@ impl Visit for Container {
 /// }
 /// ```
 ///
+/// ## Generation
+///
+/// Navigational mesh could also be generated automatically from static meshes and colliders of a scene. Generation is controlled
+/// by [`NavmeshGenerationSettings`] stored in the node, see [`crate::utils::navmesh::generation`] module docs for more info. The
+/// editor uses the same settings when the "Generate" button in the navmesh panel is pressed.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     graph::SceneGraph,
+/// #     scene::{navmesh::NavigationalMesh, node::Node, Scene},
+/// #     utils::navmesh::generation::NavmeshGeometry,
+/// # };
+/// fn regenerate(scene: &mut Scene, navmesh: Handle<Node>) {
+///     let geometry = NavmeshGeometry::from_graph(&scene.graph);
+///     if let Some(navmesh) = scene.graph.try_get_mut_of_type::<NavigationalMesh>(navmesh) {
+///         navmesh.generate(&geometry);
+///     }
+/// }
+/// ```
+///
 /// ## Agents
 ///
 /// Navigational mesh agent helps you to build paths along the surface of a navigational mesh and follow it. Agents can be
@@ -154,11 +179,46 @@ impl Visit for Container {
 ///     scene.graph[handle].as_navigational_mesh_mut()
 /// }
 /// ```
+///
+/// ## Crowds
+///
+/// Agents updated individually ignore each other, so they could walk through each other. Every navigational mesh has a
+/// [`Crowd`], that simulates its agents together with local avoidance. Add an agent to the crowd and then just change its
+/// target, the crowd is updated automatically every frame:
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, pool::Handle},
+/// #     scene::navmesh::NavigationalMesh,
+/// #     utils::navmesh::{NavmeshAgent, NavmeshAgentBuilder},
+/// # };
+/// fn spawn_agent(navmesh: &mut NavigationalMesh, position: Vector3<f32>) -> Handle<NavmeshAgent> {
+///     navmesh.crowd_mut().add_agent(
+///         NavmeshAgentBuilder::new()
+///             .with_position(position)
+///             .with_radius(0.4)
+///             .with_speed(2.0)
+///             .build(),
+///     )
+/// }
+///
+/// fn follow(navmesh: &mut NavigationalMesh, agent: Handle<NavmeshAgent>, target: Vector3<f32>) {
+///     if let Some(agent) = navmesh.crowd_mut().agent_mut(agent) {
+///         agent.set_target(target);
+///         // Use agent.position() as the position of your game character.
+///     }
+/// }
+/// ```
 #[derive(Debug, Clone, Visit, Reflect, Default, ComponentProvider)]
 pub struct NavigationalMesh {
     base: Base,
     #[reflect(read_only)]
     navmesh: InheritableVariable<Container>,
+    #[reflect(hidden)]
+    #[visit(optional)]
+    crowd: Crowd,
+    #[visit(optional)]
+    generation_settings: InheritableVariable<NavmeshGenerationSettings>,
 }
 
 impl TypeUuidProvider for NavigationalMesh {
@@ -215,6 +275,12 @@ impl NodeTrait for NavigationalMesh {
         Self::type_uuid()
     }
 
+    fn update(&mut self, context: &mut UpdateContext) {
+        if self.crowd.agents_count() > 0 {
+            self.crowd.update(context.dt, &self.navmesh.0.read());
+        }
+    }
+
     fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
         let navmesh = self.navmesh.0.read();
 
@@ -231,6 +297,16 @@ impl NodeTrait for NavigationalMesh {
                 });
             }
         }
+
+        for link in navmesh.off_mesh_links() {
+            ctx.draw_sphere(link.start, 6, 6, 0.1, Color::ORANGE);
+            ctx.draw_sphere(link.end, 6, 6, 0.1, Color::ORANGE);
+            ctx.add_line(Line {
+                begin: link.start,
+                end: link.end,
+                color: Color::ORANGE,
+            });
+        }
     }
 }
 
@@ -250,12 +326,46 @@ impl NavigationalMesh {
     pub fn navmesh(&self) -> Arc<RwLock<Navmesh>> {
         self.navmesh.0.clone()
     }
+
+    /// Returns a reference to the crowd of the navigational mesh.
+    pub fn crowd(&self) -> &Crowd {
+        &self.crowd
+    }
+
+    /// Returns a reference to the crowd of the navigational mesh. The crowd is updated automatically
+    /// every frame.
+    pub fn crowd_mut(&mut self) -> &mut Crowd {
+        &mut self.crowd
+    }
+
+    /// Returns current navmesh generation settings.
+    pub fn generation_settings(&self) -> &NavmeshGenerationSettings {
+        &self.generation_settings
+    }
+
+    /// Sets new navmesh generation settings. The navmesh is not regenerated automatically, use
+    /// [`Self::generate`] for that.
+    pub fn set_generation_settings(
+        &mut self,
+        settings: NavmeshGenerationSettings,
+    ) -> NavmeshGenerationSettings {
+        self.generation_settings
+            .set_value_and_mark_modified(settings)
+    }
+
+    /// Replaces the inner navigational mesh with the one generated from the given geometry using
+    /// current generation settings.
+    pub fn generate(&mut self, geometry: &NavmeshGeometry) {
+        let navmesh = Navmesh::from_geometry(geometry, &self.generation_settings);
+        *self.navmesh_mut() = navmesh;
+    }
 }
 
 /// Creates navigational meshes and adds them to a scene graph.
 pub struct NavigationalMeshBuilder {
     base_builder: BaseBuilder,
     navmesh: Navmesh,
+    generation_settings: NavmeshGenerationSettings,
 }
 
 impl NavigationalMeshBuilder {
@@ -264,6 +374,7 @@ impl NavigationalMeshBuilder {
         Self {
             base_builder,
             navmesh: Default::default(),
+            generation_settings: Default::default(),
         }
     }
 
@@ -273,12 +384,20 @@ impl NavigationalMeshBuilder {
         self
     }
 
+    /// Sets the settings, that will be used to generate the navmesh.
+    pub fn with_generation_settings(mut self, settings: NavmeshGenerationSettings) -> Self {
+        self.generation_settings = settings;
+        self
+    }
+
     fn build_navigational_mesh(self) -> NavigationalMesh {
         NavigationalMesh {
             base: self.base_builder.build_base(),
             navmesh: InheritableVariable::new_modified(Container(Arc::new(RwLock::new(
                 self.navmesh,
             )))),
+            crowd: Default::default(),
+            generation_settings: self.generation_settings.into(),
         }
     }
 
@@ -25,6 +25,7 @@ use crate::scene::{
     self,
     animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
     camera::Camera,
+    cloth::Cloth,
     decal::Decal,
     dim2::{self, rectangle::Rectangle},
     light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
@@ -74,6 +75,7 @@ pub fn new_node_constructor_container() -> NodeConstructorContainer {
     container.add::<AnimationBlendingStateMachine>();
     container.add::<NavigationalMesh>();
     container.add::<Ragdoll>();
+    container.add::<Cloth>();
     container.add::<TileMap>();
 
     container
@@ -0,0 +1,1192 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Replication of the state of scene nodes from a server to its clients. See [`ReplicationServer`]
+//! and [`ReplicationClient`] docs for more info.
+//!
+//! Only the nodes with the networked flag (see [`crate::scene::base::Base::set_networked`]) are
+//! replicated. Nodes are matched by their instance ids (see [`crate::scene::base::Base::instance_id`]),
+//! so the nodes of a scene, that is loaded on both sides, are replicated as is. Networked nodes, that
+//! a client does not have (for example, the ones created on the server at runtime), are spawned on
+//! the client: a node of the same type is created using the node constructors of a
+//! [`SerializationContext`] and linked to the node with the same parent id (or to the root). The
+//! serialization context must be registered in the blackboard of the client (see
+//! [`ReplicationClient::blackboard_mut`]), otherwise such nodes are skipped. Removal of networked
+//! nodes on the server is replicated to the clients.
+//!
+//! The server keeps a copy of every networked node, that was made at the last send, and periodically
+//! compares it with the actual node using reflection (see [`patch::diff`]). Changed properties are
+//! sent as JSON values (see [`serde_bridge`]), newly connected clients receive a full snapshot of the
+//! nodes. Changes of local transforms are sent over the unreliable channel, clients ignore the ones,
+//! that are older than already applied, and when a node stops moving its final transform is sent
+//! once more over the reliable channel. Other changes are sent over the reliable channel. Clients
+//! apply the properties using reflection, so custom property setters are called. Floating-point
+//! numbers, 3D vectors and rotations are interpolated to hide the discrete nature of the updates.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        err,
+        json::Value,
+        log::category,
+        net::udp::{
+            Channel, ConnectionId, NetEvent, UdpClient, UdpConfig, UdpServer,
+            MAX_UNRELIABLE_MESSAGE_SIZE,
+        },
+        reflect::{patch, prelude::*, serde_bridge},
+        uuid::Uuid,
+        visitor::Blackboard,
+        warn,
+    },
+    engine::SerializationContext,
+    scene::{
+        base::{Base, SceneNodeId},
+        graph::Graph,
+        node::Node,
+    },
+};
+use fxhash::{FxHashMap, FxHashSet};
+use fyrox_graph::{BaseSceneGraph, SceneGraph};
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    net::{SocketAddr, ToSocketAddrs},
+    time::Duration,
+};
+
+/// Settings of the replication, they should be the same on a server and its clients.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplicationSettings {
+    /// Interval between consecutive sends of the changes.
+    pub send_interval: Duration,
+    /// Time of interpolation of numeric properties on the client side. Zero means no interpolation.
+    pub interpolation_time: Duration,
+    /// A list of paths of properties (relative to a node), that won't be replicated. Nested
+    /// properties of the ignored ones are ignored too. Read-only properties are never replicated.
+    pub ignored_properties: Vec<String>,
+}
+
+impl Default for ReplicationSettings {
+    fn default() -> Self {
+        Self {
+            send_interval: Duration::from_millis(50),
+            interpolation_time: Duration::from_millis(100),
+            ignored_properties: vec!["base.scripts".to_string()],
+        }
+    }
+}
+
+impl ReplicationSettings {
+    fn is_ignored(&self, path: &str) -> bool {
+        self.ignored_properties.iter().any(|ignored| {
+            path.strip_prefix(ignored.as_str()).is_some_and(|rest| {
+                rest.is_empty() || rest.starts_with('.') || rest.starts_with('[')
+            })
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ReplicatedProperty {
+    path: String,
+    // Values are stored as JSON strings, because bincode does not support self-describing data.
+    value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+enum ReplicationMessage {
+    Spawn {
+        node: u128,
+        type_uuid: u128,
+        parent: Option<u128>,
+        properties: Vec<ReplicatedProperty>,
+    },
+    Update {
+        node: u128,
+        snap: bool,
+        properties: Vec<ReplicatedProperty>,
+    },
+    Transform {
+        node: u128,
+        tick: u64,
+        properties: Vec<ReplicatedProperty>,
+    },
+    Despawn {
+        node: u128,
+    },
+    User(Vec<u8>),
+}
+
+impl ReplicationMessage {
+    fn decode(data: &[u8]) -> Option<Self> {
+        match bincode::deserialize(data) {
+            Ok(message) => Some(message),
+            Err(err) => {
+                warn!(category: category::NET, "Malformed replication message. Reason: {err}");
+                None
+            }
+        }
+    }
+}
+
+fn encode(message: &ReplicationMessage) -> io::Result<Vec<u8>> {
+    bincode::serialize(message).map_err(io::Error::other)
+}
+
+/// Returns a path of the local transform of the node. Nodes embed their base at different depths
+/// (for example, lights have it at `base_light.base`), so the base is searched by its address.
+fn transform_path(node: &Node) -> Option<String> {
+    fn find(value: &dyn Reflect, base: &Base, path: String, depth: usize) -> Option<String> {
+        let mut result = None;
+        value.as_any(&mut |any| {
+            if any
+                .downcast_ref::<Base>()
+                .is_some_and(|candidate| std::ptr::eq(candidate, base))
+            {
+                result = Some(join(&path, "local_transform"));
+            }
+        });
+        if result.is_none() && depth > 0 {
+            value.fields_info(&mut |fields| {
+                for field in fields {
+                    if result.is_none() {
+                        result = find(
+                            field.reflect_value,
+                            base,
+                            join(&path, field.name),
+                            depth - 1,
+                        );
+                    }
+                }
+            });
+        }
+        result
+    }
+
+    find(node, node, String::new(), 4)
+}
+
+fn is_transform(path: &str, transform_path: &str) -> bool {
+    path.strip_prefix(transform_path)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+/// Checks whether a property at the path or any of its owners is read-only.
+fn is_read_only(node: &dyn Reflect, path: &str) -> bool {
+    let mut read_only = false;
+    let mut owner_end = 0;
+    for segment in path.split('.') {
+        let owner_path = &path[..owner_end.max(1) - 1];
+        let name = segment.split('[').next().unwrap_or_default();
+        owner_end += segment.len() + 1;
+
+        let mut check = |owner: &dyn Reflect| {
+            owner.fields_info(&mut |fields| {
+                read_only |= fields
+                    .iter()
+                    .any(|field| field.name == name && field.read_only)
+            })
+        };
+        if owner_path.is_empty() {
+            check(node);
+        } else {
+            node.resolve_path(owner_path, &mut |owner| {
+                if let Ok(owner) = owner {
+                    check(owner)
+                }
+            });
+        }
+
+        if read_only {
+            break;
+        }
+    }
+    read_only
+}
+
+/// Splits the value into properties, skipping ignored and read-only ones. Structures are split
+/// into separate fields, so a failure to apply one field on the client side won't affect the others.
+fn collect_properties(
+    value: &dyn Reflect,
+    json: Value,
+    path: String,
+    settings: &ReplicationSettings,
+    properties: &mut Vec<(String, Value)>,
+) {
+    let Value::Object(mut map) = json else {
+        properties.push((path, json));
+        return;
+    };
+
+    let mut fields = Vec::new();
+    value.fields_info(&mut |info| {
+        // Keys of the structure must match the fields exactly, otherwise it is something else
+        // (an enumeration for example) and it must be replicated as a whole.
+        if map
+            .keys()
+            .all(|key| info.iter().any(|field| field.name == key))
+        {
+            for field in info {
+                if let Some(json) = map.remove(field.name) {
+                    fields.push((field.name.to_string(), field.read_only, json));
+                }
+            }
+        }
+    });
+
+    if !map.is_empty() {
+        properties.push((path, Value::Object(map)));
+        return;
+    }
+
+    for (name, read_only, json) in fields {
+        let field_path = join(&path, &name);
+        if read_only || settings.is_ignored(&field_path) {
+            continue;
+        }
+        value.field(&name, &mut |field| {
+            if let Some(field) = field {
+                collect_properties(
+                    field,
+                    json.clone(),
+                    field_path.clone(),
+                    settings,
+                    properties,
+                )
+            }
+        });
+    }
+}
+
+fn properties_to_send(properties: Vec<(String, Value)>) -> Vec<ReplicatedProperty> {
+    properties
+        .into_iter()
+        .map(|(path, value)| ReplicatedProperty {
+            path,
+            value: value.to_string(),
+        })
+        .collect()
+}
+
+fn spawn(graph: &Graph, node: &Node, settings: &ReplicationSettings) -> ReplicationMessage {
+    let mut properties = Vec::new();
+    collect_properties(
+        node,
+        serde_bridge::to_value(node),
+        String::new(),
+        settings,
+        &mut properties,
+    );
+    ReplicationMessage::Spawn {
+        node: node.instance_id().0.as_u128(),
+        type_uuid: node.id().as_u128(),
+        parent: (node.parent() != graph.get_root())
+            .then(|| graph.try_get(node.parent()))
+            .flatten()
+            .map(|parent| parent.instance_id().0.as_u128()),
+        properties: properties_to_send(properties),
+    }
+}
+
+fn transform_properties(node: &Node, settings: &ReplicationSettings) -> Vec<(String, Value)> {
+    let mut properties = Vec::new();
+    if let Some(path) = transform_path(node) {
+        node.resolve_path(&path, &mut |value| {
+            if let Ok(value) = value {
+                collect_properties(
+                    value,
+                    serde_bridge::to_value(value),
+                    path.clone(),
+                    settings,
+                    &mut properties,
+                );
+            }
+        });
+    }
+    properties
+}
+
+/// Networked nodes in the order of the hierarchy, so parents are spawned before their children.
+fn networked_nodes(graph: &Graph) -> impl Iterator<Item = &Node> {
+    graph
+        .traverse_iter(graph.get_root())
+        .map(|(_, node)| node)
+        .filter(|node| node.is_networked())
+}
+
+fn make_baseline(node: &Node) -> Node {
+    let mut baseline = node.clone_box();
+    // Scripts are not needed for comparison and they must not be destroyed with the copy.
+    baseline.scripts.clear();
+    baseline
+}
+
+/// Server side of the replication. It wraps a [`UdpServer`] and sends the state of the networked
+/// nodes of a graph to every connected [`ReplicationClient`]. [`ReplicationServer::update`] must be
+/// called every frame.
+///
+/// ```rust,no_run
+/// use fyrox_impl::{
+///     core::net::udp::UdpConfig,
+///     scene::{graph::Graph, replication::{ReplicationServer, ReplicationSettings}},
+/// };
+///
+/// fn run(graph: &Graph) {
+///     let mut server =
+///         ReplicationServer::bind("0.0.0.0:7777", UdpConfig::default(), Default::default())
+///             .unwrap();
+///     loop {
+///         // Events contain connections, disconnections and user messages of the clients.
+///         for event in server.update(graph, 1.0 / 60.0) {
+///             println!("{event:?}");
+///         }
+///         # break;
+///     }
+/// }
+/// ```
+pub struct ReplicationServer {
+    transport: UdpServer,
+    settings: ReplicationSettings,
+    baselines: FxHashMap<SceneNodeId, Node>,
+    // Nodes, whose last transform change was sent over the unreliable channel.
+    moving: FxHashSet<SceneNodeId>,
+    tick: u64,
+    send_timer: f32,
+}
+
+impl ReplicationServer {
+    /// Creates a new server, that listens at the given address.
+    pub fn bind<A: ToSocketAddrs>(
+        address: A,
+        config: UdpConfig,
+        settings: ReplicationSettings,
+    ) -> io::Result<Self> {
+        Ok(Self::new(UdpServer::bind(address, config)?, settings))
+    }
+
+    /// Creates a new replication server using the given transport.
+    pub fn new(transport: UdpServer, settings: ReplicationSettings) -> Self {
+        Self {
+            transport,
+            settings,
+            baselines: Default::default(),
+            moving: Default::default(),
+            tick: 0,
+            send_timer: 0.0,
+        }
+    }
+
+    /// Returns a reference to the underlying transport.
+    pub fn transport(&self) -> &UdpServer {
+        &self.transport
+    }
+
+    /// Returns the address the server is listening at.
+    pub fn local_address(&self) -> io::Result<SocketAddr> {
+        self.transport.local_address()
+    }
+
+    /// Returns current replication settings.
+    pub fn settings(&self) -> &ReplicationSettings {
+        &self.settings
+    }
+
+    /// Sends a custom message to the given client. It will be returned as [`NetEvent::Message`] from
+    /// [`ReplicationClient::update`].
+    pub fn send(
+        &mut self,
+        connection: ConnectionId,
+        channel: Channel,
+        data: Vec<u8>,
+    ) -> io::Result<()> {
+        let data = encode(&ReplicationMessage::User(data))?;
+        self.transport.send(connection, channel, &data)
+    }
+
+    /// Sends a custom message to every client.
+    pub fn broadcast(&mut self, channel: Channel, data: Vec<u8>) -> io::Result<()> {
+        let data = encode(&ReplicationMessage::User(data))?;
+        self.transport.broadcast(channel, &data)
+    }
+
+    /// Maintains connections, sends a full snapshot of the networked nodes to new clients and
+    /// periodically sends the changes of the nodes to all clients. Returns the events of the
+    /// transport, messages contain user data only.
+    pub fn update(&mut self, graph: &Graph, dt: f32) -> Vec<NetEvent> {
+        let mut events = Vec::new();
+        for event in self.transport.poll() {
+            match event {
+                NetEvent::Connected(connection) => {
+                    self.send_snapshot(graph, connection);
+                    events.push(event);
+                }
+                NetEvent::Message {
+                    connection,
+                    channel,
+                    data,
+                } => {
+                    if let Some(ReplicationMessage::User(data)) = ReplicationMessage::decode(&data)
+                    {
+                        events.push(NetEvent::Message {
+                            connection,
+                            channel,
+                            data,
+                        })
+                    }
+                }
+                NetEvent::Disconnected { .. } => events.push(event),
+            }
+        }
+
+        self.send_timer += dt;
+        if self.send_timer >= self.settings.send_interval.as_secs_f32() {
+            self.send_timer = 0.0;
+            self.send_changes(graph);
+        }
+
+        events
+    }
+
+    fn send_snapshot(&mut self, graph: &Graph, connection: ConnectionId) {
+        for node in networked_nodes(graph) {
+            let result = encode(&spawn(graph, node, &self.settings))
+                .and_then(|data| self.transport.send(connection, Channel::Reliable, &data));
+            if let Err(err) = result {
+                err!(
+                    category: category::NET,
+                    "Unable to send a snapshot to {connection}. Reason: {err}"
+                );
+            }
+        }
+    }
+
+    fn send_changes(&mut self, graph: &Graph) {
+        let mut messages = Vec::new();
+        let mut alive = FxHashSet::default();
+        self.tick += 1;
+
+        for node in networked_nodes(graph) {
+            let id = node.instance_id();
+            alive.insert(id);
+
+            let Some(baseline) = self.baselines.get_mut(&id) else {
+                // The node was just created or became networked.
+                messages.push((Channel::Reliable, spawn(graph, node, &self.settings)));
+                self.baselines.insert(id, make_baseline(node));
+                self.moving.remove(&id);
+                continue;
+            };
+
+            let patch = patch::diff(&**baseline, &**node);
+            if patch.is_empty() {
+                if self.moving.remove(&id) {
+                    // Unreliable transforms could be lost, make sure the final one is delivered.
+                    messages.push((
+                        Channel::Reliable,
+                        ReplicationMessage::Transform {
+                            node: id.0.as_u128(),
+                            tick: self.tick,
+                            properties: properties_to_send(transform_properties(
+                                node,
+                                &self.settings,
+                            )),
+                        },
+                    ));
+                }
+                continue;
+            }
+
+            let mut paths = FxHashSet::default();
+            let mut properties = Vec::new();
+            for path in patch
+                .changes
+                .iter()
+                .map(|change| &change.path)
+                .chain(patch.unsupported.iter())
+            {
+                if paths.insert(path.as_str())
+                    && !self.settings.is_ignored(path)
+                    && !is_read_only(&**node, path)
+                {
+                    node.resolve_path(path, &mut |value| {
+                        if let Ok(value) = value {
+                            collect_properties(
+                                value,
+                                serde_bridge::to_value(value),
+                                path.clone(),
+                                &self.settings,
+                                &mut properties,
+                            );
+                        }
+                    });
+                }
+            }
+
+            let transform_path = transform_path(node);
+            let (transform, properties): (Vec<_>, Vec<_>) =
+                properties.into_iter().partition(|(path, _)| {
+                    transform_path
+                        .as_ref()
+                        .is_some_and(|transform_path| is_transform(path, transform_path))
+                });
+
+            if !properties.is_empty() {
+                messages.push((
+                    Channel::Reliable,
+                    ReplicationMessage::Update {
+                        node: id.0.as_u128(),
+                        snap: false,
+                        properties: properties_to_send(properties),
+                    },
+                ));
+            }
+
+            if !transform.is_empty() {
+                messages.push((
+                    Channel::Unreliable,
+                    ReplicationMessage::Transform {
+                        node: id.0.as_u128(),
+                        tick: self.tick,
+                        properties: properties_to_send(transform),
+                    },
+                ));
+                self.moving.insert(id);
+            } else if self.moving.remove(&id) {
+                messages.push((
+                    Channel::Reliable,
+                    ReplicationMessage::Transform {
+                        node: id.0.as_u128(),
+                        tick: self.tick,
+                        properties: properties_to_send(transform_properties(node, &self.settings)),
+                    },
+                ));
+            }
+
+            *baseline = make_baseline(node);
+        }
+
+        self.baselines.retain(|id, _| {
+            let alive = alive.contains(id);
+            if !alive {
+                messages.push((
+                    Channel::Reliable,
+                    ReplicationMessage::Despawn {
+                        node: id.0.as_u128(),
+                    },
+                ));
+            }
+            alive
+        });
+        self.moving.retain(|id| alive.contains(id));
+
+        for (channel, message) in messages {
+            let result = encode(&message).and_then(|data| {
+                let channel = if data.len() > MAX_UNRELIABLE_MESSAGE_SIZE {
+                    Channel::Reliable
+                } else {
+                    channel
+                };
+                self.transport.broadcast(channel, &data)
+            });
+            if let Err(err) = result {
+                err!(category: category::NET, "Unable to send replication data. Reason: {err}");
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Interpolated {
+    Scalar(f32),
+    Vector(Vector3<f32>),
+    Rotation(UnitQuaternion<f32>),
+}
+
+impl Interpolated {
+    fn read(value: &dyn Reflect) -> Option<Self> {
+        let mut result = None;
+        value.as_any(&mut |any| {
+            result = if let Some(scalar) = any.downcast_ref::<f32>() {
+                Some(Self::Scalar(*scalar))
+            } else if let Some(vector) = any.downcast_ref::<Vector3<f32>>() {
+                Some(Self::Vector(*vector))
+            } else {
+                any.downcast_ref::<UnitQuaternion<f32>>()
+                    .map(|rotation| Self::Rotation(*rotation))
+            }
+        });
+        result
+    }
+
+    fn parse(&self, value: &Value, blackboard: &Blackboard) -> Option<Self> {
+        fn parse<T: Reflect>(mut target: T, value: &Value, blackboard: &Blackboard) -> Option<T> {
+            serde_bridge::apply_value(&mut target, value, blackboard)
+                .ok()
+                .map(|_| target)
+        }
+
+        match self {
+            Self::Scalar(_) => parse(0.0, value, blackboard).map(Self::Scalar),
+            Self::Vector(_) => parse(Vector3::default(), value, blackboard).map(Self::Vector),
+            Self::Rotation(_) => {
+                parse(UnitQuaternion::default(), value, blackboard).map(Self::Rotation)
+            }
+        }
+    }
+
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        match (self, other) {
+            (Self::Scalar(a), Self::Scalar(b)) => Self::Scalar(a + (b - a) * t),
+            (Self::Vector(a), Self::Vector(b)) => Self::Vector(a.lerp(b, t)),
+            (Self::Rotation(a), Self::Rotation(b)) => Self::Rotation(a.nlerp(b, t)),
+            _ => *other,
+        }
+    }
+
+    fn to_value(self) -> Value {
+        match self {
+            Self::Scalar(scalar) => serde_bridge::to_value(&scalar),
+            Self::Vector(vector) => serde_bridge::to_value(&vector),
+            Self::Rotation(rotation) => serde_bridge::to_value(&rotation),
+        }
+    }
+}
+
+struct Interpolation {
+    from: Interpolated,
+    to: Interpolated,
+    elapsed: f32,
+}
+
+/// Client side of the replication. It wraps a [`UdpClient`] and applies the state received from a
+/// [`ReplicationServer`] to the networked nodes of a graph. [`ReplicationClient::update`] must be
+/// called every frame.
+pub struct ReplicationClient {
+    transport: UdpClient,
+    settings: ReplicationSettings,
+    blackboard: Blackboard,
+    interpolations: FxHashMap<(SceneNodeId, String), Interpolation>,
+    transform_ticks: FxHashMap<SceneNodeId, u64>,
+}
+
+impl ReplicationClient {
+    /// Starts connecting to the replication server at the given address.
+    pub fn connect<A: ToSocketAddrs>(
+        address: A,
+        config: UdpConfig,
+        settings: ReplicationSettings,
+    ) -> io::Result<Self> {
+        Ok(Self::new(UdpClient::connect(address, config)?, settings))
+    }
+
+    /// Creates a new replication client using the given transport.
+    pub fn new(transport: UdpClient, settings: ReplicationSettings) -> Self {
+        Self {
+            transport,
+            settings,
+            blackboard: Default::default(),
+            interpolations: Default::default(),
+            transform_ticks: Default::default(),
+        }
+    }
+
+    /// Returns a reference to the underlying transport.
+    pub fn transport(&self) -> &UdpClient {
+        &self.transport
+    }
+
+    /// Returns `true` if the client is connected to the server.
+    pub fn is_connected(&self) -> bool {
+        self.transport.is_connected()
+    }
+
+    /// Returns current replication settings.
+    pub fn settings(&self) -> &ReplicationSettings {
+        &self.settings
+    }
+
+    /// Returns a blackboard, that is used to deserialize the properties. Register a resource manager
+    /// in it to be able to receive resource properties and a [`SerializationContext`] to be able to
+    /// spawn the nodes, that exist only on the server.
+    pub fn blackboard_mut(&mut self) -> &mut Blackboard {
+        &mut self.blackboard
+    }
+
+    /// Sends a custom message to the server. It will be returned as [`NetEvent::Message`] from
+    /// [`ReplicationServer::update`].
+    pub fn send(&mut self, channel: Channel, data: Vec<u8>) -> io::Result<()> {
+        let data = encode(&ReplicationMessage::User(data))?;
+        self.transport.send(channel, &data)
+    }
+
+    /// Closes the connection.
+    pub fn disconnect(&mut self) {
+        self.transport.disconnect()
+    }
+
+    /// Receives the changes from the server, applies them to the graph and advances interpolation
+    /// of the properties. Returns the events of the transport, messages contain user data only.
+    pub fn update(&mut self, graph: &mut Graph, dt: f32) -> Vec<NetEvent> {
+        let mut events = Vec::new();
+        for event in self.transport.poll() {
+            match event {
+                NetEvent::Message {
+                    connection,
+                    channel,
+                    data,
+                } => match ReplicationMessage::decode(&data) {
+                    Some(ReplicationMessage::User(data)) => events.push(NetEvent::Message {
+                        connection,
+                        channel,
+                        data,
+                    }),
+                    Some(message) => self.apply(graph, message),
+                    None => (),
+                },
+                _ => events.push(event),
+            }
+        }
+
+        self.interpolate(graph, dt);
+
+        events
+    }
+
+    fn apply(&mut self, graph: &mut Graph, message: ReplicationMessage) {
+        match message {
+            ReplicationMessage::Spawn {
+                node,
+                type_uuid,
+                parent,
+                properties,
+            } => {
+                let id = SceneNodeId(Uuid::from_u128(node));
+                if graph.id_to_node_handle(id).is_none()
+                    && !self.spawn(graph, id, type_uuid, parent)
+                {
+                    return;
+                }
+                self.apply_properties(graph, id, true, properties);
+            }
+            ReplicationMessage::Update {
+                node,
+                snap,
+                properties,
+            } => self.apply_properties(graph, SceneNodeId(Uuid::from_u128(node)), snap, properties),
+            ReplicationMessage::Transform {
+                node,
+                tick,
+                properties,
+            } => {
+                let id = SceneNodeId(Uuid::from_u128(node));
+                let last_tick = self.transform_ticks.entry(id).or_default();
+                // Unreliable messages could come out of order.
+                if tick > *last_tick {
+                    *last_tick = tick;
+                    self.apply_properties(graph, id, false, properties);
+                }
+            }
+            ReplicationMessage::Despawn { node } => {
+                let id = SceneNodeId(Uuid::from_u128(node));
+                self.interpolations.retain(|(node, _), _| *node != id);
+                self.transform_ticks.remove(&id);
+                if let Some(handle) = graph.id_to_node_handle(id).cloned() {
+                    graph.remove_node(handle);
+                }
+            }
+            ReplicationMessage::User(_) => (),
+        }
+    }
+
+    fn spawn(
+        &mut self,
+        graph: &mut Graph,
+        id: SceneNodeId,
+        type_uuid: u128,
+        parent: Option<u128>,
+    ) -> bool {
+        let Some(context) = self.blackboard.get::<SerializationContext>() else {
+            warn!(
+                category: category::NET,
+                "Unable to spawn a replicated node {}, there's no serialization context.",
+                id.0
+            );
+            return false;
+        };
+        let type_uuid = Uuid::from_u128(type_uuid);
+        let Some(mut node) = context.node_constructors.try_create(&type_uuid) else {
+            warn!(
+                category: category::NET,
+                "Unable to spawn a replicated node {}, there's no constructor for {type_uuid}.",
+                id.0
+            );
+            return false;
+        };
+
+        node.instance_id = id;
+        let handle = graph.add_node(node);
+        if let Some(parent) = parent
+            .and_then(|parent| graph.id_to_node_handle(SceneNodeId(Uuid::from_u128(parent))))
+            .cloned()
+        {
+            graph.link_nodes(handle, parent);
+        }
+        true
+    }
+
+    fn apply_properties(
+        &mut self,
+        graph: &mut Graph,
+        id: SceneNodeId,
+        snap: bool,
+        properties: Vec<ReplicatedProperty>,
+    ) {
+        let Some((_, node)) = graph.node_by_id_mut(id) else {
+            return;
+        };
+
+        for ReplicatedProperty { path, value } in properties {
+            let value = match value.parse::<Value>() {
+                Ok(value) => value,
+                Err(err) => {
+                    warn!(
+                        category: category::NET,
+                        "Malformed value of {path}. Reason: {err}"
+                    );
+                    continue;
+                }
+            };
+
+            let key = (id, path);
+            if !snap && !self.settings.interpolation_time.is_zero() {
+                let mut from = None;
+                node.resolve_path(&key.1, &mut |current| {
+                    from = current.ok().and_then(Interpolated::read)
+                });
+                if let Some(from) = from {
+                    if let Some(to) = from.parse(&value, &self.blackboard) {
+                        self.interpolations.insert(
+                            key,
+                            Interpolation {
+                                from,
+                                to,
+                                elapsed: 0.0,
+                            },
+                        );
+                        continue;
+                    }
+                }
+            }
+
+            self.interpolations.remove(&key);
+            if let Err(err) =
+                serde_bridge::apply_value_at_path(&mut **node, &key.1, &value, &self.blackboard)
+            {
+                warn!(
+                    category: category::NET,
+                    "Unable to apply a replicated property {}. Reason: {err}",
+                    key.1
+                );
+            }
+        }
+    }
+
+    fn interpolate(&mut self, graph: &mut Graph, dt: f32) {
+        let duration = self.settings.interpolation_time.as_secs_f32();
+        self.interpolations.retain(|(id, path), interpolation| {
+            let Some((_, node)) = graph.node_by_id_mut(*id) else {
+                return false;
+            };
+
+            interpolation.elapsed += dt;
+            let t = interpolation.elapsed / duration;
+            let value = if t >= 1.0 {
+                interpolation.to
+            } else {
+                interpolation.from.lerp(&interpolation.to, t)
+            };
+
+            if let Err(err) = serde_bridge::apply_value_at_path(
+                &mut **node,
+                path,
+                &value.to_value(),
+                &self.blackboard,
+            ) {
+                warn!(
+                    category: category::NET,
+                    "Unable to apply a replicated property {path}. Reason: {err}"
+                );
+                return false;
+            }
+
+            t < 1.0
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::Vector3,
+            net::udp::{Channel, NetEvent, UdpConfig},
+            uuid::Uuid,
+        },
+        engine::SerializationContext,
+        scene::{
+            base::{BaseBuilder, SceneNodeId},
+            graph::Graph,
+            light::{point::PointLight, point::PointLightBuilder, BaseLightBuilder},
+            node::Node,
+            pivot::PivotBuilder,
+            replication::{
+                is_transform, transform_properties, ReplicationClient, ReplicationServer,
+                ReplicationSettings,
+            },
+        },
+    };
+    use fyrox_graph::BaseSceneGraph;
+    use std::{sync::Arc, time::Duration};
+
+    fn make_graph(ids: &[SceneNodeId]) -> Graph {
+        let mut graph = Graph::new();
+        for (i, id) in ids.iter().enumerate() {
+            PivotBuilder::new(
+                BaseBuilder::new()
+                    .with_name(format!("Node{i}"))
+                    .with_instance_id(*id)
+                    // The last node is not replicated.
+                    .with_networked(i + 1 < ids.len()),
+            )
+            .build(&mut graph);
+        }
+        graph
+    }
+
+    fn node(graph: &Graph, id: SceneNodeId) -> Option<&Node> {
+        graph.node_by_id(id).map(|(_, node)| node)
+    }
+
+    fn pump(
+        server: &mut ReplicationServer,
+        server_graph: &Graph,
+        client: &mut ReplicationClient,
+        client_graph: &mut Graph,
+        mut until: impl FnMut(&Graph, &[NetEvent]) -> bool,
+    ) {
+        let mut events = Vec::new();
+        for _ in 0..2000 {
+            server.update(server_graph, 0.01);
+            events.extend(client.update(client_graph, 0.01));
+            if until(client_graph, &events) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("Timed out");
+    }
+
+    fn connect() -> (ReplicationServer, ReplicationClient) {
+        let config = UdpConfig {
+            resend_interval: Duration::from_millis(10),
+            connect_interval: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let settings = ReplicationSettings {
+            send_interval: Duration::from_millis(10),
+            interpolation_time: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let server =
+            ReplicationServer::bind("127.0.0.1:0", config.clone(), settings.clone()).unwrap();
+        let client =
+            ReplicationClient::connect(server.local_address().unwrap(), config, settings).unwrap();
+        (server, client)
+    }
+
+    #[test]
+    fn test_replication() {
+        let ids = [(); 3].map(|_| SceneNodeId(Uuid::new_v4()));
+        let mut server_graph = make_graph(&ids);
+        let mut client_graph = make_graph(&ids);
+
+        server_graph
+            .node_by_id_mut(ids[0])
+            .unwrap()
+            .1
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 2.0, 3.0));
+        server_graph
+            .node_by_id_mut(ids[2])
+            .unwrap()
+            .1
+            .set_name("Ignored");
+
+        let (mut server, mut client) = connect();
+
+        // Initial snapshot.
+        pump(
+            &mut server,
+            &server_graph,
+            &mut client,
+            &mut client_graph,
+            |graph, _| {
+                **node(graph, ids[0]).unwrap().local_transform().position() != Vector3::zeros()
+            },
+        );
+        assert_eq!(
+            **node(&client_graph, ids[0])
+                .unwrap()
+                .local_transform()
+                .position(),
+            Vector3::new(1.0, 2.0, 3.0)
+        );
+        assert_eq!(node(&client_graph, ids[2]).unwrap().name(), "Node2");
+
+        // Changes are interpolated, so there must be intermediate values.
+        let handle = server_graph.id_to_node_handle(ids[1]).cloned().unwrap();
+        server_graph[handle]
+            .local_transform_mut()
+            .set_position(Vector3::new(10.0, 0.0, 0.0))
+            .set_scale(Vector3::new(2.0, 2.0, 2.0));
+        server_graph[handle].set_name("Moved");
+        server_graph[handle].set_visibility(false);
+
+        let mut intermediate = false;
+        pump(
+            &mut server,
+            &server_graph,
+            &mut client,
+            &mut client_graph,
+            |graph, _| {
+                let node = node(graph, ids[1]).unwrap();
+                let x = node.local_transform().position().x;
+                intermediate |= x > 0.0 && x < 10.0;
+                x == 10.0
+            },
+        );
+        assert!(intermediate);
+        let node1 = node(&client_graph, ids[1]).unwrap();
+        assert_eq!(node1.name(), "Moved");
+        assert!(!node1.visibility());
+        assert_eq!(
+            **node1.local_transform().scale(),
+            Vector3::new(2.0, 2.0, 2.0)
+        );
+
+        // Removal and custom messages.
+        server_graph.remove_node(server_graph.id_to_node_handle(ids[0]).cloned().unwrap());
+        server
+            .broadcast(Channel::Reliable, b"Hello".to_vec())
+            .unwrap();
+        pump(
+            &mut server,
+            &server_graph,
+            &mut client,
+            &mut client_graph,
+            |graph, events| {
+                node(graph, ids[0]).is_none()
+                    && events.iter().any(
+                        |event| matches!(event, NetEvent::Message { data, .. } if data == b"Hello"),
+                    )
+            },
+        );
+        assert!(node(&client_graph, ids[2]).is_some());
+    }
+
+    #[test]
+    fn test_spawn_at_runtime() {
+        let mut server_graph = Graph::new();
+        let mut client_graph = Graph::new();
+        let (mut server, mut client) = connect();
+        client
+            .blackboard_mut()
+            .register(Arc::new(SerializationContext::new()));
+
+        pump(
+            &mut server,
+            &server_graph,
+            &mut client,
+            &mut client_graph,
+            |_, events| !events.is_empty(),
+        );
+
+        // Both nodes exist only on the server.
+        let parent_id = SceneNodeId(Uuid::new_v4());
+        let child_id = SceneNodeId(Uuid::new_v4());
+        let parent = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_instance_id(parent_id)
+                .with_networked(true),
+        )
+        .build(&mut server_graph);
+        let child = PointLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new()
+                .with_instance_id(child_id)
+                .with_networked(true),
+        ))
+        .with_radius(7.0)
+        .build(&mut server_graph);
+        server_graph.link_nodes(child, parent);
+
+        pump(
+            &mut server,
+            &server_graph,
+            &mut client,
+            &mut client_graph,
+            |graph, _| node(graph, child_id).is_some(),
+        );
+        let light = node(&client_graph, child_id).unwrap();
+        assert_eq!(light.cast::<PointLight>().unwrap().radius(), 7.0);
+        assert!(light.is_networked());
+        assert_eq!(client_graph[light.parent()].instance_id(), parent_id);
+
+        // Transform changes of the spawned node are replicated too, they're sent separately.
+        let transform = transform_properties(&server_graph[child], client.settings());
+        assert!(!transform.is_empty());
+        assert!(transform
+            .iter()
+            .all(|(path, _)| is_transform(path, "base_light.base.local_transform")));
+        server_graph[child]
+            .local_transform_mut()
+            .set_position(Vector3::new(5.0, 0.0, 0.0));
+        pump(
+            &mut server,
+            &server_graph,
+            &mut client,
+            &mut client_graph,
+            |graph, _| {
+                node(graph, child_id)
+                    .unwrap()
+                    .local_transform()
+                    .position()
+                    .x
+                    == 5.0
+            },
+        );
+    }
+}
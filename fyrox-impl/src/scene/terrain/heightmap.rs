@@ -0,0 +1,476 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Import and export of terrain height maps. See [`HeightField`] docs for more info.
+
+use crate::{
+    core::algebra::Vector2,
+    scene::terrain::{resize_f32, Terrain},
+};
+use image::{ImageBuffer, ImageFormat, Luma};
+use std::{
+    fmt::{Display, Formatter},
+    io::Cursor,
+    path::Path,
+};
+
+/// All possible errors that may occur during height map import or export.
+#[derive(Debug)]
+pub enum HeightMapError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// Image decoding or encoding has failed.
+    Image(image::ImageError),
+    /// Amount of height values does not match the requested size.
+    InvalidSize {
+        /// Required amount of height values.
+        required: usize,
+        /// Actual amount of height values.
+        current: usize,
+    },
+    /// File extension does not correspond to any supported height map format.
+    UnsupportedFormat(String),
+    /// The terrain has no chunks, so there is nothing to import to or to export from.
+    NoChunks,
+}
+
+impl Display for HeightMapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeightMapError::Io(v) => write!(f, "An i/o error has occurred: {v}"),
+            HeightMapError::Image(v) => write!(f, "Image error: {v}"),
+            HeightMapError::InvalidSize { required, current } => {
+                write!(
+                    f,
+                    "Height map size mismatch. Required: {required} values, current: {current}."
+                )
+            }
+            HeightMapError::UnsupportedFormat(v) => {
+                write!(
+                    f,
+                    "Unsupported height map format {v}. Only 16-bit PNG and RAW are supported."
+                )
+            }
+            HeightMapError::NoChunks => write!(f, "Terrain has no chunks."),
+        }
+    }
+}
+
+impl From<std::io::Error> for HeightMapError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<image::ImageError> for HeightMapError {
+    fn from(value: image::ImageError) -> Self {
+        Self::Image(value)
+    }
+}
+
+/// A rectangular grid of height values that is not split into chunks. It is used as an intermediate
+/// representation when height data is moved between a [`Terrain`] and external tools. Rows go along the
+/// local X axis of a terrain and columns go along its local Z axis.
+///
+/// 16-bit formats store heights normalized to `[0; 1]` range, so every conversion takes a `min` and
+/// `max` height that are mapped to `0` and `u16::MAX` respectively.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeightField {
+    size: Vector2<u32>,
+    heights: Vec<f32>,
+}
+
+impl HeightField {
+    /// Creates new height field of the given size filled with the given height.
+    pub fn new(size: Vector2<u32>, height: f32) -> Self {
+        Self {
+            size,
+            heights: vec![height; size.x as usize * size.y as usize],
+        }
+    }
+
+    /// Creates new height field from the given height values. Amount of values must be equal to
+    /// `size.x * size.y`.
+    pub fn from_heights(size: Vector2<u32>, heights: Vec<f32>) -> Result<Self, HeightMapError> {
+        let required = size.x as usize * size.y as usize;
+        if heights.len() != required {
+            return Err(HeightMapError::InvalidSize {
+                required,
+                current: heights.len(),
+            });
+        }
+        Ok(Self { size, heights })
+    }
+
+    /// Creates new height field from normalized 16-bit values, mapping `0` to `min` and `u16::MAX` to `max`.
+    pub fn from_u16(
+        size: Vector2<u32>,
+        values: &[u16],
+        min: f32,
+        max: f32,
+    ) -> Result<Self, HeightMapError> {
+        let heights = values
+            .iter()
+            .map(|v| min + (*v as f32 / u16::MAX as f32) * (max - min))
+            .collect();
+        Self::from_heights(size, heights)
+    }
+
+    /// Converts the height field to normalized 16-bit values, mapping `min` to `0` and `max` to `u16::MAX`.
+    /// Heights outside of the range are clamped.
+    pub fn to_u16(&self, min: f32, max: f32) -> Vec<u16> {
+        let range = max - min;
+        self.heights
+            .iter()
+            .map(|h| {
+                let k = if range > 0.0 {
+                    ((h - min) / range).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                (k * u16::MAX as f32).round() as u16
+            })
+            .collect()
+    }
+
+    /// Decodes 16-bit grayscale PNG image from the given bytes. Images with other pixel formats are
+    /// converted to 16-bit grayscale first.
+    pub fn from_png16_bytes(bytes: &[u8], min: f32, max: f32) -> Result<Self, HeightMapError> {
+        let image = image::load_from_memory_with_format(bytes, ImageFormat::Png)?.into_luma16();
+        let size = Vector2::new(image.width(), image.height());
+        Self::from_u16(size, image.as_raw(), min, max)
+    }
+
+    /// Encodes the height field as 16-bit grayscale PNG image.
+    pub fn to_png16_bytes(&self, min: f32, max: f32) -> Result<Vec<u8>, HeightMapError> {
+        let image = ImageBuffer::<Luma<u16>, Vec<u16>>::from_raw(
+            self.size.x,
+            self.size.y,
+            self.to_u16(min, max),
+        )
+        .ok_or(HeightMapError::InvalidSize {
+            required: self.size.x as usize * self.size.y as usize,
+            current: self.heights.len(),
+        })?;
+        let mut bytes = Vec::new();
+        image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+        Ok(bytes)
+    }
+
+    /// Decodes RAW height map (a headerless sequence of little-endian 16-bit values, as exported by most
+    /// terrain generation tools) of the given size. If the size is `None`, the height map is assumed to be
+    /// square.
+    pub fn from_raw16_bytes(
+        bytes: &[u8],
+        size: Option<Vector2<u32>>,
+        min: f32,
+        max: f32,
+    ) -> Result<Self, HeightMapError> {
+        let values = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+        let size = size.unwrap_or_else(|| {
+            let side = (values.len() as f64).sqrt() as u32;
+            Vector2::new(side, side)
+        });
+        Self::from_u16(size, &values, min, max)
+    }
+
+    /// Encodes the height field as RAW height map (a headerless sequence of little-endian 16-bit values).
+    pub fn to_raw16_bytes(&self, min: f32, max: f32) -> Vec<u8> {
+        self.to_u16(min, max)
+            .into_iter()
+            .flat_map(u16::to_le_bytes)
+            .collect()
+    }
+
+    /// Loads height field from a file. The format is selected by the file extension: `png` files are
+    /// decoded as 16-bit PNG images, `raw` and `r16` files are decoded as square RAW height maps.
+    pub fn load<P: AsRef<Path>>(path: P, min: f32, max: f32) -> Result<Self, HeightMapError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        match extension(path).as_str() {
+            "png" => Self::from_png16_bytes(&bytes, min, max),
+            "raw" | "r16" => Self::from_raw16_bytes(&bytes, None, min, max),
+            ext => Err(HeightMapError::UnsupportedFormat(ext.to_string())),
+        }
+    }
+
+    /// Saves height field to a file. The format is selected by the file extension, see [`Self::load`]
+    /// for more info.
+    pub fn save<P: AsRef<Path>>(&self, path: P, min: f32, max: f32) -> Result<(), HeightMapError> {
+        let path = path.as_ref();
+        let bytes = match extension(path).as_str() {
+            "png" => self.to_png16_bytes(min, max)?,
+            "raw" | "r16" => self.to_raw16_bytes(min, max),
+            ext => return Err(HeightMapError::UnsupportedFormat(ext.to_string())),
+        };
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    /// Returns size of the height field in pixels.
+    #[inline]
+    pub fn size(&self) -> Vector2<u32> {
+        self.size
+    }
+
+    /// Returns a reference to height values, row by row.
+    #[inline]
+    pub fn heights(&self) -> &[f32] {
+        &self.heights
+    }
+
+    /// Returns a reference to height values, row by row.
+    #[inline]
+    pub fn heights_mut(&mut self) -> &mut [f32] {
+        &mut self.heights
+    }
+
+    /// Returns a height at the given position, the position is clamped to the bounds of the height field.
+    #[inline]
+    pub fn get(&self, x: i32, y: i32) -> f32 {
+        let x = x.clamp(0, self.size.x as i32 - 1) as usize;
+        let y = y.clamp(0, self.size.y as i32 - 1) as usize;
+        self.heights[y * self.size.x as usize + x]
+    }
+
+    /// Returns a mutable reference to a height at the given position, if the position is inside the height field.
+    #[inline]
+    pub fn get_mut(&mut self, x: i32, y: i32) -> Option<&mut f32> {
+        if x < 0 || y < 0 || x >= self.size.x as i32 || y >= self.size.y as i32 {
+            return None;
+        }
+        self.heights
+            .get_mut(y as usize * self.size.x as usize + x as usize)
+    }
+
+    /// Bilinearly interpolates height at the given position measured in pixels.
+    pub fn sample(&self, position: Vector2<f32>) -> f32 {
+        let x0 = position.x.floor();
+        let y0 = position.y.floor();
+        let kx = position.x - x0;
+        let ky = position.y - y0;
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let h00 = self.get(x0, y0);
+        let h10 = self.get(x0 + 1, y0);
+        let h01 = self.get(x0, y0 + 1);
+        let h11 = self.get(x0 + 1, y0 + 1);
+        let top = h00 + (h10 - h00) * kx;
+        let bottom = h01 + (h11 - h01) * kx;
+        top + (bottom - top) * ky
+    }
+
+    /// Returns min and max height of the height field.
+    pub fn min_max(&self) -> (f32, f32) {
+        self.heights
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(min, max), h| {
+                (min.min(*h), max.max(*h))
+            })
+    }
+
+    /// Creates a resampled copy of the height field with the given size.
+    pub fn resampled(&self, new_size: Vector2<u32>) -> Self {
+        if new_size == self.size {
+            return self.clone();
+        }
+        Self {
+            size: new_size,
+            heights: resize_f32(self.heights.clone(), self.size, new_size),
+        }
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+impl Terrain {
+    /// Returns the size of the terrain height data (without chunk margins) in pixels, as if the terrain
+    /// was a single chunk. Chunks share vertices along their edges, so this size is less than the
+    /// sum of the sizes of the chunks.
+    pub fn height_field_size(&self) -> Vector2<u32> {
+        let vertices = self.height_map_size().map(|x| x.saturating_sub(2));
+        let chunks_x = (self.width_chunks().end - self.width_chunks().start).max(0) as u32;
+        let chunks_y = (self.length_chunks().end - self.length_chunks().start).max(0) as u32;
+        Vector2::new(
+            chunks_x * vertices.x.saturating_sub(1) + 1,
+            chunks_y * vertices.y.saturating_sub(1) + 1,
+        )
+    }
+
+    /// Returns the distance between two adjacent height map vertices in local coordinates.
+    pub fn height_field_cell_size(&self) -> Vector2<f32> {
+        let vertices = self
+            .height_map_size()
+            .map(|x| x.saturating_sub(3).max(1) as f32);
+        let chunk_size = self.chunk_size();
+        Vector2::new(chunk_size.x / vertices.x, chunk_size.y / vertices.y)
+    }
+
+    fn chunk_height_field_origin(&self, grid_position: Vector2<i32>) -> Vector2<i32> {
+        let vertices = self.height_map_size().map(|x| x as i32 - 2);
+        Vector2::new(
+            (grid_position.x - self.width_chunks().start) * (vertices.x - 1),
+            (grid_position.y - self.length_chunks().start) * (vertices.y - 1),
+        )
+    }
+
+    /// Collects height data of every chunk into a single height field. It is the exact copy of the
+    /// height data of the terrain, which could be saved to a file using [`HeightField::save`].
+    pub fn height_field(&self) -> Result<HeightField, HeightMapError> {
+        if self.chunks_ref().is_empty() {
+            return Err(HeightMapError::NoChunks);
+        }
+        let mut field = HeightField::new(self.height_field_size(), 0.0);
+        let vertices = self.height_map_size().map(|x| x as i32 - 2);
+        for chunk in self.chunks_ref() {
+            let origin = self.chunk_height_field_origin(chunk.grid_position());
+            let data = chunk.height_data();
+            for y in 0..vertices.y {
+                for x in 0..vertices.x {
+                    if let Some(height) = field.get_mut(origin.x + x, origin.y + y) {
+                        *height = data[Vector2::new(x, y)];
+                    }
+                }
+            }
+        }
+        Ok(field)
+    }
+
+    /// Replaces height data of every chunk with the data from the given height field. If the size
+    /// of the height field does not match [`Self::height_field_size`], it is resampled first. Margins
+    /// of the chunks are updated as well, so there are no seams between chunks.
+    pub fn set_height_field(&mut self, field: &HeightField) -> Result<(), HeightMapError> {
+        if self.chunks_ref().is_empty() {
+            return Err(HeightMapError::NoChunks);
+        }
+        let field = field.resampled(self.height_field_size());
+        let size = self.height_map_size();
+        let origins = self
+            .chunks_ref()
+            .iter()
+            .map(|c| self.chunk_height_field_origin(c.grid_position()))
+            .collect::<Vec<_>>();
+        for (chunk, origin) in self.chunks_mut().iter_mut().zip(origins) {
+            let mut texture_data = chunk.heightmap().data_ref();
+            let mut texture_modifier = texture_data.modify();
+            let height_map = texture_modifier.data_mut_of_type::<f32>().unwrap();
+            for y in 0..size.y as i32 {
+                for x in 0..size.x as i32 {
+                    // Margin pixels are at -1 and take values from the neighbour chunks or they're
+                    // clamped to the edge of the terrain.
+                    height_map[(y * size.x as i32 + x) as usize] =
+                        field.get(origin.x + x - 1, origin.y + y - 1);
+                }
+            }
+        }
+        self.update_quad_trees();
+        self.bounding_box_dirty.set(true);
+        Ok(())
+    }
+
+    /// Imports a 16-bit PNG or RAW height map from the given file. The height map is resampled to
+    /// fit the terrain, `min` and `max` define the heights that correspond to black and white pixels.
+    pub fn import_height_map<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        min: f32,
+        max: f32,
+    ) -> Result<(), HeightMapError> {
+        let field = HeightField::load(path, min, max)?;
+        self.set_height_field(&field)
+    }
+
+    /// Exports the height data of the terrain to a 16-bit PNG or RAW file. Heights are normalized
+    /// using the actual min and max height of the terrain, which are returned on success so the
+    /// height map could be imported back without any loss of range.
+    pub fn export_height_map<P: AsRef<Path>>(&self, path: P) -> Result<(f32, f32), HeightMapError> {
+        let field = self.height_field()?;
+        let (min, max) = field.min_max();
+        field.save(path, min, max)?;
+        Ok((min, max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::{base::BaseBuilder, graph::Graph, terrain::TerrainBuilder};
+
+    #[test]
+    fn test_u16_round_trip() {
+        let field =
+            HeightField::from_heights(Vector2::new(2, 2), vec![0.0, 5.0, 7.5, 10.0]).unwrap();
+        let png = field.to_png16_bytes(0.0, 10.0).unwrap();
+        let from_png = HeightField::from_png16_bytes(&png, 0.0, 10.0).unwrap();
+        let raw = field.to_raw16_bytes(0.0, 10.0);
+        let from_raw = HeightField::from_raw16_bytes(&raw, None, 0.0, 10.0).unwrap();
+        for loaded in [from_png, from_raw] {
+            assert_eq!(loaded.size(), field.size());
+            for (a, b) in loaded.heights().iter().zip(field.heights()) {
+                assert!((a - b).abs() < 0.001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_size() {
+        assert!(matches!(
+            HeightField::from_heights(Vector2::new(2, 2), vec![0.0; 3]),
+            Err(HeightMapError::InvalidSize {
+                required: 4,
+                current: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn test_terrain_height_field_round_trip() {
+        let mut graph = Graph::new();
+        let handle = TerrainBuilder::new(BaseBuilder::new())
+            .with_width_chunks(0..2)
+            .with_length_chunks(0..2)
+            .with_height_map_size(Vector2::new(11, 11))
+            .build(&mut graph);
+        let terrain = graph[handle].as_terrain_mut();
+        let size = terrain.height_field_size();
+        assert_eq!(size, Vector2::new(17, 17));
+
+        let heights = (0..size.x * size.y).map(|i| i as f32).collect::<Vec<_>>();
+        let field = HeightField::from_heights(size, heights).unwrap();
+        terrain.set_height_field(&field).unwrap();
+        assert_eq!(terrain.height_field().unwrap(), field);
+
+        // Margins must match the data of neighbour chunks.
+        let chunk = terrain.find_chunk(Vector2::new(1, 0)).unwrap();
+        assert_eq!(chunk.height_data()[Vector2::new(-1, 0)], field.get(7, 0));
+
+        // Height fields of other sizes are resampled.
+        terrain
+            .set_height_field(&HeightField::new(Vector2::new(4, 4), 3.0))
+            .unwrap();
+        assert_eq!(terrain.height_field().unwrap().min_max(), (3.0, 3.0));
+    }
+}
@@ -74,11 +74,15 @@ use std::{
 
 pub mod brushstroke;
 mod geometry;
+pub mod heightmap;
+pub mod procedural;
 mod quadtree;
 
 use crate::scene::node::constructor::NodeConstructor;
 pub use brushstroke::*;
 use fyrox_graph::constructor::ConstructorProvider;
+pub use heightmap::*;
+pub use procedural::*;
 
 /// Current implementation version marker.
 pub const VERSION: u8 = 2;
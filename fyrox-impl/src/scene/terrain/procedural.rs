@@ -0,0 +1,503 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Procedural operations on terrain height data: noise generation, erosion and terracing.
+//! See [`TerrainOperation`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Vector2,
+        rand::{rngs::StdRng, Rng, SeedableRng},
+        reflect::prelude::*,
+        uuid_provider,
+    },
+    scene::terrain::{
+        heightmap::{HeightField, HeightMapError},
+        Terrain,
+    },
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Fractal value noise generation options.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+pub struct NoiseOptions {
+    /// Seed of the random number generator. The same seed always produces the same terrain.
+    pub seed: u64,
+    /// Frequency of the first octave, in cycles per meter.
+    #[reflect(min_value = 0.0, step = 0.001)]
+    pub frequency: f32,
+    /// Amount of noise layers, each next layer has higher frequency and lower amplitude.
+    #[reflect(min_value = 1.0, max_value = 16.0, step = 1.0)]
+    pub octaves: u32,
+    /// Amplitude multiplier of each next octave.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub persistence: f32,
+    /// Frequency multiplier of each next octave.
+    #[reflect(min_value = 1.0, step = 0.1)]
+    pub lacunarity: f32,
+    /// Max height of the generated noise, in meters.
+    pub amplitude: f32,
+    /// If `true`, the noise is added to the current heights. Otherwise the current heights are replaced.
+    pub additive: bool,
+}
+
+impl Default for NoiseOptions {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            frequency: 0.01,
+            octaves: 6,
+            persistence: 0.5,
+            lacunarity: 2.0,
+            amplitude: 10.0,
+            additive: false,
+        }
+    }
+}
+
+uuid_provider!(NoiseOptions = "8b00540e-44ab-4d52-9ec6-173243123d47");
+
+/// Thermal erosion options. Thermal erosion moves material down the slopes that are steeper than the
+/// talus angle, which makes cliffs crumble and creates scree slopes at their feet.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+pub struct ThermalErosionOptions {
+    /// Amount of simulation steps.
+    #[reflect(min_value = 0.0, step = 1.0)]
+    pub iterations: u32,
+    /// Max stable slope angle, in degrees.
+    #[reflect(min_value = 0.0, max_value = 90.0, step = 1.0)]
+    pub talus_angle: f32,
+    /// Fraction of unstable material that is moved at each step.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub strength: f32,
+}
+
+impl Default for ThermalErosionOptions {
+    fn default() -> Self {
+        Self {
+            iterations: 50,
+            talus_angle: 35.0,
+            strength: 0.5,
+        }
+    }
+}
+
+uuid_provider!(ThermalErosionOptions = "b06de549-1e4f-457d-933a-57425631637f");
+
+/// Droplet-based hydraulic erosion options. Every droplet flows down the terrain, picks up sediment
+/// on steep parts and deposits it when it slows down, which carves valleys and gullies.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+pub struct HydraulicErosionOptions {
+    /// Seed of the random number generator that is used to place droplets.
+    pub seed: u64,
+    /// Amount of simulated droplets.
+    #[reflect(min_value = 0.0, step = 1000.0)]
+    pub droplets: u32,
+    /// Max amount of steps a single droplet can make.
+    #[reflect(min_value = 1.0, step = 1.0)]
+    pub max_lifetime: u32,
+    /// Defines how much a droplet keeps its direction instead of following the slope.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub inertia: f32,
+    /// Multiplier of the amount of sediment a droplet can carry.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub capacity: f32,
+    /// Min amount of sediment a droplet can carry, which prevents zero capacity on flat areas.
+    #[reflect(min_value = 0.0, step = 0.001)]
+    pub min_capacity: f32,
+    /// Fraction of the excess sediment that is deposited at each step.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub deposition: f32,
+    /// Fraction of the free capacity that is eroded at each step.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub erosion: f32,
+    /// Fraction of the water that evaporates at each step.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub evaporation: f32,
+    /// Gravity acceleration that speeds droplets up when they move downhill.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub gravity: f32,
+}
+
+impl Default for HydraulicErosionOptions {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            droplets: 50000,
+            max_lifetime: 30,
+            inertia: 0.05,
+            capacity: 4.0,
+            min_capacity: 0.01,
+            deposition: 0.3,
+            erosion: 0.3,
+            evaporation: 0.01,
+            gravity: 4.0,
+        }
+    }
+}
+
+uuid_provider!(HydraulicErosionOptions = "fdfff71a-982d-4da4-82a6-4add8275b212");
+
+/// Terracing options. Terracing quantizes heights into steps.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+pub struct TerraceOptions {
+    /// Height of a single step, in meters.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub step_height: f32,
+    /// Flatness of the steps. 0.0 keeps the original heights, 1.0 makes perfectly flat steps.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub sharpness: f32,
+}
+
+impl Default for TerraceOptions {
+    fn default() -> Self {
+        Self {
+            step_height: 1.0,
+            sharpness: 0.8,
+        }
+    }
+}
+
+uuid_provider!(TerraceOptions = "c1fa05dc-1377-4189-91a3-75c910f34ecb");
+
+/// A one-shot procedural operation that modifies the whole height map of a terrain at once. Use
+/// [`Terrain::apply_operation`] to apply it to a terrain, or [`TerrainOperation::apply`] to apply it to
+/// a [`HeightField`] directly.
+#[derive(Clone, Debug, PartialEq, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum TerrainOperation {
+    /// See [`NoiseOptions`] docs.
+    Noise(NoiseOptions),
+    /// See [`ThermalErosionOptions`] docs.
+    ThermalErosion(ThermalErosionOptions),
+    /// See [`HydraulicErosionOptions`] docs.
+    HydraulicErosion(HydraulicErosionOptions),
+    /// See [`TerraceOptions`] docs.
+    Terrace(TerraceOptions),
+}
+
+uuid_provider!(TerrainOperation = "7b07bcea-2a87-424e-9b28-2038a7d0815f");
+
+impl Default for TerrainOperation {
+    fn default() -> Self {
+        Self::Noise(Default::default())
+    }
+}
+
+impl TerrainOperation {
+    /// Applies the operation to the given height field. `cell_size` is the distance between two adjacent
+    /// pixels of the height field in meters.
+    pub fn apply(&self, field: &mut HeightField, cell_size: Vector2<f32>) {
+        match self {
+            TerrainOperation::Noise(options) => generate_noise(field, cell_size, options),
+            TerrainOperation::ThermalErosion(options) => thermal_erosion(field, cell_size, options),
+            TerrainOperation::HydraulicErosion(options) => hydraulic_erosion(field, options),
+            TerrainOperation::Terrace(options) => terrace(field, options),
+        }
+    }
+}
+
+fn hash(seed: u64, x: i32, y: i32) -> f32 {
+    let mut h = seed
+        ^ (x as u32 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u32 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+    h ^= h >> 33;
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+fn value_noise(seed: u64, p: Vector2<f32>) -> f32 {
+    let x0 = p.x.floor();
+    let y0 = p.y.floor();
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let kx = smooth(p.x - x0);
+    let ky = smooth(p.y - y0);
+    let (x0, y0) = (x0 as i32, y0 as i32);
+    let top = hash(seed, x0, y0) + (hash(seed, x0 + 1, y0) - hash(seed, x0, y0)) * kx;
+    let bottom =
+        hash(seed, x0, y0 + 1) + (hash(seed, x0 + 1, y0 + 1) - hash(seed, x0, y0 + 1)) * kx;
+    top + (bottom - top) * ky
+}
+
+/// Fills the height field with fractal value noise. Noise is sampled in meters, so its features do not
+/// depend on the resolution of the height field.
+pub fn generate_noise(field: &mut HeightField, cell_size: Vector2<f32>, options: &NoiseOptions) {
+    let size = field.size();
+    let norm = (0..options.octaves)
+        .map(|i| options.persistence.powi(i as i32))
+        .sum::<f32>()
+        .max(f32::EPSILON);
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let position = Vector2::new(x as f32 * cell_size.x, y as f32 * cell_size.y);
+            let mut frequency = options.frequency;
+            let mut amplitude = 1.0;
+            let mut value = 0.0;
+            for octave in 0..options.octaves {
+                value += value_noise(
+                    options.seed.wrapping_add(octave as u64),
+                    position * frequency,
+                ) * amplitude;
+                frequency *= options.lacunarity;
+                amplitude *= options.persistence;
+            }
+            let height = value / norm * options.amplitude;
+            let index = (y * size.x + x) as usize;
+            let heights = field.heights_mut();
+            if options.additive {
+                heights[index] += height;
+            } else {
+                heights[index] = height;
+            }
+        }
+    }
+}
+
+/// Moves material from the slopes that are steeper than the talus angle to lower neighbours.
+pub fn thermal_erosion(
+    field: &mut HeightField,
+    cell_size: Vector2<f32>,
+    options: &ThermalErosionOptions,
+) {
+    let size = field.size().map(|x| x as i32);
+    let talus = options.talus_angle.to_radians().tan();
+    let neighbours = [
+        (Vector2::new(-1, 0), talus * cell_size.x),
+        (Vector2::new(1, 0), talus * cell_size.x),
+        (Vector2::new(0, -1), talus * cell_size.y),
+        (Vector2::new(0, 1), talus * cell_size.y),
+    ];
+    let mut deltas = vec![0.0f32; field.heights().len()];
+    for _ in 0..options.iterations {
+        deltas.iter_mut().for_each(|d| *d = 0.0);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let height = field.get(x, y);
+                let mut max_excess = 0.0f32;
+                let mut total_excess = 0.0f32;
+                let mut excesses = [0.0f32; 4];
+                for (excess, (offset, threshold)) in excesses.iter_mut().zip(neighbours.iter()) {
+                    let n = Vector2::new(x, y) + offset;
+                    if n.x < 0 || n.y < 0 || n.x >= size.x || n.y >= size.y {
+                        continue;
+                    }
+                    let diff = height - field.get(n.x, n.y) - threshold;
+                    if diff > 0.0 {
+                        *excess = diff;
+                        total_excess += diff;
+                        max_excess = max_excess.max(diff);
+                    }
+                }
+                if total_excess <= 0.0 {
+                    continue;
+                }
+                let moved = options.strength * max_excess * 0.5;
+                deltas[(y * size.x + x) as usize] -= moved;
+                for (excess, (offset, _)) in excesses.iter().zip(neighbours.iter()) {
+                    if *excess > 0.0 {
+                        let n = Vector2::new(x, y) + offset;
+                        deltas[(n.y * size.x + n.x) as usize] += moved * excess / total_excess;
+                    }
+                }
+            }
+        }
+        for (height, delta) in field.heights_mut().iter_mut().zip(deltas.iter()) {
+            *height += delta;
+        }
+    }
+}
+
+fn height_and_gradient(field: &HeightField, p: Vector2<f32>) -> (f32, Vector2<f32>) {
+    let x = p.x.floor() as i32;
+    let y = p.y.floor() as i32;
+    let u = p.x - x as f32;
+    let v = p.y - y as f32;
+    let h00 = field.get(x, y);
+    let h10 = field.get(x + 1, y);
+    let h01 = field.get(x, y + 1);
+    let h11 = field.get(x + 1, y + 1);
+    let gradient = Vector2::new(
+        (h10 - h00) * (1.0 - v) + (h11 - h01) * v,
+        (h01 - h00) * (1.0 - u) + (h11 - h10) * u,
+    );
+    let height =
+        h00 * (1.0 - u) * (1.0 - v) + h10 * u * (1.0 - v) + h01 * (1.0 - u) * v + h11 * u * v;
+    (height, gradient)
+}
+
+fn distribute(field: &mut HeightField, p: Vector2<f32>, amount: f32) {
+    let x = p.x.floor() as i32;
+    let y = p.y.floor() as i32;
+    let u = p.x - x as f32;
+    let v = p.y - y as f32;
+    for (dx, dy, weight) in [
+        (0, 0, (1.0 - u) * (1.0 - v)),
+        (1, 0, u * (1.0 - v)),
+        (0, 1, (1.0 - u) * v),
+        (1, 1, u * v),
+    ] {
+        if let Some(height) = field.get_mut(x + dx, y + dy) {
+            *height += amount * weight;
+        }
+    }
+}
+
+/// Simulates rain droplets that flow down the height field, eroding and depositing sediment.
+/// Distances are measured in pixels of the height field.
+pub fn hydraulic_erosion(field: &mut HeightField, options: &HydraulicErosionOptions) {
+    let size = field.size();
+    if size.x < 2 || size.y < 2 {
+        return;
+    }
+    let max = Vector2::new((size.x - 1) as f32, (size.y - 1) as f32);
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    for _ in 0..options.droplets {
+        let mut position = Vector2::new(rng.gen_range(0.0..max.x), rng.gen_range(0.0..max.y));
+        let mut direction = Vector2::<f32>::zeros();
+        let mut speed = 1.0f32;
+        let mut water = 1.0f32;
+        let mut sediment = 0.0f32;
+        for _ in 0..options.max_lifetime {
+            let (height, gradient) = height_and_gradient(field, position);
+            direction = direction * options.inertia - gradient * (1.0 - options.inertia);
+            let Some(normalized) = direction.try_normalize(f32::EPSILON) else {
+                break;
+            };
+            direction = normalized;
+            let new_position = position + direction;
+            if new_position.x < 0.0
+                || new_position.y < 0.0
+                || new_position.x >= max.x
+                || new_position.y >= max.y
+            {
+                break;
+            }
+            let (new_height, _) = height_and_gradient(field, new_position);
+            let delta_height = new_height - height;
+            let capacity =
+                (-delta_height * speed * water * options.capacity).max(options.min_capacity);
+            if sediment > capacity || delta_height > 0.0 {
+                let amount = if delta_height > 0.0 {
+                    delta_height.min(sediment)
+                } else {
+                    (sediment - capacity) * options.deposition
+                };
+                sediment -= amount;
+                distribute(field, position, amount);
+            } else {
+                let amount = ((capacity - sediment) * options.erosion).min(-delta_height);
+                sediment += amount;
+                distribute(field, position, -amount);
+            }
+            speed = (speed * speed - delta_height * options.gravity)
+                .max(0.0)
+                .sqrt();
+            water *= 1.0 - options.evaporation;
+            position = new_position;
+        }
+    }
+}
+
+/// Quantizes heights of the height field into flat steps.
+pub fn terrace(field: &mut HeightField, options: &TerraceOptions) {
+    if options.step_height <= 0.0 {
+        return;
+    }
+    for height in field.heights_mut() {
+        let step = (*height / options.step_height).floor() * options.step_height;
+        *height += (step - *height) * options.sharpness;
+    }
+}
+
+impl Terrain {
+    /// Applies the given procedural operation to the whole height map of the terrain.
+    pub fn apply_operation(&mut self, operation: &TerrainOperation) -> Result<(), HeightMapError> {
+        let mut field = self.height_field()?;
+        operation.apply(&mut field, self.height_field_cell_size());
+        self.set_height_field(&field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noise_is_deterministic() {
+        let mut a = HeightField::new(Vector2::new(16, 16), 0.0);
+        let mut b = a.clone();
+        let operation = TerrainOperation::Noise(NoiseOptions {
+            frequency: 0.3,
+            ..Default::default()
+        });
+        operation.apply(&mut a, Vector2::new(1.0, 1.0));
+        operation.apply(&mut b, Vector2::new(1.0, 1.0));
+        assert_eq!(a, b);
+        let (min, max) = a.min_max();
+        assert!(min >= 0.0 && max <= 10.0 && max > min);
+    }
+
+    #[test]
+    fn test_thermal_erosion_flattens_cliff() {
+        let size = Vector2::new(8, 1);
+        let heights = (0..8).map(|x| if x < 4 { 10.0 } else { 0.0 }).collect();
+        let mut field = HeightField::from_heights(size, heights).unwrap();
+        let total = field.heights().iter().sum::<f32>();
+        thermal_erosion(
+            &mut field,
+            Vector2::new(1.0, 1.0),
+            &ThermalErosionOptions::default(),
+        );
+        // Material is moved, not created or destroyed.
+        assert!((field.heights().iter().sum::<f32>() - total).abs() < 0.01);
+        assert!(field.get(3, 0) - field.get(4, 0) < 10.0);
+    }
+
+    #[test]
+    fn test_hydraulic_erosion_lowers_slope() {
+        let size = Vector2::new(32, 32);
+        let heights = (0..32 * 32).map(|i| (i % 32) as f32).collect();
+        let mut field = HeightField::from_heights(size, heights).unwrap();
+        let original = field.clone();
+        hydraulic_erosion(
+            &mut field,
+            &HydraulicErosionOptions {
+                droplets: 500,
+                ..Default::default()
+            },
+        );
+        assert_ne!(field, original);
+    }
+
+    #[test]
+    fn test_terrace() {
+        let mut field = HeightField::from_heights(Vector2::new(3, 1), vec![0.5, 1.2, 2.9]).unwrap();
+        terrace(
+            &mut field,
+            &TerraceOptions {
+                step_height: 1.0,
+                sharpness: 1.0,
+            },
+        );
+        assert_eq!(field.heights(), &[0.0, 1.0, 2.0]);
+    }
+}
@@ -0,0 +1,815 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Hierarchical path finding (HPA*). Plain A* search on large graphs (for example - navmeshes of
+//! open worlds) could be slow, because it has to visit a lot of vertices. Hierarchical path finding
+//! splits a graph into clusters and precomputes an abstract graph of portals between the clusters.
+//! A path search is then performed on the abstract graph first, and the resulting abstract path is
+//! refined by small local searches inside the clusters. See [`HierarchicalGraph`] docs for more
+//! info.
+
+use crate::{
+    core::algebra::Vector3,
+    utils::astar::{Graph, PathError, PathKind, VertexDataProvider},
+};
+use fxhash::FxHashMap;
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+type ClusterKey = (i32, i32, i32);
+
+#[derive(Clone, Debug)]
+struct Cluster {
+    // Indices of the abstract nodes, that belong to the cluster.
+    nodes: Vec<u32>,
+    // Amount of vertices of the source graph, that belong to the cluster.
+    vertex_count: u32,
+}
+
+#[derive(Clone, Debug)]
+struct AbstractEdge {
+    node: u32,
+    cost: f32,
+    // Vertices of the source graph, that should be visited to get to the node (including the
+    // vertex of the node).
+    path: Vec<u32>,
+}
+
+#[derive(Clone, Debug)]
+struct AbstractNode {
+    vertex: u32,
+    cluster: u32,
+    edges: Vec<AbstractEdge>,
+}
+
+#[derive(Copy, Clone)]
+struct OpenVertex {
+    f_score: f32,
+    index: u32,
+}
+
+impl PartialEq for OpenVertex {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenVertex {}
+
+impl PartialOrd for OpenVertex {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenVertex {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so the binary heap pops the vertex with the lowest score first.
+        other.f_score.total_cmp(&self.f_score)
+    }
+}
+
+fn edge_cost<T: VertexDataProvider>(graph: &Graph<T>, from: usize, to: usize) -> f32 {
+    let to = &graph.vertices[to];
+    graph.vertices[from].position.metric_distance(&to.position) * to.g_penalty
+}
+
+/// A two-level abstraction of a [`Graph`] for fast path finding on large graphs. Vertices of the
+/// graph are split into clusters (cubes with the size of [`Self::cluster_size`]), every connected
+/// part of a border between two clusters (an entrance) gets a pair of portal vertices, and the
+/// costs of travelling between portal vertices of every cluster are precomputed.
+///
+/// Unlike [`Graph::build_indexed_path`], which uses squared distances, costs of the hierarchical
+/// graph are Euclidean distances between vertices multiplied by penalties of the vertices. Found
+/// paths are near-optimal: they could be slightly longer than the shortest ones, because every
+/// entrance between clusters is crossed at a single point.
+///
+/// The hierarchy must be rebuilt every time when the source graph changes.
+///
+/// ```rust
+/// # use fyrox_impl::utils::astar::{hierarchical::HierarchicalGraph, Graph, GraphVertex, PathKind};
+/// fn find_path(graph: &Graph<GraphVertex>, from: usize, to: usize) -> Option<Vec<usize>> {
+///     // In real code the hierarchy should be created once and reused for multiple queries.
+///     let hierarchy = HierarchicalGraph::new(graph, 16.0);
+///     let mut path = Vec::new();
+///     match hierarchy.build_indexed_path(graph, from, to, &mut path) {
+///         Ok(PathKind::Full) => Some(path),
+///         _ => None,
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HierarchicalGraph {
+    cluster_size: f32,
+    vertex_clusters: Vec<u32>,
+    // Index of every vertex of the source graph inside its cluster.
+    local_indices: Vec<u32>,
+    clusters: Vec<Cluster>,
+    nodes: Vec<AbstractNode>,
+    // Reversed adjacency of the source graph, it is used to search paths backwards.
+    incoming: Vec<Vec<u32>>,
+}
+
+impl HierarchicalGraph {
+    /// Creates a new hierarchy for the given graph. Cluster size defines the size of a cluster
+    /// (in world units), large clusters make abstract graph smaller, but local searches longer.
+    pub fn new<T: VertexDataProvider>(graph: &Graph<T>, cluster_size: f32) -> Self {
+        let cluster_size = cluster_size.max(f32::EPSILON);
+
+        // Split vertices into clusters.
+        let mut cluster_map = FxHashMap::<ClusterKey, u32>::default();
+        let mut clusters = Vec::<Cluster>::new();
+        let mut local_indices = Vec::with_capacity(graph.vertices.len());
+        let vertex_clusters = graph
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let key = cluster_key(vertex.position, cluster_size);
+                let index = *cluster_map.entry(key).or_insert_with(|| {
+                    clusters.push(Cluster {
+                        nodes: Vec::new(),
+                        vertex_count: 0,
+                    });
+                    clusters.len() as u32 - 1
+                });
+                let cluster = &mut clusters[index as usize];
+                local_indices.push(cluster.vertex_count);
+                cluster.vertex_count += 1;
+                index
+            })
+            .collect::<Vec<_>>();
+
+        let mut incoming = vec![Vec::new(); graph.vertices.len()];
+        let mut crossings = FxHashMap::<(u32, u32), Vec<(u32, u32)>>::default();
+        for (index, vertex) in graph.vertices.iter().enumerate() {
+            for &neighbour in vertex.neighbours.iter() {
+                if neighbour as usize >= graph.vertices.len() || neighbour as usize == index {
+                    continue;
+                }
+                incoming[neighbour as usize].push(index as u32);
+                let (from, to) = (vertex_clusters[index], vertex_clusters[neighbour as usize]);
+                if from != to {
+                    crossings
+                        .entry((from, to))
+                        .or_default()
+                        .push((index as u32, neighbour));
+                }
+            }
+        }
+
+        let mut hierarchy = Self {
+            cluster_size,
+            vertex_clusters,
+            local_indices,
+            clusters,
+            nodes: Vec::new(),
+            incoming,
+        };
+
+        // Create portals for every entrance between clusters. Crossing edges, that are close to
+        // each other form a single entrance and only the middle edge of it is used as a portal.
+        let mut node_map = FxHashMap::<u32, u32>::default();
+        let mut crossings = crossings.into_iter().collect::<Vec<_>>();
+        // Keep the result deterministic.
+        crossings.sort_unstable_by_key(|(key, _)| *key);
+        for (_, edges) in crossings {
+            for entrance in split_entrances(graph, &edges) {
+                let (from, to) = pick_portal(graph, &entrance);
+                let from_node = hierarchy.add_node(&mut node_map, from);
+                let to_node = hierarchy.add_node(&mut node_map, to);
+                hierarchy.nodes[from_node as usize]
+                    .edges
+                    .push(AbstractEdge {
+                        node: to_node,
+                        cost: edge_cost(graph, from as usize, to as usize),
+                        path: vec![to],
+                    });
+            }
+        }
+
+        // Precompute paths between portals of each cluster.
+        let mut distances = Vec::new();
+        let mut links = Vec::new();
+        for cluster in 0..hierarchy.clusters.len() {
+            let cluster_nodes = hierarchy.clusters[cluster].nodes.clone();
+            for &node in cluster_nodes.iter() {
+                let vertex = hierarchy.nodes[node as usize].vertex;
+                hierarchy.cluster_distances(graph, vertex, false, &mut distances, &mut links);
+                for &other in cluster_nodes.iter() {
+                    if other == node {
+                        continue;
+                    }
+                    let other_vertex = hierarchy.nodes[other as usize].vertex;
+                    let cost = distances[hierarchy.local_indices[other_vertex as usize] as usize];
+                    if cost < f32::MAX {
+                        let mut path = Vec::new();
+                        hierarchy.follow_links(&links, other_vertex, vertex, &mut path);
+                        hierarchy.nodes[node as usize].edges.push(AbstractEdge {
+                            node: other,
+                            cost,
+                            path: path.into_iter().rev().map(|v| v as u32).collect(),
+                        });
+                    }
+                }
+            }
+        }
+
+        hierarchy
+    }
+
+    /// Returns the size of clusters.
+    pub fn cluster_size(&self) -> f32 {
+        self.cluster_size
+    }
+
+    /// Returns the amount of clusters.
+    pub fn cluster_count(&self) -> usize {
+        self.clusters.len()
+    }
+
+    /// Returns the amount of portal vertices (nodes of the abstract graph).
+    pub fn portal_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn add_node(&mut self, node_map: &mut FxHashMap<u32, u32>, vertex: u32) -> u32 {
+        *node_map.entry(vertex).or_insert_with(|| {
+            let index = self.nodes.len() as u32;
+            let cluster = self.vertex_clusters[vertex as usize];
+            self.nodes.push(AbstractNode {
+                vertex,
+                cluster,
+                edges: Vec::new(),
+            });
+            self.clusters[cluster as usize].nodes.push(index);
+            index
+        })
+    }
+
+    // Calculates the costs of travelling from the given vertex to every other vertex of its cluster
+    // (or from every other vertex to the given one, if `backwards` is set) using Dijkstra's
+    // algorithm. The costs are indexed by local indices of the vertices, unreachable vertices have
+    // `f32::MAX` cost. Links store the previous (or the next, if `backwards` is set) vertex of
+    // the path for every vertex.
+    fn cluster_distances<T: VertexDataProvider>(
+        &self,
+        graph: &Graph<T>,
+        source: u32,
+        backwards: bool,
+        distances: &mut Vec<f32>,
+        links: &mut Vec<u32>,
+    ) {
+        let cluster = self.vertex_clusters[source as usize];
+        let vertex_count = self.clusters[cluster as usize].vertex_count as usize;
+        distances.clear();
+        distances.resize(vertex_count, f32::MAX);
+        links.clear();
+        links.resize(vertex_count, u32::MAX);
+        let mut heap = BinaryHeap::new();
+        distances[self.local_indices[source as usize] as usize] = 0.0;
+        heap.push(OpenVertex {
+            f_score: 0.0,
+            index: source,
+        });
+        while let Some(OpenVertex { f_score, index }) = heap.pop() {
+            if distances[self.local_indices[index as usize] as usize] < f_score {
+                continue;
+            }
+            let neighbours = if backwards {
+                &self.incoming[index as usize]
+            } else {
+                &graph.vertices[index as usize].neighbours
+            };
+            for &neighbour in neighbours.iter() {
+                if neighbour as usize >= graph.vertices.len()
+                    || self.vertex_clusters[neighbour as usize] != cluster
+                {
+                    continue;
+                }
+                let cost = if backwards {
+                    edge_cost(graph, neighbour as usize, index as usize)
+                } else {
+                    edge_cost(graph, index as usize, neighbour as usize)
+                };
+                let distance = f_score + cost;
+                let local = self.local_indices[neighbour as usize] as usize;
+                if distance < distances[local] {
+                    distances[local] = distance;
+                    links[local] = index;
+                    heap.push(OpenVertex {
+                        f_score: distance,
+                        index: neighbour,
+                    });
+                }
+            }
+        }
+    }
+
+    // Follows the links, produced by `cluster_distances`, from the given vertex until the source
+    // vertex is reached. The source vertex is not added to the path.
+    fn follow_links(&self, links: &[u32], from: u32, source: u32, path: &mut Vec<usize>) {
+        let mut current = from;
+        while current != source {
+            path.push(current as usize);
+            current = links[self.local_indices[current as usize] as usize];
+        }
+    }
+
+    // Searches a path between two vertices of the same cluster and appends it (without the
+    // beginning vertex) to the given path.
+    fn cluster_path<T: VertexDataProvider>(
+        &self,
+        graph: &Graph<T>,
+        from: u32,
+        to: u32,
+        path: &mut Vec<usize>,
+    ) -> bool {
+        let cluster = self.vertex_clusters[from as usize];
+        let goal = graph.vertices[to as usize].position;
+        let vertex_count = self.clusters[cluster as usize].vertex_count as usize;
+        let mut g_scores = vec![f32::MAX; vertex_count];
+        let mut came_from = vec![u32::MAX; vertex_count];
+        let mut closed = vec![false; vertex_count];
+        let mut heap = BinaryHeap::new();
+        g_scores[self.local_indices[from as usize] as usize] = 0.0;
+        heap.push(OpenVertex {
+            f_score: 0.0,
+            index: from,
+        });
+        while let Some(OpenVertex { index, .. }) = heap.pop() {
+            if index == to {
+                let begin = path.len();
+                let mut current = to;
+                while current != from {
+                    path.push(current as usize);
+                    current = came_from[self.local_indices[current as usize] as usize];
+                }
+                path[begin..].reverse();
+                return true;
+            }
+            let local = self.local_indices[index as usize] as usize;
+            if closed[local] {
+                continue;
+            }
+            closed[local] = true;
+            let g_score = g_scores[local];
+            for &neighbour in graph.vertices[index as usize].neighbours.iter() {
+                if neighbour as usize >= graph.vertices.len()
+                    || self.vertex_clusters[neighbour as usize] != cluster
+                {
+                    continue;
+                }
+                let tentative = g_score + edge_cost(graph, index as usize, neighbour as usize);
+                let neighbour_local = self.local_indices[neighbour as usize] as usize;
+                if tentative < g_scores[neighbour_local] {
+                    g_scores[neighbour_local] = tentative;
+                    came_from[neighbour_local] = index;
+                    heap.push(OpenVertex {
+                        f_score: tentative
+                            + graph.vertices[neighbour as usize]
+                                .position
+                                .metric_distance(&goal),
+                        index: neighbour,
+                    });
+                }
+            }
+        }
+        false
+    }
+
+    /// Tries to build a path between the given vertices of the graph, that was used to create
+    /// the hierarchy. The path is stored in reversed order (from the endpoint to the beginning
+    /// point), just like [`Graph::build_indexed_path`] does. If there's no path between the
+    /// vertices, the method falls back to [`Graph::build_indexed_path`] to find a partial path.
+    pub fn build_indexed_path<T: VertexDataProvider>(
+        &self,
+        graph: &Graph<T>,
+        from: usize,
+        to: usize,
+        path: &mut Vec<usize>,
+    ) -> Result<PathKind, PathError> {
+        path.clear();
+
+        if graph.vertices.is_empty() {
+            return Err(PathError::Empty);
+        }
+        for index in [from, to] {
+            if index >= graph.vertices.len() || index >= self.vertex_clusters.len() {
+                return Err(PathError::InvalidIndex(index));
+            }
+        }
+
+        if from == to {
+            path.push(to);
+            return Ok(PathKind::Full);
+        }
+
+        let (from, to) = (from as u32, to as u32);
+        let from_cluster = self.vertex_clusters[from as usize];
+        let to_cluster = self.vertex_clusters[to as usize];
+
+        // A direct local search is enough, if both points are in the same cluster and there's
+        // a path between them inside the cluster.
+        if from_cluster == to_cluster {
+            path.push(from as usize);
+            if self.cluster_path(graph, from, to, path) {
+                path.reverse();
+                return Ok(PathKind::Full);
+            }
+            path.clear();
+        }
+
+        // Connect the beginning and the end points to the portals of their clusters.
+        let (mut start_distances, mut start_links) = (Vec::new(), Vec::new());
+        self.cluster_distances(graph, from, false, &mut start_distances, &mut start_links);
+        let (mut goal_distances, mut goal_links) = (Vec::new(), Vec::new());
+        self.cluster_distances(graph, to, true, &mut goal_distances, &mut goal_links);
+
+        // Search on the abstract graph. Two extra nodes are the beginning and the end points.
+        let start_node = self.nodes.len() as u32;
+        let goal_node = start_node + 1;
+        let node_position = |node: u32| -> Vector3<f32> {
+            let vertex = if node == start_node {
+                from
+            } else if node == goal_node {
+                to
+            } else {
+                self.nodes[node as usize].vertex
+            };
+            graph.vertices[vertex as usize].position
+        };
+        let goal_position = node_position(goal_node);
+
+        let mut g_scores = vec![f32::MAX; self.nodes.len() + 2];
+        // Previous node and the index of the edge, that leads from it.
+        let mut came_from = vec![(u32::MAX, u32::MAX); self.nodes.len() + 2];
+        let mut closed = vec![false; self.nodes.len() + 2];
+        let mut heap = BinaryHeap::new();
+        g_scores[start_node as usize] = 0.0;
+        heap.push(OpenVertex {
+            f_score: 0.0,
+            index: start_node,
+        });
+
+        let mut found = false;
+        let mut edges = Vec::new();
+        while let Some(OpenVertex { index, .. }) = heap.pop() {
+            if index == goal_node {
+                found = true;
+                break;
+            }
+            if closed[index as usize] {
+                continue;
+            }
+            closed[index as usize] = true;
+            let g_score = g_scores[index as usize];
+
+            edges.clear();
+            if index == start_node {
+                for &node in self.clusters[from_cluster as usize].nodes.iter() {
+                    let vertex = self.nodes[node as usize].vertex;
+                    let cost = start_distances[self.local_indices[vertex as usize] as usize];
+                    if cost < f32::MAX {
+                        edges.push((node, cost, u32::MAX));
+                    }
+                }
+            } else {
+                let node = &self.nodes[index as usize];
+                edges.extend(
+                    node.edges
+                        .iter()
+                        .enumerate()
+                        .map(|(i, edge)| (edge.node, edge.cost, i as u32)),
+                );
+                if node.cluster == to_cluster {
+                    let cost = goal_distances[self.local_indices[node.vertex as usize] as usize];
+                    if cost < f32::MAX {
+                        edges.push((goal_node, cost, u32::MAX));
+                    }
+                }
+            }
+
+            for &(neighbour, cost, edge) in edges.iter() {
+                let tentative = g_score + cost;
+                if tentative < g_scores[neighbour as usize] {
+                    g_scores[neighbour as usize] = tentative;
+                    came_from[neighbour as usize] = (index, edge);
+                    heap.push(OpenVertex {
+                        f_score: tentative
+                            + node_position(neighbour).metric_distance(&goal_position),
+                        index: neighbour,
+                    });
+                }
+            }
+        }
+
+        if !found {
+            return graph.build_indexed_path(from as usize, to as usize, path);
+        }
+
+        // Refine the abstract path using precomputed paths between portals. The path is built
+        // backwards, starting from the endpoint.
+        let (mut current, _) = came_from[goal_node as usize];
+        let last_portal = self.nodes[current as usize].vertex;
+        path.push(to as usize);
+        self.follow_links(&goal_links, last_portal, to, path);
+        path[1..].reverse();
+        while current != start_node {
+            let (previous, edge) = came_from[current as usize];
+            if previous == start_node {
+                break;
+            }
+            let edge = &self.nodes[previous as usize].edges[edge as usize];
+            path.extend(edge.path.iter().rev().skip(1).map(|v| *v as usize));
+            path.push(self.nodes[previous as usize].vertex as usize);
+            current = previous;
+        }
+        let first_portal = self.nodes[current as usize].vertex;
+        if first_portal != from {
+            let next = start_links[self.local_indices[first_portal as usize] as usize];
+            self.follow_links(&start_links, next, from, path);
+            path.push(from as usize);
+        }
+
+        Ok(PathKind::Full)
+    }
+}
+
+fn cluster_key(position: Vector3<f32>, cluster_size: f32) -> ClusterKey {
+    (
+        (position.x / cluster_size).floor() as i32,
+        (position.y / cluster_size).floor() as i32,
+        (position.z / cluster_size).floor() as i32,
+    )
+}
+
+fn is_adjacent<T: VertexDataProvider>(graph: &Graph<T>, a: u32, b: u32) -> bool {
+    a == b
+        || graph.vertices[a as usize].neighbours.contains(&b)
+        || graph.vertices[b as usize].neighbours.contains(&a)
+}
+
+// Splits crossing edges between two clusters into connected groups (entrances).
+fn split_entrances<T: VertexDataProvider>(
+    graph: &Graph<T>,
+    edges: &[(u32, u32)],
+) -> Vec<Vec<(u32, u32)>> {
+    let mut groups = (0..edges.len()).collect::<Vec<_>>();
+    fn find(groups: &mut [usize], mut i: usize) -> usize {
+        while groups[i] != i {
+            groups[i] = groups[groups[i]];
+            i = groups[i];
+        }
+        i
+    }
+    for i in 0..edges.len() {
+        for j in i + 1..edges.len() {
+            if is_adjacent(graph, edges[i].0, edges[j].0)
+                || is_adjacent(graph, edges[i].1, edges[j].1)
+            {
+                let (a, b) = (find(&mut groups, i), find(&mut groups, j));
+                groups[a] = b;
+            }
+        }
+    }
+    let mut entrances = FxHashMap::<usize, Vec<(u32, u32)>>::default();
+    for (i, edge) in edges.iter().enumerate() {
+        let root = find(&mut groups, i);
+        entrances.entry(root).or_default().push(*edge);
+    }
+    let mut entrances = entrances.into_values().collect::<Vec<_>>();
+    entrances.sort_unstable();
+    entrances
+}
+
+// Picks the crossing edge, that is closest to the center of the entrance.
+fn pick_portal<T: VertexDataProvider>(graph: &Graph<T>, entrance: &[(u32, u32)]) -> (u32, u32) {
+    let midpoint = |(a, b): (u32, u32)| {
+        (graph.vertices[a as usize].position + graph.vertices[b as usize].position).scale(0.5)
+    };
+    let center = entrance
+        .iter()
+        .fold(Vector3::default(), |sum, edge| sum + midpoint(*edge))
+        .scale(1.0 / entrance.len() as f32);
+    *entrance
+        .iter()
+        .min_by(|a, b| {
+            midpoint(**a)
+                .metric_distance(&center)
+                .total_cmp(&midpoint(**b).metric_distance(&center))
+        })
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, rand::Rng},
+        rand,
+        utils::astar::{
+            hierarchical::HierarchicalGraph, request::PathRequest, Graph, GraphVertex, PathKind,
+        },
+    };
+    use std::time::{Duration, Instant};
+
+    // Creates a grid graph, blocked cells are not connected with anything.
+    fn make_grid(size: usize, blocked: impl Fn(usize, usize) -> bool) -> Graph<GraphVertex> {
+        let mut graph = Graph::new();
+        let mut vertices = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                vertices.push(GraphVertex::new(Vector3::new(x as f32, y as f32, 0.0)));
+            }
+        }
+        graph.set_vertices(vertices);
+        for y in 0..size {
+            for x in 0..size {
+                if blocked(x, y) {
+                    continue;
+                }
+                if x + 1 < size && !blocked(x + 1, y) {
+                    graph.link_bidirect(y * size + x, y * size + x + 1);
+                }
+                if y + 1 < size && !blocked(x, y + 1) {
+                    graph.link_bidirect(y * size + x, (y + 1) * size + x);
+                }
+            }
+        }
+        graph
+    }
+
+    fn path_length(graph: &Graph<GraphVertex>, path: &[usize]) -> f32 {
+        path.windows(2)
+            .map(|pair| {
+                assert!(graph.vertices[pair[1]]
+                    .neighbours
+                    .contains(&(pair[0] as u32)));
+                graph.vertices[pair[0]]
+                    .position
+                    .metric_distance(&graph.vertices[pair[1]].position)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_hierarchical_path() {
+        let size = 30;
+        // A wall with a single passage at the top.
+        let graph = make_grid(size, |x, y| x == 15 && y < 25);
+        let hierarchy = HierarchicalGraph::new(&graph, 8.0);
+        assert_eq!(hierarchy.cluster_count(), 16);
+        assert!(hierarchy.portal_count() > 0);
+
+        // A single cluster is equivalent to plain A* search.
+        let reference = HierarchicalGraph::new(&graph, 1000.0);
+
+        let mut path = Vec::new();
+        let mut reference_path = Vec::new();
+        for (from, to) in [(0, size - 1), (size * 3 + 2, size * size - 1), (31, 33)] {
+            assert_eq!(
+                hierarchy
+                    .build_indexed_path(&graph, from, to, &mut path)
+                    .unwrap(),
+                PathKind::Full
+            );
+            assert_eq!(path.first(), Some(&to));
+            assert_eq!(path.last(), Some(&from));
+
+            assert_eq!(
+                reference
+                    .build_indexed_path(&graph, from, to, &mut reference_path)
+                    .unwrap(),
+                PathKind::Full
+            );
+
+            let length = path_length(&graph, &path);
+            let optimal = path_length(&graph, &reference_path);
+            assert!(length <= optimal * 1.2, "{length} vs {optimal}");
+        }
+    }
+
+    #[test]
+    fn test_hierarchical_partial_path() {
+        let size = 20;
+        // The right part of the grid is isolated.
+        let graph = make_grid(size, |x, _| x == 10);
+        let hierarchy = HierarchicalGraph::new(&graph, 4.0);
+
+        let mut path = Vec::new();
+        assert_eq!(
+            hierarchy
+                .build_indexed_path(&graph, 0, size - 1, &mut path)
+                .unwrap(),
+            PathKind::Partial
+        );
+        assert_eq!(path.last(), Some(&0));
+    }
+
+    #[ignore = "takes multiple seconds to run"]
+    #[test]
+    /// Compares plain A*, hierarchical A* and time-sliced requests on a large grid of rooms.
+    fn hierarchical_grid_benchmark() {
+        for size in [100, 200, 400] {
+            println!("benchmarking grid size of: {size}^2");
+            hierarchical_grid_benchmark_iteration(size);
+            println!();
+        }
+    }
+
+    fn hierarchical_grid_benchmark_iteration(size: usize) {
+        let room_size = 20;
+        let rooms = size / room_size;
+        let mut rng = rand::thread_rng();
+
+        // Every room has two doors: in its left wall and in its bottom wall.
+        let vertical_doors = (0..rooms * rooms)
+            .map(|_| rng.gen_range(1..room_size))
+            .collect::<Vec<_>>();
+        let horizontal_doors = (0..rooms * rooms)
+            .map(|_| rng.gen_range(1..room_size))
+            .collect::<Vec<_>>();
+        let blocked = |x: usize, y: usize| {
+            let room = (y / room_size) * rooms + x / room_size;
+            (x % room_size == 0 && y % room_size != vertical_doors[room])
+                || (y % room_size == 0 && x % room_size != horizontal_doors[room])
+        };
+        let mut graph = make_grid(size, blocked);
+        graph.max_search_iterations = -1;
+
+        let setup_start_time = Instant::now();
+        let hierarchy = HierarchicalGraph::new(&graph, room_size as f32);
+        println!(
+            "hierarchy with {} clusters and {} portals built in: {:?}",
+            hierarchy.cluster_count(),
+            hierarchy.portal_count(),
+            setup_start_time.elapsed()
+        );
+
+        let mut random_vertex = || loop {
+            let (x, y) = (rng.gen_range(0..size), rng.gen_range(0..size));
+            if !blocked(x, y) {
+                break y * size + x;
+            }
+        };
+        let queries = (0..100)
+            .map(|_| (random_vertex(), random_vertex()))
+            .collect::<Vec<_>>();
+
+        let mut path = Vec::new();
+
+        let mut measure = |name: &str, find: &dyn Fn(usize, usize, &mut Vec<usize>)| {
+            let mut total_time = Duration::default();
+            let mut longest_query = Duration::default();
+            let mut total_length = 0.0;
+            for &(from, to) in queries.iter() {
+                let query_start_time = Instant::now();
+                find(from, to, &mut path);
+                let elapsed = query_start_time.elapsed();
+                total_time += elapsed;
+                longest_query = longest_query.max(elapsed);
+                total_length += path_length(&graph, &path);
+            }
+            println!(
+                "{name}: {total_time:?}, longest query: {longest_query:?}, total path length: {total_length}"
+            );
+        };
+
+        measure("plain A*", &|from, to, path| {
+            let _ = graph.build_indexed_path(from, to, path);
+        });
+        measure("hierarchical A*", &|from, to, path| {
+            let _ = hierarchy.build_indexed_path(&graph, from, to, path);
+        });
+
+        let start_time = Instant::now();
+        let mut longest_step = Duration::default();
+        for &(from, to) in queries.iter() {
+            let mut request = PathRequest::new(from, to);
+            while !request.is_finished() {
+                let step_start_time = Instant::now();
+                request.step(&graph, 500);
+                longest_step = longest_step.max(step_start_time.elapsed());
+            }
+        }
+        println!(
+            "time-sliced A*: {:?}, longest step: {:?}",
+            start_time.elapsed(),
+            longest_step
+        );
+    }
+}
@@ -23,6 +23,9 @@
 //! A* is one of fastest graph search algorithms, it is used to construct shortest
 //! possible path from vertex to vertex. In vast majority of games it is used in pair
 //! with navigation meshes (navmesh). Check navmesh module docs for more info.
+//!
+//! Searches on large graphs could be accelerated using [`hierarchical`] path finding, and long
+//! searches could be spread over multiple frames using time-sliced [`request`]s.
 
 #![warn(missing_docs)]
 
@@ -39,6 +42,9 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
+pub mod hierarchical;
+pub mod request;
+
 /// Graph vertex that contains position in world and list of indices of neighbour
 /// vertices.
 #[derive(Clone, Debug, Visit, PartialEq)]
@@ -291,6 +297,22 @@ impl PartialPath {
 
         clone
     }
+
+    /// Returns indices of the vertices of the path, starting from the beginning point.
+    pub fn vertices(&self) -> &[usize] {
+        &self.vertices
+    }
+
+    /// Returns the cost of the path from the beginning point to the last vertex of the path.
+    pub fn g_score(&self) -> f32 {
+        self.g_score
+    }
+
+    /// Returns estimated cost of the path from the beginning point to the endpoint, that goes
+    /// through the last vertex of the path.
+    pub fn f_score(&self) -> f32 {
+        self.f_score
+    }
 }
 
 impl<T: VertexDataProvider> Graph<T> {
@@ -434,6 +456,30 @@ impl<T: VertexDataProvider> Graph<T> {
         to: usize,
         path: &mut Vec<usize>,
     ) -> Result<PathKind, PathError> {
+        self.build_indexed_path_with_cost(from, to, path, |current, neighbour| {
+            let current = &self.vertices[current];
+            let neighbour = &self.vertices[neighbour];
+            Some((current.position - neighbour.position).norm_squared() * neighbour.g_penalty)
+        })
+    }
+
+    /// Same as [`Self::build_indexed_path`], but uses the given function to calculate the cost of
+    /// travelling from a vertex (first argument) to its neighbour (second argument). The function
+    /// should return `None` if the neighbour cannot be reached from the vertex, such transitions
+    /// are ignored by the pathfinder.
+    ///
+    /// Keep in mind, that the heuristic of the pathfinder is a squared distance between vertices,
+    /// so the cost function should return values that are comparable with it.
+    pub fn build_indexed_path_with_cost<C>(
+        &self,
+        from: usize,
+        to: usize,
+        path: &mut Vec<usize>,
+        mut cost: C,
+    ) -> Result<PathKind, PathError>
+    where
+        C: FnMut(usize, usize) -> Option<f32>,
+    {
         path.clear();
 
         if self.vertices.is_empty() {
@@ -462,7 +508,7 @@ impl<T: VertexDataProvider> Graph<T> {
         search_heap.push(PartialPath::new(from));
 
         // stores best path found
-        let mut best_path = PartialPath::default();
+        let mut best_path = PartialPath::new(from);
 
         // search loop
         let mut search_iteration = 0i32;
@@ -482,14 +528,21 @@ impl<T: VertexDataProvider> Graph<T> {
                 .get(current_index)
                 .ok_or(PathError::InvalidIndex(current_index))?;
 
+            // the vertex could be pushed multiple times, only the best path to it matters
+            if searched_vertices[current_index] {
+                continue;
+            }
+
+            // breaks if end is found, even if the path to it has worse score than some other
+            // partial path (heuristic could overestimate the cost)
+            if current_index == to {
+                best_path = current_path;
+                break;
+            }
+
             // updates best path
             if current_path > best_path {
                 best_path = current_path.clone();
-
-                // breaks if end is found
-                if current_index == to {
-                    break;
-                }
             }
 
             // evaluates path scores one level deeper and adds the paths to the heap
@@ -512,9 +565,11 @@ impl<T: VertexDataProvider> Graph<T> {
                     .get(neighbour_index)
                     .ok_or(PathError::InvalidIndex(neighbour_index))?;
 
-                let neighbour_g_score = current_path.g_score
-                    + ((current_vertex.position - neighbour.position).norm_squared()
-                        * neighbour.g_penalty);
+                let Some(transition_cost) = cost(current_index, neighbour_index) else {
+                    continue;
+                };
+
+                let neighbour_g_score = current_path.g_score + transition_cost;
 
                 let neighbour_f_score = neighbour_g_score + heuristic(neighbour.position, end_pos);
 
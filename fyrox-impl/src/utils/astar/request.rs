@@ -0,0 +1,424 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Time-sliced path requests. Long path searches on large graphs could take significant amount of
+//! time and block a frame, path requests allow you to spread a search over multiple frames. See
+//! [`PathRequest`] and [`PathRequestQueue`] docs for more info.
+
+use crate::{
+    core::pool::{Handle, Pool},
+    utils::astar::{heuristic, Graph, PartialPath, PathError, PathKind, VertexDataProvider},
+};
+use std::collections::{BinaryHeap, VecDeque};
+
+/// Status of a path request.
+#[derive(Clone, Debug)]
+pub enum PathRequestStatus {
+    /// The search is not finished yet. The best path found so far is available via
+    /// [`PathRequest::partial_path`].
+    InProgress,
+    /// The search is finished, the path is available via [`PathRequest::partial_path`].
+    Finished(PathKind),
+    /// The search was interrupted by an error.
+    Failed(PathError),
+}
+
+/// A path search, that could be performed in multiple steps. It uses the same algorithm as
+/// [`Graph::build_indexed_path`], but allows you to limit the amount of search iterations per step
+/// and to inspect the best path found so far. Unlike [`Graph::build_indexed_path`], it ignores
+/// [`Graph::max_search_iterations`] - the search continues until the endpoint is found or every
+/// reachable vertex is searched.
+///
+/// ```rust
+/// # use fyrox_impl::utils::astar::{
+/// #     request::{PathRequest, PathRequestStatus},
+/// #     Graph, GraphVertex,
+/// # };
+/// fn update_search(request: &mut PathRequest, graph: &Graph<GraphVertex>) {
+///     // Do not spend more than 100 iterations per frame.
+///     match request.step(graph, 100) {
+///         PathRequestStatus::InProgress => {
+///             // The best path found so far could be used to start moving in the right direction.
+///             let _ = request.partial_path().vertices();
+///         }
+///         PathRequestStatus::Finished(_) => {
+///             let mut path = Vec::new();
+///             request.path(&mut path);
+///         }
+///         PathRequestStatus::Failed(error) => {
+///             eprintln!("{error}")
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct PathRequest {
+    from: usize,
+    to: usize,
+    search_heap: BinaryHeap<PartialPath>,
+    searched_vertices: Vec<bool>,
+    best_path: PartialPath,
+    iterations: usize,
+    started: bool,
+    status: PathRequestStatus,
+}
+
+impl PathRequest {
+    /// Creates a new path request between the given vertices.
+    pub fn new(from: usize, to: usize) -> Self {
+        Self {
+            from,
+            to,
+            search_heap: Default::default(),
+            searched_vertices: Default::default(),
+            best_path: Default::default(),
+            iterations: 0,
+            started: false,
+            status: PathRequestStatus::InProgress,
+        }
+    }
+
+    /// Returns index of the beginning vertex.
+    pub fn from(&self) -> usize {
+        self.from
+    }
+
+    /// Returns index of the endpoint.
+    pub fn to(&self) -> usize {
+        self.to
+    }
+
+    /// Returns current status of the request.
+    pub fn status(&self) -> &PathRequestStatus {
+        &self.status
+    }
+
+    /// Returns `true` if the search is finished (successfully or not).
+    pub fn is_finished(&self) -> bool {
+        !matches!(self.status, PathRequestStatus::InProgress)
+    }
+
+    /// Returns total amount of search iterations performed by the request.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Returns the best path found so far. When the search is finished, it is the resulting path.
+    /// Vertices of the path are stored in direct order (from the beginning vertex).
+    pub fn partial_path(&self) -> &PartialPath {
+        &self.best_path
+    }
+
+    /// Writes the best path found so far into the given vector. The path is stored in reversed
+    /// order (from the last vertex to the beginning vertex), just like [`Graph::build_indexed_path`]
+    /// does.
+    pub fn path(&self, path: &mut Vec<usize>) {
+        path.clear();
+        path.extend(self.best_path.vertices.iter().rev());
+    }
+
+    fn finish(&mut self, status: PathRequestStatus) -> &PathRequestStatus {
+        self.search_heap.clear();
+        self.searched_vertices = Default::default();
+        self.status = status;
+        &self.status
+    }
+
+    /// Performs up to `max_iterations` search iterations and returns the status of the request.
+    /// The graph must not be modified while the request is in progress, otherwise the result is
+    /// undefined.
+    pub fn step<T: VertexDataProvider>(
+        &mut self,
+        graph: &Graph<T>,
+        max_iterations: usize,
+    ) -> &PathRequestStatus {
+        if self.is_finished() {
+            return &self.status;
+        }
+
+        if !self.started {
+            self.started = true;
+
+            if graph.vertices.is_empty() {
+                return self.finish(PathRequestStatus::Failed(PathError::Empty));
+            }
+            for index in [self.from, self.to] {
+                if index >= graph.vertices.len() {
+                    return self.finish(PathRequestStatus::Failed(PathError::InvalidIndex(index)));
+                }
+            }
+
+            self.best_path = PartialPath::new(self.from);
+            if self.from == self.to {
+                return self.finish(PathRequestStatus::Finished(PathKind::Full));
+            }
+
+            self.searched_vertices = vec![false; graph.vertices.len()];
+            self.search_heap.push(PartialPath::new(self.from));
+        }
+
+        let end_pos = graph.vertices[self.to].position;
+
+        for _ in 0..max_iterations {
+            let Some(current_path) = self.search_heap.pop() else {
+                return self.finish(PathRequestStatus::Finished(PathKind::Partial));
+            };
+
+            let current_index = *current_path.vertices.last().unwrap();
+            if self.searched_vertices[current_index] {
+                continue;
+            }
+
+            if current_index == self.to {
+                self.best_path = current_path;
+                return self.finish(PathRequestStatus::Finished(PathKind::Full));
+            }
+
+            if current_path > self.best_path {
+                self.best_path = current_path.clone();
+            }
+
+            let current_vertex = &graph.vertices[current_index];
+            for &neighbour_index in current_vertex.neighbours.iter() {
+                let neighbour_index = neighbour_index as usize;
+
+                if neighbour_index == current_index {
+                    return self.finish(PathRequestStatus::Failed(
+                        PathError::CyclicReferenceFound(current_index),
+                    ));
+                }
+
+                let Some(neighbour) = graph.vertices.get(neighbour_index) else {
+                    return self.finish(PathRequestStatus::Failed(PathError::InvalidIndex(
+                        neighbour_index,
+                    )));
+                };
+
+                if self.searched_vertices[neighbour_index] {
+                    continue;
+                }
+
+                let neighbour_g_score = current_path.g_score
+                    + (current_vertex.position - neighbour.position).norm_squared()
+                        * neighbour.g_penalty;
+                let neighbour_f_score = neighbour_g_score + heuristic(neighbour.position, end_pos);
+
+                self.search_heap.push(current_path.clone_and_add(
+                    neighbour_index,
+                    neighbour_g_score,
+                    neighbour_f_score,
+                ));
+            }
+
+            self.searched_vertices[current_index] = true;
+            self.iterations += 1;
+        }
+
+        &self.status
+    }
+}
+
+/// A queue of path requests, that distributes a fixed amount of search iterations per update
+/// between the requests. Requests are processed in the order of submission.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     utils::astar::{request::{PathRequest, PathRequestQueue}, Graph, GraphVertex},
+/// # };
+/// fn update(
+///     queue: &mut PathRequestQueue,
+///     graph: &Graph<GraphVertex>,
+///     request: Handle<PathRequest>,
+/// ) -> Option<Vec<usize>> {
+///     queue.update(graph);
+///     if queue.request(request)?.is_finished() {
+///         let mut path = Vec::new();
+///         queue.take(request)?.path(&mut path);
+///         Some(path)
+///     } else {
+///         None
+///     }
+/// }
+/// ```
+pub struct PathRequestQueue {
+    requests: Pool<PathRequest>,
+    pending: VecDeque<Handle<PathRequest>>,
+    iterations_per_update: usize,
+}
+
+impl Default for PathRequestQueue {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+impl PathRequestQueue {
+    /// Creates a new queue, that performs the given amount of search iterations per update.
+    pub fn new(iterations_per_update: usize) -> Self {
+        Self {
+            requests: Default::default(),
+            pending: Default::default(),
+            iterations_per_update,
+        }
+    }
+
+    /// Sets the amount of search iterations per update.
+    pub fn set_iterations_per_update(&mut self, iterations: usize) {
+        self.iterations_per_update = iterations;
+    }
+
+    /// Returns the amount of search iterations per update.
+    pub fn iterations_per_update(&self) -> usize {
+        self.iterations_per_update
+    }
+
+    /// Adds a new path request to the queue and returns its handle.
+    pub fn submit(&mut self, from: usize, to: usize) -> Handle<PathRequest> {
+        let handle = self.requests.spawn(PathRequest::new(from, to));
+        self.pending.push_back(handle);
+        handle
+    }
+
+    /// Returns a reference to a request with the given handle.
+    pub fn request(&self, handle: Handle<PathRequest>) -> Option<&PathRequest> {
+        self.requests.try_borrow(handle)
+    }
+
+    /// Removes a request with the given handle from the queue and returns it. It could be used to
+    /// cancel pending requests as well as to fetch finished ones.
+    pub fn take(&mut self, handle: Handle<PathRequest>) -> Option<PathRequest> {
+        let request = self.requests.try_free(handle)?;
+        self.pending.retain(|h| *h != handle);
+        Some(request)
+    }
+
+    /// Returns the amount of requests, that are not finished yet.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Performs search iterations for pending requests. Finished requests stay in the queue until
+    /// they're taken out of it using [`Self::take`].
+    pub fn update<T: VertexDataProvider>(&mut self, graph: &Graph<T>) {
+        let mut budget = self.iterations_per_update;
+        while budget > 0 {
+            let Some(&handle) = self.pending.front() else {
+                break;
+            };
+
+            let request = &mut self.requests[handle];
+            let iterations = request.iterations();
+            request.step(graph, budget);
+            budget = budget.saturating_sub((request.iterations() - iterations).max(1));
+
+            if request.is_finished() {
+                self.pending.pop_front();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        utils::astar::{
+            request::{PathRequest, PathRequestQueue, PathRequestStatus},
+            Graph, GraphVertex, PathKind,
+        },
+    };
+
+    fn make_grid(size: usize) -> Graph<GraphVertex> {
+        let mut graph = Graph::new();
+        let mut vertices = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                vertices.push(GraphVertex::new(Vector3::new(x as f32, y as f32, 0.0)));
+            }
+        }
+        graph.set_vertices(vertices);
+        for y in 0..size {
+            for x in 0..size {
+                if x + 1 < size {
+                    graph.link_bidirect(y * size + x, y * size + x + 1);
+                }
+                if y + 1 < size {
+                    graph.link_bidirect(y * size + x, (y + 1) * size + x);
+                }
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn test_time_sliced_request() {
+        let graph = make_grid(20);
+        let mut request = PathRequest::new(0, 399);
+
+        let mut steps = 0;
+        let mut previous_score = f32::MAX;
+        while !request.is_finished() {
+            request.step(&graph, 4);
+            // The best path is refined progressively.
+            assert!(request.partial_path().f_score() <= previous_score);
+            previous_score = request.partial_path().f_score();
+            steps += 1;
+        }
+        assert!(steps > 1);
+        assert!(matches!(
+            request.status(),
+            PathRequestStatus::Finished(PathKind::Full)
+        ));
+
+        let mut path = Vec::new();
+        request.path(&mut path);
+        assert_eq!(path.first(), Some(&399));
+        assert_eq!(path.last(), Some(&0));
+        assert_eq!(path.len(), 39);
+    }
+
+    #[test]
+    fn test_request_queue() {
+        let graph = make_grid(10);
+        let mut queue = PathRequestQueue::new(10);
+        let first = queue.submit(0, 99);
+        let second = queue.submit(9, 90);
+        let cancelled = queue.submit(0, 1);
+        assert!(queue.take(cancelled).is_some());
+
+        let mut updates = 0;
+        while queue.pending_count() > 0 {
+            queue.update(&graph);
+            updates += 1;
+        }
+        assert!(updates > 2);
+
+        for (handle, from, to) in [(first, 0, 99), (second, 9, 90)] {
+            let request = queue.take(handle).unwrap();
+            assert!(matches!(
+                request.status(),
+                PathRequestStatus::Finished(PathKind::Full)
+            ));
+            assert_eq!(request.partial_path().vertices().first(), Some(&from));
+            assert_eq!(request.partial_path().vertices().last(), Some(&to));
+        }
+        assert!(queue.request(first).is_none());
+    }
+}
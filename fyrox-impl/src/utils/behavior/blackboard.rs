@@ -0,0 +1,147 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Blackboard is a typed key-value storage shared by all nodes of a behavior tree. It is used to
+//! exchange data between the game and the tree, for example a game code could write
+//! `"enemy_visible" = true` and a [`super::decorator::DecoratorKind::ConditionalAbort`] node
+//! could abort a running branch when the value changes.
+
+use crate::core::{
+    algebra::Vector3, pool::ErasedHandle, reflect::prelude::*, uuid_provider, visitor::prelude::*,
+};
+use fxhash::FxHashMap;
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// A value stored in a [`Blackboard`].
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum BlackboardValue {
+    /// A boolean value.
+    Bool(bool),
+    /// An integer value.
+    Integer(i64),
+    /// A floating point value.
+    Float(f32),
+    /// A three-dimensional vector.
+    Vector3(Vector3<f32>),
+    /// A string.
+    String(String),
+    /// A handle of some object (a scene node for example).
+    Handle(ErasedHandle),
+}
+
+uuid_provider!(BlackboardValue = "0b9c2f4e-5d1a-4c7b-8e3f-6a2d1c0b9e48");
+
+// NaN values are not expected in a blackboard.
+impl Eq for BlackboardValue {}
+
+impl Default for BlackboardValue {
+    fn default() -> Self {
+        Self::Bool(false)
+    }
+}
+
+/// A type, that could be stored in a [`Blackboard`].
+pub trait BlackboardType: Sized {
+    /// Tries to extract a value of the type from the blackboard value. Returns `None` if the
+    /// value has different type.
+    fn from_value(value: &BlackboardValue) -> Option<Self>;
+
+    /// Wraps the value in the blackboard value.
+    fn into_value(self) -> BlackboardValue;
+}
+
+macro_rules! impl_blackboard_type {
+    ($ty:ty, $variant:ident) => {
+        impl BlackboardType for $ty {
+            fn from_value(value: &BlackboardValue) -> Option<Self> {
+                if let BlackboardValue::$variant(value) = value {
+                    Some(value.clone())
+                } else {
+                    None
+                }
+            }
+
+            fn into_value(self) -> BlackboardValue {
+                BlackboardValue::$variant(self)
+            }
+        }
+
+        impl From<$ty> for BlackboardValue {
+            fn from(value: $ty) -> Self {
+                Self::$variant(value)
+            }
+        }
+    };
+}
+
+impl_blackboard_type!(bool, Bool);
+impl_blackboard_type!(i64, Integer);
+impl_blackboard_type!(f32, Float);
+impl_blackboard_type!(Vector3<f32>, Vector3);
+impl_blackboard_type!(String, String);
+impl_blackboard_type!(ErasedHandle, Handle);
+
+/// See module docs.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct Blackboard {
+    values: FxHashMap<String, BlackboardValue>,
+}
+
+uuid_provider!(Blackboard = "7e4d3a1b-2c9f-4a8e-b6d5-1f0e2a3b4c5d");
+
+impl Blackboard {
+    /// Sets a value for the given key, returns previous value (if any).
+    pub fn set<T: BlackboardType>(
+        &mut self,
+        key: impl Into<String>,
+        value: T,
+    ) -> Option<BlackboardValue> {
+        self.values.insert(key.into(), value.into_value())
+    }
+
+    /// Returns a value of the given key, if it exists and has the requested type.
+    pub fn get<T: BlackboardType>(&self, key: &str) -> Option<T> {
+        self.values.get(key).and_then(T::from_value)
+    }
+
+    /// Returns an untyped value of the given key.
+    pub fn value(&self, key: &str) -> Option<&BlackboardValue> {
+        self.values.get(key)
+    }
+
+    /// Returns `true` if the blackboard contains a value with the given key.
+    pub fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    /// Removes a value with the given key and returns it.
+    pub fn remove(&mut self, key: &str) -> Option<BlackboardValue> {
+        self.values.remove(key)
+    }
+
+    /// Removes every value from the blackboard.
+    pub fn clear(&mut self) {
+        self.values.clear()
+    }
+
+    /// Returns an iterator over all key-value pairs of the blackboard.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &BlackboardValue)> {
+        self.values.iter()
+    }
+}
@@ -24,14 +24,23 @@
 //! implement AND logical function. `Selector` node will execute children until `Status::Success`
 //! is returned from any descendant node. In other worlds `Selector` implement OR logical
 //! function.
+//!
+//! There are two more variations of the `Selector`: `RandomSelector` tries its children in random
+//! order and `UtilitySelector` tries its children in order of their utility (see
+//! [`super::Behavior::utility`]).
+//!
+//! Composite nodes remember their progress: if a child returns `Status::Running`, the next tick
+//! will continue from that child instead of starting from the beginning.
 
 use crate::{
-    core::{pool::Handle, visitor::prelude::*},
+    core::{pool::Handle, reflect::prelude::*, uuid_provider, visitor::prelude::*},
     utils::behavior::{BehaviorNode, BehaviorTree},
 };
+use std::cell::{Cell, RefCell};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 /// Defines exact behavior of the composite node.
-#[derive(Debug, PartialEq, Visit, Eq, Clone)]
+#[derive(Debug, PartialEq, Visit, Reflect, Eq, Clone, AsRefStr, EnumString, VariantNames)]
 pub enum CompositeNodeKind {
     /// `Sequence` node will execute children nodes consecutively
     /// until `Status::Failure` is returned from any descendant node. In other words `Sequence`
@@ -41,8 +50,16 @@ pub enum CompositeNodeKind {
     /// is returned from any descendant node. In other worlds `Selector` implement OR logical
     /// function.
     Selector,
+    /// `RandomSelector` works the same as `Selector`, but shuffles its children every time it
+    /// starts a new run.
+    RandomSelector,
+    /// `UtilitySelector` works the same as `Selector`, but sorts its children by their utility
+    /// (in descending order) every time it starts a new run.
+    UtilitySelector,
 }
 
+uuid_provider!(CompositeNodeKind = "5a8e7c1d-3b2f-4d6a-9e0c-4f1b2a3d5c6e");
+
 impl Default for CompositeNodeKind {
     fn default() -> Self {
         Self::Sequence
@@ -59,6 +76,12 @@ where
     pub children: Vec<Handle<BehaviorNode<B>>>,
     /// Current kind of the node.
     pub kind: CompositeNodeKind,
+    /// Index (in `order`) of a child, that will be executed on the next tick.
+    #[visit(skip)]
+    pub(super) current: Cell<usize>,
+    /// Execution order of the children for the current run.
+    #[visit(skip)]
+    pub(super) order: RefCell<Vec<usize>>,
 }
 
 impl<B> Default for CompositeNode<B>
@@ -69,6 +92,8 @@ where
         Self {
             children: Default::default(),
             kind: Default::default(),
+            current: Default::default(),
+            order: Default::default(),
         }
     }
 }
@@ -79,23 +104,32 @@ where
 {
     /// Creates new composite node of given kind and set of children nodes.
     pub fn new(kind: CompositeNodeKind, children: Vec<Handle<BehaviorNode<B>>>) -> Self {
-        Self { children, kind }
+        Self {
+            children,
+            kind,
+            current: Default::default(),
+            order: Default::default(),
+        }
     }
 
     /// Creates new sequence composite node with a set of children nodes.
     pub fn new_sequence(children: Vec<Handle<BehaviorNode<B>>>) -> Self {
-        Self {
-            children,
-            kind: CompositeNodeKind::Sequence,
-        }
+        Self::new(CompositeNodeKind::Sequence, children)
     }
 
     /// Creates new selector composite node with a set of children nodes.
     pub fn new_selector(children: Vec<Handle<BehaviorNode<B>>>) -> Self {
-        Self {
-            children,
-            kind: CompositeNodeKind::Selector,
-        }
+        Self::new(CompositeNodeKind::Selector, children)
+    }
+
+    /// Creates new random selector composite node with a set of children nodes.
+    pub fn new_random_selector(children: Vec<Handle<BehaviorNode<B>>>) -> Self {
+        Self::new(CompositeNodeKind::RandomSelector, children)
+    }
+
+    /// Creates new utility selector composite node with a set of children nodes.
+    pub fn new_utility_selector(children: Vec<Handle<BehaviorNode<B>>>) -> Self {
+        Self::new(CompositeNodeKind::UtilitySelector, children)
     }
 
     /// Adds self to the tree and return handle to self.
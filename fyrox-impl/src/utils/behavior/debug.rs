@@ -0,0 +1,187 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Debugging facilities for behavior trees. A tree with a debug owner (see
+//! [`super::BehaviorTree::set_debug_owner`]) sends a snapshot of its last tick to an attached
+//! debugger, which is then used (for example, by the editor) to show the active path of the tree
+//! of a selected entity.
+//!
+//! The game is usually running in a separate process, so the debugger listens for connections
+//! using [`BehaviorDebugServer`] and passes its address to the game using [`DEBUGGER_ADDRESS_ENV_VAR`]
+//! environment variable (the editor does it automatically when it runs the game). The game connects
+//! to the debugger when the first tree with a debug owner is ticked. Snapshots are not built at all,
+//! if there's no debugger attached.
+
+use crate::{
+    core::{
+        log::Log,
+        net::{NetListener, NetStream},
+        parking_lot::Mutex,
+        pool::ErasedHandle,
+    },
+    utils::behavior::Status,
+};
+use fxhash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    net::{SocketAddr, ToSocketAddrs},
+    sync::LazyLock,
+};
+
+/// Name of an environment variable, that contains an address of a debugger, that the game should
+/// connect to.
+pub const DEBUGGER_ADDRESS_ENV_VAR: &str = "FYROX_BEHAVIOR_DEBUGGER";
+
+/// State of a single node of a behavior tree at its last tick.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BehaviorNodeSnapshot {
+    /// Depth of the node in the tree (root has zero depth).
+    pub depth: usize,
+    /// Human-readable description of the node.
+    pub label: String,
+    /// Status of the node at the last tick, `None` means that the node wasn't ticked.
+    pub status: Option<Status>,
+}
+
+/// State of a behavior tree at its last tick. Nodes are stored in depth-first order.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BehaviorTreeSnapshot {
+    /// Nodes of the tree.
+    pub nodes: Vec<BehaviorNodeSnapshot>,
+}
+
+impl BehaviorTreeSnapshot {
+    /// Returns an iterator over the nodes, that are still running (the active path of the tree).
+    pub fn active_path(&self) -> impl Iterator<Item = &BehaviorNodeSnapshot> {
+        self.nodes
+            .iter()
+            .filter(|node| node.status == Some(Status::Running))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotMessage {
+    owner: ErasedHandle,
+    snapshot: BehaviorTreeSnapshot,
+}
+
+/// Game side of a connection to a debugger.
+pub struct BehaviorDebugClient {
+    stream: NetStream,
+    // Last sent snapshots, only changed snapshots are sent.
+    sent: FxHashMap<ErasedHandle, BehaviorTreeSnapshot>,
+}
+
+impl BehaviorDebugClient {
+    /// Connects to a debugger at the given address.
+    pub fn connect<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        Ok(Self {
+            stream: NetStream::connect(address)?,
+            sent: Default::default(),
+        })
+    }
+
+    /// Sends a snapshot of a tree of the given owner to the debugger, if it differs from the last sent
+    /// snapshot of the owner.
+    pub fn send(&mut self, owner: ErasedHandle, snapshot: BehaviorTreeSnapshot) -> io::Result<()> {
+        if self.sent.get(&owner) != Some(&snapshot) {
+            let message = SnapshotMessage { owner, snapshot };
+            self.stream.send_message(&message)?;
+            self.sent.insert(message.owner, message.snapshot);
+        }
+        Ok(())
+    }
+}
+
+/// Debugger side of a connection, it accepts connections from games and collects the snapshots sent
+/// by them.
+pub struct BehaviorDebugServer {
+    listener: NetListener,
+    connections: Vec<NetStream>,
+    snapshots: FxHashMap<ErasedHandle, BehaviorTreeSnapshot>,
+}
+
+impl BehaviorDebugServer {
+    /// Starts listening for connections at the given address. Use port `0` to pick any free port, the
+    /// actual address could be fetched using [`Self::local_address`].
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        Ok(Self {
+            listener: NetListener::bind(address)?,
+            connections: Default::default(),
+            snapshots: Default::default(),
+        })
+    }
+
+    /// Returns an address, that the games should connect to.
+    pub fn local_address(&self) -> io::Result<SocketAddr> {
+        self.listener.local_address()
+    }
+
+    /// Accepts new connections and receives pending snapshots. Must be called periodically.
+    pub fn poll(&mut self) {
+        self.connections.extend(self.listener.accept_connections());
+        for connection in self.connections.iter_mut() {
+            connection.process_input::<SnapshotMessage>(|message| {
+                self.snapshots.insert(message.owner, message.snapshot);
+            });
+        }
+    }
+
+    /// Returns the last received snapshot of a tree of the given owner.
+    pub fn snapshot(&self, owner: ErasedHandle) -> Option<&BehaviorTreeSnapshot> {
+        self.snapshots.get(&owner)
+    }
+}
+
+static CLIENT: LazyLock<Mutex<Option<BehaviorDebugClient>>> = LazyLock::new(|| {
+    let client = std::env::var(DEBUGGER_ADDRESS_ENV_VAR)
+        .ok()
+        .and_then(
+            |address| match BehaviorDebugClient::connect(address.as_str()) {
+                Ok(client) => Some(client),
+                Err(err) => {
+                    Log::err(format!(
+                        "Unable to connect to behavior tree debugger at {address}. Reason: {err}"
+                    ));
+                    None
+                }
+            },
+        );
+    Mutex::new(client)
+});
+
+/// Returns `true` if there's a debugger attached to the current process.
+pub fn is_attached() -> bool {
+    CLIENT.lock().is_some()
+}
+
+/// Sends a snapshot of a tree of the given owner to the attached debugger (if any). The connection is
+/// closed on the first error.
+pub fn publish(owner: ErasedHandle, snapshot: BehaviorTreeSnapshot) {
+    let mut client = CLIENT.lock();
+    if let Some(connection) = client.as_mut() {
+        if let Err(err) = connection.send(owner, snapshot) {
+            Log::err(format!(
+                "Behavior tree debugger was disconnected. Reason: {err}"
+            ));
+            *client = None;
+        }
+    }
+}
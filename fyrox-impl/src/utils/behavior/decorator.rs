@@ -0,0 +1,124 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Decorator is a node with a single child, that modifies the way how the child is executed or
+//! how its status is reported. See [`DecoratorKind`] for the list of available decorators.
+
+use crate::{
+    core::{pool::Handle, reflect::prelude::*, uuid_provider, visitor::prelude::*},
+    utils::behavior::{blackboard::BlackboardValue, BehaviorNode, BehaviorTree},
+};
+use std::cell::Cell;
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines exact behavior of the decorator node.
+#[derive(Debug, PartialEq, Visit, Reflect, Clone, AsRefStr, EnumString, VariantNames)]
+pub enum DecoratorKind {
+    /// Executes the child again and again until it succeeds given amount of times (`0` means
+    /// infinitely). Fails as soon as the child fails.
+    Repeat {
+        /// Amount of repetitions.
+        count: u32,
+    },
+    /// Prevents the child from execution (and fails immediately) for the given amount of seconds
+    /// after the child has finished.
+    Cooldown {
+        /// Cooldown duration in seconds.
+        duration: f32,
+    },
+    /// Aborts the child and fails, if the child is running longer than the given amount of seconds.
+    Timeout {
+        /// Maximum duration of execution in seconds.
+        duration: f32,
+    },
+    /// Checks a blackboard value on every tick (even if the child is running) and aborts the child
+    /// (and fails) when the value does not match the expected one.
+    ConditionalAbort {
+        /// A key of the value in the blackboard.
+        key: String,
+        /// Expected value.
+        value: BlackboardValue,
+    },
+}
+
+uuid_provider!(DecoratorKind = "9f1e2d3c-4b5a-4687-a9b8-c7d6e5f4a3b2");
+
+// Durations are never NaN.
+impl Eq for DecoratorKind {}
+
+impl Default for DecoratorKind {
+    fn default() -> Self {
+        Self::Repeat { count: 0 }
+    }
+}
+
+/// See module docs.
+#[derive(Debug, PartialEq, Visit, Clone)]
+pub struct Decorator<B>
+where
+    B: Clone,
+{
+    /// A handle of the child node.
+    pub child: Handle<BehaviorNode<B>>,
+    /// Current kind of the decorator.
+    pub kind: DecoratorKind,
+    /// Amount of successful runs of the child in the current run of the decorator.
+    #[visit(skip)]
+    pub(super) counter: Cell<u32>,
+    /// Tree time at which the child was started (for timeouts) or finished (for cooldowns).
+    #[visit(skip)]
+    pub(super) timestamp: Cell<Option<f32>>,
+}
+
+// Timestamps are never NaN.
+impl<B> Eq for Decorator<B> where B: Clone + Eq {}
+
+impl<B> Default for Decorator<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        Self {
+            child: Default::default(),
+            kind: Default::default(),
+            counter: Default::default(),
+            timestamp: Default::default(),
+        }
+    }
+}
+
+impl<B> Decorator<B>
+where
+    B: Clone + 'static,
+{
+    /// Creates new decorator of the given kind.
+    pub fn new(kind: DecoratorKind, child: Handle<BehaviorNode<B>>) -> Self {
+        Self {
+            child,
+            kind,
+            counter: Default::default(),
+            timestamp: Default::default(),
+        }
+    }
+
+    /// Adds self to given behavior tree and returns handle to self.
+    pub fn add_to(self, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>> {
+        tree.add_node(BehaviorNode::Decorator(self))
+    }
+}
@@ -26,8 +26,14 @@
 //! games. The main concept is in its name. Tree is a set of connected nodes, where each node could
 //! have single parent and zero or more children nodes. Execution path of the tree is defined by the
 //! actions of the nodes. Behavior tree has a set of hard coded nodes as well as leaf nodes with
-//! user-defined logic. Hard coded nodes are: Sequence, Selector, Leaf. Leaf is special - it has
-//! custom method `tick` that can contain any logic you want.
+//! user-defined logic. Hard coded nodes are: Sequence, Selector, RandomSelector, UtilitySelector,
+//! Parallel, Inverter, decorators (Repeat, Cooldown, Timeout, ConditionalAbort) and Leaf. Leaf is
+//! special - it has custom method `tick` that can contain any logic you want.
+//!
+//! Nodes that returned [`Status::Running`] are remembered by the tree, the next tick continues
+//! from them instead of starting from scratch. Nodes of the tree could share data using the
+//! [`blackboard::Blackboard`] of the tree. Trees could also be defined in data, see
+//! [`crate::resource::behavior`] for more info.
 //!
 //! For more info see:
 //! - [Wikipedia article](https://en.wikipedia.org/wiki/Behavior_tree_(artificial_intelligence,_robotics_and_control))
@@ -35,25 +41,38 @@
 
 use crate::{
     core::{
-        pool::{Handle, Pool},
+        pool::{ErasedHandle, Handle, Pool},
         visitor::prelude::*,
     },
+    rand::{seq::SliceRandom, thread_rng},
     utils::behavior::{
+        blackboard::Blackboard,
         composite::{CompositeNode, CompositeNodeKind},
+        debug::{BehaviorNodeSnapshot, BehaviorTreeSnapshot},
+        decorator::{Decorator, DecoratorKind},
         inverter::Inverter,
         leaf::LeafNode,
+        parallel::ParallelNode,
     },
 };
+use fxhash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
 use std::{
+    cell::RefCell,
     fmt::Debug,
     ops::{Index, IndexMut},
 };
 
+pub mod blackboard;
 pub mod composite;
+pub mod debug;
+pub mod decorator;
 pub mod inverter;
 pub mod leaf;
+pub mod parallel;
 
 /// Status of execution of behavior tree node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Status {
     /// Action was successful.
     Success,
@@ -72,6 +91,17 @@ pub trait Behavior<'a>: Visit + Default + PartialEq + Debug + Clone {
     /// the current execution path of the behavior tree it belongs
     /// to.
     fn tick(&mut self, context: &mut Self::Context) -> Status;
+
+    /// Returns utility (desirability) of the action in the given context. It is used by
+    /// [`CompositeNodeKind::UtilitySelector`] to decide which child to try first.
+    fn utility(&self, #[allow(unused_variables)] context: &Self::Context) -> f32 {
+        0.0
+    }
+
+    /// A function that will be called when the action was running, but it was aborted by one
+    /// of its ancestors (for example, by a timeout decorator). It could be used to revert
+    /// partially done work.
+    fn on_abort(&mut self, #[allow(unused_variables)] context: &mut Self::Context) {}
 }
 
 /// Root node of the tree.
@@ -111,6 +141,10 @@ where
     /// A node, that inverts its child state ([`Status::Failure`] becomes [`Status::Success`] and vice versa, [`Status::Running`] remains
     /// unchanged)
     Inverter(Inverter<B>),
+    /// A node, that executes all its children simultaneously.
+    Parallel(ParallelNode<B>),
+    /// A node, that modifies execution of its child (repeat, cooldown, timeout, etc.).
+    Decorator(Decorator<B>),
 }
 
 impl<B> Default for BehaviorNode<B>
@@ -122,6 +156,42 @@ where
     }
 }
 
+impl<B> BehaviorNode<B>
+where
+    B: Clone,
+{
+    /// Returns a list of children nodes of the node.
+    pub fn children(&self) -> Vec<Handle<BehaviorNode<B>>> {
+        match self {
+            BehaviorNode::Unknown | BehaviorNode::Leaf(_) => Vec::new(),
+            BehaviorNode::Root(root) => vec![root.child],
+            BehaviorNode::Composite(composite) => composite.children.clone(),
+            BehaviorNode::Inverter(inverter) => vec![inverter.child],
+            BehaviorNode::Parallel(parallel) => parallel.children.clone(),
+            BehaviorNode::Decorator(decorator) => vec![decorator.child],
+        }
+    }
+
+    // Resets the state of the node, that persists between the ticks.
+    fn reset(&self) {
+        match self {
+            BehaviorNode::Composite(composite) => {
+                composite.current.set(0);
+                composite.order.borrow_mut().clear();
+            }
+            BehaviorNode::Parallel(parallel) => parallel.finished.borrow_mut().clear(),
+            BehaviorNode::Decorator(decorator) => {
+                decorator.counter.set(0);
+                // Cooldown must survive the reset, because it starts when the child is finished.
+                if !matches!(decorator.kind, DecoratorKind::Cooldown { .. }) {
+                    decorator.timestamp.set(None);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
 /// See module docs.
 #[derive(Debug, PartialEq, Visit, Clone)]
 pub struct BehaviorTree<B>
@@ -130,6 +200,16 @@ where
 {
     nodes: Pool<BehaviorNode<B>>,
     root: Handle<BehaviorNode<B>>,
+    #[visit(optional)]
+    blackboard: Blackboard,
+    #[visit(skip)]
+    time: f32,
+    #[visit(skip)]
+    running: RefCell<FxHashSet<Handle<BehaviorNode<B>>>>,
+    #[visit(skip)]
+    statuses: RefCell<FxHashMap<Handle<BehaviorNode<B>>, Status>>,
+    #[visit(skip)]
+    debug_owner: Option<ErasedHandle>,
 }
 
 impl<B> Default for BehaviorTree<B>
@@ -140,6 +220,11 @@ where
         Self {
             nodes: Default::default(),
             root: Default::default(),
+            blackboard: Default::default(),
+            time: 0.0,
+            running: Default::default(),
+            statuses: Default::default(),
+            debug_owner: None,
         }
     }
 }
@@ -154,7 +239,11 @@ where
         let root = nodes.spawn(BehaviorNode::Root(RootNode {
             child: Default::default(),
         }));
-        Self { nodes, root }
+        Self {
+            nodes,
+            root,
+            ..Default::default()
+        }
     }
 
     /// Adds a node to the tree, returns its handle.
@@ -171,7 +260,117 @@ where
         }
     }
 
+    /// Returns a handle of the root node of the tree.
+    pub fn root(&self) -> Handle<BehaviorNode<B>> {
+        self.root
+    }
+
+    /// Returns a shared reference to the blackboard of the tree.
+    pub fn blackboard(&self) -> &Blackboard {
+        &self.blackboard
+    }
+
+    /// Returns a mutable reference to the blackboard of the tree.
+    pub fn blackboard_mut(&mut self) -> &mut Blackboard {
+        &mut self.blackboard
+    }
+
+    /// Returns current time of the tree (in seconds), see [`Self::update`].
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Sets an owner (usually a scene node) of the tree. Trees with an owner publish their
+    /// state after every tick, see [`debug`] module docs for more info.
+    pub fn set_debug_owner(&mut self, owner: Option<ErasedHandle>) {
+        self.debug_owner = owner;
+    }
+
+    /// Returns current owner of the tree, see [`Self::set_debug_owner`].
+    pub fn debug_owner(&self) -> Option<ErasedHandle> {
+        self.debug_owner
+    }
+
+    fn node_utility<'a, Ctx>(&self, handle: Handle<BehaviorNode<B>>, context: &Ctx) -> f32
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        match self.nodes.try_borrow(handle) {
+            Some(BehaviorNode::Leaf(leaf)) => leaf
+                .behavior
+                .as_ref()
+                .map_or(0.0, |behavior| behavior.borrow().utility(context)),
+            Some(node) => node
+                .children()
+                .into_iter()
+                .map(|child| self.node_utility(child, context))
+                .reduce(f32::max)
+                .unwrap_or_default(),
+            None => 0.0,
+        }
+    }
+
+    fn composite_order<'a, Ctx>(&self, composite: &CompositeNode<B>, context: &Ctx) -> Vec<usize>
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        let mut order = composite.order.borrow_mut();
+        if order.len() != composite.children.len() {
+            order.clear();
+            order.extend(0..composite.children.len());
+            match composite.kind {
+                CompositeNodeKind::RandomSelector => order.shuffle(&mut thread_rng()),
+                CompositeNodeKind::UtilitySelector => {
+                    let utilities = composite
+                        .children
+                        .iter()
+                        .map(|child| self.node_utility(*child, context))
+                        .collect::<Vec<_>>();
+                    order.sort_by(|a, b| utilities[*b].total_cmp(&utilities[*a]));
+                }
+                _ => (),
+            }
+        }
+        order.clone()
+    }
+
+    /// Aborts execution of the running nodes of the subtree starting from the given node.
+    fn abort<'a, Ctx>(&self, handle: Handle<BehaviorNode<B>>, context: &mut Ctx)
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        if !self.running.borrow_mut().remove(&handle) {
+            return;
+        }
+        if let Some(node) = self.nodes.try_borrow(handle) {
+            if let BehaviorNode::Leaf(leaf) = node {
+                if let Some(behavior) = leaf.behavior.as_ref() {
+                    behavior.borrow_mut().on_abort(context);
+                }
+            }
+            for child in node.children() {
+                self.abort(child, context);
+            }
+            node.reset();
+        }
+    }
+
     fn tick_recursive<'a, Ctx>(&self, handle: Handle<BehaviorNode<B>>, context: &mut Ctx) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        let status = self.tick_node(handle, context);
+        if status == Status::Running {
+            self.running.borrow_mut().insert(handle);
+        } else {
+            self.running.borrow_mut().remove(&handle);
+            self.nodes[handle].reset();
+        }
+        self.statuses.borrow_mut().insert(handle, status);
+        status
+    }
+
+    fn tick_node<'a, Ctx>(&self, handle: Handle<BehaviorNode<B>>, context: &mut Ctx) -> Status
     where
         B: Behavior<'a, Context = Ctx>,
     {
@@ -183,38 +382,29 @@ where
                     Status::Success
                 }
             }
-            BehaviorNode::Composite(ref composite) => match composite.kind {
-                CompositeNodeKind::Sequence => {
-                    let mut all_succeeded = true;
-                    for child in composite.children.iter() {
-                        match self.tick_recursive(*child, context) {
-                            Status::Failure => {
-                                all_succeeded = false;
-                                break;
-                            }
-                            Status::Running => {
-                                return Status::Running;
-                            }
-                            _ => (),
+            BehaviorNode::Composite(ref composite) => {
+                let order = self.composite_order(composite, context);
+                for (position, index) in order.iter().enumerate().skip(composite.current.get()) {
+                    match (
+                        &composite.kind,
+                        self.tick_recursive(composite.children[*index], context),
+                    ) {
+                        (_, Status::Running) => {
+                            composite.current.set(position);
+                            return Status::Running;
                         }
-                    }
-                    if all_succeeded {
-                        Status::Success
-                    } else {
-                        Status::Failure
+                        (CompositeNodeKind::Sequence, Status::Failure) => return Status::Failure,
+                        (CompositeNodeKind::Sequence, Status::Success) => (),
+                        (_, Status::Success) => return Status::Success,
+                        (_, Status::Failure) => (),
                     }
                 }
-                CompositeNodeKind::Selector => {
-                    for child in composite.children.iter() {
-                        match self.tick_recursive(*child, context) {
-                            Status::Success => return Status::Success,
-                            Status::Running => return Status::Running,
-                            _ => (),
-                        }
-                    }
+                if composite.kind == CompositeNodeKind::Sequence {
+                    Status::Success
+                } else {
                     Status::Failure
                 }
-            },
+            }
             BehaviorNode::Leaf(ref leaf) => {
                 leaf.behavior.as_ref().unwrap().borrow_mut().tick(context)
             }
@@ -225,6 +415,95 @@ where
                     Status::Running => Status::Running,
                 }
             }
+            BehaviorNode::Parallel(ref parallel) => {
+                let mut finished = parallel.finished.borrow().clone();
+                finished.resize(parallel.children.len(), None);
+                for (child, status) in parallel.children.iter().zip(finished.iter_mut()) {
+                    if status.is_none() {
+                        match self.tick_recursive(*child, context) {
+                            Status::Running => (),
+                            child_status => *status = Some(child_status),
+                        }
+                    }
+                }
+
+                let count = |expected| finished.iter().filter(|s| **s == Some(expected)).count();
+                let total = finished.len();
+                let status = if parallel
+                    .failure_policy
+                    .is_satisfied(count(Status::Failure), total)
+                {
+                    Status::Failure
+                } else if parallel
+                    .success_policy
+                    .is_satisfied(count(Status::Success), total)
+                {
+                    Status::Success
+                } else if finished.iter().all(|s| s.is_some()) {
+                    Status::Failure
+                } else {
+                    Status::Running
+                };
+
+                if status == Status::Running {
+                    *parallel.finished.borrow_mut() = finished;
+                } else {
+                    for child in parallel.children.iter() {
+                        self.abort(*child, context);
+                    }
+                }
+                status
+            }
+            BehaviorNode::Decorator(ref decorator) => match decorator.kind {
+                DecoratorKind::Repeat { count } => {
+                    match self.tick_recursive(decorator.child, context) {
+                        Status::Success => {
+                            let counter = decorator.counter.get() + 1;
+                            if count != 0 && counter >= count {
+                                Status::Success
+                            } else {
+                                decorator.counter.set(counter);
+                                Status::Running
+                            }
+                        }
+                        status => status,
+                    }
+                }
+                DecoratorKind::Cooldown { duration } => {
+                    if decorator
+                        .timestamp
+                        .get()
+                        .is_some_and(|finished| self.time - finished < duration)
+                    {
+                        Status::Failure
+                    } else {
+                        let status = self.tick_recursive(decorator.child, context);
+                        if status != Status::Running {
+                            decorator.timestamp.set(Some(self.time));
+                        }
+                        status
+                    }
+                }
+                DecoratorKind::Timeout { duration } => {
+                    let started = *decorator.timestamp.get().get_or_insert(self.time);
+                    if self.time - started > duration {
+                        self.abort(decorator.child, context);
+                        Status::Failure
+                    } else {
+                        let status = self.tick_recursive(decorator.child, context);
+                        decorator.timestamp.set(Some(started));
+                        status
+                    }
+                }
+                DecoratorKind::ConditionalAbort { ref key, ref value } => {
+                    if self.blackboard.value(key) == Some(value) {
+                        self.tick_recursive(decorator.child, context)
+                    } else {
+                        self.abort(decorator.child, context);
+                        Status::Failure
+                    }
+                }
+            },
             BehaviorNode::Unknown => {
                 unreachable!()
             }
@@ -241,12 +520,111 @@ where
         self.nodes.try_borrow_mut(handle)
     }
 
+    /// Returns status of the node at the last tick, `None` means that the node wasn't ticked.
+    pub fn last_status(&self, handle: Handle<BehaviorNode<B>>) -> Option<Status> {
+        self.statuses.borrow().get(&handle).cloned()
+    }
+
+    /// Returns a list of the nodes, that are still running after the last tick. The list starts
+    /// from the root and ends with a deepest running node (usually a leaf).
+    pub fn active_path(&self) -> Vec<Handle<BehaviorNode<B>>> {
+        let running = self.running.borrow();
+        let mut path = Vec::new();
+        let mut current = self.root;
+        while running.contains(&current) {
+            path.push(current);
+            match self.nodes.try_borrow(current) {
+                Some(node) => {
+                    match node
+                        .children()
+                        .into_iter()
+                        .find(|child| running.contains(child))
+                    {
+                        Some(child) => current = child,
+                        None => break,
+                    }
+                }
+                None => break,
+            }
+        }
+        path
+    }
+
+    /// Aborts every running node of the tree, so the next tick will start from the beginning.
+    pub fn reset<'a, Ctx>(&self, context: &mut Ctx)
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        self.abort(self.root, context);
+        self.statuses.borrow_mut().clear();
+    }
+
     /// Performs a single update tick with given context.
     pub fn tick<'a, Ctx>(&self, context: &mut Ctx) -> Status
     where
         B: Behavior<'a, Context = Ctx>,
     {
-        self.tick_recursive(self.root, context)
+        self.statuses.borrow_mut().clear();
+        let status = self.tick_recursive(self.root, context);
+        if let Some(owner) = self.debug_owner {
+            // Snapshots are not cheap to build, so do it only when someone is interested in them.
+            if debug::is_attached() {
+                debug::publish(owner, self.snapshot());
+            }
+        }
+        status
+    }
+
+    /// Advances time of the tree (used by time-dependent decorators) by the given amount of
+    /// seconds and performs a single update tick with given context.
+    pub fn update<'a, Ctx>(&mut self, dt: f32, context: &mut Ctx) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        self.time += dt;
+        self.tick(context)
+    }
+
+    /// Creates a snapshot of the tree with the statuses of its nodes at the last tick.
+    pub fn snapshot(&self) -> BehaviorTreeSnapshot
+    where
+        B: Debug,
+    {
+        let statuses = self.statuses.borrow();
+        let mut snapshot = BehaviorTreeSnapshot::default();
+        let mut stack = vec![(self.root, 0)];
+        while let Some((handle, depth)) = stack.pop() {
+            let Some(node) = self.nodes.try_borrow(handle) else {
+                continue;
+            };
+            let label = match node {
+                BehaviorNode::Unknown => "Unknown".to_string(),
+                BehaviorNode::Root(_) => "Root".to_string(),
+                BehaviorNode::Composite(composite) => format!("{:?}", composite.kind),
+                BehaviorNode::Leaf(leaf) => match leaf.behavior.as_ref() {
+                    Some(behavior) => format!("{:?}", behavior.borrow()),
+                    None => "Leaf".to_string(),
+                },
+                BehaviorNode::Inverter(_) => "Inverter".to_string(),
+                BehaviorNode::Parallel(parallel) => format!(
+                    "Parallel (Success: {:?}, Failure: {:?})",
+                    parallel.success_policy, parallel.failure_policy
+                ),
+                BehaviorNode::Decorator(decorator) => format!("{:?}", decorator.kind),
+            };
+            snapshot.nodes.push(BehaviorNodeSnapshot {
+                depth,
+                label,
+                status: statuses.get(&handle).cloned(),
+            });
+            stack.extend(
+                node.children()
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, depth + 1)),
+            );
+        }
+        snapshot
     }
 }
 
@@ -286,6 +664,40 @@ where
     CompositeNode::new_selector(children.to_vec()).add_to(tree)
 }
 
+/// Creates a new random selector.
+pub fn random_selector<B, const N: usize>(
+    children: [Handle<BehaviorNode<B>>; N],
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    CompositeNode::new_random_selector(children.to_vec()).add_to(tree)
+}
+
+/// Creates a new utility selector.
+pub fn utility_selector<B, const N: usize>(
+    children: [Handle<BehaviorNode<B>>; N],
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    CompositeNode::new_utility_selector(children.to_vec()).add_to(tree)
+}
+
+/// Creates a new parallel node, that succeeds when all its children succeed and fails as soon as
+/// any child fails.
+pub fn parallel<B, const N: usize>(
+    children: [Handle<BehaviorNode<B>>; N],
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    ParallelNode::new(children.to_vec()).add_to(tree)
+}
+
 /// Creates a new leaf.
 pub fn leaf<B>(behavior: B, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>>
 where
@@ -305,17 +717,81 @@ where
     Inverter::new(child).add_to(tree)
 }
 
+/// Creates a new decorator, that repeats its child given amount of times (`0` - infinitely).
+pub fn repeat<B>(
+    count: u32,
+    child: Handle<BehaviorNode<B>>,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    Decorator::new(DecoratorKind::Repeat { count }, child).add_to(tree)
+}
+
+/// Creates a new decorator, that prevents its child from execution for given amount of seconds
+/// after the child has finished.
+pub fn cooldown<B>(
+    duration: f32,
+    child: Handle<BehaviorNode<B>>,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    Decorator::new(DecoratorKind::Cooldown { duration }, child).add_to(tree)
+}
+
+/// Creates a new decorator, that aborts its child if it is running longer than given amount of
+/// seconds.
+pub fn timeout<B>(
+    duration: f32,
+    child: Handle<BehaviorNode<B>>,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    Decorator::new(DecoratorKind::Timeout { duration }, child).add_to(tree)
+}
+
+/// Creates a new decorator, that aborts its child when a blackboard value with the given key
+/// does not match the expected value.
+pub fn conditional_abort<B>(
+    key: impl Into<String>,
+    value: impl Into<blackboard::BlackboardValue>,
+    child: Handle<BehaviorNode<B>>,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    Decorator::new(
+        DecoratorKind::ConditionalAbort {
+            key: key.into(),
+            value: value.into(),
+        },
+        child,
+    )
+    .add_to(tree)
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
-        core::{futures::executor::block_on, visitor::prelude::*},
+        core::{futures::executor::block_on, pool::ErasedHandle, visitor::prelude::*},
         utils::behavior::{
             composite::{CompositeNode, CompositeNodeKind},
+            conditional_abort, cooldown,
+            debug::{BehaviorDebugClient, BehaviorDebugServer},
+            inverter, leaf,
             leaf::LeafNode,
-            Behavior, BehaviorTree, Status,
+            parallel,
+            parallel::{ParallelNode, ParallelPolicy},
+            repeat, selector, sequence, timeout, utility_selector, Behavior, BehaviorTree, Status,
         },
     };
-    use std::{env, fs::File, io::Write, path::PathBuf};
+    use std::{env, fs::File, io::Write, path::PathBuf, time::Duration};
 
     #[derive(Debug, PartialEq, Default, Visit, Clone)]
     struct WalkAction;
@@ -489,4 +965,211 @@ mod test {
 
         assert_eq!(saved_tree, loaded_tree);
     }
+
+    #[derive(Debug, PartialEq, Default, Visit, Clone)]
+    struct Action {
+        id: u32,
+        duration: u32,
+        progress: u32,
+        succeed: bool,
+        utility: f32,
+    }
+
+    impl Action {
+        fn new(id: u32, duration: u32, succeed: bool) -> Self {
+            Self {
+                id,
+                duration,
+                progress: 0,
+                succeed,
+                utility: 0.0,
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Journal {
+        ticked: Vec<u32>,
+        aborted: Vec<u32>,
+    }
+
+    impl Behavior<'_> for Action {
+        type Context = Journal;
+
+        fn tick(&mut self, context: &mut Self::Context) -> Status {
+            context.ticked.push(self.id);
+            self.progress += 1;
+            if self.progress >= self.duration {
+                self.progress = 0;
+                if self.succeed {
+                    Status::Success
+                } else {
+                    Status::Failure
+                }
+            } else {
+                Status::Running
+            }
+        }
+
+        fn utility(&self, _context: &Self::Context) -> f32 {
+            self.utility
+        }
+
+        fn on_abort(&mut self, context: &mut Self::Context) {
+            self.progress = 0;
+            context.aborted.push(self.id);
+        }
+    }
+
+    #[test]
+    fn test_running_persistence() {
+        let mut tree = BehaviorTree::new();
+        let first = leaf(Action::new(1, 1, true), &mut tree);
+        let second = leaf(Action::new(2, 2, true), &mut tree);
+        let entry = sequence([first, second], &mut tree);
+        tree.set_entry_node(entry);
+
+        let mut journal = Journal::default();
+        assert_eq!(tree.tick(&mut journal), Status::Running);
+        assert_eq!(tree.active_path(), vec![tree.root(), entry, second]);
+        // The first action must not be ticked again, while the second one is running.
+        assert_eq!(tree.tick(&mut journal), Status::Success);
+        assert_eq!(journal.ticked, vec![1, 2, 2]);
+        assert!(tree.active_path().is_empty());
+    }
+
+    #[test]
+    fn test_parallel() {
+        let mut tree = BehaviorTree::new();
+        let short = leaf(Action::new(1, 1, true), &mut tree);
+        let long = leaf(Action::new(2, 3, true), &mut tree);
+        let entry = parallel([short, long], &mut tree);
+        tree.set_entry_node(entry);
+
+        let mut journal = Journal::default();
+        assert_eq!(tree.tick(&mut journal), Status::Running);
+        assert_eq!(tree.tick(&mut journal), Status::Running);
+        assert_eq!(tree.tick(&mut journal), Status::Success);
+        assert_eq!(journal.ticked, vec![1, 2, 2, 2]);
+
+        // Failure of any child aborts the others.
+        let mut tree = BehaviorTree::new();
+        let failing = leaf(Action::new(1, 2, false), &mut tree);
+        let long = leaf(Action::new(2, 5, true), &mut tree);
+        let entry = ParallelNode::new(vec![failing, long])
+            .with_policies(ParallelPolicy::RequireOne, ParallelPolicy::RequireOne)
+            .add_to(&mut tree);
+        tree.set_entry_node(entry);
+
+        let mut journal = Journal::default();
+        assert_eq!(tree.tick(&mut journal), Status::Running);
+        assert_eq!(tree.tick(&mut journal), Status::Failure);
+        assert_eq!(journal.aborted, vec![2]);
+    }
+
+    #[test]
+    fn test_decorators() {
+        let mut tree = BehaviorTree::new();
+        let action = leaf(Action::new(1, 1, true), &mut tree);
+        let entry = repeat(3, action, &mut tree);
+        tree.set_entry_node(entry);
+
+        let mut journal = Journal::default();
+        assert_eq!(tree.tick(&mut journal), Status::Running);
+        assert_eq!(tree.tick(&mut journal), Status::Running);
+        assert_eq!(tree.tick(&mut journal), Status::Success);
+        assert_eq!(tree.tick(&mut journal), Status::Running);
+
+        let mut tree = BehaviorTree::new();
+        let action = leaf(Action::new(1, 10, true), &mut tree);
+        let entry = timeout(1.0, action, &mut tree);
+        tree.set_entry_node(entry);
+
+        let mut journal = Journal::default();
+        assert_eq!(tree.update(0.5, &mut journal), Status::Running);
+        assert_eq!(tree.update(0.5, &mut journal), Status::Running);
+        assert_eq!(tree.update(0.6, &mut journal), Status::Failure);
+        assert_eq!(journal.aborted, vec![1]);
+
+        let mut tree = BehaviorTree::new();
+        let action = leaf(Action::new(1, 1, true), &mut tree);
+        let entry = cooldown(1.0, action, &mut tree);
+        tree.set_entry_node(entry);
+
+        let mut journal = Journal::default();
+        assert_eq!(tree.update(0.5, &mut journal), Status::Success);
+        assert_eq!(tree.update(0.5, &mut journal), Status::Failure);
+        assert_eq!(tree.update(0.6, &mut journal), Status::Success);
+        assert_eq!(journal.ticked, vec![1, 1]);
+
+        let mut tree = BehaviorTree::new();
+        let action = leaf(Action::new(1, 10, true), &mut tree);
+        let entry = conditional_abort("enemy_visible", true, action, &mut tree);
+        tree.set_entry_node(entry);
+        tree.blackboard_mut().set("enemy_visible", true);
+
+        let mut journal = Journal::default();
+        assert_eq!(tree.tick(&mut journal), Status::Running);
+        tree.blackboard_mut().set("enemy_visible", false);
+        assert_eq!(tree.tick(&mut journal), Status::Failure);
+        assert_eq!(journal.aborted, vec![1]);
+    }
+
+    #[test]
+    fn test_utility_selector() {
+        let mut tree = BehaviorTree::new();
+        let mut low = Action::new(1, 1, true);
+        low.utility = 0.1;
+        let mut high = Action::new(2, 1, true);
+        high.utility = 0.9;
+        let low = leaf(low, &mut tree);
+        let high = leaf(high, &mut tree);
+        let entry = utility_selector([low, high], &mut tree);
+        tree.set_entry_node(entry);
+
+        let mut journal = Journal::default();
+        assert_eq!(tree.tick(&mut journal), Status::Success);
+        assert_eq!(journal.ticked, vec![2]);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut tree = BehaviorTree::new();
+        let first = leaf(Action::new(1, 1, true), &mut tree);
+        let second = leaf(Action::new(2, 2, true), &mut tree);
+        let entry = selector([inverter(first, &mut tree), second], &mut tree);
+        tree.set_entry_node(entry);
+        tree.tick(&mut Journal::default());
+
+        let snapshot = tree.snapshot();
+        let statuses = snapshot
+            .nodes
+            .iter()
+            .map(|node| (node.depth, node.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                (0, Some(Status::Running)),
+                (1, Some(Status::Running)),
+                (2, Some(Status::Failure)),
+                (3, Some(Status::Success)),
+                (2, Some(Status::Running)),
+            ]
+        );
+        assert_eq!(snapshot.active_path().count(), 3);
+
+        let mut server = BehaviorDebugServer::bind("127.0.0.1:0").unwrap();
+        let mut client = BehaviorDebugClient::connect(server.local_address().unwrap()).unwrap();
+        let owner = ErasedHandle::new(4, 2);
+        client.send(owner, snapshot.clone()).unwrap();
+        for _ in 0..100 {
+            server.poll();
+            if server.snapshot(owner).is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(server.snapshot(owner), Some(&snapshot));
+    }
 }
@@ -0,0 +1,114 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Parallel node executes all its children on every tick. Its resulting status is defined by
+//! a pair of policies: one for success and one for failure. Children, that have finished their
+//! execution, won't be ticked again until the parallel node itself is finished.
+
+use crate::{
+    core::{pool::Handle, reflect::prelude::*, uuid_provider, visitor::prelude::*},
+    utils::behavior::{BehaviorNode, BehaviorTree, Status},
+};
+use std::cell::RefCell;
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how many children must finish with a particular status to finish the parallel node
+/// with the same status.
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum ParallelPolicy {
+    /// At least one child must finish with the status.
+    #[default]
+    RequireOne,
+    /// Every child must finish with the status.
+    RequireAll,
+}
+
+uuid_provider!(ParallelPolicy = "c2d4e6f8-1a3b-4c5d-8e7f-9a0b1c2d3e4f");
+
+impl ParallelPolicy {
+    pub(super) fn is_satisfied(self, count: usize, total: usize) -> bool {
+        match self {
+            ParallelPolicy::RequireOne => count > 0,
+            ParallelPolicy::RequireAll => total > 0 && count == total,
+        }
+    }
+}
+
+/// See module docs.
+#[derive(Debug, PartialEq, Visit, Eq, Clone)]
+pub struct ParallelNode<B>
+where
+    B: Clone,
+{
+    /// A set of children.
+    pub children: Vec<Handle<BehaviorNode<B>>>,
+    /// Defines when the node finishes with [`Status::Success`].
+    pub success_policy: ParallelPolicy,
+    /// Defines when the node finishes with [`Status::Failure`]. Failure policy is checked first.
+    pub failure_policy: ParallelPolicy,
+    /// Statuses of the children that have finished their execution in the current run.
+    #[visit(skip)]
+    pub(super) finished: RefCell<Vec<Option<Status>>>,
+}
+
+impl<B> Default for ParallelNode<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        Self {
+            children: Default::default(),
+            success_policy: ParallelPolicy::RequireAll,
+            failure_policy: ParallelPolicy::RequireOne,
+            finished: Default::default(),
+        }
+    }
+}
+
+impl<B> ParallelNode<B>
+where
+    B: Clone + 'static,
+{
+    /// Creates new parallel node, that succeeds when all its children succeed and fails as soon
+    /// as any child fails.
+    pub fn new(children: Vec<Handle<BehaviorNode<B>>>) -> Self {
+        Self {
+            children,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the policies of the node.
+    pub fn with_policies(
+        mut self,
+        success_policy: ParallelPolicy,
+        failure_policy: ParallelPolicy,
+    ) -> Self {
+        self.success_policy = success_policy;
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    /// Adds self to given behavior tree and returns handle to self.
+    pub fn add_to(self, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>> {
+        tree.add_node(BehaviorNode::Parallel(self))
+    }
+}
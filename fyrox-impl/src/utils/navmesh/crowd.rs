@@ -0,0 +1,768 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Crowd simulation with local avoidance for navmesh agents.
+//!
+//! [`Crowd`] simulates a set of [`NavmeshAgent`]s together: every agent follows its path on a navmesh,
+//! but its velocity is adjusted to avoid collisions with other agents and dynamic obstacles. Avoidance
+//! is based on Optimal Reciprocal Collision Avoidance (ORCA) - every pair of agents shares the effort
+//! of avoiding each other (proportionally to their priorities), and every agent chooses a velocity,
+//! that is the closest to its preferred velocity while being collision-free for a short period of time.
+//!
+//! Neighbours are found using a uniform grid, so a crowd of hundreds of agents could be updated every
+//! frame. The simulation is performed in XZ plane, vertical positions of the agents are taken from the
+//! navmesh.
+//!
+//! For more info see:
+//! - [RVO2 Library](https://gamma.cs.unc.edu/RVO2/)
+//! - van den Berg J., Guy S.J., Lin M., Manocha D. (2011) Reciprocal n-Body Collision Avoidance.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        pool::{Handle, Pool},
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    utils::navmesh::{Navmesh, NavmeshAgent},
+};
+use fxhash::FxHashMap;
+
+/// Parameters of the crowd simulation.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+#[visit(optional)]
+pub struct CrowdSettings {
+    /// Maximum distance (in meters) at which agents are considered as neighbours.
+    pub neighbour_distance: f32,
+    /// Maximum amount of the closest neighbours, that are taken into account by every agent.
+    pub max_neighbours: usize,
+    /// A time span (in seconds) for which the computed velocities of the agents are safe with
+    /// respect to other agents. Larger values make agents react sooner, but make them less agile.
+    pub time_horizon: f32,
+    /// A time span (in seconds) for which the computed velocities of the agents are safe with
+    /// respect to obstacles.
+    pub obstacle_time_horizon: f32,
+}
+
+impl Default for CrowdSettings {
+    fn default() -> Self {
+        Self {
+            neighbour_distance: 5.0,
+            max_neighbours: 10,
+            time_horizon: 2.0,
+            obstacle_time_horizon: 1.0,
+        }
+    }
+}
+
+/// Shape of a dynamic obstacle. Obstacles are infinitely tall, their shapes are defined in XZ plane.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub enum ObstacleShape {
+    /// A circle (a vertical cylinder).
+    Circle {
+        /// Radius of the circle.
+        radius: f32,
+    },
+    /// An oriented rectangle (a vertical box).
+    Box {
+        /// Half-sizes of the box along its local X and Z axes.
+        half_extents: Vector2<f32>,
+        /// Rotation of the box around Y axis (in radians).
+        rotation: f32,
+    },
+}
+
+impl Default for ObstacleShape {
+    fn default() -> Self {
+        Self::Circle { radius: 0.5 }
+    }
+}
+
+/// A dynamic obstacle, that is avoided by the agents of a crowd. Obstacles could be moved at any time,
+/// for example to follow a physical object (a door, a vehicle, etc.).
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct Obstacle {
+    /// Position of the center of the obstacle.
+    pub position: Vector3<f32>,
+    /// Shape of the obstacle.
+    pub shape: ObstacleShape,
+}
+
+impl Obstacle {
+    /// Creates a new circular obstacle.
+    pub fn circle(position: Vector3<f32>, radius: f32) -> Self {
+        Self {
+            position,
+            shape: ObstacleShape::Circle { radius },
+        }
+    }
+
+    /// Creates a new box obstacle.
+    pub fn cuboid(position: Vector3<f32>, half_extents: Vector2<f32>, rotation: f32) -> Self {
+        Self {
+            position,
+            shape: ObstacleShape::Box {
+                half_extents,
+                rotation,
+            },
+        }
+    }
+
+    // Returns a point of the obstacle that is the closest to the given point (in XZ plane), and radius
+    // around it that should be avoided.
+    fn closest_point(&self, point: Vector2<f32>) -> (Vector2<f32>, f32) {
+        let center = xz(self.position);
+        match self.shape {
+            ObstacleShape::Circle { radius } => (center, radius),
+            ObstacleShape::Box {
+                half_extents,
+                rotation,
+            } => {
+                let (sin, cos) = rotation.sin_cos();
+                let d = point - center;
+                let local = Vector2::new(d.x * cos + d.y * sin, -d.x * sin + d.y * cos);
+                let mut closest = Vector2::new(
+                    local.x.clamp(-half_extents.x, half_extents.x),
+                    local.y.clamp(-half_extents.y, half_extents.y),
+                );
+                if closest == local {
+                    // The point is inside the box, mirror the closest point on the boundary around
+                    // the point, so the agent will be pushed out of the box through the nearest side.
+                    let dx = half_extents.x - local.x.abs();
+                    let dy = half_extents.y - local.y.abs();
+                    let mut boundary = local;
+                    if dx < dy {
+                        boundary.x = half_extents.x.copysign(local.x);
+                    } else {
+                        boundary.y = half_extents.y.copysign(local.y);
+                    }
+                    closest = local.scale(2.0) - boundary;
+                }
+                let world = Vector2::new(
+                    closest.x * cos - closest.y * sin,
+                    closest.x * sin + closest.y * cos,
+                );
+                (center + world, 0.0)
+            }
+        }
+    }
+
+    fn extent(&self) -> f32 {
+        match self.shape {
+            ObstacleShape::Circle { radius } => radius,
+            ObstacleShape::Box { half_extents, .. } => half_extents.norm(),
+        }
+    }
+}
+
+fn xz(v: Vector3<f32>) -> Vector2<f32> {
+    Vector2::new(v.x, v.z)
+}
+
+fn det(a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+const EPSILON: f32 = 1.0e-5;
+
+/// A half-plane of permitted velocities: every velocity to the left of the directed line is permitted.
+#[derive(Copy, Clone, Debug)]
+struct Line {
+    point: Vector2<f32>,
+    direction: Vector2<f32>,
+}
+
+// Builds a half-plane of velocities, that avoid a collision with a disk moving with the given velocity
+// during the time horizon. `responsibility` defines a portion of the avoidance effort taken by the agent.
+#[allow(clippy::too_many_arguments)]
+fn orca_line(
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+    other_position: Vector2<f32>,
+    other_velocity: Vector2<f32>,
+    combined_radius: f32,
+    time_horizon: f32,
+    responsibility: f32,
+    dt: f32,
+) -> Option<Line> {
+    let relative_position = other_position - position;
+    let relative_velocity = velocity - other_velocity;
+    let distance_sqr = relative_position.norm_squared();
+    let combined_radius_sqr = combined_radius * combined_radius;
+
+    let (direction, u) = if distance_sqr > combined_radius_sqr {
+        let inv_time_horizon = 1.0 / time_horizon;
+        // Vector from cutoff center to relative velocity.
+        let w = relative_velocity - relative_position.scale(inv_time_horizon);
+        let w_length_sqr = w.norm_squared();
+        let dot = w.dot(&relative_position);
+        if dot < 0.0 && dot * dot > combined_radius_sqr * w_length_sqr {
+            // Project on cut-off circle.
+            let w_length = w_length_sqr.sqrt();
+            if w_length <= EPSILON {
+                return None;
+            }
+            let unit_w = w.scale(1.0 / w_length);
+            (
+                Vector2::new(unit_w.y, -unit_w.x),
+                unit_w.scale(combined_radius * inv_time_horizon - w_length),
+            )
+        } else {
+            // Project on legs.
+            let leg = (distance_sqr - combined_radius_sqr).sqrt();
+            let direction = if det(relative_position, w) > 0.0 {
+                Vector2::new(
+                    relative_position.x * leg - relative_position.y * combined_radius,
+                    relative_position.x * combined_radius + relative_position.y * leg,
+                )
+                .scale(1.0 / distance_sqr)
+            } else {
+                -Vector2::new(
+                    relative_position.x * leg + relative_position.y * combined_radius,
+                    -relative_position.x * combined_radius + relative_position.y * leg,
+                )
+                .scale(1.0 / distance_sqr)
+            };
+            let dot = relative_velocity.dot(&direction);
+            (direction, direction.scale(dot) - relative_velocity)
+        }
+    } else {
+        // Collision, project on cut-off circle of the time step.
+        let inv_time_step = 1.0 / dt.max(EPSILON);
+        let w = relative_velocity - relative_position.scale(inv_time_step);
+        let w_length = w.norm();
+        if w_length <= EPSILON {
+            return None;
+        }
+        let unit_w = w.scale(1.0 / w_length);
+        (
+            Vector2::new(unit_w.y, -unit_w.x),
+            unit_w.scale(combined_radius * inv_time_step - w_length),
+        )
+    };
+
+    Some(Line {
+        point: velocity + u.scale(responsibility),
+        direction,
+    })
+}
+
+// Solves a one-dimensional linear program on the given line subject to the constraints of the previous
+// lines and a circular constraint.
+fn linear_program1(
+    lines: &[Line],
+    line_no: usize,
+    radius: f32,
+    opt_velocity: Vector2<f32>,
+    direction_opt: bool,
+    result: &mut Vector2<f32>,
+) -> bool {
+    let line = lines[line_no];
+    let dot = line.point.dot(&line.direction);
+    let discriminant = dot * dot + radius * radius - line.point.norm_squared();
+    if discriminant < 0.0 {
+        // Max speed circle fully invalidates the line.
+        return false;
+    }
+
+    let sqrt_discriminant = discriminant.sqrt();
+    let mut t_left = -dot - sqrt_discriminant;
+    let mut t_right = -dot + sqrt_discriminant;
+
+    for other in &lines[..line_no] {
+        let denominator = det(line.direction, other.direction);
+        let numerator = det(other.direction, line.point - other.point);
+        if denominator.abs() <= EPSILON {
+            // The lines are (almost) parallel.
+            if numerator < 0.0 {
+                return false;
+            }
+            continue;
+        }
+        let t = numerator / denominator;
+        if denominator >= 0.0 {
+            t_right = t_right.min(t);
+        } else {
+            t_left = t_left.max(t);
+        }
+        if t_left > t_right {
+            return false;
+        }
+    }
+
+    let t = if direction_opt {
+        if opt_velocity.dot(&line.direction) > 0.0 {
+            t_right
+        } else {
+            t_left
+        }
+    } else {
+        line.direction
+            .dot(&(opt_velocity - line.point))
+            .clamp(t_left, t_right)
+    };
+    *result = line.point + line.direction.scale(t);
+    true
+}
+
+// Solves a two-dimensional linear program subject to the linear constraints and a circular constraint.
+// Returns the index of the line on which the program has failed, or the amount of lines on success.
+fn linear_program2(
+    lines: &[Line],
+    radius: f32,
+    opt_velocity: Vector2<f32>,
+    direction_opt: bool,
+    result: &mut Vector2<f32>,
+) -> usize {
+    *result = if direction_opt {
+        opt_velocity.scale(radius)
+    } else if opt_velocity.norm_squared() > radius * radius {
+        opt_velocity.normalize().scale(radius)
+    } else {
+        opt_velocity
+    };
+
+    for (i, line) in lines.iter().enumerate() {
+        if det(line.direction, line.point - *result) > 0.0 {
+            // The result does not satisfy the constraint, compute new optimal result.
+            let previous = *result;
+            if !linear_program1(lines, i, radius, opt_velocity, direction_opt, result) {
+                *result = previous;
+                return i;
+            }
+        }
+    }
+
+    lines.len()
+}
+
+// Solves a three-dimensional linear program when the two-dimensional one is infeasible - finds the
+// velocity, that minimizes the maximum penetration into the half-planes of the agents. Obstacle lines
+// are never violated.
+fn linear_program3(
+    lines: &[Line],
+    obstacle_lines: usize,
+    begin_line: usize,
+    radius: f32,
+    result: &mut Vector2<f32>,
+) {
+    let mut distance = 0.0;
+    let mut projected_lines = Vec::with_capacity(lines.len());
+
+    for i in begin_line..lines.len() {
+        let line = lines[i];
+        if det(line.direction, line.point - *result) > distance {
+            projected_lines.clear();
+            projected_lines.extend_from_slice(&lines[..obstacle_lines]);
+
+            for other in &lines[obstacle_lines..i] {
+                let determinant = det(line.direction, other.direction);
+                let point = if determinant.abs() <= EPSILON {
+                    if line.direction.dot(&other.direction) > 0.0 {
+                        // The lines point in the same direction.
+                        continue;
+                    }
+                    (line.point + other.point).scale(0.5)
+                } else {
+                    line.point
+                        + line
+                            .direction
+                            .scale(det(other.direction, line.point - other.point) / determinant)
+                };
+                let direction = other.direction - line.direction;
+                let length = direction.norm();
+                if length <= EPSILON {
+                    continue;
+                }
+                projected_lines.push(Line {
+                    point,
+                    direction: direction.scale(1.0 / length),
+                });
+            }
+
+            let previous = *result;
+            if linear_program2(
+                &projected_lines,
+                radius,
+                Vector2::new(-line.direction.y, line.direction.x),
+                true,
+                result,
+            ) < projected_lines.len()
+            {
+                // This should in principle not happen, the result is by definition already in
+                // the feasible region of this linear program. If it fails, it is due to small
+                // floating point error, and the current result is kept.
+                *result = previous;
+            }
+
+            distance = det(line.direction, line.point - *result);
+        }
+    }
+}
+
+#[derive(Clone)]
+struct AgentState {
+    handle: Handle<NavmeshAgent>,
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+    preferred_velocity: Vector2<f32>,
+    radius: f32,
+    max_speed: f32,
+    priority: f32,
+    on_link: bool,
+}
+
+type Cell = (i32, i32);
+
+/// See module docs.
+#[derive(Clone, Debug, Default, Visit)]
+pub struct Crowd {
+    agents: Pool<NavmeshAgent>,
+    obstacles: Pool<Obstacle>,
+    settings: CrowdSettings,
+    #[visit(skip)]
+    grid: FxHashMap<Cell, Vec<usize>>,
+}
+
+impl Crowd {
+    /// Creates a new crowd with the given settings.
+    pub fn new(settings: CrowdSettings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    /// Returns a shared reference to the settings of the crowd.
+    pub fn settings(&self) -> &CrowdSettings {
+        &self.settings
+    }
+
+    /// Sets new settings of the crowd.
+    pub fn set_settings(&mut self, settings: CrowdSettings) {
+        self.settings = settings;
+    }
+
+    /// Adds a new agent to the crowd and returns its handle.
+    pub fn add_agent(&mut self, agent: NavmeshAgent) -> Handle<NavmeshAgent> {
+        self.agents.spawn(agent)
+    }
+
+    /// Removes the agent from the crowd and returns it.
+    pub fn remove_agent(&mut self, handle: Handle<NavmeshAgent>) -> Option<NavmeshAgent> {
+        self.agents.try_free(handle)
+    }
+
+    /// Tries to borrow an agent of the crowd.
+    pub fn agent(&self, handle: Handle<NavmeshAgent>) -> Option<&NavmeshAgent> {
+        self.agents.try_borrow(handle)
+    }
+
+    /// Tries to borrow an agent of the crowd as mutable. It could be used to set a new target of the
+    /// agent, change its speed, etc.
+    pub fn agent_mut(&mut self, handle: Handle<NavmeshAgent>) -> Option<&mut NavmeshAgent> {
+        self.agents.try_borrow_mut(handle)
+    }
+
+    /// Returns an iterator over every agent of the crowd.
+    pub fn agents(&self) -> impl Iterator<Item = (Handle<NavmeshAgent>, &NavmeshAgent)> {
+        self.agents.pair_iter()
+    }
+
+    /// Returns the amount of agents in the crowd.
+    pub fn agents_count(&self) -> usize {
+        self.agents.alive_count() as usize
+    }
+
+    /// Adds a new obstacle to the crowd and returns its handle.
+    pub fn add_obstacle(&mut self, obstacle: Obstacle) -> Handle<Obstacle> {
+        self.obstacles.spawn(obstacle)
+    }
+
+    /// Removes the obstacle from the crowd and returns it.
+    pub fn remove_obstacle(&mut self, handle: Handle<Obstacle>) -> Option<Obstacle> {
+        self.obstacles.try_free(handle)
+    }
+
+    /// Tries to borrow an obstacle of the crowd.
+    pub fn obstacle(&self, handle: Handle<Obstacle>) -> Option<&Obstacle> {
+        self.obstacles.try_borrow(handle)
+    }
+
+    /// Tries to borrow an obstacle of the crowd as mutable. It could be used to move the obstacle.
+    pub fn obstacle_mut(&mut self, handle: Handle<Obstacle>) -> Option<&mut Obstacle> {
+        self.obstacles.try_borrow_mut(handle)
+    }
+
+    /// Returns an iterator over every obstacle of the crowd.
+    pub fn obstacles(&self) -> impl Iterator<Item = (Handle<Obstacle>, &Obstacle)> {
+        self.obstacles.pair_iter()
+    }
+
+    fn cell(&self, position: Vector2<f32>) -> Cell {
+        let size = self.settings.neighbour_distance.max(0.1);
+        (
+            (position.x / size).floor() as i32,
+            (position.y / size).floor() as i32,
+        )
+    }
+
+    /// Performs a single simulation step of every agent of the crowd: recalculates paths (if needed),
+    /// computes collision-free velocities and moves the agents along their paths on the navmesh.
+    pub fn update(&mut self, dt: f32, navmesh: &Navmesh) {
+        if dt <= 0.0 {
+            return;
+        }
+
+        let states = self
+            .agents
+            .pair_iter_mut()
+            .map(|(handle, agent)| {
+                agent.refresh_path(navmesh);
+                // Agents on off-mesh links are moved along the links and are not affected by
+                // local avoidance.
+                let on_link = agent.traverse_link(dt);
+                AgentState {
+                    handle,
+                    position: xz(agent.position),
+                    velocity: xz(agent.velocity),
+                    preferred_velocity: if on_link {
+                        Vector2::default()
+                    } else {
+                        xz(agent.preferred_velocity(dt))
+                    },
+                    radius: agent.radius,
+                    max_speed: agent.speed,
+                    priority: agent.priority,
+                    on_link,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for cell in self.grid.values_mut() {
+            cell.clear();
+        }
+        for (i, state) in states.iter().enumerate() {
+            let cell = self.cell(state.position);
+            self.grid.entry(cell).or_default().push(i);
+        }
+
+        let mut neighbours = Vec::new();
+        let mut lines = Vec::new();
+        for (i, state) in states.iter().enumerate() {
+            if state.on_link {
+                continue;
+            }
+
+            lines.clear();
+
+            // Obstacles go first, they're hard constraints.
+            for obstacle in self.obstacles.iter() {
+                let reach = self.settings.neighbour_distance + obstacle.extent() + state.radius;
+                if (xz(obstacle.position) - state.position).norm_squared() > reach * reach {
+                    continue;
+                }
+                let (point, radius) = obstacle.closest_point(state.position);
+                lines.extend(orca_line(
+                    state.position,
+                    state.velocity,
+                    point,
+                    Vector2::default(),
+                    radius + state.radius,
+                    self.settings.obstacle_time_horizon,
+                    1.0,
+                    dt,
+                ));
+            }
+            let obstacle_lines = lines.len();
+
+            // Find the closest neighbours.
+            neighbours.clear();
+            let (cx, cy) = self.cell(state.position);
+            let range_sqr = self.settings.neighbour_distance * self.settings.neighbour_distance;
+            for x in cx - 1..=cx + 1 {
+                for y in cy - 1..=cy + 1 {
+                    let Some(indices) = self.grid.get(&(x, y)) else {
+                        continue;
+                    };
+                    for &j in indices {
+                        if j != i {
+                            let distance_sqr = (states[j].position - state.position).norm_squared();
+                            if distance_sqr < range_sqr {
+                                neighbours.push((distance_sqr, j));
+                            }
+                        }
+                    }
+                }
+            }
+            neighbours.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+            neighbours.truncate(self.settings.max_neighbours);
+
+            for &(_, j) in neighbours.iter() {
+                let other = &states[j];
+                let priorities = state.priority + other.priority;
+                let responsibility = if priorities > EPSILON {
+                    other.priority / priorities
+                } else {
+                    0.5
+                };
+                lines.extend(orca_line(
+                    state.position,
+                    state.velocity,
+                    other.position,
+                    other.velocity,
+                    state.radius + other.radius,
+                    self.settings.time_horizon,
+                    responsibility,
+                    dt,
+                ));
+            }
+
+            let mut velocity = Vector2::default();
+            let failed_line = linear_program2(
+                &lines,
+                state.max_speed,
+                state.preferred_velocity,
+                false,
+                &mut velocity,
+            );
+            if failed_line < lines.len() {
+                linear_program3(
+                    &lines,
+                    obstacle_lines,
+                    failed_line,
+                    state.max_speed,
+                    &mut velocity,
+                );
+            }
+
+            let agent = &mut self.agents[state.handle];
+            let desired = Vector3::new(
+                agent.position.x + velocity.x * dt,
+                agent.position.y,
+                agent.position.z + velocity.y * dt,
+            );
+            // Keep the agent on the navmesh.
+            let new_position = navmesh
+                .query_closest(desired)
+                .map_or(desired, |(point, _)| point);
+            agent.move_to(new_position, dt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            math::TriangleDefinition,
+        },
+        utils::navmesh::{
+            crowd::{Crowd, Obstacle},
+            Navmesh, NavmeshAgentBuilder,
+        },
+    };
+
+    fn make_navmesh() -> Navmesh {
+        Navmesh::new(
+            vec![TriangleDefinition([0, 1, 2]), TriangleDefinition([0, 2, 3])],
+            vec![
+                Vector3::new(-10.0, 0.0, -10.0),
+                Vector3::new(-10.0, 0.0, 10.0),
+                Vector3::new(10.0, 0.0, 10.0),
+                Vector3::new(10.0, 0.0, -10.0),
+            ],
+        )
+    }
+
+    fn min_distance(crowd: &Crowd) -> f32 {
+        let positions = crowd
+            .agents()
+            .map(|(_, agent)| agent.position())
+            .collect::<Vec<_>>();
+        let mut min = f32::MAX;
+        for (i, a) in positions.iter().enumerate() {
+            for b in positions[i + 1..].iter() {
+                min = min.min(a.metric_distance(b));
+            }
+        }
+        min
+    }
+
+    #[test]
+    fn test_head_on_avoidance() {
+        let navmesh = make_navmesh();
+        let mut crowd = Crowd::default();
+        let a = crowd.add_agent(
+            NavmeshAgentBuilder::new()
+                .with_position(Vector3::new(-5.0, 0.0, 0.0))
+                .with_target(Vector3::new(5.0, 0.0, 0.0))
+                .with_radius(0.5)
+                .build(),
+        );
+        let b = crowd.add_agent(
+            NavmeshAgentBuilder::new()
+                .with_position(Vector3::new(5.0, 0.0, 0.01))
+                .with_target(Vector3::new(-5.0, 0.0, 0.01))
+                .with_radius(0.5)
+                .build(),
+        );
+
+        let mut closest = f32::MAX;
+        for _ in 0..600 {
+            crowd.update(1.0 / 60.0, &navmesh);
+            closest = closest.min(min_distance(&crowd));
+        }
+
+        // Agents must never overlap and both must reach their targets.
+        assert!(closest >= 0.95, "{closest}");
+        let a = crowd.agent(a).unwrap();
+        let b = crowd.agent(b).unwrap();
+        assert!(a.position().metric_distance(&a.target()) < 0.1);
+        assert!(b.position().metric_distance(&b.target()) < 0.1);
+    }
+
+    #[test]
+    fn test_obstacle_avoidance() {
+        let navmesh = make_navmesh();
+        let mut crowd = Crowd::default();
+        crowd.add_obstacle(Obstacle::circle(Vector3::new(0.0, 0.0, 0.0), 1.0));
+        crowd.add_obstacle(Obstacle::cuboid(
+            Vector3::new(0.0, 0.0, 4.0),
+            Vector2::new(0.5, 1.0),
+            0.3,
+        ));
+        let agent = crowd.add_agent(
+            NavmeshAgentBuilder::new()
+                .with_position(Vector3::new(-5.0, 0.0, 0.05))
+                .with_target(Vector3::new(5.0, 0.0, 0.05))
+                .with_radius(0.3)
+                .build(),
+        );
+
+        for _ in 0..900 {
+            crowd.update(1.0 / 60.0, &navmesh);
+            let position = crowd.agent(agent).unwrap().position();
+            assert!(position.xz().norm() >= 1.25, "{position}");
+        }
+        let agent = crowd.agent(agent).unwrap();
+        assert!(agent.position().metric_distance(&agent.target()) < 0.1);
+    }
+}
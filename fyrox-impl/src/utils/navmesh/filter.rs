@@ -0,0 +1,135 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Area types and query filters. Every triangle of a navmesh has an area type (for example -
+//! ground, road, water, etc.), that defines the cost of travelling across it. Query filters
+//! allow agents to exclude some area types and off-mesh links from pathfinding and to override
+//! area costs.
+
+use crate::{
+    core::{reflect::prelude::*, uuid_provider, visitor::prelude::*},
+    utils::navmesh::link::OffMeshLinkKind,
+};
+
+/// Max amount of area types.
+pub const MAX_AREA_TYPES: usize = 32;
+
+/// Default area type of every triangle.
+pub const DEFAULT_AREA: u8 = 0;
+
+/// Defines which parts of a navmesh an agent is allowed to use and how much it costs.
+///
+/// ```rust
+/// # use fyrox_impl::utils::navmesh::{filter::NavmeshQueryFilter, link::OffMeshLinkKind};
+/// const WATER: u8 = 1;
+///
+/// fn land_unit_filter() -> NavmeshQueryFilter {
+///     NavmeshQueryFilter::default()
+///         .with_excluded_area(WATER)
+///         .with_excluded_link(OffMeshLinkKind::Ladder)
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct NavmeshQueryFilter {
+    /// A bit mask of allowed area types. Bit `N` allows area type `N`.
+    pub area_mask: u32,
+    /// A bit mask of allowed off-mesh link kinds. Bit `N` allows a link kind with discriminant `N`.
+    pub link_mask: u32,
+    /// Per-area cost multipliers, they're applied on top of area costs of a navmesh. Missing
+    /// values are treated as `1.0`.
+    pub area_costs: Vec<f32>,
+}
+
+uuid_provider!(NavmeshQueryFilter = "8d3b5f7a-2c4e-4a91-b6d8-0e1f3a5c7b92");
+
+impl Default for NavmeshQueryFilter {
+    fn default() -> Self {
+        Self {
+            area_mask: u32::MAX,
+            link_mask: u32::MAX,
+            area_costs: Vec::new(),
+        }
+    }
+}
+
+impl NavmeshQueryFilter {
+    /// Returns `true` if the area type is allowed by the filter.
+    pub fn allows_area(&self, area: u8) -> bool {
+        (area as usize) < MAX_AREA_TYPES && self.area_mask & (1 << area) != 0
+    }
+
+    /// Returns `true` if the off-mesh link kind is allowed by the filter.
+    pub fn allows_link(&self, kind: OffMeshLinkKind) -> bool {
+        self.link_mask & (1 << kind as u32) != 0
+    }
+
+    /// Returns cost multiplier of the area type.
+    pub fn area_cost(&self, area: u8) -> f32 {
+        self.area_costs.get(area as usize).copied().unwrap_or(1.0)
+    }
+
+    /// Allows or forbids the area type.
+    pub fn set_area_allowed(&mut self, area: u8, allowed: bool) {
+        if (area as usize) < MAX_AREA_TYPES {
+            if allowed {
+                self.area_mask |= 1 << area;
+            } else {
+                self.area_mask &= !(1 << area);
+            }
+        }
+    }
+
+    /// Allows or forbids the off-mesh link kind.
+    pub fn set_link_allowed(&mut self, kind: OffMeshLinkKind, allowed: bool) {
+        if allowed {
+            self.link_mask |= 1 << kind as u32;
+        } else {
+            self.link_mask &= !(1 << kind as u32);
+        }
+    }
+
+    /// Sets cost multiplier of the area type.
+    pub fn set_area_cost(&mut self, area: u8, cost: f32) {
+        let area = area as usize;
+        if area < MAX_AREA_TYPES {
+            if self.area_costs.len() <= area {
+                self.area_costs.resize(area + 1, 1.0);
+            }
+            self.area_costs[area] = cost;
+        }
+    }
+
+    /// Forbids the area type.
+    pub fn with_excluded_area(mut self, area: u8) -> Self {
+        self.set_area_allowed(area, false);
+        self
+    }
+
+    /// Forbids the off-mesh link kind.
+    pub fn with_excluded_link(mut self, kind: OffMeshLinkKind) -> Self {
+        self.set_link_allowed(kind, false);
+        self
+    }
+
+    /// Sets cost multiplier of the area type.
+    pub fn with_area_cost(mut self, area: u8, cost: f32) -> Self {
+        self.set_area_cost(area, cost);
+        self
+    }
+}
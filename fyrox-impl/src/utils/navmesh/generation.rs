@@ -0,0 +1,1752 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Automatic navigation mesh generation from arbitrary scene geometry. The algorithm is very
+//! similar to the one used in Recast:
+//!
+//! 1. Input triangles are rasterized into a voxel height field, each triangle marks its spans as
+//! walkable if its slope is less than the max slope of an agent.
+//! 2. Spans are filtered - low-hanging obstacles (such as stairs steps) become walkable, ledges
+//! and spans with too little free space above them become non-walkable.
+//! 3. Walkable area is eroded by the agent radius, so the agent will not clip walls.
+//! 4. Walkable spans are split into simple (monotone) regions.
+//! 5. Region contours are traced and simplified.
+//! 6. Contours are triangulated and the triangles of every tile are stitched into a single
+//! [`Navmesh`].
+//!
+//! Generation is done in square tiles, which allows rebuilding only a small part of the navmesh
+//! when some geometry changes (a door opens, a wall gets destroyed, etc.), see
+//! [`TiledNavmeshBuilder`] for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector3},
+        math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
+        reflect::prelude::*,
+        uuid_provider,
+        visitor::prelude::*,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    scene::{
+        base::Mobility,
+        collider::{Collider, ColliderShape},
+        graph::Graph,
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            surface::SurfaceData,
+            Mesh,
+        },
+        node::Node,
+        rigidbody::{RigidBody, RigidBodyType},
+        terrain::Terrain,
+    },
+    utils::navmesh::Navmesh,
+};
+use fxhash::FxHashMap;
+use fyrox_core::pool::Handle;
+
+const MAX_HEIGHT: i32 = i32::MAX / 4;
+const NOT_CONNECTED: u32 = u32::MAX;
+const BORDER_REGION_FLAG: u32 = 1 << 31;
+const NULL_NEIGHBOUR: u32 = u32::MAX;
+const DIR_OFFSET_X: [i32; 4] = [-1, 0, 1, 0];
+const DIR_OFFSET_Z: [i32; 4] = [0, 1, 0, -1];
+
+/// A set of parameters, that defines how navigational mesh will be generated. All sizes are
+/// defined in meters.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct NavmeshGenerationSettings {
+    /// Horizontal size of a single voxel. Smaller values give more precise results, but
+    /// significantly increase generation time.
+    #[reflect(min_value = 0.01, step = 0.05)]
+    pub cell_size: f32,
+    /// Vertical size of a single voxel.
+    #[reflect(min_value = 0.01, step = 0.05)]
+    pub cell_height: f32,
+    /// Minimal height of free space above a walkable surface.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub agent_height: f32,
+    /// Radius of an agent. Walkable area is shrunk by this value.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub agent_radius: f32,
+    /// Max height of a step, that an agent can climb.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub agent_max_climb: f32,
+    /// Max slope (in degrees) of a walkable surface.
+    #[reflect(min_value = 0.0, max_value = 90.0, step = 1.0)]
+    pub agent_max_slope: f32,
+    /// Isolated walkable areas with square less than this value will be removed.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub min_region_area: f32,
+    /// Max distance between simplified contour edges and the actual boundary of walkable area.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub max_edge_error: f32,
+    /// Size of a single tile in cells.
+    #[reflect(min_value = 8.0, step = 1.0)]
+    pub tile_size: u32,
+}
+
+uuid_provider!(NavmeshGenerationSettings = "b5d2c1e0-7f4a-4c39-8e16-2d9a0b3f6c71");
+
+impl Default for NavmeshGenerationSettings {
+    fn default() -> Self {
+        Self {
+            cell_size: 0.2,
+            cell_height: 0.1,
+            agent_height: 2.0,
+            agent_radius: 0.4,
+            agent_max_climb: 0.4,
+            agent_max_slope: 45.0,
+            min_region_area: 2.0,
+            max_edge_error: 0.3,
+            tile_size: 64,
+        }
+    }
+}
+
+impl NavmeshGenerationSettings {
+    fn cell_size(&self) -> f32 {
+        self.cell_size.max(0.001)
+    }
+
+    fn cell_height(&self) -> f32 {
+        self.cell_height.max(0.001)
+    }
+
+    fn tile_size(&self) -> i32 {
+        self.tile_size.max(1) as i32
+    }
+
+    fn walkable_height(&self) -> i32 {
+        (self.agent_height / self.cell_height()).ceil() as i32
+    }
+
+    fn walkable_climb(&self) -> i32 {
+        (self.agent_max_climb / self.cell_height()).floor() as i32
+    }
+
+    fn walkable_radius(&self) -> i32 {
+        (self.agent_radius / self.cell_size()).ceil() as i32
+    }
+
+    fn border_size(&self) -> i32 {
+        self.walkable_radius() + 3
+    }
+
+    fn tile_world_size(&self) -> f32 {
+        self.tile_size() as f32 * self.cell_size()
+    }
+
+    fn tile_range(&self, bounds: &AxisAlignedBoundingBox) -> ((i32, i32), (i32, i32)) {
+        let size = self.tile_world_size();
+        (
+            (
+                (bounds.min.x / size).floor() as i32,
+                (bounds.min.z / size).floor() as i32,
+            ),
+            (
+                (bounds.max.x / size).floor() as i32,
+                (bounds.max.z / size).floor() as i32,
+            ),
+        )
+    }
+}
+
+/// A triangle soup, that is used as a source for navmesh generation. It could be filled
+/// manually or collected from a scene graph using [`NavmeshGeometry::from_graph`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NavmeshGeometry {
+    /// Vertices of the geometry in world space.
+    pub vertices: Vec<Vector3<f32>>,
+    /// Triangles of the geometry. Triangles with counter-clockwise winding (when looking from
+    /// above) are treated as facing up.
+    pub triangles: Vec<TriangleDefinition>,
+}
+
+impl NavmeshGeometry {
+    /// Collects geometry of every enabled static mesh and every collider attached to a static
+    /// rigid body in the given graph.
+    pub fn from_graph(graph: &Graph) -> Self {
+        Self::from_graph_filtered(graph, |_, _| true)
+    }
+
+    /// Same as [`Self::from_graph`], but allows to exclude some nodes from the geometry. The
+    /// filter is called only for nodes, that could be used as a source of geometry.
+    pub fn from_graph_filtered<F>(graph: &Graph, mut filter: F) -> Self
+    where
+        F: FnMut(Handle<Node>, &Node) -> bool,
+    {
+        let mut geometry = Self::default();
+        for (handle, node) in graph.pair_iter() {
+            if !node.is_globally_enabled() {
+                continue;
+            }
+
+            if let Some(mesh) = node.cast::<Mesh>() {
+                if mesh.mobility() == Mobility::Static && filter(handle, node) {
+                    geometry.add_mesh(mesh);
+                }
+            } else if let Some(collider) = node.cast::<Collider>() {
+                let is_static = graph
+                    .try_get(collider.parent())
+                    .and_then(|parent| parent.cast::<RigidBody>())
+                    .map_or(true, |body| body.body_type() == RigidBodyType::Static);
+                if is_static && filter(handle, node) {
+                    geometry.add_collider(collider, graph);
+                }
+            }
+        }
+        geometry
+    }
+
+    /// Adds a triangle to the geometry.
+    pub fn add_triangle(&mut self, a: Vector3<f32>, b: Vector3<f32>, c: Vector3<f32>) {
+        let first = self.vertices.len() as u32;
+        self.vertices.extend_from_slice(&[a, b, c]);
+        self.triangles
+            .push(TriangleDefinition([first, first + 1, first + 2]));
+    }
+
+    /// Adds surface data transformed by the given matrix to the geometry.
+    pub fn add_surface_data(&mut self, data: &SurfaceData, transform: &Matrix4<f32>) {
+        let first = self.vertices.len() as u32;
+        for view in data.vertex_buffer.iter() {
+            if let Ok(position) = view.read_3_f32(VertexAttributeUsage::Position) {
+                self.vertices
+                    .push(transform.transform_point(&Point3::from(position)).coords);
+            } else {
+                return;
+            }
+        }
+        for triangle in data.geometry_buffer.iter() {
+            self.triangles
+                .push(TriangleDefinition(triangle.0.map(|i| i + first)));
+        }
+    }
+
+    /// Adds every surface of the mesh to the geometry.
+    pub fn add_mesh(&mut self, mesh: &Mesh) {
+        let transform = mesh.global_transform();
+        for surface in mesh.surfaces() {
+            let data = surface.data();
+            let data = data.data_ref();
+            self.add_surface_data(&data, &transform);
+        }
+    }
+
+    /// Adds height map of the terrain to the geometry.
+    pub fn add_terrain(&mut self, terrain: &Terrain) {
+        let transform = terrain.global_transform();
+        let size = terrain.height_map_size();
+        if size.x < 4 || size.y < 4 {
+            return;
+        }
+        // Height maps have a one pixel margin, that duplicates the neighbouring chunks.
+        let (columns, rows) = (size.x as usize - 2, size.y as usize - 2);
+        for chunk in terrain.chunks_ref() {
+            let heightmap = chunk.heightmap().data_ref();
+            let Some(heights) = heightmap.data_of_type::<f32>() else {
+                continue;
+            };
+            let origin = chunk.position();
+            let physical_size = chunk.physical_size();
+            let first = self.vertices.len() as u32;
+            for z in 0..rows {
+                for x in 0..columns {
+                    let height = heights[(z + 1) * size.x as usize + x + 1];
+                    let local = origin
+                        + Vector3::new(
+                            x as f32 / (columns - 1) as f32 * physical_size.x,
+                            height,
+                            z as f32 / (rows - 1) as f32 * physical_size.y,
+                        );
+                    self.vertices
+                        .push(transform.transform_point(&Point3::from(local)).coords);
+                }
+            }
+            for z in 0..rows - 1 {
+                for x in 0..columns - 1 {
+                    let i00 = first + (z * columns + x) as u32;
+                    let i10 = i00 + 1;
+                    let i01 = i00 + columns as u32;
+                    let i11 = i01 + 1;
+                    self.triangles.push(TriangleDefinition([i00, i01, i10]));
+                    self.triangles.push(TriangleDefinition([i10, i01, i11]));
+                }
+            }
+        }
+    }
+
+    /// Adds the shape of the collider to the geometry. Primitive shapes are approximated by
+    /// triangle meshes, triangle mesh and height field shapes take their geometry from their
+    /// sources in the given graph.
+    pub fn add_collider(&mut self, collider: &Collider, graph: &Graph) {
+        let transform = collider.global_transform();
+        let shifted =
+            |offset: f32| transform * Matrix4::new_translation(&Vector3::new(0.0, offset, 0.0));
+        match collider.shape() {
+            ColliderShape::Ball(ball) => {
+                self.add_surface_data(
+                    &SurfaceData::make_sphere(8, 8, ball.radius, &transform),
+                    &Matrix4::identity(),
+                );
+            }
+            ColliderShape::Cylinder(cylinder) => {
+                self.add_surface_data(
+                    &SurfaceData::make_cylinder(
+                        12,
+                        cylinder.radius,
+                        2.0 * cylinder.half_height,
+                        true,
+                        &shifted(-cylinder.half_height),
+                    ),
+                    &Matrix4::identity(),
+                );
+            }
+            ColliderShape::Cone(cone) => {
+                self.add_surface_data(
+                    &SurfaceData::make_cone(
+                        12,
+                        cone.radius,
+                        2.0 * cone.half_height,
+                        &shifted(-cone.half_height),
+                    ),
+                    &Matrix4::identity(),
+                );
+            }
+            ColliderShape::Cuboid(cuboid) => {
+                self.add_surface_data(
+                    &SurfaceData::make_cube(
+                        transform * Matrix4::new_nonuniform_scaling(&(cuboid.half_extents * 2.0)),
+                    ),
+                    &Matrix4::identity(),
+                );
+            }
+            ColliderShape::Capsule(capsule) => {
+                let axis = capsule.end - capsule.begin;
+                let rotation = UnitQuaternion::rotation_between(&Vector3::y(), &axis)
+                    .unwrap_or_else(UnitQuaternion::identity);
+                let body = transform
+                    * Matrix4::new_translation(&capsule.begin)
+                    * rotation.to_homogeneous();
+                self.add_surface_data(
+                    &SurfaceData::make_cylinder(12, capsule.radius, axis.norm(), false, &body),
+                    &Matrix4::identity(),
+                );
+                for center in [capsule.begin, capsule.end] {
+                    self.add_surface_data(
+                        &SurfaceData::make_sphere(
+                            8,
+                            8,
+                            capsule.radius,
+                            &(transform * Matrix4::new_translation(&center)),
+                        ),
+                        &Matrix4::identity(),
+                    );
+                }
+            }
+            ColliderShape::Triangle(triangle) => {
+                let [a, b, c] = [triangle.a, triangle.b, triangle.c]
+                    .map(|p| transform.transform_point(&Point3::from(p)).coords);
+                self.add_triangle(a, b, c);
+            }
+            ColliderShape::Segment(_) => {}
+            ColliderShape::Trimesh(trimesh) => {
+                for source in trimesh.sources.iter() {
+                    if let Some(mesh) = graph.try_get_of_type::<Mesh>(source.0) {
+                        self.add_mesh(mesh);
+                    }
+                }
+            }
+            ColliderShape::Polyhedron(polyhedron) => {
+                if let Some(mesh) = graph.try_get_of_type::<Mesh>(polyhedron.geometry_source.0) {
+                    self.add_mesh(mesh);
+                }
+            }
+            ColliderShape::Heightfield(heightfield) => {
+                if let Some(terrain) =
+                    graph.try_get_of_type::<Terrain>(heightfield.geometry_source.0)
+                {
+                    self.add_terrain(terrain);
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the geometry has no triangles.
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    /// Calculates world-space bounds of the geometry.
+    pub fn bounds(&self) -> AxisAlignedBoundingBox {
+        let mut bounds = AxisAlignedBoundingBox::default();
+        for triangle in self.triangles.iter() {
+            for &index in triangle.0.iter() {
+                bounds.add_point(self.vertices[index as usize]);
+            }
+        }
+        bounds
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Span {
+    min: i32,
+    max: i32,
+    walkable: bool,
+}
+
+/// Solid height field, each column contains a sorted list of non-overlapping spans.
+struct Heightfield {
+    width: i32,
+    depth: i32,
+    origin: (i32, i32),
+    columns: Vec<Vec<Span>>,
+}
+
+fn divide_poly(
+    poly: &[Vector3<f32>],
+    line: f32,
+    axis: usize,
+) -> (Vec<Vector3<f32>>, Vec<Vector3<f32>>) {
+    let mut below = Vec::with_capacity(poly.len() + 1);
+    let mut above = Vec::with_capacity(poly.len() + 1);
+    for i in 0..poly.len() {
+        let j = (i + poly.len() - 1) % poly.len();
+        let dj = line - poly[j][axis];
+        let di = line - poly[i][axis];
+        if (dj >= 0.0) != (di >= 0.0) {
+            let s = dj / (dj - di);
+            let point = poly[j].lerp(&poly[i], s);
+            below.push(point);
+            above.push(point);
+            // Points lying on the line were already added above.
+            if di > 0.0 {
+                below.push(poly[i]);
+            } else if di < 0.0 {
+                above.push(poly[i]);
+            }
+        } else {
+            if di >= 0.0 {
+                below.push(poly[i]);
+                if di != 0.0 {
+                    continue;
+                }
+            }
+            above.push(poly[i]);
+        }
+    }
+    (below, above)
+}
+
+fn add_span(column: &mut Vec<Span>, mut span: Span, merge_threshold: i32) {
+    let mut i = 0;
+    while i < column.len() {
+        let current = column[i];
+        if current.min > span.max {
+            break;
+        }
+        if current.max < span.min {
+            i += 1;
+            continue;
+        }
+        span.min = span.min.min(current.min);
+        if current.max > span.max {
+            // The top of the merged span belongs to the existing span.
+            if current.max - span.max > merge_threshold {
+                span.walkable = current.walkable;
+            } else {
+                span.walkable |= current.walkable;
+            }
+            span.max = current.max;
+        } else if span.max - current.max <= merge_threshold {
+            span.walkable |= current.walkable;
+        }
+        column.remove(i);
+    }
+    column.insert(i, span);
+}
+
+impl Heightfield {
+    fn new(width: i32, depth: i32, origin: (i32, i32)) -> Self {
+        Self {
+            width,
+            depth,
+            origin,
+            columns: vec![Vec::new(); (width * depth) as usize],
+        }
+    }
+
+    fn column(&self, x: i32, z: i32) -> Option<&[Span]> {
+        if x < 0 || z < 0 || x >= self.width || z >= self.depth {
+            None
+        } else {
+            Some(&self.columns[(x + z * self.width) as usize])
+        }
+    }
+
+    fn rasterize_triangle(
+        &mut self,
+        triangle: [Vector3<f32>; 3],
+        walkable: bool,
+        settings: &NavmeshGenerationSettings,
+    ) {
+        let cs = settings.cell_size();
+        let ch = settings.cell_height();
+        let min_x = self.origin.0 as f32 * cs;
+        let min_z = self.origin.1 as f32 * cs;
+        let max_x = min_x + self.width as f32 * cs;
+        let max_z = min_z + self.depth as f32 * cs;
+
+        let t_min = triangle[0].inf(&triangle[1]).inf(&triangle[2]);
+        let t_max = triangle[0].sup(&triangle[1]).sup(&triangle[2]);
+        if t_max.x < min_x || t_min.x > max_x || t_max.z < min_z || t_min.z > max_z {
+            return;
+        }
+
+        // Clipping may produce degenerate slivers, when a triangle edge lies on a cell boundary.
+        // Such slivers must be ignored, otherwise they'll add spans to the neighbouring cells.
+        // Walls, that are parallel to a cell boundary, are not affected by this.
+        let normal = (triangle[1] - triangle[0])
+            .cross(&(triangle[2] - triangle[0]))
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default();
+        let is_sliver = |poly: &[Vector3<f32>], axis: usize| {
+            let (min, max) = poly.iter().fold((f32::MAX, f32::MIN), |(a, b), p| {
+                (a.min(p[axis]), b.max(p[axis]))
+            });
+            max - min < 1.0e-3 * cs && normal[axis].abs() < 0.5
+        };
+
+        let z0 = (((t_min.z - min_z) / cs).floor() as i32).clamp(-1, self.depth - 1);
+        let z1 = (((t_max.z - min_z) / cs).floor() as i32).clamp(-1, self.depth - 1);
+
+        let mut rest = triangle.to_vec();
+        for z in z0..=z1 {
+            let line = (self.origin.1 + z + 1) as f32 * cs;
+            let (row, above) = divide_poly(&rest, line, 2);
+            rest = above;
+            if row.len() < 3 || z < 0 || is_sliver(&row, 2) {
+                continue;
+            }
+
+            let (row_min_x, row_max_x) = row
+                .iter()
+                .fold((f32::MAX, f32::MIN), |(a, b), p| (a.min(p.x), b.max(p.x)));
+            let x0 = (((row_min_x - min_x) / cs).floor() as i32).clamp(-1, self.width - 1);
+            let x1 = (((row_max_x - min_x) / cs).floor() as i32).clamp(-1, self.width - 1);
+
+            let mut row_rest = row;
+            for x in x0..=x1 {
+                let line = (self.origin.0 + x + 1) as f32 * cs;
+                let (cell, right) = divide_poly(&row_rest, line, 0);
+                row_rest = right;
+                if cell.len() < 3 || x < 0 || is_sliver(&cell, 0) {
+                    continue;
+                }
+
+                let (y_min, y_max) = cell
+                    .iter()
+                    .fold((f32::MAX, f32::MIN), |(a, b), p| (a.min(p.y), b.max(p.y)));
+                let span_min = (y_min / ch).floor() as i32;
+                let span_max = ((y_max / ch).ceil() as i32).max(span_min + 1);
+                add_span(
+                    &mut self.columns[(x + z * self.width) as usize],
+                    Span {
+                        min: span_min.clamp(-MAX_HEIGHT, MAX_HEIGHT),
+                        max: span_max.clamp(-MAX_HEIGHT, MAX_HEIGHT),
+                        walkable,
+                    },
+                    settings.walkable_climb(),
+                );
+            }
+        }
+    }
+
+    /// Marks non-walkable spans as walkable, if they're close enough to a walkable span below
+    /// them. This allows stairs, curbs and other small obstacles to be walked over.
+    fn filter_low_hanging_obstacles(&mut self, walkable_climb: i32) {
+        for column in self.columns.iter_mut() {
+            let mut previous: Option<Span> = None;
+            for span in column.iter_mut() {
+                let original = *span;
+                if let Some(previous) = previous {
+                    if !span.walkable
+                        && previous.walkable
+                        && span.max - previous.max <= walkable_climb
+                    {
+                        span.walkable = true;
+                    }
+                }
+                previous = Some(original);
+            }
+        }
+    }
+
+    /// Marks spans, that are near a ledge (a drop higher than the max climb), as non-walkable.
+    fn filter_ledge_spans(&mut self, walkable_height: i32, walkable_climb: i32) {
+        for z in 0..self.depth {
+            for x in 0..self.width {
+                let index = (x + z * self.width) as usize;
+                for s in 0..self.columns[index].len() {
+                    let column = &self.columns[index];
+                    if !column[s].walkable {
+                        continue;
+                    }
+                    let bottom = column[s].max;
+                    let top = column.get(s + 1).map_or(MAX_HEIGHT, |n| n.min);
+
+                    let mut min_height = MAX_HEIGHT;
+                    let mut accessible_min = bottom;
+                    let mut accessible_max = bottom;
+                    for dir in 0..4 {
+                        let Some(neighbour) =
+                            self.column(x + DIR_OFFSET_X[dir], z + DIR_OFFSET_Z[dir])
+                        else {
+                            min_height = -walkable_climb - 1;
+                            break;
+                        };
+
+                        // The space below the first span of the neighbour column is a drop into
+                        // nowhere.
+                        let neighbour_top = neighbour.first().map_or(MAX_HEIGHT, |n| n.min);
+                        if top.min(neighbour_top) - bottom > walkable_height {
+                            min_height = -walkable_climb - 1;
+                            break;
+                        }
+
+                        for (k, span) in neighbour.iter().enumerate() {
+                            let neighbour_bottom = span.max;
+                            let neighbour_top = neighbour.get(k + 1).map_or(MAX_HEIGHT, |n| n.min);
+                            if top.min(neighbour_top) - bottom.max(neighbour_bottom)
+                                > walkable_height
+                            {
+                                min_height = min_height.min(neighbour_bottom - bottom);
+                                if (neighbour_bottom - bottom).abs() <= walkable_climb {
+                                    accessible_min = accessible_min.min(neighbour_bottom);
+                                    accessible_max = accessible_max.max(neighbour_bottom);
+                                }
+                            }
+                        }
+                    }
+
+                    if min_height < -walkable_climb
+                        || accessible_max - accessible_min > walkable_climb
+                    {
+                        self.columns[index][s].walkable = false;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Marks spans with not enough free space above them as non-walkable.
+    fn filter_low_height_spans(&mut self, walkable_height: i32) {
+        for column in self.columns.iter_mut() {
+            for s in 0..column.len() {
+                let top = column.get(s + 1).map_or(MAX_HEIGHT, |n| n.min);
+                if top - column[s].max < walkable_height {
+                    column[s].walkable = false;
+                }
+            }
+        }
+    }
+}
+
+fn is_interior_region(region: u32) -> bool {
+    region != 0 && region & BORDER_REGION_FLAG == 0
+}
+
+#[derive(Clone, Debug)]
+struct CompactSpan {
+    y: i32,
+    connections: [u32; 4],
+    region: u32,
+    walkable: bool,
+}
+
+/// Open height field, that contains only walkable spans and connections between them.
+struct CompactHeightfield {
+    width: i32,
+    depth: i32,
+    cells: Vec<(u32, u32)>,
+    spans: Vec<CompactSpan>,
+}
+
+impl CompactHeightfield {
+    fn new(heightfield: &Heightfield, walkable_height: i32, walkable_climb: i32) -> Self {
+        let mut cells = Vec::with_capacity(heightfield.columns.len());
+        let mut spans = Vec::new();
+        let mut tops = Vec::new();
+        for column in heightfield.columns.iter() {
+            let first = spans.len() as u32;
+            for (s, span) in column.iter().enumerate() {
+                if span.walkable {
+                    spans.push(CompactSpan {
+                        y: span.max,
+                        connections: [NOT_CONNECTED; 4],
+                        region: 0,
+                        walkable: true,
+                    });
+                    tops.push(column.get(s + 1).map_or(MAX_HEIGHT, |n| n.min));
+                }
+            }
+            cells.push((first, spans.len() as u32 - first));
+        }
+
+        let mut field = Self {
+            width: heightfield.width,
+            depth: heightfield.depth,
+            cells,
+            spans,
+        };
+
+        for z in 0..field.depth {
+            for x in 0..field.width {
+                let (first, count) = field.cells[(x + z * field.width) as usize];
+                for i in first..first + count {
+                    for dir in 0..4 {
+                        let (nx, nz) = (x + DIR_OFFSET_X[dir], z + DIR_OFFSET_Z[dir]);
+                        if nx < 0 || nz < 0 || nx >= field.width || nz >= field.depth {
+                            continue;
+                        }
+                        let (neighbour_first, neighbour_count) =
+                            field.cells[(nx + nz * field.width) as usize];
+                        for k in neighbour_first..neighbour_first + neighbour_count {
+                            let (y, ny) = (field.spans[i as usize].y, field.spans[k as usize].y);
+                            let bottom = y.max(ny);
+                            let top = tops[i as usize].min(tops[k as usize]);
+                            if top - bottom >= walkable_height && (ny - y).abs() <= walkable_climb {
+                                field.spans[i as usize].connections[dir] = k;
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        field
+    }
+
+    fn neighbour(&self, span: usize, dir: usize) -> Option<usize> {
+        let connection = self.spans[span].connections[dir];
+        (connection != NOT_CONNECTED).then_some(connection as usize)
+    }
+
+    fn span_range(&self, x: i32, z: i32) -> std::ops::Range<usize> {
+        let (first, count) = self.cells[(x + z * self.width) as usize];
+        first as usize..(first + count) as usize
+    }
+
+    /// Removes all connections to non-walkable spans.
+    fn disconnect_non_walkable(&mut self) {
+        for i in 0..self.spans.len() {
+            for dir in 0..4 {
+                if let Some(neighbour) = self.neighbour(i, dir) {
+                    if !self.spans[i].walkable || !self.spans[neighbour].walkable {
+                        self.spans[i].connections[dir] = NOT_CONNECTED;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Shrinks walkable area by the given radius (in cells) using chamfer distance transform.
+    fn erode(&mut self, radius: i32) {
+        if radius <= 0 {
+            return;
+        }
+
+        let mut distance = vec![u16::MAX; self.spans.len()];
+        for (i, span) in self.spans.iter().enumerate() {
+            if span.connections.contains(&NOT_CONNECTED) {
+                distance[i] = 0;
+            }
+        }
+
+        let relax = |distance: &mut [u16], i: usize, dir: usize, diagonal_dir: usize| {
+            if let Some(a) = self.neighbour(i, dir) {
+                distance[i] = distance[i].min(distance[a].saturating_add(2));
+                if let Some(b) = self.neighbour(a, diagonal_dir) {
+                    distance[i] = distance[i].min(distance[b].saturating_add(3));
+                }
+            }
+        };
+
+        for z in 0..self.depth {
+            for x in 0..self.width {
+                for i in self.span_range(x, z) {
+                    relax(&mut distance, i, 0, 3);
+                    relax(&mut distance, i, 3, 2);
+                }
+            }
+        }
+        for z in (0..self.depth).rev() {
+            for x in (0..self.width).rev() {
+                for i in self.span_range(x, z) {
+                    relax(&mut distance, i, 2, 1);
+                    relax(&mut distance, i, 1, 0);
+                }
+            }
+        }
+
+        let threshold = (radius * 2) as u16;
+        for (span, distance) in self.spans.iter_mut().zip(distance) {
+            if distance < threshold {
+                span.walkable = false;
+            }
+        }
+        self.disconnect_non_walkable();
+    }
+
+    fn is_border_cell(&self, x: i32, z: i32, border: i32) -> bool {
+        x < border || z < border || x >= self.width - border || z >= self.depth - border
+    }
+
+    /// Removes isolated walkable islands, that are smaller than the given amount of cells.
+    /// Islands touching the border of the field are kept, because they could continue in the
+    /// neighbouring tiles.
+    fn remove_small_islands(&mut self, min_cells: usize, border: i32) {
+        let mut span_cells = vec![(0, 0); self.spans.len()];
+        for z in 0..self.depth {
+            for x in 0..self.width {
+                for i in self.span_range(x, z) {
+                    span_cells[i] = (x, z);
+                }
+            }
+        }
+
+        let mut visited = vec![false; self.spans.len()];
+        let mut stack = Vec::new();
+        let mut island = Vec::new();
+        for start in 0..self.spans.len() {
+            if visited[start] || !self.spans[start].walkable {
+                continue;
+            }
+
+            island.clear();
+            let mut touches_border = false;
+            visited[start] = true;
+            stack.push(start);
+            while let Some(i) = stack.pop() {
+                island.push(i);
+                let (x, z) = span_cells[i];
+                touches_border |= self.is_border_cell(x, z, border);
+                for dir in 0..4 {
+                    if let Some(neighbour) = self.neighbour(i, dir) {
+                        if !visited[neighbour] {
+                            visited[neighbour] = true;
+                            stack.push(neighbour);
+                        }
+                    }
+                }
+            }
+
+            if !touches_border && island.len() < min_cells {
+                for &i in island.iter() {
+                    self.spans[i].walkable = false;
+                }
+            }
+        }
+        self.disconnect_non_walkable();
+    }
+
+    /// Splits walkable area into monotone regions using sweep-line algorithm. Such regions
+    /// never have holes, which makes them easy to triangulate.
+    fn build_regions(&mut self, border: i32) {
+        // Every side of the border gets its own region, so contour vertices will be placed
+        // at the corners of a tile.
+        for z in 0..self.depth {
+            for x in 0..self.width {
+                let side = if z < border {
+                    2
+                } else if z >= self.depth - border {
+                    3
+                } else if x < border {
+                    0
+                } else if x >= self.width - border {
+                    1
+                } else {
+                    continue;
+                };
+                for i in self.span_range(x, z) {
+                    if self.spans[i].walkable {
+                        self.spans[i].region = BORDER_REGION_FLAG | side;
+                    }
+                }
+            }
+        }
+
+        #[derive(Default, Copy, Clone)]
+        struct Sweep {
+            id: u32,
+            samples: u32,
+            neighbour: u32,
+        }
+
+        let mut next_id = 1;
+        let mut sweeps: Vec<Sweep> = Vec::new();
+        let mut previous_counts: Vec<u32> = Vec::new();
+        for z in border..self.depth - border {
+            previous_counts.clear();
+            previous_counts.resize(next_id as usize, 0);
+            sweeps.clear();
+            sweeps.push(Sweep::default());
+
+            for x in border..self.width - border {
+                for i in self.span_range(x, z) {
+                    if !self.spans[i].walkable {
+                        continue;
+                    }
+
+                    // Extend the sweep from the left neighbour or start a new one.
+                    let sweep = match self.neighbour(i, 0) {
+                        Some(a) if is_interior_region(self.spans[a].region) => self.spans[a].region,
+                        _ => {
+                            sweeps.push(Sweep::default());
+                            sweeps.len() as u32 - 1
+                        }
+                    };
+
+                    if let Some(a) = self.neighbour(i, 3) {
+                        let region = self.spans[a].region;
+                        if is_interior_region(region) {
+                            let current = &mut sweeps[sweep as usize];
+                            if current.neighbour == 0 || current.neighbour == region {
+                                current.neighbour = region;
+                                current.samples += 1;
+                                previous_counts[region as usize] += 1;
+                            } else {
+                                current.neighbour = NULL_NEIGHBOUR;
+                            }
+                        }
+                    }
+
+                    self.spans[i].region = sweep;
+                }
+            }
+
+            // A sweep continues the region of the previous row only if they're connected
+            // exclusively to each other.
+            for sweep in sweeps.iter_mut().skip(1) {
+                if sweep.neighbour != NULL_NEIGHBOUR
+                    && sweep.neighbour != 0
+                    && previous_counts[sweep.neighbour as usize] == sweep.samples
+                {
+                    sweep.id = sweep.neighbour;
+                } else {
+                    sweep.id = next_id;
+                    next_id += 1;
+                }
+            }
+
+            for x in border..self.width - border {
+                for i in self.span_range(x, z) {
+                    let region = self.spans[i].region;
+                    if self.spans[i].walkable && is_interior_region(region) {
+                        self.spans[i].region = sweeps[region as usize].id;
+                    }
+                }
+            }
+        }
+    }
+
+    fn corner_height(&self, i: usize, dir: usize) -> i32 {
+        let next_dir = (dir + 1) & 3;
+        let mut height = self.spans[i].y;
+        if let Some(a) = self.neighbour(i, dir) {
+            height = height.max(self.spans[a].y);
+            if let Some(b) = self.neighbour(a, next_dir) {
+                height = height.max(self.spans[b].y);
+            }
+        }
+        if let Some(a) = self.neighbour(i, next_dir) {
+            height = height.max(self.spans[a].y);
+            if let Some(b) = self.neighbour(a, dir) {
+                height = height.max(self.spans[b].y);
+            }
+        }
+        height
+    }
+
+    fn walk_contour(
+        &self,
+        mut x: i32,
+        mut z: i32,
+        mut i: usize,
+        flags: &mut [u8],
+        points: &mut Vec<ContourPoint>,
+    ) {
+        let mut dir = 0;
+        while flags[i] & (1 << dir) == 0 {
+            dir += 1;
+        }
+        let (start_dir, start_i) = (dir, i);
+
+        for _ in 0..65536 {
+            if flags[i] & (1 << dir) != 0 {
+                let (mut px, mut pz) = (x, z);
+                match dir {
+                    0 => pz += 1,
+                    1 => {
+                        px += 1;
+                        pz += 1
+                    }
+                    2 => px += 1,
+                    _ => (),
+                }
+                points.push(ContourPoint {
+                    x: px,
+                    y: self.corner_height(i, dir),
+                    z: pz,
+                    region: self.neighbour(i, dir).map_or(0, |a| self.spans[a].region),
+                });
+                flags[i] &= !(1 << dir);
+                // Rotate clockwise.
+                dir = (dir + 1) & 3;
+            } else {
+                let Some(neighbour) = self.neighbour(i, dir) else {
+                    return;
+                };
+                x += DIR_OFFSET_X[dir];
+                z += DIR_OFFSET_Z[dir];
+                i = neighbour;
+                // Rotate counter-clockwise.
+                dir = (dir + 3) & 3;
+            }
+
+            if i == start_i && dir == start_dir {
+                break;
+            }
+        }
+    }
+
+    /// Traces and simplifies contours of every region. Each contour is a simple polygon with
+    /// vertices in cell coordinates.
+    fn build_contours(&self, max_error: f32) -> Vec<Vec<ContourPoint>> {
+        let mut flags = vec![0u8; self.spans.len()];
+        for (i, span) in self.spans.iter().enumerate() {
+            if !is_interior_region(span.region) {
+                continue;
+            }
+            let mut connected = 0;
+            for dir in 0..4 {
+                if self
+                    .neighbour(i, dir)
+                    .is_some_and(|a| self.spans[a].region == span.region)
+                {
+                    connected |= 1 << dir;
+                }
+            }
+            // Inverse the mask to mark the edges, that belong to the contour.
+            flags[i] = connected ^ 0xf;
+        }
+
+        let mut contours = Vec::new();
+        let mut raw = Vec::new();
+        for z in 0..self.depth {
+            for x in 0..self.width {
+                for i in self.span_range(x, z) {
+                    if flags[i] == 0 || flags[i] == 0xf {
+                        flags[i] = 0;
+                        continue;
+                    }
+                    raw.clear();
+                    self.walk_contour(x, z, i, &mut flags, &mut raw);
+                    let mut simplified = simplify_contour(&raw, max_error);
+                    simplified.dedup_by(|a, b| a.x == b.x && a.z == b.z);
+                    while simplified.len() > 1
+                        && simplified.first().map(|p| (p.x, p.z))
+                            == simplified.last().map(|p| (p.x, p.z))
+                    {
+                        simplified.pop();
+                    }
+                    if simplified.len() >= 3 {
+                        contours.push(simplified);
+                    }
+                }
+            }
+        }
+        contours
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct ContourPoint {
+    x: i32,
+    y: i32,
+    z: i32,
+    region: u32,
+}
+
+fn distance_to_segment_sqr(x: i32, z: i32, ax: i32, az: i32, bx: i32, bz: i32) -> f32 {
+    let (px, pz) = ((bx - ax) as f32, (bz - az) as f32);
+    let (dx, dz) = ((x - ax) as f32, (z - az) as f32);
+    let d = px * px + pz * pz;
+    let t = if d > 0.0 {
+        ((px * dx + pz * dz) / d).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (dx, dz) = (ax as f32 + t * px - x as f32, az as f32 + t * pz - z as f32);
+    dx * dx + dz * dz
+}
+
+/// Simplifies raw contour. Vertices, where the neighbouring region changes, are always kept
+/// so neighbouring contours share the same vertices. Wall edges are simplified using
+/// Douglas-Peucker algorithm.
+fn simplify_contour(points: &[ContourPoint], max_error: f32) -> Vec<ContourPoint> {
+    let count = points.len();
+    if count == 0 {
+        return Vec::new();
+    }
+
+    // Indices of the raw points in the simplified contour.
+    let mut simplified = Vec::new();
+    if points.iter().any(|p| p.region != 0) {
+        for i in 0..count {
+            if points[i].region != points[(i + 1) % count].region {
+                simplified.push(i);
+            }
+        }
+    }
+
+    if simplified.is_empty() {
+        let lower_left = (0..count)
+            .min_by_key(|&i| (points[i].x, points[i].z))
+            .unwrap();
+        let upper_right = (0..count)
+            .max_by_key(|&i| (points[i].x, points[i].z))
+            .unwrap();
+        simplified.push(lower_left);
+        if upper_right != lower_left {
+            simplified.push(upper_right);
+        }
+        simplified.sort_unstable();
+    }
+
+    let mut i = 0;
+    while i < simplified.len() {
+        let a = simplified[i];
+        let b = simplified[(i + 1) % simplified.len()];
+        let (pa, pb) = (points[a], points[b]);
+
+        // Traverse the segment in lexicographical order, so the opposite segments of the
+        // neighbouring contours are simplified in the same way.
+        let (mut ci, step, end, (ax, az), (bx, bz)) =
+            if pb.x > pa.x || (pb.x == pa.x && pb.z > pa.z) {
+                ((a + 1) % count, 1, b, (pa.x, pa.z), (pb.x, pb.z))
+            } else {
+                (
+                    (b + count - 1) % count,
+                    count - 1,
+                    a,
+                    (pb.x, pb.z),
+                    (pa.x, pa.z),
+                )
+            };
+
+        let mut max_distance = 0.0;
+        let mut max_index = None;
+        // Tessellate only wall edges.
+        if points[ci].region == 0 {
+            while ci != end {
+                let distance = distance_to_segment_sqr(points[ci].x, points[ci].z, ax, az, bx, bz);
+                if distance > max_distance {
+                    max_distance = distance;
+                    max_index = Some(ci);
+                }
+                ci = (ci + step) % count;
+            }
+        }
+
+        match max_index {
+            Some(index) if max_distance > max_error * max_error => {
+                simplified.insert(i + 1, index);
+            }
+            _ => i += 1,
+        }
+    }
+
+    simplified.into_iter().map(|i| points[i]).collect()
+}
+
+fn cross_xz(o: (i64, i64), a: (i64, i64), b: (i64, i64)) -> i64 {
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
+
+/// Triangulates a simple polygon using ear clipping. All the produced triangles have
+/// clockwise winding in XZ plane, collinear vertices do not produce degenerate triangles.
+fn triangulate(polygon: &[(i64, i64)]) -> Vec<[usize; 3]> {
+    let mut indices = (0..polygon.len()).collect::<Vec<_>>();
+    let area: i64 = (1..polygon.len().saturating_sub(1))
+        .map(|i| cross_xz(polygon[0], polygon[i], polygon[i + 1]))
+        .sum();
+    if area > 0 {
+        indices.reverse();
+    }
+
+    let mut triangles = Vec::new();
+    while indices.len() > 3 {
+        let n = indices.len();
+        let mut best = None;
+        let mut best_length = i64::MAX;
+        let mut collinear = None;
+        for i in 0..n {
+            let (prev, curr, next) = (indices[(i + n - 1) % n], indices[i], indices[(i + 1) % n]);
+            let (a, b, c) = (polygon[prev], polygon[curr], polygon[next]);
+            let cross = cross_xz(a, b, c);
+            if cross == 0 {
+                collinear.get_or_insert(i);
+                continue;
+            }
+            if cross > 0 {
+                continue;
+            }
+            let is_ear = indices.iter().all(|&k| {
+                let p = polygon[k];
+                p == a
+                    || p == b
+                    || p == c
+                    || cross_xz(a, b, p) > 0
+                    || cross_xz(b, c, p) > 0
+                    || cross_xz(c, a, p) > 0
+            });
+            if is_ear {
+                let length = (c.0 - a.0).pow(2) + (c.1 - a.1).pow(2);
+                if length < best_length {
+                    best_length = length;
+                    best = Some(i);
+                }
+            }
+        }
+
+        if let Some(i) = best {
+            triangles.push([indices[(i + n - 1) % n], indices[i], indices[(i + 1) % n]]);
+            indices.remove(i);
+        } else if let Some(i) = collinear {
+            indices.remove(i);
+        } else {
+            // Self-intersecting polygon, nothing could be done here.
+            return triangles;
+        }
+    }
+
+    if indices.len() == 3
+        && cross_xz(
+            polygon[indices[0]],
+            polygon[indices[1]],
+            polygon[indices[2]],
+        ) < 0
+    {
+        triangles.push([indices[0], indices[1], indices[2]]);
+    }
+
+    triangles
+}
+
+/// Triangles of a single tile. Vertices are stored in cell coordinates (x and z are global cell
+/// indices, y is height in cells).
+#[derive(Clone, Debug, Default)]
+struct TileMesh {
+    vertices: Vec<Vector3<i32>>,
+    triangles: Vec<[u32; 3]>,
+}
+
+fn build_tile(
+    geometry: &NavmeshGeometry,
+    tile: (i32, i32),
+    settings: &NavmeshGenerationSettings,
+) -> TileMesh {
+    let tile_size = settings.tile_size();
+    let border = settings.border_size();
+    let walkable_height = settings.walkable_height();
+    let walkable_climb = settings.walkable_climb();
+    let size = tile_size + 2 * border;
+    let origin = (tile.0 * tile_size - border, tile.1 * tile_size - border);
+
+    let mut heightfield = Heightfield::new(size, size, origin);
+    let walkable_threshold = settings.agent_max_slope.to_radians().cos();
+    for triangle in geometry.triangles.iter() {
+        let [a, b, c] = triangle.0.map(|i| geometry.vertices[i as usize]);
+        let normal = (b - a).cross(&(c - a));
+        let walkable = normal
+            .try_normalize(f32::EPSILON)
+            .is_some_and(|n| n.y >= walkable_threshold);
+        heightfield.rasterize_triangle([a, b, c], walkable, settings);
+    }
+
+    heightfield.filter_low_hanging_obstacles(walkable_climb);
+    heightfield.filter_ledge_spans(walkable_height, walkable_climb);
+    heightfield.filter_low_height_spans(walkable_height);
+
+    let mut field = CompactHeightfield::new(&heightfield, walkable_height, walkable_climb);
+    field.erode(settings.walkable_radius());
+    let cell_area = settings.cell_size() * settings.cell_size();
+    field.remove_small_islands(
+        (settings.min_region_area / cell_area).ceil() as usize,
+        border,
+    );
+    field.build_regions(border);
+
+    let mut mesh = TileMesh::default();
+    for contour in field.build_contours(settings.max_edge_error / settings.cell_size()) {
+        let polygon = contour
+            .iter()
+            .map(|p| (p.x as i64, p.z as i64))
+            .collect::<Vec<_>>();
+        let first = mesh.vertices.len() as u32;
+        mesh.vertices.extend(
+            contour
+                .iter()
+                .map(|p| Vector3::new(p.x + origin.0, p.y, p.z + origin.1)),
+        );
+        mesh.triangles.extend(
+            triangulate(&polygon)
+                .into_iter()
+                .map(|t| t.map(|i| i as u32 + first)),
+        );
+    }
+    mesh
+}
+
+fn find_vertex(
+    lookup: &FxHashMap<(i32, i32), Vec<u32>>,
+    vertices: &[Vector3<i32>],
+    v: Vector3<i32>,
+    tolerance: i32,
+) -> Option<u32> {
+    lookup.get(&(v.x, v.z)).and_then(|candidates| {
+        candidates
+            .iter()
+            .copied()
+            .find(|&i| (vertices[i as usize].y - v.y).abs() <= tolerance)
+    })
+}
+
+/// Removes connected pieces of the mesh with area (in cells) less than the given one. This is
+/// the same as removing small islands in a tile, but it works across tile boundaries as well.
+fn remove_small_pieces(
+    triangles: Vec<TriangleDefinition>,
+    vertices: &[Vector3<i32>],
+    min_area: f32,
+) -> Vec<TriangleDefinition> {
+    fn root(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+
+    let mut parents = (0..triangles.len()).collect::<Vec<_>>();
+    let mut edges: FxHashMap<(u32, u32), usize> = FxHashMap::default();
+    for (t, triangle) in triangles.iter().enumerate() {
+        for e in 0..3 {
+            let (a, b) = (triangle[e], triangle[(e + 1) % 3]);
+            if let Some(&other) = edges.get(&(a.min(b), a.max(b))) {
+                let (ra, rb) = (root(&mut parents, t), root(&mut parents, other));
+                parents[ra] = rb;
+            } else {
+                edges.insert((a.min(b), a.max(b)), t);
+            }
+        }
+    }
+
+    let mut areas = vec![0.0; triangles.len()];
+    for (t, triangle) in triangles.iter().enumerate() {
+        let [a, b, c] = triangle.0.map(|i| vertices[i as usize]);
+        let doubled_area =
+            (b.x - a.x) as i64 * (c.z - a.z) as i64 - (b.z - a.z) as i64 * (c.x - a.x) as i64;
+        areas[root(&mut parents, t)] += doubled_area.abs() as f32 * 0.5;
+    }
+
+    triangles
+        .into_iter()
+        .enumerate()
+        .filter_map(|(t, triangle)| (areas[root(&mut parents, t)] >= min_area).then_some(triangle))
+        .collect()
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Tiled navigational mesh generator. It keeps the triangles of every tile, so when some part
+/// of the level changes, only the tiles around it have to be rebuilt.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::math::aabb::AxisAlignedBoundingBox,
+/// #     scene::graph::Graph,
+/// #     utils::navmesh::{
+/// #         generation::{NavmeshGenerationSettings, NavmeshGeometry, TiledNavmeshBuilder},
+/// #         Navmesh,
+/// #     },
+/// # };
+/// #
+/// fn on_level_loaded(graph: &Graph) -> (TiledNavmeshBuilder, Navmesh) {
+///     let mut builder = TiledNavmeshBuilder::new(NavmeshGenerationSettings::default());
+///     let navmesh = builder.build(&NavmeshGeometry::from_graph(graph));
+///     (builder, navmesh)
+/// }
+///
+/// fn on_wall_destroyed(
+///     builder: &mut TiledNavmeshBuilder,
+///     graph: &Graph,
+///     wall_bounds: &AxisAlignedBoundingBox,
+/// ) -> Navmesh {
+///     // Only the tiles around the destroyed wall will be rebuilt.
+///     builder.rebuild_region(&NavmeshGeometry::from_graph(graph), wall_bounds)
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TiledNavmeshBuilder {
+    settings: NavmeshGenerationSettings,
+    tiles: FxHashMap<(i32, i32), TileMesh>,
+}
+
+impl TiledNavmeshBuilder {
+    /// Creates new navmesh builder with the given settings.
+    pub fn new(settings: NavmeshGenerationSettings) -> Self {
+        Self {
+            settings,
+            tiles: Default::default(),
+        }
+    }
+
+    /// Returns current generation settings.
+    pub fn settings(&self) -> &NavmeshGenerationSettings {
+        &self.settings
+    }
+
+    /// Sets new generation settings. Already generated tiles are discarded, because they're
+    /// no longer valid.
+    pub fn set_settings(&mut self, settings: NavmeshGenerationSettings) {
+        self.settings = settings;
+        self.tiles.clear();
+    }
+
+    /// Returns the amount of tiles, that contain at least one triangle.
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Discards all the tiles and generates a new navmesh from the given geometry.
+    pub fn build(&mut self, geometry: &NavmeshGeometry) -> Navmesh {
+        self.tiles.clear();
+        if !geometry.is_empty() {
+            let (min, max) = self.settings.tile_range(&geometry.bounds());
+            self.build_tiles(geometry, min, max);
+        }
+        self.navmesh()
+    }
+
+    /// Rebuilds every tile, that could be affected by a change of the geometry inside the given
+    /// region. The geometry must contain the whole level (or at least everything around the
+    /// region), not just the changed part.
+    pub fn rebuild_region(
+        &mut self,
+        geometry: &NavmeshGeometry,
+        region: &AxisAlignedBoundingBox,
+    ) -> Navmesh {
+        let mut region = *region;
+        // Tiles take the geometry of their border into account.
+        let border = self.settings.border_size() as f32 * self.settings.cell_size();
+        region.inflate(Vector3::repeat(2.0 * border));
+        let (min, max) = self.settings.tile_range(&region);
+        self.build_tiles(geometry, min, max);
+        self.navmesh()
+    }
+
+    fn build_tiles(&mut self, geometry: &NavmeshGeometry, min: (i32, i32), max: (i32, i32)) {
+        for z in min.1..=max.1 {
+            for x in min.0..=max.0 {
+                let tile = build_tile(geometry, (x, z), &self.settings);
+                if tile.triangles.is_empty() {
+                    self.tiles.remove(&(x, z));
+                } else {
+                    self.tiles.insert((x, z), tile);
+                }
+            }
+        }
+    }
+
+    /// Stitches all the tiles into a single navigational mesh.
+    pub fn navmesh(&self) -> Navmesh {
+        let walkable_climb = self.settings.walkable_climb().max(1);
+
+        let mut vertices: Vec<Vector3<i32>> = Vec::new();
+        let mut lookup: FxHashMap<(i32, i32), Vec<u32>> = FxHashMap::default();
+
+        // Weld vertices of all tiles.
+        let mut keys = self.tiles.keys().copied().collect::<Vec<_>>();
+        keys.sort_unstable();
+        let mut triangles = Vec::new();
+        for key in keys {
+            let tile = &self.tiles[&key];
+            let mut remap = Vec::with_capacity(tile.vertices.len());
+            for &v in tile.vertices.iter() {
+                let index = match find_vertex(&lookup, &vertices, v, walkable_climb) {
+                    Some(index) => index,
+                    None => {
+                        vertices.push(v);
+                        let index = vertices.len() as u32 - 1;
+                        lookup.entry((v.x, v.z)).or_default().push(index);
+                        index
+                    }
+                };
+                remap.push(index);
+            }
+            for triangle in tile.triangles.iter() {
+                let [a, b, c] = triangle.map(|i| remap[i as usize]);
+                if a != b && b != c && c != a {
+                    triangles.push([a, b, c]);
+                }
+            }
+        }
+
+        // Fix T-junctions, that appear on the tile seams and between regions. Every boundary
+        // edge, that passes through some other vertex, is split at this vertex.
+        let mut edge_usage: FxHashMap<(u32, u32), u32> = FxHashMap::default();
+        for triangle in triangles.iter() {
+            for e in 0..3 {
+                let (a, b) = (triangle[e], triangle[(e + 1) % 3]);
+                *edge_usage.entry((a.min(b), a.max(b))).or_default() += 1;
+            }
+        }
+
+        let mut result = Vec::with_capacity(triangles.len());
+        for triangle in triangles {
+            let mut polygon = Vec::new();
+            for e in 0..3 {
+                let (a, b) = (triangle[e], triangle[(e + 1) % 3]);
+                polygon.push(a);
+                if edge_usage[&(a.min(b), a.max(b))] != 1 {
+                    continue;
+                }
+                let (va, vb) = (vertices[a as usize], vertices[b as usize]);
+                let (dx, dz) = (vb.x - va.x, vb.z - va.z);
+                let steps = gcd(dx.abs(), dz.abs());
+                for k in 1..steps {
+                    let point = (va.x + dx / steps * k, va.z + dz / steps * k);
+                    let expected = Vector3::new(point.0, va.y + (vb.y - va.y) * k / steps, point.1);
+                    if let Some(index) = find_vertex(&lookup, &vertices, expected, walkable_climb) {
+                        polygon.push(index);
+                    }
+                }
+            }
+
+            if polygon.len() == 3 {
+                result.push(TriangleDefinition(triangle));
+            } else {
+                let points = polygon
+                    .iter()
+                    .map(|&i| (vertices[i as usize].x as i64, vertices[i as usize].z as i64))
+                    .collect::<Vec<_>>();
+                result.extend(
+                    triangulate(&points)
+                        .into_iter()
+                        .map(|t| TriangleDefinition(t.map(|i| polygon[i]))),
+                );
+            }
+        }
+
+        let cs = self.settings.cell_size();
+        let ch = self.settings.cell_height();
+        let result =
+            remove_small_pieces(result, &vertices, self.settings.min_region_area / (cs * cs));
+
+        // Remove unused vertices.
+        let mut remap = vec![u32::MAX; vertices.len()];
+        let mut used_vertices = Vec::new();
+        let triangles = result
+            .into_iter()
+            .map(|triangle| {
+                TriangleDefinition(triangle.0.map(|i| {
+                    if remap[i as usize] == u32::MAX {
+                        remap[i as usize] = used_vertices.len() as u32;
+                        let v = vertices[i as usize];
+                        used_vertices.push(Vector3::new(
+                            v.x as f32 * cs,
+                            v.y as f32 * ch,
+                            v.z as f32 * cs,
+                        ));
+                    }
+                    remap[i as usize]
+                }))
+            })
+            .collect();
+
+        Navmesh::new(triangles, used_vertices)
+    }
+}
+
+impl Navmesh {
+    /// Generates new navigational mesh from the given geometry. See [module docs](self) for
+    /// more info about the algorithm. Use [`TiledNavmeshBuilder`] if you need to rebuild parts
+    /// of the navmesh at runtime.
+    pub fn from_geometry(geometry: &NavmeshGeometry, settings: &NavmeshGenerationSettings) -> Self {
+        TiledNavmeshBuilder::new(settings.clone()).build(geometry)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::astar::PathKind;
+
+    fn plane(half_size: f32) -> NavmeshGeometry {
+        let mut geometry = NavmeshGeometry::default();
+        let p00 = Vector3::new(-half_size, 0.0, -half_size);
+        let p10 = Vector3::new(half_size, 0.0, -half_size);
+        let p01 = Vector3::new(-half_size, 0.0, half_size);
+        let p11 = Vector3::new(half_size, 0.0, half_size);
+        geometry.add_triangle(p00, p01, p10);
+        geometry.add_triangle(p10, p01, p11);
+        geometry
+    }
+
+    fn add_box(geometry: &mut NavmeshGeometry, center: Vector3<f32>, size: Vector3<f32>) {
+        geometry.add_surface_data(
+            &SurfaceData::make_cube(
+                Matrix4::new_translation(&center) * Matrix4::new_nonuniform_scaling(&size),
+            ),
+            &Matrix4::identity(),
+        );
+    }
+
+    // Checks whether the navmesh covers the given point on the ground level.
+    fn covers(navmesh: &Navmesh, x: f32, z: f32) -> bool {
+        navmesh.triangles().iter().any(|triangle| {
+            let [a, b, c] = triangle.0.map(|i| navmesh.vertices()[i as usize]);
+            if a.y.max(b.y).max(c.y) > 0.5 {
+                return false;
+            }
+            let side = |a: Vector3<f32>, b: Vector3<f32>| {
+                (b.x - a.x) * (z - a.z) - (b.z - a.z) * (x - a.x)
+            };
+            let (d0, d1, d2) = (side(a, b), side(b, c), side(c, a));
+            (d0 <= 0.0 && d1 <= 0.0 && d2 <= 0.0) || (d0 >= 0.0 && d1 >= 0.0 && d2 >= 0.0)
+        })
+    }
+
+    fn path_kind(navmesh: &Navmesh, from: Vector3<f32>, to: Vector3<f32>) -> PathKind {
+        let (_, from) = navmesh.query_closest(from).unwrap();
+        let (_, to) = navmesh.query_closest(to).unwrap();
+        let mut path = Vec::new();
+        navmesh.build_path(from, to, &mut path).unwrap()
+    }
+
+    #[test]
+    fn test_flat_plane() {
+        let settings = NavmeshGenerationSettings::default();
+        let navmesh = Navmesh::from_geometry(&plane(5.0), &settings);
+
+        assert!(!navmesh.triangles().is_empty());
+        for vertex in navmesh.vertices() {
+            assert!(vertex.y.abs() <= settings.cell_height);
+            // Walkable area is shrunk by the agent radius.
+            assert!(vertex.x.abs() <= 5.0 - settings.agent_radius + 0.01);
+            assert!(vertex.z.abs() <= 5.0 - settings.agent_radius + 0.01);
+        }
+        assert!(covers(&navmesh, 0.0, 0.0));
+        assert!(covers(&navmesh, 4.0, -4.0));
+        assert!(!covers(&navmesh, 4.9, 4.9));
+        assert_eq!(
+            path_kind(
+                &navmesh,
+                Vector3::new(-4.0, 0.0, -4.0),
+                Vector3::new(4.0, 0.0, 4.0)
+            ),
+            PathKind::Full
+        );
+    }
+
+    #[test]
+    fn test_obstacle_and_slope() {
+        let mut geometry = plane(6.0);
+        add_box(
+            &mut geometry,
+            Vector3::new(0.0, 1.5, 0.0),
+            Vector3::new(2.0, 3.0, 2.0),
+        );
+        // A steep ramp, which is not walkable.
+        geometry.add_triangle(
+            Vector3::new(-10.0, 0.0, -1.0),
+            Vector3::new(-10.0, 0.0, 1.0),
+            Vector3::new(-8.0, 5.0, -1.0),
+        );
+
+        let settings = NavmeshGenerationSettings {
+            tile_size: 16,
+            ..Default::default()
+        };
+        let navmesh = Navmesh::from_geometry(&geometry, &settings);
+
+        assert!(!covers(&navmesh, 0.0, 0.0));
+        assert!(!covers(&navmesh, 1.2, 0.0));
+        assert!(!covers(&navmesh, -9.0, 0.0));
+        assert!(covers(&navmesh, 3.0, 0.0));
+        assert!(covers(&navmesh, -3.0, 0.0));
+
+        // The path goes around the obstacle, across several tiles.
+        assert_eq!(
+            path_kind(
+                &navmesh,
+                Vector3::new(-4.0, 0.0, 0.0),
+                Vector3::new(4.0, 0.0, 0.0)
+            ),
+            PathKind::Full
+        );
+    }
+
+    #[test]
+    fn test_tiled_rebuild() {
+        let settings = NavmeshGenerationSettings {
+            tile_size: 16,
+            ..Default::default()
+        };
+
+        let mut geometry = plane(6.0);
+        let mut builder = TiledNavmeshBuilder::new(settings.clone());
+        let empty_level = builder.build(&geometry);
+        assert!(builder.tile_count() > 1);
+        assert!(covers(&empty_level, 2.0, 2.0));
+
+        // Put a wall on the level and rebuild only the tiles around it.
+        let center = Vector3::new(2.0, 1.5, 2.0);
+        let size = Vector3::new(1.0, 3.0, 1.0);
+        add_box(&mut geometry, center, size);
+        let rebuilt = builder.rebuild_region(
+            &geometry,
+            &AxisAlignedBoundingBox::from_min_max(center - size * 0.5, center + size * 0.5),
+        );
+        assert!(!covers(&rebuilt, 2.0, 2.0));
+        assert_eq!(rebuilt, Navmesh::from_geometry(&geometry, &settings));
+    }
+}
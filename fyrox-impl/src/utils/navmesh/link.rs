@@ -0,0 +1,135 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Off-mesh links allow agents to travel between points of a navmesh, that are not connected by
+//! walkable surface - jumps over gaps, ladders, teleports, etc. See [`OffMeshLink`] docs for more
+//! info.
+
+use crate::core::{algebra::Vector3, reflect::prelude::*, uuid_provider, visitor::prelude::*};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how an agent should traverse an off-mesh link. The navmesh itself does not care about
+/// the actual kind of a link (except teleports, which are traversed instantly), it is up to game
+/// code to play an appropriate animation when an agent reports that it traverses a link.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+#[repr(u32)]
+pub enum OffMeshLinkKind {
+    /// A jump from one point to another.
+    #[default]
+    Jump = 0,
+    /// A ladder, an agent moves along the link in a straight line.
+    Ladder = 1,
+    /// A teleport, an agent is moved to the end of the link instantly.
+    Teleport = 2,
+}
+
+uuid_provider!(OffMeshLinkKind = "4c1f6a2e-93b7-4d85-a0e2-5b8d7c3f1e94");
+
+/// A link between two points of a navmesh, that are not connected by walkable surface. Both points
+/// are projected on the closest triangles of the navmesh when the navigation graph is built.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::algebra::Vector3,
+/// #     utils::navmesh::{link::{OffMeshLink, OffMeshLinkKind}, Navmesh},
+/// # };
+/// fn add_ladder(navmesh: &mut Navmesh, bottom: Vector3<f32>, top: Vector3<f32>) -> usize {
+///     navmesh.add_off_mesh_link(OffMeshLink::new(bottom, top, OffMeshLinkKind::Ladder))
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct OffMeshLink {
+    /// Starting point of the link.
+    pub start: Vector3<f32>,
+    /// Ending point of the link.
+    pub end: Vector3<f32>,
+    /// Defines how an agent should traverse the link.
+    pub kind: OffMeshLinkKind,
+    /// If `true`, the link can be traversed in both directions. Otherwise only from the start to
+    /// the end.
+    pub bidirectional: bool,
+    /// Cost multiplier of the link. Larger values make pathfinder less likely to use the link.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub cost: f32,
+}
+
+uuid_provider!(OffMeshLink = "e2a7b9c4-1d3f-4e68-b5a0-7c9d2f4e6a18");
+
+impl Default for OffMeshLink {
+    fn default() -> Self {
+        Self {
+            start: Default::default(),
+            end: Default::default(),
+            kind: Default::default(),
+            bidirectional: true,
+            cost: 1.0,
+        }
+    }
+}
+
+impl OffMeshLink {
+    /// Creates new bidirectional link between the given points.
+    pub fn new(start: Vector3<f32>, end: Vector3<f32>, kind: OffMeshLinkKind) -> Self {
+        Self {
+            start,
+            end,
+            kind,
+            ..Default::default()
+        }
+    }
+
+    /// Makes the link one-directional (from the start to the end).
+    pub fn one_way(mut self) -> Self {
+        self.bidirectional = false;
+        self
+    }
+
+    /// Sets new cost multiplier of the link.
+    pub fn with_cost(mut self, cost: f32) -> Self {
+        self.cost = cost;
+        self
+    }
+}
+
+/// Describes an off-mesh link, that is currently traversed by an agent.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Visit)]
+pub struct OffMeshLinkTraversal {
+    /// Index of the link in the navmesh.
+    pub link: u32,
+    /// Kind of the link.
+    pub kind: OffMeshLinkKind,
+    /// Point, where the traversal begins. It is the end of the link, if the link is traversed
+    /// backwards.
+    pub from: Vector3<f32>,
+    /// Point, where the traversal ends.
+    pub to: Vector3<f32>,
+}
@@ -27,7 +27,7 @@
 
 use crate::{
     core::{
-        algebra::{Point3, Vector3},
+        algebra::{Point3, Vector2, Vector3},
         arrayvec::ArrayVec,
         math::{self, plane::Plane, ray::Ray, PositionProvider, TriangleDefinition, Vector3Ext},
         reflect::prelude::*,
@@ -39,6 +39,10 @@ use crate::{
     },
     utils::{
         astar::{Graph, GraphVertex, PathError, PathKind, VertexData, VertexDataProvider},
+        navmesh::{
+            filter::{NavmeshQueryFilter, DEFAULT_AREA},
+            link::{OffMeshLink, OffMeshLinkKind, OffMeshLinkTraversal},
+        },
         raw_mesh::{RawMeshBuilder, RawVertex},
     },
 };
@@ -46,6 +50,11 @@ use fxhash::{FxBuildHasher, FxHashMap};
 use fyrox_core::math::octree::{Octree, OctreeNode};
 use std::ops::{Deref, DerefMut};
 
+pub mod crowd;
+pub mod filter;
+pub mod generation;
+pub mod link;
+
 #[derive(Clone, Debug, Default, Visit)]
 struct Vertex {
     triangle_index: usize,
@@ -82,11 +91,22 @@ pub struct Navmesh {
     triangles: Vec<TriangleDefinition>,
     vertices: Vec<Vector3<f32>>,
     graph: Graph<Vertex>,
+    areas: Vec<u8>,
+    area_costs: Vec<f32>,
+    links: Vec<OffMeshLink>,
+    // Indices of the triangles, that contain start and end points of every off-mesh link.
+    link_triangles: Vec<Option<[usize; 2]>>,
+    // Indices of the off-mesh links, that could be traversed from a triangle with the given index.
+    triangle_links: FxHashMap<usize, Vec<usize>>,
 }
 
 impl PartialEq for Navmesh {
     fn eq(&self, other: &Self) -> bool {
-        self.triangles == other.triangles && self.vertices == other.vertices
+        self.triangles == other.triangles
+            && self.vertices == other.vertices
+            && self.areas == other.areas
+            && self.area_costs == other.area_costs
+            && self.links == other.links
     }
 }
 
@@ -111,6 +131,10 @@ impl Visit for Navmesh {
         }
 
         self.triangles.visit("Triangles", &mut region)?;
+        // Optional fields, older navmeshes do not have them.
+        self.areas.visit("Areas", &mut region).ok();
+        self.area_costs.visit("AreaCosts", &mut region).ok();
+        self.links.visit("OffMeshLinks", &mut region).ok();
 
         drop(region);
 
@@ -131,8 +155,7 @@ impl Visit for Navmesh {
             self.octree = Octree::new(&raw_triangles, 32);
         }
 
-        let graph = make_graph(&self.triangles, &self.vertices);
-        self.graph = graph;
+        self.rebuild_graph();
 
         Ok(())
     }
@@ -231,8 +254,7 @@ pub struct NavmeshModificationContext<'a> {
 
 impl Drop for NavmeshModificationContext<'_> {
     fn drop(&mut self) {
-        let graph = make_graph(&self.navmesh.triangles, &self.navmesh.vertices);
-        self.navmesh.graph = graph;
+        self.navmesh.rebuild_graph();
     }
 }
 
@@ -242,11 +264,15 @@ impl NavmeshModificationContext<'_> {
     pub fn add_triangle(&mut self, triangle: TriangleDefinition) -> u32 {
         let index = self.navmesh.triangles.len();
         self.navmesh.triangles.push(triangle);
+        self.navmesh.areas.resize(index + 1, DEFAULT_AREA);
         index as u32
     }
 
     /// Removes a triangle at the given index from the navigational mesh.
     pub fn remove_triangle(&mut self, index: usize) -> TriangleDefinition {
+        if index < self.navmesh.areas.len() {
+            self.navmesh.areas.remove(index);
+        }
         self.navmesh.triangles.remove(index)
     }
 
@@ -361,12 +387,172 @@ impl Navmesh {
             })
             .collect::<Vec<[Vector3<f32>; 3]>>();
 
-        Self {
-            graph: make_graph(&triangles, &vertices),
+        let mut navmesh = Self {
+            graph: Default::default(),
+            areas: vec![DEFAULT_AREA; triangles.len()],
             triangles,
             vertices,
             octree: Octree::new(&raw_triangles, 32),
+            area_costs: Default::default(),
+            links: Default::default(),
+            link_triangles: Default::default(),
+            triangle_links: Default::default(),
+        };
+        navmesh.rebuild_graph();
+        navmesh
+    }
+
+    // Rebuilds navigation graph, that includes walkable connections between adjacent triangles and
+    // off-mesh links.
+    fn rebuild_graph(&mut self) {
+        self.areas.resize(self.triangles.len(), DEFAULT_AREA);
+        self.graph = make_graph(&self.triangles, &self.vertices);
+
+        self.link_triangles = self
+            .links
+            .iter()
+            .map(|link| {
+                let (_, start) = self.query_closest(link.start)?;
+                let (_, end) = self.query_closest(link.end)?;
+                Some([start, end])
+            })
+            .collect();
+
+        self.triangle_links.clear();
+        for (index, (link, triangles)) in self
+            .links
+            .iter()
+            .zip(self.link_triangles.iter())
+            .enumerate()
+        {
+            if let Some([start, end]) = *triangles {
+                if start != end {
+                    self.triangle_links.entry(start).or_default().push(index);
+                    if link.bidirectional {
+                        self.triangle_links.entry(end).or_default().push(index);
+                        self.graph.link_bidirect(start, end);
+                    } else {
+                        self.graph.link_unidirect(start, end);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sets area type of a triangle at the given index. Area types define the cost of travelling
+    /// across triangles and could be used to exclude some parts of the navmesh from pathfinding
+    /// using [`NavmeshQueryFilter`]. See [`filter`] module docs for more info.
+    pub fn set_triangle_area(&mut self, triangle: usize, area: u8) {
+        if let Some(triangle_area) = self.areas.get_mut(triangle) {
+            *triangle_area = area;
+        }
+    }
+
+    /// Returns area type of a triangle at the given index.
+    pub fn triangle_area(&self, triangle: usize) -> u8 {
+        self.areas.get(triangle).copied().unwrap_or(DEFAULT_AREA)
+    }
+
+    /// Sets cost multiplier of the given area type. Default cost of every area type is `1.0`.
+    pub fn set_area_cost(&mut self, area: u8, cost: f32) {
+        let area = area as usize;
+        if self.area_costs.len() <= area {
+            self.area_costs.resize(area + 1, 1.0);
         }
+        self.area_costs[area] = cost.max(0.0);
+    }
+
+    /// Returns cost multiplier of the given area type.
+    pub fn area_cost(&self, area: u8) -> f32 {
+        self.area_costs.get(area as usize).copied().unwrap_or(1.0)
+    }
+
+    /// Adds a new off-mesh link and returns its index. Navigation graph is rebuilt automatically.
+    pub fn add_off_mesh_link(&mut self, link: OffMeshLink) -> usize {
+        let index = self.links.len();
+        self.links.push(link);
+        self.rebuild_graph();
+        index
+    }
+
+    /// Removes an off-mesh link at the given index. Navigation graph is rebuilt automatically.
+    pub fn remove_off_mesh_link(&mut self, index: usize) -> Option<OffMeshLink> {
+        if index < self.links.len() {
+            let link = self.links.remove(index);
+            self.rebuild_graph();
+            Some(link)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a reference to the array of off-mesh links.
+    pub fn off_mesh_links(&self) -> &[OffMeshLink] {
+        &self.links
+    }
+
+    // Calculates the cost of travelling from one triangle to another using the given filter. Walking
+    // is preferred over off-mesh links, unless a link is cheaper. Returns the cost and an index
+    // of the link (with a flag, that tells whether the link is traversed backwards).
+    fn hop(
+        &self,
+        from: usize,
+        to: usize,
+        filter: &NavmeshQueryFilter,
+    ) -> Option<(f32, Option<(usize, bool)>)> {
+        let area = self.triangle_area(to);
+        if !filter.allows_area(area) {
+            return None;
+        }
+
+        let distance = self.graph.vertices[from]
+            .position
+            .metric_distance(&self.graph.vertices[to].position);
+        let base_cost = distance * distance * self.area_cost(area) * filter.area_cost(area);
+
+        let mut best = self
+            .portal_between(from, to)
+            .map(|_| (base_cost, None::<(usize, bool)>));
+
+        for &index in self.triangle_links.get(&from).into_iter().flatten() {
+            let link = &self.links[index];
+            let Some([start, end]) = self.link_triangles[index] else {
+                continue;
+            };
+            let reversed = if start == from && end == to {
+                false
+            } else if link.bidirectional && start == to && end == from {
+                true
+            } else {
+                continue;
+            };
+            if !filter.allows_link(link.kind) {
+                continue;
+            }
+            let cost = base_cost * link.cost;
+            if best.map_or(true, |(best_cost, _)| cost < best_cost) {
+                best = Some((cost, Some((index, reversed))));
+            }
+        }
+
+        best
+    }
+
+    /// Tries to build a path of triangle indices between the given triangles, using the given
+    /// filter. The filter defines which area types and off-mesh links could be used and overrides
+    /// area costs. Just like [`Graph::build_indexed_path`], the path is stored in reversed order
+    /// (from the destination triangle to the source triangle).
+    pub fn build_indexed_path_filtered(
+        &self,
+        from: usize,
+        to: usize,
+        filter: &NavmeshQueryFilter,
+        path: &mut Vec<usize>,
+    ) -> Result<PathKind, PathError> {
+        self.graph
+            .build_indexed_path_with_cost(from, to, path, |current, neighbour| {
+                self.hop(current, neighbour, filter).map(|(cost, _)| cost)
+            })
     }
 
     /// Creates new navigation mesh (navmesh) from given mesh. It is most simple way to create complex
@@ -665,6 +851,13 @@ pub struct NavmeshAgent {
     path_dirty: bool,
     radius: f32,
     interpolator: f32,
+    priority: f32,
+    velocity: Vector3<f32>,
+    filter: NavmeshQueryFilter,
+    // Off-mesh link traversals for every segment of the path. `None` means that the segment is
+    // walkable.
+    #[visit(skip)]
+    path_links: Vec<Option<OffMeshLinkTraversal>>,
 }
 
 impl Default for NavmeshAgent {
@@ -688,6 +881,10 @@ impl NavmeshAgent {
             path_dirty: true,
             radius: 0.2,
             interpolator: 0.0,
+            priority: 1.0,
+            velocity: Default::default(),
+            filter: Default::default(),
+            path_links: vec![],
         }
     }
 
@@ -736,6 +933,45 @@ impl NavmeshAgent {
     pub fn radius(&self) -> f32 {
         self.radius
     }
+
+    /// Sets a new priority of the agent. The priority is used by [`crowd::Crowd`] to decide which
+    /// agent should give way to another: an agent with higher priority makes smaller effort to avoid
+    /// an agent with lower priority. The default value is 1.0.
+    pub fn set_priority(&mut self, priority: f32) {
+        self.priority = priority.max(0.0);
+    }
+
+    /// Returns the current priority of the navmesh agent. See [`Self::set_priority`] for more info.
+    pub fn priority(&self) -> f32 {
+        self.priority
+    }
+
+    /// Returns the current velocity of the agent. It is calculated only for the agents, that are
+    /// simulated by a [`crowd::Crowd`].
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
+    /// Sets a new query filter of the agent. The filter defines which area types and off-mesh
+    /// links the agent is allowed to use. The path will be recalculated on next update.
+    pub fn set_filter(&mut self, filter: NavmeshQueryFilter) {
+        self.filter = filter;
+        self.path_dirty = true;
+    }
+
+    /// Returns the current query filter of the agent.
+    pub fn filter(&self) -> &NavmeshQueryFilter {
+        &self.filter
+    }
+
+    /// Returns an off-mesh link, that is currently traversed by the agent (if any). Game code
+    /// could use it to play an appropriate animation (jump, climb, etc.).
+    pub fn current_link(&self) -> Option<OffMeshLinkTraversal> {
+        self.path_links
+            .get(self.current as usize)
+            .copied()
+            .flatten()
+    }
 }
 
 impl NavmeshAgent {
@@ -748,6 +984,7 @@ impl NavmeshAgent {
         dest_point: Vector3<f32>,
     ) -> Result<PathKind, PathError> {
         self.path.clear();
+        self.path_links.clear();
 
         self.current = 0;
         self.interpolator = 0.0;
@@ -758,25 +995,64 @@ impl NavmeshAgent {
                 if src_triangle == dest_triangle {
                     self.path.push(src_point_on_navmesh);
                     self.path.push(dest_point_on_navmesh);
+                    self.path_links.push(None);
 
                     return Ok(PathKind::Full);
                 }
 
                 let mut path_triangle_indices = Vec::new();
-                let path_kind = navmesh.graph.build_indexed_path(
+                let path_kind = navmesh.build_indexed_path_filtered(
                     src_triangle,
                     dest_triangle,
+                    &self.filter,
                     &mut path_triangle_indices,
                 )?;
 
                 path_triangle_indices.reverse();
 
+                // Split the corridor at off-mesh links, every walkable part is straightened
+                // separately and the links are inserted between them as is.
+                let mut segment_begin = src_point_on_navmesh;
+                let mut corridor_begin = 0;
+                for (i, pair) in path_triangle_indices.windows(2).enumerate() {
+                    let Some((_, Some((link_index, reversed)))) =
+                        navmesh.hop(pair[0], pair[1], &self.filter)
+                    else {
+                        continue;
+                    };
+
+                    let link = &navmesh.links[link_index];
+                    let (from, to) = if reversed {
+                        (link.end, link.start)
+                    } else {
+                        (link.start, link.end)
+                    };
+
+                    self.straighten_path(
+                        navmesh,
+                        segment_begin,
+                        from,
+                        &path_triangle_indices[corridor_begin..=i],
+                    );
+                    self.path_links.resize(self.path.len() - 1, None);
+                    self.path_links.push(Some(OffMeshLinkTraversal {
+                        link: link_index as u32,
+                        kind: link.kind,
+                        from,
+                        to,
+                    }));
+
+                    segment_begin = to;
+                    corridor_begin = i + 1;
+                }
+
                 self.straighten_path(
                     navmesh,
-                    src_point_on_navmesh,
+                    segment_begin,
                     dest_point_on_navmesh,
-                    &path_triangle_indices,
+                    &path_triangle_indices[corridor_begin..],
                 );
+                self.path_links.resize(self.path.len() - 1, None);
 
                 return Ok(path_kind);
             }
@@ -877,6 +1153,16 @@ impl NavmeshAgent {
 
         if let Some(source) = self.path.get(self.current as usize) {
             if let Some(destination) = self.path.get((self.current + 1) as usize) {
+                if self
+                    .current_link()
+                    .is_some_and(|link| link.kind == OffMeshLinkKind::Teleport)
+                {
+                    self.position = *destination;
+                    self.current += 1;
+                    self.interpolator = 0.0;
+                    return Ok(PathKind::Full);
+                }
+
                 let len = destination.metric_distance(source);
                 self.position = source.lerp(destination, self.interpolator.clamp(0.0, 1.0));
                 self.interpolator += (self.speed * dt) / len.max(f32::EPSILON);
@@ -893,6 +1179,93 @@ impl NavmeshAgent {
         Ok(PathKind::Full)
     }
 
+    // Recalculates the path if the target or the agent have moved significantly. Path errors are
+    // not fatal for the crowd simulation, the agent just stops.
+    fn refresh_path(&mut self, navmesh: &Navmesh) {
+        if self.path_dirty {
+            if self
+                .calculate_path(navmesh, self.position, self.target)
+                .is_err()
+            {
+                self.path.clear();
+                self.path_links.clear();
+            }
+            self.path_dirty = false;
+        }
+    }
+
+    // Calculates a velocity, that moves the agent to the next corner of the path at its maximum
+    // speed, the agent slows down when it approaches the last point of the path.
+    fn preferred_velocity(&mut self, dt: f32) -> Vector3<f32> {
+        let corner_threshold = self.radius.max(0.05);
+        while self.current as usize + 2 < self.path.len()
+            && self.current_link().is_none()
+            && distance_xz(self.position, self.path[self.current as usize + 1]) <= corner_threshold
+        {
+            self.current += 1;
+        }
+
+        // Off-mesh links are traversed separately.
+        if self.current_link().is_some() {
+            return Vector3::default();
+        }
+
+        let Some(corner) = self.path.get(self.current as usize + 1) else {
+            return Vector3::default();
+        };
+        let mut delta = corner - self.position;
+        delta.y = 0.0;
+        let distance = delta.norm();
+        if distance <= f32::EPSILON {
+            return Vector3::default();
+        }
+        let speed = if self.current as usize + 2 >= self.path.len() {
+            self.speed.min(distance / dt.max(f32::EPSILON))
+        } else {
+            self.speed
+        };
+        delta.scale(speed / distance)
+    }
+
+    // Moves the agent along an off-mesh link, that is currently traversed by the agent. Returns
+    // `false` if the agent is not on a link.
+    fn traverse_link(&mut self, dt: f32) -> bool {
+        let Some(link) = self.current_link() else {
+            return false;
+        };
+
+        let old_position = self.position;
+        let delta = link.to - self.position;
+        let distance = delta.norm();
+        let step = self.speed * dt;
+        if link.kind == OffMeshLinkKind::Teleport || distance <= step {
+            self.position = link.to;
+            self.current += 1;
+        } else {
+            self.position += delta.scale(step / distance);
+        }
+        self.velocity = (self.position - old_position).scale(1.0 / dt.max(f32::EPSILON));
+
+        true
+    }
+
+    // Moves the agent to a new position, chosen by the crowd simulation. The path is marked dirty if
+    // the agent was pushed too far away from it.
+    fn move_to(&mut self, new_position: Vector3<f32>, dt: f32) {
+        self.velocity = (new_position - self.position).scale(1.0 / dt.max(f32::EPSILON));
+        self.position = new_position;
+        if let (Some(begin), Some(end)) = (
+            self.path.get(self.current as usize),
+            self.path.get(self.current as usize + 1),
+        ) {
+            let deviation = distance_to_segment_xz(self.position, *begin, *end);
+            if deviation > self.recalculation_threshold + 2.0 * self.radius {
+                self.path_dirty = true;
+                self.last_warp_position = self.position;
+            }
+        }
+    }
+
     /// Returns current steering target which in most cases next path point from which
     /// agent is close to.
     pub fn steering_target(&self) -> Option<Vector3<f32>> {
@@ -928,12 +1301,33 @@ impl NavmeshAgent {
     }
 }
 
+fn distance_xz(a: Vector3<f32>, b: Vector3<f32>) -> f32 {
+    (a.x - b.x).hypot(a.z - b.z)
+}
+
+fn distance_to_segment_xz(point: Vector3<f32>, begin: Vector3<f32>, end: Vector3<f32>) -> f32 {
+    let point = Vector2::new(point.x, point.z);
+    let begin = Vector2::new(begin.x, begin.z);
+    let end = Vector2::new(end.x, end.z);
+    let segment = end - begin;
+    let length_sqr = segment.norm_squared();
+    let t = if length_sqr > f32::EPSILON {
+        ((point - begin).dot(&segment) / length_sqr).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (begin + segment.scale(t)).metric_distance(&point)
+}
+
 /// Allows you to build agent in declarative manner.
 pub struct NavmeshAgentBuilder {
     position: Vector3<f32>,
     target: Vector3<f32>,
     recalculation_threshold: f32,
     speed: f32,
+    radius: f32,
+    priority: f32,
+    filter: NavmeshQueryFilter,
 }
 
 impl Default for NavmeshAgentBuilder {
@@ -950,6 +1344,9 @@ impl NavmeshAgentBuilder {
             target: Default::default(),
             recalculation_threshold: 0.25,
             speed: 1.5,
+            radius: 0.2,
+            priority: 1.0,
+            filter: Default::default(),
         }
     }
 
@@ -977,6 +1374,26 @@ impl NavmeshAgentBuilder {
         self
     }
 
+    /// Sets new desired radius of the agent being built.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets new desired priority of the agent being built. See [`NavmeshAgent::set_priority`] for
+    /// more info.
+    pub fn with_priority(mut self, priority: f32) -> Self {
+        self.priority = priority.max(0.0);
+        self
+    }
+
+    /// Sets new desired query filter of the agent being built. See [`NavmeshAgent::set_filter`]
+    /// for more info.
+    pub fn with_filter(mut self, filter: NavmeshQueryFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
     /// Build the agent.
     pub fn build(self) -> NavmeshAgent {
         NavmeshAgent {
@@ -986,6 +1403,9 @@ impl NavmeshAgentBuilder {
             last_target_position: self.target,
             recalculation_threshold: self.recalculation_threshold,
             speed: self.speed,
+            radius: self.radius,
+            priority: self.priority,
+            filter: self.filter,
             ..Default::default()
         }
     }
@@ -995,9 +1415,42 @@ impl NavmeshAgentBuilder {
 mod test {
     use crate::{
         core::{algebra::Vector3, math::TriangleDefinition},
-        utils::navmesh::{Navmesh, NavmeshAgent},
+        utils::{
+            astar::PathKind,
+            navmesh::{
+                filter::NavmeshQueryFilter,
+                link::{OffMeshLink, OffMeshLinkKind},
+                Navmesh, NavmeshAgent, NavmeshAgentBuilder,
+            },
+        },
     };
 
+    // Makes a grid of quads, every quad consists of two triangles. Triangles of a quad at (x, z)
+    // have indices `2 * (z * width + x)` and `2 * (z * width + x) + 1`.
+    fn make_grid(width: u32, height: u32, offset: Vector3<f32>) -> Navmesh {
+        let mut vertices = Vec::new();
+        for z in 0..=height {
+            for x in 0..=width {
+                vertices.push(offset + Vector3::new(x as f32, 0.0, z as f32));
+            }
+        }
+        let index = |x: u32, z: u32| z * (width + 1) + x;
+        let mut triangles = Vec::new();
+        for z in 0..height {
+            for x in 0..width {
+                let (a, b, c, d) = (
+                    index(x, z),
+                    index(x, z + 1),
+                    index(x + 1, z + 1),
+                    index(x + 1, z),
+                );
+                triangles.push(TriangleDefinition([a, b, d]));
+                triangles.push(TriangleDefinition([b, c, d]));
+            }
+        }
+        Navmesh::new(triangles, vertices)
+    }
+
     #[test]
     fn test_navmesh() {
         let navmesh = Navmesh::new(
@@ -1054,4 +1507,102 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn test_area_filter() {
+        const WATER: u8 = 1;
+
+        let mut navmesh = make_grid(3, 2, Vector3::default());
+        // The quad in the middle of the bottom row is water.
+        navmesh.set_triangle_area(2, WATER);
+        navmesh.set_triangle_area(3, WATER);
+
+        let mut path = Vec::new();
+        let filter = NavmeshQueryFilter::default();
+        assert_eq!(
+            navmesh
+                .build_indexed_path_filtered(0, 5, &filter, &mut path)
+                .unwrap(),
+            PathKind::Full
+        );
+        assert!(path.contains(&2) || path.contains(&3));
+
+        let filter = NavmeshQueryFilter::default().with_excluded_area(WATER);
+        assert_eq!(
+            navmesh
+                .build_indexed_path_filtered(0, 5, &filter, &mut path)
+                .unwrap(),
+            PathKind::Full
+        );
+        assert!(!path.contains(&2) && !path.contains(&3));
+
+        navmesh.set_area_cost(WATER, 100.0);
+        let filter = NavmeshQueryFilter::default();
+        assert_eq!(
+            navmesh
+                .build_indexed_path_filtered(0, 5, &filter, &mut path)
+                .unwrap(),
+            PathKind::Full
+        );
+        assert!(!path.contains(&2) && !path.contains(&3));
+    }
+
+    #[test]
+    fn test_off_mesh_link() {
+        // Two disjoint islands, the second one is higher than the first one.
+        let first = make_grid(1, 1, Vector3::default());
+        let second = make_grid(1, 1, Vector3::new(3.0, 1.0, 0.0));
+        let mut vertices = first.vertices().to_vec();
+        let mut triangles = first.triangles().to_vec();
+        let base = vertices.len() as u32;
+        vertices.extend_from_slice(second.vertices());
+        triangles.extend(
+            second
+                .triangles()
+                .iter()
+                .map(|t| TriangleDefinition([t[0] + base, t[1] + base, t[2] + base])),
+        );
+        let mut navmesh = Navmesh::new(triangles, vertices);
+
+        let begin = Vector3::new(0.1, 0.0, 0.5);
+        let end = Vector3::new(3.9, 1.0, 0.5);
+
+        let mut agent = NavmeshAgentBuilder::new()
+            .with_position(begin)
+            .with_target(end)
+            .build();
+        assert_eq!(
+            agent.calculate_path(&navmesh, begin, end).unwrap(),
+            PathKind::Partial
+        );
+
+        navmesh.add_off_mesh_link(OffMeshLink::new(
+            Vector3::new(0.9, 0.0, 0.5),
+            Vector3::new(3.1, 1.0, 0.5),
+            OffMeshLinkKind::Ladder,
+        ));
+        assert_eq!(
+            agent.calculate_path(&navmesh, begin, end).unwrap(),
+            PathKind::Full
+        );
+
+        // Walk until the end, the agent must report the ladder on its way.
+        let mut climbed = false;
+        for _ in 0..1000 {
+            agent.update(1.0 / 30.0, &navmesh).unwrap();
+            if let Some(link) = agent.current_link() {
+                assert_eq!(link.kind, OffMeshLinkKind::Ladder);
+                climbed = true;
+            }
+        }
+        assert!(climbed);
+        assert!(agent.position().metric_distance(&end) < 0.01);
+
+        // Agents that cannot use ladders cannot reach the target.
+        agent.set_filter(NavmeshQueryFilter::default().with_excluded_link(OffMeshLinkKind::Ladder));
+        assert_eq!(
+            agent.calculate_path(&navmesh, begin, end).unwrap(),
+            PathKind::Partial
+        );
+    }
 }
@@ -0,0 +1,775 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Dynamic bounding volume hierarchy (an AABB tree) that supports fast insertion, removal and
+//! update of its entries. Unlike [`crate::octree::Octree`], the tree does not need to be rebuilt
+//! when its entries are moving, it is incrementally re-balanced using tree rotations instead.
+//!
+//! Every entry (proxy) is stored with a "fat" bounding box, which is slightly larger than the actual
+//! bounding box of the entry. Small movements of the entry that keep its actual bounding box inside
+//! the fat one do not modify the tree at all.
+
+use crate::{aabb::AxisAlignedBoundingBox, frustum::Frustum, ray::Ray};
+use nalgebra::Vector3;
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+};
+
+const NULL: usize = usize::MAX;
+
+/// Default margin of the fat bounding boxes. See [`DynamicBvh::with_margin`].
+pub const DEFAULT_MARGIN: f32 = 0.1;
+
+/// An identifier of an entry of [`DynamicBvh`]. Identifiers of removed entries could be reused.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ProxyId(usize);
+
+impl ProxyId {
+    /// Returns index of the proxy in the internal storage of the tree.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Clone, Debug)]
+struct BvhNode<T> {
+    aabb: AxisAlignedBoundingBox,
+    // Index of the next free node, when the node is in the free list.
+    parent: usize,
+    left: usize,
+    right: usize,
+    // Leaves have zero height, free nodes - negative.
+    height: i32,
+    data: Option<T>,
+}
+
+impl<T> BvhNode<T> {
+    fn is_leaf(&self) -> bool {
+        self.left == NULL
+    }
+}
+
+fn merge(a: &AxisAlignedBoundingBox, b: &AxisAlignedBoundingBox) -> AxisAlignedBoundingBox {
+    let mut result = *a;
+    result.add_box(*b);
+    result
+}
+
+fn surface_area(aabb: &AxisAlignedBoundingBox) -> f32 {
+    let size = aabb.max - aabb.min;
+    2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+}
+
+fn contains(outer: &AxisAlignedBoundingBox, inner: &AxisAlignedBoundingBox) -> bool {
+    outer.min.x <= inner.min.x
+        && outer.min.y <= inner.min.y
+        && outer.min.z <= inner.min.z
+        && outer.max.x >= inner.max.x
+        && outer.max.y >= inner.max.y
+        && outer.max.z >= inner.max.z
+}
+
+/// Returns the distance from the point to the closest point of the bounding box. The distance is
+/// zero if the point is inside of the box.
+pub fn distance_to_aabb(aabb: &AxisAlignedBoundingBox, point: Vector3<f32>) -> f32 {
+    (aabb.min - point)
+        .sup(&(point - aabb.max))
+        .sup(&Vector3::zeros())
+        .norm()
+}
+
+/// Returns the range of ray parameters, where the ray is inside of the bounding box. Unlike
+/// [`Ray::aabb_intersection`], it correctly handles rays parallel to the sides of the box and
+/// degenerate (flat) boxes.
+pub fn ray_aabb_intersection(ray: &Ray, aabb: &AxisAlignedBoundingBox) -> Option<(f32, f32)> {
+    let mut t_min = f32::NEG_INFINITY;
+    let mut t_max = f32::INFINITY;
+
+    for i in 0..3 {
+        if ray.dir[i] == 0.0 {
+            if ray.origin[i] < aabb.min[i] || ray.origin[i] > aabb.max[i] {
+                return None;
+            }
+        } else {
+            let t1 = (aabb.min[i] - ray.origin[i]) / ray.dir[i];
+            let t2 = (aabb.max[i] - ray.origin[i]) / ray.dir[i];
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+            if t_min > t_max {
+                return None;
+            }
+        }
+    }
+
+    Some((t_min, t_max))
+}
+
+#[derive(PartialEq)]
+struct Candidate {
+    distance: f32,
+    index: usize,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+/// See module docs.
+#[derive(Clone, Debug)]
+pub struct DynamicBvh<T> {
+    nodes: Vec<BvhNode<T>>,
+    root: usize,
+    free_list: usize,
+    margin: f32,
+    count: usize,
+}
+
+impl<T> Default for DynamicBvh<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DynamicBvh<T> {
+    /// Creates a new empty tree with default margin of fat bounding boxes.
+    pub fn new() -> Self {
+        Self::with_margin(DEFAULT_MARGIN)
+    }
+
+    /// Creates a new empty tree with the given margin of fat bounding boxes. Larger margin means less
+    /// tree updates for moving entries, but less precise queries.
+    pub fn with_margin(margin: f32) -> Self {
+        Self {
+            nodes: Default::default(),
+            root: NULL,
+            free_list: NULL,
+            margin,
+            count: 0,
+        }
+    }
+
+    /// Returns margin of fat bounding boxes.
+    pub fn margin(&self) -> f32 {
+        self.margin
+    }
+
+    /// Returns the amount of entries in the tree.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns `true` if the tree has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the height of the tree, it is zero for an empty tree or a tree with a single entry.
+    pub fn height(&self) -> usize {
+        if self.root == NULL {
+            0
+        } else {
+            self.nodes[self.root].height as usize
+        }
+    }
+
+    /// Removes all entries from the tree.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.root = NULL;
+        self.free_list = NULL;
+        self.count = 0;
+    }
+
+    fn leaf(&self, id: ProxyId) -> Option<&BvhNode<T>> {
+        self.nodes
+            .get(id.0)
+            .filter(|node| node.height == 0 && node.data.is_some())
+    }
+
+    /// Returns a reference to the data of the entry.
+    pub fn data(&self, id: ProxyId) -> Option<&T> {
+        self.leaf(id).and_then(|node| node.data.as_ref())
+    }
+
+    /// Returns a reference to the data of the entry.
+    pub fn data_mut(&mut self, id: ProxyId) -> Option<&mut T> {
+        self.nodes
+            .get_mut(id.0)
+            .filter(|node| node.height == 0)
+            .and_then(|node| node.data.as_mut())
+    }
+
+    /// Returns the fat bounding box of the entry.
+    pub fn fat_aabb(&self, id: ProxyId) -> Option<&AxisAlignedBoundingBox> {
+        self.leaf(id).map(|node| &node.aabb)
+    }
+
+    /// Returns an iterator over all entries of the tree.
+    pub fn iter(&self) -> impl Iterator<Item = (ProxyId, &T)> {
+        self.nodes.iter().enumerate().filter_map(|(index, node)| {
+            if node.height == 0 {
+                node.data.as_ref().map(|data| (ProxyId(index), data))
+            } else {
+                None
+            }
+        })
+    }
+
+    fn fatten(&self, aabb: &AxisAlignedBoundingBox) -> AxisAlignedBoundingBox {
+        let mut fat = *aabb;
+        fat.inflate(Vector3::repeat(2.0 * self.margin));
+        fat
+    }
+
+    fn allocate_node(&mut self) -> usize {
+        let node = BvhNode {
+            aabb: Default::default(),
+            parent: NULL,
+            left: NULL,
+            right: NULL,
+            height: 0,
+            data: None,
+        };
+
+        if self.free_list == NULL {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        } else {
+            let index = self.free_list;
+            self.free_list = self.nodes[index].parent;
+            self.nodes[index] = node;
+            index
+        }
+    }
+
+    fn free_node(&mut self, index: usize) -> Option<T> {
+        let node = &mut self.nodes[index];
+        node.parent = self.free_list;
+        node.left = NULL;
+        node.right = NULL;
+        node.height = -1;
+        self.free_list = index;
+        node.data.take()
+    }
+
+    /// Adds a new entry with the given bounding box to the tree.
+    pub fn insert(&mut self, aabb: AxisAlignedBoundingBox, data: T) -> ProxyId {
+        let leaf = self.allocate_node();
+        self.nodes[leaf].aabb = self.fatten(&aabb);
+        self.nodes[leaf].data = Some(data);
+        self.insert_leaf(leaf);
+        self.count += 1;
+        ProxyId(leaf)
+    }
+
+    /// Removes the entry from the tree and returns its data.
+    pub fn remove(&mut self, id: ProxyId) -> Option<T> {
+        self.leaf(id)?;
+        self.remove_leaf(id.0);
+        self.count -= 1;
+        self.free_node(id.0)
+    }
+
+    /// Updates the bounding box of the entry. The tree is modified only if the new bounding box is
+    /// not inside of the fat bounding box of the entry, in this case the method returns `true`.
+    pub fn update(&mut self, id: ProxyId, aabb: AxisAlignedBoundingBox) -> bool {
+        let Some(leaf) = self.leaf(id) else {
+            return false;
+        };
+
+        if contains(&leaf.aabb, &aabb) {
+            return false;
+        }
+
+        self.remove_leaf(id.0);
+        self.nodes[id.0].aabb = self.fatten(&aabb);
+        self.insert_leaf(id.0);
+        true
+    }
+
+    fn insert_leaf(&mut self, leaf: usize) {
+        if self.root == NULL {
+            self.root = leaf;
+            self.nodes[leaf].parent = NULL;
+            return;
+        }
+
+        // Find the best sibling using surface area heuristic.
+        let leaf_aabb = self.nodes[leaf].aabb;
+        let mut index = self.root;
+        while !self.nodes[index].is_leaf() {
+            let node = &self.nodes[index];
+            let area = surface_area(&node.aabb);
+            let combined_area = surface_area(&merge(&node.aabb, &leaf_aabb));
+
+            // Cost of creating a new parent for this node and the new leaf.
+            let cost = 2.0 * combined_area;
+            // Minimum cost of pushing the leaf further down the tree.
+            let inheritance_cost = 2.0 * (combined_area - area);
+
+            let child_cost = |child: &BvhNode<T>| {
+                let merged_area = surface_area(&merge(&leaf_aabb, &child.aabb));
+                if child.is_leaf() {
+                    merged_area + inheritance_cost
+                } else {
+                    merged_area - surface_area(&child.aabb) + inheritance_cost
+                }
+            };
+
+            let left_cost = child_cost(&self.nodes[node.left]);
+            let right_cost = child_cost(&self.nodes[node.right]);
+
+            if cost < left_cost && cost < right_cost {
+                break;
+            }
+
+            index = if left_cost < right_cost {
+                node.left
+            } else {
+                node.right
+            };
+        }
+
+        let sibling = index;
+        let old_parent = self.nodes[sibling].parent;
+        let new_parent = self.allocate_node();
+        self.nodes[new_parent].parent = old_parent;
+        self.nodes[new_parent].aabb = merge(&leaf_aabb, &self.nodes[sibling].aabb);
+        self.nodes[new_parent].height = self.nodes[sibling].height + 1;
+        self.nodes[new_parent].left = sibling;
+        self.nodes[new_parent].right = leaf;
+        self.nodes[sibling].parent = new_parent;
+        self.nodes[leaf].parent = new_parent;
+
+        if old_parent == NULL {
+            self.root = new_parent;
+        } else if self.nodes[old_parent].left == sibling {
+            self.nodes[old_parent].left = new_parent;
+        } else {
+            self.nodes[old_parent].right = new_parent;
+        }
+
+        self.refit_ancestors(self.nodes[leaf].parent);
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) {
+        if leaf == self.root {
+            self.root = NULL;
+            return;
+        }
+
+        let parent = self.nodes[leaf].parent;
+        let grand_parent = self.nodes[parent].parent;
+        let sibling = if self.nodes[parent].left == leaf {
+            self.nodes[parent].right
+        } else {
+            self.nodes[parent].left
+        };
+
+        self.free_node(parent);
+        self.nodes[sibling].parent = grand_parent;
+
+        if grand_parent == NULL {
+            self.root = sibling;
+        } else {
+            if self.nodes[grand_parent].left == parent {
+                self.nodes[grand_parent].left = sibling;
+            } else {
+                self.nodes[grand_parent].right = sibling;
+            }
+            self.refit_ancestors(grand_parent);
+        }
+    }
+
+    fn refit_ancestors(&mut self, mut index: usize) {
+        while index != NULL {
+            index = self.balance(index);
+
+            let left = self.nodes[index].left;
+            let right = self.nodes[index].right;
+            self.nodes[index].height = 1 + self.nodes[left].height.max(self.nodes[right].height);
+            self.nodes[index].aabb = merge(&self.nodes[left].aabb, &self.nodes[right].aabb);
+
+            index = self.nodes[index].parent;
+        }
+    }
+
+    fn replace_child(&mut self, parent: usize, old_child: usize, new_child: usize) {
+        if parent == NULL {
+            self.root = new_child;
+        } else if self.nodes[parent].left == old_child {
+            self.nodes[parent].left = new_child;
+        } else {
+            self.nodes[parent].right = new_child;
+        }
+    }
+
+    // Performs a rotation if the subtree at the given index is imbalanced, returns the index of the
+    // new root of the subtree.
+    fn balance(&mut self, a: usize) -> usize {
+        if self.nodes[a].is_leaf() || self.nodes[a].height < 2 {
+            return a;
+        }
+
+        let b = self.nodes[a].left;
+        let c = self.nodes[a].right;
+        let balance = self.nodes[c].height - self.nodes[b].height;
+
+        if balance > 1 {
+            self.rotate_up(a, c, b, false)
+        } else if balance < -1 {
+            self.rotate_up(a, b, c, true)
+        } else {
+            a
+        }
+    }
+
+    // Moves the `child` node of `a` one level up, `a` becomes a child of `child`. `other` is the
+    // other child of `a`, `left` tells whether `child` is the left child of `a`.
+    fn rotate_up(&mut self, a: usize, child: usize, other: usize, left: bool) -> usize {
+        let f = self.nodes[child].left;
+        let g = self.nodes[child].right;
+
+        let a_parent = self.nodes[a].parent;
+        self.nodes[child].parent = a_parent;
+        self.nodes[a].parent = child;
+        self.replace_child(a_parent, a, child);
+
+        // The higher grandchild stays with the `child`, the lower one is moved to `a`.
+        let (high, low) = if self.nodes[f].height > self.nodes[g].height {
+            (f, g)
+        } else {
+            (g, f)
+        };
+
+        self.nodes[child].left = a;
+        self.nodes[child].right = high;
+        if left {
+            self.nodes[a].left = low;
+        } else {
+            self.nodes[a].right = low;
+        }
+        self.nodes[low].parent = a;
+
+        self.nodes[a].aabb = merge(&self.nodes[other].aabb, &self.nodes[low].aabb);
+        self.nodes[a].height = 1 + self.nodes[other].height.max(self.nodes[low].height);
+        self.nodes[child].aabb = merge(&self.nodes[a].aabb, &self.nodes[high].aabb);
+        self.nodes[child].height = 1 + self.nodes[a].height.max(self.nodes[high].height);
+
+        child
+    }
+
+    /// Visits every entry whose fat bounding box satisfies the given predicate. The predicate must be
+    /// conservative: if it returns `false` for a box, it must return `false` for every box inside it.
+    pub fn query<P, F>(&self, mut predicate: P, mut func: F)
+    where
+        P: FnMut(&AxisAlignedBoundingBox) -> bool,
+        F: FnMut(ProxyId, &T),
+    {
+        if self.root == NULL {
+            return;
+        }
+
+        let mut stack = vec![self.root];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !predicate(&node.aabb) {
+                continue;
+            }
+
+            if node.is_leaf() {
+                if let Some(data) = node.data.as_ref() {
+                    func(ProxyId(index), data);
+                }
+            } else {
+                stack.push(node.left);
+                stack.push(node.right);
+            }
+        }
+    }
+
+    /// Visits every entry whose fat bounding box intersects the given bounding box.
+    pub fn query_aabb<F>(&self, aabb: &AxisAlignedBoundingBox, func: F)
+    where
+        F: FnMut(ProxyId, &T),
+    {
+        self.query(|node_aabb| node_aabb.is_intersects_aabb(aabb), func)
+    }
+
+    /// Visits every entry whose fat bounding box intersects the given sphere.
+    pub fn query_sphere<F>(&self, center: Vector3<f32>, radius: f32, func: F)
+    where
+        F: FnMut(ProxyId, &T),
+    {
+        self.query(
+            |node_aabb| node_aabb.is_intersects_sphere(center, radius),
+            func,
+        )
+    }
+
+    /// Visits every entry whose fat bounding box intersects the given frustum.
+    pub fn query_frustum<F>(&self, frustum: &Frustum, func: F)
+    where
+        F: FnMut(ProxyId, &T),
+    {
+        self.query(|node_aabb| frustum.is_intersects_aabb(node_aabb), func)
+    }
+
+    /// Visits every entry whose fat bounding box is intersected by the given ray. The ray is treated
+    /// as a segment from its origin to `origin + dir`.
+    pub fn query_ray<F>(&self, ray: &Ray, func: F)
+    where
+        F: FnMut(ProxyId, &T),
+    {
+        self.query(
+            |node_aabb| {
+                ray_aabb_intersection(ray, node_aabb)
+                    .is_some_and(|(t_min, t_max)| t_max >= 0.0 && t_min <= 1.0)
+            },
+            func,
+        )
+    }
+
+    /// Searches for at most `k` entries closest to the given point. The distance to an entry is
+    /// calculated by `distance` function, which could also filter out the entry by returning `None`.
+    /// The distance must not be less than the distance from the point to the fat bounding box of the
+    /// entry (see [`distance_to_aabb`]), otherwise the result will be incorrect. Returns pairs of
+    /// entries and their distances sorted by the distance.
+    pub fn k_nearest<D>(
+        &self,
+        point: Vector3<f32>,
+        k: usize,
+        mut distance: D,
+    ) -> Vec<(ProxyId, f32)>
+    where
+        D: FnMut(ProxyId, &T) -> Option<f32>,
+    {
+        let mut result = BinaryHeap::<Candidate>::new();
+
+        if self.root == NULL || k == 0 {
+            return Vec::new();
+        }
+
+        // Best-first traversal, nodes are visited in order of distance to their bounding boxes.
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse(Candidate {
+            distance: distance_to_aabb(&self.nodes[self.root].aabb, point),
+            index: self.root,
+        }));
+
+        while let Some(Reverse(candidate)) = queue.pop() {
+            if result.len() == k
+                && result
+                    .peek()
+                    .is_some_and(|farthest| candidate.distance > farthest.distance)
+            {
+                break;
+            }
+
+            let node = &self.nodes[candidate.index];
+            if node.is_leaf() {
+                let Some(data) = node.data.as_ref() else {
+                    continue;
+                };
+                if let Some(distance) = distance(ProxyId(candidate.index), data) {
+                    result.push(Candidate {
+                        distance,
+                        index: candidate.index,
+                    });
+                    if result.len() > k {
+                        result.pop();
+                    }
+                }
+            } else {
+                for child in [node.left, node.right] {
+                    queue.push(Reverse(Candidate {
+                        distance: distance_to_aabb(&self.nodes[child].aabb, point),
+                        index: child,
+                    }));
+                }
+            }
+        }
+
+        result
+            .into_sorted_vec()
+            .into_iter()
+            .map(|candidate| (ProxyId(candidate.index), candidate.distance))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn unit_box(position: Vector3<f32>) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::from_min_max(
+            position - Vector3::repeat(0.5),
+            position + Vector3::repeat(0.5),
+        )
+    }
+
+    fn grid() -> Vec<Vector3<f32>> {
+        let mut points = Vec::new();
+        for x in 0..10 {
+            for y in 0..5 {
+                for z in 0..10 {
+                    points.push(Vector3::new(x as f32 * 2.0, y as f32 * 2.0, z as f32 * 2.0));
+                }
+            }
+        }
+        points
+    }
+
+    fn sorted(mut items: Vec<usize>) -> Vec<usize> {
+        items.sort_unstable();
+        items
+    }
+
+    #[test]
+    fn test_bvh_queries() {
+        let points = grid();
+        let mut bvh = DynamicBvh::with_margin(0.0);
+        let ids = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| bvh.insert(unit_box(*p), i))
+            .collect::<Vec<_>>();
+
+        assert_eq!(bvh.len(), points.len());
+        // The tree must stay balanced.
+        assert!(bvh.height() < 20);
+
+        let query = unit_box(Vector3::new(4.0, 4.0, 4.0));
+        let mut found = Vec::new();
+        bvh.query_aabb(&query, |_, i| found.push(*i));
+        let expected = (0..points.len())
+            .filter(|i| unit_box(points[*i]).is_intersects_aabb(&query))
+            .collect::<Vec<_>>();
+        assert_eq!(sorted(found), expected);
+
+        let center = Vector3::new(5.0, 3.0, 5.0);
+        let mut found = Vec::new();
+        bvh.query_sphere(center, 3.0, |_, i| found.push(*i));
+        let expected = (0..points.len())
+            .filter(|i| unit_box(points[*i]).is_intersects_sphere(center, 3.0))
+            .collect::<Vec<_>>();
+        assert_eq!(sorted(found), expected);
+
+        let ray = Ray::from_two_points(Vector3::new(-5.0, 0.0, 0.0), Vector3::new(50.0, 0.0, 0.0));
+        let mut found = Vec::new();
+        bvh.query_ray(&ray, |_, i| found.push(*i));
+        assert_eq!(found.len(), 10);
+
+        // Remove half of the entries and move the rest.
+        for (i, id) in ids.iter().enumerate() {
+            if i % 2 == 0 {
+                assert_eq!(bvh.remove(*id), Some(i));
+            } else {
+                assert!(bvh.update(*id, unit_box(points[i] + Vector3::new(100.0, 0.0, 0.0))));
+            }
+        }
+        assert_eq!(bvh.len(), points.len() / 2);
+        assert_eq!(bvh.remove(ids[0]), None);
+
+        let mut found = Vec::new();
+        bvh.query_aabb(&query, |_, i| found.push(*i));
+        assert!(found.is_empty());
+
+        let query = unit_box(Vector3::new(104.0, 4.0, 4.0));
+        let mut found = Vec::new();
+        bvh.query_aabb(&query, |_, i| found.push(*i));
+        let expected = (0..points.len())
+            .filter(|i| {
+                i % 2 == 1
+                    && unit_box(points[*i])
+                        .is_intersects_aabb(&unit_box(Vector3::new(4.0, 4.0, 4.0)))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(sorted(found), expected);
+    }
+
+    #[test]
+    fn test_ray_aabb_intersection() {
+        let aabb = unit_box(Vector3::new(0.0, 0.0, 2.0));
+        let ray = Ray::from_two_points(Vector3::zeros(), Vector3::new(0.0, 0.0, 4.0));
+        assert_eq!(ray_aabb_intersection(&ray, &aabb), Some((0.375, 0.625)));
+
+        let ray = Ray::from_two_points(Vector3::new(0.6, 0.0, 0.0), Vector3::new(0.6, 0.0, 4.0));
+        assert_eq!(ray_aabb_intersection(&ray, &aabb), None);
+
+        // Flat box.
+        let point = AxisAlignedBoundingBox::from_point(Vector3::new(0.0, 0.0, 2.0));
+        let ray = Ray::from_two_points(Vector3::zeros(), Vector3::new(0.0, 0.0, 4.0));
+        assert_eq!(ray_aabb_intersection(&ray, &point), Some((0.5, 0.5)));
+    }
+
+    #[test]
+    fn test_bvh_fat_aabb() {
+        let mut bvh = DynamicBvh::with_margin(0.5);
+        let id = bvh.insert(unit_box(Vector3::zeros()), ());
+        assert!(!bvh.update(id, unit_box(Vector3::new(0.5, 0.0, 0.0))));
+        assert!(bvh.update(id, unit_box(Vector3::new(2.0, 0.0, 0.0))));
+        assert!(bvh
+            .fat_aabb(id)
+            .unwrap()
+            .is_contains_point(Vector3::new(2.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_bvh_k_nearest() {
+        let points = grid();
+        let mut bvh = DynamicBvh::new();
+        for (i, p) in points.iter().enumerate() {
+            bvh.insert(unit_box(*p), i);
+        }
+
+        let target = Vector3::new(7.3, 1.2, 9.9);
+        let nearest = bvh.k_nearest(target, 5, |_, i| {
+            // Only even entries.
+            (i % 2 == 0).then(|| (points[*i] - target).norm())
+        });
+
+        let mut expected = (0..points.len())
+            .filter(|i| i % 2 == 0)
+            .map(|i| (i, (points[i] - target).norm()))
+            .collect::<Vec<_>>();
+        expected.sort_by(|a, b| a.1.total_cmp(&b.1));
+        expected.truncate(5);
+
+        assert_eq!(nearest.len(), 5);
+        for ((id, distance), (i, expected_distance)) in nearest.iter().zip(expected) {
+            assert_eq!(*bvh.data(*id).unwrap(), i);
+            assert_eq!(*distance, expected_distance);
+        }
+    }
+}
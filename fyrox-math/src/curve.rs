@@ -283,6 +283,72 @@ impl Curve {
         self.fetch_at(location, |a, b, t| a.interpolate_angles(b, t))
     }
 
+    /// Removes every key that can be reproduced by the interpolation between its neighbours with an
+    /// error that does not exceed the given `tolerance`. The first and the last keys are always kept, so
+    /// the length of the curve remains the same. Returns the amount of removed keys.
+    pub fn reduce_keys(&mut self, tolerance: f32) -> usize {
+        self.reduce_keys_with(tolerance, |a, b, t| a.interpolate(b, t), Curve::value_at)
+    }
+
+    /// Does the same as [`Self::reduce_keys`], but treats key values as angles and uses short-path
+    /// interpolation (see [`Self::angle_at`]) to check the error.
+    pub fn reduce_angle_keys(&mut self, tolerance: f32) -> usize {
+        self.reduce_keys_with(
+            tolerance,
+            |a, b, t| a.interpolate_angles(b, t),
+            Curve::angle_at,
+        )
+    }
+
+    fn reduce_keys_with<I, F>(&mut self, tolerance: f32, interpolator: I, fetch: F) -> usize
+    where
+        I: Fn(&CurveKey, &CurveKey, f32) -> f32,
+        F: Fn(&Curve, f32) -> f32,
+    {
+        if self.keys.len() < 3 {
+            return 0;
+        }
+
+        let original = self.clone();
+        let keys = &original.keys;
+
+        // Checks whether a span between `left` and `right` keys reproduces every original key between
+        // them, as well as the middle points of the original segments.
+        let span_fits = |left: usize, right: usize| -> bool {
+            let (a, b) = (&keys[left], &keys[right]);
+            let length = b.location - a.location;
+            if length <= f32::EPSILON {
+                return false;
+            }
+            let error = |location: f32| {
+                let t = (location - a.location) / length;
+                (interpolator(a, b, t) - fetch(&original, location)).abs()
+            };
+            keys[left..right]
+                .iter()
+                .zip(&keys[left + 1..=right])
+                .all(|(k0, k1)| {
+                    error(k1.location) <= tolerance
+                        && error((k0.location + k1.location) * 0.5) <= tolerance
+                })
+        };
+
+        let mut reduced = Vec::with_capacity(keys.len());
+        reduced.push(keys[0].clone());
+        let mut anchor = 0;
+        for (i, key) in keys.iter().enumerate().take(keys.len() - 1).skip(1) {
+            if !span_fits(anchor, i + 1) {
+                reduced.push(key.clone());
+                anchor = i;
+            }
+        }
+        reduced.push(keys[keys.len() - 1].clone());
+
+        let removed = keys.len() - reduced.len();
+        self.keys = reduced;
+        removed
+    }
+
     pub fn bounds(&self) -> Rect<f32> {
         // Handle edge cases first.
         if self.keys.is_empty() {
@@ -478,6 +544,38 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_curve_reduce_keys() {
+        let mut curve = Curve::from(
+            (0..=10)
+                .map(|i| CurveKey::new(i as f32, i as f32 * 2.0, CurveKeyKind::Linear))
+                .collect::<Vec<_>>(),
+        );
+
+        // A straight line must be reduced to just two keys.
+        assert_eq!(curve.reduce_keys(0.001), 9);
+        assert_eq!(curve.keys().len(), 2);
+        assert_eq!(curve.value_at(5.0), 10.0);
+
+        let mut curve = Curve::from(
+            (0..=10)
+                .map(|i| CurveKey::new(i as f32, (i as f32).sin(), CurveKeyKind::Linear))
+                .collect::<Vec<_>>(),
+        );
+        let reference = curve.clone();
+
+        curve.reduce_keys(0.1);
+        assert!(curve.keys().len() < reference.keys().len());
+        assert_eq!(curve.max_location(), reference.max_location());
+        for key in reference.keys() {
+            assert!((curve.value_at(key.location) - key.value).abs() <= 0.1);
+        }
+
+        // Nothing must be removed if the tolerance is zero.
+        let mut curve = reference.clone();
+        assert_eq!(curve.reduce_keys(0.0), 0);
+    }
+
     #[test]
     fn test_curve_key_kind() {
         assert_eq!(CurveKeyKind::default(), CurveKeyKind::Constant);
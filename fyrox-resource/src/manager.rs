@@ -25,13 +25,15 @@ use crate::{
     collect_used_resources,
     constructor::ResourceConstructorContainer,
     core::{
-        append_extension,
+        append_extension, err,
         futures::future::join_all,
+        info,
         io::FileLoadError,
-        log::Log,
+        log::{category, Log},
         make_relative_path, notify,
         parking_lot::{Mutex, MutexGuard},
         task::TaskPool,
+        visitor::{Visitor, VisitorFormat},
         watcher::FileSystemWatcher,
         TypeUuidProvider,
     },
@@ -484,14 +486,17 @@ impl ResourceManager {
                     if let ResourceState::Ok(ref mut data) = header.state {
                         // Save the resource back.
                         match data.save(&loaded_resource_path) {
-                            Ok(_) => Log::info(format!(
+                            Ok(_) => info!(
+                                category: category::RESOURCE,
                                 "Resource {} was saved successfully!",
                                 header.kind
-                            )),
-                            Err(err) => Log::err(format!(
+                            ),
+                            Err(err) => err!(
+                                category: category::RESOURCE,
                                 "Unable to save {} resource. Reason: {:?}",
-                                header.kind, err
-                            )),
+                                header.kind,
+                                err
+                            ),
                         };
                     }
                 }
@@ -516,6 +521,115 @@ impl ResourceManager {
         let resources = self.state().reload_resources();
         join_all(resources).await;
     }
+
+    /// Loads and saves back every resource stored in [`Visitor`] format (either binary or text) in
+    /// the given directory and its subdirectories. Loading applies all registered migrations
+    /// (see [`crate::core::visitor::migration::register_migration`]), so this method could be used
+    /// to upgrade all assets of a project to the latest version of their data types. Resources are
+    /// saved back in the same format they were stored in.
+    pub async fn upgrade_resources(
+        &self,
+        directory: impl AsRef<Path>,
+    ) -> Result<ResourceUpgradeReport, FileLoadError> {
+        self.upgrade_resources_with_progress(directory, |_| {})
+            .await
+    }
+
+    /// The same as [`Self::upgrade_resources`], but calls the given closure before upgrading each
+    /// resource, which could be used to report progress.
+    pub async fn upgrade_resources_with_progress(
+        &self,
+        directory: impl AsRef<Path>,
+        mut on_progress: impl FnMut(ResourceUpgradeProgress),
+    ) -> Result<ResourceUpgradeReport, FileLoadError> {
+        let io = self.resource_io();
+        let mut report = ResourceUpgradeReport::default();
+
+        let mut candidates = Vec::new();
+        for path in io.walk_directory(directory.as_ref()).await? {
+            let is_supported = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| {
+                    self.state()
+                        .loaders
+                        .iter()
+                        .any(|loader| loader.supports_extension(ext))
+                });
+            if !is_supported || !io.is_file(&path).await {
+                continue;
+            }
+            let format = match io.load_file(&path).await {
+                Ok(data) => Visitor::detect_format(&data),
+                Err(err) => {
+                    report.failed.push((path, format!("{err:?}")));
+                    continue;
+                }
+            };
+            if let Some(format) = format {
+                candidates.push((self.request_untyped(&path), path, format));
+            }
+        }
+
+        let total = candidates.len();
+        for (index, (resource, path, format)) in candidates.into_iter().enumerate() {
+            on_progress(ResourceUpgradeProgress {
+                path: &path,
+                index,
+                total,
+            });
+
+            let result = match resource.await {
+                Ok(resource) => resource.save(&path).map_err(|err| err.to_string()),
+                Err(err) => Err(format!("{err:?}")),
+            }
+            .map(|_| format == VisitorFormat::Text);
+
+            let result = match result {
+                // Resources are always saved in binary format, convert them back to text.
+                Ok(true) => match io.load_file(&path).await {
+                    Ok(data) if Visitor::detect_format(&data) == Some(VisitorFormat::Binary) => {
+                        Visitor::load_from_memory(&data)
+                            .and_then(|visitor| {
+                                visitor.save_with_format(VisitorFormat::Text, &path)
+                            })
+                            .map_err(|err| err.to_string())
+                    }
+                    Ok(_) => Ok(()),
+                    Err(err) => Err(format!("{err:?}")),
+                },
+                Ok(false) => Ok(()),
+                Err(err) => Err(err),
+            };
+
+            match result {
+                Ok(_) => report.upgraded.push(path),
+                Err(err) => report.failed.push((path, err)),
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Progress of [`ResourceManager::upgrade_resources_with_progress`].
+#[derive(Debug)]
+pub struct ResourceUpgradeProgress<'a> {
+    /// Path of a resource, that is about to be upgraded.
+    pub path: &'a Path,
+    /// Index of the resource.
+    pub index: usize,
+    /// Total number of resources to upgrade.
+    pub total: usize,
+}
+
+/// The result of [`ResourceManager::upgrade_resources`].
+#[derive(Default, Debug)]
+pub struct ResourceUpgradeReport {
+    /// Paths of the resources that were successfully upgraded.
+    pub upgraded: Vec<PathBuf>,
+    /// Paths of the resources that failed to upgrade, with the reasons.
+    pub failed: Vec<(PathBuf, String)>,
 }
 
 impl ResourceManagerState {
@@ -586,10 +700,11 @@ impl ResourceManagerState {
                 resource.time_to_live -= dt;
                 if resource.time_to_live <= 0.0 {
                     if let Some(path) = resource.0.lock().kind.path_owned() {
-                        Log::info(format!(
+                        info!(
+                            category: category::RESOURCE,
                             "Resource {} destroyed because it is not used anymore!",
                             path.display()
-                        ));
+                        );
 
                         self.event_broadcaster
                             .broadcast(ResourceEvent::Removed(path));
@@ -755,10 +870,11 @@ impl ResourceManagerState {
                 Ok(data) => {
                     let data = data.0;
 
-                    Log::info(format!(
+                    info!(
+                        category: category::RESOURCE,
                         "Resource {} was loaded successfully!",
                         path.display()
-                    ));
+                    );
 
                     // Separate scope to keep mutex locking time at minimum.
                     {
@@ -771,11 +887,12 @@ impl ResourceManagerState {
                     event_broadcaster.broadcast_loaded_or_reloaded(resource, reload);
                 }
                 Err(error) => {
-                    Log::info(format!(
+                    info!(
+                        category: category::RESOURCE,
                         "Resource {} failed to load. Reason: {:?}",
                         path.display(),
                         error
-                    ));
+                    );
 
                     resource.commit_error(error);
                 }
@@ -870,7 +987,7 @@ mod test {
     use fyrox_core::uuid::{uuid, Uuid};
     use fyrox_core::{
         reflect::{FieldInfo, Reflect},
-        visitor::{Visit, VisitResult, Visitor},
+        visitor::{Blackboard, Visit, VisitResult, Visitor},
         TypeUuidProvider,
     };
 
@@ -911,6 +1028,135 @@ mod test {
         }
     }
 
+    #[derive(Debug, Default, Reflect, Visit)]
+    #[visit(version = 1)]
+    struct Versioned {
+        value: u32,
+    }
+
+    impl TypeUuidProvider for Versioned {
+        fn type_uuid() -> Uuid {
+            uuid!("1c6a3a43-4e2a-4f2c-9c44-8ad4d1a36d3e")
+        }
+    }
+
+    impl ResourceData for Versioned {
+        fn type_uuid(&self) -> Uuid {
+            <Self as TypeUuidProvider>::type_uuid()
+        }
+
+        fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+            let mut visitor = Visitor::new();
+            self.visit("Versioned", &mut visitor)?;
+            visitor.save_binary(path)?;
+            Ok(())
+        }
+
+        fn can_be_saved(&self) -> bool {
+            true
+        }
+    }
+
+    struct VersionedLoader;
+
+    impl ResourceLoader for VersionedLoader {
+        fn extensions(&self) -> &[&str] {
+            &["ver"]
+        }
+
+        fn data_type_uuid(&self) -> Uuid {
+            <Versioned as TypeUuidProvider>::type_uuid()
+        }
+
+        fn load(&self, path: PathBuf, io: Arc<dyn ResourceIo>) -> BoxedLoaderFuture {
+            Box::pin(async move {
+                let data = io.load_file(&path).await.map_err(LoadError::new)?;
+                let mut visitor = Visitor::load_from_memory(&data).map_err(LoadError::new)?;
+                let mut versioned = Versioned::default();
+                versioned
+                    .visit("Versioned", &mut visitor)
+                    .map_err(LoadError::new)?;
+                Ok(LoaderPayload::new(versioned))
+            })
+        }
+    }
+
+    #[test]
+    fn resource_manager_upgrade_resources() {
+        crate::core::visitor::migration::register_migration::<Versioned>(0, |visitor| {
+            visitor.rename_field("OldValue", "Value");
+            Ok(())
+        });
+
+        let dir = Path::new("test_upgrade_resources");
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir).unwrap();
+        let path = dir.join("data.ver");
+        std::fs::write(
+            &path,
+            "RG3D_TEXT 1\n__ROOT__ {\n    Versioned {\n        OldValue: u32 = 42\n    }\n}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("ignored.ver"), "not a visitor data").unwrap();
+
+        let resource_manager = ResourceManager::new(Arc::new(Default::default()));
+        resource_manager.state().loaders.set(VersionedLoader);
+        let report =
+            fyrox_core::futures::executor::block_on(resource_manager.upgrade_resources(dir))
+                .unwrap();
+        assert_eq!(report.upgraded, vec![path.clone()]);
+        assert!(report.failed.is_empty());
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains("__Version: u32 = 1"));
+        assert!(text.contains("Value: u32 = 42"));
+        assert!(!text.contains("OldValue"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn resource_serde_bridge() {
+        use crate::untyped;
+        use fyrox_core::{json::Value, reflect::serde_bridge};
+
+        untyped::register_serde_bridge();
+
+        #[derive(Debug, Default, Reflect)]
+        struct Holder {
+            optional: Option<Resource<Stub>>,
+            resource: Resource<Stub>,
+        }
+
+        let resource_manager = ResourceManager::new(Arc::new(Default::default()));
+        resource_manager.state().loaders.set(Stub::default());
+        serde_bridge::register_type::<Resource<Stub>>();
+
+        let holder = Holder {
+            optional: None,
+            resource: resource_manager.request::<Stub>("foo.txt"),
+        };
+        let value = serde_bridge::to_value(&holder);
+        assert_eq!(value["optional"], Value::Null);
+        assert_eq!(value["resource"], Value::from("foo.txt"));
+
+        let mut blackboard = Blackboard::new();
+        blackboard.register(Arc::new(resource_manager.clone()));
+
+        let mut loaded = Holder::default();
+        serde_bridge::from_json(
+            &mut loaded,
+            r#"{ "optional": "bar.txt", "resource": "foo.txt" }"#,
+            &blackboard,
+        )
+        .unwrap();
+        assert_eq!(loaded.resource, holder.resource);
+        assert_eq!(
+            loaded.optional.unwrap().kind(),
+            ResourceKind::External("bar.txt".into())
+        );
+    }
+
     fn new_resource_manager() -> ResourceManagerState {
         ResourceManagerState::new(Arc::new(Default::default()))
     }
@@ -22,8 +22,15 @@
 
 use crate::{
     core::{
-        math::curve::Curve, parking_lot::Mutex, reflect::prelude::*, uuid, uuid::Uuid,
-        visitor::prelude::*, visitor::RegionGuard, TypeUuidProvider,
+        json::Value,
+        math::curve::Curve,
+        parking_lot::Mutex,
+        reflect::{prelude::*, serde_bridge},
+        uuid,
+        uuid::Uuid,
+        visitor::prelude::*,
+        visitor::RegionGuard,
+        TypeUuidProvider,
     },
     manager::ResourceManager,
     state::{LoadError, ResourceState},
@@ -39,7 +46,7 @@ use std::{
     marker::PhantomData,
     path::{Path, PathBuf},
     pin::Pin,
-    sync::Arc,
+    sync::{Arc, Once},
     task::{Context, Poll},
 };
 
@@ -334,6 +341,38 @@ impl Visit for UntypedResource {
     }
 }
 
+/// Registers untyped resources in the serde bridge of the reflection system (see
+/// [`fyrox_core::reflect::serde_bridge`]), so resource references are written as paths to the
+/// resources (or `null` for embedded resources) and could be restored back if a resource manager is
+/// registered in the blackboard. Typed resources are written the same way. The engine calls it on
+/// initialization, call it manually if you're using the resource manager without the engine.
+pub fn register_serde_bridge() {
+    static REGISTERED: Once = Once::new();
+    REGISTERED.call_once(|| {
+        serde_bridge::register_converter::<UntypedResource>(
+            |resource| {
+                resource
+                    .kind()
+                    .path()
+                    .map(|path| Value::String(path.to_string_lossy().replace('\\', "/")))
+                    .unwrap_or_default()
+            },
+            |value, blackboard| match value {
+                Value::Null => Ok(UntypedResource::default()),
+                Value::String(path) => blackboard
+                    .get::<ResourceManager>()
+                    .map(|resource_manager| resource_manager.request_untyped(path))
+                    .ok_or_else(|| {
+                        "Resource manager must be registered in the blackboard to load resources!"
+                            .to_string()
+                    }),
+                _ => Err(format!("expected a path to a resource, got {value}")),
+            },
+        );
+        serde_bridge::register_transparent_field_type::<UntypedResource>();
+    });
+}
+
 impl Default for UntypedResource {
     fn default() -> Self {
         Self(Arc::new(Mutex::new(ResourceHeader {
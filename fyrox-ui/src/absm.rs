@@ -62,6 +62,9 @@ pub type BlendSpace =
 /// UI-specific animation blending state machine blend space point.
 pub type BlendSpacePoint =
     crate::generic_animation::machine::node::blendspace::BlendSpacePoint<Handle<UiNode>>;
+/// UI-specific specific animation blending state machine pose difference node.
+pub type PoseDifference =
+    crate::generic_animation::machine::node::difference::PoseDifference<Handle<UiNode>>;
 /// UI-specific animation blending state machine layer mask.
 pub type LayerMask = crate::generic_animation::machine::mask::LayerMask<Handle<UiNode>>;
 /// UI-specific animation blending state machine layer mask.
@@ -104,12 +107,14 @@ pub mod prelude {
         AndNode, AnimationBlendingStateMachine, AnimationBlendingStateMachineBuilder,
         AnimationEventsSource, BasePoseNode, BlendAnimations, BlendAnimationsByIndex, BlendPose,
         BlendSpace, BlendSpacePoint, Event, IndexedBlendInput, LayerAnimationEventsCollection,
-        LayerMask, LogicNode, Machine, MachineLayer, NotNode, OrNode, PlayAnimation, PoseNode,
-        RootMotionSettings, State, StateAction, StateActionWrapper, Transition, XorNode,
+        LayerMask, LogicNode, Machine, MachineLayer, NotNode, OrNode, PlayAnimation,
+        PoseDifference, PoseNode, RootMotionSettings, State, StateAction, StateActionWrapper,
+        Transition, XorNode,
     };
     pub use crate::generic_animation::machine::{
         node::AnimationEventCollectionStrategy,
         parameter::{Parameter, ParameterContainer, ParameterDefinition, PoseWeight},
+        BlendSyncMode, LayerBlendMode,
     };
 }
 
@@ -83,7 +83,7 @@ pub mod prelude {
     };
     pub use crate::generic_animation::{
         container::{TrackDataContainer, TrackValueKind},
-        signal::AnimationSignal,
+        signal::{AnimationSignal, SignalPayload},
         value::{BoundValueCollection, TrackValue, ValueBinding, ValueType},
         AnimationEvent,
     };
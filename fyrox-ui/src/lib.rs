@@ -277,8 +277,10 @@ use crate::{
     core::{
         algebra::{Matrix3, Vector2},
         color::Color,
+        log::category,
         math::Rect,
         pool::{Handle, Pool},
+        profile_scope,
         reflect::prelude::*,
         uuid::uuid,
         visitor::prelude::*,
@@ -1267,6 +1269,8 @@ impl UserInterface {
     }
 
     pub fn update_layout(&mut self, screen_size: Vector2<f32>) {
+        profile_scope!(category: category::UI, "UserInterface::update_layout");
+
         self.screen_size = screen_size;
 
         self.handle_layout_events();
@@ -1291,6 +1295,8 @@ impl UserInterface {
     }
 
     pub fn update(&mut self, screen_size: Vector2<f32>, dt: f32, switches: &UiUpdateSwitches) {
+        profile_scope!(category: category::UI, "UserInterface::update");
+
         for entry in self.double_click_entries.values_mut() {
             entry.timer -= dt;
         }
@@ -1364,6 +1370,8 @@ impl UserInterface {
     }
 
     pub fn draw(&mut self) -> &DrawingContext {
+        profile_scope!(category: category::UI, "UserInterface::draw");
+
         self.drawing_context.clear();
 
         for node in self.nodes.iter_mut() {
@@ -30,10 +30,11 @@ use crate::{
     message::{MessageDirection, UiMessage},
     popup::{Placement, PopupBuilder, PopupMessage},
     scroll_viewer::ScrollViewerBuilder,
+    searchbar::{SearchBarBuilder, SearchBarMessage},
     stack_panel::StackPanelBuilder,
     style::{resource::StyleResourceExt, Style},
     text::{Text, TextBuilder},
-    utils::{make_dropdown_list_option, make_image_button_with_tooltip},
+    utils::{make_dropdown_list_option, make_image_button_with_tooltip, make_simple_tooltip},
     widget::{WidgetBuilder, WidgetMessage},
     window::{WindowBuilder, WindowMessage, WindowTitle},
     BuildContext, HorizontalAlignment, Orientation, RcUiNodeHandle, Thickness, UiNode,
@@ -105,6 +106,8 @@ pub struct LogPanel {
     receiver: Receiver<LogMessage>,
     severity: MessageKind,
     severity_list: Handle<UiNode>,
+    categories: Vec<String>,
+    category_filter: Handle<UiNode>,
     context_menu: ContextMenu,
     pub message_count: usize,
 }
@@ -119,6 +122,7 @@ impl LogPanel {
         let messages;
         let clear;
         let severity_list;
+        let category_filter;
         let window = WindowBuilder::new(
             WidgetBuilder::new()
                 .with_width(400.0)
@@ -164,6 +168,22 @@ impl LogPanel {
                                     .with_selected(1)
                                     .build(ctx);
                                     severity_list
+                                })
+                                .with_child({
+                                    category_filter = SearchBarBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_tab_index(Some(2))
+                                            .with_width(200.0)
+                                            .with_margin(Thickness::uniform(1.0))
+                                            .with_tooltip(make_simple_tooltip(
+                                                ctx,
+                                                "Comma-separated list of categories to show, \
+                                                subcategories are included. Shows messages of \
+                                                all categories when empty.",
+                                            )),
+                                    )
+                                    .build(ctx);
+                                    category_filter
                                 }),
                         )
                         .with_orientation(Orientation::Horizontal)
@@ -204,6 +224,8 @@ impl LogPanel {
             receiver: message_receiver,
             severity: MessageKind::Warning,
             severity_list,
+            categories: Default::default(),
+            category_filter,
             context_menu,
             message_count: 0,
         }
@@ -258,11 +280,30 @@ impl LogPanel {
                     _ => (),
                 };
             }
+        } else if let Some(SearchBarMessage::Text(text)) = message.data() {
+            if message.destination() == self.category_filter
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.categories = text
+                    .split(',')
+                    .map(|category| category.trim().to_string())
+                    .filter(|category| !category.is_empty())
+                    .collect();
+            }
         }
 
         self.context_menu.handle_ui_message(message, ui);
     }
 
+    fn is_category_visible(&self, category: &str) -> bool {
+        self.categories.is_empty()
+            || self.categories.iter().any(|filter| {
+                category
+                    .strip_prefix(filter.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+    }
+
     pub fn update(&mut self, max_log_entries: usize, ui: &mut UserInterface) -> bool {
         let existing_items = ui
             .node(self.messages)
@@ -295,14 +336,21 @@ impl LogPanel {
         let mut received_anything = false;
 
         while let Ok(msg) = self.receiver.try_recv() {
-            if msg.kind < self.severity {
+            if msg.kind < self.severity || !self.is_category_visible(&msg.category) {
                 continue;
             }
 
             self.message_count += 1;
             received_anything = true;
 
-            let text = format!("[{:.2}s] {}", msg.time.as_secs_f32(), msg.content);
+            let mut text = format!("[{:.2}s] ", msg.time.as_secs_f32());
+            if !msg.category.is_empty() {
+                text += &format!("[{}] ", msg.category);
+            }
+            text += msg.content.trim_end_matches('\n');
+            for field in msg.fields.iter() {
+                text += &format!(" {}={}", field.key, field.value);
+            }
 
             let ctx = &mut ui.build_ctx();
             let item = BorderBuilder::new(